@@ -0,0 +1,80 @@
+//! Measures the demuxing throughput of the test files.
+//!
+//! Run with `cargo bench`. The buffered benchmarks read through a `BufReader`, which
+//! discards its buffer on every seek, so they show the cost of seeking while demuxing.
+
+use std::{
+    hint::black_box,
+    io::{BufReader, Cursor},
+};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use matroska_demuxer::{Frame, MatroskaFile};
+
+const FILES: [&str; 3] = [
+    "tests/data/simple.mkv",
+    "tests/data/block_groups.mkv",
+    "tests/data/events.mkv",
+];
+
+fn demux(c: &mut Criterion) {
+    let mut group = c.benchmark_group("demux");
+    for path in FILES {
+        let data = std::fs::read(path).expect("can't read the test file");
+        group.throughput(Throughput::Bytes(data.len() as u64));
+
+        group.bench_with_input(BenchmarkId::new("open", path), &data, |b, data| {
+            b.iter(|| MatroskaFile::from_slice(black_box(data)).expect("invalid test file"));
+        });
+
+        group.bench_with_input(BenchmarkId::new("next_frame", path), &data, |b, data| {
+            b.iter(|| {
+                let mut mkv = MatroskaFile::from_slice(black_box(data)).expect("invalid test file");
+                let mut frame = Frame::default();
+                while mkv
+                    .next_frame(&mut frame)
+                    .expect("can't demux the test file")
+                {
+                    black_box(&frame);
+                }
+            });
+        });
+
+        group.bench_with_input(
+            BenchmarkId::new("next_frame_buffered", path),
+            &data,
+            |b, data| {
+                b.iter(|| {
+                    let reader = BufReader::new(Cursor::new(black_box(data)));
+                    let mut mkv = MatroskaFile::open(reader).expect("invalid test file");
+                    let mut frame = Frame::default();
+                    while mkv
+                        .next_frame(&mut frame)
+                        .expect("can't demux the test file")
+                    {
+                        black_box(&frame);
+                    }
+                });
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("next_raw_block", path),
+            &data,
+            |b, data| {
+                b.iter(|| {
+                    let mut mkv =
+                        MatroskaFile::from_slice(black_box(data)).expect("invalid test file");
+                    while let Some(block) = mkv.next_raw_block().expect("can't demux the test file")
+                    {
+                        black_box(block);
+                    }
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, demux);
+criterion_main!(benches);
@@ -0,0 +1,223 @@
+//! Lists the contents of a Matroska file and extracts its tracks and attachments.
+//!
+//! ```text
+//! cargo run --example mkv_extract -- tracks <file>
+//! cargo run --example mkv_extract -- chapters <file>
+//! cargo run --example mkv_extract -- tags <file>
+//! cargo run --example mkv_extract -- attachments <file>
+//! cargo run --example mkv_extract -- extract <file> <track number> <output>
+//! cargo run --example mkv_extract -- attachment <file> <attachment uid> <output>
+//! ```
+//!
+//! Tracks are extracted as elementary streams: VP8, VP9 and AV1 as IVF, AVC and HEVC as
+//! Annex B, AAC as ADTS and UTF-8 subtitles as SRT. All other codecs are written as the
+//! concatenated frame data.
+
+use std::{
+    error::Error,
+    fs::File,
+    io::{BufWriter, Write},
+    time::Duration,
+};
+
+use matroska_demuxer::{
+    codec_ids, AdtsFramer, AnnexBConverter, ChapterAtom, Frame, IvfWriter, MatroskaFile,
+};
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    match args.as_slice() {
+        ["tracks", path] => list_tracks(&open(path)?),
+        ["chapters", path] => list_chapters(&open(path)?),
+        ["tags", path] => list_tags(&open(path)?),
+        ["attachments", path] => list_attachments(&open(path)?),
+        ["extract", path, track, output] => extract_track(&mut open(path)?, track.parse()?, output),
+        ["attachment", path, uid, output] => {
+            extract_attachment(&mut open(path)?, uid.parse()?, output)
+        }
+        _ => {
+            eprintln!(
+                "usage: mkv_extract (tracks | chapters | tags | attachments) <file>\n\
+                 \x20      mkv_extract extract <file> <track number> <output>\n\
+                 \x20      mkv_extract attachment <file> <attachment uid> <output>"
+            );
+            std::process::exit(2);
+        }
+    }
+}
+
+fn open(path: &str) -> Result<MatroskaFile<File>> {
+    Ok(MatroskaFile::open(File::open(path)?)?)
+}
+
+fn list_tracks(mkv: &MatroskaFile<File>) -> Result<()> {
+    for track in mkv.tracks() {
+        println!(
+            "{}: {} {} (language {}, name {})",
+            track.track_number(),
+            track.track_type(),
+            track.codec_id(),
+            track.language().unwrap_or("und"),
+            track.name().unwrap_or("-"),
+        );
+    }
+    Ok(())
+}
+
+fn list_chapters(mkv: &MatroskaFile<File>) -> Result<()> {
+    for (i, edition) in mkv.chapters().unwrap_or_default().iter().enumerate() {
+        println!("Edition {}", i + 1);
+        print_chapter_atoms(edition.chapter_atoms(), 1);
+    }
+    Ok(())
+}
+
+fn print_chapter_atoms(atoms: &[ChapterAtom], depth: usize) {
+    for atom in atoms {
+        let title = atom
+            .displays()
+            .first()
+            .map_or("", |display| display.string());
+        println!(
+            "{}{} {}",
+            "  ".repeat(depth),
+            format_time(atom.start_time().as_duration(), '.'),
+            title
+        );
+        print_chapter_atoms(atom.children(), depth + 1);
+    }
+}
+
+fn list_tags(mkv: &MatroskaFile<File>) -> Result<()> {
+    for tag in mkv.tags().unwrap_or_default() {
+        let target = tag
+            .targets()
+            .and_then(|targets| targets.target_type_value())
+            .unwrap_or(50);
+        println!("Target {}", target);
+        for simple_tag in tag.simple_tags() {
+            println!(
+                "  {} = {}",
+                simple_tag.name(),
+                simple_tag.string().unwrap_or("<binary>")
+            );
+        }
+    }
+    Ok(())
+}
+
+fn list_attachments(mkv: &MatroskaFile<File>) -> Result<()> {
+    for attachment in mkv.attachments().unwrap_or_default() {
+        let range = attachment.data_range();
+        println!(
+            "{}: {} ({}, {} bytes)",
+            attachment.uid(),
+            attachment.name(),
+            attachment.media_type(),
+            range.end - range.start
+        );
+    }
+    Ok(())
+}
+
+fn extract_attachment(mkv: &mut MatroskaFile<File>, uid: u64, output: &str) -> Result<()> {
+    let mut reader = mkv.attachment_reader(uid)?;
+    let mut writer = BufWriter::new(File::create(output)?);
+    std::io::copy(&mut reader, &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// The container format of an extracted track.
+enum Output {
+    Ivf(IvfWriter<BufWriter<File>>),
+    AnnexB(AnnexBConverter, BufWriter<File>),
+    Adts(AdtsFramer, BufWriter<File>),
+    Srt(usize, BufWriter<File>),
+    Raw(BufWriter<File>),
+}
+
+fn extract_track(mkv: &mut MatroskaFile<File>, track_number: u64, output: &str) -> Result<()> {
+    let track = mkv
+        .track_by_number(track_number)
+        .ok_or("the file has no track with this number")?;
+    let writer = BufWriter::new(File::create(output)?);
+    let mut output = match track.codec_id() {
+        codec_ids::V_VP8 | codec_ids::V_VP9 | codec_ids::V_AV1 => {
+            Output::Ivf(IvfWriter::new(writer, track)?)
+        }
+        codec_ids::V_MPEG4_ISO_AVC | codec_ids::V_MPEGH_ISO_HEVC => {
+            Output::AnnexB(AnnexBConverter::from_track(track)?, writer)
+        }
+        codec_id if codec_id.starts_with(codec_ids::A_AAC) => {
+            Output::Adts(AdtsFramer::from_track(track)?, writer)
+        }
+        codec_ids::S_TEXT_UTF8 => Output::Srt(0, writer),
+        _ => Output::Raw(writer),
+    };
+
+    let mut frame = Frame::default();
+    let mut data = Vec::new();
+    while mkv.next_frame(&mut frame)? {
+        if frame.track != track_number {
+            continue;
+        }
+
+        match &mut output {
+            Output::Ivf(writer) => writer.write_frame(&frame)?,
+            Output::AnnexB(converter, writer) => {
+                data.clear();
+                converter.convert(&frame.data, frame.is_keyframe.unwrap_or(false), &mut data)?;
+                writer.write_all(&data)?;
+            }
+            Output::Adts(framer, writer) => {
+                data.clear();
+                framer.convert(&frame.data, &mut data)?;
+                writer.write_all(&data)?;
+            }
+            Output::Srt(index, writer) => {
+                *index += 1;
+                let start = frame.time().as_duration();
+                let duration = frame
+                    .duration_time()
+                    .map_or(Duration::from_secs(2), |d| d.as_duration());
+                writeln!(
+                    writer,
+                    "{}\n{} --> {}\n{}\n",
+                    index,
+                    format_time(start, ','),
+                    format_time(start + duration, ','),
+                    String::from_utf8_lossy(&frame.data)
+                )?;
+            }
+            Output::Raw(writer) => writer.write_all(&frame.data)?,
+        }
+    }
+
+    let mut writer = match output {
+        Output::Ivf(writer) => writer.finish()?,
+        Output::AnnexB(_, writer)
+        | Output::Adts(_, writer)
+        | Output::Srt(_, writer)
+        | Output::Raw(writer) => writer,
+    };
+    writer.flush()?;
+    Ok(())
+}
+
+/// Formats the time as `HH:MM:SS.mmm`, with the given separator before the milliseconds.
+fn format_time(time: Duration, separator: char) -> String {
+    let seconds = time.as_secs();
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        separator,
+        time.subsec_millis()
+    )
+}
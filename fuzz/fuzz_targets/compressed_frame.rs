@@ -0,0 +1,77 @@
+#![no_main]
+
+//! Decompresses the input as the frame of a track with a bzlib or LZO1X
+//! `ContentCompression`. The first byte selects the algorithm.
+
+use std::io::Cursor;
+
+use libfuzzer_sys::fuzz_target;
+use matroska_demuxer::{Frame, MatroskaFile, ParserSettings};
+
+fuzz_target!(|data: &[u8]| {
+    let (algo, frame_data) = match data.split_first() {
+        Some((selector, frame_data)) => (1 + u64::from(selector & 1), frame_data),
+        None => return,
+    };
+
+    let settings = ParserSettings::default().max_decompressed_size(1 << 20);
+    let file = Cursor::new(compressed_file(algo, frame_data));
+    let mut mkv = MatroskaFile::open_with_settings(file, &settings).unwrap();
+    let mut frame = Frame::default();
+    if mkv.next_frame(&mut frame).is_ok() {
+        assert!(frame.data.len() <= 1 << 20);
+    }
+});
+
+/// Creates a file with a single track, whose only frame is compressed with the given
+/// `ContentCompAlgo`.
+fn compressed_file(algo: u64, frame_data: &[u8]) -> Vec<u8> {
+    let ebml_header = [
+        unsigned(0x4286, 1),
+        unsigned(0x42F7, 1),
+        unsigned(0x42F2, 4),
+        unsigned(0x42F3, 8),
+        element(0x4282, b"matroska"),
+        unsigned(0x4287, 4),
+        unsigned(0x4285, 2),
+    ];
+    let info = [
+        unsigned(0x2AD7B1, 1_000_000),
+        element(0x4D80, b"fuzz"),
+        element(0x5741, b"fuzz"),
+    ];
+    let compression = element(0x5034, &unsigned(0x4254, algo));
+    let encoding = element(0x6240, &[unsigned(0x5032, 1), compression].concat());
+    let track_entry = [
+        unsigned(0xD7, 1),
+        unsigned(0x73C5, 1),
+        unsigned(0x83, 0x11),
+        element(0x86, b"S_TEXT/UTF8"),
+        element(0x6D80, &encoding),
+    ];
+    let block = [&[0x81, 0x00, 0x00, 0x80], frame_data].concat();
+    let cluster = [unsigned(0xE7, 0), element(0xA3, &block)];
+
+    let segment = [
+        element(0x1549A966, &info.concat()),
+        element(0x1654AE6B, &element(0xAE, &track_entry.concat())),
+        element(0x1F43B675, &cluster.concat()),
+    ];
+    [
+        element(0x1A45DFA3, &ebml_header.concat()),
+        element(0x18538067, &segment.concat()),
+    ]
+    .concat()
+}
+
+fn element(id: u32, data: &[u8]) -> Vec<u8> {
+    let id_bytes = id.to_be_bytes();
+    let id_start = id_bytes.iter().position(|b| *b != 0).unwrap_or(3);
+    // The size is always stored in eight bytes.
+    let size = u64::try_from(data.len()).unwrap().to_be_bytes();
+    [&id_bytes[id_start..], &[0x01], &size[1..], data].concat()
+}
+
+fn unsigned(id: u32, value: u64) -> Vec<u8> {
+    element(id, &value.to_be_bytes())
+}
@@ -0,0 +1,15 @@
+#![no_main]
+
+use std::io::Cursor;
+
+use libfuzzer_sys::fuzz_target;
+use matroska_demuxer::{Frame, MatroskaFile};
+
+fuzz_target!(|data: &[u8]| {
+    let mut mkv = match MatroskaFile::open(Cursor::new(data)) {
+        Ok(mkv) => mkv,
+        Err(_) => return,
+    };
+    let mut frame = Frame::default();
+    while let Ok(true) = mkv.next_frame(&mut frame) {}
+});
@@ -0,0 +1,150 @@
+//! Timestamp analysis of the demuxed frames.
+
+use crate::TrackType;
+
+/// The kind of timestamp irregularity found inside a track.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TimestampIssueKind {
+    /// The distance between two consecutive frames is larger than the `DefaultDuration`
+    /// of the track.
+    Gap,
+    /// The timestamp of a frame is smaller than the timestamp of the previous frame.
+    ///
+    /// For video tracks, frames are allowed to be reordered between keyframes, so only jumps
+    /// before the last keyframe are reported.
+    BackwardsJump,
+    /// The duration of a subtitle frame overlaps with the next subtitle frame.
+    Overlap,
+}
+
+/// A timestamp irregularity between two frames of a track.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TimestampIssue {
+    /// The kind of the irregularity.
+    pub kind: TimestampIssueKind,
+    /// The timestamp of the earlier frame.
+    pub previous_timestamp: u64,
+    /// The timestamp of the frame where the irregularity was detected.
+    pub timestamp: u64,
+}
+
+/// The result of the timestamp analysis of a single track.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TrackTimestampReport {
+    /// The track number.
+    pub track: u64,
+    /// The number of frames found for the track.
+    pub frame_count: u64,
+    /// The smallest timestamp of the track.
+    pub first_timestamp: Option<u64>,
+    /// The largest timestamp of the track.
+    pub last_timestamp: Option<u64>,
+    /// The irregularities found, sorted by their timestamp.
+    pub issues: Vec<TimestampIssue>,
+}
+
+/// The result of the timestamp analysis of all tracks.
+///
+/// All timestamps are expressed in the timestamp scale of the segment.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TimestampReport {
+    /// The reports of every track that has frames or is declared in the tracks element.
+    pub tracks: Vec<TrackTimestampReport>,
+}
+
+impl TimestampReport {
+    /// Returns the report of the given track.
+    pub fn track(&self, track: u64) -> Option<&TrackTimestampReport> {
+        self.tracks.iter().find(|t| t.track == track)
+    }
+
+    /// Returns `true` if no irregularities were found.
+    pub fn is_clean(&self) -> bool {
+        self.tracks.iter().all(|t| t.issues.is_empty())
+    }
+}
+
+/// The timing information of a frame that is needed for the analysis.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct FrameTiming {
+    pub(crate) timestamp: u64,
+    pub(crate) duration: Option<u64>,
+    pub(crate) is_keyframe: bool,
+}
+
+/// Analyzes the frames of a single track. `default_duration` is expressed in the
+/// timestamp scale of the segment.
+pub(crate) fn analyze_track(
+    track: u64,
+    track_type: Option<TrackType>,
+    default_duration: Option<u64>,
+    frames: &[FrameTiming],
+) -> TrackTimestampReport {
+    let mut issues = Vec::new();
+
+    // Backwards jumps are checked in stream order.
+    let mut previous: Option<u64> = None;
+    let mut last_keyframe: Option<u64> = None;
+    for frame in frames {
+        if let Some(previous_timestamp) = previous {
+            let is_jump = if track_type == Some(TrackType::Video) {
+                last_keyframe.is_some_and(|k| frame.timestamp < k)
+            } else {
+                frame.timestamp < previous_timestamp
+            };
+
+            if is_jump {
+                issues.push(TimestampIssue {
+                    kind: TimestampIssueKind::BackwardsJump,
+                    previous_timestamp,
+                    timestamp: frame.timestamp,
+                });
+            }
+        }
+        if frame.is_keyframe {
+            last_keyframe = Some(frame.timestamp);
+        }
+        previous = Some(frame.timestamp);
+    }
+
+    // Gaps and overlaps are checked in presentation order.
+    let mut sorted = frames.to_vec();
+    sorted.sort_by_key(|f| f.timestamp);
+
+    for pair in sorted.windows(2) {
+        let (previous, current) = (pair[0], pair[1]);
+
+        if let Some(default_duration) = default_duration {
+            // We allow one tick of rounding error.
+            if current.timestamp - previous.timestamp > default_duration.saturating_add(1) {
+                issues.push(TimestampIssue {
+                    kind: TimestampIssueKind::Gap,
+                    previous_timestamp: previous.timestamp,
+                    timestamp: current.timestamp,
+                });
+            }
+        }
+
+        if track_type == Some(TrackType::Subtitle) {
+            if let Some(duration) = previous.duration {
+                if previous.timestamp.saturating_add(duration) > current.timestamp {
+                    issues.push(TimestampIssue {
+                        kind: TimestampIssueKind::Overlap,
+                        previous_timestamp: previous.timestamp,
+                        timestamp: current.timestamp,
+                    });
+                }
+            }
+        }
+    }
+
+    issues.sort_by_key(|i| i.timestamp);
+
+    TrackTimestampReport {
+        track,
+        frame_count: u64::try_from(frames.len()).unwrap_or(u64::MAX),
+        first_timestamp: sorted.first().map(|f| f.timestamp),
+        last_timestamp: sorted.last().map(|f| f.timestamp),
+        issues,
+    }
+}
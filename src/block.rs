@@ -3,12 +3,11 @@ use std::{
     collections::VecDeque,
     convert::{TryFrom, TryInto},
     io::{Read, Seek},
-    ops::Add,
 };
 
 use crate::{
     ebml::{parse_variable_i64, parse_variable_u64},
-    Result,
+    DemuxError, Result, TimestampPolicy,
 };
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -33,11 +32,18 @@ impl From<u8> for Lacing {
 #[derive(Clone, Copy, Debug)]
 pub(crate) struct LacedFrame {
     pub(crate) track: u64,
-    pub(crate) timestamp: u64,
+    pub(crate) timestamp: i64,
+    pub(crate) cluster_timestamp: u64,
+    pub(crate) relative_timestamp: i16,
     pub(crate) size: u64,
     pub(crate) is_invisible: bool,
     pub(crate) is_keyframe: Option<bool>,
     pub(crate) is_discardable: Option<bool>,
+    pub(crate) lace_index: Option<(u32, u32)>,
+    pub(crate) duration: Option<u64>,
+    pub(crate) reference_priority: Option<u64>,
+    /// The start and end of the block group, if the frame is part of one.
+    pub(crate) block_group: Option<(u64, u64)>,
 }
 
 pub(crate) fn probe_block_timestamp<R: Read + Seek>(
@@ -47,7 +53,29 @@ pub(crate) fn probe_block_timestamp<R: Read + Seek>(
     parse_variable_u64(r)?;
     let timestamp = parse_timestamp(r, cluster_timestamp)?;
 
-    Ok(timestamp)
+    // Blocks before the start of the segment are treated as starting at 0.
+    Ok(u64::try_from(timestamp).unwrap_or(0))
+}
+
+/// Reads the track number of the block at the current location of the reader.
+pub(crate) fn probe_block_track<R: Read + Seek>(r: &mut R) -> Result<u64> {
+    parse_variable_u64(r)
+}
+
+/// Parses the header of the block at the current location of the reader and returns the
+/// track number, the timestamp and the flags of the block. Leaves the reader at the start
+/// of the (laced) payload.
+pub(crate) fn parse_block_header<R: Read + Seek>(
+    r: &mut R,
+    cluster_timestamp: u64,
+) -> Result<(u64, i64, u8)> {
+    let track = parse_variable_u64(r)?;
+    let timestamp = parse_timestamp(r, cluster_timestamp)?;
+
+    let mut header_byte = [0_u8];
+    r.read_exact(&mut header_byte)?;
+
+    Ok((track, timestamp, header_byte[0]))
 }
 
 pub(crate) fn parse_laced_frames<R: Read + Seek>(
@@ -58,39 +86,49 @@ pub(crate) fn parse_laced_frames<R: Read + Seek>(
     header_start: u64,
     is_simple_block: bool,
 ) -> Result<()> {
-    let track = parse_variable_u64(r)?;
-    let timestamp = parse_timestamp(r, cluster_timestamp)?;
-
-    let mut header_byte = [0_u8];
-    r.read_exact(&mut header_byte)?;
+    let first_frame = frames.len();
+    let (track, timestamp, flags) = parse_block_header(r, cluster_timestamp)?;
+    let relative_timestamp =
+        i16::try_from(timestamp.saturating_sub(i64::try_from(cluster_timestamp)?))?;
 
     let is_keyframe = if is_simple_block {
-        let is_keyframe: bool = ((header_byte[0] & 0x80) >> 7) == 1;
+        let is_keyframe: bool = ((flags & 0x80) >> 7) == 1;
         Some(is_keyframe)
     } else {
         None
     };
-    let is_invisible: bool = ((header_byte[0] & 0x08) >> 3) == 1;
-    let lacing: Lacing = ((header_byte[0] & 0x06) >> 1).into();
+    let is_invisible: bool = ((flags & 0x08) >> 3) == 1;
+    let lacing: Lacing = ((flags & 0x06) >> 1).into();
     let is_discardable = if is_simple_block {
-        let is_discardable: bool = (header_byte[0] & 0x01) == 1;
+        let is_discardable: bool = (flags & 0x01) == 1;
         Some(is_discardable)
     } else {
         None
     };
 
+    let template = LacedFrame {
+        track,
+        timestamp,
+        cluster_timestamp,
+        relative_timestamp,
+        size: 0,
+        is_invisible,
+        is_keyframe,
+        is_discardable,
+        lace_index: None,
+        duration: None,
+        reference_priority: None,
+        block_group: None,
+    };
+
     if lacing == Lacing::None {
         let header_end = r.stream_position()?;
         let header_size = header_end - header_start;
         let data_size = block_size - header_size;
 
         let frame = LacedFrame {
-            track,
-            timestamp,
             size: data_size,
-            is_invisible,
-            is_keyframe,
-            is_discardable,
+            ..template
         };
 
         frames.push_back(frame);
@@ -116,28 +154,14 @@ pub(crate) fn parse_laced_frames<R: Read + Seek>(
                     let size = parse_xiph_frame_size(r)?;
                     encoded_sizes += size;
 
-                    frames.push_back(LacedFrame {
-                        track,
-                        timestamp,
-                        size,
-                        is_invisible,
-                        is_keyframe,
-                        is_discardable,
-                    });
+                    frames.push_back(LacedFrame { size, ..template });
                 }
                 let header_end = r.stream_position()?;
                 let header_size = header_end - header_start;
                 let data_size = block_size - header_size;
                 let size = data_size - encoded_sizes;
 
-                frames.push_back(LacedFrame {
-                    track,
-                    timestamp,
-                    size,
-                    is_invisible,
-                    is_keyframe,
-                    is_discardable,
-                });
+                frames.push_back(LacedFrame { size, ..template });
             }
             /*
                 EBML lacing
@@ -155,14 +179,7 @@ pub(crate) fn parse_laced_frames<R: Read + Seek>(
                 let mut size = parse_variable_u64(r)?;
                 let mut encoded_size = size;
 
-                frames.push_back(LacedFrame {
-                    track,
-                    timestamp,
-                    size,
-                    is_invisible,
-                    is_keyframe,
-                    is_discardable,
-                });
+                frames.push_back(LacedFrame { size, ..template });
 
                 if frame_count > 2 {
                     for _ in 0..frame_count - 2 {
@@ -176,14 +193,7 @@ pub(crate) fn parse_laced_frames<R: Read + Seek>(
                         };
                         encoded_size += size;
 
-                        frames.push_back(LacedFrame {
-                            track,
-                            timestamp,
-                            size,
-                            is_invisible,
-                            is_keyframe,
-                            is_discardable,
-                        });
+                        frames.push_back(LacedFrame { size, ..template });
                     }
                 }
 
@@ -192,14 +202,7 @@ pub(crate) fn parse_laced_frames<R: Read + Seek>(
                 let data_size = block_size - header_size;
                 let size = data_size - encoded_size;
 
-                frames.push_back(LacedFrame {
-                    track,
-                    timestamp,
-                    size,
-                    is_invisible,
-                    is_keyframe,
-                    is_discardable,
-                });
+                frames.push_back(LacedFrame { size, ..template });
             }
             /*
                 Fixed-size lacing
@@ -217,34 +220,41 @@ pub(crate) fn parse_laced_frames<R: Read + Seek>(
                 let size = data_size / frame_count;
 
                 for _ in 0..frame_count {
-                    frames.push_back(LacedFrame {
-                        track,
-                        timestamp,
-                        size,
-                        is_invisible,
-                        is_keyframe,
-                        is_discardable,
-                    });
+                    frames.push_back(LacedFrame { size, ..template });
                 }
             }
             Lacing::None => { /* Unreachable */ }
         }
+
+        let lace_count = u32::try_from(frame_count)?;
+        for (lace_index, frame) in frames.iter_mut().skip(first_frame).enumerate() {
+            frame.lace_index = Some((u32::try_from(lace_index)?, lace_count));
+        }
     }
 
     Ok(())
 }
 
-fn parse_timestamp<R: Read + Seek>(r: &mut R, cluster_timestamp: u64) -> Result<u64> {
+/// Block timestamps are relative to the cluster timestamp and can point before the start
+/// of the segment.
+///
+/// Cluster timestamps that don't fit into an `i64` are rejected. The sum can only overflow
+/// for cluster timestamps close to `i64::MAX` and is saturated in this case.
+fn parse_timestamp<R: Read + Seek>(r: &mut R, cluster_timestamp: u64) -> Result<i64> {
     let timestamp = parse_i16(r)?;
+    let cluster_timestamp: i64 = cluster_timestamp.try_into()?;
 
-    let abs: u64 = timestamp.abs().try_into()?;
-    let timestamp = if timestamp.is_positive() {
-        cluster_timestamp.add(abs)
-    } else {
-        cluster_timestamp.saturating_sub(abs)
-    };
+    Ok(cluster_timestamp.saturating_add(i64::from(timestamp)))
+}
 
-    Ok(timestamp)
+/// Converts the signed timestamp of a frame into its unsigned timestamp according to the
+/// given policy. Returns the timestamp and if it was clamped.
+pub(crate) fn unsigned_timestamp(timestamp: i64, policy: TimestampPolicy) -> Result<(u64, bool)> {
+    match (u64::try_from(timestamp), policy) {
+        (Ok(timestamp), _) => Ok((timestamp, false)),
+        (Err(_), TimestampPolicy::Clamp) => Ok((0, true)),
+        (Err(_), TimestampPolicy::Error) => Err(DemuxError::NegativeTimestamp(timestamp)),
+    }
 }
 
 fn parse_xiph_frame_size<R: Read + Seek>(r: &mut R) -> Result<u64> {
@@ -274,3 +284,56 @@ fn parse_i16<R: Read + Seek>(r: &mut R) -> Result<i16> {
     r.read_exact(&mut bytes)?;
     Ok(i16::from_be_bytes(bytes))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_fixed_size_laced_frames() -> Result<()> {
+        let data: Vec<u8> = vec![
+            0x81, 0x00, 0x02, 0x84, 0x02, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06,
+        ];
+        let block_size = u64::try_from(data.len())?;
+        let mut cursor = Cursor::new(data);
+        let mut frames = VecDeque::new();
+        parse_laced_frames(&mut cursor, &mut frames, block_size, 10, 0, true)?;
+
+        assert_eq!(frames.len(), 3);
+        for (i, frame) in frames.iter().enumerate() {
+            assert_eq!(frame.track, 1);
+            assert_eq!(frame.timestamp, 12);
+            assert_eq!(frame.size, 2);
+            assert_eq!(frame.is_keyframe, Some(true));
+            assert_eq!(frame.lace_index, Some((u32::try_from(i)?, 3)));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_negative_block_timestamp() -> Result<()> {
+        // Track 1 with a relative timestamp of -10.
+        let data: Vec<u8> = vec![0x81, 0xFF, 0xF6, 0x80, 0x01];
+        let block_size = u64::try_from(data.len())?;
+        let mut cursor = Cursor::new(data);
+        let mut frames = VecDeque::new();
+        parse_laced_frames(&mut cursor, &mut frames, block_size, 4, 0, true)?;
+
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].timestamp, -6);
+        assert_eq!(frames[0].cluster_timestamp, 4);
+        assert_eq!(frames[0].relative_timestamp, -10);
+
+        let mut cursor = Cursor::new(vec![0x81, 0xFF, 0xF6, 0x80, 0x01]);
+        assert_eq!(probe_block_timestamp(&mut cursor, 4)?, 0);
+
+        assert_eq!(unsigned_timestamp(-6, TimestampPolicy::Clamp)?, (0, true));
+        assert_eq!(unsigned_timestamp(6, TimestampPolicy::Error)?, (6, false));
+        assert!(unsigned_timestamp(-6, TimestampPolicy::Error).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+//! A reader for the binary structures inside codec configurations and exported indexes.
+
+use crate::{DemuxError, Result};
+
+/// Reads the fields of a binary structure from a byte slice.
+///
+/// Reading past the end of the data fails with `DemuxError::InvalidCodecData`, unless
+/// the reader was created with another error by `with_error()`.
+pub(crate) struct ByteReader<'a> {
+    data: &'a [u8],
+    error: fn() -> DemuxError,
+}
+
+impl<'a> ByteReader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self::with_error(data, || DemuxError::InvalidCodecData)
+    }
+
+    pub(crate) fn with_error(data: &'a [u8], error: fn() -> DemuxError) -> Self {
+        Self { data, error }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Takes the next `count` bytes.
+    pub(crate) fn bytes(&mut self, count: usize) -> Result<&'a [u8]> {
+        if count > self.data.len() {
+            return Err((self.error)());
+        }
+        let (bytes, rest) = self.data.split_at(count);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    /// Takes the next `N` bytes as array, for example to convert them with `from_le_bytes()`.
+    pub(crate) fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let mut array = [0_u8; N];
+        array.copy_from_slice(self.bytes(N)?);
+        Ok(array)
+    }
+
+    pub(crate) fn skip(&mut self, count: usize) -> Result<()> {
+        self.bytes(count).map(|_| ())
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    /// Reads a big endian u16.
+    pub(crate) fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_be_bytes(self.array()?))
+    }
+
+    /// Reads data that is prefixed with its big endian 16 bit length.
+    pub(crate) fn length_prefixed(&mut self) -> Result<&'a [u8]> {
+        let length = self.u16()?;
+        self.bytes(usize::from(length))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_byte_reader() -> Result<()> {
+        let mut reader = ByteReader::new(&[0x01, 0x00, 0x02, 0xAB, 0xCD, 0x04, 0x03]);
+        assert_eq!(reader.u8()?, 0x01);
+        assert_eq!(reader.length_prefixed()?, [0xAB, 0xCD]);
+        assert_eq!(u16::from_le_bytes(reader.array()?), 0x0304);
+        assert!(reader.is_empty());
+        assert!(matches!(reader.u8(), Err(DemuxError::InvalidCodecData)));
+
+        let mut reader = ByteReader::with_error(&[0x01], || DemuxError::InvalidIndexData);
+        assert!(matches!(
+            reader.array::<2>(),
+            Err(DemuxError::InvalidIndexData)
+        ));
+        Ok(())
+    }
+}
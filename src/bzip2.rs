@@ -0,0 +1,423 @@
+//! Decompression of frames that are compressed with bzip2.
+
+use crate::{compression::check_limit, DemuxError, Result};
+
+/// The magic number that starts a block.
+const BLOCK_MAGIC: u64 = 0x3141_5926_5359;
+/// The magic number that ends a stream.
+const END_MAGIC: u64 = 0x1772_4538_5090;
+/// The longest Huffman code.
+const MAX_CODE_LENGTH: usize = 20;
+/// The number of symbols that are decoded with the same Huffman table.
+const GROUP_SIZE: usize = 50;
+/// The largest number of selectors that are used by a block.
+const MAX_SELECTORS: usize = 18002;
+
+/// Decompresses a bzip2 compressed frame, which may not be larger than `limit` bytes.
+/// Concatenated streams are decompressed one after another.
+pub(crate) fn decompress(input: &[u8], limit: usize) -> Result<Vec<u8>> {
+    let mut reader = BitReader {
+        input,
+        position: 0,
+        bit: 0,
+    };
+    let mut out = Vec::with_capacity(input.len().saturating_mul(4).min(limit));
+
+    loop {
+        if reader.bits(24)? != 0x42_5A_68 {
+            return Err(DemuxError::InvalidCompressedData);
+        }
+        let level = reader.bits(8)?;
+        if !(u64::from(b'1')..=u64::from(b'9')).contains(&level) {
+            return Err(DemuxError::InvalidCompressedData);
+        }
+        let max_block_size = usize::try_from(level - u64::from(b'0'))? * 100_000;
+
+        loop {
+            match reader.bits(48)? {
+                BLOCK_MAGIC => {
+                    let crc = u32::try_from(reader.bits(32)?)?;
+                    let start = out.len();
+                    decompress_block(&mut reader, max_block_size, limit, &mut out)?;
+                    if block_crc(&out[start..]) != crc {
+                        return Err(DemuxError::InvalidCompressedData);
+                    }
+                }
+                END_MAGIC => {
+                    // The combined CRC of the stream is covered by the block CRCs.
+                    reader.bits(32)?;
+                    break;
+                }
+                _ => return Err(DemuxError::InvalidCompressedData),
+            }
+        }
+
+        reader.align();
+        if reader.position >= input.len() {
+            return Ok(out);
+        }
+    }
+}
+
+fn decompress_block(
+    reader: &mut BitReader,
+    max_block_size: usize,
+    limit: usize,
+    out: &mut Vec<u8>,
+) -> Result<()> {
+    if reader.bit()? {
+        // Randomized blocks were deprecated before bzip2 0.9.5.
+        return Err(DemuxError::InvalidCompressedData);
+    }
+    let origin = usize::try_from(reader.bits(24)?)?;
+
+    // The bytes that are used inside the block.
+    let used_groups = reader.bits(16)?;
+    let mut symbols = Vec::with_capacity(256);
+    for group in 0..16_u8 {
+        if used_groups & (0x8000 >> group) == 0 {
+            continue;
+        }
+        let used = reader.bits(16)?;
+        for i in 0..16_u8 {
+            if used & (0x8000 >> i) != 0 {
+                symbols.push(group * 16 + i);
+            }
+        }
+    }
+    if symbols.is_empty() {
+        return Err(DemuxError::InvalidCompressedData);
+    }
+    let alphabet_size = symbols.len() + 2;
+
+    let table_count = usize::try_from(reader.bits(3)?)?;
+    if !(2..=6).contains(&table_count) {
+        return Err(DemuxError::InvalidCompressedData);
+    }
+    let selector_count = usize::try_from(reader.bits(15)?)?;
+    if selector_count == 0 {
+        return Err(DemuxError::InvalidCompressedData);
+    }
+
+    // The selectors are move-to-front encoded.
+    let mut table_order: Vec<usize> = (0..table_count).collect();
+    let mut selectors = Vec::with_capacity(selector_count.min(MAX_SELECTORS));
+    for _ in 0..selector_count {
+        let mut index = 0;
+        while reader.bit()? {
+            index += 1;
+            if index >= table_count {
+                return Err(DemuxError::InvalidCompressedData);
+            }
+        }
+        let table = table_order.remove(index);
+        table_order.insert(0, table);
+        if selectors.len() < MAX_SELECTORS {
+            selectors.push(table);
+        }
+    }
+
+    let mut tables = Vec::with_capacity(table_count);
+    for _ in 0..table_count {
+        let mut lengths = Vec::with_capacity(alphabet_size);
+        let mut length = reader.bits(5)?;
+        for _ in 0..alphabet_size {
+            loop {
+                if !(1..=20).contains(&length) {
+                    return Err(DemuxError::InvalidCompressedData);
+                }
+                if !reader.bit()? {
+                    break;
+                }
+                match reader.bit()? {
+                    false => length += 1,
+                    true => length -= 1,
+                }
+            }
+            lengths.push(usize::try_from(length)?);
+        }
+        tables.push(HuffmanTable::new(&lengths)?);
+    }
+
+    // Decode the move-to-front and run-length encoded symbols.
+    let end_of_block = alphabet_size - 1;
+    let mut order = symbols.clone();
+    let mut block: Vec<u8> = Vec::with_capacity(max_block_size);
+    let mut run: usize = 0;
+    let mut run_weight: usize = 1;
+    let mut decoded = 0;
+    loop {
+        let table = selectors
+            .get(decoded / GROUP_SIZE)
+            .and_then(|&selector| tables.get(selector))
+            .ok_or(DemuxError::InvalidCompressedData)?;
+        let symbol = table.decode(reader)?;
+        decoded += 1;
+
+        if symbol <= 1 {
+            // RUNA and RUNB encode the run length in bijective base 2.
+            run = run
+                .checked_add(run_weight << symbol)
+                .filter(|&run| run <= max_block_size)
+                .ok_or(DemuxError::InvalidCompressedData)?;
+            run_weight = run_weight
+                .checked_mul(2)
+                .ok_or(DemuxError::InvalidCompressedData)?;
+            continue;
+        }
+
+        if run > 0 {
+            let byte = order
+                .first()
+                .copied()
+                .ok_or(DemuxError::InvalidCompressedData)?;
+            block.resize(block.len() + run, byte);
+            run = 0;
+            run_weight = 1;
+        }
+        if symbol == end_of_block {
+            break;
+        }
+
+        let index = symbol - 1;
+        if index >= order.len() {
+            return Err(DemuxError::InvalidCompressedData);
+        }
+        let byte = order.remove(index);
+        order.insert(0, byte);
+        block.push(byte);
+        if block.len() > max_block_size {
+            return Err(DemuxError::InvalidCompressedData);
+        }
+    }
+    if block.len() > max_block_size || origin >= block.len() {
+        return Err(DemuxError::InvalidCompressedData);
+    }
+
+    // Undo the Burrows-Wheeler transform.
+    let mut starts = [0_usize; 256];
+    for &byte in &block {
+        starts[usize::from(byte)] += 1;
+    }
+    let mut sum = 0;
+    for start in starts.iter_mut() {
+        let count = *start;
+        *start = sum;
+        sum += count;
+    }
+    let mut next = vec![0_usize; block.len()];
+    for (i, &byte) in block.iter().enumerate() {
+        let start = &mut starts[usize::from(byte)];
+        next[*start] = i;
+        *start += 1;
+    }
+
+    // Undo the initial run-length encoding, which stores runs of 4 to 255 equal bytes as
+    // 4 bytes and a repeat count.
+    let mut position = next[origin];
+    let mut last = None;
+    let mut equal = 0;
+    for _ in 0..block.len() {
+        let byte = block[position];
+        position = next[position];
+
+        if equal == 4 {
+            let last = last.ok_or(DemuxError::InvalidCompressedData)?;
+            check_limit(out.len() + usize::from(byte), limit)?;
+            out.resize(out.len() + usize::from(byte), last);
+            equal = 0;
+            continue;
+        }
+        if Some(byte) == last {
+            equal += 1;
+        } else {
+            last = Some(byte);
+            equal = 1;
+        }
+        check_limit(out.len() + 1, limit)?;
+        out.push(byte);
+    }
+
+    Ok(())
+}
+
+/// A canonical Huffman table.
+struct HuffmanTable {
+    /// The largest code of each length, or -1 if there is no code of the length.
+    limits: [i64; MAX_CODE_LENGTH + 2],
+    /// The offset of the first code of each length into the symbols.
+    bases: [i64; MAX_CODE_LENGTH + 2],
+    /// The symbols sorted by their code length.
+    symbols: Vec<usize>,
+    min_length: usize,
+}
+
+impl HuffmanTable {
+    fn new(lengths: &[usize]) -> Result<Self> {
+        let min_length = lengths.iter().copied().min().unwrap_or(1);
+        let max_length = lengths.iter().copied().max().unwrap_or(1);
+
+        let mut symbols = Vec::with_capacity(lengths.len());
+        for length in min_length..=max_length {
+            symbols.extend(
+                lengths
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &l)| l == length)
+                    .map(|(symbol, _)| symbol),
+            );
+        }
+
+        let mut limits = [-1_i64; MAX_CODE_LENGTH + 2];
+        let mut bases = [0_i64; MAX_CODE_LENGTH + 2];
+        let mut code = 0_i64;
+        let mut offset = 0_i64;
+        for length in min_length..=max_length {
+            let count = i64::try_from(lengths.iter().filter(|&&l| l == length).count())?;
+            bases[length] = code - offset;
+            code += count;
+            offset += count;
+            limits[length] = code - 1;
+            code <<= 1;
+        }
+
+        Ok(Self {
+            limits,
+            bases,
+            symbols,
+            min_length,
+        })
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<usize> {
+        let mut length = self.min_length;
+        let mut code = i64::try_from(reader.bits(length)?)?;
+        loop {
+            if code <= self.limits[length] {
+                let index = usize::try_from(code - self.bases[length])
+                    .map_err(|_| DemuxError::InvalidCompressedData)?;
+                return self
+                    .symbols
+                    .get(index)
+                    .copied()
+                    .ok_or(DemuxError::InvalidCompressedData);
+            }
+            length += 1;
+            if length > MAX_CODE_LENGTH {
+                return Err(DemuxError::InvalidCompressedData);
+            }
+            code = (code << 1) | i64::from(reader.bit()?);
+        }
+    }
+}
+
+/// Reads the bits of the input starting with the most significant bit.
+struct BitReader<'a> {
+    input: &'a [u8],
+    position: usize,
+    bit: u8,
+}
+
+impl<'a> BitReader<'a> {
+    fn bit(&mut self) -> Result<bool> {
+        let byte = self
+            .input
+            .get(self.position)
+            .ok_or(DemuxError::InvalidCompressedData)?;
+        let bit = byte & (0x80 >> self.bit) != 0;
+        self.bit += 1;
+        if self.bit == 8 {
+            self.bit = 0;
+            self.position += 1;
+        }
+        Ok(bit)
+    }
+
+    fn bits(&mut self, count: usize) -> Result<u64> {
+        let mut value = 0;
+        for _ in 0..count {
+            value = (value << 1) | u64::from(self.bit()?);
+        }
+        Ok(value)
+    }
+
+    /// Skips to the start of the next byte.
+    fn align(&mut self) {
+        if self.bit != 0 {
+            self.bit = 0;
+            self.position += 1;
+        }
+    }
+}
+
+/// The CRC-32 of a block, which uses the polynomial 0x04C11DB7 without reflection.
+fn block_crc(data: &[u8]) -> u32 {
+    let mut crc = u32::MAX;
+    for &byte in data {
+        crc ^= u32::from(byte) << 24;
+        for _ in 0..8 {
+            crc = match crc & 0x8000_0000 {
+                0 => crc << 1,
+                _ => (crc << 1) ^ 0x04C1_1DB7,
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compression::{check_damaged, read_corpus};
+
+    #[test]
+    fn test_decompress() -> Result<()> {
+        // bz2.compress(b"hello hello hello hello, aaaaaaaaaaaaaaaaaaaaa")
+        let compressed = [
+            0x42, 0x5A, 0x68, 0x39, 0x31, 0x41, 0x59, 0x26, 0x53, 0x59, 0x40, 0x97, 0x19, 0x9E,
+            0x00, 0x00, 0x07, 0x31, 0x00, 0x20, 0x00, 0x40, 0x04, 0x22, 0x44, 0xA0, 0x00, 0x20,
+            0xAA, 0x08, 0xC1, 0x0C, 0x08, 0xBB, 0xD2, 0xA3, 0xD8, 0x7E, 0xA3, 0x0D, 0x1C, 0x2E,
+            0xE4, 0x8A, 0x70, 0xA1, 0x20, 0x81, 0x2E, 0x33, 0x3C,
+        ];
+        assert_eq!(
+            decompress(&compressed, usize::MAX)?,
+            b"hello hello hello hello, aaaaaaaaaaaaaaaaaaaaa"
+        );
+        assert!(matches!(
+            decompress(&compressed, 20),
+            Err(DemuxError::DecompressedSizeExceeded(20))
+        ));
+
+        assert!(decompress(&compressed[..compressed.len() - 4], usize::MAX).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_decompress_corpus() -> Result<()> {
+        // The corpus was created with the reference implementation.
+        let corpus = read_corpus("bzip2", "bz2")?;
+        assert!(!corpus.is_empty());
+        for (path, compressed, raw) in corpus {
+            assert_eq!(
+                decompress(&compressed, usize::MAX)?,
+                raw,
+                "{}",
+                path.display()
+            );
+            if let Some(limit) = raw.len().checked_sub(1) {
+                assert!(
+                    matches!(
+                        decompress(&compressed, limit),
+                        Err(DemuxError::DecompressedSizeExceeded(_))
+                    ),
+                    "{}",
+                    path.display()
+                );
+            }
+            if compressed.len() <= 1024 {
+                check_damaged(decompress, &compressed, 1 << 20);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,730 @@
+//! Parsers for the `CodecPrivate` of codecs, which players need to configure their
+//! decoders before the first frame.
+
+use crate::{byte_reader::ByteReader, codec_ids, DemuxError, Result, TrackEntry};
+
+/// The OBU type of an AV1 sequence header.
+const OBU_SEQUENCE_HEADER: u8 = 1;
+/// The sampling frequencies of AAC, indexed by their sampling frequency index.
+pub(crate) const AAC_SAMPLING_FREQUENCIES: [f64; 13] = [
+    96000.0, 88200.0, 64000.0, 48000.0, 44100.0, 32000.0, 24000.0, 22050.0, 16000.0, 12000.0,
+    11025.0, 8000.0, 7350.0,
+];
+
+/// The `AV1CodecConfigurationRecord` (`av1C`) stored in the `CodecPrivate` of `V_AV1` tracks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Av1Config {
+    seq_profile: u8,
+    seq_level_idx_0: u8,
+    seq_tier_0: u8,
+    high_bitdepth: bool,
+    twelve_bit: bool,
+    monochrome: bool,
+    chroma_subsampling_x: bool,
+    chroma_subsampling_y: bool,
+    chroma_sample_position: u8,
+    initial_presentation_delay: Option<u8>,
+    config_obus: Vec<u8>,
+}
+
+impl Av1Config {
+    /// Parses the `CodecPrivate` of the given `V_AV1` track.
+    pub fn from_track(track: &TrackEntry) -> Result<Self> {
+        if track.codec_id() != codec_ids::V_AV1 {
+            return Err(DemuxError::UnsupportedCodec(track.codec_id().to_owned()));
+        }
+        let codec_private = track
+            .decoded_codec_private()?
+            .ok_or(DemuxError::InvalidCodecData)?;
+        Self::parse(&codec_private)
+    }
+
+    /// Parses an `AV1CodecConfigurationRecord`.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let (header, config_obus) = match data {
+            [marker, profile, flags, delay, config_obus @ ..] => {
+                ([*marker, *profile, *flags, *delay], config_obus)
+            }
+            _ => return Err(DemuxError::InvalidCodecData),
+        };
+        // The marker bit and version 1.
+        if header[0] != 0x81 {
+            return Err(DemuxError::InvalidCodecData);
+        }
+
+        Ok(Self {
+            seq_profile: header[1] >> 5,
+            seq_level_idx_0: header[1] & 0x1F,
+            seq_tier_0: header[2] >> 7,
+            high_bitdepth: header[2] & 0x40 != 0,
+            twelve_bit: header[2] & 0x20 != 0,
+            monochrome: header[2] & 0x10 != 0,
+            chroma_subsampling_x: header[2] & 0x08 != 0,
+            chroma_subsampling_y: header[2] & 0x04 != 0,
+            chroma_sample_position: header[2] & 0x03,
+            initial_presentation_delay: match header[3] & 0x10 {
+                0 => None,
+                _ => Some((header[3] & 0x0F) + 1),
+            },
+            config_obus: config_obus.to_vec(),
+        })
+    }
+
+    /// The profile of the sequence.
+    pub fn seq_profile(&self) -> u8 {
+        self.seq_profile
+    }
+
+    /// The level of the first operating point.
+    pub fn seq_level_idx_0(&self) -> u8 {
+        self.seq_level_idx_0
+    }
+
+    /// The tier of the first operating point.
+    pub fn seq_tier_0(&self) -> u8 {
+        self.seq_tier_0
+    }
+
+    /// The bit depth of the samples: 8, 10 or 12.
+    pub fn bit_depth(&self) -> u8 {
+        match (self.high_bitdepth, self.twelve_bit) {
+            (false, _) => 8,
+            (true, false) => 10,
+            (true, true) => 12,
+        }
+    }
+
+    /// Returns `true` if the video has no chroma planes.
+    pub fn monochrome(&self) -> bool {
+        self.monochrome
+    }
+
+    /// The horizontal chroma subsampling.
+    pub fn chroma_subsampling_x(&self) -> bool {
+        self.chroma_subsampling_x
+    }
+
+    /// The vertical chroma subsampling.
+    pub fn chroma_subsampling_y(&self) -> bool {
+        self.chroma_subsampling_y
+    }
+
+    /// The position of the chroma samples, as defined by `chroma_sample_position` of the
+    /// AV1 specification.
+    pub fn chroma_sample_position(&self) -> u8 {
+        self.chroma_sample_position
+    }
+
+    /// The number of frames a decoder should buffer before the presentation starts.
+    pub fn initial_presentation_delay(&self) -> Option<u8> {
+        self.initial_presentation_delay
+    }
+
+    /// The OBUs of the configuration, usually the sequence header and optional metadata.
+    pub fn config_obus(&self) -> &[u8] {
+        &self.config_obus
+    }
+
+    /// The sequence header OBU of the configuration, including its OBU header.
+    pub fn sequence_header_obu(&self) -> Result<Option<&[u8]>> {
+        let mut data = self.config_obus.as_slice();
+        while let Some(&header) = data.first() {
+            let obu_type = (header >> 3) & 0x0F;
+            let has_extension = header & 0x04 != 0;
+            let has_size = header & 0x02 != 0;
+
+            let mut header_size = if has_extension { 2 } else { 1 };
+            let payload_size = match has_size {
+                true => {
+                    let (size, length) = read_leb128(data.get(header_size..).unwrap_or_default())?;
+                    header_size += length;
+                    usize::try_from(size)?
+                }
+                false => data.len().saturating_sub(header_size),
+            };
+            let end = header_size
+                .checked_add(payload_size)
+                .filter(|&end| end <= data.len())
+                .ok_or(DemuxError::InvalidCodecData)?;
+
+            let (obu, rest) = data.split_at(end);
+            if obu_type == OBU_SEQUENCE_HEADER {
+                return Ok(Some(obu));
+            }
+            data = rest;
+        }
+        Ok(None)
+    }
+}
+
+/// Reads an unsigned LEB128 number. Returns the number and its length in bytes.
+fn read_leb128(data: &[u8]) -> Result<(u64, usize)> {
+    let mut value = 0_u64;
+    for (i, &byte) in data.iter().take(8).enumerate() {
+        value |= u64::from(byte & 0x7F) << (i * 7);
+        if byte & 0x80 == 0 {
+            return Ok((value, i + 1));
+        }
+    }
+    Err(DemuxError::InvalidCodecData)
+}
+
+/// The Codec Feature Metadata that is stored in the `CodecPrivate` of `V_VP9` tracks.
+///
+/// All features are optional, so tracks without `CodecPrivate` return an empty
+/// configuration.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Vp9Config {
+    profile: Option<u8>,
+    level: Option<u8>,
+    bit_depth: Option<u8>,
+    chroma_subsampling: Option<u8>,
+}
+
+impl Vp9Config {
+    /// Parses the `CodecPrivate` of the given `V_VP9` track.
+    pub fn from_track(track: &TrackEntry) -> Result<Self> {
+        if track.codec_id() != codec_ids::V_VP9 {
+            return Err(DemuxError::UnsupportedCodec(track.codec_id().to_owned()));
+        }
+        match track.decoded_codec_private()? {
+            Some(codec_private) => Self::parse(&codec_private),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Parses the Codec Feature Metadata. Features with an unknown ID are skipped.
+    pub fn parse(mut data: &[u8]) -> Result<Self> {
+        let mut config = Self::default();
+        while let [id, length, rest @ ..] = data {
+            let length = usize::from(*length);
+            if length > rest.len() {
+                return Err(DemuxError::InvalidCodecData);
+            }
+            let (value, rest) = rest.split_at(length);
+            let value = match value {
+                [value] => Some(*value),
+                _ => None,
+            };
+            match id {
+                1 => config.profile = value,
+                2 => config.level = value,
+                3 => config.bit_depth = value,
+                4 => config.chroma_subsampling = value,
+                _ => {}
+            }
+            data = rest;
+        }
+        if !data.is_empty() {
+            return Err(DemuxError::InvalidCodecData);
+        }
+        Ok(config)
+    }
+
+    /// The VP9 profile.
+    pub fn profile(&self) -> Option<u8> {
+        self.profile
+    }
+
+    /// The VP9 level, for example 31 for level 3.1.
+    pub fn level(&self) -> Option<u8> {
+        self.level
+    }
+
+    /// The bit depth of the luma and chroma samples.
+    pub fn bit_depth(&self) -> Option<u8> {
+        self.bit_depth
+    }
+
+    /// The chroma subsampling: 0 is 4:2:0 with vertical chroma samples, 1 is 4:2:0 with
+    /// collocated chroma samples, 2 is 4:2:2 and 3 is 4:4:4.
+    pub fn chroma_subsampling(&self) -> Option<u8> {
+        self.chroma_subsampling
+    }
+}
+
+/// The identification header (`OpusHead`) stored in the `CodecPrivate` of `A_OPUS` tracks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpusConfig {
+    version: u8,
+    channel_count: u8,
+    pre_skip: u16,
+    input_sample_rate: u32,
+    output_gain: i16,
+    channel_mapping_family: u8,
+    stream_count: Option<u8>,
+    coupled_count: Option<u8>,
+    channel_mapping: Vec<u8>,
+}
+
+impl OpusConfig {
+    /// Parses the `CodecPrivate` of the given `A_OPUS` track.
+    pub fn from_track(track: &TrackEntry) -> Result<Self> {
+        if track.codec_id() != codec_ids::A_OPUS {
+            return Err(DemuxError::UnsupportedCodec(track.codec_id().to_owned()));
+        }
+        let codec_private = track
+            .decoded_codec_private()?
+            .ok_or(DemuxError::InvalidCodecData)?;
+        Self::parse(&codec_private)
+    }
+
+    /// Parses an `OpusHead`.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let mut reader = ByteReader::new(data);
+        if reader.bytes(8)? != b"OpusHead" {
+            return Err(DemuxError::InvalidCodecData);
+        }
+        let version = reader.u8()?;
+        // Only the lower four bits are the major version.
+        if version & 0xF0 != 0 {
+            return Err(DemuxError::InvalidCodecData);
+        }
+        let channel_count = reader.u8()?;
+        let pre_skip = u16::from_le_bytes(reader.array()?);
+        let input_sample_rate = u32::from_le_bytes(reader.array()?);
+        let output_gain = i16::from_le_bytes(reader.array()?);
+        let channel_mapping_family = reader.u8()?;
+
+        let (stream_count, coupled_count, channel_mapping) = match channel_mapping_family {
+            0 => (None, None, Vec::new()),
+            _ => {
+                let [stream_count, coupled_count] = reader.array()?;
+                let channel_mapping = reader.bytes(usize::from(channel_count))?;
+                (
+                    Some(stream_count),
+                    Some(coupled_count),
+                    channel_mapping.to_vec(),
+                )
+            }
+        };
+
+        Ok(Self {
+            version,
+            channel_count,
+            pre_skip,
+            input_sample_rate,
+            output_gain,
+            channel_mapping_family,
+            stream_count,
+            coupled_count,
+            channel_mapping,
+        })
+    }
+
+    /// The version of the header.
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// The number of output channels.
+    pub fn channel_count(&self) -> u8 {
+        self.channel_count
+    }
+
+    /// The number of samples at 48 kHz to discard from the decoder output when starting
+    /// playback.
+    pub fn pre_skip(&self) -> u16 {
+        self.pre_skip
+    }
+
+    /// The sample rate of the original input. Opus is always decoded at 48 kHz.
+    pub fn input_sample_rate(&self) -> u32 {
+        self.input_sample_rate
+    }
+
+    /// The gain to apply to the decoder output in Q7.8 dB.
+    pub fn output_gain(&self) -> i16 {
+        self.output_gain
+    }
+
+    /// The channel mapping family.
+    pub fn channel_mapping_family(&self) -> u8 {
+        self.channel_mapping_family
+    }
+
+    /// The number of Opus streams in each frame. Only present if the channel mapping
+    /// family is not 0.
+    pub fn stream_count(&self) -> Option<u8> {
+        self.stream_count
+    }
+
+    /// The number of streams that decode to two channels. Only present if the channel
+    /// mapping family is not 0.
+    pub fn coupled_count(&self) -> Option<u8> {
+        self.coupled_count
+    }
+
+    /// The decoded channel of each output channel. Empty if the channel mapping family is 0.
+    pub fn channel_mapping(&self) -> &[u8] {
+        &self.channel_mapping
+    }
+}
+
+/// The `STREAMINFO` of the `CodecPrivate` of `A_FLAC` tracks, which consists of the `fLaC`
+/// marker and the metadata blocks of the stream.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FlacConfig {
+    min_block_size: u16,
+    max_block_size: u16,
+    min_frame_size: u32,
+    max_frame_size: u32,
+    sample_rate: u32,
+    channel_count: u8,
+    bits_per_sample: u8,
+    total_samples: u64,
+    md5: [u8; 16],
+}
+
+impl FlacConfig {
+    /// Parses the `CodecPrivate` of the given `A_FLAC` track.
+    pub fn from_track(track: &TrackEntry) -> Result<Self> {
+        if track.codec_id() != codec_ids::A_FLAC {
+            return Err(DemuxError::UnsupportedCodec(track.codec_id().to_owned()));
+        }
+        let codec_private = track
+            .decoded_codec_private()?
+            .ok_or(DemuxError::InvalidCodecData)?;
+        Self::parse(&codec_private)
+    }
+
+    /// Parses the `fLaC` marker and the `STREAMINFO` metadata block that follows it.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let mut reader = ByteReader::new(data);
+        if reader.bytes(4)? != b"fLaC" {
+            return Err(DemuxError::InvalidCodecData);
+        }
+        // The STREAMINFO must be the first metadata block.
+        let [block_type, length @ ..] = reader.array::<4>()?;
+        if block_type & 0x7F != 0 || length != [0, 0, 34] {
+            return Err(DemuxError::InvalidCodecData);
+        }
+        let info = reader.array::<34>()?;
+
+        let min_block_size = u16::from_be_bytes([info[0], info[1]]);
+        let max_block_size = u16::from_be_bytes([info[2], info[3]]);
+        let min_frame_size = u32::from_be_bytes([0, info[4], info[5], info[6]]);
+        let max_frame_size = u32::from_be_bytes([0, info[7], info[8], info[9]]);
+        let packed = u64::from_be_bytes([
+            info[10], info[11], info[12], info[13], info[14], info[15], info[16], info[17],
+        ]);
+        let mut md5 = [0_u8; 16];
+        md5.copy_from_slice(&info[18..]);
+
+        Ok(Self {
+            min_block_size,
+            max_block_size,
+            min_frame_size,
+            max_frame_size,
+            sample_rate: u32::try_from(packed >> 44)?,
+            channel_count: u8::try_from((packed >> 41) & 0x07)? + 1,
+            bits_per_sample: u8::try_from((packed >> 36) & 0x1F)? + 1,
+            total_samples: packed & 0x0F_FFFF_FFFF,
+            md5,
+        })
+    }
+
+    /// The smallest block size in samples.
+    pub fn min_block_size(&self) -> u16 {
+        self.min_block_size
+    }
+
+    /// The largest block size in samples.
+    pub fn max_block_size(&self) -> u16 {
+        self.max_block_size
+    }
+
+    /// The smallest frame size in bytes. 0 if unknown.
+    pub fn min_frame_size(&self) -> u32 {
+        self.min_frame_size
+    }
+
+    /// The largest frame size in bytes. 0 if unknown.
+    pub fn max_frame_size(&self) -> u32 {
+        self.max_frame_size
+    }
+
+    /// The sample rate in Hz.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// The number of channels.
+    pub fn channel_count(&self) -> u8 {
+        self.channel_count
+    }
+
+    /// The bits per sample.
+    pub fn bits_per_sample(&self) -> u8 {
+        self.bits_per_sample
+    }
+
+    /// The total number of samples per channel. 0 if unknown.
+    pub fn total_samples(&self) -> u64 {
+        self.total_samples
+    }
+
+    /// The MD5 of the unencoded audio data. All zero if unknown.
+    pub fn md5(&self) -> &[u8; 16] {
+        &self.md5
+    }
+}
+
+/// The standard layout of the channels of an audio track.
+///
+/// The channel order follows the Vorbis channel mapping, which is also used by Opus and
+/// FLAC.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChannelLayout {
+    /// Mono.
+    Mono,
+    /// Front left and front right.
+    Stereo,
+    /// Front left, front center and front right.
+    Surround3_0,
+    /// Front left, front center, front right and back center.
+    Surround4_0,
+    /// Front left, front right, back left and back right.
+    Quad,
+    /// Front left, front center, front right, back left and back right.
+    Surround5_0,
+    /// 5.0 with LFE.
+    Surround5_1,
+    /// Front left, front center, front right, side left, side right, back center and LFE.
+    Surround6_1,
+    /// Front left, front center, front right, side left, side right, back left, back right
+    /// and LFE.
+    Surround7_1,
+    /// A layout without a standard speaker assignment for the given number of channels.
+    Unknown(u64),
+}
+
+impl ChannelLayout {
+    /// Derives the layout of an audio track from its `CodecPrivate` and its channel count.
+    ///
+    /// Returns `None` if the track is not an audio track.
+    pub fn from_track(track: &TrackEntry) -> Option<Self> {
+        let channels = track.audio()?.channels().get();
+        let codec_private = track.decoded_codec_private().ok().flatten();
+        let codec_private = codec_private.as_deref();
+
+        let layout = match (track.codec_id(), codec_private) {
+            (codec_ids::A_OPUS, Some(data)) => match OpusConfig::parse(data) {
+                Ok(config) if config.channel_mapping_family() <= 1 => {
+                    Self::from_channel_count(u64::from(config.channel_count()))
+                }
+                Ok(config) => Self::Unknown(u64::from(config.channel_count())),
+                Err(_) => Self::from_channel_count(channels),
+            },
+            (codec_ids::A_FLAC, Some(data)) => match FlacConfig::parse(data) {
+                Ok(config) => Self::from_channel_count(u64::from(config.channel_count())),
+                Err(_) => Self::from_channel_count(channels),
+            },
+            (codec_id, Some(data)) if codec_id.starts_with(codec_ids::A_AAC) => {
+                match AudioSpecificConfig::parse(data).map(|config| config.channel_config) {
+                    Ok(1) => Self::Mono,
+                    Ok(2) => Self::Stereo,
+                    Ok(3) => Self::Surround3_0,
+                    Ok(4) => Self::Surround4_0,
+                    Ok(5) => Self::Surround5_0,
+                    Ok(6) => Self::Surround5_1,
+                    Ok(7) => Self::Surround7_1,
+                    _ => Self::from_channel_count(channels),
+                }
+            }
+            _ => Self::from_channel_count(channels),
+        };
+        Some(layout)
+    }
+
+    /// The default layout for the given number of channels.
+    pub fn from_channel_count(channels: u64) -> Self {
+        match channels {
+            1 => Self::Mono,
+            2 => Self::Stereo,
+            3 => Self::Surround3_0,
+            4 => Self::Quad,
+            5 => Self::Surround5_0,
+            6 => Self::Surround5_1,
+            7 => Self::Surround6_1,
+            8 => Self::Surround7_1,
+            channels => Self::Unknown(channels),
+        }
+    }
+
+    /// The number of channels of the layout.
+    pub fn channel_count(&self) -> u64 {
+        match self {
+            Self::Mono => 1,
+            Self::Stereo => 2,
+            Self::Surround3_0 => 3,
+            Self::Surround4_0 | Self::Quad => 4,
+            Self::Surround5_0 => 5,
+            Self::Surround5_1 => 6,
+            Self::Surround6_1 => 7,
+            Self::Surround7_1 => 8,
+            Self::Unknown(channels) => *channels,
+        }
+    }
+}
+
+/// The fields of an MPEG-4 `AudioSpecificConfig` that are needed to describe the stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct AudioSpecificConfig {
+    /// The audio object type, for example 2 for AAC LC.
+    pub(crate) object_type: u8,
+    /// The index into `AAC_SAMPLING_FREQUENCIES` or 15 for an explicit sampling frequency.
+    pub(crate) frequency_index: u8,
+    /// The channel configuration. 0 if the channels are defined by the decoder
+    /// specific configuration.
+    pub(crate) channel_config: u8,
+}
+
+impl AudioSpecificConfig {
+    /// Parses the start of an `AudioSpecificConfig` up to the channel configuration.
+    pub(crate) fn parse(data: &[u8]) -> Result<Self> {
+        let mut bytes = [0_u8; 8];
+        let length = data.len().min(8);
+        bytes[..length].copy_from_slice(&data[..length]);
+        let bits = u64::from_be_bytes(bytes);
+        let read = |position: usize, width: usize| {
+            u8::try_from((bits >> (64 - position - width)) & ((1 << width) - 1))
+        };
+
+        let mut position = 5;
+        let mut object_type = read(0, 5)?;
+        if object_type == 31 {
+            // An escaped audio object type.
+            object_type = 32 + read(position, 6)?;
+            position += 6;
+        }
+        let frequency_index = read(position, 4)?;
+        position += 4;
+        if frequency_index == 15 {
+            // An explicit sampling frequency.
+            position += 24;
+        }
+        if position + 4 > length * 8 {
+            return Err(DemuxError::InvalidCodecData);
+        }
+
+        Ok(Self {
+            object_type,
+            frequency_index,
+            channel_config: read(position, 4)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_av1_config() -> Result<()> {
+        let data = [
+            0x81, 0x08, 0x4C, 0x00, // Main profile, level 8, 10 bit 4:2:0.
+            0x0A, 0x03, 0x00, 0x00, 0x00, // A sequence header OBU with a size field.
+        ];
+        let config = Av1Config::parse(&data)?;
+        assert_eq!(config.seq_profile(), 0);
+        assert_eq!(config.seq_level_idx_0(), 8);
+        assert_eq!(config.bit_depth(), 10);
+        assert!(config.chroma_subsampling_x() && config.chroma_subsampling_y());
+        assert_eq!(config.initial_presentation_delay(), None);
+        assert_eq!(config.sequence_header_obu()?, Some(&data[4..]));
+
+        assert!(Av1Config::parse(&[0x01, 0x08, 0x4C, 0x00]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_vp9_config() -> Result<()> {
+        let config = Vp9Config::parse(&[1, 1, 2, 2, 1, 31, 3, 1, 10, 4, 1, 1])?;
+        assert_eq!(config.profile(), Some(2));
+        assert_eq!(config.level(), Some(31));
+        assert_eq!(config.bit_depth(), Some(10));
+        assert_eq!(config.chroma_subsampling(), Some(1));
+
+        assert!(Vp9Config::parse(&[1, 2, 0]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_opus_config() -> Result<()> {
+        let mut data = b"OpusHead".to_vec();
+        data.extend_from_slice(&[1, 2, 0x38, 0x01, 0x80, 0xBB, 0x00, 0x00, 0x00, 0x00, 0]);
+        let config = OpusConfig::parse(&data)?;
+        assert_eq!(config.channel_count(), 2);
+        assert_eq!(config.pre_skip(), 312);
+        assert_eq!(config.input_sample_rate(), 48000);
+        assert_eq!(config.channel_mapping_family(), 0);
+        assert_eq!(config.stream_count(), None);
+
+        let mut data = b"OpusHead".to_vec();
+        data.extend_from_slice(&[1, 3, 0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 1, 0, 2, 1]);
+        let config = OpusConfig::parse(&data)?;
+        assert_eq!(config.stream_count(), Some(2));
+        assert_eq!(config.coupled_count(), Some(1));
+        assert_eq!(config.channel_mapping(), &[0, 2, 1]);
+
+        assert!(OpusConfig::parse(&data[..data.len() - 1]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_flac_config() -> Result<()> {
+        let mut data = b"fLaC".to_vec();
+        data.extend_from_slice(&[0x80, 0, 0, 34]);
+        data.extend_from_slice(&[0x10, 0x00, 0x10, 0x00, 0x00, 0x00, 0x0E, 0x00, 0x1A, 0x2B]);
+        // 44100 Hz, 2 channels, 16 bits and 88200 samples.
+        data.extend_from_slice(&[0x0A, 0xC4, 0x42, 0xF0, 0x00, 0x01, 0x58, 0x88]);
+        data.extend_from_slice(&[0xAB; 16]);
+        let config = FlacConfig::parse(&data)?;
+        assert_eq!(config.min_block_size(), 4096);
+        assert_eq!(config.max_block_size(), 4096);
+        assert_eq!(config.min_frame_size(), 14);
+        assert_eq!(config.max_frame_size(), 6699);
+        assert_eq!(config.sample_rate(), 44100);
+        assert_eq!(config.channel_count(), 2);
+        assert_eq!(config.bits_per_sample(), 16);
+        assert_eq!(config.total_samples(), 88200);
+        assert_eq!(config.md5(), &[0xAB; 16]);
+
+        assert!(FlacConfig::parse(&data[..20]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_channel_layout() -> Result<()> {
+        // AAC LC at 48 kHz with channel configuration 6.
+        assert_eq!(
+            AudioSpecificConfig::parse(&[0x11, 0xB0])?,
+            AudioSpecificConfig {
+                object_type: 2,
+                frequency_index: 3,
+                channel_config: 6,
+            }
+        );
+        // An explicit sampling frequency of 48 kHz with channel configuration 2.
+        let config = AudioSpecificConfig::parse(&[0x17, 0x80, 0x5D, 0xC0, 0x10])?;
+        assert_eq!((config.frequency_index, config.channel_config), (15, 2));
+        // An escaped audio object type.
+        assert_eq!(
+            AudioSpecificConfig::parse(&[0xF8, 0x19, 0x90])?.object_type,
+            32
+        );
+        assert!(AudioSpecificConfig::parse(&[0x11]).is_err());
+
+        assert_eq!(
+            ChannelLayout::from_channel_count(6),
+            ChannelLayout::Surround5_1
+        );
+        assert_eq!(
+            ChannelLayout::from_channel_count(12),
+            ChannelLayout::Unknown(12)
+        );
+        assert_eq!(ChannelLayout::Surround7_1.channel_count(), 8);
+        Ok(())
+    }
+}
@@ -0,0 +1,179 @@
+//! The codec IDs of the Matroska codec registry, as returned by `TrackEntry::codec_id()`.
+//!
+//! The names of the constants are the codec IDs with `/` replaced by `_`.
+
+// Video codecs.
+
+/// Video for Windows, the `CodecPrivate` contains a `BITMAPINFOHEADER`.
+pub const V_MS_VFW_FOURCC: &str = "V_MS/VFW/FOURCC";
+/// Uncompressed video, the format is given by the `UncompressedFourCC` of the track.
+pub const V_UNCOMPRESSED: &str = "V_UNCOMPRESSED";
+/// MPEG-4 Part 2 Simple Profile.
+pub const V_MPEG4_ISO_SP: &str = "V_MPEG4/ISO/SP";
+/// MPEG-4 Part 2 Advanced Simple Profile.
+pub const V_MPEG4_ISO_ASP: &str = "V_MPEG4/ISO/ASP";
+/// MPEG-4 Part 2 Advanced Profile.
+pub const V_MPEG4_ISO_AP: &str = "V_MPEG4/ISO/AP";
+/// Microsoft MPEG-4 V3.
+pub const V_MPEG4_MS_V3: &str = "V_MPEG4/MS/V3";
+/// MPEG-1 video.
+pub const V_MPEG1: &str = "V_MPEG1";
+/// MPEG-2 video.
+pub const V_MPEG2: &str = "V_MPEG2";
+/// H.264 / AVC.
+pub const V_MPEG4_ISO_AVC: &str = "V_MPEG4/ISO/AVC";
+/// H.265 / HEVC.
+pub const V_MPEGH_ISO_HEVC: &str = "V_MPEGH/ISO/HEVC";
+/// H.266 / VVC.
+pub const V_MPEGI_ISO_VVC: &str = "V_MPEGI/ISO/VVC";
+/// AVS2.
+pub const V_AVS2: &str = "V_AVS2";
+/// AVS3.
+pub const V_AVS3: &str = "V_AVS3";
+/// RealVideo 1.0.
+pub const V_REAL_RV10: &str = "V_REAL/RV10";
+/// RealVideo G2 and RealVideo G2+SVT.
+pub const V_REAL_RV20: &str = "V_REAL/RV20";
+/// RealVideo 8.
+pub const V_REAL_RV30: &str = "V_REAL/RV30";
+/// RealVideo 9.
+pub const V_REAL_RV40: &str = "V_REAL/RV40";
+/// QuickTime video.
+pub const V_QUICKTIME: &str = "V_QUICKTIME";
+/// Theora.
+pub const V_THEORA: &str = "V_THEORA";
+/// Apple ProRes.
+pub const V_PRORES: &str = "V_PRORES";
+/// VP8.
+pub const V_VP8: &str = "V_VP8";
+/// VP9.
+pub const V_VP9: &str = "V_VP9";
+/// FFV1.
+pub const V_FFV1: &str = "V_FFV1";
+/// AV1.
+pub const V_AV1: &str = "V_AV1";
+
+// Audio codecs.
+
+/// MPEG audio layer 3.
+pub const A_MPEG_L3: &str = "A_MPEG/L3";
+/// MPEG audio layer 2.
+pub const A_MPEG_L2: &str = "A_MPEG/L2";
+/// MPEG audio layer 1.
+pub const A_MPEG_L1: &str = "A_MPEG/L1";
+/// Big endian integer PCM.
+pub const A_PCM_INT_BIG: &str = "A_PCM/INT/BIG";
+/// Little endian integer PCM.
+pub const A_PCM_INT_LIT: &str = "A_PCM/INT/LIT";
+/// IEEE floating point PCM.
+pub const A_PCM_FLOAT_IEEE: &str = "A_PCM/FLOAT/IEEE";
+/// Musepack SV8.
+pub const A_MPC: &str = "A_MPC";
+/// AC-3.
+pub const A_AC3: &str = "A_AC3";
+/// AC-3 with a bit stream ID of 9.
+pub const A_AC3_BSID9: &str = "A_AC3/BSID9";
+/// AC-3 with a bit stream ID of 10.
+pub const A_AC3_BSID10: &str = "A_AC3/BSID10";
+/// Apple Lossless Audio Codec.
+pub const A_ALAC: &str = "A_ALAC";
+/// DTS.
+pub const A_DTS: &str = "A_DTS";
+/// DTS Express.
+pub const A_DTS_EXPRESS: &str = "A_DTS/EXPRESS";
+/// DTS-HD Master Audio.
+pub const A_DTS_LOSSLESS: &str = "A_DTS/LOSSLESS";
+/// Vorbis.
+pub const A_VORBIS: &str = "A_VORBIS";
+/// FLAC.
+pub const A_FLAC: &str = "A_FLAC";
+/// RealAudio 1.
+pub const A_REAL_14_4: &str = "A_REAL/14_4";
+/// RealAudio 2.
+pub const A_REAL_28_8: &str = "A_REAL/28_8";
+/// RealAudio Cook.
+pub const A_REAL_COOK: &str = "A_REAL/COOK";
+/// RealAudio Sipro.
+pub const A_REAL_SIPR: &str = "A_REAL/SIPR";
+/// RealAudio Lossless.
+pub const A_REAL_RALF: &str = "A_REAL/RALF";
+/// RealAudio ATRAC3.
+pub const A_REAL_ATRC: &str = "A_REAL/ATRC";
+/// Audio Compression Manager, the `CodecPrivate` contains a `WAVEFORMATEX`.
+pub const A_MS_ACM: &str = "A_MS/ACM";
+/// AAC, the profile is given by the `CodecPrivate`.
+pub const A_AAC: &str = "A_AAC";
+/// MPEG-2 AAC Main profile.
+pub const A_AAC_MPEG2_MAIN: &str = "A_AAC/MPEG2/MAIN";
+/// MPEG-2 AAC Low Complexity profile.
+pub const A_AAC_MPEG2_LC: &str = "A_AAC/MPEG2/LC";
+/// MPEG-2 AAC Low Complexity profile with Spectral Band Replication.
+pub const A_AAC_MPEG2_LC_SBR: &str = "A_AAC/MPEG2/LC/SBR";
+/// MPEG-2 AAC Scalable Sampling Rate profile.
+pub const A_AAC_MPEG2_SSR: &str = "A_AAC/MPEG2/SSR";
+/// MPEG-4 AAC Main profile.
+pub const A_AAC_MPEG4_MAIN: &str = "A_AAC/MPEG4/MAIN";
+/// MPEG-4 AAC Low Complexity profile.
+pub const A_AAC_MPEG4_LC: &str = "A_AAC/MPEG4/LC";
+/// MPEG-4 AAC Low Complexity profile with Spectral Band Replication.
+pub const A_AAC_MPEG4_LC_SBR: &str = "A_AAC/MPEG4/LC/SBR";
+/// MPEG-4 AAC Scalable Sampling Rate profile.
+pub const A_AAC_MPEG4_SSR: &str = "A_AAC/MPEG4/SSR";
+/// MPEG-4 AAC Long Term Prediction profile.
+pub const A_AAC_MPEG4_LTP: &str = "A_AAC/MPEG4/LTP";
+/// QuickTime audio.
+pub const A_QUICKTIME: &str = "A_QUICKTIME";
+/// QDesign Music.
+pub const A_QUICKTIME_QDMC: &str = "A_QUICKTIME/QDMC";
+/// QDesign Music v2.
+pub const A_QUICKTIME_QDM2: &str = "A_QUICKTIME/QDM2";
+/// The True Audio.
+pub const A_TTA1: &str = "A_TTA1";
+/// WavPack 4.
+pub const A_WAVPACK4: &str = "A_WAVPACK4";
+/// ATRAC1.
+pub const A_ATRAC_AT1: &str = "A_ATRAC/AT1";
+/// Enhanced AC-3.
+pub const A_EAC3: &str = "A_EAC3";
+/// Dolby TrueHD.
+pub const A_TRUEHD: &str = "A_TRUEHD";
+/// Opus.
+pub const A_OPUS: &str = "A_OPUS";
+
+// Subtitle codecs.
+
+/// Plain UTF-8 text.
+pub const S_TEXT_UTF8: &str = "S_TEXT/UTF8";
+/// SubStation Alpha.
+pub const S_TEXT_SSA: &str = "S_TEXT/SSA";
+/// Advanced SubStation Alpha.
+pub const S_TEXT_ASS: &str = "S_TEXT/ASS";
+/// WebVTT.
+pub const S_TEXT_WEBVTT: &str = "S_TEXT/WEBVTT";
+/// Bitmap images.
+pub const S_IMAGE_BMP: &str = "S_IMAGE/BMP";
+/// DVB subtitles.
+pub const S_DVBSUB: &str = "S_DVBSUB";
+/// VobSub subtitles of DVDs.
+pub const S_VOBSUB: &str = "S_VOBSUB";
+/// Presentation Graphic Stream subtitles of Blu-rays.
+pub const S_HDMV_PGS: &str = "S_HDMV/PGS";
+/// Text subtitles of Blu-rays.
+pub const S_HDMV_TEXTST: &str = "S_HDMV/TEXTST";
+/// Kate.
+pub const S_KATE: &str = "S_KATE";
+/// ARIB STD-B24 subtitles.
+pub const S_ARIBSUB: &str = "S_ARIBSUB";
+/// WebVTT subtitles as stored in WebM.
+pub const D_WEBVTT_SUBTITLES: &str = "D_WEBVTT/SUBTITLES";
+/// WebVTT captions as stored in WebM.
+pub const D_WEBVTT_CAPTIONS: &str = "D_WEBVTT/CAPTIONS";
+/// WebVTT descriptions as stored in WebM.
+pub const D_WEBVTT_DESCRIPTIONS: &str = "D_WEBVTT/DESCRIPTIONS";
+/// WebVTT metadata as stored in WebM.
+pub const D_WEBVTT_METADATA: &str = "D_WEBVTT/METADATA";
+
+// Buttons.
+
+/// Buttons of DVD menus.
+pub const B_VOBBTN: &str = "B_VOBBTN";
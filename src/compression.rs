@@ -0,0 +1,164 @@
+//! Decompression of frames of tracks with a `ContentCompression`.
+
+use std::ops::Range;
+
+use crate::{ContentCompAlgo, ContentCompression, DemuxError, Result};
+
+/// Decompresses the frame, or every frame of a combined lace. Also used for the
+/// `CodecPrivate`, which has no lace ranges. The decompressed data may not be larger than
+/// `limit` bytes.
+///
+/// Zlib compressed frames are returned as stored, since they can be inflated by the caller,
+/// and the algorithm is returned to mark them. Returns `DemuxError::UnsupportedCompression`
+/// if any other algorithm is not supported or its feature is not enabled, so that
+/// compressed frames are never returned silently.
+pub(crate) fn decompress_frame(
+    compression: &ContentCompression,
+    data: &mut Vec<u8>,
+    lace_ranges: &mut [Range<usize>],
+    limit: usize,
+) -> Result<Option<ContentCompAlgo>> {
+    match compression.algo() {
+        ContentCompAlgo::Zlib => return Ok(Some(ContentCompAlgo::Zlib)),
+        ContentCompAlgo::HeaderStripping => {
+            let header = compression.settings().unwrap_or_default();
+            restore_stripped_header(header, data, lace_ranges);
+            check_limit(data.len(), limit)?;
+        }
+        #[cfg(feature = "bzip2")]
+        ContentCompAlgo::Bzlib => {
+            decompress_laces(data, lace_ranges, limit, crate::bzip2::decompress)?;
+        }
+        #[cfg(feature = "lzo")]
+        ContentCompAlgo::Lzo1x => {
+            decompress_laces(data, lace_ranges, limit, crate::lzo::decompress)?;
+        }
+        algo => return Err(DemuxError::UnsupportedCompression(algo)),
+    }
+    Ok(None)
+}
+
+/// Returns `DemuxError::DecompressedSizeExceeded` if the size is larger than the limit.
+pub(crate) fn check_limit(size: usize, limit: usize) -> Result<()> {
+    match size > limit {
+        true => Err(DemuxError::DecompressedSizeExceeded(u64::try_from(limit)?)),
+        false => Ok(()),
+    }
+}
+
+/// Decompresses every laced frame on its own, since each of them was compressed separately.
+/// The limit applies to all frames together.
+#[cfg(any(feature = "bzip2", feature = "lzo"))]
+fn decompress_laces(
+    data: &mut Vec<u8>,
+    lace_ranges: &mut [Range<usize>],
+    limit: usize,
+    decompress: fn(&[u8], usize) -> Result<Vec<u8>>,
+) -> Result<()> {
+    if lace_ranges.is_empty() {
+        *data = decompress(data, limit)?;
+        return Ok(());
+    }
+
+    let mut decompressed = Vec::with_capacity(data.len().min(limit));
+    for range in lace_ranges.iter_mut() {
+        let frame = data
+            .get(range.clone())
+            .ok_or(DemuxError::UnexpectedDataType)?;
+        let start = decompressed.len();
+        let frame = decompress(frame, limit)?;
+        check_limit(start.saturating_add(frame.len()), limit)?;
+        decompressed.extend_from_slice(&frame);
+        *range = start..decompressed.len();
+    }
+    *data = decompressed;
+    Ok(())
+}
+
+/// Prepends the stripped header to the frame, or to every frame of a combined lace.
+pub(crate) fn restore_stripped_header(
+    header: &[u8],
+    data: &mut Vec<u8>,
+    lace_ranges: &mut [Range<usize>],
+) {
+    if header.is_empty() {
+        return;
+    }
+    if lace_ranges.is_empty() {
+        data.splice(0..0, header.iter().copied());
+        return;
+    }
+
+    let mut restored = Vec::with_capacity(data.len() + header.len() * lace_ranges.len());
+    for range in lace_ranges.iter_mut() {
+        let start = restored.len();
+        restored.extend_from_slice(header);
+        restored.extend_from_slice(data.get(range.clone()).unwrap_or_default());
+        *range = start..restored.len();
+    }
+    *data = restored;
+}
+
+/// The path of a compressed file of the corpus, its data and the expected data.
+#[cfg(all(test, any(feature = "bzip2", feature = "lzo")))]
+pub(crate) type CorpusEntry = (std::path::PathBuf, Vec<u8>, Vec<u8>);
+
+/// Returns the files of the corpus in the given directory of `tests/data/corpus` that have
+/// the given extension, together with the expected data of their `.raw` files.
+#[cfg(all(test, any(feature = "bzip2", feature = "lzo")))]
+pub(crate) fn read_corpus(directory: &str, extension: &str) -> Result<Vec<CorpusEntry>> {
+    let mut corpus = Vec::new();
+    for entry in std::fs::read_dir(format!("tests/data/corpus/{directory}"))? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|e| e == extension) {
+            let compressed = std::fs::read(&path)?;
+            let raw = std::fs::read(path.with_extension("raw"))?;
+            corpus.push((path, compressed, raw));
+        }
+    }
+    corpus.sort();
+    Ok(corpus)
+}
+
+/// Decompresses every prefix of the data and the data with single bytes damaged. The
+/// decompression must either fail or stay within the limit, but never panic.
+#[cfg(all(test, any(feature = "bzip2", feature = "lzo")))]
+pub(crate) fn check_damaged(
+    decompress: fn(&[u8], usize) -> Result<Vec<u8>>,
+    data: &[u8],
+    limit: usize,
+) {
+    for end in 0..data.len() {
+        if let Ok(out) = decompress(&data[..end], limit) {
+            assert!(out.len() <= limit);
+        }
+    }
+    let mut damaged = data.to_vec();
+    for i in 0..damaged.len() {
+        for mask in [0x01, 0x80, 0xFF] {
+            damaged[i] ^= mask;
+            if let Ok(out) = decompress(&damaged, limit) {
+                assert!(out.len() <= limit);
+            }
+            damaged[i] ^= mask;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restore_stripped_header() {
+        let mut data = b"ab".to_vec();
+        restore_stripped_header(b"\x00\x01", &mut data, &mut []);
+        assert_eq!(data, b"\x00\x01ab");
+
+        let mut data = b"abc".to_vec();
+        let mut lace_ranges = [0..1, 1..3];
+        restore_stripped_header(b"H", &mut data, &mut lace_ranges);
+        assert_eq!(data, b"HaHbc");
+        assert_eq!(lace_ranges, [0..2, 2..5]);
+    }
+}
@@ -0,0 +1,321 @@
+//! Human readable dump of the element tree of a file, similar to `mkvinfo`.
+//!
+//! Every element is printed on its own line with its name, raw Element ID, offset, size and
+//! value. Children are indented below their parent:
+//!
+//! ```text
+//! + Segment [18538067] offset 40, size 47669
+//!   + Info [1549A966] offset 4151, size 122
+//!     + TimestampScale [2AD7B1] offset 4156, size 3: 1000000
+//! ```
+
+use std::{
+    collections::HashMap,
+    io::{Read, Seek, SeekFrom},
+};
+
+use crate::{
+    block::parse_block_header,
+    ebml::{
+        parse_element_data_of_type, try_next_element_header, ElementData, HeaderLimits, MAX_DEPTH,
+    },
+    element_id::{element_id_to_type, element_parents, ElementId, ElementType},
+    CustomElement, MatroskaFile, Result,
+};
+
+/// Options that control the output of `dump()`.
+#[derive(Clone, Debug)]
+pub struct DumpOptions {
+    binary_bytes: usize,
+    blocks: bool,
+}
+
+impl Default for DumpOptions {
+    fn default() -> Self {
+        Self {
+            binary_bytes: 16,
+            blocks: true,
+        }
+    }
+}
+
+impl DumpOptions {
+    /// The number of bytes of binary elements that are printed as hex. Defaults to 16.
+    pub fn binary_bytes(mut self, binary_bytes: usize) -> Self {
+        self.binary_bytes = binary_bytes;
+        self
+    }
+
+    /// Prints the `SimpleBlock` and `BlockGroup` elements of the clusters. Defaults to `true`.
+    ///
+    /// Blocks are printed with their track number, relative timestamp and flags instead of
+    /// their payload.
+    pub fn blocks(mut self, blocks: bool) -> Self {
+        self.blocks = blocks;
+        self
+    }
+}
+
+/// Walks the whole element tree of the file and returns a human readable report of it.
+///
+/// The reader is walked from its start. Damaged parts of the file are reported inside the
+/// report and end the walk, so that the report can be attached to bug reports.
+///
+/// Use `MatroskaFile::dump()` to print the elements registered with
+/// `ParserSettings::register_element()` with their name and value.
+pub fn dump<R: Read + Seek>(r: &mut R, options: &DumpOptions) -> Result<String> {
+    dump_elements(r, options, &HashMap::new())
+}
+
+fn dump_elements<R: Read + Seek>(
+    r: &mut R,
+    options: &DumpOptions,
+    custom_elements: &HashMap<u32, CustomElement>,
+) -> Result<String> {
+    let end = r.seek(SeekFrom::End(0))?;
+    r.seek(SeekFrom::Start(0))?;
+
+    let mut dumper = Dumper {
+        r,
+        options,
+        custom_elements,
+        out: String::new(),
+    };
+    dumper.dump_children(None, end, false, 0)?;
+    Ok(dumper.out)
+}
+
+impl<R: Read + Seek> MatroskaFile<R> {
+    /// Returns a human readable report of the element tree of the file. Works like
+    /// `dump::dump()`, but prints the registered elements with their name and type.
+    ///
+    /// The position of the demuxer is restored afterwards.
+    pub fn dump(&mut self, options: &DumpOptions) -> Result<String> {
+        let position = self.file.stream_position()?;
+        let result = dump_elements(
+            &mut self.file,
+            options,
+            &self.index.settings.custom_elements,
+        );
+        self.file.seek(SeekFrom::Start(position))?;
+        result
+    }
+}
+
+struct Dumper<'a, R> {
+    r: &'a mut R,
+    options: &'a DumpOptions,
+    custom_elements: &'a HashMap<u32, CustomElement>,
+    out: String,
+}
+
+impl<'a, R: Read + Seek> Dumper<'a, R> {
+    /// Prints the elements until the given end. Children of a master element with an unknown
+    /// size end with the first element that is not allowed inside of it.
+    ///
+    /// Returns `false` if the walk was stopped by an error.
+    fn dump_children(
+        &mut self,
+        parent: Option<ElementId>,
+        end: u64,
+        is_unknown_size: bool,
+        depth: usize,
+    ) -> Result<bool> {
+        loop {
+            let position = self.r.stream_position()?;
+            if position >= end {
+                return Ok(true);
+            }
+
+            let (id, element_id, size, element_data) = match self.next_element() {
+                Ok(Some(element)) => element,
+                Ok(None) => return Ok(true),
+                Err(err) => {
+                    self.line(depth, &format!("! error at offset {}: {}", position, err));
+                    return Ok(false);
+                }
+            };
+
+            if let (Some(parent), true) = (parent, is_unknown_size) {
+                let is_allowed =
+                    element_parents(element_id).map_or(true, |parents| parents.contains(&parent));
+                if !is_allowed {
+                    self.r.seek(SeekFrom::Start(position))?;
+                    return Ok(true);
+                }
+            }
+
+            let size_text = match size {
+                u64::MAX => "unknown size".to_owned(),
+                size => format!("size {}", size),
+            };
+            let header = format!(
+                "+ {} [{:X}] offset {}, {}",
+                self.element_name(element_id),
+                id,
+                position,
+                size_text
+            );
+
+            match element_data {
+                ElementData::Location { offset, size }
+                    if self.element_type(element_id) == ElementType::Master =>
+                {
+                    // The reader is already positioned behind elements with a known size.
+                    if !self.options.blocks && element_id == ElementId::BlockGroup {
+                        continue;
+                    }
+                    self.line(depth, &header);
+
+                    let child_end = match size {
+                        u64::MAX => end,
+                        size => offset.saturating_add(size),
+                    };
+                    if depth + 1 >= MAX_DEPTH {
+                        self.line(depth + 1, "! the elements are nested too deeply");
+                        return Ok(false);
+                    }
+                    let is_unknown_size = size == u64::MAX;
+                    self.r.seek(SeekFrom::Start(offset))?;
+                    if !self.dump_children(
+                        Some(element_id),
+                        child_end,
+                        is_unknown_size,
+                        depth + 1,
+                    )? {
+                        return Ok(false);
+                    }
+                    if size != u64::MAX {
+                        self.r.seek(SeekFrom::Start(child_end))?;
+                    }
+                }
+                ElementData::Location { offset, size } => {
+                    let is_block = matches!(element_id, ElementId::SimpleBlock | ElementId::Block);
+                    if !self.options.blocks && element_id == ElementId::SimpleBlock {
+                        continue;
+                    }
+                    let value = match is_block {
+                        true => self.block_value(offset),
+                        false => self.binary_value(offset, size),
+                    };
+                    let value = value.unwrap_or_else(|err| format!("! {}", err));
+                    self.line(depth, &format!("{}: {}", header, value));
+                    self.r.seek(SeekFrom::Start(offset.saturating_add(size)))?;
+                }
+                ElementData::Unsigned(value) => {
+                    self.line(depth, &format!("{}: {}", header, value));
+                }
+                ElementData::Signed(value) | ElementData::Date(value) => {
+                    self.line(depth, &format!("{}: {}", header, value));
+                }
+                ElementData::Float(value) => {
+                    self.line(depth, &format!("{}: {}", header, value));
+                }
+                ElementData::String(value) => {
+                    self.line(depth, &format!("{}: {:?}", header, value));
+                }
+                ElementData::InvalidString(bytes) => {
+                    let value = String::from_utf8_lossy(&bytes);
+                    self.line(depth, &format!("{}: {:?} (invalid UTF-8)", header, value));
+                }
+            }
+        }
+    }
+
+    /// Reads the next element. Registered elements are parsed with their registered type.
+    fn next_element(&mut self) -> Result<Option<(u32, ElementId, u64, ElementData)>> {
+        let (id, element_id, size) = match try_next_element_header(self.r, HeaderLimits::default())?
+        {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+        let element_id = match element_id {
+            ElementId::Unknown if self.custom_elements.contains_key(&id) => ElementId::Custom(id),
+            element_id => element_id,
+        };
+        let element_data = parse_element_data_of_type(self.r, self.element_type(element_id), size)?;
+        Ok(Some((id, element_id, size, element_data)))
+    }
+
+    fn element_type(&self, element_id: ElementId) -> ElementType {
+        match element_id {
+            ElementId::Custom(id) => self
+                .custom_elements
+                .get(&id)
+                .map_or(ElementType::Unknown, |custom| custom.element_type),
+            element_id => element_id_to_type(element_id),
+        }
+    }
+
+    fn element_name(&self, element_id: ElementId) -> String {
+        match element_id {
+            ElementId::Custom(id) => self
+                .custom_elements
+                .get(&id)
+                .map_or_else(|| format!("{:?}", element_id), |custom| custom.name.clone()),
+            element_id => format!("{:?}", element_id),
+        }
+    }
+
+    fn block_value(&mut self, offset: u64) -> Result<String> {
+        self.r.seek(SeekFrom::Start(offset))?;
+        let (track, timestamp, flags) = parse_block_header(self.r, 0)?;
+        Ok(format!(
+            "track {}, timestamp {}, flags 0x{:02X}",
+            track, timestamp, flags
+        ))
+    }
+
+    fn binary_value(&mut self, offset: u64, size: u64) -> Result<String> {
+        let length = usize::try_from(size)
+            .unwrap_or(usize::MAX)
+            .min(self.options.binary_bytes);
+        let mut data = vec![0_u8; length];
+        self.r.seek(SeekFrom::Start(offset))?;
+        self.r.read_exact(&mut data)?;
+
+        let mut value: String = data.iter().map(|byte| format!("{:02x}", byte)).collect();
+        if u64::try_from(length)? < size {
+            value.push_str("...");
+        }
+        Ok(value)
+    }
+
+    fn line(&mut self, depth: usize, text: &str) {
+        for _ in 0..depth {
+            self.out.push_str("  ");
+        }
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn test_dump_unknown_size_cluster() -> Result<()> {
+        let data: Vec<u8> = vec![
+            // Cluster with an unknown size.
+            0x1F, 0x43, 0xB6, 0x75, 0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, //
+            0xE7, 0x81, 0x00, // Timestamp 0
+            0xA3, 0x85, 0x81, 0x00, 0x00, 0x80, 0xAA, // SimpleBlock of track 1
+            // Next cluster.
+            0x1F, 0x43, 0xB6, 0x75, 0x83, //
+            0xE7, 0x81, 0x02, // Timestamp 2
+        ];
+        let report = dump(&mut Cursor::new(data), &DumpOptions::default())?;
+        assert_eq!(
+            report,
+            "+ Cluster [1F43B675] offset 0, unknown size\n\
+             \x20 + Timestamp [E7] offset 12, size 1: 0\n\
+             \x20 + SimpleBlock [A3] offset 15, size 5: track 1, timestamp 0, flags 0x80\n\
+             + Cluster [1F43B675] offset 22, size 3\n\
+             \x20 + Timestamp [E7] offset 27, size 1: 2\n"
+        );
+        Ok(())
+    }
+}
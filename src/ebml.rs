@@ -2,7 +2,7 @@
 
 use std::{
     convert::{TryFrom, TryInto},
-    io::{Read, Seek, SeekFrom},
+    io::{ErrorKind, Read, Seek, SeekFrom},
     num::NonZeroU64,
 };
 
@@ -27,12 +27,47 @@ pub enum ElementData {
     Date(i64),
     /// String.
     String(String),
+    /// String that is not valid UTF-8, with its raw bytes.
+    InvalidString(Vec<u8>),
 }
 
+/// Parses a master element from its children.
+///
+/// The `options` are passed on to the children that are parsed by the element itself.
 pub(crate) trait ParsableElement<R: Read + Seek> {
     type Output;
 
-    fn new(r: &mut R, fields: &[(ElementId, ElementData)]) -> Result<Self::Output>;
+    fn new(
+        r: &mut R,
+        fields: &[(ElementId, ElementData)],
+        options: ParseOptions,
+    ) -> Result<Self::Output>;
+}
+
+/// The options that are used when parsing master elements and their children.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct ParseOptions {
+    /// Decode strings that are not valid UTF-8 lossily instead of failing.
+    pub(crate) lossy_strings: bool,
+    /// The maximum lengths of the Element IDs and data sizes of the children.
+    pub(crate) limits: HeaderLimits,
+}
+
+/// Fails on the first string of the children that is not valid UTF-8, unless such strings
+/// are decoded lossily.
+pub(crate) fn check_strings(
+    fields: &[(ElementId, ElementData)],
+    lossy_strings: bool,
+) -> Result<()> {
+    if lossy_strings {
+        return Ok(());
+    }
+    for (_, data) in fields {
+        if let ElementData::InvalidString(bytes) = data {
+            String::from_utf8(bytes.clone())?;
+        }
+    }
+    Ok(())
 }
 
 /// Tries to parse an element with the given Element ID that returns a master element at the current location of the reader. Leaves the reader at the first byte after the master entry.
@@ -40,8 +75,9 @@ pub(crate) fn expect_master<R: Read + Seek>(
     r: &mut R,
     expected_id: ElementId,
     from: Option<u64>,
+    limits: HeaderLimits,
 ) -> Result<(u64, u64)> {
-    let (element_id, size) = parse_element_header(r, from)?;
+    let (element_id, size) = parse_element_header(r, from, limits)?;
 
     if element_id != expected_id {
         return Err(DemuxError::UnexpectedElement((expected_id, element_id)));
@@ -51,21 +87,57 @@ pub(crate) fn expect_master<R: Read + Seek>(
     Ok((offset, size))
 }
 
+/// The maximum lengths of Element IDs and data sizes declared by the EBML header. Longer
+/// encodings are rejected while parsing the element headers.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct HeaderLimits {
+    pub(crate) max_id_length: u64,
+    pub(crate) max_size_length: u64,
+}
+
+impl Default for HeaderLimits {
+    /// The longest encodings the parser supports.
+    fn default() -> Self {
+        Self {
+            max_id_length: 4,
+            max_size_length: 8,
+        }
+    }
+}
+
+/// Returns the length of the encoded Element ID in bytes.
+pub(crate) fn element_id_length(id: u32) -> u64 {
+    4 - u64::from(id.leading_zeros() / 8)
+}
+
+/// The maximum depth of nested master elements, so that a malicious file can't exhaust the
+/// stack.
+pub(crate) const MAX_DEPTH: usize = 64;
+
 /// Collects the children of a master element.
 pub(crate) fn collect_children<R: Read + Seek>(
     r: &mut R,
     offset: u64,
     size: u64,
+    limits: HeaderLimits,
 ) -> Result<Vec<(ElementId, ElementData)>> {
     let mut children = Vec::with_capacity(16);
-    r.seek(SeekFrom::Start(offset))?;
+    // Buffered readers discard their buffer on every seek, so we only seek if needed.
+    if r.stream_position()? != offset {
+        r.seek(SeekFrom::Start(offset))?;
+    }
     let end = offset + size;
 
     while r.stream_position()? < end {
-        let (element_id, element_data) = next_element(r)?;
+        let (element_id, element_data) = next_element(r, limits)?;
 
         if let ElementData::Location { offset, size } = element_data {
             if size == u64::MAX {
+                trace!(
+                    ?element_id,
+                    offset,
+                    "stopped collecting at a child with unknown size"
+                );
                 break;
             }
             r.seek(SeekFrom::Start(offset + size))?;
@@ -84,6 +156,7 @@ pub(crate) fn try_parse_children<R, T>(
     fields: &[(ElementId, ElementData)],
     parent_id: ElementId,
     child_id: ElementId,
+    options: ParseOptions,
 ) -> Result<Option<Vec<T::Output>>>
 where
     R: Read + Seek,
@@ -92,7 +165,7 @@ where
     let children = if let Some((_, ElementData::Location { offset, size })) =
         fields.iter().find(|(id, _)| *id == parent_id)
     {
-        let content_encodings = parse_children_inner::<_, T>(r, *offset, *size, child_id)?;
+        let content_encodings = parse_children_inner::<_, T>(r, *offset, *size, child_id, options)?;
         Some(content_encodings)
     } else {
         None
@@ -106,13 +179,14 @@ pub(crate) fn parse_children_at_offset<R, T>(
     offset: u64,
     master_id: ElementId,
     child_id: ElementId,
+    options: ParseOptions,
 ) -> Result<Vec<T::Output>>
 where
     R: Read + Seek,
     T: ParsableElement<R>,
 {
-    let (data_offset, data_size) = expect_master(r, master_id, Some(offset))?;
-    let children = parse_children_inner::<_, T>(r, data_offset, data_size, child_id)?;
+    let (data_offset, data_size) = expect_master(r, master_id, Some(offset), options.limits)?;
+    let children = parse_children_inner::<_, T>(r, data_offset, data_size, child_id, options)?;
     Ok(children)
 }
 
@@ -121,43 +195,31 @@ fn parse_children_inner<R, T>(
     offset: u64,
     size: u64,
     child_id: ElementId,
+    options: ParseOptions,
 ) -> Result<Vec<T::Output>>
 where
     R: Read + Seek,
     T: ParsableElement<R>,
 {
     let mut children = vec![];
-    let master_fields = collect_children(r, offset, size)?;
+    let master_fields = collect_children(r, offset, size, options.limits)?;
     for (_, element_data) in master_fields.iter().filter(|(id, _)| *id == child_id) {
         if let ElementData::Location { offset, size } = element_data {
-            let child_fields = collect_children(r, *offset, *size)?;
-            let track_entry = T::new(r, &child_fields)?;
+            let child_fields = collect_children(r, *offset, *size, options.limits)?;
+            check_strings(&child_fields, options.lossy_strings)?;
+            let track_entry = T::new(r, &child_fields, options)?;
             children.push(track_entry)
         }
     }
     Ok(children)
 }
 
-/// Expects to find the child with the given Element ID from the given fields and reader.
-pub(crate) fn parse_child<R, T>(
-    r: &mut R,
-    fields: &[(ElementId, ElementData)],
-    element_id: ElementId,
-) -> Result<T::Output>
-where
-    R: Read + Seek,
-    T: ParsableElement<R>,
-{
-    let child = try_parse_child::<_, T>(r, fields, element_id)?
-        .ok_or(DemuxError::ElementNotFound(element_id))?;
-    Ok(child)
-}
-
 /// Tries to parse the child with the given Element ID from the given fields and reader.
 pub(crate) fn try_parse_child<R, T>(
     r: &mut R,
     fields: &[(ElementId, ElementData)],
     element_id: ElementId,
+    options: ParseOptions,
 ) -> Result<Option<T::Output>>
 where
     R: Read + Seek,
@@ -165,8 +227,9 @@ where
 {
     let child = if let Some((_, element_data)) = fields.iter().find(|(id, _)| *id == element_id) {
         if let ElementData::Location { offset, size } = element_data {
-            let child_fields = collect_children(r, *offset, *size)?;
-            let child = T::new(r, &child_fields)?;
+            let child_fields = collect_children(r, *offset, *size, options.limits)?;
+            check_strings(&child_fields, options.lossy_strings)?;
+            let child = T::new(r, &child_fields, options)?;
             Some(child)
         } else {
             return Err(DemuxError::UnexpectedDataType);
@@ -213,6 +276,41 @@ pub(crate) fn try_find_unsigned(
     }
 }
 
+/// Finds all elements with the Element ID for an unsigned integer inside a list of children.
+pub(crate) fn find_all_unsigned(
+    fields: &[(ElementId, ElementData)],
+    element_id: ElementId,
+) -> Result<Vec<u64>> {
+    fields
+        .iter()
+        .filter(|(id, _)| *id == element_id)
+        .map(|(_, data)| {
+            if let ElementData::Unsigned(value) = data {
+                Ok(*value)
+            } else {
+                Err(DemuxError::UnexpectedDataType)
+            }
+        })
+        .collect()
+}
+
+/// Finds all elements with the Element ID for a string inside a list of children. Strings
+/// that are not valid UTF-8 are decoded lossily, see `check_strings()`.
+pub(crate) fn find_all_strings(
+    fields: &[(ElementId, ElementData)],
+    element_id: ElementId,
+) -> Result<Vec<String>> {
+    fields
+        .iter()
+        .filter(|(id, _)| *id == element_id)
+        .map(|(_, data)| match data {
+            ElementData::String(value) => Ok(value.clone()),
+            ElementData::InvalidString(bytes) => Ok(String::from_utf8_lossy(bytes).into_owned()),
+            _ => Err(DemuxError::UnexpectedDataType),
+        })
+        .collect()
+}
+
 /// Tries to find an element with the Element ID for a custom type inside a list of children, otherwise sets the default value.
 pub(crate) fn try_find_custom_type_or<T: From<u64>>(
     fields: &[(ElementId, ElementData)],
@@ -355,21 +453,44 @@ pub(crate) fn find_string(
 }
 
 /// Tries to find an element with the Element ID for a string inside a list of children.
+/// Strings that are not valid UTF-8 are decoded lossily, see `check_strings()`.
 pub(crate) fn try_find_string(
     fields: &[(ElementId, ElementData)],
     element_id: ElementId,
 ) -> Result<Option<String>> {
-    if let Some((_, data)) = fields.iter().find(|(id, _)| *id == element_id) {
-        if let ElementData::String(value) = data {
-            Ok(Some(value.clone()))
-        } else {
-            Err(DemuxError::UnexpectedDataType)
+    match fields.iter().find(|(id, _)| *id == element_id) {
+        Some((_, ElementData::String(value))) => Ok(Some(value.clone())),
+        Some((_, ElementData::InvalidString(bytes))) => {
+            Ok(Some(String::from_utf8_lossy(bytes).into_owned()))
         }
-    } else {
-        Ok(None)
+        Some(_) => Err(DemuxError::UnexpectedDataType),
+        None => Ok(None),
+    }
+}
+
+/// Tries to find an element with the Element ID for a string that is not valid UTF-8 inside
+/// a list of children and returns its raw bytes.
+pub(crate) fn try_find_invalid_string(
+    fields: &[(ElementId, ElementData)],
+    element_id: ElementId,
+) -> Option<Vec<u8>> {
+    match fields.iter().find(|(id, _)| *id == element_id) {
+        Some((_, ElementData::InvalidString(bytes))) => Some(bytes.clone()),
+        _ => None,
     }
 }
 
+/// Expects to find an element with the Element ID for binary inside a list of children.
+pub(crate) fn find_binary<R: Read + Seek>(
+    r: &mut R,
+    fields: &[(ElementId, ElementData)],
+    element_id: ElementId,
+) -> Result<Vec<u8>> {
+    let value =
+        try_find_binary(r, fields, element_id)?.ok_or(DemuxError::ElementNotFound(element_id))?;
+    Ok(value)
+}
+
 /// Tries to find an element with the Element ID for binary inside a list of children.
 pub(crate) fn try_find_binary<R: Read + Seek>(
     r: &mut R,
@@ -408,10 +529,78 @@ pub(crate) fn try_find_date(
 }
 
 /// Parses the next Element at the current location of the reader and returns it's data.
-pub(crate) fn next_element<R: Read + Seek>(r: &mut R) -> Result<(ElementId, ElementData)> {
-    let (element_id, size) = parse_element_header(r, None)?;
+pub(crate) fn next_element<R: Read + Seek>(
+    r: &mut R,
+    limits: HeaderLimits,
+) -> Result<(ElementId, ElementData)> {
+    let (_, element_id, _, element_data) = next_raw_element(r, limits)?;
+    Ok((element_id, element_data))
+}
+
+/// Parses the next Element at the current location of the reader and returns it's raw
+/// Element ID, the size of it's data and it's data.
+pub(crate) fn next_raw_element<R: Read + Seek>(
+    r: &mut R,
+    limits: HeaderLimits,
+) -> Result<(u32, ElementId, u64, ElementData)> {
+    try_next_raw_element(r, limits)?
+        .ok_or_else(|| std::io::Error::from(ErrorKind::UnexpectedEof).into())
+}
+
+/// Parses the next Element like `next_raw_element()`, but returns `None` if the reader is at
+/// the end of the file before the Element starts.
+pub(crate) fn try_next_raw_element<R: Read + Seek>(
+    r: &mut R,
+    limits: HeaderLimits,
+) -> Result<Option<(u32, ElementId, u64, ElementData)>> {
+    let (id, element_id, size) = match try_next_element_header(r, limits)? {
+        Some(header) => header,
+        None => return Ok(None),
+    };
+    let element_data = parse_element_data(r, element_id, size)?;
+    Ok(Some((id, element_id, size, element_data)))
+}
+
+/// Parses the header of the next Element and returns its raw Element ID, Element ID and the
+/// size of its data. The reader is positioned at the start of the data afterwards.
+///
+/// Returns `None` if the reader is at the end of the file before the Element starts.
+pub(crate) fn try_next_element_header<R: Read + Seek>(
+    r: &mut R,
+    limits: HeaderLimits,
+) -> Result<Option<(u32, ElementId, u64)>> {
+    let id = match try_parse_variable_u32(r, limits.max_id_length)? {
+        Some(id) => id,
+        None => return Ok(None),
+    };
+    let size = parse_data_size(r, limits.max_size_length)?;
+    trace!(id, size, "parsed element header");
+    let element_id = id_to_element_id(id);
+
+    if size == u64::MAX && element_id_to_type(element_id) != ElementType::Master {
+        return Err(DemuxError::UnknownSizeNotAllowed(element_id));
+    }
 
-    let element_data = match element_id_to_type(element_id) {
+    Ok(Some((id, element_id, size)))
+}
+
+/// Parses the data of an Element, whose header was parsed by `try_next_element_header()`.
+pub(crate) fn parse_element_data<R: Read + Seek>(
+    r: &mut R,
+    element_id: ElementId,
+    size: u64,
+) -> Result<ElementData> {
+    parse_element_data_of_type(r, element_id_to_type(element_id), size)
+}
+
+/// Parses the data of an Element like `parse_element_data()`, but with the given type, for
+/// example the type of a registered element.
+pub(crate) fn parse_element_data_of_type<R: Read + Seek>(
+    r: &mut R,
+    element_type: ElementType,
+    size: u64,
+) -> Result<ElementData> {
+    let element_data = match element_type {
         ElementType::Master | ElementType::Binary | ElementType::Unknown => {
             let (offset, size) = parse_location(r, size)?;
             ElementData::Location { offset, size }
@@ -432,46 +621,62 @@ pub(crate) fn next_element<R: Read + Seek>(r: &mut R) -> Result<(ElementId, Elem
             let value = parse_date(r, size)?;
             ElementData::Date(value)
         }
-        ElementType::String => {
-            let value = parse_string(r, size)?;
-            ElementData::String(value)
-        }
+        ElementType::String => parse_string(r, size)?,
     };
 
-    Ok((element_id, element_data))
+    Ok(element_data)
 }
 
 /// Parses the next element from the given location inside the reader. Returns the Element ID and the size of the data.
 pub(crate) fn parse_element_header<R: Read + Seek>(
     r: &mut R,
     from: Option<u64>,
+    limits: HeaderLimits,
 ) -> Result<(ElementId, u64)> {
     if let Some(from) = from {
         r.seek(SeekFrom::Start(from))?;
     }
 
-    let id = parse_variable_u32(r)?;
-    let element_id = id_to_element_id(id);
+    let id = parse_variable_u32(r, limits.max_id_length)?;
+    let size = parse_data_size(r, limits.max_size_length)?;
+    trace!(id, size, "parsed element header");
+    Ok((id_to_element_id(id), size))
+}
 
-    let size = parse_variable_u64(r)?;
-    Ok((element_id, size))
+/// Parses a variable length EBML u32 (as used for the Element ID), which is encoded with at
+/// most `max_length` bytes.
+fn parse_variable_u32<R: Read>(r: &mut R, max_length: u64) -> Result<u32> {
+    try_parse_variable_u32(r, max_length)?
+        .ok_or_else(|| std::io::Error::from(ErrorKind::UnexpectedEof).into())
 }
 
-/// Parses a variable length EBML u32 (as used for the Element ID).
-fn parse_variable_u32<R: Read>(r: &mut R) -> Result<u32> {
+/// Parses a variable length EBML u32 like `parse_variable_u32()`, but returns `None` if the
+/// reader reaches the end of the file before the variable starts.
+fn try_parse_variable_u32<R: Read>(r: &mut R, max_length: u64) -> Result<Option<u32>> {
     loop {
         let mut bytes = [0u8];
-        r.read_exact(&mut bytes)?;
-        let element_id = match bytes[0] {
+        if !read_byte_or_eof(r, &mut bytes)? {
+            return Ok(None);
+        }
+        let left = match bytes[0] {
             // We keep reading bytes until we find a valid variable.
             byte if (byte & 0xF0) == 0x00 => continue,
-            byte if (byte & 0x80) == 0x80 => byte.into(),
-            byte if (byte & 0xC0) == 0x40 => parse_variable_u32_data(r, byte, 1)?,
-            byte if (byte & 0xE0) == 0x20 => parse_variable_u32_data(r, byte, 2)?,
-            byte if (byte & 0xF0) == 0x10 => parse_variable_u32_data(r, byte, 3)?,
-            _ => return Err(DemuxError::InvalidEbmlElementId),
+            byte if (byte & 0x80) == 0x80 => 0,
+            byte if (byte & 0xC0) == 0x40 => 1,
+            byte if (byte & 0xE0) == 0x20 => 2,
+            _ => 3,
         };
-        return Ok(element_id);
+        if u64::from(left) >= max_length {
+            return Err(DemuxError::InvalidEbmlElementId);
+        }
+        let element_id = match left {
+            0 => bytes[0].into(),
+            left => parse_variable_u32_data(r, bytes[0], left)?,
+        };
+        if is_reserved_element_id(element_id) {
+            return Err(DemuxError::InvalidEbmlElementId);
+        }
+        return Ok(Some(element_id));
     }
 }
 
@@ -500,21 +705,63 @@ pub(crate) fn parse_variable_i64<R: Read>(r: &mut R) -> Result<i64> {
 
 /// Parses a variable length EBML u64 (as used for the data size).
 pub(crate) fn parse_variable_u64<R: Read>(r: &mut R) -> Result<u64> {
+    let (value, _) = parse_variable_u64_with_width(r)?;
+    Ok(value)
+}
+
+/// Parses the data size of an element, which is encoded with at most `max_length` bytes.
+/// Returns `u64::MAX` if the size is unknown.
+fn parse_data_size<R: Read>(r: &mut R, max_length: u64) -> Result<u64> {
+    let (size, width) = parse_variable_u64_with_width(r)?;
+    if u64::from(width) > max_length {
+        return Err(DemuxError::InvalidEbmlDataSize);
+    }
+    // A VINT_DATA of all ones marks an unknown size, regardless of the VINT_WIDTH.
+    if size == (1u64 << (7 * width)) - 1 {
+        Ok(u64::MAX)
+    } else {
+        Ok(size)
+    }
+}
+
+fn parse_variable_u64_with_width<R: Read>(r: &mut R) -> Result<(u64, u8)> {
     let mut bytes = [0u8];
     r.read_exact(&mut bytes)?;
-    let size = match bytes[0] {
-        byte if byte == 0xFF => u64::MAX,
-        byte if (byte & 0x80) == 0x80 => (0x7F & byte).into(),
-        byte if (byte & 0xC0) == 0x40 => parse_variable_u64_data(r, 0x3F & byte, 1)?,
-        byte if (byte & 0xE0) == 0x20 => parse_variable_u64_data(r, 0x1F & byte, 2)?,
-        byte if (byte & 0xF0) == 0x10 => parse_variable_u64_data(r, 0x0F & byte, 3)?,
-        byte if (byte & 0xF8) == 0x08 => parse_variable_u64_data(r, 0x07 & byte, 4)?,
-        byte if (byte & 0xFC) == 0x04 => parse_variable_u64_data(r, 0x03 & byte, 5)?,
-        byte if (byte & 0xFE) == 0x02 => parse_variable_u64_data(r, 0x01 & byte, 6)?,
-        byte if byte == 0x01 => parse_variable_u64_data(r, 0, 7)?,
+    let left = match bytes[0] {
+        byte if (byte & 0x80) == 0x80 => return Ok(((0x7F & byte).into(), 1)),
+        byte if (byte & 0xC0) == 0x40 => 1,
+        byte if (byte & 0xE0) == 0x20 => 2,
+        byte if (byte & 0xF0) == 0x10 => 3,
+        byte if (byte & 0xF8) == 0x08 => 4,
+        byte if (byte & 0xFC) == 0x04 => 5,
+        byte if (byte & 0xFE) == 0x02 => 6,
+        0x01 => 7,
         _ => return Err(DemuxError::InvalidEbmlDataSize),
     };
-    Ok(size)
+    let marker = 0x80 >> left;
+    let value = parse_variable_u64_data(r, bytes[0] & (marker - 1), left)?;
+    Ok((value, left + 1))
+}
+
+/// Returns true if the VINT_DATA of the Element ID is all ones, which is reserved by
+/// the EBML specification. All zeros are reserved too, but Matroska uses 0x80 for
+/// `ChapterDisplay`.
+fn is_reserved_element_id(id: u32) -> bool {
+    let width = element_id_length(id);
+    let data_mask = (1u64 << (7 * width)) - 1;
+    u64::from(id) & data_mask == data_mask
+}
+
+/// Reads a single byte. Returns `false` if the reader is at the end of the file.
+fn read_byte_or_eof<R: Read>(r: &mut R, byte: &mut [u8; 1]) -> Result<bool> {
+    loop {
+        match r.read(byte) {
+            Ok(0) => return Ok(false),
+            Ok(_) => return Ok(true),
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err.into()),
+        }
+    }
 }
 
 fn parse_variable_u32_data<R: Read>(r: &mut R, byte: u8, left: u8) -> Result<u32> {
@@ -599,14 +846,24 @@ fn parse_date<R: Read>(r: &mut R, size: u64) -> Result<i64> {
     Ok(i64::from_be_bytes(bytes) >> shift)
 }
 
-fn parse_string<R: Read>(r: &mut R, size: u64) -> Result<String> {
+/// Parses a string. Trailing null bytes, which the spec allows as padding, are removed.
+fn parse_string<R: Read>(r: &mut R, size: u64) -> Result<ElementData> {
     if size == 0 {
-        return Ok(String::from(""));
+        return Ok(ElementData::String(String::from("")));
     }
     let size: usize = size.try_into()?;
     let mut bytes = vec![0u8; size];
     r.read_exact(&mut bytes[0..size])?;
-    Ok(String::from_utf8(bytes)?)
+
+    let length = bytes
+        .iter()
+        .rposition(|&byte| byte != 0)
+        .map_or(0, |i| i + 1);
+    bytes.truncate(length);
+    match String::from_utf8(bytes) {
+        Ok(value) => Ok(ElementData::String(value)),
+        Err(err) => Ok(ElementData::InvalidString(err.into_bytes())),
+    }
 }
 
 #[cfg(test)]
@@ -621,7 +878,7 @@ mod tests {
     fn test_parse_master_element() -> Result<()> {
         let data: Vec<u8> = vec![0x1A, 0x45, 0xDF, 0xA3, 0xA2];
         let mut cursor = Cursor::new(data);
-        let (element_id, element_data) = next_element(&mut cursor)?;
+        let (element_id, element_data) = next_element(&mut cursor, HeaderLimits::default())?;
         assert_eq!(element_id, ElementId::Ebml);
         assert_eq!(
             element_data,
@@ -638,7 +895,7 @@ mod tests {
     fn test_parse_unsigned() -> Result<()> {
         let data: Vec<u8> = vec![0x42, 0x86, 0x81, 0x01];
         let mut cursor = Cursor::new(data);
-        let (element_id, element_data) = next_element(&mut cursor)?;
+        let (element_id, element_data) = next_element(&mut cursor, HeaderLimits::default())?;
         assert_eq!(element_id, ElementId::EbmlVersion);
         assert_eq!(element_data, ElementData::Unsigned(1));
 
@@ -649,7 +906,7 @@ mod tests {
     fn test_parse_signed() -> Result<()> {
         let data: Vec<u8> = vec![0xFB, 0x82, 0xFF, 0xFB];
         let mut cursor = Cursor::new(data);
-        let (element_id, element_data) = next_element(&mut cursor)?;
+        let (element_id, element_data) = next_element(&mut cursor, HeaderLimits::default())?;
         assert_eq!(element_id, ElementId::ReferenceBlock);
         assert_eq!(element_data, ElementData::Signed(-5));
 
@@ -660,7 +917,7 @@ mod tests {
     fn test_parse_date() -> Result<()> {
         let data: Vec<u8> = vec![0x44, 0x61, 0x84, 0xFF, 0xB3, 0xB4, 0xC0];
         let mut cursor = Cursor::new(data);
-        let (element_id, element_data) = next_element(&mut cursor)?;
+        let (element_id, element_data) = next_element(&mut cursor, HeaderLimits::default())?;
         assert_eq!(element_id, ElementId::DateUtc);
         assert_eq!(element_data, ElementData::Date(-5_000_000));
 
@@ -671,7 +928,7 @@ mod tests {
     fn test_parse_float_32() -> Result<()> {
         let data: Vec<u8> = vec![0x44, 0x89, 0x84, 0x43, 0x1C, 0x20, 0x07];
         let mut cursor = Cursor::new(data);
-        let (element_id, element_data) = next_element(&mut cursor)?;
+        let (element_id, element_data) = next_element(&mut cursor, HeaderLimits::default())?;
         assert_eq!(element_id, ElementId::Duration);
         if let ElementData::Float(x) = element_data {
             assert!((x - 156.1251).abs() < 0.00001)
@@ -688,7 +945,7 @@ mod tests {
             0x44, 0x89, 0x88, 0x40, 0xA9, 0xE0, 0x43, 0x30, 0xBC, 0x60, 0x6E,
         ];
         let mut cursor = Cursor::new(data);
-        let (element_id, element_data) = next_element(&mut cursor)?;
+        let (element_id, element_data) = next_element(&mut cursor, HeaderLimits::default())?;
         assert_eq!(element_id, ElementId::Duration);
         if let ElementData::Float(x) = element_data {
             assert!((x - 3312.1312312).abs() < 0.00001)
@@ -705,7 +962,7 @@ mod tests {
             0x42, 0x82, 0x88, 0x6D, 0x61, 0x74, 0x72, 0x6F, 0x73, 0x6B, 0x61,
         ];
         let mut cursor = Cursor::new(data);
-        let (element_id, element_data) = next_element(&mut cursor)?;
+        let (element_id, element_data) = next_element(&mut cursor, HeaderLimits::default())?;
         assert_eq!(element_id, ElementId::DocType);
         assert_eq!(element_data, ElementData::String("matroska".to_owned()));
 
@@ -719,7 +976,7 @@ mod tests {
             0x90, 0xE3, 0x81, 0x8A, 0xE3, 0x81, 0x8B, 0xE3, 0x82, 0x86,
         ];
         let mut cursor = Cursor::new(data);
-        let (element_id, element_data) = next_element(&mut cursor)?;
+        let (element_id, element_data) = next_element(&mut cursor, HeaderLimits::default())?;
         assert_eq!(element_id, ElementId::MuxingApp);
         assert_eq!(
             element_data,
@@ -736,7 +993,7 @@ mod tests {
             0x90, 0xE3, 0x81, 0x8A, 0xE3, 0x81, 0x8B, 0xE3, 0x82, 0x86,
         ];
         let mut cursor = Cursor::new(data);
-        let (element_id, element_data) = next_element(&mut cursor)?;
+        let (element_id, element_data) = next_element(&mut cursor, HeaderLimits::default())?;
         assert_eq!(element_id, ElementId::CodecPrivate);
         assert_eq!(
             element_data,
@@ -753,7 +1010,7 @@ mod tests {
     fn test_parse_default_unsigned() -> Result<()> {
         let data: Vec<u8> = vec![0x42, 0x86, 0x80];
         let mut cursor = Cursor::new(data);
-        let (element_id, element_data) = next_element(&mut cursor)?;
+        let (element_id, element_data) = next_element(&mut cursor, HeaderLimits::default())?;
         assert_eq!(element_id, ElementId::EbmlVersion);
         assert_eq!(element_data, ElementData::Unsigned(0));
 
@@ -764,7 +1021,7 @@ mod tests {
     fn test_parse_default_signed() -> Result<()> {
         let data: Vec<u8> = vec![0xFB, 0x80];
         let mut cursor = Cursor::new(data);
-        let (element_id, element_data) = next_element(&mut cursor)?;
+        let (element_id, element_data) = next_element(&mut cursor, HeaderLimits::default())?;
         assert_eq!(element_id, ElementId::ReferenceBlock);
         assert_eq!(element_data, ElementData::Signed(0));
 
@@ -775,7 +1032,7 @@ mod tests {
     fn test_parse_default_date() -> Result<()> {
         let data: Vec<u8> = vec![0x44, 0x61, 0x80];
         let mut cursor = Cursor::new(data);
-        let (element_id, element_data) = next_element(&mut cursor)?;
+        let (element_id, element_data) = next_element(&mut cursor, HeaderLimits::default())?;
         assert_eq!(element_id, ElementId::DateUtc);
         assert_eq!(element_data, ElementData::Date(0));
 
@@ -786,7 +1043,7 @@ mod tests {
     fn test_parse_default_float() -> Result<()> {
         let data: Vec<u8> = vec![0x44, 0x89, 0x80];
         let mut cursor = Cursor::new(data);
-        let (element_id, element_data) = next_element(&mut cursor)?;
+        let (element_id, element_data) = next_element(&mut cursor, HeaderLimits::default())?;
         assert_eq!(element_id, ElementId::Duration);
         if let ElementData::Float(x) = element_data {
             assert!((x).abs() < 0.00001)
@@ -801,21 +1058,105 @@ mod tests {
     fn test_parse_default_ascii_string() -> Result<()> {
         let data: Vec<u8> = vec![0x42, 0x82, 0x80];
         let mut cursor = Cursor::new(data);
-        let (element_id, element_data) = next_element(&mut cursor)?;
+        let (element_id, element_data) = next_element(&mut cursor, HeaderLimits::default())?;
         assert_eq!(element_id, ElementId::DocType);
         assert_eq!(element_data, ElementData::String("".to_owned()));
 
         Ok(())
     }
 
+    #[test]
+    fn test_parse_padded_and_invalid_string() -> Result<()> {
+        let data: Vec<u8> = vec![0x4D, 0x80, 0x84, b'a', b'b', 0x00, 0x00];
+        let (_, element_data) = next_element(&mut Cursor::new(data), HeaderLimits::default())?;
+        assert_eq!(element_data, ElementData::String("ab".to_owned()));
+
+        let data: Vec<u8> = vec![0x4D, 0x80, 0x83, b'a', 0xFF, 0x00];
+        let (_, element_data) = next_element(&mut Cursor::new(data), HeaderLimits::default())?;
+        assert_eq!(element_data, ElementData::InvalidString(vec![b'a', 0xFF]));
+
+        let fields = [(ElementId::MuxingApp, element_data)];
+        assert!(check_strings(&fields, false).is_err());
+        check_strings(&fields, true)?;
+        let value = try_find_string(&fields, ElementId::MuxingApp)?;
+        assert_eq!(value.as_deref(), Some("a\u{FFFD}"));
+
+        Ok(())
+    }
+
     #[test]
     fn test_parse_default_utf8_string() -> Result<()> {
         let data: Vec<u8> = vec![0x4D, 0x80, 0x80];
         let mut cursor = Cursor::new(data);
-        let (element_id, element_data) = next_element(&mut cursor)?;
+        let (element_id, element_data) = next_element(&mut cursor, HeaderLimits::default())?;
         assert_eq!(element_id, ElementId::MuxingApp);
         assert_eq!(element_data, ElementData::String("".to_owned()));
 
         Ok(())
     }
+
+    #[test]
+    fn test_parse_unknown_size() -> Result<()> {
+        // An unknown size can be encoded with any VINT_WIDTH.
+        let data: Vec<u8> = vec![0x1F, 0x43, 0xB6, 0x75, 0x7F, 0xFF];
+        let mut cursor = Cursor::new(data);
+        let (element_id, element_data) = next_element(&mut cursor, HeaderLimits::default())?;
+        assert_eq!(element_id, ElementId::Cluster);
+        assert_eq!(
+            element_data,
+            ElementData::Location {
+                offset: 6,
+                size: u64::MAX,
+            }
+        );
+
+        // Only master elements can have an unknown size.
+        let data: Vec<u8> = vec![0x42, 0x86, 0xFF, 0x01];
+        let mut cursor = Cursor::new(data);
+        assert!(matches!(
+            next_element(&mut cursor, HeaderLimits::default()),
+            Err(DemuxError::UnknownSizeNotAllowed(ElementId::EbmlVersion))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_reserved_element_id() {
+        let data: Vec<u8> = vec![0x7F, 0xFF, 0x81, 0x01];
+        let mut cursor = Cursor::new(data);
+        assert!(matches!(
+            next_element(&mut cursor, HeaderLimits::default()),
+            Err(DemuxError::InvalidEbmlElementId)
+        ));
+    }
+
+    #[test]
+    fn test_parse_header_limits() -> Result<()> {
+        let limits = HeaderLimits {
+            max_id_length: 2,
+            max_size_length: 4,
+        };
+        // EBMLVersion with a four byte data size.
+        let data: Vec<u8> = vec![0x42, 0x86, 0x10, 0x00, 0x00, 0x01, 0x01];
+        let (element_id, element_data) = next_element(&mut Cursor::new(data), limits)?;
+        assert_eq!(element_id, ElementId::EbmlVersion);
+        assert_eq!(element_data, ElementData::Unsigned(1));
+
+        // EBMLVersion with a five byte data size.
+        let data: Vec<u8> = vec![0x42, 0x86, 0x08, 0x00, 0x00, 0x00, 0x01, 0x01];
+        assert!(matches!(
+            next_element(&mut Cursor::new(data), limits),
+            Err(DemuxError::InvalidEbmlDataSize)
+        ));
+
+        // The four byte Element ID of the Tracks.
+        let data: Vec<u8> = vec![0x16, 0x54, 0xAE, 0x6B, 0x80];
+        assert!(matches!(
+            next_element(&mut Cursor::new(data), limits),
+            Err(DemuxError::InvalidEbmlElementId)
+        ));
+
+        Ok(())
+    }
 }
@@ -0,0 +1,678 @@
+//! In-place editing of the metadata of a Matroska file.
+
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    io::{ErrorKind, Read, Seek, SeekFrom, Write},
+};
+
+use crate::{
+    ebml::{try_next_element_header, HeaderLimits},
+    element_id::element_id_to_id,
+    writer::{
+        parse_children, read_element, size_length, write_id, write_size, write_void, RawElement,
+        CRC32_ID, FLAG_DEFAULT_ID, FLAG_FORCED_ID, INFO_ID, LANGUAGE_BCP47_ID, LANGUAGE_ID,
+        NAME_ID, SEEK_HEAD_ID, SEEK_ID, SEEK_ID_ID, SEEK_POSITION_ID, SIMPLE_TAG_ID, TAGS_ID,
+        TAG_ATTACHMENT_UID_ID, TAG_BINARY_ID, TAG_CHAPTER_UID_ID, TAG_EDITION_UID_ID, TAG_ID,
+        TAG_NAME_ID, TAG_STRING_ID, TAG_TRACK_UID_ID, TARGETS_ID, TARGET_TYPE_VALUE_ID, TITLE_ID,
+        TRACKS_ID, TRACK_ENTRY_ID, TRACK_NUMBER_ID, VOID_ID,
+    },
+    DemuxError, ElementId, MatroskaIndex, Result,
+};
+
+/// Edits the metadata of a Matroska file in place, similar to `mkvpropedit`.
+///
+/// Edits are queued and written by `write()`. An edited element is written over its old
+/// place, using directly following `Void` elements as free space. If it doesn't fit, it's
+/// moved to the end of the segment, which requires the segment to end at the end of the
+/// file and to start with a SeekHead, which is updated. Clusters are never touched.
+///
+/// Only the `Tags` element referenced by the SeekHead is edited. Further `Tags` elements
+/// are left as they are.
+#[derive(Debug)]
+pub struct MatroskaEditor<F> {
+    file: F,
+    index: MatroskaIndex,
+    title: Option<Option<String>>,
+    tracks: HashMap<u64, TrackEdit>,
+    tags: Vec<TagEdit>,
+}
+
+#[derive(Debug, Default)]
+struct TrackEdit {
+    name: Option<Option<String>>,
+    language: Option<String>,
+    flag_default: Option<bool>,
+    flag_forced: Option<bool>,
+}
+
+#[derive(Debug)]
+struct TagEdit {
+    track_uid: Option<u64>,
+    name: String,
+    value: Option<String>,
+}
+
+impl<F: Read + Write + Seek> MatroskaEditor<F> {
+    /// Opens the Matroska file for editing. The file must be positioned at the EBML header.
+    pub fn open(mut file: F) -> Result<Self> {
+        let index = MatroskaIndex::parse(&mut file)?;
+        Ok(Self {
+            file,
+            index,
+            title: None,
+            tracks: HashMap::new(),
+            tags: Vec::new(),
+        })
+    }
+
+    /// The metadata of the file. Updated by `write()`.
+    pub fn index(&self) -> &MatroskaIndex {
+        &self.index
+    }
+
+    /// Sets the title of the segment. `None` removes the title.
+    pub fn set_title(&mut self, title: Option<&str>) {
+        self.title = Some(title.map(str::to_owned));
+    }
+
+    /// Sets the name of a track. `None` removes the name.
+    pub fn set_track_name(&mut self, track: u64, name: Option<&str>) -> Result<()> {
+        self.track_edit(track)?.name = Some(name.map(str::to_owned));
+        Ok(())
+    }
+
+    /// Sets the language of a track in the Matroska form of ISO 639-2. A `LanguageBCP47`
+    /// of the track is removed, since it would override the language.
+    pub fn set_track_language(&mut self, track: u64, language: &str) -> Result<()> {
+        self.track_edit(track)?.language = Some(language.to_owned());
+        Ok(())
+    }
+
+    /// Sets the default flag of a track.
+    pub fn set_track_flag_default(&mut self, track: u64, flag_default: bool) -> Result<()> {
+        self.track_edit(track)?.flag_default = Some(flag_default);
+        Ok(())
+    }
+
+    /// Sets the forced flag of a track.
+    pub fn set_track_flag_forced(&mut self, track: u64, flag_forced: bool) -> Result<()> {
+        self.track_edit(track)?.flag_forced = Some(flag_forced);
+        Ok(())
+    }
+
+    /// Sets the string value of a simple tag. The tag targets the given track or, if no
+    /// track is given, the whole segment. `None` removes the simple tag.
+    ///
+    /// The first `Tag` with matching targets is edited. A new `Tag` is added if there is
+    /// none.
+    pub fn set_tag(&mut self, track: Option<u64>, name: &str, value: Option<&str>) -> Result<()> {
+        let track_uid = match track {
+            Some(track) => Some(
+                self.index
+                    .track_by_number(track)
+                    .ok_or(DemuxError::TrackNotFound(track))?
+                    .track_uid()
+                    .get(),
+            ),
+            None => None,
+        };
+        self.tags.push(TagEdit {
+            track_uid,
+            name: name.to_owned(),
+            value: value.map(str::to_owned),
+        });
+        Ok(())
+    }
+
+    fn track_edit(&mut self, track: u64) -> Result<&mut TrackEdit> {
+        if self.index.track_by_number(track).is_none() {
+            return Err(DemuxError::TrackNotFound(track));
+        }
+        Ok(self.tracks.entry(track).or_default())
+    }
+
+    /// Writes the queued edits into the file and parses the metadata again.
+    ///
+    /// If an error occurs, the edits that were already written stay in the file.
+    pub fn write(&mut self) -> Result<()> {
+        if let Some(title) = self.title.take() {
+            self.write_info(title)?;
+        }
+        if !self.tracks.is_empty() {
+            let edits = std::mem::take(&mut self.tracks);
+            self.write_tracks(edits)?;
+        }
+        if !self.tags.is_empty() {
+            let edits = std::mem::take(&mut self.tags);
+            self.write_tags(edits)?;
+        }
+        self.file.flush()?;
+
+        let settings = self.index.settings.clone();
+        self.file.seek(SeekFrom::Start(self.index.header_offset))?;
+        self.index = MatroskaIndex::parse_with_settings(&mut self.file, &settings)?;
+        Ok(())
+    }
+
+    /// Returns the underlying file.
+    pub fn into_inner(self) -> F {
+        self.file
+    }
+
+    fn write_info(&mut self, title: Option<String>) -> Result<()> {
+        let offset = self.element_offset(ElementId::Info)?;
+        let (info, _) = self.read_element(offset)?;
+        let mut children = parse_children(&info.data)?;
+        children.retain(|child| child.id != CRC32_ID);
+        set_child(
+            &mut children,
+            TITLE_ID,
+            title.map(|title| RawElement::string(TITLE_ID, &title)),
+        );
+        let info = RawElement::master(INFO_ID, &children)?;
+        self.replace_element(ElementId::Info, Some(offset), Some(info))
+    }
+
+    fn write_tracks(&mut self, mut edits: HashMap<u64, TrackEdit>) -> Result<()> {
+        let offset = self.element_offset(ElementId::Tracks)?;
+        let (tracks, _) = self.read_element(offset)?;
+        let mut entries = parse_children(&tracks.data)?;
+        entries.retain(|entry| entry.id != CRC32_ID);
+
+        for entry in entries
+            .iter_mut()
+            .filter(|entry| entry.id == TRACK_ENTRY_ID)
+        {
+            let mut children = parse_children(&entry.data)?;
+            let edit = match children
+                .iter()
+                .find(|child| child.id == TRACK_NUMBER_ID)
+                .and_then(RawElement::as_unsigned)
+                .and_then(|number| edits.remove(&number))
+            {
+                Some(edit) => edit,
+                None => continue,
+            };
+            children.retain(|child| child.id != CRC32_ID);
+            edit.apply(&mut children);
+            *entry = RawElement::master(TRACK_ENTRY_ID, &children)?;
+        }
+
+        let tracks = RawElement::master(TRACKS_ID, &entries)?;
+        self.replace_element(ElementId::Tracks, Some(offset), Some(tracks))
+    }
+
+    fn write_tags(&mut self, edits: Vec<TagEdit>) -> Result<()> {
+        let offset = self.index.seek_head.get(&ElementId::Tags).copied();
+        let mut tags = match offset {
+            Some(offset) => parse_children(&self.read_element(offset)?.0.data)?,
+            None => Vec::new(),
+        };
+        tags.retain(|tag| tag.id != CRC32_ID);
+        for edit in &edits {
+            edit.apply(&mut tags)?;
+        }
+
+        // A `Tags` element must contain at least one `Tag`.
+        let tags = match tags.is_empty() {
+            true => None,
+            false => Some(RawElement::master(TAGS_ID, &tags)?),
+        };
+        self.replace_element(ElementId::Tags, offset, tags)
+    }
+
+    fn element_offset(&self, element_id: ElementId) -> Result<u64> {
+        self.index
+            .seek_head
+            .get(&element_id)
+            .copied()
+            .ok_or(DemuxError::ElementNotFound(element_id))
+    }
+
+    fn read_element(&mut self, offset: u64) -> Result<(RawElement, u64)> {
+        read_element(&mut self.file, offset)
+    }
+
+    /// Returns the end of the element at the given offset, including all `Void` elements
+    /// that directly follow it.
+    fn available_end(&mut self, offset: u64) -> Result<u64> {
+        let limit = match self.index.segment_data_size {
+            Some(size) => self.index.segment_data_offset + size,
+            None => self.file.seek(SeekFrom::End(0))?,
+        };
+
+        let mut end = self.element_end(offset)?;
+        while end < limit {
+            self.file.seek(SeekFrom::Start(end))?;
+            let mut byte = [0_u8];
+            if self.file.read(&mut byte)? == 0 || u32::from(byte[0]) != VOID_ID {
+                break;
+            }
+            let void_end = self.element_end(end)?;
+            if void_end > limit {
+                break;
+            }
+            end = void_end;
+        }
+        Ok(end)
+    }
+
+    /// Returns the end of the element at the given offset without reading its data.
+    fn element_end(&mut self, offset: u64) -> Result<u64> {
+        self.file.seek(SeekFrom::Start(offset))?;
+        let (_, element_id, size) =
+            try_next_element_header(&mut self.file, HeaderLimits::default())?
+                .ok_or_else(|| DemuxError::IoError(ErrorKind::UnexpectedEof.into()))?;
+        if size == u64::MAX {
+            return Err(DemuxError::UnknownSizeNotAllowed(element_id));
+        }
+        self.file
+            .stream_position()?
+            .checked_add(size)
+            .ok_or(DemuxError::InvalidEbmlDataSize)
+    }
+
+    /// Writes the element over its old place or moves it to the end of the segment.
+    /// `None` removes the element.
+    fn replace_element(
+        &mut self,
+        element_id: ElementId,
+        offset: Option<u64>,
+        element: Option<RawElement>,
+    ) -> Result<()> {
+        let old = match offset {
+            Some(offset) => Some((offset, self.available_end(offset)?)),
+            None => None,
+        };
+
+        let mut writes = Vec::new();
+        let in_place = match old {
+            Some((offset, end)) => fit(element.as_ref(), end - offset)?,
+            None => None,
+        };
+        match (in_place, element, old) {
+            (Some(data), element, Some((offset, _))) => {
+                // The SeekHead is updated first, so that it never points at a `Void`.
+                if element.is_none() {
+                    writes.extend(self.update_seek_head(element_id, Some(offset), None)?);
+                }
+                writes.push((offset, data));
+            }
+            (_, Some(element), old) => {
+                let file_end = self.file.seek(SeekFrom::End(0))?;
+                let segment_end = self
+                    .index
+                    .segment_data_size
+                    .map(|size| self.index.segment_data_offset + size);
+                if matches!(segment_end, Some(end) if end != file_end) {
+                    return Err(DemuxError::NotEnoughSpace(element_id));
+                }
+
+                let mut data = Vec::new();
+                element.encode(&mut data)?;
+                let length = u64::try_from(data.len())?;
+                writes.push((file_end, data));
+
+                if let Some(size) = self.index.segment_data_size {
+                    writes.push(self.segment_size_field(size + length)?);
+                }
+                // The SeekHead is updated before the old element is voided, so that it
+                // never points at a `Void`.
+                writes.extend(self.update_seek_head(
+                    element_id,
+                    old.map(|(offset, _)| offset),
+                    Some(file_end),
+                )?);
+                if let Some((offset, end)) = old {
+                    let mut void = Vec::new();
+                    write_void(&mut void, end - offset)?;
+                    writes.push((offset, void));
+                }
+
+                self.index.segment_data_size =
+                    self.index.segment_data_size.map(|size| size + length);
+            }
+            _ => return Ok(()),
+        }
+
+        for (position, data) in writes {
+            self.file.seek(SeekFrom::Start(position))?;
+            self.file.write_all(&data)?;
+        }
+        Ok(())
+    }
+
+    /// Encodes the data size of the segment with the width of the existing size.
+    fn segment_size_field(&mut self, size: u64) -> Result<(u64, Vec<u8>)> {
+        // The ID of the segment is four bytes long.
+        let position = self.index.segment_header_offset + 4;
+        let length = usize::try_from(self.index.segment_data_offset - position)?;
+
+        let mut data = Vec::new();
+        write_size(&mut data, size, length)
+            .map_err(|_| DemuxError::NotEnoughSpace(ElementId::Segment))?;
+        Ok((position, data))
+    }
+
+    /// Returns the offset of the SeekHead, if the segment starts with one.
+    fn seek_head_offset(&mut self) -> Result<Option<u64>> {
+        let mut position = self.index.segment_data_offset;
+        loop {
+            let (element, end) = self.read_element(position)?;
+            match element.id {
+                SEEK_HEAD_ID => return Ok(Some(position)),
+                CRC32_ID => position = end,
+                _ => return Ok(None),
+            }
+        }
+    }
+
+    /// Replaces the SeekHead entry of the element at the old position with the new
+    /// position. `None` as the old position adds an entry and `None` as the new position
+    /// removes the entry. Returns the new SeekHead and its offset.
+    ///
+    /// Fails if the segment doesn't start with a SeekHead, since the moved element couldn't
+    /// be found without scanning the whole file otherwise.
+    fn update_seek_head(
+        &mut self,
+        element_id: ElementId,
+        old: Option<u64>,
+        new: Option<u64>,
+    ) -> Result<Option<(u64, Vec<u8>)>> {
+        let offset = self
+            .seek_head_offset()?
+            .ok_or(DemuxError::ElementNotFound(ElementId::SeekHead))?;
+        let segment_data_offset = self.index.segment_data_offset;
+        let mut seek_id = Vec::new();
+        write_id(&mut seek_id, element_id_to_id(element_id));
+
+        let (seek_head, _) = self.read_element(offset)?;
+        let mut seeks = parse_children(&seek_head.data)?;
+        seeks.retain(|seek| seek.id != CRC32_ID);
+
+        let mut found = None;
+        for (i, seek) in seeks.iter().enumerate() {
+            if seek.id != SEEK_ID {
+                continue;
+            }
+            let children = parse_children(&seek.data)?;
+            let matches_id = children
+                .iter()
+                .any(|child| child.id == SEEK_ID_ID && child.data == seek_id);
+            let position = children
+                .iter()
+                .find(|child| child.id == SEEK_POSITION_ID)
+                .and_then(RawElement::as_unsigned);
+            if matches_id && position.is_some() && position == old.map(|o| o - segment_data_offset)
+            {
+                found = Some(i);
+                break;
+            }
+        }
+
+        let seek = |position: u64| {
+            RawElement::master(
+                SEEK_ID,
+                &[
+                    RawElement {
+                        id: SEEK_ID_ID,
+                        data: seek_id.clone(),
+                    },
+                    RawElement::unsigned(SEEK_POSITION_ID, position - segment_data_offset),
+                ],
+            )
+        };
+        match (found, new) {
+            (Some(i), Some(new)) => seeks[i] = seek(new)?,
+            (Some(i), None) => {
+                seeks.remove(i);
+            }
+            (None, Some(new)) => seeks.push(seek(new)?),
+            (None, None) => return Ok(None),
+        }
+
+        let seek_head = RawElement::master(SEEK_HEAD_ID, &seeks)?;
+        let end = self.available_end(offset)?;
+        match fit(Some(&seek_head), end - offset)? {
+            Some(data) => Ok(Some((offset, data))),
+            None => Err(DemuxError::NotEnoughSpace(ElementId::SeekHead)),
+        }
+    }
+}
+
+impl TrackEdit {
+    fn apply(self, children: &mut Vec<RawElement>) {
+        if let Some(name) = self.name {
+            set_child(
+                children,
+                NAME_ID,
+                name.map(|name| RawElement::string(NAME_ID, &name)),
+            );
+        }
+        if let Some(language) = self.language {
+            set_child(
+                children,
+                LANGUAGE_ID,
+                Some(RawElement::string(LANGUAGE_ID, &language)),
+            );
+            set_child(children, LANGUAGE_BCP47_ID, None);
+        }
+        if let Some(flag_default) = self.flag_default {
+            set_child(
+                children,
+                FLAG_DEFAULT_ID,
+                Some(RawElement::unsigned(
+                    FLAG_DEFAULT_ID,
+                    u64::from(flag_default),
+                )),
+            );
+        }
+        if let Some(flag_forced) = self.flag_forced {
+            set_child(
+                children,
+                FLAG_FORCED_ID,
+                Some(RawElement::unsigned(FLAG_FORCED_ID, u64::from(flag_forced))),
+            );
+        }
+    }
+}
+
+impl TagEdit {
+    fn apply(&self, tags: &mut Vec<RawElement>) -> Result<()> {
+        let mut position = None;
+        for (i, tag) in tags.iter().enumerate() {
+            if tag.id == TAG_ID && self.matches_targets(tag)? {
+                position = Some(i);
+                break;
+            }
+        }
+
+        let children = match position {
+            Some(position) => parse_children(&tags[position].data)?,
+            None => {
+                let targets = match self.track_uid {
+                    Some(track_uid) => vec![RawElement::unsigned(TAG_TRACK_UID_ID, track_uid)],
+                    None => Vec::new(),
+                };
+                vec![RawElement::master(TARGETS_ID, &targets)?]
+            }
+        };
+
+        let mut found = false;
+        let mut edited = Vec::with_capacity(children.len() + 1);
+        for child in children {
+            if child.id == CRC32_ID {
+                continue;
+            }
+            if child.id != SIMPLE_TAG_ID || !self.matches_name(&child)? {
+                edited.push(child);
+                continue;
+            }
+            // Further simple tags with the same name are removed.
+            let value = match (&self.value, found) {
+                (Some(value), false) => value,
+                _ => continue,
+            };
+            found = true;
+            let mut simple_tag = parse_children(&child.data)?;
+            simple_tag.retain(|c| c.id != CRC32_ID && c.id != TAG_BINARY_ID);
+            set_child(
+                &mut simple_tag,
+                TAG_STRING_ID,
+                Some(RawElement::string(TAG_STRING_ID, value)),
+            );
+            edited.push(RawElement::master(SIMPLE_TAG_ID, &simple_tag)?);
+        }
+        if let (Some(value), false) = (&self.value, found) {
+            edited.push(RawElement::master(
+                SIMPLE_TAG_ID,
+                &[
+                    RawElement::string(TAG_NAME_ID, &self.name),
+                    RawElement::string(TAG_STRING_ID, value),
+                ],
+            )?);
+        }
+
+        // A `Tag` must contain at least one `SimpleTag`.
+        let has_simple_tags = edited.iter().any(|child| child.id == SIMPLE_TAG_ID);
+        match (position, has_simple_tags) {
+            (Some(position), true) => tags[position] = RawElement::master(TAG_ID, &edited)?,
+            (Some(position), false) => {
+                tags.remove(position);
+            }
+            (None, true) => tags.push(RawElement::master(TAG_ID, &edited)?),
+            (None, false) => {}
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if the tag targets only the track or, without a track, the whole
+    /// segment.
+    fn matches_targets(&self, tag: &RawElement) -> Result<bool> {
+        let targets = match parse_children(&tag.data)?
+            .into_iter()
+            .find(|child| child.id == TARGETS_ID)
+        {
+            Some(targets) => parse_children(&targets.data)?,
+            None => Vec::new(),
+        };
+
+        let mut track_uids = Vec::new();
+        for target in targets {
+            let value = target.as_unsigned();
+            match target.id {
+                TARGET_TYPE_VALUE_ID if value != Some(50) => return Ok(false),
+                TAG_EDITION_UID_ID | TAG_CHAPTER_UID_ID | TAG_ATTACHMENT_UID_ID
+                    if value != Some(0) =>
+                {
+                    return Ok(false)
+                }
+                TAG_TRACK_UID_ID if value != Some(0) => track_uids.push(value),
+                _ => {}
+            }
+        }
+
+        Ok(match self.track_uid {
+            Some(track_uid) => track_uids == [Some(track_uid)],
+            None => track_uids.is_empty(),
+        })
+    }
+
+    fn matches_name(&self, simple_tag: &RawElement) -> Result<bool> {
+        Ok(parse_children(&simple_tag.data)?
+            .iter()
+            .any(|child| child.id == TAG_NAME_ID && child.data == self.name.as_bytes()))
+    }
+}
+
+/// Replaces the first child with the given ID and removes all others. The child is added
+/// if there is none. `None` removes all children with the ID.
+fn set_child(children: &mut Vec<RawElement>, id: u32, child: Option<RawElement>) {
+    let mut child = child;
+    let mut i = 0;
+    while i < children.len() {
+        if children[i].id != id {
+            i += 1;
+            continue;
+        }
+        match child.take() {
+            Some(child) => {
+                children[i] = child;
+                i += 1;
+            }
+            None => {
+                children.remove(i);
+            }
+        }
+    }
+    if let Some(child) = child {
+        children.push(child);
+    }
+}
+
+/// Encodes the element so that it occupies exactly the available space, filling the rest
+/// with a `Void` element. Returns `None` if it doesn't fit.
+fn fit(element: Option<&RawElement>, available: u64) -> Result<Option<Vec<u8>>> {
+    let mut data = Vec::new();
+    if let Some(element) = element {
+        element.encode(&mut data)?;
+    }
+    let size = u64::try_from(data.len())?;
+
+    if size == available {
+        return Ok(Some(data));
+    }
+    if size + 2 <= available {
+        write_void(&mut data, available - size)?;
+        return Ok(Some(data));
+    }
+    // A single byte can't be filled with a `Void` element, so the data size is encoded
+    // with one more byte instead.
+    if let Some(element) = element {
+        let length = size_length(u64::try_from(element.data.len())?) + 1;
+        if size + 1 == available && length <= 8 {
+            let mut data = Vec::new();
+            element.encode_with_size_length(&mut data, length)?;
+            return Ok(Some(data));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit() -> Result<()> {
+        let element = RawElement::string(TITLE_ID, "Title");
+        assert_eq!(fit(Some(&element), 8)?.map(|data| data.len()), Some(8));
+        assert_eq!(fit(Some(&element), 9)?.map(|data| data.len()), Some(9));
+        assert_eq!(fit(Some(&element), 10)?.map(|data| data.len()), Some(10));
+        assert_eq!(fit(Some(&element), 7)?, None);
+        assert_eq!(fit(None, 2)?.map(|data| data.len()), Some(2));
+
+        let mut children = vec![
+            RawElement::unsigned(FLAG_DEFAULT_ID, 1),
+            RawElement::string(NAME_ID, "A"),
+            RawElement::unsigned(FLAG_DEFAULT_ID, 1),
+        ];
+        set_child(
+            &mut children,
+            FLAG_DEFAULT_ID,
+            Some(RawElement::unsigned(FLAG_DEFAULT_ID, 0)),
+        );
+        assert_eq!(
+            children,
+            [
+                RawElement::unsigned(FLAG_DEFAULT_ID, 0),
+                RawElement::string(NAME_ID, "A")
+            ]
+        );
+        set_child(&mut children, NAME_ID, None);
+        assert_eq!(children, [RawElement::unsigned(FLAG_DEFAULT_ID, 0)]);
+
+        Ok(())
+    }
+}
@@ -1,13 +1,12 @@
 //! Element IDs defines by the EBML and Matroska specifications.
 
-use std::collections::HashMap;
-use std::sync::OnceLock;
-
 /// The IDs of the supported elements.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 #[allow(missing_docs)]
 pub enum ElementId {
     Unknown,
+    /// An element that was registered with `ParserSettings::register_element()`.
+    Custom(u32),
     Ebml,
     EbmlVersion,
     EbmlReadVersion,
@@ -30,9 +29,16 @@ pub enum ElementId {
     Title,
     MuxingApp,
     WritingApp,
+    ChapterTranslate,
+    ChapterTranslateId,
+    ChapterTranslateCodec,
+    ChapterTranslateEditionUid,
     Cluster,
     Timestamp,
+    Position,
     PrevSize,
+    SilentTracks,
+    SilentTrackNumber,
     SimpleBlock,
     BlockGroup,
     Block,
@@ -42,6 +48,8 @@ pub enum ElementId {
     BlockAdditional,
     BlockDuration,
     ReferenceBlock,
+    ReferencePriority,
+    CodecState,
     DiscardPadding,
     Tracks,
     TrackEntry,
@@ -58,13 +66,34 @@ pub enum ElementId {
     FlagCommentary,
     FlagLacing,
     DefaultDuration,
+    DefaultDecodedFieldDuration,
+    MinCache,
+    MaxCache,
+    MaxBlockAdditionId,
     Name,
     Language,
+    LanguageIetf,
     CodecId,
     CodecPrivate,
     CodecName,
     CodecDelay,
     SeekPreRoll,
+    BlockAdditionMapping,
+    BlockAddIdValue,
+    BlockAddIdName,
+    BlockAddIdType,
+    BlockAddIdExtraData,
+    TrackTranslate,
+    TrackTranslateTrackId,
+    TrackTranslateCodec,
+    TrackTranslateEditionUid,
+    TrackOperation,
+    TrackCombinePlanes,
+    TrackPlane,
+    TrackPlaneUid,
+    TrackPlaneType,
+    TrackJoinBlocks,
+    TrackJoinUid,
     Video,
     FlagInterlaced,
     StereoMode,
@@ -84,11 +113,16 @@ pub enum ElementId {
     OutputSamplingFrequency,
     Channels,
     BitDepth,
+    Emphasis,
+    ChannelPositions,
     ContentEncodings,
     ContentEncoding,
     ContentEncodingOrder,
     ContentEncodingScope,
     ContentEncodingType,
+    ContentCompression,
+    ContentCompAlgo,
+    ContentCompSettings,
     ContentEncryption,
     ContentEncAlgo,
     ContentEncKeyId,
@@ -128,8 +162,25 @@ pub enum ElementId {
     CueRelativePosition,
     CueDuration,
     CueBlockNumber,
+    CueCodecState,
+    CueReference,
+    CueRefTime,
+    Attachments,
+    AttachedFile,
+    FileDescription,
+    FileName,
+    FileMediaType,
+    FileData,
+    FileUid,
     Chapters,
     EditionEntry,
+    EditionUid,
+    EditionFlagHidden,
+    EditionFlagDefault,
+    EditionFlagOrdered,
+    EditionDisplay,
+    EditionString,
+    EditionLanguageIetf,
     ChapterAtom,
     ChapterUid,
     ChapterStringUid,
@@ -140,12 +191,21 @@ pub enum ElementId {
     ChapLanguage,
     ChapLanguageIetf,
     ChapCountry,
+    ChapProcess,
+    ChapProcessCodecId,
+    ChapProcessPrivate,
+    ChapProcessCommand,
+    ChapProcessTime,
+    ChapProcessData,
     Tags,
     Tag,
     Targets,
     TargetTypeValue,
     TargetType,
     TagTrackUid,
+    TagEditionUid,
+    TagChapterUid,
+    TagAttachmentUid,
     SimpleTag,
     TagName,
     TagLanguage,
@@ -154,321 +214,858 @@ pub enum ElementId {
     TagBinary,
 }
 
-static ELEMENT_ID_TO_TYPE: OnceLock<HashMap<ElementId, ElementType>> = OnceLock::new();
-
 pub(crate) fn element_id_to_type(id: ElementId) -> ElementType {
-    let mapping = ELEMENT_ID_TO_TYPE.get_or_init(|| {
-        let mut m = HashMap::with_capacity(144);
-        m.insert(ElementId::Ebml, ElementType::Master);
-        m.insert(ElementId::EbmlVersion, ElementType::Unsigned);
-        m.insert(ElementId::EbmlReadVersion, ElementType::Unsigned);
-        m.insert(ElementId::EbmlMaxIdLength, ElementType::Unsigned);
-        m.insert(ElementId::EbmlMaxSizeLength, ElementType::Unsigned);
-        m.insert(ElementId::DocType, ElementType::String);
-        m.insert(ElementId::DocTypeVersion, ElementType::Unsigned);
-        m.insert(ElementId::DocTypeReadVersion, ElementType::Unsigned);
-        m.insert(ElementId::Crc32, ElementType::Binary);
-        m.insert(ElementId::Void, ElementType::Binary);
-        m.insert(ElementId::Segment, ElementType::Master);
-        m.insert(ElementId::SeekHead, ElementType::Master);
-        m.insert(ElementId::Seek, ElementType::Master);
-        // This is a binary in the spec, but we convert the IDs to u32.
-        m.insert(ElementId::SeekId, ElementType::Unsigned);
-        m.insert(ElementId::SeekPosition, ElementType::Unsigned);
-        m.insert(ElementId::Info, ElementType::Master);
-        m.insert(ElementId::TimestampScale, ElementType::Unsigned);
-        m.insert(ElementId::Duration, ElementType::Float);
-        m.insert(ElementId::DateUtc, ElementType::Date);
-        m.insert(ElementId::Title, ElementType::String);
-        m.insert(ElementId::MuxingApp, ElementType::String);
-        m.insert(ElementId::WritingApp, ElementType::String);
-        m.insert(ElementId::Cluster, ElementType::Master);
-        m.insert(ElementId::Timestamp, ElementType::Unsigned);
-        m.insert(ElementId::PrevSize, ElementType::Unsigned);
-        m.insert(ElementId::SimpleBlock, ElementType::Binary);
-        m.insert(ElementId::BlockGroup, ElementType::Master);
-        m.insert(ElementId::Block, ElementType::Binary);
-        m.insert(ElementId::BlockAdditions, ElementType::Master);
-        m.insert(ElementId::BlockMore, ElementType::Master);
-        m.insert(ElementId::BlockAddId, ElementType::Unsigned);
-        m.insert(ElementId::BlockAdditional, ElementType::Binary);
-        m.insert(ElementId::BlockDuration, ElementType::Unsigned);
-        m.insert(ElementId::ReferenceBlock, ElementType::Signed);
-        m.insert(ElementId::DiscardPadding, ElementType::Signed);
-        m.insert(ElementId::Tracks, ElementType::Master);
-        m.insert(ElementId::TrackEntry, ElementType::Master);
-        m.insert(ElementId::TrackNumber, ElementType::Unsigned);
-        m.insert(ElementId::TrackUid, ElementType::Unsigned);
-        m.insert(ElementId::TrackType, ElementType::Unsigned);
-        m.insert(ElementId::FlagEnabled, ElementType::Unsigned);
-        m.insert(ElementId::FlagDefault, ElementType::Unsigned);
-        m.insert(ElementId::FlagForced, ElementType::Unsigned);
-        m.insert(ElementId::FlagHearingImpaired, ElementType::Unsigned);
-        m.insert(ElementId::FlagVisualImpaired, ElementType::Unsigned);
-        m.insert(ElementId::FlagTextDescriptions, ElementType::Unsigned);
-        m.insert(ElementId::FlagOriginal, ElementType::Unsigned);
-        m.insert(ElementId::FlagCommentary, ElementType::Unsigned);
-        m.insert(ElementId::FlagLacing, ElementType::Unsigned);
-        m.insert(ElementId::DefaultDuration, ElementType::Unsigned);
-        m.insert(ElementId::Name, ElementType::String);
-        m.insert(ElementId::Language, ElementType::String);
-        m.insert(ElementId::CodecId, ElementType::String);
-        m.insert(ElementId::CodecPrivate, ElementType::Binary);
-        m.insert(ElementId::CodecName, ElementType::String);
-        m.insert(ElementId::CodecDelay, ElementType::Unsigned);
-        m.insert(ElementId::SeekPreRoll, ElementType::Unsigned);
-        m.insert(ElementId::Video, ElementType::Master);
-        m.insert(ElementId::FlagInterlaced, ElementType::Unsigned);
-        m.insert(ElementId::StereoMode, ElementType::Unsigned);
-        m.insert(ElementId::AlphaMode, ElementType::Unsigned);
-        m.insert(ElementId::PixelWidth, ElementType::Unsigned);
-        m.insert(ElementId::PixelHeight, ElementType::Unsigned);
-        m.insert(ElementId::PixelCropBottom, ElementType::Unsigned);
-        m.insert(ElementId::PixelCropTop, ElementType::Unsigned);
-        m.insert(ElementId::PixelCropLeft, ElementType::Unsigned);
-        m.insert(ElementId::PixelCropRight, ElementType::Unsigned);
-        m.insert(ElementId::DisplayWidth, ElementType::Unsigned);
-        m.insert(ElementId::DisplayHeight, ElementType::Unsigned);
-        m.insert(ElementId::DisplayUnit, ElementType::Unsigned);
-        m.insert(ElementId::AspectRatioType, ElementType::Unsigned);
-        m.insert(ElementId::Audio, ElementType::Master);
-        m.insert(ElementId::SamplingFrequency, ElementType::Float);
-        m.insert(ElementId::OutputSamplingFrequency, ElementType::Float);
-        m.insert(ElementId::Channels, ElementType::Unsigned);
-        m.insert(ElementId::BitDepth, ElementType::Unsigned);
-        m.insert(ElementId::ContentEncodings, ElementType::Master);
-        m.insert(ElementId::ContentEncoding, ElementType::Master);
-        m.insert(ElementId::ContentEncodingOrder, ElementType::Unsigned);
-        m.insert(ElementId::ContentEncodingScope, ElementType::Unsigned);
-        m.insert(ElementId::ContentEncodingType, ElementType::Unsigned);
-        m.insert(ElementId::ContentEncryption, ElementType::Master);
-        m.insert(ElementId::ContentEncAlgo, ElementType::Unsigned);
-        m.insert(ElementId::ContentEncKeyId, ElementType::Unsigned);
-        m.insert(ElementId::ContentEncAesSettings, ElementType::Master);
-        m.insert(ElementId::AesSettingsCipherMode, ElementType::Unsigned);
-        m.insert(ElementId::Colour, ElementType::Master);
-        m.insert(ElementId::MatrixCoefficients, ElementType::Unsigned);
-        m.insert(ElementId::BitsPerChannel, ElementType::Unsigned);
-        m.insert(ElementId::ChromaSubsamplingHorz, ElementType::Unsigned);
-        m.insert(ElementId::ChromaSubsamplingVert, ElementType::Unsigned);
-        m.insert(ElementId::CbSubsamplingHorz, ElementType::Unsigned);
-        m.insert(ElementId::CbSubsamplingVert, ElementType::Unsigned);
-        m.insert(ElementId::ChromaSitingHorz, ElementType::Unsigned);
-        m.insert(ElementId::ChromaSitingVert, ElementType::Unsigned);
-        m.insert(ElementId::Range, ElementType::Unsigned);
-        m.insert(ElementId::TransferCharacteristics, ElementType::Unsigned);
-        m.insert(ElementId::Primaries, ElementType::Unsigned);
-        m.insert(ElementId::MaxCll, ElementType::Unsigned);
-        m.insert(ElementId::MaxFall, ElementType::Unsigned);
-        m.insert(ElementId::MasteringMetadata, ElementType::Master);
-        m.insert(ElementId::PrimaryRChromaticityX, ElementType::Float);
-        m.insert(ElementId::PrimaryRChromaticityY, ElementType::Float);
-        m.insert(ElementId::PrimaryGChromaticityX, ElementType::Float);
-        m.insert(ElementId::PrimaryGChromaticityY, ElementType::Float);
-        m.insert(ElementId::PrimaryBChromaticityX, ElementType::Float);
-        m.insert(ElementId::PrimaryBChromaticityY, ElementType::Float);
-        m.insert(ElementId::WhitePointChromaticityX, ElementType::Float);
-        m.insert(ElementId::WhitePointChromaticityY, ElementType::Float);
-        m.insert(ElementId::LuminanceMax, ElementType::Float);
-        m.insert(ElementId::LuminanceMin, ElementType::Float);
-        m.insert(ElementId::Cues, ElementType::Master);
-        m.insert(ElementId::CuePoint, ElementType::Master);
-        m.insert(ElementId::CueTime, ElementType::Unsigned);
-        m.insert(ElementId::CueTrackPositions, ElementType::Master);
-        m.insert(ElementId::CueTrack, ElementType::Unsigned);
-        m.insert(ElementId::CueClusterPosition, ElementType::Unsigned);
-        m.insert(ElementId::CueRelativePosition, ElementType::Unsigned);
-        m.insert(ElementId::CueDuration, ElementType::Unsigned);
-        m.insert(ElementId::CueBlockNumber, ElementType::Unsigned);
-        m.insert(ElementId::Chapters, ElementType::Master);
-        m.insert(ElementId::EditionEntry, ElementType::Master);
-        m.insert(ElementId::ChapterAtom, ElementType::Master);
-        m.insert(ElementId::ChapterUid, ElementType::Unsigned);
-        m.insert(ElementId::ChapterStringUid, ElementType::String);
-        m.insert(ElementId::ChapterTimeStart, ElementType::Unsigned);
-        m.insert(ElementId::ChapterTimeEnd, ElementType::Unsigned);
-        m.insert(ElementId::ChapterDisplay, ElementType::Master);
-        m.insert(ElementId::ChapString, ElementType::String);
-        m.insert(ElementId::ChapLanguage, ElementType::String);
-        m.insert(ElementId::ChapLanguageIetf, ElementType::String);
-        m.insert(ElementId::ChapCountry, ElementType::String);
-        m.insert(ElementId::Tags, ElementType::Master);
-        m.insert(ElementId::Tag, ElementType::Master);
-        m.insert(ElementId::Targets, ElementType::Master);
-        m.insert(ElementId::TargetTypeValue, ElementType::Unsigned);
-        m.insert(ElementId::TargetType, ElementType::String);
-        m.insert(ElementId::TagTrackUid, ElementType::Unsigned);
-        m.insert(ElementId::SimpleTag, ElementType::Master);
-        m.insert(ElementId::TagName, ElementType::String);
-        m.insert(ElementId::TagLanguage, ElementType::String);
-        m.insert(ElementId::TagDefault, ElementType::Unsigned);
-        m.insert(ElementId::TagString, ElementType::String);
-        m.insert(ElementId::TagBinary, ElementType::Binary);
-        m
-    });
-    mapping.get(&id).copied().unwrap_or(ElementType::Unknown)
+    match id {
+        ElementId::Ebml => ElementType::Master,
+        ElementId::EbmlVersion => ElementType::Unsigned,
+        ElementId::EbmlReadVersion => ElementType::Unsigned,
+        ElementId::EbmlMaxIdLength => ElementType::Unsigned,
+        ElementId::EbmlMaxSizeLength => ElementType::Unsigned,
+        ElementId::DocType => ElementType::String,
+        ElementId::DocTypeVersion => ElementType::Unsigned,
+        ElementId::DocTypeReadVersion => ElementType::Unsigned,
+        ElementId::Crc32 => ElementType::Binary,
+        ElementId::Void => ElementType::Binary,
+        ElementId::Segment => ElementType::Master,
+        ElementId::SeekHead => ElementType::Master,
+        ElementId::Seek => ElementType::Master,
+        ElementId::SeekId => ElementType::Unsigned,
+        ElementId::SeekPosition => ElementType::Unsigned,
+        ElementId::Info => ElementType::Master,
+        ElementId::TimestampScale => ElementType::Unsigned,
+        ElementId::Duration => ElementType::Float,
+        ElementId::DateUtc => ElementType::Date,
+        ElementId::Title => ElementType::String,
+        ElementId::MuxingApp => ElementType::String,
+        ElementId::WritingApp => ElementType::String,
+        ElementId::ChapterTranslate => ElementType::Master,
+        ElementId::ChapterTranslateId => ElementType::Binary,
+        ElementId::ChapterTranslateCodec => ElementType::Unsigned,
+        ElementId::ChapterTranslateEditionUid => ElementType::Unsigned,
+        ElementId::Cluster => ElementType::Master,
+        ElementId::Timestamp => ElementType::Unsigned,
+        ElementId::Position => ElementType::Unsigned,
+        ElementId::PrevSize => ElementType::Unsigned,
+        ElementId::SilentTracks => ElementType::Master,
+        ElementId::SilentTrackNumber => ElementType::Unsigned,
+        ElementId::SimpleBlock => ElementType::Binary,
+        ElementId::BlockGroup => ElementType::Master,
+        ElementId::Block => ElementType::Binary,
+        ElementId::BlockAdditions => ElementType::Master,
+        ElementId::BlockMore => ElementType::Master,
+        ElementId::BlockAddId => ElementType::Unsigned,
+        ElementId::BlockAdditional => ElementType::Binary,
+        ElementId::BlockDuration => ElementType::Unsigned,
+        ElementId::ReferenceBlock => ElementType::Signed,
+        ElementId::ReferencePriority => ElementType::Unsigned,
+        ElementId::CodecState => ElementType::Binary,
+        ElementId::DiscardPadding => ElementType::Signed,
+        ElementId::Tracks => ElementType::Master,
+        ElementId::TrackEntry => ElementType::Master,
+        ElementId::TrackNumber => ElementType::Unsigned,
+        ElementId::TrackUid => ElementType::Unsigned,
+        ElementId::TrackType => ElementType::Unsigned,
+        ElementId::FlagEnabled => ElementType::Unsigned,
+        ElementId::FlagDefault => ElementType::Unsigned,
+        ElementId::FlagForced => ElementType::Unsigned,
+        ElementId::FlagHearingImpaired => ElementType::Unsigned,
+        ElementId::FlagVisualImpaired => ElementType::Unsigned,
+        ElementId::FlagTextDescriptions => ElementType::Unsigned,
+        ElementId::FlagOriginal => ElementType::Unsigned,
+        ElementId::FlagCommentary => ElementType::Unsigned,
+        ElementId::FlagLacing => ElementType::Unsigned,
+        ElementId::DefaultDuration => ElementType::Unsigned,
+        ElementId::DefaultDecodedFieldDuration => ElementType::Unsigned,
+        ElementId::MinCache => ElementType::Unsigned,
+        ElementId::MaxCache => ElementType::Unsigned,
+        ElementId::MaxBlockAdditionId => ElementType::Unsigned,
+        ElementId::Name => ElementType::String,
+        ElementId::Language => ElementType::String,
+        ElementId::LanguageIetf => ElementType::String,
+        ElementId::CodecId => ElementType::String,
+        ElementId::CodecPrivate => ElementType::Binary,
+        ElementId::CodecName => ElementType::String,
+        ElementId::CodecDelay => ElementType::Unsigned,
+        ElementId::SeekPreRoll => ElementType::Unsigned,
+        ElementId::BlockAdditionMapping => ElementType::Master,
+        ElementId::BlockAddIdValue => ElementType::Unsigned,
+        ElementId::BlockAddIdName => ElementType::String,
+        ElementId::BlockAddIdType => ElementType::Unsigned,
+        ElementId::BlockAddIdExtraData => ElementType::Binary,
+        ElementId::TrackTranslate => ElementType::Master,
+        ElementId::TrackTranslateTrackId => ElementType::Binary,
+        ElementId::TrackTranslateCodec => ElementType::Unsigned,
+        ElementId::TrackTranslateEditionUid => ElementType::Unsigned,
+        ElementId::TrackOperation => ElementType::Master,
+        ElementId::TrackCombinePlanes => ElementType::Master,
+        ElementId::TrackPlane => ElementType::Master,
+        ElementId::TrackPlaneUid => ElementType::Unsigned,
+        ElementId::TrackPlaneType => ElementType::Unsigned,
+        ElementId::TrackJoinBlocks => ElementType::Master,
+        ElementId::TrackJoinUid => ElementType::Unsigned,
+        ElementId::Video => ElementType::Master,
+        ElementId::FlagInterlaced => ElementType::Unsigned,
+        ElementId::StereoMode => ElementType::Unsigned,
+        ElementId::AlphaMode => ElementType::Unsigned,
+        ElementId::PixelWidth => ElementType::Unsigned,
+        ElementId::PixelHeight => ElementType::Unsigned,
+        ElementId::PixelCropBottom => ElementType::Unsigned,
+        ElementId::PixelCropTop => ElementType::Unsigned,
+        ElementId::PixelCropLeft => ElementType::Unsigned,
+        ElementId::PixelCropRight => ElementType::Unsigned,
+        ElementId::DisplayWidth => ElementType::Unsigned,
+        ElementId::DisplayHeight => ElementType::Unsigned,
+        ElementId::DisplayUnit => ElementType::Unsigned,
+        ElementId::AspectRatioType => ElementType::Unsigned,
+        ElementId::Audio => ElementType::Master,
+        ElementId::SamplingFrequency => ElementType::Float,
+        ElementId::OutputSamplingFrequency => ElementType::Float,
+        ElementId::Channels => ElementType::Unsigned,
+        ElementId::BitDepth => ElementType::Unsigned,
+        ElementId::Emphasis => ElementType::Unsigned,
+        ElementId::ChannelPositions => ElementType::Binary,
+        ElementId::ContentEncodings => ElementType::Master,
+        ElementId::ContentEncoding => ElementType::Master,
+        ElementId::ContentEncodingOrder => ElementType::Unsigned,
+        ElementId::ContentEncodingScope => ElementType::Unsigned,
+        ElementId::ContentEncodingType => ElementType::Unsigned,
+        ElementId::ContentCompression => ElementType::Master,
+        ElementId::ContentCompAlgo => ElementType::Unsigned,
+        ElementId::ContentCompSettings => ElementType::Binary,
+        ElementId::ContentEncryption => ElementType::Master,
+        ElementId::ContentEncAlgo => ElementType::Unsigned,
+        ElementId::ContentEncKeyId => ElementType::Binary,
+        ElementId::ContentEncAesSettings => ElementType::Master,
+        ElementId::AesSettingsCipherMode => ElementType::Unsigned,
+        ElementId::Colour => ElementType::Master,
+        ElementId::MatrixCoefficients => ElementType::Unsigned,
+        ElementId::BitsPerChannel => ElementType::Unsigned,
+        ElementId::ChromaSubsamplingHorz => ElementType::Unsigned,
+        ElementId::ChromaSubsamplingVert => ElementType::Unsigned,
+        ElementId::CbSubsamplingHorz => ElementType::Unsigned,
+        ElementId::CbSubsamplingVert => ElementType::Unsigned,
+        ElementId::ChromaSitingHorz => ElementType::Unsigned,
+        ElementId::ChromaSitingVert => ElementType::Unsigned,
+        ElementId::Range => ElementType::Unsigned,
+        ElementId::TransferCharacteristics => ElementType::Unsigned,
+        ElementId::Primaries => ElementType::Unsigned,
+        ElementId::MaxCll => ElementType::Unsigned,
+        ElementId::MaxFall => ElementType::Unsigned,
+        ElementId::MasteringMetadata => ElementType::Master,
+        ElementId::PrimaryRChromaticityX => ElementType::Float,
+        ElementId::PrimaryRChromaticityY => ElementType::Float,
+        ElementId::PrimaryGChromaticityX => ElementType::Float,
+        ElementId::PrimaryGChromaticityY => ElementType::Float,
+        ElementId::PrimaryBChromaticityX => ElementType::Float,
+        ElementId::PrimaryBChromaticityY => ElementType::Float,
+        ElementId::WhitePointChromaticityX => ElementType::Float,
+        ElementId::WhitePointChromaticityY => ElementType::Float,
+        ElementId::LuminanceMax => ElementType::Float,
+        ElementId::LuminanceMin => ElementType::Float,
+        ElementId::Cues => ElementType::Master,
+        ElementId::CuePoint => ElementType::Master,
+        ElementId::CueTime => ElementType::Unsigned,
+        ElementId::CueTrackPositions => ElementType::Master,
+        ElementId::CueTrack => ElementType::Unsigned,
+        ElementId::CueClusterPosition => ElementType::Unsigned,
+        ElementId::CueRelativePosition => ElementType::Unsigned,
+        ElementId::CueDuration => ElementType::Unsigned,
+        ElementId::CueBlockNumber => ElementType::Unsigned,
+        ElementId::CueCodecState => ElementType::Unsigned,
+        ElementId::CueReference => ElementType::Master,
+        ElementId::CueRefTime => ElementType::Unsigned,
+        ElementId::Attachments => ElementType::Master,
+        ElementId::AttachedFile => ElementType::Master,
+        ElementId::FileDescription => ElementType::String,
+        ElementId::FileName => ElementType::String,
+        ElementId::FileMediaType => ElementType::String,
+        ElementId::FileData => ElementType::Binary,
+        ElementId::FileUid => ElementType::Unsigned,
+        ElementId::Chapters => ElementType::Master,
+        ElementId::EditionEntry => ElementType::Master,
+        ElementId::EditionUid => ElementType::Unsigned,
+        ElementId::EditionFlagHidden => ElementType::Unsigned,
+        ElementId::EditionFlagDefault => ElementType::Unsigned,
+        ElementId::EditionFlagOrdered => ElementType::Unsigned,
+        ElementId::EditionDisplay => ElementType::Master,
+        ElementId::EditionString => ElementType::String,
+        ElementId::EditionLanguageIetf => ElementType::String,
+        ElementId::ChapterAtom => ElementType::Master,
+        ElementId::ChapterUid => ElementType::Unsigned,
+        ElementId::ChapterStringUid => ElementType::String,
+        ElementId::ChapterTimeStart => ElementType::Unsigned,
+        ElementId::ChapterTimeEnd => ElementType::Unsigned,
+        ElementId::ChapterDisplay => ElementType::Master,
+        ElementId::ChapString => ElementType::String,
+        ElementId::ChapLanguage => ElementType::String,
+        ElementId::ChapLanguageIetf => ElementType::String,
+        ElementId::ChapCountry => ElementType::String,
+        ElementId::ChapProcess => ElementType::Master,
+        ElementId::ChapProcessCodecId => ElementType::Unsigned,
+        ElementId::ChapProcessPrivate => ElementType::Binary,
+        ElementId::ChapProcessCommand => ElementType::Master,
+        ElementId::ChapProcessTime => ElementType::Unsigned,
+        ElementId::ChapProcessData => ElementType::Binary,
+        ElementId::Tags => ElementType::Master,
+        ElementId::Tag => ElementType::Master,
+        ElementId::Targets => ElementType::Master,
+        ElementId::TargetTypeValue => ElementType::Unsigned,
+        ElementId::TargetType => ElementType::String,
+        ElementId::TagTrackUid => ElementType::Unsigned,
+        ElementId::TagEditionUid => ElementType::Unsigned,
+        ElementId::TagChapterUid => ElementType::Unsigned,
+        ElementId::TagAttachmentUid => ElementType::Unsigned,
+        ElementId::SimpleTag => ElementType::Master,
+        ElementId::TagName => ElementType::String,
+        ElementId::TagLanguage => ElementType::String,
+        ElementId::TagDefault => ElementType::Unsigned,
+        ElementId::TagString => ElementType::String,
+        ElementId::TagBinary => ElementType::Binary,
+        ElementId::Unknown | ElementId::Custom(_) => ElementType::Unknown,
+    }
 }
 
-static ID_TO_ELEMENT_ID: OnceLock<HashMap<u32, ElementId>> = OnceLock::new();
-
 pub(crate) fn id_to_element_id(id: u32) -> ElementId {
-    let mapping = ID_TO_ELEMENT_ID.get_or_init(|| {
-        let mut m = HashMap::with_capacity(144);
-        m.insert(0x1A45DFA3, ElementId::Ebml);
-        m.insert(0x4286, ElementId::EbmlVersion);
-        m.insert(0x42F7, ElementId::EbmlReadVersion);
-        m.insert(0x42F2, ElementId::EbmlMaxIdLength);
-        m.insert(0x42F3, ElementId::EbmlMaxSizeLength);
-        m.insert(0x4282, ElementId::DocType);
-        m.insert(0x4287, ElementId::DocTypeVersion);
-        m.insert(0x4285, ElementId::DocTypeReadVersion);
-        m.insert(0xBF, ElementId::Crc32);
-        m.insert(0xEC, ElementId::Void);
-        m.insert(0x18538067, ElementId::Segment);
-        m.insert(0x114D9B74, ElementId::SeekHead);
-        m.insert(0x4DBB, ElementId::Seek);
-        m.insert(0x53AB, ElementId::SeekId);
-        m.insert(0x53AC, ElementId::SeekPosition);
-        m.insert(0x1549A966, ElementId::Info);
-        m.insert(0x2AD7B1, ElementId::TimestampScale);
-        m.insert(0x4489, ElementId::Duration);
-        m.insert(0x4461, ElementId::DateUtc);
-        m.insert(0x7BA9, ElementId::Title);
-        m.insert(0x4D80, ElementId::MuxingApp);
-        m.insert(0x5741, ElementId::WritingApp);
-        m.insert(0x1F43B675, ElementId::Cluster);
-        m.insert(0xE7, ElementId::Timestamp);
-        m.insert(0xAB, ElementId::PrevSize);
-        m.insert(0xA3, ElementId::SimpleBlock);
-        m.insert(0xA0, ElementId::BlockGroup);
-        m.insert(0xA1, ElementId::Block);
-        m.insert(0x75A1, ElementId::BlockAdditions);
-        m.insert(0xA6, ElementId::BlockMore);
-        m.insert(0xEE, ElementId::BlockAddId);
-        m.insert(0xA5, ElementId::BlockAdditional);
-        m.insert(0x9B, ElementId::BlockDuration);
-        m.insert(0xFB, ElementId::ReferenceBlock);
-        m.insert(0x75A2, ElementId::DiscardPadding);
-        m.insert(0x1654AE6B, ElementId::Tracks);
-        m.insert(0xAE, ElementId::TrackEntry);
-        m.insert(0xD7, ElementId::TrackNumber);
-        m.insert(0x73C5, ElementId::TrackUid);
-        m.insert(0x83, ElementId::TrackType);
-        m.insert(0xB9, ElementId::FlagEnabled);
-        m.insert(0x88, ElementId::FlagDefault);
-        m.insert(0x55AA, ElementId::FlagForced);
-        m.insert(0x55AB, ElementId::FlagHearingImpaired);
-        m.insert(0x55AC, ElementId::FlagVisualImpaired);
-        m.insert(0x55AD, ElementId::FlagTextDescriptions);
-        m.insert(0x55AE, ElementId::FlagOriginal);
-        m.insert(0x55AF, ElementId::FlagCommentary);
-        m.insert(0x9C, ElementId::FlagLacing);
-        m.insert(0x23E383, ElementId::DefaultDuration);
-        m.insert(0x536E, ElementId::Name);
-        m.insert(0x22B59C, ElementId::Language);
-        m.insert(0x86, ElementId::CodecId);
-        m.insert(0x63A2, ElementId::CodecPrivate);
-        m.insert(0x258688, ElementId::CodecName);
-        m.insert(0x56AA, ElementId::CodecDelay);
-        m.insert(0x56BB, ElementId::SeekPreRoll);
-        m.insert(0xE0, ElementId::Video);
-        m.insert(0x9A, ElementId::FlagInterlaced);
-        m.insert(0x53B8, ElementId::StereoMode);
-        m.insert(0x53C0, ElementId::AlphaMode);
-        m.insert(0xB0, ElementId::PixelWidth);
-        m.insert(0xBA, ElementId::PixelHeight);
-        m.insert(0x54AA, ElementId::PixelCropBottom);
-        m.insert(0x54BB, ElementId::PixelCropTop);
-        m.insert(0x54CC, ElementId::PixelCropLeft);
-        m.insert(0x54DD, ElementId::PixelCropRight);
-        m.insert(0x54B0, ElementId::DisplayWidth);
-        m.insert(0x54BA, ElementId::DisplayHeight);
-        m.insert(0x54B2, ElementId::DisplayUnit);
-        m.insert(0x54B3, ElementId::AspectRatioType);
-        m.insert(0xE1, ElementId::Audio);
-        m.insert(0xB5, ElementId::SamplingFrequency);
-        m.insert(0x78B5, ElementId::OutputSamplingFrequency);
-        m.insert(0x9F, ElementId::Channels);
-        m.insert(0x6264, ElementId::BitDepth);
-        m.insert(0x6D80, ElementId::ContentEncodings);
-        m.insert(0x6240, ElementId::ContentEncoding);
-        m.insert(0x5031, ElementId::ContentEncodingOrder);
-        m.insert(0x5032, ElementId::ContentEncodingScope);
-        m.insert(0x5033, ElementId::ContentEncodingType);
-        m.insert(0x5035, ElementId::ContentEncryption);
-        m.insert(0x47E1, ElementId::ContentEncAlgo);
-        m.insert(0x47E2, ElementId::ContentEncKeyId);
-        m.insert(0x47E7, ElementId::ContentEncAesSettings);
-        m.insert(0x47E8, ElementId::AesSettingsCipherMode);
-        m.insert(0x55B0, ElementId::Colour);
-        m.insert(0x55B1, ElementId::MatrixCoefficients);
-        m.insert(0x55B2, ElementId::BitsPerChannel);
-        m.insert(0x55B3, ElementId::ChromaSubsamplingHorz);
-        m.insert(0x55B4, ElementId::ChromaSubsamplingVert);
-        m.insert(0x55B5, ElementId::CbSubsamplingHorz);
-        m.insert(0x55B6, ElementId::CbSubsamplingVert);
-        m.insert(0x55B7, ElementId::ChromaSitingHorz);
-        m.insert(0x55B8, ElementId::ChromaSitingVert);
-        m.insert(0x55B9, ElementId::Range);
-        m.insert(0x55BA, ElementId::TransferCharacteristics);
-        m.insert(0x55BB, ElementId::Primaries);
-        m.insert(0x55BC, ElementId::MaxCll);
-        m.insert(0x55BD, ElementId::MaxFall);
-        m.insert(0x55D0, ElementId::MasteringMetadata);
-        m.insert(0x55D1, ElementId::PrimaryRChromaticityX);
-        m.insert(0x55D2, ElementId::PrimaryRChromaticityY);
-        m.insert(0x55D3, ElementId::PrimaryGChromaticityX);
-        m.insert(0x55D4, ElementId::PrimaryGChromaticityY);
-        m.insert(0x55D5, ElementId::PrimaryBChromaticityX);
-        m.insert(0x55D6, ElementId::PrimaryBChromaticityY);
-        m.insert(0x55D7, ElementId::WhitePointChromaticityX);
-        m.insert(0x55D8, ElementId::WhitePointChromaticityY);
-        m.insert(0x55D9, ElementId::LuminanceMax);
-        m.insert(0x55DA, ElementId::LuminanceMin);
-        m.insert(0x1C53BB6B, ElementId::Cues);
-        m.insert(0xBB, ElementId::CuePoint);
-        m.insert(0xB3, ElementId::CueTime);
-        m.insert(0xB7, ElementId::CueTrackPositions);
-        m.insert(0xF7, ElementId::CueTrack);
-        m.insert(0xF1, ElementId::CueClusterPosition);
-        m.insert(0xF0, ElementId::CueRelativePosition);
-        m.insert(0xB2, ElementId::CueDuration);
-        m.insert(0x5378, ElementId::CueBlockNumber);
-        m.insert(0x1043A770, ElementId::Chapters);
-        m.insert(0x45B9, ElementId::EditionEntry);
-        m.insert(0xB6, ElementId::ChapterAtom);
-        m.insert(0x73C4, ElementId::ChapterUid);
-        m.insert(0x5654, ElementId::ChapterStringUid);
-        m.insert(0x91, ElementId::ChapterTimeStart);
-        m.insert(0x92, ElementId::ChapterTimeEnd);
-        m.insert(0x80, ElementId::ChapterDisplay);
-        m.insert(0x85, ElementId::ChapString);
-        m.insert(0x437C, ElementId::ChapLanguage);
-        m.insert(0x437D, ElementId::ChapLanguageIetf);
-        m.insert(0x437E, ElementId::ChapCountry);
-        m.insert(0x1254C367, ElementId::Tags);
-        m.insert(0x7373, ElementId::Tag);
-        m.insert(0x63C0, ElementId::Targets);
-        m.insert(0x68CA, ElementId::TargetTypeValue);
-        m.insert(0x63CA, ElementId::TargetType);
-        m.insert(0x63C5, ElementId::TagTrackUid);
-        m.insert(0x67C8, ElementId::SimpleTag);
-        m.insert(0x45A3, ElementId::TagName);
-        m.insert(0x447A, ElementId::TagLanguage);
-        m.insert(0x4484, ElementId::TagDefault);
-        m.insert(0x4487, ElementId::TagString);
-        m.insert(0x4485, ElementId::TagBinary);
-        m
-    });
-    mapping.get(&id).copied().unwrap_or(ElementId::Unknown)
+    match id {
+        0x1A45DFA3 => ElementId::Ebml,
+        0x4286 => ElementId::EbmlVersion,
+        0x42F7 => ElementId::EbmlReadVersion,
+        0x42F2 => ElementId::EbmlMaxIdLength,
+        0x42F3 => ElementId::EbmlMaxSizeLength,
+        0x4282 => ElementId::DocType,
+        0x4287 => ElementId::DocTypeVersion,
+        0x4285 => ElementId::DocTypeReadVersion,
+        0xBF => ElementId::Crc32,
+        0xEC => ElementId::Void,
+        0x18538067 => ElementId::Segment,
+        0x114D9B74 => ElementId::SeekHead,
+        0x4DBB => ElementId::Seek,
+        0x53AB => ElementId::SeekId,
+        0x53AC => ElementId::SeekPosition,
+        0x1549A966 => ElementId::Info,
+        0x2AD7B1 => ElementId::TimestampScale,
+        0x4489 => ElementId::Duration,
+        0x4461 => ElementId::DateUtc,
+        0x7BA9 => ElementId::Title,
+        0x4D80 => ElementId::MuxingApp,
+        0x5741 => ElementId::WritingApp,
+        0x6924 => ElementId::ChapterTranslate,
+        0x69A5 => ElementId::ChapterTranslateId,
+        0x69BF => ElementId::ChapterTranslateCodec,
+        0x69FC => ElementId::ChapterTranslateEditionUid,
+        0x1F43B675 => ElementId::Cluster,
+        0xE7 => ElementId::Timestamp,
+        0xA7 => ElementId::Position,
+        0xAB => ElementId::PrevSize,
+        0x5854 => ElementId::SilentTracks,
+        0x58D7 => ElementId::SilentTrackNumber,
+        0xA3 => ElementId::SimpleBlock,
+        0xA0 => ElementId::BlockGroup,
+        0xA1 => ElementId::Block,
+        0x75A1 => ElementId::BlockAdditions,
+        0xA6 => ElementId::BlockMore,
+        0xEE => ElementId::BlockAddId,
+        0xA5 => ElementId::BlockAdditional,
+        0x9B => ElementId::BlockDuration,
+        0xFB => ElementId::ReferenceBlock,
+        0xFA => ElementId::ReferencePriority,
+        0xA4 => ElementId::CodecState,
+        0x75A2 => ElementId::DiscardPadding,
+        0x1654AE6B => ElementId::Tracks,
+        0xAE => ElementId::TrackEntry,
+        0xD7 => ElementId::TrackNumber,
+        0x73C5 => ElementId::TrackUid,
+        0x83 => ElementId::TrackType,
+        0xB9 => ElementId::FlagEnabled,
+        0x88 => ElementId::FlagDefault,
+        0x55AA => ElementId::FlagForced,
+        0x55AB => ElementId::FlagHearingImpaired,
+        0x55AC => ElementId::FlagVisualImpaired,
+        0x55AD => ElementId::FlagTextDescriptions,
+        0x55AE => ElementId::FlagOriginal,
+        0x55AF => ElementId::FlagCommentary,
+        0x9C => ElementId::FlagLacing,
+        0x23E383 => ElementId::DefaultDuration,
+        0x234E7A => ElementId::DefaultDecodedFieldDuration,
+        0x6DE7 => ElementId::MinCache,
+        0x6DF8 => ElementId::MaxCache,
+        0x55EE => ElementId::MaxBlockAdditionId,
+        0x536E => ElementId::Name,
+        0x22B59C => ElementId::Language,
+        0x22B59D => ElementId::LanguageIetf,
+        0x86 => ElementId::CodecId,
+        0x63A2 => ElementId::CodecPrivate,
+        0x258688 => ElementId::CodecName,
+        0x56AA => ElementId::CodecDelay,
+        0x56BB => ElementId::SeekPreRoll,
+        0x41E4 => ElementId::BlockAdditionMapping,
+        0x41F0 => ElementId::BlockAddIdValue,
+        0x41A4 => ElementId::BlockAddIdName,
+        0x41E7 => ElementId::BlockAddIdType,
+        0x41ED => ElementId::BlockAddIdExtraData,
+        0x6624 => ElementId::TrackTranslate,
+        0x66A5 => ElementId::TrackTranslateTrackId,
+        0x66BF => ElementId::TrackTranslateCodec,
+        0x66FC => ElementId::TrackTranslateEditionUid,
+        0xE2 => ElementId::TrackOperation,
+        0xE3 => ElementId::TrackCombinePlanes,
+        0xE4 => ElementId::TrackPlane,
+        0xE5 => ElementId::TrackPlaneUid,
+        0xE6 => ElementId::TrackPlaneType,
+        0xE9 => ElementId::TrackJoinBlocks,
+        0xED => ElementId::TrackJoinUid,
+        0xE0 => ElementId::Video,
+        0x9A => ElementId::FlagInterlaced,
+        0x53B8 => ElementId::StereoMode,
+        0x53C0 => ElementId::AlphaMode,
+        0xB0 => ElementId::PixelWidth,
+        0xBA => ElementId::PixelHeight,
+        0x54AA => ElementId::PixelCropBottom,
+        0x54BB => ElementId::PixelCropTop,
+        0x54CC => ElementId::PixelCropLeft,
+        0x54DD => ElementId::PixelCropRight,
+        0x54B0 => ElementId::DisplayWidth,
+        0x54BA => ElementId::DisplayHeight,
+        0x54B2 => ElementId::DisplayUnit,
+        0x54B3 => ElementId::AspectRatioType,
+        0xE1 => ElementId::Audio,
+        0xB5 => ElementId::SamplingFrequency,
+        0x78B5 => ElementId::OutputSamplingFrequency,
+        0x9F => ElementId::Channels,
+        0x6264 => ElementId::BitDepth,
+        0x52F1 => ElementId::Emphasis,
+        0x7D7B => ElementId::ChannelPositions,
+        0x6D80 => ElementId::ContentEncodings,
+        0x6240 => ElementId::ContentEncoding,
+        0x5031 => ElementId::ContentEncodingOrder,
+        0x5032 => ElementId::ContentEncodingScope,
+        0x5033 => ElementId::ContentEncodingType,
+        0x5034 => ElementId::ContentCompression,
+        0x4254 => ElementId::ContentCompAlgo,
+        0x4255 => ElementId::ContentCompSettings,
+        0x5035 => ElementId::ContentEncryption,
+        0x47E1 => ElementId::ContentEncAlgo,
+        0x47E2 => ElementId::ContentEncKeyId,
+        0x47E7 => ElementId::ContentEncAesSettings,
+        0x47E8 => ElementId::AesSettingsCipherMode,
+        0x55B0 => ElementId::Colour,
+        0x55B1 => ElementId::MatrixCoefficients,
+        0x55B2 => ElementId::BitsPerChannel,
+        0x55B3 => ElementId::ChromaSubsamplingHorz,
+        0x55B4 => ElementId::ChromaSubsamplingVert,
+        0x55B5 => ElementId::CbSubsamplingHorz,
+        0x55B6 => ElementId::CbSubsamplingVert,
+        0x55B7 => ElementId::ChromaSitingHorz,
+        0x55B8 => ElementId::ChromaSitingVert,
+        0x55B9 => ElementId::Range,
+        0x55BA => ElementId::TransferCharacteristics,
+        0x55BB => ElementId::Primaries,
+        0x55BC => ElementId::MaxCll,
+        0x55BD => ElementId::MaxFall,
+        0x55D0 => ElementId::MasteringMetadata,
+        0x55D1 => ElementId::PrimaryRChromaticityX,
+        0x55D2 => ElementId::PrimaryRChromaticityY,
+        0x55D3 => ElementId::PrimaryGChromaticityX,
+        0x55D4 => ElementId::PrimaryGChromaticityY,
+        0x55D5 => ElementId::PrimaryBChromaticityX,
+        0x55D6 => ElementId::PrimaryBChromaticityY,
+        0x55D7 => ElementId::WhitePointChromaticityX,
+        0x55D8 => ElementId::WhitePointChromaticityY,
+        0x55D9 => ElementId::LuminanceMax,
+        0x55DA => ElementId::LuminanceMin,
+        0x1C53BB6B => ElementId::Cues,
+        0xBB => ElementId::CuePoint,
+        0xB3 => ElementId::CueTime,
+        0xB7 => ElementId::CueTrackPositions,
+        0xF7 => ElementId::CueTrack,
+        0xF1 => ElementId::CueClusterPosition,
+        0xF0 => ElementId::CueRelativePosition,
+        0xB2 => ElementId::CueDuration,
+        0x5378 => ElementId::CueBlockNumber,
+        0xEA => ElementId::CueCodecState,
+        0xDB => ElementId::CueReference,
+        0x96 => ElementId::CueRefTime,
+        0x1941A469 => ElementId::Attachments,
+        0x61A7 => ElementId::AttachedFile,
+        0x467E => ElementId::FileDescription,
+        0x466E => ElementId::FileName,
+        0x4660 => ElementId::FileMediaType,
+        0x465C => ElementId::FileData,
+        0x46AE => ElementId::FileUid,
+        0x1043A770 => ElementId::Chapters,
+        0x45B9 => ElementId::EditionEntry,
+        0x45BC => ElementId::EditionUid,
+        0x45BD => ElementId::EditionFlagHidden,
+        0x45DB => ElementId::EditionFlagDefault,
+        0x45DD => ElementId::EditionFlagOrdered,
+        0x4520 => ElementId::EditionDisplay,
+        0x4521 => ElementId::EditionString,
+        0x45E4 => ElementId::EditionLanguageIetf,
+        0xB6 => ElementId::ChapterAtom,
+        0x73C4 => ElementId::ChapterUid,
+        0x5654 => ElementId::ChapterStringUid,
+        0x91 => ElementId::ChapterTimeStart,
+        0x92 => ElementId::ChapterTimeEnd,
+        0x80 => ElementId::ChapterDisplay,
+        0x85 => ElementId::ChapString,
+        0x437C => ElementId::ChapLanguage,
+        0x437D => ElementId::ChapLanguageIetf,
+        0x437E => ElementId::ChapCountry,
+        0x6944 => ElementId::ChapProcess,
+        0x6955 => ElementId::ChapProcessCodecId,
+        0x450D => ElementId::ChapProcessPrivate,
+        0x6911 => ElementId::ChapProcessCommand,
+        0x6922 => ElementId::ChapProcessTime,
+        0x6933 => ElementId::ChapProcessData,
+        0x1254C367 => ElementId::Tags,
+        0x7373 => ElementId::Tag,
+        0x63C0 => ElementId::Targets,
+        0x68CA => ElementId::TargetTypeValue,
+        0x63CA => ElementId::TargetType,
+        0x63C5 => ElementId::TagTrackUid,
+        0x63C9 => ElementId::TagEditionUid,
+        0x63C4 => ElementId::TagChapterUid,
+        0x63C6 => ElementId::TagAttachmentUid,
+        0x67C8 => ElementId::SimpleTag,
+        0x45A3 => ElementId::TagName,
+        0x447A => ElementId::TagLanguage,
+        0x4484 => ElementId::TagDefault,
+        0x4487 => ElementId::TagString,
+        0x4485 => ElementId::TagBinary,
+        _ => ElementId::Unknown,
+    }
+}
+
+/// Returns the raw Element ID of the given element. `Unknown` has no ID and returns 0.
+pub(crate) const fn element_id_to_id(id: ElementId) -> u32 {
+    match id {
+        ElementId::Unknown => 0,
+        ElementId::Custom(id) => id,
+        ElementId::Ebml => 0x1A45DFA3,
+        ElementId::EbmlVersion => 0x4286,
+        ElementId::EbmlReadVersion => 0x42F7,
+        ElementId::EbmlMaxIdLength => 0x42F2,
+        ElementId::EbmlMaxSizeLength => 0x42F3,
+        ElementId::DocType => 0x4282,
+        ElementId::DocTypeVersion => 0x4287,
+        ElementId::DocTypeReadVersion => 0x4285,
+        ElementId::Crc32 => 0xBF,
+        ElementId::Void => 0xEC,
+        ElementId::Segment => 0x18538067,
+        ElementId::SeekHead => 0x114D9B74,
+        ElementId::Seek => 0x4DBB,
+        ElementId::SeekId => 0x53AB,
+        ElementId::SeekPosition => 0x53AC,
+        ElementId::Info => 0x1549A966,
+        ElementId::TimestampScale => 0x2AD7B1,
+        ElementId::Duration => 0x4489,
+        ElementId::DateUtc => 0x4461,
+        ElementId::Title => 0x7BA9,
+        ElementId::MuxingApp => 0x4D80,
+        ElementId::WritingApp => 0x5741,
+        ElementId::ChapterTranslate => 0x6924,
+        ElementId::ChapterTranslateId => 0x69A5,
+        ElementId::ChapterTranslateCodec => 0x69BF,
+        ElementId::ChapterTranslateEditionUid => 0x69FC,
+        ElementId::Cluster => 0x1F43B675,
+        ElementId::Timestamp => 0xE7,
+        ElementId::Position => 0xA7,
+        ElementId::PrevSize => 0xAB,
+        ElementId::SilentTracks => 0x5854,
+        ElementId::SilentTrackNumber => 0x58D7,
+        ElementId::SimpleBlock => 0xA3,
+        ElementId::BlockGroup => 0xA0,
+        ElementId::Block => 0xA1,
+        ElementId::BlockAdditions => 0x75A1,
+        ElementId::BlockMore => 0xA6,
+        ElementId::BlockAddId => 0xEE,
+        ElementId::BlockAdditional => 0xA5,
+        ElementId::BlockDuration => 0x9B,
+        ElementId::ReferenceBlock => 0xFB,
+        ElementId::ReferencePriority => 0xFA,
+        ElementId::CodecState => 0xA4,
+        ElementId::DiscardPadding => 0x75A2,
+        ElementId::Tracks => 0x1654AE6B,
+        ElementId::TrackEntry => 0xAE,
+        ElementId::TrackNumber => 0xD7,
+        ElementId::TrackUid => 0x73C5,
+        ElementId::TrackType => 0x83,
+        ElementId::FlagEnabled => 0xB9,
+        ElementId::FlagDefault => 0x88,
+        ElementId::FlagForced => 0x55AA,
+        ElementId::FlagHearingImpaired => 0x55AB,
+        ElementId::FlagVisualImpaired => 0x55AC,
+        ElementId::FlagTextDescriptions => 0x55AD,
+        ElementId::FlagOriginal => 0x55AE,
+        ElementId::FlagCommentary => 0x55AF,
+        ElementId::FlagLacing => 0x9C,
+        ElementId::DefaultDuration => 0x23E383,
+        ElementId::DefaultDecodedFieldDuration => 0x234E7A,
+        ElementId::MinCache => 0x6DE7,
+        ElementId::MaxCache => 0x6DF8,
+        ElementId::MaxBlockAdditionId => 0x55EE,
+        ElementId::Name => 0x536E,
+        ElementId::Language => 0x22B59C,
+        ElementId::LanguageIetf => 0x22B59D,
+        ElementId::CodecId => 0x86,
+        ElementId::CodecPrivate => 0x63A2,
+        ElementId::CodecName => 0x258688,
+        ElementId::CodecDelay => 0x56AA,
+        ElementId::SeekPreRoll => 0x56BB,
+        ElementId::BlockAdditionMapping => 0x41E4,
+        ElementId::BlockAddIdValue => 0x41F0,
+        ElementId::BlockAddIdName => 0x41A4,
+        ElementId::BlockAddIdType => 0x41E7,
+        ElementId::BlockAddIdExtraData => 0x41ED,
+        ElementId::TrackTranslate => 0x6624,
+        ElementId::TrackTranslateTrackId => 0x66A5,
+        ElementId::TrackTranslateCodec => 0x66BF,
+        ElementId::TrackTranslateEditionUid => 0x66FC,
+        ElementId::TrackOperation => 0xE2,
+        ElementId::TrackCombinePlanes => 0xE3,
+        ElementId::TrackPlane => 0xE4,
+        ElementId::TrackPlaneUid => 0xE5,
+        ElementId::TrackPlaneType => 0xE6,
+        ElementId::TrackJoinBlocks => 0xE9,
+        ElementId::TrackJoinUid => 0xED,
+        ElementId::Video => 0xE0,
+        ElementId::FlagInterlaced => 0x9A,
+        ElementId::StereoMode => 0x53B8,
+        ElementId::AlphaMode => 0x53C0,
+        ElementId::PixelWidth => 0xB0,
+        ElementId::PixelHeight => 0xBA,
+        ElementId::PixelCropBottom => 0x54AA,
+        ElementId::PixelCropTop => 0x54BB,
+        ElementId::PixelCropLeft => 0x54CC,
+        ElementId::PixelCropRight => 0x54DD,
+        ElementId::DisplayWidth => 0x54B0,
+        ElementId::DisplayHeight => 0x54BA,
+        ElementId::DisplayUnit => 0x54B2,
+        ElementId::AspectRatioType => 0x54B3,
+        ElementId::Audio => 0xE1,
+        ElementId::SamplingFrequency => 0xB5,
+        ElementId::OutputSamplingFrequency => 0x78B5,
+        ElementId::Channels => 0x9F,
+        ElementId::BitDepth => 0x6264,
+        ElementId::Emphasis => 0x52F1,
+        ElementId::ChannelPositions => 0x7D7B,
+        ElementId::ContentEncodings => 0x6D80,
+        ElementId::ContentEncoding => 0x6240,
+        ElementId::ContentEncodingOrder => 0x5031,
+        ElementId::ContentEncodingScope => 0x5032,
+        ElementId::ContentEncodingType => 0x5033,
+        ElementId::ContentCompression => 0x5034,
+        ElementId::ContentCompAlgo => 0x4254,
+        ElementId::ContentCompSettings => 0x4255,
+        ElementId::ContentEncryption => 0x5035,
+        ElementId::ContentEncAlgo => 0x47E1,
+        ElementId::ContentEncKeyId => 0x47E2,
+        ElementId::ContentEncAesSettings => 0x47E7,
+        ElementId::AesSettingsCipherMode => 0x47E8,
+        ElementId::Colour => 0x55B0,
+        ElementId::MatrixCoefficients => 0x55B1,
+        ElementId::BitsPerChannel => 0x55B2,
+        ElementId::ChromaSubsamplingHorz => 0x55B3,
+        ElementId::ChromaSubsamplingVert => 0x55B4,
+        ElementId::CbSubsamplingHorz => 0x55B5,
+        ElementId::CbSubsamplingVert => 0x55B6,
+        ElementId::ChromaSitingHorz => 0x55B7,
+        ElementId::ChromaSitingVert => 0x55B8,
+        ElementId::Range => 0x55B9,
+        ElementId::TransferCharacteristics => 0x55BA,
+        ElementId::Primaries => 0x55BB,
+        ElementId::MaxCll => 0x55BC,
+        ElementId::MaxFall => 0x55BD,
+        ElementId::MasteringMetadata => 0x55D0,
+        ElementId::PrimaryRChromaticityX => 0x55D1,
+        ElementId::PrimaryRChromaticityY => 0x55D2,
+        ElementId::PrimaryGChromaticityX => 0x55D3,
+        ElementId::PrimaryGChromaticityY => 0x55D4,
+        ElementId::PrimaryBChromaticityX => 0x55D5,
+        ElementId::PrimaryBChromaticityY => 0x55D6,
+        ElementId::WhitePointChromaticityX => 0x55D7,
+        ElementId::WhitePointChromaticityY => 0x55D8,
+        ElementId::LuminanceMax => 0x55D9,
+        ElementId::LuminanceMin => 0x55DA,
+        ElementId::Cues => 0x1C53BB6B,
+        ElementId::CuePoint => 0xBB,
+        ElementId::CueTime => 0xB3,
+        ElementId::CueTrackPositions => 0xB7,
+        ElementId::CueTrack => 0xF7,
+        ElementId::CueClusterPosition => 0xF1,
+        ElementId::CueRelativePosition => 0xF0,
+        ElementId::CueDuration => 0xB2,
+        ElementId::CueBlockNumber => 0x5378,
+        ElementId::CueCodecState => 0xEA,
+        ElementId::CueReference => 0xDB,
+        ElementId::CueRefTime => 0x96,
+        ElementId::Attachments => 0x1941A469,
+        ElementId::AttachedFile => 0x61A7,
+        ElementId::FileDescription => 0x467E,
+        ElementId::FileName => 0x466E,
+        ElementId::FileMediaType => 0x4660,
+        ElementId::FileData => 0x465C,
+        ElementId::FileUid => 0x46AE,
+        ElementId::Chapters => 0x1043A770,
+        ElementId::EditionEntry => 0x45B9,
+        ElementId::EditionUid => 0x45BC,
+        ElementId::EditionFlagHidden => 0x45BD,
+        ElementId::EditionFlagDefault => 0x45DB,
+        ElementId::EditionFlagOrdered => 0x45DD,
+        ElementId::EditionDisplay => 0x4520,
+        ElementId::EditionString => 0x4521,
+        ElementId::EditionLanguageIetf => 0x45E4,
+        ElementId::ChapterAtom => 0xB6,
+        ElementId::ChapterUid => 0x73C4,
+        ElementId::ChapterStringUid => 0x5654,
+        ElementId::ChapterTimeStart => 0x91,
+        ElementId::ChapterTimeEnd => 0x92,
+        ElementId::ChapterDisplay => 0x80,
+        ElementId::ChapString => 0x85,
+        ElementId::ChapLanguage => 0x437C,
+        ElementId::ChapLanguageIetf => 0x437D,
+        ElementId::ChapCountry => 0x437E,
+        ElementId::ChapProcess => 0x6944,
+        ElementId::ChapProcessCodecId => 0x6955,
+        ElementId::ChapProcessPrivate => 0x450D,
+        ElementId::ChapProcessCommand => 0x6911,
+        ElementId::ChapProcessTime => 0x6922,
+        ElementId::ChapProcessData => 0x6933,
+        ElementId::Tags => 0x1254C367,
+        ElementId::Tag => 0x7373,
+        ElementId::Targets => 0x63C0,
+        ElementId::TargetTypeValue => 0x68CA,
+        ElementId::TargetType => 0x63CA,
+        ElementId::TagTrackUid => 0x63C5,
+        ElementId::TagEditionUid => 0x63C9,
+        ElementId::TagChapterUid => 0x63C4,
+        ElementId::TagAttachmentUid => 0x63C6,
+        ElementId::SimpleTag => 0x67C8,
+        ElementId::TagName => 0x45A3,
+        ElementId::TagLanguage => 0x447A,
+        ElementId::TagDefault => 0x4484,
+        ElementId::TagString => 0x4487,
+        ElementId::TagBinary => 0x4485,
+    }
+}
+
+/// Returns the elements that are allowed as the parent of the given element, as defined by the
+/// Matroska EBML schema. An empty slice means that the element is a top level element and `None`
+/// that the element is allowed anywhere.
+pub(crate) fn element_parents(id: ElementId) -> Option<&'static [ElementId]> {
+    match id {
+        ElementId::Crc32 | ElementId::Void | ElementId::Unknown | ElementId::Custom(_) => None,
+        ElementId::Ebml | ElementId::Segment => Some(&[]),
+        ElementId::EbmlVersion
+        | ElementId::EbmlReadVersion
+        | ElementId::EbmlMaxIdLength
+        | ElementId::EbmlMaxSizeLength
+        | ElementId::DocType
+        | ElementId::DocTypeVersion
+        | ElementId::DocTypeReadVersion => Some(&[ElementId::Ebml]),
+        ElementId::SeekHead
+        | ElementId::Info
+        | ElementId::Cluster
+        | ElementId::Tracks
+        | ElementId::Cues
+        | ElementId::Attachments
+        | ElementId::Chapters
+        | ElementId::Tags => Some(&[ElementId::Segment]),
+        ElementId::Seek => Some(&[ElementId::SeekHead]),
+        ElementId::SeekId | ElementId::SeekPosition => Some(&[ElementId::Seek]),
+        ElementId::TimestampScale
+        | ElementId::Duration
+        | ElementId::DateUtc
+        | ElementId::Title
+        | ElementId::MuxingApp
+        | ElementId::WritingApp
+        | ElementId::ChapterTranslate => Some(&[ElementId::Info]),
+        ElementId::ChapterTranslateId
+        | ElementId::ChapterTranslateCodec
+        | ElementId::ChapterTranslateEditionUid => Some(&[ElementId::ChapterTranslate]),
+        ElementId::Timestamp
+        | ElementId::Position
+        | ElementId::PrevSize
+        | ElementId::SilentTracks
+        | ElementId::SimpleBlock
+        | ElementId::BlockGroup => Some(&[ElementId::Cluster]),
+        ElementId::SilentTrackNumber => Some(&[ElementId::SilentTracks]),
+        ElementId::Block
+        | ElementId::BlockAdditions
+        | ElementId::BlockDuration
+        | ElementId::ReferenceBlock
+        | ElementId::ReferencePriority
+        | ElementId::CodecState
+        | ElementId::DiscardPadding => Some(&[ElementId::BlockGroup]),
+        ElementId::BlockMore => Some(&[ElementId::BlockAdditions]),
+        ElementId::BlockAddId | ElementId::BlockAdditional => Some(&[ElementId::BlockMore]),
+        ElementId::TrackEntry => Some(&[ElementId::Tracks]),
+        ElementId::TrackNumber
+        | ElementId::TrackUid
+        | ElementId::TrackType
+        | ElementId::FlagEnabled
+        | ElementId::FlagDefault
+        | ElementId::FlagForced
+        | ElementId::FlagHearingImpaired
+        | ElementId::FlagVisualImpaired
+        | ElementId::FlagTextDescriptions
+        | ElementId::FlagOriginal
+        | ElementId::FlagCommentary
+        | ElementId::FlagLacing
+        | ElementId::DefaultDuration
+        | ElementId::DefaultDecodedFieldDuration
+        | ElementId::MinCache
+        | ElementId::MaxCache
+        | ElementId::MaxBlockAdditionId
+        | ElementId::Name
+        | ElementId::Language
+        | ElementId::LanguageIetf
+        | ElementId::CodecId
+        | ElementId::CodecPrivate
+        | ElementId::CodecName
+        | ElementId::CodecDelay
+        | ElementId::SeekPreRoll
+        | ElementId::BlockAdditionMapping
+        | ElementId::TrackTranslate
+        | ElementId::TrackOperation
+        | ElementId::Video
+        | ElementId::Audio
+        | ElementId::ContentEncodings => Some(&[ElementId::TrackEntry]),
+        ElementId::BlockAddIdValue
+        | ElementId::BlockAddIdName
+        | ElementId::BlockAddIdType
+        | ElementId::BlockAddIdExtraData => Some(&[ElementId::BlockAdditionMapping]),
+        ElementId::TrackTranslateTrackId
+        | ElementId::TrackTranslateCodec
+        | ElementId::TrackTranslateEditionUid => Some(&[ElementId::TrackTranslate]),
+        ElementId::TrackCombinePlanes | ElementId::TrackJoinBlocks => {
+            Some(&[ElementId::TrackOperation])
+        }
+        ElementId::TrackPlane => Some(&[ElementId::TrackCombinePlanes]),
+        ElementId::TrackPlaneUid | ElementId::TrackPlaneType => Some(&[ElementId::TrackPlane]),
+        ElementId::TrackJoinUid => Some(&[ElementId::TrackJoinBlocks]),
+        ElementId::FlagInterlaced
+        | ElementId::StereoMode
+        | ElementId::AlphaMode
+        | ElementId::PixelWidth
+        | ElementId::PixelHeight
+        | ElementId::PixelCropBottom
+        | ElementId::PixelCropTop
+        | ElementId::PixelCropLeft
+        | ElementId::PixelCropRight
+        | ElementId::DisplayWidth
+        | ElementId::DisplayHeight
+        | ElementId::DisplayUnit
+        | ElementId::AspectRatioType
+        | ElementId::Colour => Some(&[ElementId::Video]),
+        ElementId::MatrixCoefficients
+        | ElementId::BitsPerChannel
+        | ElementId::ChromaSubsamplingHorz
+        | ElementId::ChromaSubsamplingVert
+        | ElementId::CbSubsamplingHorz
+        | ElementId::CbSubsamplingVert
+        | ElementId::ChromaSitingHorz
+        | ElementId::ChromaSitingVert
+        | ElementId::Range
+        | ElementId::TransferCharacteristics
+        | ElementId::Primaries
+        | ElementId::MaxCll
+        | ElementId::MaxFall
+        | ElementId::MasteringMetadata => Some(&[ElementId::Colour]),
+        ElementId::PrimaryRChromaticityX
+        | ElementId::PrimaryRChromaticityY
+        | ElementId::PrimaryGChromaticityX
+        | ElementId::PrimaryGChromaticityY
+        | ElementId::PrimaryBChromaticityX
+        | ElementId::PrimaryBChromaticityY
+        | ElementId::WhitePointChromaticityX
+        | ElementId::WhitePointChromaticityY
+        | ElementId::LuminanceMax
+        | ElementId::LuminanceMin => Some(&[ElementId::MasteringMetadata]),
+        ElementId::SamplingFrequency
+        | ElementId::OutputSamplingFrequency
+        | ElementId::Channels
+        | ElementId::BitDepth
+        | ElementId::Emphasis
+        | ElementId::ChannelPositions => Some(&[ElementId::Audio]),
+        ElementId::ContentEncoding => Some(&[ElementId::ContentEncodings]),
+        ElementId::ContentEncodingOrder
+        | ElementId::ContentEncodingScope
+        | ElementId::ContentEncodingType
+        | ElementId::ContentCompression
+        | ElementId::ContentEncryption => Some(&[ElementId::ContentEncoding]),
+        ElementId::ContentCompAlgo | ElementId::ContentCompSettings => {
+            Some(&[ElementId::ContentCompression])
+        }
+        ElementId::ContentEncAlgo
+        | ElementId::ContentEncKeyId
+        | ElementId::ContentEncAesSettings => Some(&[ElementId::ContentEncryption]),
+        ElementId::AesSettingsCipherMode => Some(&[ElementId::ContentEncAesSettings]),
+        ElementId::CuePoint => Some(&[ElementId::Cues]),
+        ElementId::CueTime | ElementId::CueTrackPositions => Some(&[ElementId::CuePoint]),
+        ElementId::CueTrack
+        | ElementId::CueClusterPosition
+        | ElementId::CueRelativePosition
+        | ElementId::CueDuration
+        | ElementId::CueBlockNumber
+        | ElementId::CueCodecState
+        | ElementId::CueReference => Some(&[ElementId::CueTrackPositions]),
+        ElementId::CueRefTime => Some(&[ElementId::CueReference]),
+        ElementId::AttachedFile => Some(&[ElementId::Attachments]),
+        ElementId::FileDescription
+        | ElementId::FileName
+        | ElementId::FileMediaType
+        | ElementId::FileData
+        | ElementId::FileUid => Some(&[ElementId::AttachedFile]),
+        ElementId::EditionEntry => Some(&[ElementId::Chapters]),
+        ElementId::EditionUid
+        | ElementId::EditionFlagHidden
+        | ElementId::EditionFlagDefault
+        | ElementId::EditionFlagOrdered
+        | ElementId::EditionDisplay => Some(&[ElementId::EditionEntry]),
+        ElementId::EditionString | ElementId::EditionLanguageIetf => {
+            Some(&[ElementId::EditionDisplay])
+        }
+        ElementId::ChapterAtom => Some(&[ElementId::EditionEntry, ElementId::ChapterAtom]),
+        ElementId::ChapterUid
+        | ElementId::ChapterStringUid
+        | ElementId::ChapterTimeStart
+        | ElementId::ChapterTimeEnd
+        | ElementId::ChapterDisplay
+        | ElementId::ChapProcess => Some(&[ElementId::ChapterAtom]),
+        ElementId::ChapString
+        | ElementId::ChapLanguage
+        | ElementId::ChapLanguageIetf
+        | ElementId::ChapCountry => Some(&[ElementId::ChapterDisplay]),
+        ElementId::ChapProcessCodecId
+        | ElementId::ChapProcessPrivate
+        | ElementId::ChapProcessCommand => Some(&[ElementId::ChapProcess]),
+        ElementId::ChapProcessTime | ElementId::ChapProcessData => {
+            Some(&[ElementId::ChapProcessCommand])
+        }
+        ElementId::Tag => Some(&[ElementId::Tags]),
+        ElementId::Targets => Some(&[ElementId::Tag]),
+        ElementId::TargetTypeValue
+        | ElementId::TargetType
+        | ElementId::TagTrackUid
+        | ElementId::TagEditionUid
+        | ElementId::TagChapterUid
+        | ElementId::TagAttachmentUid => Some(&[ElementId::Targets]),
+        ElementId::SimpleTag => Some(&[ElementId::Tag, ElementId::SimpleTag]),
+        ElementId::TagName
+        | ElementId::TagLanguage
+        | ElementId::TagDefault
+        | ElementId::TagString
+        | ElementId::TagBinary => Some(&[ElementId::SimpleTag]),
+    }
 }
 
 /// The types of elements an EBML file can have.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
-pub(crate) enum ElementType {
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ElementType {
     /// Unknown element.
     Unknown,
-    /// An element that contains other elements als children.
+    /// An element that contains other elements as children.
     Master,
     /// Unsigned integer.
     Unsigned,
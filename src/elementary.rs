@@ -0,0 +1,345 @@
+//! Helpers to write the frames of a track as an elementary stream, as needed by decoders
+//! and tools that don't understand the framing used inside Matroska.
+
+use std::{
+    io::{Seek, SeekFrom, Write},
+    num::NonZeroU64,
+};
+
+use crate::{
+    byte_reader::ByteReader,
+    codec_config::{AudioSpecificConfig, AAC_SAMPLING_FREQUENCIES},
+    codec_ids, DemuxError, Frame, Result, TrackEntry,
+};
+
+/// The start code that precedes every NAL unit of an Annex B stream.
+const START_CODE: [u8; 4] = [0, 0, 0, 1];
+/// The size of the header of an IVF file.
+const IVF_HEADER_SIZE: u16 = 32;
+/// The offset of the frame count inside the header of an IVF file.
+const IVF_FRAME_COUNT_OFFSET: u64 = 24;
+/// The IVF timestamps are expressed in milliseconds.
+const IVF_TIMESTAMP_SCALE: u64 = 1_000_000;
+
+/// Converts the frames of an AVC or HEVC track to an Annex B byte stream.
+///
+/// Matroska stores NAL units with a length prefix and the parameter sets in the
+/// `CodecPrivate` (`AVCDecoderConfigurationRecord` or `HEVCDecoderConfigurationRecord`).
+/// Annex B streams use start codes instead and carry the parameter sets in band.
+#[derive(Clone, Debug)]
+pub struct AnnexBConverter {
+    length_size: usize,
+    parameter_sets: Vec<u8>,
+}
+
+impl AnnexBConverter {
+    /// Creates a converter for the given track, which must use the `V_MPEG4/ISO/AVC` or
+    /// `V_MPEGH/ISO/HEVC` codec.
+    pub fn from_track(track: &TrackEntry) -> Result<Self> {
+        let codec_private = track
+            .decoded_codec_private()?
+            .ok_or(DemuxError::InvalidCodecData)?;
+        match track.codec_id() {
+            codec_ids::V_MPEG4_ISO_AVC => Self::from_avc_config(&codec_private),
+            codec_ids::V_MPEGH_ISO_HEVC => Self::from_hevc_config(&codec_private),
+            codec_id => Err(DemuxError::UnsupportedCodec(codec_id.to_owned())),
+        }
+    }
+
+    /// Parses an `AVCDecoderConfigurationRecord`.
+    fn from_avc_config(config: &[u8]) -> Result<Self> {
+        let mut reader = ByteReader::new(config);
+        reader.skip(4)?;
+        let length_size = usize::from(reader.u8()? & 0x03) + 1;
+
+        let mut parameter_sets = Vec::new();
+        let sps_count = reader.u8()? & 0x1F;
+        for _ in 0..sps_count {
+            push_nal_unit(&mut parameter_sets, reader.length_prefixed()?);
+        }
+        let pps_count = reader.u8()?;
+        for _ in 0..pps_count {
+            push_nal_unit(&mut parameter_sets, reader.length_prefixed()?);
+        }
+
+        Ok(Self {
+            length_size,
+            parameter_sets,
+        })
+    }
+
+    /// Parses a `HEVCDecoderConfigurationRecord`.
+    fn from_hevc_config(config: &[u8]) -> Result<Self> {
+        let mut reader = ByteReader::new(config);
+        reader.skip(21)?;
+        let length_size = usize::from(reader.u8()? & 0x03) + 1;
+
+        let mut parameter_sets = Vec::new();
+        let array_count = reader.u8()?;
+        for _ in 0..array_count {
+            reader.skip(1)?;
+            let nal_unit_count = reader.u16()?;
+            for _ in 0..nal_unit_count {
+                push_nal_unit(&mut parameter_sets, reader.length_prefixed()?);
+            }
+        }
+
+        Ok(Self {
+            length_size,
+            parameter_sets,
+        })
+    }
+
+    /// The parameter sets of the `CodecPrivate` as Annex B byte stream.
+    pub fn parameter_sets(&self) -> &[u8] {
+        &self.parameter_sets
+    }
+
+    /// Appends the frame as Annex B byte stream to `out`. The parameter sets are written
+    /// before keyframes, so that a decoder can start at every keyframe.
+    pub fn convert(&self, data: &[u8], is_keyframe: bool, out: &mut Vec<u8>) -> Result<()> {
+        if is_keyframe {
+            out.extend_from_slice(&self.parameter_sets);
+        }
+
+        let mut reader = ByteReader::new(data);
+        while !reader.is_empty() {
+            let length = reader
+                .bytes(self.length_size)?
+                .iter()
+                .fold(0_usize, |length, &byte| (length << 8) | usize::from(byte));
+            push_nal_unit(out, reader.bytes(length)?);
+        }
+        Ok(())
+    }
+}
+
+fn push_nal_unit(out: &mut Vec<u8>, nal_unit: &[u8]) {
+    out.extend_from_slice(&START_CODE);
+    out.extend_from_slice(nal_unit);
+}
+
+/// Creates the ADTS headers for the frames of an AAC track, so that they can be written
+/// as `.aac` file.
+#[derive(Clone, Copy, Debug)]
+pub struct AdtsFramer {
+    profile: u8,
+    frequency_index: u8,
+    channel_config: u8,
+}
+
+impl AdtsFramer {
+    /// Creates a framer for the given AAC track.
+    ///
+    /// The configuration is read from the `AudioSpecificConfig` in the `CodecPrivate`. Tracks
+    /// of the legacy codec IDs like `A_AAC/MPEG4/LC` without `CodecPrivate` use the profile of
+    /// the codec ID and the sampling frequency and channels of the track.
+    pub fn from_track(track: &TrackEntry) -> Result<Self> {
+        let codec_id = track.codec_id();
+        if !codec_id.starts_with(codec_ids::A_AAC) {
+            return Err(DemuxError::UnsupportedCodec(codec_id.to_owned()));
+        }
+
+        if let Some(config) = track.decoded_codec_private()? {
+            return Self::from_audio_specific_config(&config);
+        }
+
+        let object_type = match codec_id {
+            codec_ids::A_AAC_MPEG2_MAIN | codec_ids::A_AAC_MPEG4_MAIN => 1,
+            codec_ids::A_AAC_MPEG2_LC
+            | codec_ids::A_AAC_MPEG2_LC_SBR
+            | codec_ids::A_AAC_MPEG4_LC
+            | codec_ids::A_AAC_MPEG4_LC_SBR => 2,
+            codec_ids::A_AAC_MPEG2_SSR | codec_ids::A_AAC_MPEG4_SSR => 3,
+            codec_ids::A_AAC_MPEG4_LTP => 4,
+            _ => return Err(DemuxError::InvalidCodecData),
+        };
+        let audio = track.audio().ok_or(DemuxError::InvalidCodecData)?;
+        let frequency_index = AAC_SAMPLING_FREQUENCIES
+            .iter()
+            .position(|&frequency| (frequency - audio.sampling_frequency()).abs() < 1.0)
+            .ok_or(DemuxError::InvalidCodecData)?;
+
+        Ok(Self {
+            profile: object_type - 1,
+            frequency_index: u8::try_from(frequency_index)?,
+            channel_config: u8::try_from(audio.channels().get())
+                .ok()
+                .filter(|&channels| channels < 8)
+                .ok_or(DemuxError::InvalidCodecData)?,
+        })
+    }
+
+    fn from_audio_specific_config(config: &[u8]) -> Result<Self> {
+        let config = AudioSpecificConfig::parse(config)?;
+        let object_type = match config.object_type {
+            // HE-AAC signals its core with the sampling frequency of the LC layer.
+            5 | 29 => 2,
+            object_type @ 1..=4 => object_type,
+            _ => return Err(DemuxError::InvalidCodecData),
+        };
+        // Explicit sampling frequencies and channel layouts can't be signaled in an ADTS
+        // header.
+        if usize::from(config.frequency_index) >= AAC_SAMPLING_FREQUENCIES.len()
+            || config.channel_config >= 8
+        {
+            return Err(DemuxError::InvalidCodecData);
+        }
+
+        Ok(Self {
+            profile: object_type - 1,
+            frequency_index: config.frequency_index,
+            channel_config: config.channel_config,
+        })
+    }
+
+    /// Returns the ADTS header for a frame with the given size.
+    pub fn header(&self, frame_size: usize) -> Result<[u8; 7]> {
+        let length = frame_size + 7;
+        if length >= 1 << 13 {
+            return Err(DemuxError::InvalidCodecData);
+        }
+        let length = u16::try_from(length)?;
+        let [length_high, length_low] = length.to_be_bytes();
+
+        Ok([
+            0xFF,
+            0xF1,
+            (self.profile << 6) | (self.frequency_index << 2) | (self.channel_config >> 2),
+            ((self.channel_config & 0x03) << 6) | (length_high >> 3),
+            (length_high << 5) | (length_low >> 3),
+            ((length_low & 0x07) << 5) | 0x1F,
+            0xFC,
+        ])
+    }
+
+    /// Appends the frame together with its ADTS header to `out`.
+    pub fn convert(&self, data: &[u8], out: &mut Vec<u8>) -> Result<()> {
+        out.extend_from_slice(&self.header(data.len())?);
+        out.extend_from_slice(data);
+        Ok(())
+    }
+}
+
+/// Writes the frames of a VP8, VP9 or AV1 track as IVF file.
+///
+/// The timestamps of the frames are written in milliseconds. The frame count of the header
+/// is written by `finish()`, which needs a seekable writer.
+#[derive(Debug)]
+pub struct IvfWriter<W: Write> {
+    writer: W,
+    frame_count: u32,
+    /// The number of bytes written, including the header.
+    written: u64,
+}
+
+impl<W: Write> IvfWriter<W> {
+    /// Writes the IVF header for the given track, which must use the `V_VP8`, `V_VP9` or
+    /// `V_AV1` codec.
+    pub fn new(mut writer: W, track: &TrackEntry) -> Result<Self> {
+        let fourcc = match track.codec_id() {
+            codec_ids::V_VP8 => b"VP80",
+            codec_ids::V_VP9 => b"VP90",
+            codec_ids::V_AV1 => b"AV01",
+            codec_id => return Err(DemuxError::UnsupportedCodec(codec_id.to_owned())),
+        };
+        let video = track.video().ok_or(DemuxError::InvalidCodecData)?;
+        let width = u16::try_from(video.pixel_width().get())?;
+        let height = u16::try_from(video.pixel_height().get())?;
+
+        let mut header = Vec::with_capacity(usize::from(IVF_HEADER_SIZE));
+        header.extend_from_slice(b"DKIF");
+        header.extend_from_slice(&0_u16.to_le_bytes());
+        header.extend_from_slice(&IVF_HEADER_SIZE.to_le_bytes());
+        header.extend_from_slice(fourcc);
+        header.extend_from_slice(&width.to_le_bytes());
+        header.extend_from_slice(&height.to_le_bytes());
+        header.extend_from_slice(&1000_u32.to_le_bytes());
+        header.extend_from_slice(&1_u32.to_le_bytes());
+        header.extend_from_slice(&0_u32.to_le_bytes());
+        header.extend_from_slice(&0_u32.to_le_bytes());
+        writer.write_all(&header)?;
+
+        Ok(Self {
+            writer,
+            frame_count: 0,
+            written: u64::from(IVF_HEADER_SIZE),
+        })
+    }
+
+    /// Writes the given frame.
+    pub fn write_frame(&mut self, frame: &Frame) -> Result<()> {
+        let scale = NonZeroU64::new(IVF_TIMESTAMP_SCALE).ok_or(DemuxError::InvalidCodecData)?;
+        let timestamp = u64::try_from(frame.time().rescale(scale).ticks()).unwrap_or(0);
+
+        self.writer
+            .write_all(&u32::try_from(frame.data.len())?.to_le_bytes())?;
+        self.writer.write_all(&timestamp.to_le_bytes())?;
+        self.writer.write_all(&frame.data)?;
+        self.frame_count = self.frame_count.saturating_add(1);
+        self.written += 12 + u64::try_from(frame.data.len())?;
+        Ok(())
+    }
+
+    /// The number of frames that were written.
+    pub fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    /// Returns the writer without updating the frame count of the header.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write + Seek> IvfWriter<W> {
+    /// Writes the frame count into the header and returns the writer.
+    pub fn finish(mut self) -> Result<W> {
+        let end = self.writer.stream_position()?;
+        let start = end.saturating_sub(self.written);
+        self.writer
+            .seek(SeekFrom::Start(start + IVF_FRAME_COUNT_OFFSET))?;
+        self.writer.write_all(&self.frame_count.to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(end))?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annex_b_converter() -> Result<()> {
+        let config = [
+            0x01, 0x64, 0x00, 0x1F, 0xFF, // 4 byte NAL unit lengths.
+            0xE1, 0x00, 0x02, 0x67, 0x64, // One SPS.
+            0x01, 0x00, 0x02, 0x68, 0xEE, // One PPS.
+        ];
+        let converter = AnnexBConverter::from_avc_config(&config)?;
+        assert_eq!(
+            converter.parameter_sets(),
+            [0, 0, 0, 1, 0x67, 0x64, 0, 0, 0, 1, 0x68, 0xEE]
+        );
+
+        let mut out = Vec::new();
+        converter.convert(&[0, 0, 0, 2, 0x41, 0x9A, 0, 0, 0, 1, 0x06], false, &mut out)?;
+        assert_eq!(out, [0, 0, 0, 1, 0x41, 0x9A, 0, 0, 0, 1, 0x06]);
+        assert!(converter
+            .convert(&[0, 0, 0, 9, 0x41], false, &mut out)
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_adts_header() -> Result<()> {
+        // AAC LC with 44.1 kHz and two channels.
+        let framer = AdtsFramer::from_audio_specific_config(&[0x12, 0x10])?;
+        assert_eq!(
+            framer.header(100)?,
+            [0xFF, 0xF1, 0x50, 0x80, 0x0D, 0x7F, 0xFC]
+        );
+        assert!(framer.header(8192).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,245 @@
+//! Decryption of frames that are encrypted following the WebM encryption specification.
+
+use std::{ops::Range, sync::Arc};
+
+use crate::{DemuxError, Result};
+
+/// The signal byte bit that marks an encrypted frame.
+const SIGNAL_ENCRYPTED: u8 = 0x01;
+/// The signal byte bit that marks a partitioned frame.
+const SIGNAL_PARTITIONED: u8 = 0x02;
+/// The size of the IV of an encrypted frame.
+const IV_SIZE: usize = 8;
+
+/// Decrypts the frames of an encrypted track.
+///
+/// Encrypted WebM frames start with a signal byte, followed by an IV and optional partition
+/// offsets if the frame is encrypted. The demuxer parses and strips this header and only
+/// passes the encrypted parts of the frame to the decryptor. This crate doesn't bundle any
+/// cryptography itself.
+pub trait ContentDecryptor: Send + Sync {
+    /// Decrypts the given data in place with AES-CTR.
+    ///
+    /// The 16 byte counter block is the 8 byte IV followed by an 8 byte block counter,
+    /// which starts at zero for every frame. `key_id` is the `ContentEncKeyID` of the track.
+    fn decrypt(
+        &self,
+        key_id: &[u8],
+        iv: [u8; IV_SIZE],
+        data: &mut [u8],
+    ) -> std::result::Result<(), DemuxError>;
+}
+
+/// A registered decryptor.
+#[derive(Clone)]
+pub(crate) struct DecryptorHandle(pub(crate) Arc<dyn ContentDecryptor>);
+
+impl std::fmt::Debug for DecryptorHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("DecryptorHandle")
+    }
+}
+
+/// The encryption header of a frame of an encrypted track.
+///
+/// Encrypted WebM frames start with a signal byte. If the frame is encrypted, the signal
+/// byte is followed by the IV and optional partition offsets.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FrameEncryptionInfo {
+    /// The IV of the frame. Only set if the frame is encrypted.
+    pub iv: Option<[u8; IV_SIZE]>,
+    /// The offsets of the partitions inside the frame data. The partitions alternate between
+    /// clear and encrypted data, starting with clear data.
+    ///
+    /// Empty if the whole frame is encrypted.
+    pub partitions: Vec<u32>,
+}
+
+impl FrameEncryptionInfo {
+    /// Returns true if the frame is encrypted.
+    pub fn is_encrypted(&self) -> bool {
+        self.iv.is_some()
+    }
+}
+
+/// Parses the encryption header at the start of the frame data. Returns the header and
+/// its size in bytes.
+fn parse_signal_header(data: &[u8]) -> Result<(FrameEncryptionInfo, usize)> {
+    let signal = *data.first().ok_or(DemuxError::InvalidEncryptionSignal)?;
+    if signal & SIGNAL_ENCRYPTED == 0 {
+        return Ok((FrameEncryptionInfo::default(), 1));
+    }
+
+    let mut iv = [0u8; IV_SIZE];
+    iv.copy_from_slice(
+        data.get(1..=IV_SIZE)
+            .ok_or(DemuxError::InvalidEncryptionSignal)?,
+    );
+    let mut size = 1 + IV_SIZE;
+
+    let mut partitions = Vec::new();
+    if signal & SIGNAL_PARTITIONED != 0 {
+        let count = *data.get(size).ok_or(DemuxError::InvalidEncryptionSignal)?;
+        size += 1;
+        for _ in 0..count {
+            let bytes = data
+                .get(size..size + 4)
+                .ok_or(DemuxError::InvalidEncryptionSignal)?;
+            partitions.push(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]));
+            size += 4;
+        }
+    }
+
+    let info = FrameEncryptionInfo {
+        iv: Some(iv),
+        partitions,
+    };
+    Ok((info, size))
+}
+
+/// Returns the encrypted ranges of a payload with the given partition offsets.
+fn encrypted_ranges(partitions: &[u32], len: usize) -> Result<Vec<Range<usize>>> {
+    let mut ranges = Vec::with_capacity(partitions.len() / 2 + 1);
+    let mut start = 0;
+    for (i, end) in partitions.iter().enumerate() {
+        let end = usize::try_from(*end)?;
+        if end < start || end > len {
+            return Err(DemuxError::InvalidEncryptionSignal);
+        }
+        if i % 2 == 1 {
+            ranges.push(start..end);
+        }
+        start = end;
+    }
+    if partitions.len() % 2 == 1 {
+        ranges.push(start..len);
+    }
+    Ok(ranges)
+}
+
+/// Strips the encryption header of the frame and decrypts it in place, if a decryptor
+/// is given.
+fn strip_payload(
+    decryptor: Option<&dyn ContentDecryptor>,
+    key_id: &[u8],
+    data: &mut Vec<u8>,
+) -> Result<FrameEncryptionInfo> {
+    let (info, size) = parse_signal_header(data)?;
+    data.drain(..size);
+
+    let (decryptor, iv) = match (decryptor, info.iv) {
+        (Some(decryptor), Some(iv)) => (decryptor, iv),
+        _ => return Ok(info),
+    };
+
+    if info.partitions.is_empty() {
+        decryptor.decrypt(key_id, iv, data)?;
+        return Ok(info);
+    }
+
+    // The encrypted partitions form a single continuous AES-CTR stream.
+    let ranges = encrypted_ranges(&info.partitions, data.len())?;
+    let mut encrypted = Vec::with_capacity(data.len());
+    for range in ranges.iter() {
+        encrypted.extend_from_slice(&data[range.clone()]);
+    }
+    decryptor.decrypt(key_id, iv, &mut encrypted)?;
+    let mut decrypted = encrypted.as_slice();
+    for range in ranges {
+        let (part, rest) = decrypted.split_at(range.len());
+        data[range].copy_from_slice(part);
+        decrypted = rest;
+    }
+
+    Ok(info)
+}
+
+/// Strips the encryption header of a frame and decrypts it, if a decryptor is given.
+///
+/// If the frame contains multiple laced frames, each of them is processed on its own and
+/// the lace ranges are updated. The encryption header of the first laced frame is returned.
+pub(crate) fn strip_frame(
+    decryptor: Option<&dyn ContentDecryptor>,
+    key_id: &[u8],
+    data: &mut Vec<u8>,
+    lace_ranges: &mut [Range<usize>],
+) -> Result<FrameEncryptionInfo> {
+    if lace_ranges.is_empty() {
+        return strip_payload(decryptor, key_id, data);
+    }
+
+    let mut first_info = None;
+    let mut stripped = Vec::with_capacity(data.len());
+    let mut payload = Vec::new();
+    for range in lace_ranges.iter_mut() {
+        payload.clear();
+        payload.extend_from_slice(
+            data.get(range.clone())
+                .ok_or(DemuxError::UnexpectedDataType)?,
+        );
+        let info = strip_payload(decryptor, key_id, &mut payload)?;
+        first_info.get_or_insert(info);
+        let start = stripped.len();
+        stripped.extend_from_slice(&payload);
+        *range = start..stripped.len();
+    }
+    *data = stripped;
+
+    Ok(first_info.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod xor_decryptor {
+        include!("../tests/common/xor_decryptor.rs");
+    }
+    use xor_decryptor::XorDecryptor;
+
+    #[test]
+    fn test_strip_frame() -> Result<()> {
+        let mut encrypted = vec![
+            0x03, 0x20, 0, 0, 0, 0, 0, 0, 0, 0x01, 0, 0, 0, 0x01, b'a', b'B',
+        ];
+        let info = strip_frame(None, b"key", &mut encrypted, &mut [])?;
+        assert_eq!(encrypted, b"aB");
+        assert_eq!(info.iv, Some([0x20, 0, 0, 0, 0, 0, 0, 0]));
+        assert_eq!(info.partitions, [1]);
+
+        let mut clear = vec![0x00, b'a'];
+        let info = strip_frame(None, b"key", &mut clear, &mut [])?;
+        assert_eq!(clear, b"a");
+        assert!(!info.is_encrypted());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decrypt_frame() -> Result<()> {
+        let mut clear = vec![0x00, b'a', b'b'];
+        strip_frame(Some(&XorDecryptor), b"key", &mut clear, &mut [])?;
+        assert_eq!(clear, b"ab");
+
+        let mut encrypted = vec![0x01, 0x20, 0, 0, 0, 0, 0, 0, 0, b'A', b'B'];
+        strip_frame(Some(&XorDecryptor), b"key", &mut encrypted, &mut [])?;
+        assert_eq!(encrypted, b"ab");
+
+        // One partition at offset 1: the first byte is clear, the rest is encrypted.
+        let mut partitioned = vec![
+            0x03, 0x20, 0, 0, 0, 0, 0, 0, 0, 0x01, 0, 0, 0, 0x01, b'a', b'B', b'C',
+        ];
+        strip_frame(Some(&XorDecryptor), b"key", &mut partitioned, &mut [])?;
+        assert_eq!(partitioned, b"abc");
+
+        let mut laced = vec![0x00, b'a', 0x01, 0x20, 0, 0, 0, 0, 0, 0, 0, b'B'];
+        let mut lace_ranges = [0..2, 2..12];
+        strip_frame(Some(&XorDecryptor), b"key", &mut laced, &mut lace_ranges)?;
+        assert_eq!(laced, b"ab");
+        assert_eq!(lace_ranges, [0..1, 1..2]);
+
+        assert!(strip_frame(Some(&XorDecryptor), b"key", &mut vec![0x01, 0x20], &mut []).is_err());
+
+        Ok(())
+    }
+}
@@ -1,13 +1,52 @@
 //! Enums exposed in the API.
 
+use std::{
+    fmt,
+    ops::{BitAnd, BitOr, BitOrAssign},
+    str::FromStr,
+};
+
+use crate::DemuxError;
+
+/// Implements `Display` and `FromStr` for an enum with the given names.
+///
+/// Unsupported values are written as their number. Parsing a number maps it back to the
+/// variant that covers the value.
+macro_rules! enum_names {
+    ($name:ident { $($variant:ident => $text:literal,)* }) => {
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self {
+                    $($name::$variant => f.write_str($text),)*
+                    $name::Unsupported(value) => write!(f, "{}", value),
+                }
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = DemuxError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    $($text => Ok($name::$variant),)*
+                    _ => s
+                        .parse::<u64>()
+                        .map($name::from)
+                        .map_err(|_| DemuxError::InvalidEnumName(s.to_owned())),
+                }
+            }
+        }
+    };
+}
+
 /// The Matrix Coefficients of the video used to derive luma and chroma values
 /// from red, green, and blue color primaries.
 ///
 /// For clarity, the value and meanings for `MatrixCoefficients` are adopted from
 /// Table 4 of ISO/IEC 23001-8:2016 or ITU-T H.273.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum MatrixCoefficients {
-    /// Unknown,
+    /// Unknown.
     Unknown,
     /// Identity.
     Identity,
@@ -35,6 +74,8 @@ pub enum MatrixCoefficients {
     ChromaDerivedCl,
     /// ITU-R BT.2100-0.
     Bt2100,
+    /// A value that is not covered by the specification.
+    Unsupported(u64),
 }
 
 impl From<u64> for MatrixCoefficients {
@@ -42,6 +83,7 @@ impl From<u64> for MatrixCoefficients {
         match d {
             0 => MatrixCoefficients::Identity,
             1 => MatrixCoefficients::Bt709,
+            2 => MatrixCoefficients::Unknown,
             4 => MatrixCoefficients::Fcc73682,
             5 => MatrixCoefficients::Bt470bg,
             6 => MatrixCoefficients::Smpte170,
@@ -53,13 +95,52 @@ impl From<u64> for MatrixCoefficients {
             12 => MatrixCoefficients::ChromaDerivedNcl,
             13 => MatrixCoefficients::ChromaDerivedCl,
             14 => MatrixCoefficients::Bt2100,
-            _ => MatrixCoefficients::Unknown,
+            d => MatrixCoefficients::Unsupported(d),
+        }
+    }
+}
+
+impl From<MatrixCoefficients> for u64 {
+    fn from(d: MatrixCoefficients) -> Self {
+        match d {
+            MatrixCoefficients::Identity => 0,
+            MatrixCoefficients::Bt709 => 1,
+            MatrixCoefficients::Unknown => 2,
+            MatrixCoefficients::Fcc73682 => 4,
+            MatrixCoefficients::Bt470bg => 5,
+            MatrixCoefficients::Smpte170 => 6,
+            MatrixCoefficients::Smpte240 => 7,
+            MatrixCoefficients::YCoCg => 8,
+            MatrixCoefficients::Bt2020Ncl => 9,
+            MatrixCoefficients::Bt2020Cl => 10,
+            MatrixCoefficients::SmpteSt2085 => 11,
+            MatrixCoefficients::ChromaDerivedNcl => 12,
+            MatrixCoefficients::ChromaDerivedCl => 13,
+            MatrixCoefficients::Bt2100 => 14,
+            MatrixCoefficients::Unsupported(d) => d,
         }
     }
 }
 
+enum_names!(MatrixCoefficients {
+    Unknown => "unknown",
+    Identity => "identity",
+    Bt709 => "bt709",
+    Fcc73682 => "fcc73682",
+    Bt470bg => "bt470bg",
+    Smpte170 => "smpte170",
+    Smpte240 => "smpte240",
+    YCoCg => "ycocg",
+    Bt2020Ncl => "bt2020_ncl",
+    Bt2020Cl => "bt2020_cl",
+    SmpteSt2085 => "smpte_st2085",
+    ChromaDerivedNcl => "chroma_derived_ncl",
+    ChromaDerivedCl => "chroma_derived_cl",
+    Bt2100 => "bt2100",
+});
+
 /// How `DisplayWidth` & `DisplayHeight` are interpreted.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum DisplayUnit {
     /// In pixels.
     Pixels,
@@ -71,6 +152,8 @@ pub enum DisplayUnit {
     DisplayAspectRatio,
     /// Unknown.
     Unknown,
+    /// A value that is not covered by the specification.
+    Unsupported(u64),
 }
 
 impl From<u64> for DisplayUnit {
@@ -80,22 +163,44 @@ impl From<u64> for DisplayUnit {
             1 => DisplayUnit::Centimeters,
             2 => DisplayUnit::Inches,
             3 => DisplayUnit::DisplayAspectRatio,
-            _ => DisplayUnit::Unknown,
+            4 => DisplayUnit::Unknown,
+            d => DisplayUnit::Unsupported(d),
         }
     }
 }
 
+impl From<DisplayUnit> for u64 {
+    fn from(d: DisplayUnit) -> Self {
+        match d {
+            DisplayUnit::Pixels => 0,
+            DisplayUnit::Centimeters => 1,
+            DisplayUnit::Inches => 2,
+            DisplayUnit::DisplayAspectRatio => 3,
+            DisplayUnit::Unknown => 4,
+            DisplayUnit::Unsupported(d) => d,
+        }
+    }
+}
+
+enum_names!(DisplayUnit {
+    Pixels => "pixels",
+    Centimeters => "centimeters",
+    Inches => "inches",
+    DisplayAspectRatio => "display_aspect_ratio",
+    Unknown => "unknown",
+});
+
 /// Specify the possible modifications to the aspect ratio.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum AspectRatioType {
-    /// Unknown.
-    Unknown,
     /// Allow free resizing.
     FreeResizing,
     /// Keep the aspect ratio.
     KeepAspectRatio,
     /// Fixed size.
     Fixed,
+    /// A value that is not covered by the specification.
+    Unsupported(u64),
 }
 
 impl From<u64> for AspectRatioType {
@@ -104,16 +209,31 @@ impl From<u64> for AspectRatioType {
             0 => AspectRatioType::FreeResizing,
             1 => AspectRatioType::KeepAspectRatio,
             2 => AspectRatioType::Fixed,
-            _ => AspectRatioType::Unknown,
+            d => AspectRatioType::Unsupported(d),
+        }
+    }
+}
+
+impl From<AspectRatioType> for u64 {
+    fn from(d: AspectRatioType) -> Self {
+        match d {
+            AspectRatioType::FreeResizing => 0,
+            AspectRatioType::KeepAspectRatio => 1,
+            AspectRatioType::Fixed => 2,
+            AspectRatioType::Unsupported(d) => d,
         }
     }
 }
 
+enum_names!(AspectRatioType {
+    FreeResizing => "free_resizing",
+    KeepAspectRatio => "keep_aspect_ratio",
+    Fixed => "fixed",
+});
+
 /// Type of the track.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum TrackType {
-    /// Unknown.
-    Unknown,
     /// Video track.
     Video,
     /// Audio track.
@@ -130,6 +250,8 @@ pub enum TrackType {
     Control,
     /// Metadata.
     Metadata,
+    /// A value that is not covered by the specification.
+    Unsupported(u64),
 }
 
 impl From<u64> for TrackType {
@@ -143,13 +265,80 @@ impl From<u64> for TrackType {
             18 => TrackType::Buttons,
             32 => TrackType::Control,
             33 => TrackType::Metadata,
-            _ => TrackType::Unknown,
+            d => TrackType::Unsupported(d),
+        }
+    }
+}
+
+impl From<TrackType> for u64 {
+    fn from(d: TrackType) -> Self {
+        match d {
+            TrackType::Video => 1,
+            TrackType::Audio => 2,
+            TrackType::Complex => 3,
+            TrackType::Logo => 16,
+            TrackType::Subtitle => 17,
+            TrackType::Buttons => 18,
+            TrackType::Control => 32,
+            TrackType::Metadata => 33,
+            TrackType::Unsupported(d) => d,
+        }
+    }
+}
+
+enum_names!(TrackType {
+    Video => "video",
+    Audio => "audio",
+    Complex => "complex",
+    Logo => "logo",
+    Subtitle => "subtitle",
+    Buttons => "buttons",
+    Control => "control",
+    Metadata => "metadata",
+});
+
+/// The kind of plane a track represents in a `TrackOperation`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TrackPlaneType {
+    /// The left eye of a stereo 3D video.
+    LeftEye,
+    /// The right eye of a stereo 3D video.
+    RightEye,
+    /// The background of a video.
+    Background,
+    /// A value that is not covered by the specification.
+    Unsupported(u64),
+}
+
+impl From<u64> for TrackPlaneType {
+    fn from(d: u64) -> Self {
+        match d {
+            0 => TrackPlaneType::LeftEye,
+            1 => TrackPlaneType::RightEye,
+            2 => TrackPlaneType::Background,
+            d => TrackPlaneType::Unsupported(d),
+        }
+    }
+}
+
+impl From<TrackPlaneType> for u64 {
+    fn from(d: TrackPlaneType) -> Self {
+        match d {
+            TrackPlaneType::LeftEye => 0,
+            TrackPlaneType::RightEye => 1,
+            TrackPlaneType::Background => 2,
+            TrackPlaneType::Unsupported(d) => d,
         }
     }
 }
 
+enum_names!(TrackPlaneType {
+    LeftEye => "left_eye",
+    RightEye => "right_eye",
+    Background => "background",
+});
 /// A flag to declare if the video is known to be progressive or interlaced.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum FlagInterlaced {
     /// Unknown.
     Unknown,
@@ -157,20 +346,40 @@ pub enum FlagInterlaced {
     Interlaced,
     /// Progressive.
     Progressive,
+    /// A value that is not covered by the specification.
+    Unsupported(u64),
 }
 
 impl From<u64> for FlagInterlaced {
     fn from(d: u64) -> Self {
         match d {
+            0 => FlagInterlaced::Unknown,
             1 => FlagInterlaced::Interlaced,
             2 => FlagInterlaced::Progressive,
-            _ => FlagInterlaced::Unknown,
+            d => FlagInterlaced::Unsupported(d),
         }
     }
 }
 
+impl From<FlagInterlaced> for u64 {
+    fn from(d: FlagInterlaced) -> Self {
+        match d {
+            FlagInterlaced::Unknown => 0,
+            FlagInterlaced::Interlaced => 1,
+            FlagInterlaced::Progressive => 2,
+            FlagInterlaced::Unsupported(d) => d,
+        }
+    }
+}
+
+enum_names!(FlagInterlaced {
+    Unknown => "unknown",
+    Interlaced => "interlaced",
+    Progressive => "progressive",
+});
+
 /// Declare the field ordering of the video.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum FieldOrder {
     /// Unknown.
     Unknown,
@@ -184,6 +393,8 @@ pub enum FieldOrder {
     BffSwapped,
     /// Bottom Field First (swapped).
     TffSwapped,
+    /// A value that is not covered by the specification.
+    Unsupported(u64),
 }
 
 impl From<u64> for FieldOrder {
@@ -191,19 +402,41 @@ impl From<u64> for FieldOrder {
         match d {
             0 => FieldOrder::Progressive,
             1 => FieldOrder::Tff,
+            2 => FieldOrder::Unknown,
             6 => FieldOrder::Bff,
             9 => FieldOrder::BffSwapped,
             14 => FieldOrder::TffSwapped,
-            _ => FieldOrder::Unknown,
+            d => FieldOrder::Unsupported(d),
         }
     }
 }
 
+impl From<FieldOrder> for u64 {
+    fn from(d: FieldOrder) -> Self {
+        match d {
+            FieldOrder::Progressive => 0,
+            FieldOrder::Tff => 1,
+            FieldOrder::Unknown => 2,
+            FieldOrder::Bff => 6,
+            FieldOrder::BffSwapped => 9,
+            FieldOrder::TffSwapped => 14,
+            FieldOrder::Unsupported(d) => d,
+        }
+    }
+}
+
+enum_names!(FieldOrder {
+    Unknown => "unknown",
+    Progressive => "progressive",
+    Tff => "tff",
+    Bff => "bff",
+    BffSwapped => "bff_swapped",
+    TffSwapped => "tff_swapped",
+});
+
 /// Stereo-3D video mode.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum StereoMode {
-    /// Unknown.
-    Unknown,
     /// Mono.
     Mono,
     /// Side by side (left eye first).
@@ -234,6 +467,8 @@ pub enum StereoMode {
     LacedLeftEyeFirst,
     /// Both eyes laced in one Block (right eye is first).
     LacedRightEyeFirst,
+    /// A value that is not covered by the specification.
+    Unsupported(u64),
 }
 
 impl From<u64> for StereoMode {
@@ -254,13 +489,54 @@ impl From<u64> for StereoMode {
             12 => StereoMode::AnaglyphGreenMagenta,
             13 => StereoMode::LacedLeftEyeFirst,
             14 => StereoMode::LacedRightEyeFirst,
-            _ => StereoMode::Unknown,
+            d => StereoMode::Unsupported(d),
         }
     }
 }
 
+impl From<StereoMode> for u64 {
+    fn from(d: StereoMode) -> Self {
+        match d {
+            StereoMode::Mono => 0,
+            StereoMode::SideBySideLeftEyeFirst => 1,
+            StereoMode::TopBottomRightEyeFirst => 2,
+            StereoMode::TopBottomLeftEyeFirst => 3,
+            StereoMode::CheckboardRightEyeFirst => 4,
+            StereoMode::CheckboardLeftEyeFirst => 5,
+            StereoMode::RowInterleavedRightEyeFirst => 6,
+            StereoMode::RowInterleavedLeftEyeFirst => 7,
+            StereoMode::ColumnInterleavedRightEyeFirst => 8,
+            StereoMode::ColumnInterleavedLeftEyeFirst => 9,
+            StereoMode::AnaglyphCyanRed => 10,
+            StereoMode::SideBySideRightEyeFirst => 11,
+            StereoMode::AnaglyphGreenMagenta => 12,
+            StereoMode::LacedLeftEyeFirst => 13,
+            StereoMode::LacedRightEyeFirst => 14,
+            StereoMode::Unsupported(d) => d,
+        }
+    }
+}
+
+enum_names!(StereoMode {
+    Mono => "mono",
+    SideBySideLeftEyeFirst => "side_by_side_left_eye_first",
+    TopBottomRightEyeFirst => "top_bottom_right_eye_first",
+    TopBottomLeftEyeFirst => "top_bottom_left_eye_first",
+    CheckboardRightEyeFirst => "checkboard_right_eye_first",
+    CheckboardLeftEyeFirst => "checkboard_left_eye_first",
+    RowInterleavedRightEyeFirst => "row_interleaved_right_eye_first",
+    RowInterleavedLeftEyeFirst => "row_interleaved_left_eye_first",
+    ColumnInterleavedRightEyeFirst => "column_interleaved_right_eye_first",
+    ColumnInterleavedLeftEyeFirst => "column_interleaved_left_eye_first",
+    AnaglyphCyanRed => "anaglyph_cyan_red",
+    SideBySideRightEyeFirst => "side_by_side_right_eye_first",
+    AnaglyphGreenMagenta => "anaglyph_green_magenta",
+    LacedLeftEyeFirst => "laced_left_eye_first",
+    LacedRightEyeFirst => "laced_right_eye_first",
+});
+
 /// How chroma is sub sampled horizontally.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum ChromaSitingHorz {
     /// Unknown.
     Unknown,
@@ -268,20 +544,40 @@ pub enum ChromaSitingHorz {
     LeftCollated,
     /// Half.
     Half,
+    /// A value that is not covered by the specification.
+    Unsupported(u64),
 }
 
 impl From<u64> for ChromaSitingHorz {
     fn from(d: u64) -> Self {
         match d {
+            0 => ChromaSitingHorz::Unknown,
             1 => ChromaSitingHorz::LeftCollated,
             2 => ChromaSitingHorz::Half,
-            _ => ChromaSitingHorz::Unknown,
+            d => ChromaSitingHorz::Unsupported(d),
         }
     }
 }
 
+impl From<ChromaSitingHorz> for u64 {
+    fn from(d: ChromaSitingHorz) -> Self {
+        match d {
+            ChromaSitingHorz::Unknown => 0,
+            ChromaSitingHorz::LeftCollated => 1,
+            ChromaSitingHorz::Half => 2,
+            ChromaSitingHorz::Unsupported(d) => d,
+        }
+    }
+}
+
+enum_names!(ChromaSitingHorz {
+    Unknown => "unknown",
+    LeftCollated => "left_collated",
+    Half => "half",
+});
+
 /// How chroma is sub sampled vertically.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum ChromaSitingVert {
     /// Unknown.
     Unknown,
@@ -289,20 +585,40 @@ pub enum ChromaSitingVert {
     LeftCollated,
     /// Half.
     Half,
+    /// A value that is not covered by the specification.
+    Unsupported(u64),
 }
 
 impl From<u64> for ChromaSitingVert {
     fn from(d: u64) -> Self {
         match d {
+            0 => ChromaSitingVert::Unknown,
             1 => ChromaSitingVert::LeftCollated,
             2 => ChromaSitingVert::Half,
-            _ => ChromaSitingVert::Unknown,
+            d => ChromaSitingVert::Unsupported(d),
+        }
+    }
+}
+
+impl From<ChromaSitingVert> for u64 {
+    fn from(d: ChromaSitingVert) -> Self {
+        match d {
+            ChromaSitingVert::Unknown => 0,
+            ChromaSitingVert::LeftCollated => 1,
+            ChromaSitingVert::Half => 2,
+            ChromaSitingVert::Unsupported(d) => d,
         }
     }
 }
 
+enum_names!(ChromaSitingVert {
+    Unknown => "unknown",
+    LeftCollated => "left_collated",
+    Half => "half",
+});
+
 /// Clipping of the color ranges.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum Range {
     /// Unknown.
     Unknown,
@@ -312,24 +628,46 @@ pub enum Range {
     Full,
     /// Defined by MatrixCoefficients / TransferCharacteristics.
     Defined,
+    /// A value that is not covered by the specification.
+    Unsupported(u64),
 }
 
 impl From<u64> for Range {
     fn from(d: u64) -> Self {
         match d {
+            0 => Range::Unknown,
             1 => Range::Broadcast,
             2 => Range::Full,
             3 => Range::Defined,
-            _ => Range::Unknown,
+            d => Range::Unsupported(d),
         }
     }
 }
 
+impl From<Range> for u64 {
+    fn from(d: Range) -> Self {
+        match d {
+            Range::Unknown => 0,
+            Range::Broadcast => 1,
+            Range::Full => 2,
+            Range::Defined => 3,
+            Range::Unsupported(d) => d,
+        }
+    }
+}
+
+enum_names!(Range {
+    Unknown => "unknown",
+    Broadcast => "broadcast",
+    Full => "full",
+    Defined => "defined",
+});
+
 /// The transfer characteristics of the video.
 ///
 /// For clarity, the value and meanings for `TransferCharacteristics` are adopted
 /// from Table 3 of ISO/IEC 23091-4 or ITU-T H.273.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum TransferCharacteristics {
     /// Unknown.
     Unknown,
@@ -365,12 +703,15 @@ pub enum TransferCharacteristics {
     SmpteSt428_1,
     /// ARIB STD-B67 (HLG).
     Hlg,
+    /// A value that is not covered by the specification.
+    Unsupported(u64),
 }
 
 impl From<u64> for TransferCharacteristics {
     fn from(d: u64) -> Self {
         match d {
             1 => TransferCharacteristics::Bt709,
+            2 => TransferCharacteristics::Unknown,
             4 => TransferCharacteristics::Bt407m,
             5 => TransferCharacteristics::Bt407bg,
             6 => TransferCharacteristics::Smpte170,
@@ -386,16 +727,61 @@ impl From<u64> for TransferCharacteristics {
             16 => TransferCharacteristics::Bt2100,
             17 => TransferCharacteristics::SmpteSt428_1,
             18 => TransferCharacteristics::Hlg,
-            _ => TransferCharacteristics::Unknown,
+            d => TransferCharacteristics::Unsupported(d),
+        }
+    }
+}
+
+impl From<TransferCharacteristics> for u64 {
+    fn from(d: TransferCharacteristics) -> Self {
+        match d {
+            TransferCharacteristics::Bt709 => 1,
+            TransferCharacteristics::Unknown => 2,
+            TransferCharacteristics::Bt407m => 4,
+            TransferCharacteristics::Bt407bg => 5,
+            TransferCharacteristics::Smpte170 => 6,
+            TransferCharacteristics::Smpte240 => 7,
+            TransferCharacteristics::Linear => 8,
+            TransferCharacteristics::Log => 9,
+            TransferCharacteristics::LogSqrt => 10,
+            TransferCharacteristics::Iec61966_2_4 => 11,
+            TransferCharacteristics::Bt1361 => 12,
+            TransferCharacteristics::Iec61966_2_1 => 13,
+            TransferCharacteristics::Bt220_10 => 14,
+            TransferCharacteristics::Bt220_12 => 15,
+            TransferCharacteristics::Bt2100 => 16,
+            TransferCharacteristics::SmpteSt428_1 => 17,
+            TransferCharacteristics::Hlg => 18,
+            TransferCharacteristics::Unsupported(d) => d,
         }
     }
 }
 
+enum_names!(TransferCharacteristics {
+    Unknown => "unknown",
+    Bt709 => "bt709",
+    Bt407m => "bt407m",
+    Bt407bg => "bt407bg",
+    Smpte170 => "smpte170",
+    Smpte240 => "smpte240",
+    Linear => "linear",
+    Log => "log",
+    LogSqrt => "log_sqrt",
+    Iec61966_2_4 => "iec61966_2_4",
+    Bt1361 => "bt1361",
+    Iec61966_2_1 => "iec61966_2_1",
+    Bt220_10 => "bt220_10",
+    Bt220_12 => "bt220_12",
+    Bt2100 => "bt2100",
+    SmpteSt428_1 => "smpte_st428_1",
+    Hlg => "hlg",
+});
+
 /// The colour primaries of the video.
 ///
 /// For clarity, the value and meanings for `Primaries` are adopted
 /// from Table 2 of ISO/IEC 23091-4 or ITU-T H.273.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum Primaries {
     /// Unknown.
     Unknown,
@@ -421,12 +807,15 @@ pub enum Primaries {
     SmpteEg432_2,
     /// EBU Tech. 3213-E - JEDEC P22 phosphors.
     JedecP22,
+    /// A value that is not covered by the specification.
+    Unsupported(u64),
 }
 
 impl From<u64> for Primaries {
     fn from(d: u64) -> Self {
         match d {
             1 => Primaries::Bt709,
+            2 => Primaries::Unknown,
             4 => Primaries::Bt470m,
             5 => Primaries::Bt601,
             6 => Primaries::Smpte170,
@@ -437,20 +826,55 @@ impl From<u64> for Primaries {
             11 => Primaries::SmpteRp432_2,
             12 => Primaries::SmpteEg432_2,
             22 => Primaries::JedecP22,
-            _ => Primaries::Unknown,
+            d => Primaries::Unsupported(d),
+        }
+    }
+}
+
+impl From<Primaries> for u64 {
+    fn from(d: Primaries) -> Self {
+        match d {
+            Primaries::Bt709 => 1,
+            Primaries::Unknown => 2,
+            Primaries::Bt470m => 4,
+            Primaries::Bt601 => 5,
+            Primaries::Smpte170 => 6,
+            Primaries::Smpte240 => 7,
+            Primaries::Film => 8,
+            Primaries::Bt2020 => 9,
+            Primaries::SmpteSt428_1 => 10,
+            Primaries::SmpteRp432_2 => 11,
+            Primaries::SmpteEg432_2 => 12,
+            Primaries::JedecP22 => 22,
+            Primaries::Unsupported(d) => d,
         }
     }
 }
 
+enum_names!(Primaries {
+    Unknown => "unknown",
+    Bt709 => "bt709",
+    Bt470m => "bt470m",
+    Bt601 => "bt601",
+    Smpte170 => "smpte170",
+    Smpte240 => "smpte240",
+    Film => "film",
+    Bt2020 => "bt2020",
+    SmpteSt428_1 => "smpte_st428_1",
+    SmpteRp432_2 => "smpte_rp432_2",
+    SmpteEg432_2 => "smpte_eg432_2",
+    JedecP22 => "jedec_p22",
+});
+
 /// Describing what kind of transformation is applied.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum ContentEncodingType {
-    /// Unknown.
-    Unknown,
     /// Transformation is a compression.
     Compression,
     /// Transformation is a encryption.
     Encryption,
+    /// A value that is not covered by the specification.
+    Unsupported(u64),
 }
 
 impl From<u64> for ContentEncodingType {
@@ -458,18 +882,150 @@ impl From<u64> for ContentEncodingType {
         match d {
             0 => ContentEncodingType::Compression,
             1 => ContentEncodingType::Encryption,
-            _ => ContentEncodingType::Unknown,
+            d => ContentEncodingType::Unsupported(d),
+        }
+    }
+}
+
+impl From<ContentEncodingType> for u64 {
+    fn from(d: ContentEncodingType) -> Self {
+        match d {
+            ContentEncodingType::Compression => 0,
+            ContentEncodingType::Encryption => 1,
+            ContentEncodingType::Unsupported(d) => d,
         }
     }
 }
 
+enum_names!(ContentEncodingType {
+    Compression => "compression",
+    Encryption => "encryption",
+});
+
+/// A bit field that describes which elements were modified by a `ContentEncoding`.
+///
+/// The flags can be combined with `|`, for example `ContentEncodingScope::FRAME |
+/// ContentEncodingScope::PRIVATE`. Unknown bits are kept.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ContentEncodingScope(u64);
+
+impl ContentEncodingScope {
+    /// All frame contents, excluding lacing data.
+    pub const FRAME: Self = Self(1);
+    /// The `CodecPrivate` of the track.
+    pub const PRIVATE: Self = Self(2);
+    /// The next `ContentEncoding`, whose `ContentCompSettings` or `ContentEncKeyID` were
+    /// modified.
+    pub const NEXT: Self = Self(4);
+
+    /// Returns the raw bits.
+    pub fn bits(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns `true` if all flags of `other` are set.
+    pub fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns `true` if no flag is set.
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl Default for ContentEncodingScope {
+    fn default() -> Self {
+        Self::FRAME
+    }
+}
+
+impl From<u64> for ContentEncodingScope {
+    fn from(d: u64) -> Self {
+        Self(d)
+    }
+}
+
+impl From<ContentEncodingScope> for u64 {
+    fn from(d: ContentEncodingScope) -> Self {
+        d.0
+    }
+}
+
+impl BitOr for ContentEncodingScope {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for ContentEncodingScope {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitAnd for ContentEncodingScope {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+/// The compression algorithm used.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ContentCompAlgo {
+    /// zlib compression - RFC 1950.
+    Zlib,
+    /// bzip2 compression.
+    Bzlib,
+    /// LZO1X compression.
+    Lzo1x,
+    /// Header stripping. The bytes of `ContentCompSettings` were removed from the start
+    /// of every frame.
+    HeaderStripping,
+    /// A value that is not covered by the specification.
+    Unsupported(u64),
+}
+
+impl From<u64> for ContentCompAlgo {
+    fn from(d: u64) -> Self {
+        match d {
+            0 => ContentCompAlgo::Zlib,
+            1 => ContentCompAlgo::Bzlib,
+            2 => ContentCompAlgo::Lzo1x,
+            3 => ContentCompAlgo::HeaderStripping,
+            d => ContentCompAlgo::Unsupported(d),
+        }
+    }
+}
+
+impl From<ContentCompAlgo> for u64 {
+    fn from(d: ContentCompAlgo) -> Self {
+        match d {
+            ContentCompAlgo::Zlib => 0,
+            ContentCompAlgo::Bzlib => 1,
+            ContentCompAlgo::Lzo1x => 2,
+            ContentCompAlgo::HeaderStripping => 3,
+            ContentCompAlgo::Unsupported(d) => d,
+        }
+    }
+}
+
+enum_names!(ContentCompAlgo {
+    Zlib => "zlib",
+    Bzlib => "bzlib",
+    Lzo1x => "lzo1x",
+    HeaderStripping => "header_stripping",
+});
+
 /// The encryption algorithm used.
 ///
 /// `NotEncrypted` means that the contents have not been encrypted but only signed.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum ContentEncAlgo {
-    /// Unknown.
-    Unknown,
     /// Not encrypted.
     NotEncrypted,
     /// DES - FIPS 46-3.
@@ -482,6 +1038,8 @@ pub enum ContentEncAlgo {
     Blowfish,
     /// AES - FIPS 187.
     Aes,
+    /// A value that is not covered by the specification.
+    Unsupported(u64),
 }
 
 impl From<u64> for ContentEncAlgo {
@@ -493,28 +1051,289 @@ impl From<u64> for ContentEncAlgo {
             3 => ContentEncAlgo::Twofish,
             4 => ContentEncAlgo::Blowfish,
             5 => ContentEncAlgo::Aes,
-            _ => ContentEncAlgo::Unknown,
+            d => ContentEncAlgo::Unsupported(d),
+        }
+    }
+}
+
+impl From<ContentEncAlgo> for u64 {
+    fn from(d: ContentEncAlgo) -> Self {
+        match d {
+            ContentEncAlgo::NotEncrypted => 0,
+            ContentEncAlgo::Des => 1,
+            ContentEncAlgo::TripleDes => 2,
+            ContentEncAlgo::Twofish => 3,
+            ContentEncAlgo::Blowfish => 4,
+            ContentEncAlgo::Aes => 5,
+            ContentEncAlgo::Unsupported(d) => d,
         }
     }
 }
 
+enum_names!(ContentEncAlgo {
+    NotEncrypted => "not_encrypted",
+    Des => "des",
+    TripleDes => "triple_des",
+    Twofish => "twofish",
+    Blowfish => "blowfish",
+    Aes => "aes",
+});
+
 /// The AES cipher mode used in the encryption.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum AesSettingsCipherMode {
-    /// Unknown.
-    Unknown,
     /// AES-CTR / Counter, NIST SP 800-38A.
     Ctr,
     /// AES-CBC / Cipher Block Chaining, NIST SP 800-38A.
     Cbc,
+    /// A value that is not covered by the specification.
+    Unsupported(u64),
 }
 
 impl From<u64> for AesSettingsCipherMode {
     fn from(d: u64) -> Self {
         match d {
-            0 => AesSettingsCipherMode::Ctr,
-            1 => AesSettingsCipherMode::Cbc,
-            _ => AesSettingsCipherMode::Unknown,
+            1 => AesSettingsCipherMode::Ctr,
+            2 => AesSettingsCipherMode::Cbc,
+            d => AesSettingsCipherMode::Unsupported(d),
         }
     }
 }
+
+impl From<AesSettingsCipherMode> for u64 {
+    fn from(d: AesSettingsCipherMode) -> Self {
+        match d {
+            AesSettingsCipherMode::Ctr => 1,
+            AesSettingsCipherMode::Cbc => 2,
+            AesSettingsCipherMode::Unsupported(d) => d,
+        }
+    }
+}
+
+enum_names!(AesSettingsCipherMode {
+    Ctr => "ctr",
+    Cbc => "cbc",
+});
+
+/// A chapter codec, used by chapter translations and chapter processes.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TranslateCodec {
+    /// Matroska Script.
+    MatroskaScript,
+    /// DVD-menu.
+    DvdMenu,
+    /// A value that is not covered by the specification.
+    Unsupported(u64),
+}
+
+impl From<u64> for TranslateCodec {
+    fn from(d: u64) -> Self {
+        match d {
+            0 => TranslateCodec::MatroskaScript,
+            1 => TranslateCodec::DvdMenu,
+            d => TranslateCodec::Unsupported(d),
+        }
+    }
+}
+
+impl From<TranslateCodec> for u64 {
+    fn from(d: TranslateCodec) -> Self {
+        match d {
+            TranslateCodec::MatroskaScript => 0,
+            TranslateCodec::DvdMenu => 1,
+            TranslateCodec::Unsupported(d) => d,
+        }
+    }
+}
+
+enum_names!(TranslateCodec {
+    MatroskaScript => "matroska_script",
+    DvdMenu => "dvd_menu",
+});
+
+/// Defines when a chapter process command should be handled.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ChapterProcessTime {
+    /// During the whole chapter.
+    During,
+    /// Before starting playback.
+    Before,
+    /// After playback of the chapter.
+    After,
+    /// A value that is not covered by the specification.
+    Unsupported(u64),
+}
+
+impl From<u64> for ChapterProcessTime {
+    fn from(d: u64) -> Self {
+        match d {
+            0 => ChapterProcessTime::During,
+            1 => ChapterProcessTime::Before,
+            2 => ChapterProcessTime::After,
+            d => ChapterProcessTime::Unsupported(d),
+        }
+    }
+}
+
+impl From<ChapterProcessTime> for u64 {
+    fn from(d: ChapterProcessTime) -> Self {
+        match d {
+            ChapterProcessTime::During => 0,
+            ChapterProcessTime::Before => 1,
+            ChapterProcessTime::After => 2,
+            ChapterProcessTime::Unsupported(d) => d,
+        }
+    }
+}
+
+enum_names!(ChapterProcessTime {
+    During => "during",
+    Before => "before",
+    After => "after",
+});
+
+/// The audio emphasis applied on audio samples.
+///
+/// The player must apply the inverse emphasis to get the proper audio samples.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Emphasis {
+    /// No emphasis.
+    NoEmphasis,
+    /// First order filter with zero point at 50 microseconds and a pole at 15 microseconds. Also found on DVD Audio and MPEG audio.
+    CdAudio,
+    /// Defined in ITU-R J.17.
+    CcitJ17,
+    /// FM Radio in Europe. RC Filter with a time constant of 50 microseconds.
+    Fm50,
+    /// FM Radio in the USA. RC Filter with a time constant of 75 microseconds.
+    Fm75,
+    /// Phono filter with time constants of t1=3180, t2=318 and t3=75 microseconds. NAB/RIAA format.
+    PhonoRiaa,
+    /// Phono filter with time constants of t1=3180, t2=450 and t3=50 microseconds.
+    PhonoIecN78,
+    /// Phono filter with time constants of t1=3180, t2=318 and t3=50 microseconds.
+    PhonoTeldec,
+    /// Phono filter with time constants of t1=2500, t2=500 and t3=70 microseconds.
+    PhonoEmi,
+    /// Phono filter with time constants of t1=1590, t2=318 and t3=100 microseconds.
+    PhonoColumbiaLp,
+    /// Phono filter with time constants of t1=1590, t2=318 and t3=50 microseconds.
+    PhonoLondon,
+    /// Phono filter with time constants of t1=3180, t2=318 and t3=100 microseconds.
+    PhonoNartb,
+    /// A value that is not covered by the specification.
+    Unsupported(u64),
+}
+
+impl From<u64> for Emphasis {
+    fn from(d: u64) -> Self {
+        match d {
+            0 => Emphasis::NoEmphasis,
+            1 => Emphasis::CdAudio,
+            3 => Emphasis::CcitJ17,
+            4 => Emphasis::Fm50,
+            5 => Emphasis::Fm75,
+            10 => Emphasis::PhonoRiaa,
+            11 => Emphasis::PhonoIecN78,
+            12 => Emphasis::PhonoTeldec,
+            13 => Emphasis::PhonoEmi,
+            14 => Emphasis::PhonoColumbiaLp,
+            15 => Emphasis::PhonoLondon,
+            16 => Emphasis::PhonoNartb,
+            d => Emphasis::Unsupported(d),
+        }
+    }
+}
+
+impl From<Emphasis> for u64 {
+    fn from(d: Emphasis) -> Self {
+        match d {
+            Emphasis::NoEmphasis => 0,
+            Emphasis::CdAudio => 1,
+            Emphasis::CcitJ17 => 3,
+            Emphasis::Fm50 => 4,
+            Emphasis::Fm75 => 5,
+            Emphasis::PhonoRiaa => 10,
+            Emphasis::PhonoIecN78 => 11,
+            Emphasis::PhonoTeldec => 12,
+            Emphasis::PhonoEmi => 13,
+            Emphasis::PhonoColumbiaLp => 14,
+            Emphasis::PhonoLondon => 15,
+            Emphasis::PhonoNartb => 16,
+            Emphasis::Unsupported(d) => d,
+        }
+    }
+}
+
+enum_names!(Emphasis {
+    NoEmphasis => "no_emphasis",
+    CdAudio => "cd_audio",
+    CcitJ17 => "ccit_j17",
+    Fm50 => "fm_50",
+    Fm75 => "fm_75",
+    PhonoRiaa => "phono_riaa",
+    PhonoIecN78 => "phono_iec_n78",
+    PhonoTeldec => "phono_teldec",
+    PhonoEmi => "phono_emi",
+    PhonoColumbiaLp => "phono_columbia_lp",
+    PhonoLondon => "phono_london",
+    PhonoNartb => "phono_nartb",
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enum_names() -> Result<(), DemuxError> {
+        assert_eq!(MatrixCoefficients::from(2), MatrixCoefficients::Unknown);
+        assert_eq!(
+            MatrixCoefficients::from(3),
+            MatrixCoefficients::Unsupported(3)
+        );
+        assert_eq!(TrackType::from(99), TrackType::Unsupported(99));
+
+        assert_eq!(TransferCharacteristics::Hlg.to_string(), "hlg");
+        assert_eq!(TrackType::Unsupported(99).to_string(), "99");
+        assert_eq!("bt2020".parse::<Primaries>()?, Primaries::Bt2020);
+        assert_eq!("99".parse::<TrackType>()?, TrackType::Unsupported(99));
+        assert_eq!("1".parse::<TrackType>()?, TrackType::Video);
+        assert!("bt2020 ".parse::<Primaries>().is_err());
+
+        for value in 0..32 {
+            let stereo_mode = StereoMode::from(value);
+            assert_eq!(stereo_mode.to_string().parse::<StereoMode>()?, stereo_mode);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_enum_values_roundtrip() -> Result<(), DemuxError> {
+        for value in 0..64 {
+            assert_eq!(u64::from(MatrixCoefficients::from(value)), value);
+            assert_eq!(u64::from(DisplayUnit::from(value)), value);
+            assert_eq!(u64::from(StereoMode::from(value)), value);
+            assert_eq!(u64::from(TrackType::from(value)), value);
+            assert_eq!(u64::from(TransferCharacteristics::from(value)), value);
+            assert_eq!(u64::from(Primaries::from(value)), value);
+            assert_eq!(u64::from(ChapterProcessTime::from(value)), value);
+            assert_eq!(u64::from(Emphasis::from(value)), value);
+            assert_eq!(u64::from(TrackPlaneType::from(value)), value);
+            assert_eq!(u64::from(ContentCompAlgo::from(value)), value);
+            assert_eq!(u64::from(ContentEncodingScope::from(value)), value);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_content_encoding_scope() {
+        let scope = ContentEncodingScope::FRAME | ContentEncodingScope::PRIVATE;
+        assert_eq!(scope.bits(), 3);
+        assert!(scope.contains(ContentEncodingScope::FRAME));
+        assert!(!scope.contains(ContentEncodingScope::FRAME | ContentEncodingScope::NEXT));
+        assert!((scope & ContentEncodingScope::NEXT).is_empty());
+        assert_eq!(ContentEncodingScope::default(), ContentEncodingScope::FRAME);
+    }
+}
@@ -2,7 +2,7 @@
 
 use std::error::Error;
 
-use crate::element_id::ElementId;
+use crate::{element_id::ElementId, ContentCompAlgo};
 
 /// Errors that can occur when demuxing Matroska files.
 #[derive(Debug)]
@@ -41,6 +41,245 @@ pub enum DemuxError {
     NonZeroValueIsZero(ElementId),
     /// A value that should be positive is not positive.
     PositiveValueIsNotPositive,
+    /// The file has no track with the given track number.
+    TrackNotFound(u64),
+    /// The file ended inside an element.
+    UnexpectedEof,
+    /// An element that is not a master element has an unknown data size.
+    UnknownSizeNotAllowed(ElementId),
+    /// The signal byte, IV or partitions of an encrypted frame are invalid.
+    InvalidEncryptionSignal,
+    /// A string doesn't name a value of an enum.
+    InvalidEnumName(String),
+    /// An exported index is invalid or doesn't belong to the file.
+    InvalidIndexData,
+    /// The file has no attachment with the given UID.
+    AttachmentNotFound(u64),
+    /// The codec of the track is not a supported subtitle codec.
+    UnsupportedSubtitleCodec(String),
+    /// An element was found inside a parent it is not allowed in. Only reported in strict
+    /// mode.
+    MisplacedElement {
+        /// The misplaced element.
+        element: ElementId,
+        /// The parent the element was found in.
+        parent: ElementId,
+        /// The offset of the element header.
+        offset: u64,
+    },
+    /// A block contains more laced frames than allowed by
+    /// `ParserSettings::max_laced_frames()`. Contains the number of frames of the block.
+    TooManyLacedFrames(u64),
+    /// An entry of the `SeekHead` doesn't point at the element it declares. Contains the
+    /// declared element and the offset inside the file the entry points at.
+    InvalidSeekEntry(ElementId, u64),
+    /// The frames of the track are compressed with an algorithm that is not supported or
+    /// whose feature is not enabled.
+    UnsupportedCompression(ContentCompAlgo),
+    /// The compressed data of a frame is damaged.
+    InvalidCompressedData,
+    /// A decompressed frame is larger than allowed by
+    /// `ParserSettings::max_decompressed_size()`. Contains the limit.
+    DecompressedSizeExceeded(u64),
+    /// The codec of the track is not supported by the requested operation.
+    UnsupportedCodec(String),
+    /// The `CodecPrivate` of the track or the data of a frame doesn't follow the format of
+    /// its codec.
+    InvalidCodecData,
+    /// The end of the written data of a followed file was reached. Reading can be retried
+    /// once the file grew.
+    WouldBlock,
+    /// A frame has a timestamp before the start of the segment and
+    /// `TimestampPolicy::Error` is used. Contains the timestamp.
+    NegativeTimestamp(i64),
+    /// An edited element neither fits into its old place nor can be moved to the end of
+    /// the segment. Contains the element.
+    NotEnoughSpace(ElementId),
+    /// Master elements are nested deeper than the supported 64 levels. Contains the
+    /// element at which the limit was reached.
+    NestingTooDeep(ElementId),
+}
+
+/// The context in which an error occurred while demuxing. It's returned by
+/// `MatroskaFile::error_context()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ErrorContext {
+    /// The offset of the element that was read when the error occurred.
+    pub offset: u64,
+    /// The path of elements from the segment to the element that was read,
+    /// for example `Segment`, `Cluster`, `BlockGroup`.
+    pub element_path: Vec<ElementId>,
+}
+
+/// A stable, machine-readable code for each kind of `DemuxError`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    /// A `std::io::Error`.
+    Io,
+    /// A `std::string::FromUtf8Error`.
+    FromUtf8,
+    /// A `TryFromIntError`.
+    TryFromInt,
+    /// An invalid EBML Element ID was found.
+    InvalidEbmlElementId,
+    /// An invalid EBML data size was found.
+    InvalidEbmlDataSize,
+    /// An invalid EBML header was found.
+    InvalidEbmlHeader,
+    /// Wrong float size.
+    WrongFloatSize,
+    /// Wrong integer size.
+    WrongIntegerSize,
+    /// Wrong date size.
+    WrongDateSize,
+    /// Unsupported DocType.
+    UnsupportedDocType,
+    /// Unsupported DocTypeReadVersion.
+    UnsupportedDocTypeReadVersion,
+    /// Unexpected element found.
+    UnexpectedElement,
+    /// Unexpected data type found.
+    UnexpectedDataType,
+    /// Can't find the expected element.
+    ElementNotFound,
+    /// Can't find a cluster element.
+    CantFindCluster,
+    /// A value that should not be zero was zero.
+    NonZeroValueIsZero,
+    /// A value that should be positive is not positive.
+    PositiveValueIsNotPositive,
+    /// The file has no track with the given track number.
+    TrackNotFound,
+    /// The file ended inside an element.
+    UnexpectedEof,
+    /// An element that is not a master element has an unknown data size.
+    UnknownSizeNotAllowed,
+    /// The signal byte, IV or partitions of an encrypted frame are invalid.
+    InvalidEncryptionSignal,
+    /// A string doesn't name a value of an enum.
+    InvalidEnumName,
+    /// An exported index is invalid or doesn't belong to the file.
+    InvalidIndexData,
+    /// The file has no attachment with the given UID.
+    AttachmentNotFound,
+    /// The codec of the track is not a supported subtitle codec.
+    UnsupportedSubtitleCodec,
+    /// An element was found inside a parent it is not allowed in.
+    MisplacedElement,
+    /// A block contains more laced frames than allowed.
+    TooManyLacedFrames,
+    /// An entry of the `SeekHead` doesn't point at the element it declares.
+    InvalidSeekEntry,
+    /// The frames of the track are compressed with an unsupported algorithm.
+    UnsupportedCompression,
+    /// The compressed data of a frame is damaged.
+    InvalidCompressedData,
+    /// A decompressed frame is larger than allowed.
+    DecompressedSizeExceeded,
+    /// The codec of the track is not supported by the requested operation.
+    UnsupportedCodec,
+    /// The codec data of a track or frame is malformed.
+    InvalidCodecData,
+    /// The end of a followed file was reached.
+    WouldBlock,
+    /// A frame has a timestamp before the start of the segment.
+    NegativeTimestamp,
+    /// An edited element can't be written into the file.
+    NotEnoughSpace,
+    /// Master elements are nested too deeply.
+    NestingTooDeep,
+}
+
+impl ErrorCode {
+    /// Returns the code as a string, which is stable across versions.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::Io => "io",
+            ErrorCode::FromUtf8 => "from_utf8",
+            ErrorCode::TryFromInt => "try_from_int",
+            ErrorCode::InvalidEbmlElementId => "invalid_ebml_element_id",
+            ErrorCode::InvalidEbmlDataSize => "invalid_ebml_data_size",
+            ErrorCode::InvalidEbmlHeader => "invalid_ebml_header",
+            ErrorCode::WrongFloatSize => "wrong_float_size",
+            ErrorCode::WrongIntegerSize => "wrong_integer_size",
+            ErrorCode::WrongDateSize => "wrong_date_size",
+            ErrorCode::UnsupportedDocType => "unsupported_doc_type",
+            ErrorCode::UnsupportedDocTypeReadVersion => "unsupported_doc_type_read_version",
+            ErrorCode::UnexpectedElement => "unexpected_element",
+            ErrorCode::UnexpectedDataType => "unexpected_data_type",
+            ErrorCode::ElementNotFound => "element_not_found",
+            ErrorCode::CantFindCluster => "cant_find_cluster",
+            ErrorCode::NonZeroValueIsZero => "non_zero_value_is_zero",
+            ErrorCode::PositiveValueIsNotPositive => "positive_value_is_not_positive",
+            ErrorCode::TrackNotFound => "track_not_found",
+            ErrorCode::UnexpectedEof => "unexpected_eof",
+            ErrorCode::UnknownSizeNotAllowed => "unknown_size_not_allowed",
+            ErrorCode::InvalidEncryptionSignal => "invalid_encryption_signal",
+            ErrorCode::InvalidEnumName => "invalid_enum_name",
+            ErrorCode::InvalidIndexData => "invalid_index_data",
+            ErrorCode::AttachmentNotFound => "attachment_not_found",
+            ErrorCode::UnsupportedSubtitleCodec => "unsupported_subtitle_codec",
+            ErrorCode::MisplacedElement => "misplaced_element",
+            ErrorCode::TooManyLacedFrames => "too_many_laced_frames",
+            ErrorCode::InvalidSeekEntry => "invalid_seek_entry",
+            ErrorCode::UnsupportedCompression => "unsupported_compression",
+            ErrorCode::InvalidCompressedData => "invalid_compressed_data",
+            ErrorCode::DecompressedSizeExceeded => "decompressed_size_exceeded",
+            ErrorCode::UnsupportedCodec => "unsupported_codec",
+            ErrorCode::InvalidCodecData => "invalid_codec_data",
+            ErrorCode::WouldBlock => "would_block",
+            ErrorCode::NegativeTimestamp => "negative_timestamp",
+            ErrorCode::NotEnoughSpace => "not_enough_space",
+            ErrorCode::NestingTooDeep => "nesting_too_deep",
+        }
+    }
+}
+
+impl DemuxError {
+    /// Returns the machine-readable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            DemuxError::IoError(_) => ErrorCode::Io,
+            DemuxError::FromUtf8Error(_) => ErrorCode::FromUtf8,
+            DemuxError::TryFromIntError(_) => ErrorCode::TryFromInt,
+            DemuxError::InvalidEbmlElementId => ErrorCode::InvalidEbmlElementId,
+            DemuxError::InvalidEbmlDataSize => ErrorCode::InvalidEbmlDataSize,
+            DemuxError::InvalidEbmlHeader(_) => ErrorCode::InvalidEbmlHeader,
+            DemuxError::WrongFloatSize(_) => ErrorCode::WrongFloatSize,
+            DemuxError::WrongIntegerSize(_) => ErrorCode::WrongIntegerSize,
+            DemuxError::WrongDateSize(_) => ErrorCode::WrongDateSize,
+            DemuxError::UnsupportedDocType(_) => ErrorCode::UnsupportedDocType,
+            DemuxError::UnsupportedDocTypeReadVersion(_) => {
+                ErrorCode::UnsupportedDocTypeReadVersion
+            }
+            DemuxError::UnexpectedElement(_) => ErrorCode::UnexpectedElement,
+            DemuxError::UnexpectedDataType => ErrorCode::UnexpectedDataType,
+            DemuxError::ElementNotFound(_) => ErrorCode::ElementNotFound,
+            DemuxError::CantFindCluster => ErrorCode::CantFindCluster,
+            DemuxError::NonZeroValueIsZero(_) => ErrorCode::NonZeroValueIsZero,
+            DemuxError::PositiveValueIsNotPositive => ErrorCode::PositiveValueIsNotPositive,
+            DemuxError::TrackNotFound(_) => ErrorCode::TrackNotFound,
+            DemuxError::UnexpectedEof => ErrorCode::UnexpectedEof,
+            DemuxError::UnknownSizeNotAllowed(_) => ErrorCode::UnknownSizeNotAllowed,
+            DemuxError::InvalidEncryptionSignal => ErrorCode::InvalidEncryptionSignal,
+            DemuxError::InvalidEnumName(_) => ErrorCode::InvalidEnumName,
+            DemuxError::InvalidIndexData => ErrorCode::InvalidIndexData,
+            DemuxError::AttachmentNotFound(_) => ErrorCode::AttachmentNotFound,
+            DemuxError::UnsupportedSubtitleCodec(_) => ErrorCode::UnsupportedSubtitleCodec,
+            DemuxError::MisplacedElement { .. } => ErrorCode::MisplacedElement,
+            DemuxError::TooManyLacedFrames(_) => ErrorCode::TooManyLacedFrames,
+            DemuxError::InvalidSeekEntry(..) => ErrorCode::InvalidSeekEntry,
+            DemuxError::UnsupportedCompression(_) => ErrorCode::UnsupportedCompression,
+            DemuxError::InvalidCompressedData => ErrorCode::InvalidCompressedData,
+            DemuxError::DecompressedSizeExceeded(_) => ErrorCode::DecompressedSizeExceeded,
+            DemuxError::UnsupportedCodec(_) => ErrorCode::UnsupportedCodec,
+            DemuxError::InvalidCodecData => ErrorCode::InvalidCodecData,
+            DemuxError::WouldBlock => ErrorCode::WouldBlock,
+            DemuxError::NegativeTimestamp(_) => ErrorCode::NegativeTimestamp,
+            DemuxError::NotEnoughSpace(_) => ErrorCode::NotEnoughSpace,
+            DemuxError::NestingTooDeep(_) => ErrorCode::NestingTooDeep,
+        }
+    }
 }
 
 impl std::fmt::Display for DemuxError {
@@ -117,6 +356,85 @@ impl std::fmt::Display for DemuxError {
             DemuxError::PositiveValueIsNotPositive => {
                 write!(f, "a value that should be positive is not positive")
             }
+            DemuxError::TrackNotFound(track) => {
+                write!(f, "can't find a track with the track number: {}", track)
+            }
+            DemuxError::UnexpectedEof => {
+                write!(f, "the file ended inside an element")
+            }
+            DemuxError::UnknownSizeNotAllowed(element_id) => {
+                write!(
+                    f,
+                    "only master elements can have an unknown size: {:?}",
+                    element_id
+                )
+            }
+            DemuxError::InvalidEncryptionSignal => {
+                write!(f, "invalid signal header of an encrypted frame was found")
+            }
+            DemuxError::InvalidEnumName(name) => {
+                write!(f, "invalid enum name: {}", name)
+            }
+            DemuxError::InvalidIndexData => {
+                write!(
+                    f,
+                    "the exported index is invalid or doesn't belong to the file"
+                )
+            }
+            DemuxError::AttachmentNotFound(uid) => {
+                write!(f, "can't find an attachment with the UID: {}", uid)
+            }
+            DemuxError::UnsupportedSubtitleCodec(codec_id) => {
+                write!(f, "unsupported subtitle codec: {}", codec_id)
+            }
+            DemuxError::MisplacedElement {
+                element,
+                parent,
+                offset,
+            } => {
+                write!(
+                    f,
+                    "the element {:?} at offset {} is not allowed inside {:?}",
+                    element, offset, parent
+                )
+            }
+            DemuxError::TooManyLacedFrames(count) => {
+                write!(f, "the block contains too many laced frames: {}", count)
+            }
+            DemuxError::InvalidSeekEntry(element_id, offset) => {
+                write!(
+                    f,
+                    "the SeekHead entry of {:?} doesn't point at the element: {}",
+                    element_id, offset
+                )
+            }
+            DemuxError::UnsupportedCompression(algo) => {
+                write!(f, "unsupported content compression: {:?}", algo)
+            }
+            DemuxError::InvalidCompressedData => {
+                write!(f, "the compressed data of the frame is invalid")
+            }
+            DemuxError::DecompressedSizeExceeded(limit) => {
+                write!(f, "the decompressed frame is larger than {} bytes", limit)
+            }
+            DemuxError::UnsupportedCodec(codec_id) => {
+                write!(f, "unsupported codec: {}", codec_id)
+            }
+            DemuxError::InvalidCodecData => {
+                write!(f, "the codec data doesn't follow the format of the codec")
+            }
+            DemuxError::WouldBlock => {
+                write!(f, "reached the end of the written data of the file")
+            }
+            DemuxError::NegativeTimestamp(timestamp) => {
+                write!(f, "the frame has a negative timestamp: {}", timestamp)
+            }
+            DemuxError::NotEnoughSpace(element_id) => {
+                write!(f, "not enough space to write the element: {:?}", element_id)
+            }
+            DemuxError::NestingTooDeep(element_id) => {
+                write!(f, "the elements are nested too deeply: {:?}", element_id)
+            }
         }
     }
 }
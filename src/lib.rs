@@ -26,62 +26,296 @@
 //!     }
 //! }
 //! ```
+//!
+//! # Timestamps
+//!
+//! Matroska stores timestamps in different units: block, cluster and cue timestamps are
+//! expressed in ticks of the `TimestampScale` of the segment, while chapter timestamps are
+//! expressed in nanoseconds. Accessors like `CuePoint::time()` or `ChapterAtom::time_start()`
+//! return the raw values as stored in the file.
+//!
+//! Every timestamp-like value is also available as a `Timestamp`, which carries its scale,
+//! for example `CuePoint::cue_time()`, `ChapterAtom::start_time()` or `Frame::time()`.
+//! Use `Timestamp::ticks()` to get the raw value or `Timestamp::as_nanos()` and
+//! `Timestamp::as_duration()` to get the converted value. `Timestamp`s of different
+//! scales can be compared directly.
+//!
+//! Enable `ParserSettings::nanosecond_timestamps()` to get all frame, block, event and cue
+//! timestamps in nanoseconds instead.
 
 use std::{
-    collections::{HashMap, VecDeque},
+    borrow::Cow,
+    collections::{hash_map::Entry, HashMap, VecDeque},
     convert::TryInto,
-    error::Error,
-    io::{Read, Seek, SeekFrom},
+    io::{Cursor, ErrorKind, Read, Seek, SeekFrom},
     num::NonZeroU64,
+    sync::{Arc, OnceLock},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
+pub use analysis::{TimestampIssue, TimestampIssueKind, TimestampReport, TrackTimestampReport};
+pub use codec_config::{Av1Config, ChannelLayout, FlacConfig, OpusConfig, Vp9Config};
 use ebml::{
-    collect_children, expect_master, find_bool_or, find_custom_type, find_float_or, find_nonzero,
-    find_nonzero_or, find_string, find_unsigned, find_unsigned_or, next_element,
-    parse_children_at_offset, parse_element_header, try_find_binary, try_find_custom_type,
-    try_find_custom_type_or, try_find_date, try_find_float, try_find_nonzero, try_find_string,
-    try_find_unsigned, try_parse_child, try_parse_children, ElementData, ParsableElement,
+    check_strings, collect_children, expect_master, find_all_strings, find_all_unsigned,
+    find_binary, find_bool_or, find_custom_type, find_float_or, find_nonzero, find_nonzero_or,
+    find_string, find_unsigned, find_unsigned_or, next_element, parse_children_at_offset,
+    parse_element_data, parse_element_header, try_find_binary, try_find_custom_type,
+    try_find_custom_type_or, try_find_date, try_find_float, try_find_invalid_string,
+    try_find_nonzero, try_find_string, try_find_unsigned, try_next_element_header,
+    try_next_raw_element, try_parse_child, try_parse_children, ElementData, HeaderLimits,
+    ParsableElement, ParseOptions, MAX_DEPTH,
 };
-pub use element_id::ElementId;
+pub use editor::MatroskaEditor;
+pub use element_id::{ElementId, ElementType};
+pub use elementary::{AdtsFramer, AnnexBConverter, IvfWriter};
+pub use encryption::{ContentDecryptor, FrameEncryptionInfo};
 pub use enums::*;
-pub use error::DemuxError;
-
-use crate::element_id::id_to_element_id;
+pub use error::{DemuxError, ErrorCode, ErrorContext};
+#[cfg(feature = "mmap")]
+pub use memmap2;
+pub use pool::{FramePool, PooledFrame};
+pub use probe::{is_matroska, probe, ContainerKind, ProbeResult};
+pub use sample_index::{SampleIndex, SampleIndexEntry, SampleSeek};
+pub use settings::{
+    CustomElement, ParserSettings, SeekHeadPolicy, TimestampPolicy, UnknownTrackPolicy,
+};
+pub use source::{ByteRangeReader, ByteRangeSource};
+#[cfg(feature = "spool")]
+pub use spool::SpoolReader;
+pub use statistics::MuxerStatistics;
+#[cfg(feature = "subtitle")]
+pub use subtitle::{SubtitleEvent, SubtitleEvents, SubtitleFormat};
+pub use timestamp::Timestamp;
+pub use validation::{Severity, ValidationIssue, ValidationIssueKind, ValidationReport};
+
+use crate::element_id::{element_id_to_type, element_parents, id_to_element_id};
 use crate::{
-    block::{parse_laced_frames, probe_block_timestamp, LacedFrame},
-    ebml::{parse_child, try_find_bool},
+    analysis::{analyze_track, FrameTiming},
+    block::{
+        parse_block_header, parse_laced_frames, probe_block_timestamp, probe_block_track,
+        unsigned_timestamp, LacedFrame,
+    },
+    compression::decompress_frame,
+    ebml::try_find_bool,
+    encryption::{strip_frame, DecryptorHandle},
+    persist::{RecordingReader, ReplayReader},
+    sample_index::samples_per_frame,
+    settings::DEFAULT_MAX_DECOMPRESSED_SIZE,
+    validation::validate_file,
 };
 
+#[macro_use]
+mod trace;
+
+mod analysis;
 mod block;
+mod byte_reader;
+#[cfg(feature = "bzip2")]
+mod bzip2;
+mod codec_config;
+pub mod codec_ids;
+mod compression;
+#[cfg(feature = "dump")]
+pub mod dump;
 mod ebml;
+mod editor;
 pub(crate) mod element_id;
+mod elementary;
+mod encryption;
 mod enums;
 mod error;
+#[cfg(feature = "lzo")]
+mod lzo;
+#[cfg(feature = "parallel")]
+mod parallel;
+mod persist;
+mod pool;
+mod probe;
+mod remux;
+mod sample_index;
+mod settings;
+mod source;
+#[cfg(feature = "spool")]
+mod spool;
+mod statistics;
+#[cfg(feature = "subtitle")]
+mod subtitle;
+mod timestamp;
+mod validation;
+mod writer;
 
 /// The doc type version this demuxer supports.
 const DEMUXER_DOC_TYPE_VERSION: u64 = 4;
 
+/// The Matroska epoch (2001-01-01T00:00:00 UTC) in seconds since the Unix epoch.
+const MATROSKA_EPOCH_UNIX_SECONDS: u64 = 978_307_200;
+
 type Result<T> = std::result::Result<T, DemuxError>;
 
+/// The kind of block a frame was stored in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlockKind {
+    /// A `SimpleBlock` inside a cluster.
+    #[default]
+    SimpleBlock,
+    /// A `Block` inside a `BlockGroup`.
+    Block,
+}
+
 /// A data frame inside the Matroska container.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug)]
 pub struct Frame {
     /// The ID of the track.
     pub track: u64,
     /// The timestamp of the frame.
+    ///
+    /// Timestamps before the start of the segment are handled according to the
+    /// `TimestampPolicy` and clamped to 0 by default. Use `signed_timestamp` to get the
+    /// unclamped value.
     pub timestamp: u64,
-    /// The data of the frame.
+    /// Set when the timestamp of the frame lies before the start of the segment and
+    /// `timestamp` was clamped to 0.
+    pub is_timestamp_clamped: bool,
+    /// The timestamp of the frame, which can be negative when a block is placed before the
+    /// start of the segment, for example to carry audio priming samples.
+    pub signed_timestamp: i64,
+    /// The timestamp of the cluster that contains the frame, including the offset applied
+    /// by `ParserSettings::stitch_timestamps()`.
+    pub cluster_timestamp: u64,
+    /// The timestamp of the block relative to `cluster_timestamp`, as stored in the block
+    /// header. All laced frames of a block share this value, so for laced frames after the
+    /// first frame, `timestamp` is spread by the `DefaultDuration` of the track and differs
+    /// from `cluster_timestamp + relative_timestamp`.
+    pub relative_timestamp: i16,
+    /// The timestamp at which the frame should be presented. It's the `signed_timestamp`
+    /// minus the `CodecDelay` of the track, so decoded priming samples with a negative
+    /// presentation timestamp should be discarded.
+    pub presentation_timestamp: i64,
+    /// The data of the frame. Frames of tracks with a `ContentCompression` are decompressed.
+    /// Header stripping is always supported, bzlib and LZO1X need the `bzip2` and `lzo`
+    /// features. Zlib compressed frames are returned as stored and marked by `compression`.
     pub data: Vec<u8>,
+    /// The algorithm `data` is still compressed with.
+    ///
+    /// Only set for frames of tracks with a zlib `ContentCompression`, which are not
+    /// inflated by the demuxer.
+    pub compression: Option<ContentCompAlgo>,
     /// Set when the codec should decode this frame but not display it.
     pub is_invisible: bool,
-    /// Block marked this frame as a keyframe.
+    /// Set when the frame is a keyframe.
     ///
-    /// Only set for files that use simple blocks.
+    /// Simple blocks mark keyframes in their header. Frames of block groups are keyframes
+    /// if the block group doesn't contain a `ReferenceBlock`.
     pub is_keyframe: Option<bool>,
     /// Set when the frame can be discarded during playing if needed.
     ///
     /// Only set for files that use simple blocks.
     pub is_discardable: Option<bool>,
+    /// The index of the frame inside the lace and the number of frames in the lace.
+    ///
+    /// Only set when the frame was part of a laced block.
+    pub lace_index: Option<(u32, u32)>,
+    /// The duration of the block the frame belongs to.
+    ///
+    /// Only set for frames of block groups that contain a `BlockDuration`, or from the
+    /// `DefaultDuration` of the track if `ParserSettings::default_duration_fallback()`
+    /// is enabled.
+    pub duration: Option<u64>,
+    /// The timestamp scale of the segment in nanoseconds, which all timestamps of the frame
+    /// are expressed in.
+    pub timestamp_scale: NonZeroU64,
+    /// The byte ranges of the laced frames inside `data`.
+    ///
+    /// Only set when `ParserSettings::combine_laced_frames()` is enabled and the frame
+    /// contains all frames of a laced block.
+    pub lace_ranges: Vec<std::ops::Range<usize>>,
+    /// The kind of block the frame was stored in.
+    pub block_kind: BlockKind,
+    /// The byte range of the `BlockGroup` the frame was stored in, including its header.
+    ///
+    /// Only set for frames of block groups.
+    pub block_group_range: Option<std::ops::Range<u64>>,
+    /// The importance of the block the frame belongs to for decoding other blocks.
+    /// A value of 0 means the block is not referenced by other blocks.
+    ///
+    /// Only set for frames of block groups.
+    pub reference_priority: Option<u64>,
+    /// A new codec state that the decoder has to use from this frame on.
+    ///
+    /// Set for frames of block groups that contain a `CodecState`. After a seek, it's also
+    /// set for the first frame of a track if a codec state was found for the track on the
+    /// way to the seek target, so that the decoder can be re-initialized.
+    pub codec_state: Option<Vec<u8>>,
+    /// The encryption header of the frame, which is stripped from `data`.
+    ///
+    /// Only set for frames of tracks with an AES `ContentEncryption`. The data is decrypted
+    /// if a `ContentDecryptor` is registered for the track.
+    pub encryption: Option<FrameEncryptionInfo>,
+    /// The type of the track the frame belongs to.
+    ///
+    /// Only set when `ParserSettings::stamp_track_type()` is enabled and the track is
+    /// present in the `Tracks` element.
+    pub track_type: Option<TrackType>,
+    /// Set for frames without data that were synthesized by the demuxer.
+    ///
+    /// Only emitted when `ParserSettings::clear_expired_subtitles()` is enabled.
+    pub is_virtual: bool,
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Self {
+            track: 0,
+            timestamp: 0,
+            is_timestamp_clamped: false,
+            signed_timestamp: 0,
+            cluster_timestamp: 0,
+            relative_timestamp: 0,
+            presentation_timestamp: 0,
+            data: Vec::new(),
+            compression: None,
+            is_invisible: false,
+            is_keyframe: None,
+            is_discardable: None,
+            lace_index: None,
+            duration: None,
+            timestamp_scale: Timestamp::default().scale(),
+            lace_ranges: Vec::new(),
+            block_kind: BlockKind::SimpleBlock,
+            block_group_range: None,
+            reference_priority: None,
+            codec_state: None,
+            track_type: None,
+            is_virtual: false,
+            encryption: None,
+        }
+    }
+}
+
+impl Frame {
+    /// The timestamp of the frame together with its timestamp scale.
+    pub fn time(&self) -> Timestamp {
+        Timestamp::new(self.signed_timestamp, self.timestamp_scale)
+    }
+
+    /// The presentation timestamp of the frame together with its timestamp scale.
+    pub fn presentation_time(&self) -> Timestamp {
+        Timestamp::new(self.presentation_timestamp, self.timestamp_scale)
+    }
+
+    /// The duration of the block the frame belongs to together with its timestamp scale.
+    pub fn duration_time(&self) -> Option<Timestamp> {
+        self.duration
+            .map(|duration| ticks_to_timestamp(duration, self.timestamp_scale))
+    }
+
+    /// Shrinks the capacity of the frame data to its length.
+    ///
+    /// The data buffer is reused between frames and only ever grows. Call this after
+    /// reading an unusually large frame to release the memory.
+    pub fn shrink_to_fit(&mut self) {
+        self.data.shrink_to_fit();
+    }
 }
 
 impl From<Vec<u8>> for Frame {
@@ -93,6 +327,293 @@ impl From<Vec<u8>> for Frame {
     }
 }
 
+/// An event that occurs while demuxing, returned by `MatroskaFile::next_event()`.
+///
+/// All timestamps are expressed in the timestamp scale of the segment.
+#[derive(Clone, Debug)]
+pub enum DemuxEvent {
+    /// The next frame was read into the given `Frame`.
+    Frame,
+    /// A cluster with the given timestamp started.
+    ClusterStart {
+        /// The timestamp of the cluster.
+        timestamp: u64,
+    },
+    /// A `Chapters` element was found after the first cluster, for example inside a live
+    /// stream. Contains the byte range of the element including its header, which can be
+    /// parsed via `MatroskaFile::read_chapters()`.
+    Chapters(std::ops::Range<u64>),
+    /// A `Tags` element was found after the first cluster, for example inside a live stream.
+    /// Contains the byte range of the element including its header, which can be parsed via
+    /// `MatroskaFile::read_tags()`.
+    Tags(std::ops::Range<u64>),
+    /// An `Attachments` element was found after the first cluster. Contains the byte range
+    /// of the element including its header, which can be read via
+    /// `MatroskaFile::read_byte_range()`.
+    Attachments(std::ops::Range<u64>),
+    /// The distance of the next frame of the track to its previous frame is larger than the
+    /// `DefaultDuration` of the track. The frame is returned by the next event.
+    Gap {
+        /// The track of the frame.
+        track: u64,
+        /// The timestamp of the previous frame of the track.
+        previous_timestamp: u64,
+        /// The timestamp of the frame.
+        timestamp: u64,
+    },
+    /// The timestamp of a cluster is smaller than the timestamp of the previous cluster, as
+    /// found in files that were created by appending the segments of several files.
+    /// Followed by the `DemuxEvent::ClusterStart` of the cluster.
+    TimestampDiscontinuity {
+        /// The timestamp of the previous cluster.
+        previous_timestamp: u64,
+        /// The timestamp of the cluster as stored in the file.
+        timestamp: u64,
+        /// The offset that is added to the timestamps from this cluster on. Always 0 unless
+        /// `ParserSettings::stitch_timestamps()` is enabled.
+        offset: u64,
+    },
+    /// The segment ended. Either the file ends or a new segment follows, which is
+    /// reported by the next event.
+    EndOfSegment,
+    /// A new EBML header and `Segment` started inside the stream, as emitted by some live
+    /// streams after a restart. The index was replaced by the metadata of the new segment,
+    /// so the tracks and their track numbers may have changed.
+    TracksChanged,
+}
+
+/// A block inside the Matroska container, that was neither de-laced nor read.
+///
+/// Remuxers can copy the laced payload verbatim by reading `payload_range()` via
+/// `MatroskaFile::read_byte_range()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RawBlock {
+    /// The ID of the track.
+    pub track: u64,
+    /// The timestamp of the block. Can be negative when the block is placed before the
+    /// start of the segment.
+    pub timestamp: i64,
+    /// The flags byte of the block header, including the lacing bits.
+    pub flags: u8,
+    /// Set when the block is a `SimpleBlock`, unset when it's the `Block` of a `BlockGroup`.
+    pub is_simple_block: bool,
+    /// The offset of the payload inside the file, which starts with the lacing header
+    /// for laced blocks.
+    pub laced_payload_offset: u64,
+    /// The size of the payload in bytes.
+    pub size: u64,
+    /// The duration of the block.
+    ///
+    /// Only set for blocks of block groups that contain a `BlockDuration`.
+    pub duration: Option<u64>,
+    /// The timestamp scale of the segment in nanoseconds, which all timestamps of the block
+    /// are expressed in.
+    pub timestamp_scale: NonZeroU64,
+}
+
+impl RawBlock {
+    /// The timestamp of the block together with its timestamp scale.
+    pub fn time(&self) -> Timestamp {
+        Timestamp::new(self.timestamp, self.timestamp_scale)
+    }
+
+    /// The duration of the block together with its timestamp scale.
+    pub fn duration_time(&self) -> Option<Timestamp> {
+        self.duration
+            .map(|duration| ticks_to_timestamp(duration, self.timestamp_scale))
+    }
+
+    /// The byte range of the payload inside the file.
+    pub fn payload_range(&self) -> std::ops::Range<u64> {
+        self.laced_payload_offset..self.laced_payload_offset + self.size
+    }
+}
+
+/// The progress of reading through the segment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Progress {
+    /// The position of the reader relative to the start of the segment data in bytes.
+    pub position: u64,
+    /// The size of the segment data in bytes. `None` if the segment has an unknown size,
+    /// which happens for live streams.
+    pub segment_size: Option<u64>,
+}
+
+impl Progress {
+    /// The read fraction of the segment in the range of `0.0` to `1.0`.
+    #[allow(clippy::as_conversions)]
+    pub fn fraction(&self) -> Option<f64> {
+        match self.segment_size {
+            Some(0) | None => None,
+            Some(size) => Some((self.position.min(size) as f64) / (size as f64)),
+        }
+    }
+}
+
+/// The result of a seek that respects the `SeekPreRoll` and `CodecDelay` of a track.
+///
+/// All timestamps are expressed in the timestamp scale of the segment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PreRollSeek {
+    /// The block timestamp the demuxer was positioned at.
+    pub seek_timestamp: u64,
+    /// Decoded output with a presentation timestamp smaller than this timestamp
+    /// must be discarded.
+    pub discard_until: u64,
+    /// The duration of the decoded output that must be discarded.
+    pub discard_duration: u64,
+}
+
+/// The result of a seek operation.
+///
+/// All timestamps are expressed in the timestamp scale of the segment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SeekOutcome {
+    /// The timestamp that was requested.
+    pub requested: u64,
+    /// The timestamp of the block the demuxer is positioned at. This is the first block at
+    /// or after the requested timestamp. `None` if the requested timestamp is after the
+    /// last block of the file.
+    pub reached: Option<u64>,
+    /// True if the `Cues` were used to find the cluster.
+    pub used_cues: bool,
+    /// The absolute position of the cluster that contains the reached block.
+    pub cluster_offset: Option<u64>,
+}
+
+/// A cluster aligned part of the file that can be appended to a Media Source Extensions
+/// buffer after the initialization segment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MediaSegment {
+    /// The timestamp of the first cluster of the media segment.
+    pub timestamp: u64,
+    /// The byte range of the media segment inside the file.
+    pub range: std::ops::Range<u64>,
+}
+
+/// The layout of the file as needed by WebM DASH and Media Source Extensions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StreamingLayout {
+    /// The byte range of the initialization segment, which contains the EBML header
+    /// and all top level elements of the segment before the first cluster.
+    pub init_segment: std::ops::Range<u64>,
+    /// The media segments in the order of the file. Every media segment starts at a cluster
+    /// referenced by the `Cues` and ends before the next referenced cluster.
+    pub media_segments: Vec<MediaSegment>,
+}
+
+/// A track that couldn't be parsed and was skipped in lenient mode.
+#[derive(Debug)]
+pub struct TrackError {
+    /// The offset of the `TrackEntry` data inside the file.
+    pub offset: u64,
+    /// The track number, if it could be read.
+    pub track_number: Option<u64>,
+    /// The error that occurred while parsing the track.
+    pub error: DemuxError,
+}
+
+/// An entry of the `SeekHead` that doesn't point at the element it declares, found with
+/// `SeekHeadPolicy::Repair`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SeekHeadMismatch {
+    /// The element declared by the entry.
+    pub element_id: ElementId,
+    /// The offset inside the file the entry points at.
+    pub offset: u64,
+    /// The element found at the offset, or `None` if no element header could be read.
+    pub found: Option<ElementId>,
+    /// The offset of the element found by the scan of the top level elements, or `None` if
+    /// the element couldn't be found and the entry was removed.
+    pub repaired_offset: Option<u64>,
+}
+
+/// An element that was skipped while demuxing frames.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SkippedElement {
+    /// The raw Element ID.
+    pub id: u32,
+    /// The known Element ID, `ElementId::Custom` for registered elements or
+    /// `ElementId::Unknown`.
+    pub element_id: ElementId,
+    /// The offset of the element header inside the file.
+    pub offset: u64,
+    /// The size of the element data. `u64::MAX` if the size is unknown.
+    pub size: u64,
+}
+
+/// An offset that is added to the timestamps of the following clusters to stitch
+/// appended parts of a file, see `ParserSettings::stitch_timestamps()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimestampOffset {
+    /// The offset of the first cluster the offset applies to.
+    pub position: u64,
+    /// The timestamp of the cluster as stored in the file.
+    pub timestamp: u64,
+    /// The offset that is added to the timestamps of the cluster and all following clusters.
+    pub offset: u64,
+}
+
+/// The state used to detect and stitch timestamp discontinuities.
+#[derive(Clone, Debug, Default)]
+struct StitchState {
+    /// The position of the current cluster.
+    cluster_position: u64,
+    /// The stitched timestamp of the previous cluster.
+    previous_cluster_timestamp: Option<u64>,
+    /// The largest end of a frame read so far.
+    max_frame_end: u64,
+    /// The offset that is currently applied.
+    offset: u64,
+}
+
+/// The state of the demuxer that depends on the position of the reader. It's saved while
+/// the file is read for another purpose, for example to analyze it.
+#[derive(Debug)]
+struct SavedState {
+    position: u64,
+    index: Arc<MatroskaIndex>,
+    cluster_timestamp: u64,
+    queued_frames: VecDeque<LacedFrame>,
+    current_element: Option<(u64, ElementId)>,
+    parent_elements: Vec<(ElementId, u64)>,
+    silent_tracks: Vec<u64>,
+    last_timestamps: HashMap<u64, u64>,
+    end_of_segment: bool,
+    pending_codec_states: HashMap<u64, Vec<u8>>,
+    subtitle_expiries: HashMap<u64, u64>,
+    held_event: Option<(Frame, Option<DemuxEvent>)>,
+    pending_event: Option<DemuxEvent>,
+    pending_frame: Option<Frame>,
+    stitch: StitchState,
+    cluster_end: Option<u64>,
+    block_group_end: Option<u64>,
+    needs_first_cluster: bool,
+    skipped_elements: usize,
+    resume_position: Option<(u64, u64)>,
+    unknown_size_cluster: bool,
+}
+
+/// Callback that is invoked every time a new cluster is entered.
+#[derive(Clone)]
+struct ProgressCallback(Arc<dyn Fn(Progress) + Send + Sync>);
+
+impl std::fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ProgressCallback")
+    }
+}
+
+/// Invoked when the end of a followed file is reached.
+#[derive(Clone)]
+struct FollowCallback(Arc<dyn Fn(u32) -> bool + Send + Sync>);
+
+impl std::fmt::Debug for FollowCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("FollowCallback")
+    }
+}
+
 /// The EBML header of the file.
 #[derive(Clone, Debug)]
 pub struct EbmlHeader {
@@ -108,7 +629,11 @@ pub struct EbmlHeader {
 impl<R: Read + Seek> ParsableElement<R> for EbmlHeader {
     type Output = Self;
 
-    fn new(_r: &mut R, fields: &[(ElementId, ElementData)]) -> Result<Self> {
+    fn new(
+        _r: &mut R,
+        fields: &[(ElementId, ElementData)],
+        _options: ParseOptions,
+    ) -> Result<Self> {
         let version = try_find_unsigned(fields, ElementId::EbmlVersion)?;
         let read_version = try_find_unsigned(fields, ElementId::EbmlReadVersion)?;
         let max_id_length = find_unsigned_or(fields, ElementId::EbmlMaxIdLength, 4)?;
@@ -117,10 +642,7 @@ impl<R: Read + Seek> ParsableElement<R> for EbmlHeader {
         let doc_type_version = find_unsigned(fields, ElementId::DocTypeVersion)?;
         let doc_type_read_version = find_unsigned(fields, ElementId::DocTypeReadVersion)?;
 
-        // The spec allows Null-terminated strings.
-        let trimmed_doc_type = doc_type.trim_end_matches('\0');
-
-        if trimmed_doc_type != "matroska" && trimmed_doc_type != "webm" {
+        if doc_type != "matroska" && doc_type != "webm" {
             return Err(DemuxError::InvalidEbmlHeader(format!(
                 "unsupported DocType: {}",
                 doc_type
@@ -195,6 +717,14 @@ impl EbmlHeader {
     pub fn doc_type_read_version(&self) -> u64 {
         self.doc_type_read_version
     }
+
+    /// The limits that element headers have to follow in strict mode.
+    pub(crate) fn header_limits(&self) -> HeaderLimits {
+        HeaderLimits {
+            max_id_length: self.max_id_length,
+            max_size_length: self.max_size_length,
+        }
+    }
 }
 
 /// Contains general information about the segment.
@@ -204,20 +734,29 @@ pub struct Info {
     duration: Option<f64>,
     date_utc: Option<i64>,
     title: Option<String>,
+    invalid_title: Option<Vec<u8>>,
     muxing_app: String,
     writing_app: String,
+    chapter_translates: Vec<ChapterTranslate>,
 }
 
 impl<R: Read + Seek> ParsableElement<R> for Info {
     type Output = Self;
 
-    fn new(_r: &mut R, fields: &[(ElementId, ElementData)]) -> Result<Self> {
+    fn new(r: &mut R, fields: &[(ElementId, ElementData)], options: ParseOptions) -> Result<Self> {
         let timestamp_scale = find_nonzero_or(fields, ElementId::TimestampScale, 1000000)?;
         let duration = try_find_float(fields, ElementId::Duration)?;
         let date_utc = try_find_date(fields, ElementId::DateUtc)?;
         let title = try_find_string(fields, ElementId::Title)?;
+        let invalid_title = try_find_invalid_string(fields, ElementId::Title);
         let muxing_app = find_string(fields, ElementId::MuxingApp)?;
         let writing_app = find_string(fields, ElementId::WritingApp)?;
+        let chapter_translates = find_children_in_fields::<_, ChapterTranslate>(
+            r,
+            fields,
+            ElementId::ChapterTranslate,
+            options,
+        )?;
 
         if let Some(duration) = duration {
             if duration < 0.0 {
@@ -230,8 +769,10 @@ impl<R: Read + Seek> ParsableElement<R> for Info {
             duration,
             date_utc,
             title,
+            invalid_title,
             muxing_app,
             writing_app,
+            chapter_translates,
         })
     }
 }
@@ -247,11 +788,44 @@ impl Info {
         self.duration
     }
 
+    /// Duration of the Segment as a `Timestamp`, rounded to whole nanoseconds.
+    #[allow(clippy::as_conversions)]
+    pub fn duration_time(&self) -> Option<Timestamp> {
+        let nanos = (self.duration? * self.timestamp_scale.get() as f64).round();
+        // Float to integer casts saturate.
+        Some(Timestamp::from_nanos(nanos as i64))
+    }
+
     /// The date and time that the Segment was created by the muxing application or library.
+    ///
+    /// Expressed in nanoseconds relative to the Matroska epoch (2001-01-01T00:00:00 UTC).
     pub fn date_utc(&self) -> Option<i64> {
         self.date_utc
     }
 
+    /// The date and time that the Segment was created as a `SystemTime`.
+    pub fn date_utc_as_system_time(&self) -> Option<SystemTime> {
+        let date_utc = self.date_utc?;
+        let epoch = UNIX_EPOCH.checked_add(Duration::from_secs(MATROSKA_EPOCH_UNIX_SECONDS))?;
+        let offset = Duration::from_nanos(date_utc.unsigned_abs());
+        if date_utc >= 0 {
+            epoch.checked_add(offset)
+        } else {
+            epoch.checked_sub(offset)
+        }
+    }
+
+    /// The date and time that the Segment was created as a `chrono::DateTime`.
+    #[cfg(feature = "chrono")]
+    pub fn date_utc_as_date_time(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        let date_utc = self.date_utc?;
+        let seconds = date_utc
+            .div_euclid(1_000_000_000)
+            .checked_add(i64::try_from(MATROSKA_EPOCH_UNIX_SECONDS).ok()?)?;
+        let nanoseconds = u32::try_from(date_utc.rem_euclid(1_000_000_000)).ok()?;
+        chrono::DateTime::from_timestamp(seconds, nanoseconds)
+    }
+
     /// General name of the Segment.
     pub fn title(&self) -> Option<&str> {
         match self.title.as_ref() {
@@ -260,15 +834,71 @@ impl Info {
         }
     }
 
+    /// The raw bytes of the title, if it is not valid UTF-8. Such titles are only accepted
+    /// in lenient mode and decoded lossily by `title()`.
+    pub fn invalid_title(&self) -> Option<&[u8]> {
+        self.invalid_title.as_deref()
+    }
+
     /// Muxing application or library.
+    ///
+    /// Empty if the element is missing and the file was parsed in lenient mode.
     pub fn muxing_app(&self) -> &str {
         &self.muxing_app
     }
 
     /// Writing  application.
+    ///
+    /// Empty if the element is missing and the file was parsed in lenient mode.
     pub fn writing_app(&self) -> &str {
         &self.writing_app
     }
+
+    /// The mapping between this Segment and a segment value in the given Chapter Codec.
+    pub fn chapter_translates(&self) -> &[ChapterTranslate] {
+        self.chapter_translates.as_ref()
+    }
+}
+
+/// The mapping between a segment and a segment value in the given Chapter Codec.
+#[derive(Clone, Debug)]
+pub struct ChapterTranslate {
+    id: Vec<u8>,
+    codec: TranslateCodec,
+    edition_uids: Vec<u64>,
+}
+
+impl<R: Read + Seek> ParsableElement<R> for ChapterTranslate {
+    type Output = Self;
+
+    fn new(r: &mut R, fields: &[(ElementId, ElementData)], _options: ParseOptions) -> Result<Self> {
+        let id = find_binary(r, fields, ElementId::ChapterTranslateId)?;
+        let codec = find_custom_type(fields, ElementId::ChapterTranslateCodec)?;
+        let edition_uids = find_all_unsigned(fields, ElementId::ChapterTranslateEditionUid)?;
+
+        Ok(Self {
+            id,
+            codec,
+            edition_uids,
+        })
+    }
+}
+
+impl ChapterTranslate {
+    /// The binary value used to represent this segment in the chapter codec data.
+    pub fn id(&self) -> &[u8] {
+        self.id.as_ref()
+    }
+
+    /// The chapter codec the translation applies to.
+    pub fn codec(&self) -> TranslateCodec {
+        self.codec
+    }
+
+    /// The editions the mapping applies to. If empty, it applies to all editions of the segment.
+    pub fn edition_uids(&self) -> &[u64] {
+        self.edition_uids.as_ref()
+    }
 }
 
 /// Describes a track.
@@ -282,22 +912,33 @@ pub struct TrackEntry {
     flag_forced: bool,
     flag_lacing: bool,
     default_duration: Option<NonZeroU64>,
+    default_decoded_field_duration: Option<NonZeroU64>,
+    min_cache: u64,
+    max_cache: Option<u64>,
+    max_block_addition_id: u64,
     name: Option<String>,
+    invalid_name: Option<Vec<u8>>,
     language: Option<String>,
+    language_ietf: Option<String>,
     codec_id: String,
     codec_private: Option<Vec<u8>>,
     codec_name: Option<String>,
     codec_delay: Option<u64>,
     seek_pre_roll: Option<u64>,
+    block_addition_mappings: Vec<BlockAdditionMapping>,
+    track_translates: Vec<TrackTranslate>,
+    operation: Option<TrackOperation>,
     audio: Option<Audio>,
     video: Option<Video>,
     content_encodings: Option<Vec<ContentEncoding>>,
+    /// Set from `ParserSettings::max_decompressed_size()` after parsing.
+    max_decompressed_size: usize,
 }
 
 impl<R: Read + Seek> ParsableElement<R> for TrackEntry {
     type Output = Self;
 
-    fn new(r: &mut R, fields: &[(ElementId, ElementData)]) -> Result<Self> {
+    fn new(r: &mut R, fields: &[(ElementId, ElementData)], options: ParseOptions) -> Result<Self> {
         let track_number = find_nonzero(fields, ElementId::TrackNumber)?;
         let track_uid = find_nonzero(fields, ElementId::TrackUid)?;
         let track_type = find_custom_type(fields, ElementId::TrackType)?;
@@ -306,22 +947,45 @@ impl<R: Read + Seek> ParsableElement<R> for TrackEntry {
         let flag_forced = find_bool_or(fields, ElementId::FlagForced, false)?;
         let flag_lacing = find_bool_or(fields, ElementId::FlagLacing, false)?;
         let default_duration = try_find_nonzero(fields, ElementId::DefaultDuration)?;
+        let default_decoded_field_duration =
+            try_find_nonzero(fields, ElementId::DefaultDecodedFieldDuration)?;
+        let min_cache = find_unsigned_or(fields, ElementId::MinCache, 0)?;
+        let max_cache = try_find_unsigned(fields, ElementId::MaxCache)?;
+        let max_block_addition_id = find_unsigned_or(fields, ElementId::MaxBlockAdditionId, 0)?;
         let name = try_find_string(fields, ElementId::Name)?;
+        let invalid_name = try_find_invalid_string(fields, ElementId::Name);
         let language = try_find_string(fields, ElementId::Language)?;
+        let language_ietf = try_find_string(fields, ElementId::LanguageIetf)?;
         let codec_id = find_string(fields, ElementId::CodecId)?;
         let codec_private = try_find_binary(r, fields, ElementId::CodecPrivate)?;
         let codec_name = try_find_string(fields, ElementId::CodecName)?;
         let codec_delay = try_find_unsigned(fields, ElementId::CodecDelay)?;
         let seek_pre_roll = try_find_unsigned(fields, ElementId::SeekPreRoll)?;
 
-        let audio = try_parse_child::<_, Audio>(r, fields, ElementId::Audio)?;
-        let video = try_parse_child::<_, Video>(r, fields, ElementId::Video)?;
+        let block_addition_mappings = find_children_in_fields::<_, BlockAdditionMapping>(
+            r,
+            fields,
+            ElementId::BlockAdditionMapping,
+            options,
+        )?;
+        let track_translates = find_children_in_fields::<_, TrackTranslate>(
+            r,
+            fields,
+            ElementId::TrackTranslate,
+            options,
+        )?;
+        let operation =
+            try_parse_child::<_, TrackOperation>(r, fields, ElementId::TrackOperation, options)?;
+
+        let audio = try_parse_child::<_, Audio>(r, fields, ElementId::Audio, options)?;
+        let video = try_parse_child::<_, Video>(r, fields, ElementId::Video, options)?;
 
         let content_encodings = try_parse_children::<_, ContentEncoding>(
             r,
             fields,
             ElementId::ContentEncodings,
             ElementId::ContentEncoding,
+            options,
         )?;
 
         Ok(Self {
@@ -333,16 +997,26 @@ impl<R: Read + Seek> ParsableElement<R> for TrackEntry {
             flag_forced,
             flag_lacing,
             default_duration,
+            default_decoded_field_duration,
+            min_cache,
+            max_cache,
+            max_block_addition_id,
             name,
+            invalid_name,
             language,
+            language_ietf,
             codec_id,
             codec_private,
             codec_name,
             codec_delay,
             seek_pre_roll,
+            block_addition_mappings,
+            track_translates,
+            operation,
             audio,
             video,
             content_encodings,
+            max_decompressed_size: DEFAULT_MAX_DECOMPRESSED_SIZE,
         })
     }
 }
@@ -392,6 +1066,37 @@ impl TrackEntry {
         self.default_duration
     }
 
+    /// The `DefaultDuration` in ticks of the given timestamp scale. Rounded towards zero
+    /// like `Timestamp::rescale()`.
+    pub(crate) fn default_duration_ticks(&self, timestamp_scale: NonZeroU64) -> Option<u64> {
+        self.default_duration
+            .map(|duration| duration.get() / timestamp_scale.get())
+    }
+
+    /// The period in nanoseconds (not scaled by TimestampScale) between two successive fields
+    /// at the output of the decoding process.
+    pub fn default_decoded_field_duration(&self) -> Option<NonZeroU64> {
+        self.default_decoded_field_duration
+    }
+
+    /// The minimum number of frames a player should be able to cache during playback.
+    /// If set to 0, the reference pseudo-cache system is not used.
+    pub fn min_cache(&self) -> u64 {
+        self.min_cache
+    }
+
+    /// The maximum cache size necessary to store referenced frames in and the current frame.
+    /// 0 means no cache is needed.
+    pub fn max_cache(&self) -> Option<u64> {
+        self.max_cache
+    }
+
+    /// The maximum value of BlockAddID. A value of 0 means there is no BlockAdditions
+    /// for this track.
+    pub fn max_block_addition_id(&self) -> u64 {
+        self.max_block_addition_id
+    }
+
     /// A human-readable track name.
     pub fn name(&self) -> Option<&str> {
         match self.name.as_ref() {
@@ -400,6 +1105,12 @@ impl TrackEntry {
         }
     }
 
+    /// The raw bytes of the track name, if it is not valid UTF-8. In lenient mode `name()`
+    /// returns the name with the invalid bytes replaced by `U+FFFD`.
+    pub fn invalid_name(&self) -> Option<&[u8]> {
+        self.invalid_name.as_deref()
+    }
+
     /// Specifies the language of the track.
     pub fn language(&self) -> Option<&str> {
         match self.language.as_ref() {
@@ -408,12 +1119,38 @@ impl TrackEntry {
         }
     }
 
+    /// Specifies the language of the track according to BCP47 and using the
+    /// IANA Language Subtag Registry. Takes precedence over `language()` when present.
+    pub fn language_ietf(&self) -> Option<&str> {
+        match self.language_ietf.as_ref() {
+            None => None,
+            Some(language_ietf) => Some(language_ietf),
+        }
+    }
+
     /// An ID corresponding to the codec.
     pub fn codec_id(&self) -> &str {
         &self.codec_id
     }
 
-    /// Private data only known to the codec.
+    /// Returns `true` if the codec ID is a video codec ID, which starts with `V_`.
+    pub fn is_video_codec(&self) -> bool {
+        self.codec_id.starts_with("V_")
+    }
+
+    /// Returns `true` if the codec ID is an audio codec ID, which starts with `A_`.
+    pub fn is_audio_codec(&self) -> bool {
+        self.codec_id.starts_with("A_")
+    }
+
+    /// Returns `true` if the codec ID is a subtitle codec ID, which starts with `S_`, or
+    /// a WebVTT codec ID of WebM, which starts with `D_WEBVTT/`.
+    pub fn is_subtitle_codec(&self) -> bool {
+        self.codec_id.starts_with("S_") || self.codec_id.starts_with("D_WEBVTT/")
+    }
+
+    /// Private data only known to the codec, as stored in the file. See
+    /// `decoded_codec_private()` for tracks whose private data is compressed.
     pub fn codec_private(&self) -> Option<&[u8]> {
         match self.codec_private.as_ref() {
             None => None,
@@ -421,6 +1158,28 @@ impl TrackEntry {
         }
     }
 
+    /// The `CodecPrivate` of the track with its `ContentCompression` reversed, if the
+    /// `ContentEncodingScope` of the compression includes `ContentEncodingScope::PRIVATE`.
+    ///
+    /// Supports the same algorithms as the frames, so bzlib and LZO1X need the `bzip2` and
+    /// `lzo` features. An encrypted `CodecPrivate` is returned as stored. A zlib compressed
+    /// `CodecPrivate` is returned as stored, too, which can be detected with
+    /// `content_compression()`.
+    pub fn decoded_codec_private(&self) -> Result<Option<Cow<'_, [u8]>>> {
+        let codec_private = match self.codec_private.as_ref() {
+            None => return Ok(None),
+            Some(codec_private) => codec_private,
+        };
+        match self.content_compression(ContentEncodingScope::PRIVATE) {
+            None => Ok(Some(Cow::Borrowed(codec_private))),
+            Some(compression) => {
+                let mut data = codec_private.clone();
+                decompress_frame(compression, &mut data, &mut [], self.max_decompressed_size)?;
+                Ok(Some(Cow::Owned(data)))
+            }
+        }
+    }
+
     /// A human-readable string specifying the codec.
     pub fn codec_name(&self) -> Option<&str> {
         match self.codec_name.as_ref() {
@@ -441,6 +1200,22 @@ impl TrackEntry {
         self.seek_pre_roll
     }
 
+    /// Describes how the BlockAdditional data of the track's blocks should be interpreted.
+    pub fn block_addition_mappings(&self) -> &[BlockAdditionMapping] {
+        self.block_addition_mappings.as_ref()
+    }
+
+    /// The mapping between this track and a track value in the given Chapter Codec.
+    pub fn track_translates(&self) -> &[TrackTranslate] {
+        self.track_translates.as_ref()
+    }
+
+    /// Describes how this track is combined from other tracks, for example the planes
+    /// of a 3D video.
+    pub fn operation(&self) -> Option<&TrackOperation> {
+        self.operation.as_ref()
+    }
+
     /// Video settings.
     pub fn video(&self) -> Option<&Video> {
         self.video.as_ref()
@@ -451,6 +1226,14 @@ impl TrackEntry {
         self.audio.as_ref()
     }
 
+    /// The statistics of the track that were written by the muxer into the given tags,
+    /// for example the bitrate and the number of frames written by mkvmerge.
+    ///
+    /// Returns `None` if the tags contain no statistics for the track.
+    pub fn muxer_statistics(&self, tags: &[Tag]) -> Option<MuxerStatistics> {
+        MuxerStatistics::from_tags(self, tags)
+    }
+
     /// Settings for several content encoding mechanisms like compression or encryption.
     pub fn content_encodings(&self) -> Option<&[ContentEncoding]> {
         match &self.content_encodings {
@@ -458,90 +1241,319 @@ impl TrackEntry {
             Some(content_encodings) => Some(content_encodings),
         }
     }
+
+    /// The encryption settings of the track, if its frames are encrypted with AES.
+    pub(crate) fn content_encryption(&self) -> Option<&ContentEncryption> {
+        self.content_encodings
+            .iter()
+            .flatten()
+            .filter(|encoding| encoding.scope().contains(ContentEncodingScope::FRAME))
+            .filter_map(|encoding| encoding.encryption())
+            .find(|encryption| encryption.algo() == ContentEncAlgo::Aes)
+    }
+
+    /// The compression settings that apply to the given scope, if it is compressed.
+    pub(crate) fn content_compression(
+        &self,
+        scope: ContentEncodingScope,
+    ) -> Option<&ContentCompression> {
+        self.content_encodings
+            .iter()
+            .flatten()
+            .filter(|encoding| encoding.scope().contains(scope))
+            .find_map(|encoding| encoding.compression())
+    }
 }
 
-/// Audio settings.
+/// Describes the contents of the BlockAdditional data of a track.
 #[derive(Clone, Debug)]
-pub struct Audio {
-    sampling_frequency: f64,
-    output_sampling_frequency: Option<f64>,
-    channels: NonZeroU64,
-    bit_depth: Option<NonZeroU64>,
+pub struct BlockAdditionMapping {
+    id_value: Option<u64>,
+    id_name: Option<String>,
+    id_type: u64,
+    id_extra_data: Option<Vec<u8>>,
 }
 
-impl<R: Read + Seek> ParsableElement<R> for Audio {
+impl<R: Read + Seek> ParsableElement<R> for BlockAdditionMapping {
     type Output = Self;
 
-    fn new(_r: &mut R, fields: &[(ElementId, ElementData)]) -> Result<Self> {
-        let sampling_frequency = find_float_or(fields, ElementId::SamplingFrequency, 8000.0)?;
-        let output_sampling_frequency = try_find_float(fields, ElementId::OutputSamplingFrequency)?;
-        let channels = find_nonzero_or(fields, ElementId::Channels, 1)?;
-        let bit_depth = try_find_nonzero(fields, ElementId::BitDepth)?;
-
-        if sampling_frequency < 0.0 {
-            return Err(DemuxError::PositiveValueIsNotPositive);
-        }
-
-        if let Some(output_sampling_frequency) = output_sampling_frequency {
-            if output_sampling_frequency < 0.0 {
-                return Err(DemuxError::PositiveValueIsNotPositive);
-            }
-        }
+    fn new(r: &mut R, fields: &[(ElementId, ElementData)], _options: ParseOptions) -> Result<Self> {
+        let id_value = try_find_unsigned(fields, ElementId::BlockAddIdValue)?;
+        let id_name = try_find_string(fields, ElementId::BlockAddIdName)?;
+        let id_type = find_unsigned_or(fields, ElementId::BlockAddIdType, 0)?;
+        let id_extra_data = try_find_binary(r, fields, ElementId::BlockAddIdExtraData)?;
 
-        Ok(Audio {
-            sampling_frequency,
-            output_sampling_frequency,
-            channels,
-            bit_depth,
+        Ok(Self {
+            id_value,
+            id_name,
+            id_type,
+            id_extra_data,
         })
     }
 }
 
-impl Audio {
-    /// Sampling frequency in Hz.
-    pub fn sampling_frequency(&self) -> f64 {
-        self.sampling_frequency
+impl BlockAdditionMapping {
+    /// The BlockAddID value being described. Used to match the BlockAddID of a BlockMore element.
+    pub fn id_value(&self) -> Option<u64> {
+        self.id_value
     }
 
-    /// Real output sampling frequency in Hz.
-    pub fn output_sampling_frequency(&self) -> Option<f64> {
-        self.output_sampling_frequency
+    /// A human-friendly name describing the type of BlockAdditional data.
+    pub fn id_name(&self) -> Option<&str> {
+        match self.id_name.as_ref() {
+            None => None,
+            Some(id_name) => Some(id_name),
+        }
     }
 
-    /// Numbers of channels in the track.
-    pub fn channels(&self) -> NonZeroU64 {
-        self.channels
+    /// Stores the registered identifier of the Block Additional Mapping
+    /// to define how the BlockAdditional data should be handled.
+    pub fn id_type(&self) -> u64 {
+        self.id_type
     }
 
-    /// Bits per sample.
-    pub fn bit_depth(&self) -> Option<NonZeroU64> {
-        self.bit_depth
+    /// Extra binary data that the BlockAddIDType can use to interpret the BlockAdditional data.
+    pub fn id_extra_data(&self) -> Option<&[u8]> {
+        match self.id_extra_data.as_ref() {
+            None => None,
+            Some(id_extra_data) => Some(id_extra_data),
+        }
     }
 }
 
-/// Video settings.
+/// The mapping between a track and a track value in the given Chapter Codec.
 #[derive(Clone, Debug)]
-pub struct Video {
-    flag_interlaced: FlagInterlaced,
-    stereo_mode: Option<StereoMode>,
-    alpha_mode: Option<u64>,
-    pixel_width: NonZeroU64,
-    pixel_height: NonZeroU64,
-    pixel_crop_bottom: Option<u64>,
-    pixel_crop_top: Option<u64>,
-    pixel_crop_left: Option<u64>,
-    pixel_crop_right: Option<u64>,
-    display_width: Option<NonZeroU64>,
-    display_height: Option<NonZeroU64>,
-    display_unit: Option<DisplayUnit>,
-    aspect_ratio_type: Option<AspectRatioType>,
-    colour: Option<Colour>,
+pub struct TrackTranslate {
+    track_id: Vec<u8>,
+    codec: TranslateCodec,
+    edition_uids: Vec<u64>,
+}
+
+impl<R: Read + Seek> ParsableElement<R> for TrackTranslate {
+    type Output = Self;
+
+    fn new(r: &mut R, fields: &[(ElementId, ElementData)], _options: ParseOptions) -> Result<Self> {
+        let track_id = find_binary(r, fields, ElementId::TrackTranslateTrackId)?;
+        let codec = find_custom_type(fields, ElementId::TrackTranslateCodec)?;
+        let edition_uids = find_all_unsigned(fields, ElementId::TrackTranslateEditionUid)?;
+
+        Ok(Self {
+            track_id,
+            codec,
+            edition_uids,
+        })
+    }
+}
+
+impl TrackTranslate {
+    /// The binary value used to represent this track in the chapter codec data.
+    pub fn track_id(&self) -> &[u8] {
+        self.track_id.as_ref()
+    }
+
+    /// The chapter codec the translation applies to.
+    pub fn codec(&self) -> TranslateCodec {
+        self.codec
+    }
+
+    /// The editions the mapping applies to. If empty, it applies to all editions of the segment.
+    pub fn edition_uids(&self) -> &[u64] {
+        self.edition_uids.as_ref()
+    }
+}
+
+/// Describes an operation that needs to be applied on tracks to create this virtual track.
+#[derive(Clone, Debug)]
+pub struct TrackOperation {
+    combine_planes: Vec<TrackPlane>,
+    join_blocks: Vec<u64>,
+}
+
+impl<R: Read + Seek> ParsableElement<R> for TrackOperation {
+    type Output = Self;
+
+    fn new(r: &mut R, fields: &[(ElementId, ElementData)], options: ParseOptions) -> Result<Self> {
+        let combine_planes = try_parse_children::<_, TrackPlane>(
+            r,
+            fields,
+            ElementId::TrackCombinePlanes,
+            ElementId::TrackPlane,
+            options,
+        )?
+        .unwrap_or_default();
+        let join_blocks =
+            try_parse_child::<_, TrackJoinBlocks>(r, fields, ElementId::TrackJoinBlocks, options)?
+                .unwrap_or_default();
+
+        Ok(Self {
+            combine_planes,
+            join_blocks,
+        })
+    }
+}
+
+impl TrackOperation {
+    /// The video planes to combine to create this track.
+    pub fn combine_planes(&self) -> &[TrackPlane] {
+        self.combine_planes.as_ref()
+    }
+
+    /// The UIDs of the tracks whose blocks are joined to create this track.
+    pub fn join_blocks(&self) -> &[u64] {
+        self.join_blocks.as_ref()
+    }
+}
+
+/// Contains a video plane track that needs to be combined to create this track.
+#[derive(Clone, Copy, Debug)]
+pub struct TrackPlane {
+    uid: NonZeroU64,
+    plane_type: TrackPlaneType,
+}
+
+impl<R: Read + Seek> ParsableElement<R> for TrackPlane {
+    type Output = Self;
+
+    fn new(
+        _r: &mut R,
+        fields: &[(ElementId, ElementData)],
+        _options: ParseOptions,
+    ) -> Result<Self> {
+        let uid = find_nonzero(fields, ElementId::TrackPlaneUid)?;
+        let plane_type = find_custom_type(fields, ElementId::TrackPlaneType)?;
+
+        Ok(Self { uid, plane_type })
+    }
+}
+
+impl TrackPlane {
+    /// The UID of the track that is used as a plane.
+    pub fn uid(&self) -> NonZeroU64 {
+        self.uid
+    }
+
+    /// The kind of plane the track represents.
+    pub fn plane_type(&self) -> TrackPlaneType {
+        self.plane_type
+    }
+}
+
+/// The UIDs of the tracks whose blocks are joined.
+struct TrackJoinBlocks;
+
+impl<R: Read + Seek> ParsableElement<R> for TrackJoinBlocks {
+    type Output = Vec<u64>;
+
+    fn new(
+        _r: &mut R,
+        fields: &[(ElementId, ElementData)],
+        _options: ParseOptions,
+    ) -> Result<Vec<u64>> {
+        find_all_unsigned(fields, ElementId::TrackJoinUid)
+    }
+}
+
+/// Audio settings.
+#[derive(Clone, Debug)]
+pub struct Audio {
+    sampling_frequency: f64,
+    output_sampling_frequency: Option<f64>,
+    channels: NonZeroU64,
+    bit_depth: Option<NonZeroU64>,
+    emphasis: Emphasis,
+    channel_positions: Option<Vec<u8>>,
+}
+
+impl<R: Read + Seek> ParsableElement<R> for Audio {
+    type Output = Self;
+
+    fn new(r: &mut R, fields: &[(ElementId, ElementData)], _options: ParseOptions) -> Result<Self> {
+        let sampling_frequency = find_float_or(fields, ElementId::SamplingFrequency, 8000.0)?;
+        let output_sampling_frequency = try_find_float(fields, ElementId::OutputSamplingFrequency)?;
+        let channels = find_nonzero_or(fields, ElementId::Channels, 1)?;
+        let bit_depth = try_find_nonzero(fields, ElementId::BitDepth)?;
+        let emphasis = try_find_custom_type_or(fields, ElementId::Emphasis, Emphasis::NoEmphasis)?;
+        let channel_positions = try_find_binary(r, fields, ElementId::ChannelPositions)?;
+
+        if sampling_frequency < 0.0 {
+            return Err(DemuxError::PositiveValueIsNotPositive);
+        }
+
+        if let Some(output_sampling_frequency) = output_sampling_frequency {
+            if output_sampling_frequency < 0.0 {
+                return Err(DemuxError::PositiveValueIsNotPositive);
+            }
+        }
+
+        Ok(Audio {
+            sampling_frequency,
+            output_sampling_frequency,
+            channels,
+            bit_depth,
+            emphasis,
+            channel_positions,
+        })
+    }
+}
+
+impl Audio {
+    /// Sampling frequency in Hz.
+    pub fn sampling_frequency(&self) -> f64 {
+        self.sampling_frequency
+    }
+
+    /// Real output sampling frequency in Hz.
+    pub fn output_sampling_frequency(&self) -> Option<f64> {
+        self.output_sampling_frequency
+    }
+
+    /// Numbers of channels in the track.
+    pub fn channels(&self) -> NonZeroU64 {
+        self.channels
+    }
+
+    /// Bits per sample.
+    pub fn bit_depth(&self) -> Option<NonZeroU64> {
+        self.bit_depth
+    }
+
+    /// The audio emphasis applied on the audio samples.
+    pub fn emphasis(&self) -> Emphasis {
+        self.emphasis
+    }
+
+    /// Table of horizontal angles for each successive channel.
+    ///
+    /// The format of the table is not specified, so it's returned as stored. Use
+    /// [`ChannelLayout::from_track()`] to get the layout of the channels.
+    pub fn channel_positions(&self) -> Option<&[u8]> {
+        self.channel_positions.as_deref()
+    }
+}
+
+/// Video settings.
+#[derive(Clone, Debug)]
+pub struct Video {
+    flag_interlaced: FlagInterlaced,
+    stereo_mode: Option<StereoMode>,
+    alpha_mode: Option<u64>,
+    pixel_width: NonZeroU64,
+    pixel_height: NonZeroU64,
+    pixel_crop_bottom: Option<u64>,
+    pixel_crop_top: Option<u64>,
+    pixel_crop_left: Option<u64>,
+    pixel_crop_right: Option<u64>,
+    display_width: Option<NonZeroU64>,
+    display_height: Option<NonZeroU64>,
+    display_unit: Option<DisplayUnit>,
+    aspect_ratio_type: Option<AspectRatioType>,
+    colour: Option<Colour>,
 }
 
 impl<R: Read + Seek> ParsableElement<R> for Video {
     type Output = Self;
 
-    fn new(r: &mut R, fields: &[(ElementId, ElementData)]) -> Result<Self> {
+    fn new(r: &mut R, fields: &[(ElementId, ElementData)], options: ParseOptions) -> Result<Self> {
         let flag_interlaced =
             try_find_custom_type_or(fields, ElementId::FlagInterlaced, FlagInterlaced::Unknown)?;
         let stereo_mode = try_find_custom_type(fields, ElementId::StereoMode)?;
@@ -556,7 +1568,7 @@ impl<R: Read + Seek> ParsableElement<R> for Video {
         let display_height = try_find_nonzero(fields, ElementId::DisplayHeight)?;
         let display_unit = try_find_custom_type(fields, ElementId::DisplayUnit)?;
         let aspect_ratio_type = try_find_custom_type(fields, ElementId::AspectRatioType)?;
-        let colour = try_parse_child::<_, Colour>(r, fields, ElementId::Colour)?;
+        let colour = try_parse_child::<_, Colour>(r, fields, ElementId::Colour, options)?;
 
         Ok(Self {
             flag_interlaced,
@@ -675,7 +1687,7 @@ pub struct Colour {
 impl<R: Read + Seek> ParsableElement<R> for Colour {
     type Output = Self;
 
-    fn new(r: &mut R, fields: &[(ElementId, ElementData)]) -> Result<Self> {
+    fn new(r: &mut R, fields: &[(ElementId, ElementData)], options: ParseOptions) -> Result<Self> {
         let matrix_coefficients = try_find_custom_type(fields, ElementId::MatrixCoefficients)?;
         let bits_per_channel = try_find_unsigned(fields, ElementId::BitsPerChannel)?;
         let chroma_subsampling_horz = try_find_unsigned(fields, ElementId::ChromaSubsamplingHorz)?;
@@ -688,10 +1700,14 @@ impl<R: Read + Seek> ParsableElement<R> for Colour {
         let transfer_characteristics =
             try_find_custom_type(fields, ElementId::TransferCharacteristics)?;
         let primaries = try_find_custom_type(fields, ElementId::Primaries)?;
-        let max_cll = try_find_unsigned(fields, ElementId::MatrixCoefficients)?;
-        let max_fall = try_find_unsigned(fields, ElementId::MatrixCoefficients)?;
-        let mastering_metadata =
-            try_parse_child::<_, MasteringMetadata>(r, fields, ElementId::MasteringMetadata)?;
+        let max_cll = try_find_unsigned(fields, ElementId::MaxCll)?;
+        let max_fall = try_find_unsigned(fields, ElementId::MaxFall)?;
+        let mastering_metadata = try_parse_child::<_, MasteringMetadata>(
+            r,
+            fields,
+            ElementId::MasteringMetadata,
+            options,
+        )?;
 
         Ok(Self {
             matrix_coefficients,
@@ -784,6 +1800,58 @@ impl Colour {
     pub fn mastering_metadata(&self) -> Option<&MasteringMetadata> {
         self.mastering_metadata.as_ref()
     }
+
+    /// The HDR metadata of the video.
+    ///
+    /// Returns `None` if neither the content light levels nor the mastering metadata
+    /// are present.
+    pub fn hdr_metadata(&self) -> Option<HdrMetadata> {
+        let mastering_display = self
+            .mastering_metadata
+            .as_ref()
+            .and_then(MasteringMetadata::mastering_display);
+
+        if self.max_cll.is_none() && self.max_fall.is_none() && mastering_display.is_none() {
+            return None;
+        }
+
+        Some(HdrMetadata {
+            max_cll: self.max_cll,
+            max_fall: self.max_fall,
+            mastering_display,
+        })
+    }
+}
+
+/// The static HDR metadata of a video, which consists of the content light levels
+/// (CTA-861.3) and the mastering display colour volume (SMPTE 2086).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct HdrMetadata {
+    /// Maximum brightness of a single pixel (cd/m^2^).
+    pub max_cll: Option<u64>,
+    /// Maximum brightness of a single full frame (cd/m^2^).
+    pub max_fall: Option<u64>,
+    /// The mastering display colour volume.
+    pub mastering_display: Option<MasteringDisplay>,
+}
+
+/// The SMPTE 2086 mastering display colour volume.
+///
+/// All coordinates are CIE 1931 xy chromaticity coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MasteringDisplay {
+    /// The red primary.
+    pub primary_r: (f64, f64),
+    /// The green primary.
+    pub primary_g: (f64, f64),
+    /// The blue primary.
+    pub primary_b: (f64, f64),
+    /// The white point.
+    pub white_point: (f64, f64),
+    /// Maximum luminance in candelas per square meter (cd/m^2^).
+    pub luminance_max: f64,
+    /// Minimum luminance in candelas per square meter (cd/m^2^).
+    pub luminance_min: f64,
 }
 
 /// SMPTE 2086 mastering data.
@@ -804,13 +1872,17 @@ pub struct MasteringMetadata {
 impl<R: Read + Seek> ParsableElement<R> for MasteringMetadata {
     type Output = Self;
 
-    fn new(_r: &mut R, fields: &[(ElementId, ElementData)]) -> Result<Self> {
+    fn new(
+        _r: &mut R,
+        fields: &[(ElementId, ElementData)],
+        _options: ParseOptions,
+    ) -> Result<Self> {
         let primary_r_chromaticity_x = try_find_float(fields, ElementId::PrimaryRChromaticityX)?;
-        let primary_r_chromaticity_y = try_find_float(fields, ElementId::PrimaryRChromaticityX)?;
+        let primary_r_chromaticity_y = try_find_float(fields, ElementId::PrimaryRChromaticityY)?;
         let primary_g_chromaticity_x = try_find_float(fields, ElementId::PrimaryGChromaticityX)?;
-        let primary_g_chromaticity_y = try_find_float(fields, ElementId::PrimaryGChromaticityX)?;
+        let primary_g_chromaticity_y = try_find_float(fields, ElementId::PrimaryGChromaticityY)?;
         let primary_b_chromaticity_x = try_find_float(fields, ElementId::PrimaryBChromaticityX)?;
-        let primary_b_chromaticity_y = try_find_float(fields, ElementId::PrimaryBChromaticityX)?;
+        let primary_b_chromaticity_y = try_find_float(fields, ElementId::PrimaryBChromaticityY)?;
         let white_point_chromaticity_x =
             try_find_float(fields, ElementId::WhitePointChromaticityX)?;
         let white_point_chromaticity_y =
@@ -883,23 +1955,52 @@ impl MasteringMetadata {
     pub fn luminance_min(&self) -> Option<f64> {
         self.luminance_min
     }
+
+    /// The mastering display colour volume. Returns `None` if any value is missing.
+    pub fn mastering_display(&self) -> Option<MasteringDisplay> {
+        Some(MasteringDisplay {
+            primary_r: (
+                self.primary_r_chromaticity_x?,
+                self.primary_r_chromaticity_y?,
+            ),
+            primary_g: (
+                self.primary_g_chromaticity_x?,
+                self.primary_g_chromaticity_y?,
+            ),
+            primary_b: (
+                self.primary_b_chromaticity_x?,
+                self.primary_b_chromaticity_y?,
+            ),
+            white_point: (
+                self.white_point_chromaticity_x?,
+                self.white_point_chromaticity_y?,
+            ),
+            luminance_max: self.luminance_max?,
+            luminance_min: self.luminance_min?,
+        })
+    }
 }
 
 /// Settings for one content encoding like compression or encryption.
 #[derive(Clone, Debug)]
 pub struct ContentEncoding {
     order: u64,
-    scope: u64,
+    scope: ContentEncodingScope,
     encoding_type: ContentEncodingType,
+    compression: Option<ContentCompression>,
     encryption: Option<ContentEncryption>,
 }
 
 impl<R: Read + Seek> ParsableElement<R> for ContentEncoding {
     type Output = Self;
 
-    fn new(r: &mut R, fields: &[(ElementId, ElementData)]) -> Result<Self> {
+    fn new(r: &mut R, fields: &[(ElementId, ElementData)], options: ParseOptions) -> Result<Self> {
         let order = find_unsigned_or(fields, ElementId::ContentEncodingOrder, 0)?;
-        let scope = find_unsigned_or(fields, ElementId::ContentEncodingScope, 1)?;
+        let scope = ContentEncodingScope::from(find_unsigned_or(
+            fields,
+            ElementId::ContentEncodingScope,
+            ContentEncodingScope::FRAME.bits(),
+        )?);
 
         let encoding_type = try_find_custom_type_or(
             fields,
@@ -907,13 +2008,24 @@ impl<R: Read + Seek> ParsableElement<R> for ContentEncoding {
             ContentEncodingType::Compression,
         )?;
 
-        let encryption =
-            try_parse_child::<_, ContentEncryption>(r, fields, ElementId::ContentEncryption)?;
+        let compression = try_parse_child::<_, ContentCompression>(
+            r,
+            fields,
+            ElementId::ContentCompression,
+            options,
+        )?;
+        let encryption = try_parse_child::<_, ContentEncryption>(
+            r,
+            fields,
+            ElementId::ContentEncryption,
+            options,
+        )?;
 
         Ok(Self {
             order,
             scope,
             encoding_type,
+            compression,
             encryption,
         })
     }
@@ -926,14 +2038,8 @@ impl ContentEncoding {
         self.order
     }
 
-    /// A bit field that describes which Elements have been modified in this way.
-    ///
-    /// Values (big-endian) can be OR'ed:
-    ///
-    /// 1 - All frame contents, excluding lacing data.
-    /// 2 - The track's private data.
-    /// 4 - The next ContentEncoding.
-    pub fn scope(&self) -> u64 {
+    /// Describes which elements have been modified in this way.
+    pub fn scope(&self) -> ContentEncodingScope {
         self.scope
     }
 
@@ -942,12 +2048,52 @@ impl ContentEncoding {
         self.encoding_type
     }
 
+    /// Settings describing the compression used.
+    pub fn compression(&self) -> Option<&ContentCompression> {
+        self.compression.as_ref()
+    }
+
     /// Settings describing the encryption used.
     pub fn encryption(&self) -> Option<&ContentEncryption> {
         self.encryption.as_ref()
     }
 }
 
+/// Settings describing the compression used.
+#[derive(Clone, Debug)]
+pub struct ContentCompression {
+    algo: ContentCompAlgo,
+    settings: Option<Vec<u8>>,
+}
+
+impl<R: Read + Seek> ParsableElement<R> for ContentCompression {
+    type Output = Self;
+
+    fn new(r: &mut R, fields: &[(ElementId, ElementData)], _options: ParseOptions) -> Result<Self> {
+        let algo =
+            try_find_custom_type_or(fields, ElementId::ContentCompAlgo, ContentCompAlgo::Zlib)?;
+        let settings = try_find_binary(r, fields, ElementId::ContentCompSettings)?;
+
+        Ok(Self { algo, settings })
+    }
+}
+
+impl ContentCompression {
+    /// The compression algorithm used.
+    pub fn algo(&self) -> ContentCompAlgo {
+        self.algo
+    }
+
+    /// Settings that might be needed by the decompressor. For header stripping these are
+    /// the bytes that were removed from the start of every frame.
+    pub fn settings(&self) -> Option<&[u8]> {
+        match self.settings.as_ref() {
+            None => None,
+            Some(settings) => Some(settings),
+        }
+    }
+}
+
 /// Settings describing the encryption used.
 #[derive(Clone, Debug)]
 pub struct ContentEncryption {
@@ -959,7 +2105,7 @@ pub struct ContentEncryption {
 impl<R: Read + Seek> ParsableElement<R> for ContentEncryption {
     type Output = Self;
 
-    fn new(r: &mut R, fields: &[(ElementId, ElementData)]) -> Result<Self> {
+    fn new(r: &mut R, fields: &[(ElementId, ElementData)], options: ParseOptions) -> Result<Self> {
         let algo = try_find_custom_type_or(
             fields,
             ElementId::ContentEncAlgo,
@@ -970,6 +2116,7 @@ impl<R: Read + Seek> ParsableElement<R> for ContentEncryption {
             r,
             fields,
             ElementId::ContentEncAesSettings,
+            options,
         )?;
 
         Ok(Self {
@@ -1009,7 +2156,11 @@ pub struct ContentEncAesSettings {
 impl<R: Read + Seek> ParsableElement<R> for ContentEncAesSettings {
     type Output = Self;
 
-    fn new(_r: &mut R, fields: &[(ElementId, ElementData)]) -> Result<Self> {
+    fn new(
+        _r: &mut R,
+        fields: &[(ElementId, ElementData)],
+        _options: ParseOptions,
+    ) -> Result<Self> {
         let aes_settings_cipher_mode =
             try_find_custom_type(fields, ElementId::AesSettingsCipherMode)?;
 
@@ -1029,25 +2180,177 @@ impl ContentEncAesSettings {
 /// Contains all information about a segment edition.
 #[derive(Clone, Debug)]
 pub struct EditionEntry {
+    uid: Option<NonZeroU64>,
+    flag_hidden: bool,
+    flag_default: bool,
+    flag_ordered: bool,
+    displays: Vec<EditionDisplay>,
     chapter_atoms: Vec<ChapterAtom>,
 }
 
 impl<R: Read + Seek> ParsableElement<R> for EditionEntry {
     type Output = Self;
 
-    fn new(r: &mut R, fields: &[(ElementId, ElementData)]) -> Result<Self> {
+    fn new(r: &mut R, fields: &[(ElementId, ElementData)], options: ParseOptions) -> Result<Self> {
+        let uid = try_find_nonzero(fields, ElementId::EditionUid)?;
+        let flag_hidden = find_bool_or(fields, ElementId::EditionFlagHidden, false)?;
+        let flag_default = find_bool_or(fields, ElementId::EditionFlagDefault, false)?;
+        let flag_ordered = find_bool_or(fields, ElementId::EditionFlagOrdered, false)?;
+        let displays = find_children_in_fields::<_, EditionDisplay>(
+            r,
+            fields,
+            ElementId::EditionDisplay,
+            options,
+        )?;
         let chapter_atoms =
-            find_children_in_fields::<_, ChapterAtom>(r, fields, ElementId::ChapterAtom)?;
+            find_children_in_fields::<_, ChapterAtom>(r, fields, ElementId::ChapterAtom, options)?;
 
-        Ok(Self { chapter_atoms })
+        Ok(Self {
+            uid,
+            flag_hidden,
+            flag_default,
+            flag_ordered,
+            displays,
+            chapter_atoms,
+        })
     }
 }
 
 impl EditionEntry {
+    /// A unique ID to identify the edition.
+    pub fn uid(&self) -> Option<NonZeroU64> {
+        self.uid
+    }
+
+    /// Set if the edition should be hidden in the user interface.
+    pub fn flag_hidden(&self) -> bool {
+        self.flag_hidden
+    }
+
+    /// Set if the edition should be used as the default one.
+    pub fn flag_default(&self) -> bool {
+        self.flag_default
+    }
+
+    /// Set if the chapters of the edition are ordered chapters, which define the order
+    /// in which the segment is played.
+    pub fn flag_ordered(&self) -> bool {
+        self.flag_ordered
+    }
+
+    /// Contains all possible strings to use for the edition display.
+    pub fn displays(&self) -> &[EditionDisplay] {
+        self.displays.as_ref()
+    }
+
     /// Contains the atom information to use as the chapter atom (apply to all tracks).
     pub fn chapter_atoms(&self) -> &[ChapterAtom] {
         self.chapter_atoms.as_ref()
     }
+
+    /// Returns the innermost chapter that plays at the given time in nanoseconds.
+    ///
+    /// A chapter without `ChapterTimeEnd` ends where the next chapter on the same level
+    /// starts, or at the end of the segment.
+    pub fn chapter_at(&self, timestamp_ns: u64) -> Option<&ChapterAtom> {
+        find_chapter(&self.chapter_atoms, timestamp_ns)
+    }
+
+    /// Returns the first start or end of a chapter after the given time in nanoseconds.
+    pub fn next_chapter_boundary(&self, timestamp_ns: u64) -> Option<u64> {
+        next_chapter_boundary(&self.chapter_atoms, timestamp_ns)
+    }
+}
+
+/// Finds the innermost chapter of the atoms that plays at the given time.
+fn find_chapter(atoms: &[ChapterAtom], timestamp_ns: u64) -> Option<&ChapterAtom> {
+    let mut found = None;
+    let mut atoms = atoms;
+    loop {
+        // A chapter without an end only plays until the next sibling starts, so of those
+        // only the ones with the latest start before the time can play.
+        let latest_start = atoms
+            .iter()
+            .map(|atom| atom.time_start)
+            .filter(|&start| start <= timestamp_ns)
+            .max();
+        let atom = atoms
+            .iter()
+            .filter(|atom| match atom.time_end {
+                Some(end) => atom.time_start <= timestamp_ns && timestamp_ns < end,
+                None => Some(atom.time_start) == latest_start,
+            })
+            .max_by_key(|atom| atom.time_start);
+        match atom {
+            Some(atom) => {
+                found = Some(atom);
+                atoms = &atom.children;
+            }
+            None => return found,
+        }
+    }
+}
+
+/// Finds the first start or end of the atoms and their sub-chapters after the given time.
+fn next_chapter_boundary(atoms: &[ChapterAtom], timestamp_ns: u64) -> Option<u64> {
+    atoms
+        .iter()
+        .flat_map(|atom| {
+            [
+                Some(atom.time_start),
+                atom.time_end,
+                next_chapter_boundary(&atom.children, timestamp_ns),
+            ]
+        })
+        .flatten()
+        .filter(|&time| time > timestamp_ns)
+        .min()
+}
+
+/// Contains a string to use for the edition display.
+#[derive(Clone, Debug)]
+pub struct EditionDisplay {
+    string: String,
+    invalid_string: Option<Vec<u8>>,
+    languages_ietf: Vec<String>,
+}
+
+impl<R: Read + Seek> ParsableElement<R> for EditionDisplay {
+    type Output = Self;
+
+    fn new(
+        _r: &mut R,
+        fields: &[(ElementId, ElementData)],
+        _options: ParseOptions,
+    ) -> Result<Self> {
+        let string = find_string(fields, ElementId::EditionString)?;
+        let invalid_string = try_find_invalid_string(fields, ElementId::EditionString);
+        let languages_ietf = find_all_strings(fields, ElementId::EditionLanguageIetf)?;
+
+        Ok(Self {
+            string,
+            invalid_string,
+            languages_ietf,
+        })
+    }
+}
+
+impl EditionDisplay {
+    /// Contains the string to use as the edition name.
+    pub fn string(&self) -> &str {
+        self.string.as_ref()
+    }
+
+    /// The raw bytes of the edition name, if it is not valid UTF-8.
+    pub fn invalid_string(&self) -> Option<&[u8]> {
+        self.invalid_string.as_deref()
+    }
+
+    /// The languages corresponding to the string according to BCP47 and using the
+    /// IANA Language Subtag Registry.
+    pub fn languages_ietf(&self) -> &[String] {
+        self.languages_ietf.as_ref()
+    }
 }
 
 /// Contains the atom information to use as the chapter atom.
@@ -1058,26 +2361,69 @@ pub struct ChapterAtom {
     time_start: u64,
     time_end: Option<u64>,
     displays: Vec<ChapterDisplay>,
+    processes: Vec<ChapterProcess>,
+    children: Vec<ChapterAtom>,
 }
 
 impl<R: Read + Seek> ParsableElement<R> for ChapterAtom {
     type Output = Self;
 
-    fn new(r: &mut R, fields: &[(ElementId, ElementData)]) -> Result<Self> {
+    fn new(r: &mut R, fields: &[(ElementId, ElementData)], options: ParseOptions) -> Result<Self> {
+        Self::parse(r, fields, 0, options)
+    }
+}
+
+impl ChapterAtom {
+    /// Parses the chapter and its nested chapters, which are limited to `MAX_DEPTH` levels.
+    fn parse<R: Read + Seek>(
+        r: &mut R,
+        fields: &[(ElementId, ElementData)],
+        depth: usize,
+        options: ParseOptions,
+    ) -> Result<Self> {
         let uid = find_nonzero(fields, ElementId::ChapterUid)?;
         let string_uid = try_find_string(fields, ElementId::ChapterStringUid)?;
         let time_start = find_unsigned(fields, ElementId::ChapterTimeStart)?;
         let time_end = try_find_unsigned(fields, ElementId::ChapterTimeEnd)?;
 
-        let displays =
-            find_children_in_fields::<_, ChapterDisplay>(r, fields, ElementId::ChapterDisplay)?;
+        let displays = find_children_in_fields::<_, ChapterDisplay>(
+            r,
+            fields,
+            ElementId::ChapterDisplay,
+            options,
+        )?;
+        let processes = find_children_in_fields::<_, ChapterProcess>(
+            r,
+            fields,
+            ElementId::ChapProcess,
+            options,
+        )?;
 
-        Ok(Self {
-            uid,
+        let mut children = vec![];
+        for (_, data) in fields
+            .iter()
+            .filter(|(id, _)| *id == ElementId::ChapterAtom)
+        {
+            if depth + 1 >= MAX_DEPTH {
+                return Err(DemuxError::NestingTooDeep(ElementId::ChapterAtom));
+            }
+            if let ElementData::Location { offset, size } = data {
+                let child_fields = collect_children(r, *offset, *size, options.limits)?;
+                check_strings(&child_fields, options.lossy_strings)?;
+                children.push(Self::parse(r, &child_fields, depth + 1, options)?);
+            } else {
+                return Err(DemuxError::UnexpectedDataType);
+            }
+        }
+
+        Ok(Self {
+            uid,
             string_uid,
             time_start,
             time_end,
             displays,
+            processes,
+            children,
         })
     }
 }
@@ -1106,16 +2452,123 @@ impl ChapterAtom {
         self.time_end
     }
 
+    /// Timestamp of the start of Chapter as a `Timestamp`.
+    pub fn start_time(&self) -> Timestamp {
+        ticks_to_timestamp(self.time_start, NonZeroU64::MIN)
+    }
+
+    /// Timestamp of the end of Chapter as a `Timestamp`.
+    pub fn end_time(&self) -> Option<Timestamp> {
+        self.time_end
+            .map(|time_end| ticks_to_timestamp(time_end, NonZeroU64::MIN))
+    }
+
     /// Contains all possible strings to use for the chapter display.
     pub fn displays(&self) -> &[ChapterDisplay] {
         self.displays.as_ref()
     }
+
+    /// Contains all the commands associated to the chapter.
+    pub fn processes(&self) -> &[ChapterProcess] {
+        self.processes.as_ref()
+    }
+
+    /// The sub-chapters of the chapter. Chapters nested deeper than 64 levels are rejected
+    /// with `DemuxError::NestingTooDeep`.
+    pub fn children(&self) -> &[ChapterAtom] {
+        self.children.as_ref()
+    }
+}
+
+/// Contains all the commands associated to the chapter for a chapter codec.
+#[derive(Clone, Debug)]
+pub struct ChapterProcess {
+    codec_id: TranslateCodec,
+    private: Option<Vec<u8>>,
+    commands: Vec<ChapterProcessCommand>,
+}
+
+impl<R: Read + Seek> ParsableElement<R> for ChapterProcess {
+    type Output = Self;
+
+    fn new(r: &mut R, fields: &[(ElementId, ElementData)], options: ParseOptions) -> Result<Self> {
+        let codec_id = try_find_custom_type_or(
+            fields,
+            ElementId::ChapProcessCodecId,
+            TranslateCodec::MatroskaScript,
+        )?;
+        let private = try_find_binary(r, fields, ElementId::ChapProcessPrivate)?;
+        let commands = find_children_in_fields::<_, ChapterProcessCommand>(
+            r,
+            fields,
+            ElementId::ChapProcessCommand,
+            options,
+        )?;
+
+        Ok(Self {
+            codec_id,
+            private,
+            commands,
+        })
+    }
+}
+
+impl ChapterProcess {
+    /// The chapter codec the commands are written for.
+    pub fn codec_id(&self) -> TranslateCodec {
+        self.codec_id
+    }
+
+    /// Optional data attached to the codec. For the DVD-menu codec it contains
+    /// the DVD level and number.
+    pub fn private(&self) -> Option<&[u8]> {
+        match self.private.as_ref() {
+            None => None,
+            Some(private) => Some(private),
+        }
+    }
+
+    /// The commands of the chapter codec.
+    pub fn commands(&self) -> &[ChapterProcessCommand] {
+        self.commands.as_ref()
+    }
+}
+
+/// A command of a chapter codec.
+#[derive(Clone, Debug)]
+pub struct ChapterProcessCommand {
+    time: ChapterProcessTime,
+    data: Vec<u8>,
+}
+
+impl<R: Read + Seek> ParsableElement<R> for ChapterProcessCommand {
+    type Output = Self;
+
+    fn new(r: &mut R, fields: &[(ElementId, ElementData)], _options: ParseOptions) -> Result<Self> {
+        let time = find_custom_type(fields, ElementId::ChapProcessTime)?;
+        let data = find_binary(r, fields, ElementId::ChapProcessData)?;
+
+        Ok(Self { time, data })
+    }
+}
+
+impl ChapterProcessCommand {
+    /// Defines when the command should be handled.
+    pub fn time(&self) -> ChapterProcessTime {
+        self.time
+    }
+
+    /// The command data interpreted by the chapter codec.
+    pub fn data(&self) -> &[u8] {
+        self.data.as_ref()
+    }
 }
 
 /// Contains all possible strings to use for the chapter display.
 #[derive(Clone, Debug)]
 pub struct ChapterDisplay {
     string: String,
+    invalid_string: Option<Vec<u8>>,
     language: Option<String>,
     language_ietf: Option<String>,
     country: Option<String>,
@@ -1124,14 +2577,20 @@ pub struct ChapterDisplay {
 impl<R: Read + Seek> ParsableElement<R> for ChapterDisplay {
     type Output = Self;
 
-    fn new(_r: &mut R, fields: &[(ElementId, ElementData)]) -> Result<Self> {
+    fn new(
+        _r: &mut R,
+        fields: &[(ElementId, ElementData)],
+        _options: ParseOptions,
+    ) -> Result<Self> {
         let string = find_string(fields, ElementId::ChapString)?;
+        let invalid_string = try_find_invalid_string(fields, ElementId::ChapString);
         let language = try_find_string(fields, ElementId::ChapLanguage)?;
         let language_ietf = try_find_string(fields, ElementId::ChapLanguageIetf)?;
         let country = try_find_string(fields, ElementId::ChapCountry)?;
 
         Ok(Self {
             string,
+            invalid_string,
             language,
             language_ietf,
             country,
@@ -1145,6 +2604,11 @@ impl ChapterDisplay {
         self.string.as_ref()
     }
 
+    /// The raw bytes of the chapter name, if it is not valid UTF-8.
+    pub fn invalid_string(&self) -> Option<&[u8]> {
+        self.invalid_string.as_deref()
+    }
+
     /// The languages corresponding to the string, in the bibliographic ISO-639-2 form.
     pub fn language(&self) -> Option<&str> {
         match self.language.as_ref() {
@@ -1171,6 +2635,99 @@ impl ChapterDisplay {
     }
 }
 
+/// A file attached to the segment, for example a font or a cover image.
+///
+/// The data of the file is not read while parsing. Use `MatroskaFile::read_attachment()`,
+/// `MatroskaFile::read_attachment_chunks()` or `MatroskaFile::attachment_reader()` to read it.
+#[derive(Clone, Debug)]
+pub struct AttachedFile {
+    uid: NonZeroU64,
+    name: String,
+    invalid_name: Option<Vec<u8>>,
+    description: Option<String>,
+    invalid_description: Option<Vec<u8>>,
+    media_type: String,
+    data_range: std::ops::Range<u64>,
+}
+
+impl<R: Read + Seek> ParsableElement<R> for AttachedFile {
+    type Output = Self;
+
+    fn new(
+        _r: &mut R,
+        fields: &[(ElementId, ElementData)],
+        _options: ParseOptions,
+    ) -> Result<Self> {
+        let uid = find_nonzero(fields, ElementId::FileUid)?;
+        let name = find_string(fields, ElementId::FileName)?;
+        let invalid_name = try_find_invalid_string(fields, ElementId::FileName);
+        let description = try_find_string(fields, ElementId::FileDescription)?;
+        let invalid_description = try_find_invalid_string(fields, ElementId::FileDescription);
+        let media_type = find_string(fields, ElementId::FileMediaType)?;
+        let data_range = match fields.iter().find(|(id, _)| *id == ElementId::FileData) {
+            Some((_, ElementData::Location { offset, size })) => *offset..*offset + *size,
+            Some(_) => return Err(DemuxError::UnexpectedDataType),
+            None => return Err(DemuxError::ElementNotFound(ElementId::FileData)),
+        };
+
+        Ok(Self {
+            uid,
+            name,
+            invalid_name,
+            description,
+            invalid_description,
+            media_type,
+            data_range,
+        })
+    }
+}
+
+impl AttachedFile {
+    /// A unique ID to identify the attached file.
+    pub fn uid(&self) -> NonZeroU64 {
+        self.uid
+    }
+
+    /// Filename of the attached file.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The raw bytes of the filename, if it is not valid UTF-8. Filenames written on
+    /// systems with a legacy code page often are.
+    pub fn invalid_name(&self) -> Option<&[u8]> {
+        self.invalid_name.as_deref()
+    }
+
+    /// A human-friendly name for the attached file.
+    pub fn description(&self) -> Option<&str> {
+        match self.description.as_ref() {
+            None => None,
+            Some(description) => Some(description),
+        }
+    }
+
+    /// The raw bytes of the description, if it is not valid UTF-8.
+    pub fn invalid_description(&self) -> Option<&[u8]> {
+        self.invalid_description.as_deref()
+    }
+
+    /// Media type of the file following the format described in RFC 6838.
+    pub fn media_type(&self) -> &str {
+        &self.media_type
+    }
+
+    /// The byte range of the data of the file inside the Matroska file.
+    pub fn data_range(&self) -> std::ops::Range<u64> {
+        self.data_range.clone()
+    }
+
+    /// The size of the data of the file in bytes.
+    pub fn size(&self) -> u64 {
+        self.data_range.end - self.data_range.start
+    }
+}
+
 /// A single metadata descriptor.
 #[derive(Clone, Debug)]
 pub struct Tag {
@@ -1181,9 +2738,10 @@ pub struct Tag {
 impl<R: Read + Seek> ParsableElement<R> for Tag {
     type Output = Self;
 
-    fn new(r: &mut R, fields: &[(ElementId, ElementData)]) -> Result<Self> {
-        let targets = try_parse_child::<_, Targets>(r, fields, ElementId::Targets)?;
-        let simple_tags = find_children_in_fields::<_, SimpleTag>(r, fields, ElementId::SimpleTag)?;
+    fn new(r: &mut R, fields: &[(ElementId, ElementData)], options: ParseOptions) -> Result<Self> {
+        let targets = try_parse_child::<_, Targets>(r, fields, ElementId::Targets, options)?;
+        let simple_tags =
+            find_children_in_fields::<_, SimpleTag>(r, fields, ElementId::SimpleTag, options)?;
 
         Ok(Self {
             targets,
@@ -1216,7 +2774,11 @@ pub struct Targets {
 impl<R: Read + Seek> ParsableElement<R> for Targets {
     type Output = Self;
 
-    fn new(_r: &mut R, fields: &[(ElementId, ElementData)]) -> Result<Self> {
+    fn new(
+        _r: &mut R,
+        fields: &[(ElementId, ElementData)],
+        _options: ParseOptions,
+    ) -> Result<Self> {
         let target_type_value = try_find_unsigned(fields, ElementId::TargetTypeValue)?;
         let target_type = try_find_string(fields, ElementId::TargetType)?;
         let tag_track_uid = try_find_unsigned(fields, ElementId::TagTrackUid)?;
@@ -1249,17 +2811,19 @@ pub struct SimpleTag {
     language: Option<String>,
     default: Option<bool>,
     string: Option<String>,
+    invalid_string: Option<Vec<u8>>,
     binary: Option<Vec<u8>>,
 }
 
 impl<R: Read + Seek> ParsableElement<R> for SimpleTag {
     type Output = Self;
 
-    fn new(r: &mut R, fields: &[(ElementId, ElementData)]) -> Result<Self> {
+    fn new(r: &mut R, fields: &[(ElementId, ElementData)], _options: ParseOptions) -> Result<Self> {
         let name = find_string(fields, ElementId::TagName)?;
         let language = try_find_string(fields, ElementId::TagLanguage)?;
         let default = try_find_bool(fields, ElementId::TagDefault)?;
         let string = try_find_string(fields, ElementId::TagString)?;
+        let invalid_string = try_find_invalid_string(fields, ElementId::TagString);
         let binary = try_find_binary(r, fields, ElementId::TagBinary)?;
 
         Ok(Self {
@@ -1267,6 +2831,7 @@ impl<R: Read + Seek> ParsableElement<R> for SimpleTag {
             language,
             default,
             string,
+            invalid_string,
             binary,
         })
     }
@@ -1299,6 +2864,14 @@ impl SimpleTag {
         }
     }
 
+    /// The raw bytes of the value of the tag, if it is a string that is not valid UTF-8.
+    ///
+    /// Such strings are only accepted in lenient mode, in which `string()` returns the
+    /// value with the invalid bytes replaced by `U+FFFD`.
+    pub fn invalid_string(&self) -> Option<&[u8]> {
+        self.invalid_string.as_deref()
+    }
+
     /// The value of the tag, if it is binary.
     pub fn binary(&self) -> Option<&[u8]> {
         match self.binary.as_ref() {
@@ -1318,7 +2891,11 @@ struct SeekEntry {
 impl<R: Read + Seek> ParsableElement<R> for SeekEntry {
     type Output = Self;
 
-    fn new(_r: &mut R, fields: &[(ElementId, ElementData)]) -> Result<Self> {
+    fn new(
+        _r: &mut R,
+        fields: &[(ElementId, ElementData)],
+        _options: ParseOptions,
+    ) -> Result<Self> {
         let id: u32 = find_unsigned(fields, ElementId::SeekId)?.try_into()?;
         let id = id_to_element_id(id);
         let offset = find_unsigned(fields, ElementId::SeekPosition)?;
@@ -1329,143 +2906,554 @@ impl<R: Read + Seek> ParsableElement<R> for SeekEntry {
 
 /// Contains all information relative to a seek point in the segment.
 #[derive(Clone, Debug)]
-struct CuePoint {
+pub struct CuePoint {
     time: u64,
-    track_position: CueTrackPositions,
+    timestamp_scale: NonZeroU64,
+    in_nanos: bool,
+    track_positions: Vec<CueTrackPositions>,
 }
 
 impl<R: Read + Seek> ParsableElement<R> for CuePoint {
     type Output = Self;
 
-    fn new(r: &mut R, fields: &[(ElementId, ElementData)]) -> Result<Self> {
+    fn new(r: &mut R, fields: &[(ElementId, ElementData)], options: ParseOptions) -> Result<Self> {
         let time = find_unsigned(fields, ElementId::CueTime)?;
-        let track_position =
-            parse_child::<_, CueTrackPositions>(r, fields, ElementId::CueTrackPositions)?;
+        let track_positions = find_children_in_fields::<_, CueTrackPositions>(
+            r,
+            fields,
+            ElementId::CueTrackPositions,
+            options,
+        )?;
+        if track_positions.is_empty() {
+            return Err(DemuxError::ElementNotFound(ElementId::CueTrackPositions));
+        }
 
         Ok(Self {
             time,
-            track_position,
+            timestamp_scale: Timestamp::default().scale(),
+            in_nanos: false,
+            track_positions,
         })
     }
 }
 
+impl CuePoint {
+    /// Absolute timestamp of the seek point, expressed in segment ticks or in nanoseconds
+    /// if `ParserSettings::nanosecond_timestamps()` is enabled.
+    pub fn time(&self) -> u64 {
+        match self.in_nanos {
+            true => ticks_to_nanos(self.time, self.timestamp_scale),
+            false => self.time,
+        }
+    }
+
+    /// Absolute timestamp of the seek point as a `Timestamp`.
+    pub fn cue_time(&self) -> Timestamp {
+        ticks_to_timestamp(self.time, self.timestamp_scale)
+    }
+
+    /// Contains positions for different tracks corresponding to the timestamp.
+    pub fn track_positions(&self) -> &[CueTrackPositions] {
+        self.track_positions.as_ref()
+    }
+}
+
 /// Contain positions for different tracks corresponding to the timestamp.
 #[derive(Clone, Debug)]
-struct CueTrackPositions {
-    _track: u64,
+pub struct CueTrackPositions {
+    track: u64,
     cluster_position: u64,
     relative_position: Option<u64>,
-    _duration: Option<u64>,
-    _block_number: Option<u64>,
+    duration: Option<u64>,
+    timestamp_scale: NonZeroU64,
+    in_nanos: bool,
+    block_number: Option<u64>,
+    codec_state: Option<u64>,
+    references: Vec<CueReference>,
 }
 
 impl<R: Read + Seek> ParsableElement<R> for CueTrackPositions {
     type Output = Self;
 
-    fn new(_r: &mut R, fields: &[(ElementId, ElementData)]) -> Result<Self> {
+    fn new(r: &mut R, fields: &[(ElementId, ElementData)], options: ParseOptions) -> Result<Self> {
         let track = find_unsigned(fields, ElementId::CueTrack)?;
         let cluster_position = find_unsigned(fields, ElementId::CueClusterPosition)?;
         let relative_position = try_find_unsigned(fields, ElementId::CueRelativePosition)?;
         let duration = try_find_unsigned(fields, ElementId::CueDuration)?;
         let block_number = try_find_unsigned(fields, ElementId::CueBlockNumber)?;
+        // A value of 0 means that the codec state of the track entry is used.
+        let codec_state = try_find_unsigned(fields, ElementId::CueCodecState)?.filter(|p| *p != 0);
+        let references = find_children_in_fields::<_, CueReference>(
+            r,
+            fields,
+            ElementId::CueReference,
+            options,
+        )?;
 
         Ok(Self {
-            _track: track,
+            track,
             cluster_position,
             relative_position,
-            _duration: duration,
-            _block_number: block_number,
+            duration,
+            timestamp_scale: Timestamp::default().scale(),
+            in_nanos: false,
+            block_number,
+            codec_state,
+            references,
         })
     }
 }
 
-/// Demuxer for Matroska files.
+impl CueTrackPositions {
+    /// The track for which a position is given.
+    pub fn track(&self) -> u64 {
+        self.track
+    }
+
+    /// The absolute position of the cluster inside the file.
+    pub fn cluster_position(&self) -> u64 {
+        self.cluster_position
+    }
+
+    /// The relative position inside the cluster of the `SimpleBlock` or `BlockGroup`
+    /// with the timestamp of the cue point.
+    pub fn relative_position(&self) -> Option<u64> {
+        self.relative_position
+    }
+
+    /// The duration of the block, expressed in segment ticks or in nanoseconds if
+    /// `ParserSettings::nanosecond_timestamps()` is enabled.
+    pub fn duration(&self) -> Option<u64> {
+        match self.in_nanos {
+            true => self
+                .duration
+                .map(|duration| ticks_to_nanos(duration, self.timestamp_scale)),
+            false => self.duration,
+        }
+    }
+
+    /// The duration of the block as a `Timestamp`.
+    pub fn duration_time(&self) -> Option<Timestamp> {
+        self.duration
+            .map(|duration| ticks_to_timestamp(duration, self.timestamp_scale))
+    }
+
+    /// Number of the block in the specified cluster, starting with 1.
+    pub fn block_number(&self) -> Option<u64> {
+        self.block_number
+    }
+
+    /// The absolute position of the codec state that is valid at the cue point inside
+    /// the file. `None` if the codec state of the track entry is used.
+    pub fn codec_state(&self) -> Option<u64> {
+        self.codec_state
+    }
+
+    /// The earlier entries that are needed to decode the block of the cue point,
+    /// for example the keyframes a subtitle or video frame depends on.
+    pub fn references(&self) -> &[CueReference] {
+        self.references.as_ref()
+    }
+}
+
+/// A reference to an earlier cue point that is needed to decode the referencing block.
 #[derive(Clone, Debug)]
-pub struct MatroskaFile<R: Read + Seek> {
-    file: R,
+pub struct CueReference {
+    ref_time: u64,
+    timestamp_scale: NonZeroU64,
+    in_nanos: bool,
+}
+
+impl<R: Read + Seek> ParsableElement<R> for CueReference {
+    type Output = Self;
+
+    fn new(
+        _r: &mut R,
+        fields: &[(ElementId, ElementData)],
+        _options: ParseOptions,
+    ) -> Result<Self> {
+        let ref_time = find_unsigned(fields, ElementId::CueRefTime)?;
+
+        Ok(Self {
+            ref_time,
+            timestamp_scale: Timestamp::default().scale(),
+            in_nanos: false,
+        })
+    }
+}
+
+impl CueReference {
+    /// Timestamp of the referenced block, expressed in segment ticks or in nanoseconds if
+    /// `ParserSettings::nanosecond_timestamps()` is enabled.
+    pub fn ref_time(&self) -> u64 {
+        match self.in_nanos {
+            true => ticks_to_nanos(self.ref_time, self.timestamp_scale),
+            false => self.ref_time,
+        }
+    }
+
+    /// Timestamp of the referenced block as a `Timestamp`.
+    pub fn cue_ref_time(&self) -> Timestamp {
+        ticks_to_timestamp(self.ref_time, self.timestamp_scale)
+    }
+}
+
+/// A cue point of a single track, referencing a `CueTrackPositions` of the cue points.
+#[derive(Clone, Copy, Debug)]
+struct TrackCue {
+    time: u64,
+    point: usize,
+    position: usize,
+}
+
+/// The parsed metadata of a Matroska file.
+///
+/// The index is immutable after it has been parsed and can be shared between threads
+/// via an `Arc`, so that multiple readers can demux the same file concurrently without
+/// parsing the metadata again.
+#[derive(Clone, Debug)]
+pub struct MatroskaIndex {
+    /// The offset of the EBML header, so that the metadata can be parsed again.
+    header_offset: u64,
     ebml_header: EbmlHeader,
+    /// The offset of the `Segment` element, so that its data size can be rewritten.
+    segment_header_offset: u64,
+    segment_data_offset: u64,
+    segment_data_size: Option<u64>,
     seek_head: HashMap<ElementId, u64>,
+    /// The offsets of further `Chapters`, `Tags` and `Attachments` elements, which are
+    /// merged with the element referenced by the SeekHead.
+    duplicate_elements: HashMap<ElementId, Vec<u64>>,
     info: Info,
     tracks: Vec<TrackEntry>,
-    cue_points: Option<Vec<CuePoint>>,
-    chapters: Option<Vec<EditionEntry>>,
-    tags: Option<Vec<Tag>>,
-
-    /// The timestamp of the current cluster.
-    cluster_timestamp: u64,
-    /// Queued frames of a block we are currently reading.
-    queued_frames: VecDeque<LacedFrame>,
+    track_errors: Arc<[TrackError]>,
+    seek_head_mismatches: Arc<[SeekHeadMismatch]>,
+    parse_warnings: Arc<[ValidationIssue]>,
+    /// Deferred elements are only initialized once they are accessed.
+    cue_points: OnceLock<Option<Vec<CuePoint>>>,
+    /// The cue points grouped by track. Created on the first seek of a track.
+    track_cues: OnceLock<HashMap<u64, Vec<TrackCue>>>,
+    chapters: OnceLock<Option<Vec<EditionEntry>>>,
+    tags: OnceLock<Option<Vec<Tag>>>,
+    attachments: OnceLock<Option<Vec<AttachedFile>>>,
+    /// Readers created from the index inherit the settings.
+    settings: ParserSettings,
 }
 
-impl<R: Read + Seek> MatroskaFile<R> {
-    /// Opens a Matroska file.
-    pub fn open(mut file: R) -> Result<Self> {
+impl MatroskaIndex {
+    /// Parses the metadata of a Matroska file.
+    pub fn parse<R: Read + Seek>(file: R) -> Result<Self> {
+        Self::parse_with_settings(file, &ParserSettings::default())
+    }
+
+    /// Parses the metadata of a Matroska file with the given settings.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn parse_with_settings<R: Read + Seek>(
+        mut file: R,
+        settings: &ParserSettings,
+    ) -> Result<Self> {
+        let header_offset = file.stream_position()?;
         let ebml_header = parse_ebml_header(&mut file)?;
+        let limits = settings.parse_options(&ebml_header).limits;
 
-        let (segment_data_offset, _) = expect_master(&mut file, ElementId::Segment, None)?;
+        let segment_offset = file.stream_position()?;
+        let (segment_data_offset, segment_data_size) =
+            expect_master(&mut file, ElementId::Segment, None, limits)?;
+        let segment_data_size = (segment_data_size != u64::MAX).then_some(segment_data_size);
+        debug!(segment_data_offset, ?segment_data_size, "found segment");
 
-        let optional_seek_head = search_seek_head(&mut file, segment_data_offset)?;
-        let mut seek_head = parse_seek_head(&mut file, segment_data_offset, optional_seek_head)?;
+        let optional_seek_head = search_seek_head(&mut file, segment_data_offset, limits)?;
+        let mut duplicate_elements = HashMap::new();
+        let mut seek_head = parse_seek_head(
+            &mut file,
+            segment_data_offset,
+            optional_seek_head,
+            &mut duplicate_elements,
+            limits,
+        )?;
+        let seek_head_mismatches = verify_seek_head(
+            &mut file,
+            segment_data_offset,
+            &mut seek_head,
+            &mut duplicate_elements,
+            settings.seek_head_policy,
+        )?;
 
         if seek_head.is_empty() {
-            build_seek_head(&mut file, segment_data_offset, &mut seek_head)?;
+            debug!("no SeekHead found, building it from the top level elements");
+            build_seek_head(
+                &mut file,
+                segment_data_offset,
+                &mut seek_head,
+                &mut duplicate_elements,
+            )?;
+        } else if settings.scan_top_level_elements {
+            debug!("scanning the top level elements for unreferenced metadata");
+            let mut scanned = HashMap::new();
+            build_seek_head(
+                &mut file,
+                segment_data_offset,
+                &mut scanned,
+                &mut duplicate_elements,
+            )?;
+            for element_id in MERGED_ELEMENTS {
+                if let Some(offset) = scanned.get(&element_id) {
+                    match seek_head.get(&element_id) {
+                        Some(primary) if primary != offset => duplicate_elements
+                            .entry(element_id)
+                            .or_default()
+                            .push(*offset),
+                        Some(_) => {}
+                        None => {
+                            seek_head.insert(element_id, *offset);
+                        }
+                    }
+                }
+            }
+        }
+
+        for (element_id, offsets) in duplicate_elements.iter_mut() {
+            let primary = seek_head.get(element_id);
+            offsets.retain(|offset| Some(offset) != primary);
+            offsets.sort_unstable();
+            offsets.dedup();
         }
+        duplicate_elements.retain(|_, offsets| !offsets.is_empty());
 
-        if seek_head.get(&ElementId::Cluster).is_none() {
+        if settings.metadata_only {
+            debug!("skipping the search for the first cluster");
+        } else if !seek_head.contains_key(&ElementId::Cluster) {
+            debug!("SeekHead doesn't reference a cluster, searching for the first cluster");
             find_first_cluster_offset(&mut file, &mut seek_head)?;
         }
 
-        let info = parse_segment_info(&mut file, &seek_head)?;
+        if settings.strict {
+            let mut offsets: Vec<u64> = seek_head
+                .iter()
+                .filter(|(element_id, _)| **element_id != ElementId::Cluster)
+                .map(|(_, offset)| *offset)
+                .collect();
+            offsets.sort_unstable();
+            for offset in offsets {
+                check_element_placement(&mut file, ElementId::Segment, offset, limits)?;
+            }
+        }
 
-        let tracks = try_parse_top_element_collection::<_, TrackEntry>(
+        let mut parse_warnings = Vec::new();
+        let info = parse_segment_info(
             &mut file,
             &seek_head,
-            ElementId::Tracks,
-            ElementId::TrackEntry,
-        )?
-        .ok_or(DemuxError::ElementNotFound(ElementId::Tracks))?;
+            settings.lenient,
+            limits,
+            &mut parse_warnings,
+        )?;
+
+        let (mut tracks, track_errors) =
+            parse_tracks(&mut file, &seek_head, settings.lenient, limits)?;
+        for track in tracks.iter_mut() {
+            track.max_decompressed_size = settings.decompressed_size_limit();
+        }
+
+        let index = Self {
+            header_offset,
+            ebml_header,
+            segment_header_offset: segment_offset,
+            segment_data_offset,
+            segment_data_size,
+            seek_head,
+            duplicate_elements,
+            info,
+            tracks,
+            track_errors: track_errors.into(),
+            seek_head_mismatches: seek_head_mismatches.into(),
+            parse_warnings: parse_warnings.into(),
+            cue_points: OnceLock::new(),
+            track_cues: OnceLock::new(),
+            chapters: OnceLock::new(),
+            tags: OnceLock::new(),
+            attachments: OnceLock::new(),
+            settings: settings.clone(),
+        };
+
+        if settings.lazy_metadata {
+            debug!("deferring the parsing of Cues, Chapters, Tags and Attachments");
+        } else {
+            if !settings.metadata_only {
+                index.load_cue_points(&mut file)?;
+            }
+            index.load_chapters(&mut file)?;
+            index.load_tags(&mut file)?;
+            index.load_attachments(&mut file)?;
+        }
+
+        Ok(index)
+    }
+
+    /// Parses the cue points if they were deferred.
+    fn load_cue_points<R: Read + Seek>(&self, r: &mut R) -> Result<Option<&[CuePoint]>> {
+        if let Some(cue_points) = self.cue_points.get() {
+            return Ok(cue_points.as_deref());
+        }
+        debug!("parsing Cues");
 
         let mut cue_points = try_parse_top_element_collection::<_, CuePoint>(
-            &mut file,
-            &seek_head,
+            r,
+            &self.seek_head,
             ElementId::Cues,
             ElementId::CuePoint,
+            self.parse_options(),
         )?;
 
         if let Some(cue_points) = cue_points.as_mut() {
-            cue_points
-                .iter_mut()
-                .for_each(|p| p.track_position.cluster_position += segment_data_offset);
+            let timestamp_scale = self.info.timestamp_scale;
+            let in_nanos = self.settings.nanosecond_timestamps;
+            for point in cue_points.iter_mut() {
+                point.timestamp_scale = timestamp_scale;
+                point.in_nanos = in_nanos;
+                for positions in point.track_positions.iter_mut() {
+                    positions.cluster_position += self.segment_data_offset;
+                    if let Some(codec_state) = positions.codec_state.as_mut() {
+                        *codec_state += self.segment_data_offset;
+                    }
+                    positions.timestamp_scale = timestamp_scale;
+                    positions.in_nanos = in_nanos;
+                    for reference in positions.references.iter_mut() {
+                        reference.timestamp_scale = timestamp_scale;
+                        reference.in_nanos = in_nanos;
+                    }
+                }
+            }
         }
 
-        let chapters = try_parse_top_element_collection::<_, EditionEntry>(
-            &mut file,
-            &seek_head,
+        Ok(self.cue_points.get_or_init(|| cue_points).as_deref())
+    }
+
+    /// Returns the cue points of the given track, ordered like the cue points.
+    fn track_cues(&self, cue_points: &[CuePoint], track: u64) -> &[TrackCue] {
+        let track_cues = self.track_cues.get_or_init(|| {
+            let mut track_cues: HashMap<u64, Vec<TrackCue>> = HashMap::new();
+            for (point, cue_point) in cue_points.iter().enumerate() {
+                for (position, track_position) in cue_point.track_positions.iter().enumerate() {
+                    track_cues
+                        .entry(track_position.track)
+                        .or_default()
+                        .push(TrackCue {
+                            time: cue_point.time,
+                            point,
+                            position,
+                        });
+                }
+            }
+            track_cues
+        });
+        track_cues.get(&track).map_or(&[], Vec::as_slice)
+    }
+
+    /// Parses the chapters if they were deferred.
+    fn load_chapters<R: Read + Seek>(&self, r: &mut R) -> Result<Option<&[EditionEntry]>> {
+        if let Some(chapters) = self.chapters.get() {
+            return Ok(chapters.as_deref());
+        }
+        debug!("parsing Chapters");
+
+        let chapters = self.parse_merged_collection::<_, EditionEntry>(
+            r,
             ElementId::Chapters,
             ElementId::EditionEntry,
         )?;
 
-        let tags = try_parse_top_element_collection::<_, Tag>(
-            &mut file,
-            &seek_head,
-            ElementId::Tags,
-            ElementId::Tag,
+        Ok(self.chapters.get_or_init(|| chapters).as_deref())
+    }
+
+    /// Parses the tags if they were deferred.
+    fn load_tags<R: Read + Seek>(&self, r: &mut R) -> Result<Option<&[Tag]>> {
+        if let Some(tags) = self.tags.get() {
+            return Ok(tags.as_deref());
+        }
+        debug!("parsing Tags");
+
+        let tags = self.parse_merged_collection::<_, Tag>(r, ElementId::Tags, ElementId::Tag)?;
+
+        Ok(self.tags.get_or_init(|| tags).as_deref())
+    }
+
+    /// Parses the attachments if they were deferred.
+    fn load_attachments<R: Read + Seek>(&self, r: &mut R) -> Result<Option<&[AttachedFile]>> {
+        if let Some(attachments) = self.attachments.get() {
+            return Ok(attachments.as_deref());
+        }
+        debug!("parsing Attachments");
+
+        let attachments = self.parse_merged_collection::<_, AttachedFile>(
+            r,
+            ElementId::Attachments,
+            ElementId::AttachedFile,
         )?;
 
-        seek_to_first_cluster(&mut file, &seek_head)?;
+        Ok(self.attachments.get_or_init(|| attachments).as_deref())
+    }
+
+    /// Parses the children of a top level element and of all its duplicates in file order.
+    fn parse_merged_collection<R, T>(
+        &self,
+        r: &mut R,
+        master_id: ElementId,
+        child_id: ElementId,
+    ) -> Result<Option<Vec<T::Output>>>
+    where
+        R: Read + Seek,
+        T: ParsableElement<R>,
+    {
+        let mut offsets: Vec<u64> = self
+            .seek_head
+            .get(&master_id)
+            .into_iter()
+            .chain(
+                self.duplicate_elements
+                    .get(&master_id)
+                    .into_iter()
+                    .flatten(),
+            )
+            .copied()
+            .collect();
+        if offsets.is_empty() {
+            return Ok(None);
+        }
+        offsets.sort_unstable();
+
+        let mut children = Vec::new();
+        for offset in offsets {
+            children.extend(parse_children_at_offset::<_, T>(
+                r,
+                offset,
+                master_id,
+                child_id,
+                self.parse_options(),
+            )?);
+        }
+        Ok(Some(children))
+    }
 
-        Ok(Self {
-            file,
-            ebml_header,
-            seek_head,
-            info,
-            tracks,
-            cue_points,
-            chapters,
-            tags,
-            cluster_timestamp: 0,
-            queued_frames: VecDeque::with_capacity(8),
-        })
+    /// The options to parse the elements of the segment with.
+    pub(crate) fn parse_options(&self) -> ParseOptions {
+        self.settings.parse_options(&self.ebml_header)
+    }
+
+    /// The limits that the element headers of the segment have to follow.
+    pub(crate) fn header_limits(&self) -> HeaderLimits {
+        self.parse_options().limits
+    }
+
+    /// Returns the offset of the segment data inside the file.
+    pub fn segment_offset(&self) -> u64 {
+        self.segment_data_offset
+    }
+
+    /// Returns the size of the segment data in bytes.
+    ///
+    /// Returns `None` if the segment has an unknown size, which happens for live streams.
+    pub fn segment_size(&self) -> Option<u64> {
+        self.segment_data_size
     }
 
     /// Returns the EBML header.
@@ -1483,127 +3471,2692 @@ impl<R: Read + Seek> MatroskaFile<R> {
         self.tracks.as_ref()
     }
 
-    /// Returns the chapters of the file.
-    pub fn chapters(&self) -> Option<&[EditionEntry]> {
-        match self.chapters.as_ref() {
-            None => None,
-            Some(chapters) => Some(chapters),
+    /// Returns the tracks that couldn't be parsed. Only filled in lenient mode.
+    pub fn track_errors(&self) -> &[TrackError] {
+        self.track_errors.as_ref()
+    }
+
+    /// Returns the entries of the `SeekHead` that didn't point at the elements they declare
+    /// and were repaired. Only filled with `SeekHeadPolicy::Repair`.
+    pub fn seek_head_mismatches(&self) -> &[SeekHeadMismatch] {
+        self.seek_head_mismatches.as_ref()
+    }
+
+    /// Returns the problems that were tolerated while parsing the metadata, for example
+    /// missing mandatory elements that were substituted in lenient mode.
+    pub fn parse_warnings(&self) -> &[ValidationIssue] {
+        self.parse_warnings.as_ref()
+    }
+
+    /// Returns the track with the given track number.
+    pub fn track_by_number(&self, track_number: u64) -> Option<&TrackEntry> {
+        self.tracks
+            .iter()
+            .find(|t| t.track_number.get() == track_number)
+    }
+
+    /// Returns the track with the given track UID.
+    pub fn track_by_uid(&self, track_uid: u64) -> Option<&TrackEntry> {
+        self.tracks.iter().find(|t| t.track_uid.get() == track_uid)
+    }
+
+    /// Builds a table for `MatroskaFile::set_track_remap()` from pairs of a track UID and
+    /// the track number that should be reported for the track. UIDs without a track in
+    /// this file are skipped.
+    pub fn track_remap_by_uid<I>(&self, numbers: I) -> HashMap<u64, u64>
+    where
+        I: IntoIterator<Item = (u64, u64)>,
+    {
+        numbers
+            .into_iter()
+            .filter_map(|(track_uid, number)| {
+                self.track_by_uid(track_uid)
+                    .map(|t| (t.track_number.get(), number))
+            })
+            .collect()
+    }
+
+    /// Builds a table for `MatroskaFile::set_track_remap()` that adds the given offset to
+    /// the track numbers of all tracks, so that the tracks of several files can be combined
+    /// without collisions.
+    pub fn track_remap_with_offset(&self, offset: u64) -> HashMap<u64, u64> {
+        self.tracks
+            .iter()
+            .map(|t| {
+                let number = t.track_number.get();
+                (number, number.saturating_add(offset))
+            })
+            .collect()
+    }
+
+    /// Returns all tracks of the given type.
+    pub fn tracks_of_type(&self, track_type: TrackType) -> impl Iterator<Item = &TrackEntry> {
+        self.tracks
+            .iter()
+            .filter(move |t| t.track_type == track_type)
+    }
+
+    /// Returns the track of the given type that a player should select automatically.
+    ///
+    /// Only enabled tracks are considered. A track with the forced flag is preferred over a
+    /// track with the default flag. If neither flag is set for any track, the first track is
+    /// selected, except for subtitles, which are only selected when flagged.
+    pub fn default_track(&self, track_type: TrackType) -> Option<&TrackEntry> {
+        let candidates: Vec<&TrackEntry> = self
+            .tracks_of_type(track_type)
+            .filter(|t| t.flag_enabled)
+            .collect();
+
+        let forced = candidates.iter().find(|t| t.flag_forced);
+        let default = candidates.iter().find(|t| t.flag_default);
+        let fallback = if track_type == TrackType::Subtitle {
+            None
+        } else {
+            candidates.first()
+        };
+
+        forced.or(default).or(fallback).copied()
+    }
+
+    /// Returns the cue points of the file.
+    ///
+    /// Returns `None` if the cues were deferred and not loaded yet.
+    pub fn cue_points(&self) -> Option<&[CuePoint]> {
+        match self.cue_points.get() {
+            Some(Some(cue_points)) => Some(cue_points),
+            _ => None,
+        }
+    }
+
+    /// Returns the chapters of the file.
+    ///
+    /// Returns `None` if the chapters were deferred and not loaded yet.
+    pub fn chapters(&self) -> Option<&[EditionEntry]> {
+        match self.chapters.get() {
+            Some(Some(chapters)) => Some(chapters),
+            _ => None,
+        }
+    }
+
+    /// Returns the editions that are not hidden, in the order of the file.
+    ///
+    /// Returns no editions if the chapters were deferred and not loaded yet.
+    pub fn editions(&self) -> impl Iterator<Item = &EditionEntry> {
+        self.chapters()
+            .unwrap_or_default()
+            .iter()
+            .filter(|edition| !edition.flag_hidden())
+    }
+
+    /// Returns the edition a player should use: the first edition with
+    /// `EditionFlagDefault` set, or the first edition if no edition is flagged. Hidden
+    /// editions are never selected.
+    ///
+    /// Returns `None` if the chapters were deferred and not loaded yet.
+    pub fn default_edition(&self) -> Option<&EditionEntry> {
+        self.editions()
+            .find(|edition| edition.flag_default())
+            .or_else(|| self.editions().next())
+    }
+
+    /// Element containing metadata describing tracks, editions,
+    /// chapters, attachments, or the segment as a whole.
+    ///
+    /// Returns `None` if the tags were deferred and not loaded yet.
+    pub fn tags(&self) -> Option<&[Tag]> {
+        match self.tags.get() {
+            Some(Some(tags)) => Some(tags),
+            _ => None,
+        }
+    }
+
+    /// The files attached to the segment.
+    ///
+    /// Returns `None` if the attachments were deferred and not loaded yet.
+    pub fn attachments(&self) -> Option<&[AttachedFile]> {
+        match self.attachments.get() {
+            Some(Some(attachments)) => Some(attachments),
+            _ => None,
+        }
+    }
+
+    /// Creates the timestamp report from the timings of the frames of each track.
+    fn timestamp_report(&self, timings: &HashMap<u64, Vec<FrameTiming>>) -> TimestampReport {
+        let mut track_numbers: Vec<u64> =
+            self.tracks.iter().map(|t| t.track_number.get()).collect();
+        track_numbers.extend(timings.keys().copied());
+        track_numbers.sort_unstable();
+        track_numbers.dedup();
+
+        let tracks = track_numbers
+            .into_iter()
+            .map(|track| {
+                let entry = self.track_by_number(track);
+                let track_type = entry.map(|t| t.track_type);
+                let timestamp_scale = self.info.timestamp_scale;
+                let default_duration =
+                    entry.and_then(|t| t.default_duration_ticks(timestamp_scale));
+                let frames = timings.get(&track).map(Vec::as_slice).unwrap_or_default();
+                analyze_track(track, track_type, default_duration, frames)
+            })
+            .collect();
+
+        TimestampReport { tracks }
+    }
+
+    /// Returns `true` if the deferred `Cues`, `Chapters`, `Tags` and `Attachments`
+    /// elements are loaded.
+    pub fn is_metadata_loaded(&self) -> bool {
+        self.cue_points.get().is_some()
+            && self.chapters.get().is_some()
+            && self.tags.get().is_some()
+            && self.attachments.get().is_some()
+    }
+}
+
+/// Demuxer for Matroska files.
+///
+/// Cloning a demuxer is cheap, since the parsed metadata is shared between the clones.
+/// The reader itself is cloned though, so it needs to provide its own position.
+///
+/// The demuxer is `Send` if the reader is `Send`, so it can be moved to a worker thread.
+/// The metadata lives in the `MatroskaIndex`, which is `Send` and `Sync`, so it can be
+/// handed to other threads via `index()` or `into_parts()` while the demuxer reads frames.
+#[derive(Clone, Debug)]
+pub struct MatroskaFile<R: Read + Seek> {
+    file: R,
+    index: Arc<MatroskaIndex>,
+
+    /// The timestamp of the current cluster.
+    cluster_timestamp: u64,
+    /// Queued frames of a block we are currently reading.
+    queued_frames: VecDeque<LacedFrame>,
+    /// Invoked every time a new cluster is entered.
+    progress_callback: Option<ProgressCallback>,
+    /// Elements skipped while demuxing, if recording is enabled.
+    skipped_elements: Vec<SkippedElement>,
+    /// The offset and ID of the element that is currently read.
+    current_element: Option<(u64, ElementId)>,
+    /// The entered master elements inside the segment that contain the current element,
+    /// together with their ends.
+    parent_elements: Vec<(ElementId, u64)>,
+    /// The context of the last error that occurred while reading an element.
+    error_context: Option<ErrorContext>,
+    /// Decryptors of encrypted tracks, keyed by the track number.
+    decryptors: HashMap<u64, DecryptorHandle>,
+    /// The silent tracks of the current cluster.
+    silent_tracks: Vec<u64>,
+    /// The timestamp of the last frame of each track, used to report gaps.
+    last_timestamps: HashMap<u64, u64>,
+    /// Set when the end of the current segment was reported.
+    end_of_segment: bool,
+    /// A frame that is returned after the `DemuxEvent::Gap` that precedes it.
+    pending_frame: Option<Frame>,
+    /// Codec states that are reported with the next frame of their track.
+    pending_codec_states: HashMap<u64, Vec<u8>>,
+    /// The timestamps at which the subtitles of each track expire.
+    subtitle_expiries: HashMap<u64, u64>,
+    /// A frame and its event held back while expired subtitles are cleared.
+    held_event: Option<(Frame, Option<DemuxEvent>)>,
+    /// An event that is returned before any other.
+    pending_event: Option<DemuxEvent>,
+    /// The state of the timestamp discontinuity detection.
+    stitch: StitchState,
+    /// The applied timestamp offsets, kept across seeks.
+    timestamp_offsets: Vec<TimestampOffset>,
+    /// The end of the current cluster, used to check the placement of elements in strict
+    /// mode. `None` if it's unknown, for example after a seek.
+    cluster_end: Option<u64>,
+    /// The end of the current block group, whose remaining children are read after its block.
+    block_group_end: Option<u64>,
+    /// Set when the file was opened without searching the first cluster.
+    needs_first_cluster: bool,
+    /// The offset of the first cluster, if it was searched after `open_metadata_only()`.
+    first_cluster: Option<u64>,
+    /// The track numbers that are reported instead of the track numbers of the file.
+    track_remap: HashMap<u64, u64>,
+    /// The position of the element at which the end of the file was reached, where
+    /// reading continues after `refresh()`, and the size of the file at this time.
+    resume_position: Option<(u64, u64)>,
+    /// Set when a cluster with an unknown size was read.
+    unknown_size_cluster: bool,
+    /// Set when the file is followed while it grows.
+    follow: bool,
+    /// Invoked when the end of a followed file is reached.
+    follow_callback: Option<FollowCallback>,
+    /// The sample indices of the audio tracks that were built so far.
+    sample_indices: HashMap<u64, SampleIndex>,
+}
+
+/// Reads the data of an attachment, returned by `MatroskaFile::attachment_reader()`.
+#[derive(Debug)]
+pub struct AttachmentReader<'a, R: Read + Seek> {
+    file: &'a mut R,
+    position: u64,
+    end: u64,
+}
+
+impl<'a, R: Read + Seek> AttachmentReader<'a, R> {
+    /// The number of bytes that are left to read.
+    pub fn remaining(&self) -> u64 {
+        self.end - self.position
+    }
+}
+
+impl<'a, R: Read + Seek> Read for AttachmentReader<'a, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = usize::try_from(self.remaining())
+            .unwrap_or(usize::MAX)
+            .min(buf.len());
+        if len == 0 {
+            return Ok(0);
+        }
+
+        let restore = self.file.stream_position()?;
+        self.file.seek(SeekFrom::Start(self.position))?;
+        let result = self.file.read(&mut buf[..len]);
+        self.file.seek(SeekFrom::Start(restore))?;
+
+        let read = result?;
+        if read == 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "the file ended inside the attachment",
+            ));
+        }
+        self.position += u64::try_from(read).unwrap_or(u64::MAX);
+        Ok(read)
+    }
+}
+
+/// A lightweight reader that demuxes frames using a shared `MatroskaIndex`.
+///
+/// ```no_run
+/// use std::{fs::File, sync::Arc};
+/// use matroska_demuxer::{Frame, MatroskaIndex, MatroskaReader};
+///
+/// let index = Arc::new(MatroskaIndex::parse(File::open("test.mkv").unwrap()).unwrap());
+///
+/// let handles: Vec<_> = (0..2)
+///     .map(|_| {
+///         let index = Arc::clone(&index);
+///         std::thread::spawn(move || {
+///             let file = File::open("test.mkv").unwrap();
+///             let mut reader = MatroskaReader::new(index, file).unwrap();
+///             let mut frame = Frame::default();
+///             while reader.next_frame(&mut frame).unwrap() {}
+///         })
+///     })
+///     .collect();
+/// ```
+#[derive(Debug)]
+pub struct MatroskaReader<R: Read + Seek> {
+    demuxer: MatroskaFile<R>,
+}
+
+impl<R: Read + Seek> MatroskaReader<R> {
+    /// Creates a reader for the given file, using the already parsed index of the file.
+    ///
+    /// The metadata is not parsed again. The reader is positioned at the first cluster.
+    pub fn new(index: Arc<MatroskaIndex>, file: R) -> Result<Self> {
+        Ok(Self {
+            demuxer: MatroskaFile::new(index, file)?,
+        })
+    }
+
+    /// Returns the shared index of the file.
+    pub fn index(&self) -> &Arc<MatroskaIndex> {
+        self.demuxer.index()
+    }
+
+    /// Returns the tracks of the file.
+    pub fn tracks(&self) -> &[TrackEntry] {
+        self.demuxer.tracks()
+    }
+
+    /// Reads the next frame data into the given `Frame`. Works like
+    /// `MatroskaFile::next_frame()`.
+    pub fn next_frame(&mut self, frame: &mut Frame) -> Result<bool> {
+        self.demuxer.next_frame(frame)
+    }
+
+    /// Reads the next event. Works like `MatroskaFile::next_event()`.
+    pub fn next_event(&mut self, frame: &mut Frame) -> Result<Option<DemuxEvent>> {
+        self.demuxer.next_event(frame)
+    }
+
+    /// Seeks to the given timestamp. Works like `MatroskaFile::seek()`.
+    pub fn seek(&mut self, seek_timestamp: u64) -> Result<SeekOutcome> {
+        self.demuxer.seek(seek_timestamp)
+    }
+
+    /// Seeks to the given timestamp of a track. Works like `MatroskaFile::seek_track()`.
+    pub fn seek_track(&mut self, track: u64, seek_timestamp: u64) -> Result<SeekOutcome> {
+        self.demuxer.seek_track(track, seek_timestamp)
+    }
+
+    /// Returns the context of the last error. Works like `MatroskaFile::error_context()`.
+    pub fn error_context(&self) -> Option<&ErrorContext> {
+        self.demuxer.error_context()
+    }
+
+    /// Consumes the reader and returns the underlying file.
+    pub fn into_inner(self) -> R {
+        self.demuxer.into_inner()
+    }
+}
+
+impl<R: Read + Seek> From<MatroskaReader<R>> for MatroskaFile<R> {
+    fn from(reader: MatroskaReader<R>) -> Self {
+        reader.demuxer
+    }
+}
+
+impl<R: Read + Seek> MatroskaFile<R> {
+    /// Opens a Matroska file.
+    pub fn open(file: R) -> Result<Self> {
+        Self::open_with_settings(file, &ParserSettings::default())
+    }
+
+    /// Opens a Matroska file with the given settings.
+    pub fn open_with_settings(mut file: R, settings: &ParserSettings) -> Result<Self> {
+        let index = MatroskaIndex::parse_with_settings(&mut file, settings)?;
+        Self::new(Arc::new(index), file)
+    }
+
+    /// Opens a Matroska file, but only parses the metadata that describes its content,
+    /// for example to scan a media library.
+    ///
+    /// The `Info`, `Tracks`, `Chapters`, `Tags` and `Attachments` are parsed. The `Cues`
+    /// are deferred like with `ParserSettings::lazy_metadata()` and the first cluster is
+    /// neither searched nor read. It's searched once it's needed, for example when the first
+    /// frame is read.
+    pub fn open_metadata_only(mut file: R) -> Result<Self> {
+        let settings = ParserSettings {
+            metadata_only: true,
+            ..ParserSettings::default()
+        };
+        let index = MatroskaIndex::parse_with_settings(&mut file, &settings)?;
+        Ok(Self::with_index(Arc::new(index), file, true))
+    }
+
+    /// Opens a Matroska file with an index that was exported by `export_index()`.
+    ///
+    /// The metadata is parsed from the exported index. The bytes stored in the index are
+    /// compared with the file to verify that the index belongs to it, so the index of a
+    /// file whose metadata was edited afterwards is rejected. The settings must be the same
+    /// that were used when the index was exported.
+    pub fn open_with_index(
+        mut file: R,
+        index_data: &[u8],
+        settings: &ParserSettings,
+    ) -> Result<Self> {
+        let mut replay = ReplayReader::decode(index_data)?;
+        if !replay.matches(&mut file)? {
+            return Err(DemuxError::InvalidIndexData);
+        }
+
+        let settings = settings.clone().lazy_metadata(false);
+        let index =
+            MatroskaIndex::parse_with_settings(&mut replay, &settings).map_err(
+                |err| match err {
+                    DemuxError::IoError(err) if err.kind() == std::io::ErrorKind::InvalidData => {
+                        DemuxError::InvalidIndexData
+                    }
+                    err => err,
+                },
+            )?;
+        Self::new(Arc::new(index), file)
+    }
+
+    /// Exports the metadata of the file into a compact binary blob, which can be stored
+    /// and used with `open_with_index()` to skip parsing the metadata on later opens.
+    ///
+    /// The blob contains the bytes of the file that are needed to parse the `SeekHead`,
+    /// `Info`, `Tracks`, `Cues`, `Chapters` and `Tags`. The position of the demuxer is
+    /// restored after the export.
+    pub fn export_index(&mut self) -> Result<Vec<u8>> {
+        let position = self.file.stream_position()?;
+        let settings = self.index.settings.clone().lazy_metadata(false);
+
+        let result = self
+            .file
+            .seek(SeekFrom::Start(self.index.header_offset))
+            .map_err(Into::into)
+            .and_then(|_| {
+                let mut recorder = RecordingReader::new(&mut self.file)?;
+                MatroskaIndex::parse_with_settings(&mut recorder, &settings)?;
+                recorder.encode()
+            });
+
+        self.file.seek(SeekFrom::Start(position))?;
+        result
+    }
+
+    /// Creates a demuxer for an already parsed index. The reader must provide the same
+    /// file the index was parsed from.
+    ///
+    /// The reader is positioned at the first cluster, so no metadata is parsed again.
+    pub fn new(index: Arc<MatroskaIndex>, mut file: R) -> Result<Self> {
+        if !index.settings.metadata_only {
+            seek_to_first_cluster(&mut file, &index.seek_head)?;
+        }
+        let needs_first_cluster = index.settings.metadata_only;
+        Ok(Self::with_index(index, file, needs_first_cluster))
+    }
+
+    fn with_index(index: Arc<MatroskaIndex>, file: R, needs_first_cluster: bool) -> Self {
+        Self {
+            file,
+            index,
+            cluster_timestamp: 0,
+            queued_frames: VecDeque::with_capacity(8),
+            progress_callback: None,
+            skipped_elements: Vec::new(),
+            current_element: None,
+            parent_elements: Vec::new(),
+            error_context: None,
+            decryptors: HashMap::new(),
+            silent_tracks: Vec::new(),
+            last_timestamps: HashMap::new(),
+            pending_codec_states: HashMap::new(),
+            subtitle_expiries: HashMap::new(),
+            held_event: None,
+            pending_event: None,
+            stitch: StitchState::default(),
+            timestamp_offsets: Vec::new(),
+            cluster_end: None,
+            block_group_end: None,
+            needs_first_cluster,
+            first_cluster: None,
+            track_remap: HashMap::new(),
+            resume_position: None,
+            unknown_size_cluster: false,
+            follow: false,
+            follow_callback: None,
+            sample_indices: HashMap::new(),
+            end_of_segment: false,
+            pending_frame: None,
+        }
+    }
+
+    /// Returns the shared index of the file.
+    pub fn index(&self) -> &Arc<MatroskaIndex> {
+        &self.index
+    }
+
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.file
+    }
+
+    /// Returns a mutable reference to the underlying reader.
+    ///
+    /// The demuxer expects the reader to stay at the position it left it. Reading from or
+    /// seeking the reader invalidates the position of the demuxer, so it must be restored
+    /// afterwards or the demuxer repositioned with `seek()` or `seek_to_offset()`.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.file
+    }
+
+    /// Consumes the demuxer and returns the underlying reader, for example to reuse
+    /// a pooled connection.
+    ///
+    /// The reader is left at the current position of the demuxer. Together with `index()`,
+    /// it can be turned back into a demuxer with `MatroskaFile::new()`.
+    pub fn into_inner(self) -> R {
+        self.file
+    }
+
+    /// Consumes the demuxer and returns the shared index and the underlying reader.
+    ///
+    /// The demuxer can be recreated from both parts with `new()`.
+    pub fn into_parts(self) -> (Arc<MatroskaIndex>, R) {
+        (self.index, self.file)
+    }
+
+    /// Returns the current byte position of the reader inside the file.
+    pub fn stream_position(&mut self) -> Result<u64> {
+        Ok(self.file.stream_position()?)
+    }
+
+    /// Returns the offset of the segment data inside the file.
+    pub fn segment_offset(&self) -> u64 {
+        self.index.segment_data_offset
+    }
+
+    /// Returns the size of the segment data in bytes.
+    ///
+    /// Returns `None` if the segment has an unknown size, which happens for live streams.
+    pub fn segment_size(&self) -> Option<u64> {
+        self.index.segment_data_size
+    }
+
+    /// Returns `true` if the file appears to be a live stream or a recording that is still
+    /// being written.
+    ///
+    /// This is the case if the segment or a cluster read so far has an unknown size, or if
+    /// the segment has neither a `Duration` nor `Cues`.
+    pub fn is_live(&self) -> bool {
+        self.index.segment_data_size.is_none()
+            || self.unknown_size_cluster
+            || (self.index.info.duration().is_none()
+                && !self.index.seek_head.contains_key(&ElementId::Cues))
+    }
+
+    /// Continues a file that grows while it's read, for example the recording of a DVR.
+    ///
+    /// Once the end of the written data is reached, `next_frame()` returns `false` or
+    /// `DemuxError::UnexpectedEof` is returned if the file ends inside an element. After
+    /// calling `refresh()`, reading continues at the element at which the data ended.
+    ///
+    /// The metadata is parsed again, so that a `Duration`, `Cues` or `Tags` that were
+    /// written when the recording finished are picked up. Returns `true` if the file grew
+    /// since the end was reached.
+    pub fn refresh(&mut self) -> Result<bool> {
+        let (position, previous_size) = match self.resume_position {
+            Some(resume_position) => resume_position,
+            None => {
+                let position = self.file.stream_position()?;
+                (position, position)
+            }
+        };
+        let file_size = self.file.seek(SeekFrom::End(0))?;
+
+        self.file.seek(SeekFrom::Start(self.index.header_offset))?;
+        let result = MatroskaIndex::parse_with_settings(&mut self.file, &self.index.settings);
+        self.file.seek(SeekFrom::Start(position))?;
+        self.index = Arc::new(result?);
+        self.end_of_segment = false;
+
+        let grown = file_size > previous_size;
+        if grown {
+            self.resume_position = None;
+            self.sample_indices.clear();
+        }
+        Ok(grown)
+    }
+
+    /// Follows the file while it grows, for example to play a recording that is still being
+    /// written.
+    ///
+    /// Once the end of the written data is reached, `next_frame()`, `next_event()` and
+    /// `next_raw_block()` check if the file grew and continue reading. Otherwise the
+    /// callback set by `set_follow_callback()` is asked to wait, or
+    /// `DemuxError::WouldBlock` is returned and the read can be retried later.
+    ///
+    /// The metadata is not parsed again. Use `refresh()` for this.
+    pub fn set_follow_mode(&mut self, follow: bool) {
+        self.follow = follow;
+    }
+
+    /// Sets a callback that is invoked in follow mode when the end of the written data is
+    /// reached. It receives the number of previous attempts and can block until more data
+    /// is available. The file is checked again if it returns `true`, otherwise
+    /// `DemuxError::WouldBlock` is returned.
+    pub fn set_follow_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(u32) -> bool + Send + Sync + 'static,
+    {
+        self.follow_callback = Some(FollowCallback(Arc::new(callback)));
+    }
+
+    /// Removes the follow callback.
+    pub fn clear_follow_callback(&mut self) {
+        self.follow_callback = None;
+    }
+
+    /// Waits until the file grew past the position at which its end was reached.
+    fn wait_for_data(&mut self, attempt: &mut u32) -> Result<()> {
+        let (position, previous_size) = match self.resume_position {
+            Some(resume_position) => resume_position,
+            None => return Err(DemuxError::UnexpectedEof),
+        };
+        loop {
+            let file_size = self.file.seek(SeekFrom::End(0))?;
+            if file_size > previous_size {
+                debug!(position, file_size, "the followed file grew");
+                self.file.seek(SeekFrom::Start(position))?;
+                self.resume_position = None;
+                self.end_of_segment = false;
+                return Ok(());
+            }
+
+            let retry = match self.follow_callback.as_ref() {
+                Some(callback) => (callback.0)(*attempt),
+                None => false,
+            };
+            *attempt = attempt.saturating_add(1);
+            if !retry {
+                self.file.seek(SeekFrom::Start(position))?;
+                self.end_of_segment = false;
+                return Err(DemuxError::WouldBlock);
+            }
+        }
+    }
+
+    /// Returns the byte range of a top level element like `Info`, `Tracks`, `Cues`, `Tags`
+    /// or `Attachments`, including its header. For `Cluster` the range of the first cluster
+    /// is returned.
+    ///
+    /// Returns `None` if the segment doesn't contain the element. The position of the
+    /// demuxer is restored afterwards.
+    pub fn element_range(&mut self, element_id: ElementId) -> Result<Option<std::ops::Range<u64>>> {
+        let offset = match self.index.seek_head.get(&element_id) {
+            Some(offset) => *offset,
+            None => return Ok(None),
+        };
+
+        let position = self.file.stream_position()?;
+        let result = self.top_level_element_range(element_id, offset);
+        self.file.seek(SeekFrom::Start(position))?;
+
+        result.map(Some)
+    }
+
+    fn top_level_element_range(
+        &mut self,
+        element_id: ElementId,
+        offset: u64,
+    ) -> Result<std::ops::Range<u64>> {
+        let (found_id, size) =
+            parse_element_header(&mut self.file, Some(offset), self.index.header_limits())?;
+        if found_id != element_id {
+            return Err(DemuxError::UnexpectedElement((element_id, found_id)));
+        }
+
+        let data_offset = self.file.stream_position()?;
+        let end = if size == u64::MAX {
+            match self.index.segment_data_size {
+                Some(segment_size) => self.index.segment_data_offset + segment_size,
+                None => return Err(DemuxError::InvalidEbmlDataSize),
+            }
+        } else {
+            data_offset + size
+        };
+
+        Ok(offset..end)
+    }
+
+    /// Returns the progress of the reader through the segment.
+    pub fn progress(&mut self) -> Result<Progress> {
+        let position = self.file.stream_position()?;
+        Ok(Progress {
+            position: position.saturating_sub(self.index.segment_data_offset),
+            segment_size: self.index.segment_data_size,
+        })
+    }
+
+    /// Sets a callback that is invoked every time a new cluster is entered while
+    /// demuxing or seeking. Can be used to report the progress of long running operations.
+    pub fn set_progress_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(Progress) + Send + Sync + 'static,
+    {
+        self.progress_callback = Some(ProgressCallback(Arc::new(callback)));
+    }
+
+    /// Removes the progress callback.
+    pub fn clear_progress_callback(&mut self) {
+        self.progress_callback = None;
+    }
+
+    fn report_progress(&mut self) -> Result<()> {
+        if self.progress_callback.is_some() {
+            let progress = self.progress()?;
+            if let Some(callback) = self.progress_callback.as_ref() {
+                (callback.0)(progress);
+            }
+        }
+        Ok(())
+    }
+
+    /// Registers a decryptor for the frames of the given track.
+    ///
+    /// The frames of tracks with a `ContentEncryption` are passed through the decryptor, so
+    /// that `Frame::data` contains the decrypted data without the encryption header.
+    pub fn set_decryptor<D>(&mut self, track: u64, decryptor: D)
+    where
+        D: ContentDecryptor + 'static,
+    {
+        self.decryptors
+            .insert(track, DecryptorHandle(Arc::new(decryptor)));
+    }
+
+    /// Removes the decryptor of the given track.
+    pub fn clear_decryptor(&mut self, track: u64) {
+        self.decryptors.remove(&track);
+    }
+
+    /// Replaces the track numbers of the frames and blocks that are returned by the demuxer.
+    ///
+    /// The table maps the track numbers of the file to the track numbers that are reported
+    /// in `Frame::track`, `RawBlock::track` and `DemuxEvent::Gap`. Tracks without an entry
+    /// keep their number. `sample_index()` and `seek_sample()` take the reported track
+    /// numbers as well. All other methods, like `track_by_number()` or `set_decryptor()`,
+    /// still use the track numbers of the file.
+    ///
+    /// Use `MatroskaIndex::track_remap_by_uid()` or `MatroskaIndex::track_remap_with_offset()`
+    /// to build the table.
+    pub fn set_track_remap(&mut self, track_remap: HashMap<u64, u64>) {
+        self.track_remap = track_remap;
+        self.sample_indices.clear();
+    }
+
+    /// Removes the track remap table, so that the track numbers of the file are reported.
+    pub fn clear_track_remap(&mut self) {
+        self.track_remap.clear();
+        self.sample_indices.clear();
+    }
+
+    /// Returns the track number that is reported for the given track number of the file.
+    pub fn remapped_track(&self, track: u64) -> u64 {
+        self.track_remap.get(&track).copied().unwrap_or(track)
+    }
+
+    /// Returns the track numbers of the tracks that are silent in the current cluster.
+    ///
+    /// Silent tracks have no frames in the cluster on purpose, so their decoders can be
+    /// paused until the next cluster.
+    pub fn silent_tracks(&self) -> &[u64] {
+        self.silent_tracks.as_ref()
+    }
+
+    fn read_silent_tracks(&mut self, element_data: &ElementData) -> Result<()> {
+        if let ElementData::Location { offset, size } = element_data {
+            let fields =
+                collect_children(&mut self.file, *offset, *size, self.index.header_limits())?;
+            self.silent_tracks = find_all_unsigned(&fields, ElementId::SilentTrackNumber)?;
+            trace!(silent_tracks = ?self.silent_tracks, "silent tracks");
+            Ok(())
+        } else {
+            Err(DemuxError::UnexpectedDataType)
+        }
+    }
+
+    /// Returns the elements that were skipped while demuxing frames.
+    ///
+    /// Elements are only recorded if `ParserSettings::record_skipped_elements()` is set.
+    pub fn skipped_elements(&self) -> &[SkippedElement] {
+        self.skipped_elements.as_ref()
+    }
+
+    /// Returns the offset and the path of the element that was read when the last error
+    /// occurred while reading frames, events or raw blocks.
+    pub fn error_context(&self) -> Option<&ErrorContext> {
+        self.error_context.as_ref()
+    }
+
+    /// Returns the timestamp offsets that were applied so far to stitch the appended parts
+    /// of a file.
+    ///
+    /// Offsets are only applied if `ParserSettings::stitch_timestamps()` is set.
+    pub fn timestamp_offsets(&self) -> &[TimestampOffset] {
+        self.timestamp_offsets.as_ref()
+    }
+
+    /// Returns the element with the given raw Element ID that was registered with
+    /// `ParserSettings::register_element()`.
+    pub fn custom_element(&self, id: u32) -> Option<&CustomElement> {
+        self.index.settings.custom_elements.get(&id)
+    }
+
+    /// Removes all recorded skipped elements.
+    pub fn clear_skipped_elements(&mut self) {
+        self.skipped_elements.clear();
+    }
+
+    /// Returns the EBML header.
+    pub fn ebml_header(&self) -> &EbmlHeader {
+        self.index.ebml_header()
+    }
+
+    /// Returns the segment info.
+    pub fn info(&self) -> &Info {
+        self.index.info()
+    }
+
+    /// Returns the tracks of the file.
+    pub fn tracks(&self) -> &[TrackEntry] {
+        self.index.tracks()
+    }
+
+    /// Returns the tracks that couldn't be parsed. Only filled in lenient mode.
+    pub fn track_errors(&self) -> &[TrackError] {
+        self.index.track_errors()
+    }
+
+    /// Returns the entries of the `SeekHead` that didn't point at the elements they declare
+    /// and were repaired. Only filled with `SeekHeadPolicy::Repair`.
+    pub fn seek_head_mismatches(&self) -> &[SeekHeadMismatch] {
+        self.index.seek_head_mismatches()
+    }
+
+    /// Returns the problems that were tolerated while parsing the metadata, for example
+    /// missing mandatory elements that were substituted in lenient mode.
+    pub fn parse_warnings(&self) -> &[ValidationIssue] {
+        self.index.parse_warnings()
+    }
+
+    /// Returns the track with the given track number.
+    pub fn track_by_number(&self, track_number: u64) -> Option<&TrackEntry> {
+        self.index.track_by_number(track_number)
+    }
+
+    /// Returns the track the given frame belongs to.
+    pub fn track_for_frame(&self, frame: &Frame) -> Option<&TrackEntry> {
+        self.index.track_by_number(frame.track)
+    }
+
+    /// Returns the track with the given track UID.
+    pub fn track_by_uid(&self, track_uid: u64) -> Option<&TrackEntry> {
+        self.index.track_by_uid(track_uid)
+    }
+
+    /// Returns all tracks of the given type.
+    pub fn tracks_of_type(&self, track_type: TrackType) -> impl Iterator<Item = &TrackEntry> {
+        self.index.tracks_of_type(track_type)
+    }
+
+    /// Returns the track of the given type that a player should select automatically.
+    ///
+    /// See `MatroskaIndex::default_track()` for the selection rules.
+    pub fn default_track(&self, track_type: TrackType) -> Option<&TrackEntry> {
+        self.index.default_track(track_type)
+    }
+
+    /// Returns the cue points of the file.
+    ///
+    /// Returns `None` if the cues were deferred by `ParserSettings::lazy_metadata()`
+    /// and not loaded yet. Use `load_cue_points()` in this case.
+    pub fn cue_points(&self) -> Option<&[CuePoint]> {
+        self.index.cue_points()
+    }
+
+    /// Returns the chapters of the file.
+    ///
+    /// Returns `None` if the chapters were deferred by `ParserSettings::lazy_metadata()`
+    /// and not loaded yet. Use `load_chapters()` in this case.
+    pub fn chapters(&self) -> Option<&[EditionEntry]> {
+        self.index.chapters()
+    }
+
+    /// Returns the editions that are not hidden, in the order of the file.
+    ///
+    /// Returns no editions if the chapters were deferred by
+    /// `ParserSettings::lazy_metadata()` and not loaded yet.
+    pub fn editions(&self) -> impl Iterator<Item = &EditionEntry> {
+        self.index.editions()
+    }
+
+    /// Returns the edition a player should use: the first edition with
+    /// `EditionFlagDefault` set, or the first edition if no edition is flagged. Hidden
+    /// editions are never selected.
+    ///
+    /// Returns `None` if the chapters were deferred by `ParserSettings::lazy_metadata()`
+    /// and not loaded yet.
+    pub fn default_edition(&self) -> Option<&EditionEntry> {
+        self.index.default_edition()
+    }
+
+    /// Returns the innermost chapter that plays at the given time in nanoseconds.
+    ///
+    /// The chapters of the given edition are searched, or of the `default_edition()` if
+    /// `None` is given.
+    pub fn chapter_at<'a>(
+        &'a self,
+        timestamp_ns: u64,
+        edition: Option<&'a EditionEntry>,
+    ) -> Option<&'a ChapterAtom> {
+        edition
+            .or_else(|| self.default_edition())?
+            .chapter_at(timestamp_ns)
+    }
+
+    /// Returns the first start or end of a chapter of the `default_edition()` after the
+    /// given time in nanoseconds, for example to skip to the next chapter.
+    pub fn next_chapter_boundary(&self, timestamp_ns: u64) -> Option<u64> {
+        self.default_edition()?.next_chapter_boundary(timestamp_ns)
+    }
+
+    /// Element containing metadata describing tracks, editions,
+    /// chapters, attachments, or the segment as a whole.
+    ///
+    /// Returns `None` if the tags were deferred by `ParserSettings::lazy_metadata()`
+    /// and not loaded yet. Use `load_tags()` in this case.
+    pub fn tags(&self) -> Option<&[Tag]> {
+        self.index.tags()
+    }
+
+    /// The files attached to the segment.
+    ///
+    /// Returns `None` if the attachments were deferred by `ParserSettings::lazy_metadata()`
+    /// and not loaded yet. Use `load_attachments()` in this case.
+    pub fn attachments(&self) -> Option<&[AttachedFile]> {
+        self.index.attachments()
+    }
+
+    /// Returns the cue points of the file and parses them first if they were deferred.
+    ///
+    /// The position of the demuxer is restored after the parsing.
+    pub fn load_cue_points(&mut self) -> Result<Option<&[CuePoint]>> {
+        if self.index.cue_points.get().is_none() {
+            let position = self.file.stream_position()?;
+            let result = self.index.load_cue_points(&mut self.file).map(|_| ());
+            self.file.seek(SeekFrom::Start(position))?;
+            result?;
+        }
+        Ok(self.index.cue_points())
+    }
+
+    /// Returns the chapters of the file and parses them first if they were deferred.
+    ///
+    /// The position of the demuxer is restored after the parsing.
+    pub fn load_chapters(&mut self) -> Result<Option<&[EditionEntry]>> {
+        if self.index.chapters.get().is_none() {
+            let position = self.file.stream_position()?;
+            let result = self.index.load_chapters(&mut self.file).map(|_| ());
+            self.file.seek(SeekFrom::Start(position))?;
+            result?;
+        }
+        Ok(self.index.chapters())
+    }
+
+    /// Returns the tags of the file and parses them first if they were deferred.
+    ///
+    /// The position of the demuxer is restored after the parsing.
+    pub fn load_tags(&mut self) -> Result<Option<&[Tag]>> {
+        if self.index.tags.get().is_none() {
+            let position = self.file.stream_position()?;
+            let result = self.index.load_tags(&mut self.file).map(|_| ());
+            self.file.seek(SeekFrom::Start(position))?;
+            result?;
+        }
+        Ok(self.index.tags())
+    }
+
+    /// Returns the attachments of the file and parses them first if they were deferred.
+    ///
+    /// The position of the demuxer is restored after the parsing.
+    pub fn load_attachments(&mut self) -> Result<Option<&[AttachedFile]>> {
+        if self.index.attachments.get().is_none() {
+            let position = self.file.stream_position()?;
+            let result = self.index.load_attachments(&mut self.file).map(|_| ());
+            self.file.seek(SeekFrom::Start(position))?;
+            result?;
+        }
+        Ok(self.index.attachments())
+    }
+
+    /// Returns the byte range of the data of the attachment with the given UID.
+    fn attachment_data_range(&mut self, uid: u64) -> Result<std::ops::Range<u64>> {
+        self.load_attachments()?
+            .into_iter()
+            .flatten()
+            .find(|attachment| attachment.uid.get() == uid)
+            .map(|attachment| attachment.data_range())
+            .ok_or(DemuxError::AttachmentNotFound(uid))
+    }
+
+    /// Reads the data of the attachment with the given UID.
+    ///
+    /// The position of the demuxer is restored after the read. Use `read_attachment_chunks()`
+    /// or `attachment_reader()` for large attachments to not buffer the whole data.
+    pub fn read_attachment(&mut self, uid: u64) -> Result<Vec<u8>> {
+        let range = self.attachment_data_range(uid)?;
+        self.read_byte_range(range)
+    }
+
+    /// Reads the data of the attachment with the given UID in chunks of at most `chunk_size`
+    /// bytes and passes every chunk to the given callback.
+    ///
+    /// The position of the demuxer is restored after the read.
+    pub fn read_attachment_chunks(
+        &mut self,
+        uid: u64,
+        chunk_size: usize,
+        mut callback: impl FnMut(&[u8]),
+    ) -> Result<()> {
+        let mut reader = self.attachment_reader(uid)?;
+        let mut buffer = vec![0_u8; chunk_size.max(1)];
+        loop {
+            let read = reader.read(&mut buffer)?;
+            if read == 0 {
+                return Ok(());
+            }
+            callback(&buffer[..read]);
+        }
+    }
+
+    /// Returns a reader over the data of the attachment with the given UID.
+    ///
+    /// The position of the demuxer is restored after every read, so the reader can be used
+    /// while demuxing.
+    pub fn attachment_reader(&mut self, uid: u64) -> Result<AttachmentReader<'_, R>> {
+        let range = self.attachment_data_range(uid)?;
+        Ok(AttachmentReader {
+            file: &mut self.file,
+            position: range.start,
+            end: range.end,
+        })
+    }
+
+    /// Returns `true` if the file contains cues, which allow a fast seek. Parses the cues
+    /// first if they were deferred.
+    ///
+    /// The position of the demuxer is restored after the parsing.
+    pub fn has_cues(&mut self) -> Result<bool> {
+        let position = self.file.stream_position()?;
+        let result = self
+            .index
+            .load_cue_points(&mut self.file)
+            .map(|c| c.is_some());
+        self.file.seek(SeekFrom::Start(position))?;
+        result
+    }
+
+    /// Reads the next frame data into the given `Frame`.
+    ///
+    /// Returns `false` if the end of the file is reached. Returns `DemuxError::UnexpectedEof`
+    /// if the file ends inside an element, for example when the file is truncated.
+    ///
+    /// The context of errors that occur while reading elements is returned by
+    /// `error_context()`.
+    ///
+    /// If a new segment starts inside the stream, the demuxer continues with the metadata of
+    /// the new segment. Use `next_event()` to get notified about it.
+    pub fn next_frame(&mut self, frame: &mut Frame) -> Result<bool> {
+        loop {
+            match self.next_event(frame)? {
+                Some(DemuxEvent::Frame) => return Ok(true),
+                Some(_) => continue,
+                None => return Ok(false),
+            }
+        }
+    }
+
+    /// Reads the next frame like `next_frame()`, but keeps the track number of the file.
+    fn read_next_frame(&mut self, frame: &mut Frame) -> Result<bool> {
+        loop {
+            let event = self
+                .read_next_event(frame)
+                .map_err(|err| self.record_error_context(err))?;
+            match event {
+                Some(DemuxEvent::Frame) => return Ok(true),
+                Some(_) => continue,
+                None => return Ok(false),
+            }
+        }
+    }
+
+    /// Reads the next event. If the event is `DemuxEvent::Frame`, the next frame was read
+    /// into the given `Frame`. Other events leave the `Frame` untouched.
+    ///
+    /// If a new EBML header and `Segment` start inside the stream, `DemuxEvent::EndOfSegment`
+    /// is followed by `DemuxEvent::TracksChanged`. The index is replaced by the metadata of
+    /// the new segment before the event is returned, so `tracks()` reports the new tracks.
+    ///
+    /// Returns `None` after the `DemuxEvent::EndOfSegment` of the last segment.
+    ///
+    /// In follow mode, `DemuxError::WouldBlock` is returned instead of
+    /// `DemuxEvent::EndOfSegment` once the end of the written data is reached.
+    pub fn next_event(&mut self, frame: &mut Frame) -> Result<Option<DemuxEvent>> {
+        let mut attempt = 0;
+        let event = loop {
+            let result = self
+                .read_next_event(frame)
+                .map_err(|err| self.record_error_context(err));
+            let at_end = match &result {
+                Ok(Some(DemuxEvent::EndOfSegment) | None) => self.resume_position.is_some(),
+                Ok(Some(_)) => false,
+                Err(err) => err.code() == ErrorCode::UnexpectedEof,
+            };
+            if !self.follow || !at_end {
+                break result?;
+            }
+            self.wait_for_data(&mut attempt)?;
+        };
+        Ok(event.map(|event| self.remap_event(frame, event)))
+    }
+
+    fn remap_event(&self, frame: &mut Frame, event: DemuxEvent) -> DemuxEvent {
+        let in_nanos = self.index.settings.nanosecond_timestamps;
+        let scale = self.index.info.timestamp_scale;
+        let convert = |ticks: u64| match in_nanos {
+            true => ticks_to_nanos(ticks, scale),
+            false => ticks,
+        };
+        match event {
+            DemuxEvent::Frame => {
+                frame.track = self.remapped_track(frame.track);
+                if in_nanos {
+                    frame_to_nanos(frame);
+                }
+                DemuxEvent::Frame
+            }
+            DemuxEvent::ClusterStart { timestamp } => DemuxEvent::ClusterStart {
+                timestamp: convert(timestamp),
+            },
+            DemuxEvent::Gap {
+                track,
+                previous_timestamp,
+                timestamp,
+            } => DemuxEvent::Gap {
+                track: self.remapped_track(track),
+                previous_timestamp: convert(previous_timestamp),
+                timestamp: convert(timestamp),
+            },
+            DemuxEvent::TimestampDiscontinuity {
+                previous_timestamp,
+                timestamp,
+                offset,
+            } => DemuxEvent::TimestampDiscontinuity {
+                previous_timestamp: convert(previous_timestamp),
+                timestamp: convert(timestamp),
+                offset: convert(offset),
+            },
+            event => event,
+        }
+    }
+
+    fn read_next_event(&mut self, frame: &mut Frame) -> Result<Option<DemuxEvent>> {
+        if !self.index.settings.clear_expired_subtitles {
+            return self.read_event(frame);
+        }
+
+        let event = match self.held_event.take() {
+            Some((held_frame, event)) => {
+                *frame = held_frame;
+                event
+            }
+            None => self.read_event(frame)?,
+        };
+
+        // Subtitles that expired before the frame are cleared first.
+        let limit = match event {
+            Some(DemuxEvent::Frame) => Some(frame.timestamp),
+            Some(DemuxEvent::EndOfSegment) | None => None,
+            Some(_) => return Ok(event),
+        };
+        if let Some(clear_frame) = self.next_subtitle_clear(limit) {
+            let held_frame = std::mem::replace(frame, clear_frame);
+            self.held_event = Some((held_frame, event));
+            return Ok(Some(DemuxEvent::Frame));
+        }
+
+        if limit.is_some() {
+            self.schedule_subtitle_clear(frame);
+        }
+        Ok(event)
+    }
+
+    /// Removes the earliest subtitle expiry up to the given timestamp and returns the
+    /// virtual frame that clears it.
+    fn next_subtitle_clear(&mut self, limit: Option<u64>) -> Option<Frame> {
+        let (track, timestamp) = self
+            .subtitle_expiries
+            .iter()
+            .filter(|(_, expiry)| limit.map_or(true, |limit| **expiry <= limit))
+            .min_by_key(|(track, expiry)| (**expiry, **track))
+            .map(|(track, expiry)| (*track, *expiry))?;
+        self.subtitle_expiries.remove(&track);
+        trace!(track, timestamp, "clearing expired subtitle");
+
+        Some(Frame {
+            track,
+            timestamp,
+            signed_timestamp: i64::try_from(timestamp).unwrap_or(i64::MAX),
+            presentation_timestamp: i64::try_from(timestamp).unwrap_or(i64::MAX),
+            timestamp_scale: self.index.info.timestamp_scale,
+            track_type: Some(TrackType::Subtitle).filter(|_| self.index.settings.stamp_track_type),
+            is_virtual: true,
+            ..Frame::default()
+        })
+    }
+
+    /// Remembers when the subtitle of the given frame expires. Overlapping subtitles of a
+    /// track are cleared once the last of them expired.
+    fn schedule_subtitle_clear(&mut self, frame: &Frame) {
+        let track = match self.index.track_by_number(frame.track) {
+            Some(track) if track.track_type == TrackType::Subtitle => track,
+            _ => return,
+        };
+        let default_duration = track.default_duration_ticks(self.index.info.timestamp_scale);
+        if let Some(duration) = frame.duration.or(default_duration) {
+            let expiry = frame.timestamp.saturating_add(duration);
+            let entry = self.subtitle_expiries.entry(frame.track).or_insert(expiry);
+            *entry = (*entry).max(expiry);
+        }
+    }
+
+    fn read_event(&mut self, frame: &mut Frame) -> Result<Option<DemuxEvent>> {
+        self.seek_to_first_cluster_if_needed()?;
+        if let Some(event) = self.pending_event.take() {
+            return Ok(Some(event));
+        }
+        if let Some(pending_frame) = self.pending_frame.take() {
+            *frame = pending_frame;
+            return Ok(Some(DemuxEvent::Frame));
+        }
+        if self.try_pop_frame(frame)? {
+            return Ok(Some(self.frame_event(frame)));
+        };
+
+        // Search for the next block.
+        loop {
+            let position = self.file.stream_position()?;
+            self.enter_element(position, ElementId::Unknown);
+            let (id, element_id, size) = match self.next_element_header_or_eof()? {
+                Some(header) => header,
+                None => {
+                    debug!("reached the end of the file");
+                    self.resume_position = Some((position, position));
+                    if self.end_of_segment {
+                        return Ok(None);
+                    }
+                    self.end_of_segment = true;
+                    return Ok(Some(DemuxEvent::EndOfSegment));
+                }
+            };
+            self.enter_element(position, element_id);
+            let element_data = self.read_element_data(element_id, size)?;
+            if self.index.settings.strict {
+                self.check_stream_placement(position, element_id, &element_data)?;
+            }
+            match element_id {
+                // A new segment starts inside the stream.
+                ElementId::Ebml => {
+                    if !self.end_of_segment {
+                        self.end_of_segment = true;
+                        self.file.seek(SeekFrom::Start(position))?;
+                        return Ok(Some(DemuxEvent::EndOfSegment));
+                    }
+                    self.start_new_segment(position)?;
+                    return Ok(Some(DemuxEvent::TracksChanged));
+                }
+                // Metadata elements after the first cluster are only reported as events. They
+                // are not parsed, so that reading frames doesn't depend on them.
+                ElementId::Chapters | ElementId::Tags | ElementId::Attachments => {
+                    self.record_skipped_element(id, element_id, position, size);
+                    let range = match element_data {
+                        ElementData::Location { offset, size } => position..offset + size,
+                        _ => return Err(DemuxError::UnexpectedDataType),
+                    };
+                    return Ok(Some(match element_id {
+                        ElementId::Chapters => DemuxEvent::Chapters(range),
+                        ElementId::Tags => DemuxEvent::Tags(range),
+                        _ => DemuxEvent::Attachments(range),
+                    }));
+                }
+                // We enter cluster and block groups.
+                ElementId::Cluster => {
+                    trace!(?element_data, "entering cluster");
+                    self.stitch.cluster_position = position;
+                    self.note_cluster(&element_data);
+                    self.silent_tracks.clear();
+                    self.report_progress()?;
+                }
+                ElementId::SilentTracks => {
+                    self.read_silent_tracks(&element_data)?;
+                }
+                // Update the current cluster timestamp.
+                ElementId::Timestamp => {
+                    if let ElementData::Unsigned(timestamp) = element_data {
+                        trace!(timestamp, "cluster timestamp");
+                        let discontinuity = self.stitch_cluster_timestamp(timestamp);
+                        let cluster_start = DemuxEvent::ClusterStart {
+                            timestamp: self.cluster_timestamp,
+                        };
+                        return match discontinuity {
+                            Some(discontinuity) => {
+                                self.pending_event = Some(cluster_start);
+                                Ok(Some(discontinuity))
+                            }
+                            None => Ok(Some(cluster_start)),
+                        };
+                    } else {
+                        return Err(DemuxError::UnexpectedDataType);
+                    }
+                }
+                // Parse the block data.
+                ElementId::SimpleBlock => {
+                    if let ElementData::Location { offset, size } = element_data {
+                        self.queue_block(offset, size, true)?;
+                        if self.try_pop_frame(frame)? {
+                            return Ok(Some(self.frame_event(frame)));
+                        }
+                    } else {
+                        return Err(DemuxError::UnexpectedDataType);
+                    }
+                }
+                // Parse the block group and its block.
+                ElementId::BlockGroup => {
+                    if let ElementData::Location { offset, size } = element_data {
+                        if !self.queue_block_group(position, offset, size)? {
+                            debug!(offset, "skipping BlockGroup without Block");
+                        } else if self.try_pop_frame(frame)? {
+                            return Ok(Some(self.frame_event(frame)));
+                        }
+                    } else {
+                        return Err(DemuxError::UnexpectedDataType);
+                    }
+                }
+                _ => {
+                    // We ignore all other elements.
+                    self.record_skipped_element(id, element_id, position, size);
+                }
+            }
+        }
+    }
+
+    /// Seeks to the first cluster if the file was opened by `open_metadata_only()` and
+    /// nothing was read yet.
+    fn seek_to_first_cluster_if_needed(&mut self) -> Result<()> {
+        if !self.needs_first_cluster {
+            return Ok(());
+        }
+        let cluster_start = self.first_cluster_offset()?;
+        self.file.seek(SeekFrom::Start(cluster_start))?;
+        self.needs_first_cluster = false;
+        Ok(())
+    }
+
+    /// Returns the offset of the first cluster. It's searched once if the SeekHead doesn't
+    /// reference a cluster and the file was opened by `open_metadata_only()`.
+    fn first_cluster_offset(&mut self) -> Result<u64> {
+        if let Some(offset) = self.index.seek_head.get(&ElementId::Cluster) {
+            return Ok(*offset);
+        }
+        if let Some(offset) = self.first_cluster {
+            return Ok(offset);
+        }
+        if !self.index.settings.metadata_only {
+            return Err(DemuxError::CantFindCluster);
+        }
+
+        let mut seek_head = self.index.seek_head.clone();
+        find_first_cluster_offset(&mut self.file, &mut seek_head)?;
+        let offset = *seek_head
+            .get(&ElementId::Cluster)
+            .ok_or(DemuxError::CantFindCluster)?;
+        self.first_cluster = Some(offset);
+        Ok(offset)
+    }
+
+    fn record_skipped_element(&mut self, id: u32, element_id: ElementId, offset: u64, size: u64) {
+        if self.index.settings.record_skipped_elements {
+            let element_id = match element_id {
+                ElementId::Unknown if self.index.settings.custom_elements.contains_key(&id) => {
+                    ElementId::Custom(id)
+                }
+                element_id => element_id,
+            };
+            self.skipped_elements.push(SkippedElement {
+                id,
+                element_id,
+                offset,
+                size,
+            });
+        }
+    }
+
+    /// Sets the timestamp of the entered cluster and detects backwards jumps of the
+    /// cluster timestamps. Returns the event that reports a discontinuity.
+    fn stitch_cluster_timestamp(&mut self, timestamp: u64) -> Option<DemuxEvent> {
+        let stitch_timestamps = self.index.settings.stitch_timestamps;
+        let position = self.stitch.cluster_position;
+        if self.stitch.previous_cluster_timestamp.is_none() {
+            self.restore_timestamp_offset(position);
+        }
+
+        let mut event = None;
+        if let Some(previous_timestamp) = self.stitch.previous_cluster_timestamp {
+            if timestamp.saturating_add(self.stitch.offset) < previous_timestamp {
+                if stitch_timestamps {
+                    // The stitched timestamp is at least the previous one.
+                    self.stitch.offset =
+                        self.stitch.max_frame_end.max(previous_timestamp) - timestamp;
+                    if self
+                        .timestamp_offsets
+                        .last()
+                        .map_or(true, |o| o.position < position)
+                    {
+                        self.timestamp_offsets.push(TimestampOffset {
+                            position,
+                            timestamp,
+                            offset: self.stitch.offset,
+                        });
+                    }
+                }
+                debug!(
+                    previous_timestamp,
+                    timestamp,
+                    offset = self.stitch.offset,
+                    "found a timestamp discontinuity"
+                );
+                event = Some(DemuxEvent::TimestampDiscontinuity {
+                    previous_timestamp,
+                    timestamp,
+                    offset: self.stitch.offset,
+                });
+            }
+        }
+
+        self.cluster_timestamp = timestamp.saturating_add(self.stitch.offset);
+        self.stitch.previous_cluster_timestamp = Some(self.cluster_timestamp);
+        event
+    }
+
+    /// Applies the recorded offset of the cluster at the given position after a seek.
+    fn restore_timestamp_offset(&mut self, cluster_position: u64) {
+        if !self.index.settings.stitch_timestamps {
+            return;
+        }
+        self.stitch.offset = self
+            .timestamp_offsets
+            .iter()
+            .rev()
+            .find(|o| o.position <= cluster_position)
+            .map_or(0, |o| o.offset);
+    }
+
+    /// Applies the recorded offset to the timestamp of the cluster at the given position,
+    /// which was read by a seek.
+    fn restitch_cluster(&mut self, cluster_position: u64) {
+        self.restore_timestamp_offset(cluster_position);
+        self.stitch.cluster_position = cluster_position;
+        self.cluster_timestamp = self.cluster_timestamp.saturating_add(self.stitch.offset);
+        self.stitch.previous_cluster_timestamp = Some(self.cluster_timestamp);
+    }
+
+    /// Returns the event of a frame that was just read. Reports a gap if the distance to the
+    /// previous frame of the track is larger than the `DefaultDuration` of the track. The
+    /// frame is then held back and returned by the next event.
+    fn frame_event(&mut self, frame: &mut Frame) -> DemuxEvent {
+        let previous = self.last_timestamps.insert(frame.track, frame.timestamp);
+        let default_duration = self
+            .index
+            .track_by_number(frame.track)
+            .and_then(|t| t.default_duration_ticks(self.index.info.timestamp_scale));
+
+        match (previous, default_duration) {
+            // We allow one tick of rounding error.
+            (Some(previous_timestamp), Some(default_duration))
+                if frame.timestamp > previous_timestamp
+                    && frame.timestamp - previous_timestamp
+                        > default_duration.saturating_add(1) =>
+            {
+                let gap = DemuxEvent::Gap {
+                    track: frame.track,
+                    previous_timestamp,
+                    timestamp: frame.timestamp,
+                };
+                self.pending_frame = Some(std::mem::take(frame));
+                gap
+            }
+            _ => DemuxEvent::Frame,
         }
     }
 
-    /// Element containing metadata describing tracks, editions,
-    /// chapters, attachments, or the segment as a whole.
-    pub fn tags(&self) -> Option<&[Tag]> {
-        match self.tags.as_ref() {
-            None => None,
-            Some(tags) => Some(tags),
+    /// Parses the metadata of a segment that starts inside the stream with the EBML header
+    /// at the given position and continues with its first cluster.
+    fn start_new_segment(&mut self, position: u64) -> Result<()> {
+        debug!(position, "found a new segment inside the stream");
+        self.file.seek(SeekFrom::Start(position))?;
+        let settings = self.index.settings.clone();
+        let index = MatroskaIndex::parse_with_settings(&mut self.file, &settings)?;
+        seek_to_first_cluster(&mut self.file, &index.seek_head)?;
+
+        self.index = Arc::new(index);
+        self.reset_state();
+
+        Ok(())
+    }
+
+    /// Checks that an element that is read while demuxing is allowed at its position.
+    fn check_stream_placement(
+        &mut self,
+        position: u64,
+        element_id: ElementId,
+        element_data: &ElementData,
+    ) -> Result<()> {
+        // A new segment can start inside the stream.
+        if element_id == ElementId::Ebml {
+            self.cluster_end = None;
+            return Ok(());
+        }
+        // The children of the block group were checked when it was entered.
+        if self.block_group_end.is_some_and(|end| position < end) {
+            return Ok(());
+        }
+
+        let is_cluster_child =
+            element_parents(element_id).map_or(true, |p| p.contains(&ElementId::Cluster));
+        let parent = match self.cluster_end {
+            // A cluster of unknown size ends at the first element that is not allowed in it.
+            Some(end) if position < end && (end != u64::MAX || is_cluster_child) => {
+                ElementId::Cluster
+            }
+            Some(_) => ElementId::Segment,
+            // After a seek, the position inside the cluster is unknown.
+            None if is_cluster_child => ElementId::Cluster,
+            None => ElementId::Segment,
+        };
+
+        match (element_id, element_data) {
+            (ElementId::Cluster, ElementData::Location { offset, size }) => {
+                check_placement(element_id, parent, position)?;
+                self.cluster_end = Some(offset.saturating_add(*size));
+            }
+            // The children of block groups are checked as well.
+            (ElementId::BlockGroup, ElementData::Location { offset, size }) => {
+                check_element_placement(
+                    &mut self.file,
+                    parent,
+                    position,
+                    self.index.header_limits(),
+                )?;
+                self.file.seek(SeekFrom::Start(*offset))?;
+                self.block_group_end = Some(offset.saturating_add(*size));
+            }
+            _ => check_placement(element_id, parent, position)?,
         }
+
+        Ok(())
     }
 
-    /// Reads the next frame data into the given `Frame`.
+    /// Returns the number of frames of the current block that were not read yet, for
+    /// example the remaining frames of a laced block.
+    pub fn pending_frames(&self) -> usize {
+        self.queued_frames.len()
+    }
+
+    /// Skips the frames of the current block that were not read yet and returns their
+    /// number. The next read starts with the next block.
+    pub fn drain_pending(&mut self) -> Result<usize> {
+        self.skip_pending_frames()
+            .map_err(|err| self.record_error_context(err))
+    }
+
+    fn skip_pending_frames(&mut self) -> Result<usize> {
+        let count = self.queued_frames.len();
+        if count > 0 {
+            let remaining: u64 = self.queued_frames.drain(..).map(|f| f.size).sum();
+            self.file.seek(SeekFrom::Current(remaining.try_into()?))?;
+        }
+        Ok(count)
+    }
+
+    /// Reads the next block without de-lacing it or reading its payload.
     ///
-    /// Returns `false` if the end of the file is reached.
-    pub fn next_frame(&mut self, frame: &mut Frame) -> Result<bool> {
-        if self.try_pop_frame(frame)? {
-            return Ok(true);
+    /// Returns `None` if the end of the file is reached. Remaining frames of a block that
+    /// was partially read by `next_frame()` are skipped.
+    pub fn next_raw_block(&mut self) -> Result<Option<RawBlock>> {
+        let mut attempt = 0;
+        let block = loop {
+            let result = self
+                .read_next_raw_block()
+                .map_err(|err| self.record_error_context(err));
+            let at_end = match &result {
+                Ok(Some(_)) => false,
+                Ok(None) => self.resume_position.is_some(),
+                Err(err) => err.code() == ErrorCode::UnexpectedEof,
+            };
+            if !self.follow || !at_end {
+                break result?;
+            }
+            self.wait_for_data(&mut attempt)?;
         };
+        Ok(block.map(|mut block| {
+            block.track = self.remapped_track(block.track);
+            if self.index.settings.nanosecond_timestamps {
+                let scale = block.timestamp_scale;
+                block.timestamp = signed_ticks_to_nanos(block.timestamp, scale);
+                block.duration = block.duration.map(|d| ticks_to_nanos(d, scale));
+                block.timestamp_scale = NonZeroU64::MIN;
+            }
+            block
+        }))
+    }
+
+    fn read_next_raw_block(&mut self) -> Result<Option<RawBlock>> {
+        self.seek_to_first_cluster_if_needed()?;
+        self.skip_pending_frames()?;
 
-        // Search for the next block.
         loop {
-            match next_element(&mut self.file) {
-                Ok((element_id, element_data)) => match element_id {
-                    // We enter cluster and block groups.
-                    ElementId::Cluster | ElementId::BlockGroup => {
-                        self.enter_data_location(&element_data)?;
+            let position = self.file.stream_position()?;
+            self.enter_element(position, ElementId::Unknown);
+            let (_, element_id, size) = match self.next_element_header_or_eof()? {
+                Some(header) => header,
+                None => {
+                    self.resume_position = Some((position, position));
+                    return Ok(None);
+                }
+            };
+            self.enter_element(position, element_id);
+            let element_data = self.read_element_data(element_id, size)?;
+            if self.index.settings.strict {
+                self.check_stream_placement(position, element_id, &element_data)?;
+            }
+            match element_id {
+                ElementId::Ebml => {
+                    self.start_new_segment(position)?;
+                }
+                ElementId::Cluster => {
+                    self.note_cluster(&element_data);
+                    self.silent_tracks.clear();
+                    self.report_progress()?;
+                }
+                ElementId::SilentTracks => {
+                    self.read_silent_tracks(&element_data)?;
+                }
+                ElementId::Timestamp => {
+                    if let ElementData::Unsigned(timestamp) = element_data {
+                        self.cluster_timestamp = timestamp;
+                    } else {
+                        return Err(DemuxError::UnexpectedDataType);
                     }
-                    // Update the current cluster timestamp.
-                    ElementId::Timestamp => {
-                        if let ElementData::Unsigned(timestamp) = element_data {
-                            self.cluster_timestamp = timestamp;
-                        } else {
-                            return Err(DemuxError::UnexpectedDataType);
+                }
+                ElementId::SimpleBlock => {
+                    return if let ElementData::Location { offset, size } = element_data {
+                        let block = self.read_raw_block(offset, size, true, None)?;
+                        self.file.seek(SeekFrom::Start(offset + size))?;
+                        Ok(Some(block))
+                    } else {
+                        Err(DemuxError::UnexpectedDataType)
+                    };
+                }
+                ElementId::BlockGroup => {
+                    if let ElementData::Location { offset, size } = element_data {
+                        let fields = collect_children(
+                            &mut self.file,
+                            offset,
+                            size,
+                            self.index.header_limits(),
+                        )?;
+                        let block = match fields.iter().find(|(id, _)| *id == ElementId::Block) {
+                            Some((_, ElementData::Location { offset, size })) => {
+                                let duration =
+                                    try_find_unsigned(&fields, ElementId::BlockDuration)?;
+                                self.file.seek(SeekFrom::Start(*offset))?;
+                                Some(self.read_raw_block(*offset, *size, false, duration)?)
+                            }
+                            Some(_) => return Err(DemuxError::UnexpectedDataType),
+                            None => None,
+                        };
+                        self.file.seek(SeekFrom::Start(offset + size))?;
+                        if block.is_some() {
+                            return Ok(block);
                         }
+                    } else {
+                        return Err(DemuxError::UnexpectedDataType);
                     }
-                    // Parse the block data.
-                    ElementId::SimpleBlock | ElementId::Block => {
-                        return if let ElementData::Location {
-                            offset: header_start,
-                            size: block_size,
-                        } = element_data
-                        {
-                            self.file.seek(SeekFrom::Start(header_start))?;
-
-                            parse_laced_frames(
-                                &mut self.file,
-                                &mut self.queued_frames,
-                                block_size,
-                                self.cluster_timestamp,
-                                header_start,
-                                element_id == ElementId::SimpleBlock,
-                            )?;
-                            self.try_pop_frame(frame)?;
-
-                            Ok(true)
-                        } else {
-                            Err(DemuxError::UnexpectedDataType)
-                        };
+                }
+                _ => { /* We ignore all other elements */ }
+            }
+        }
+    }
+
+    /// Parses the header of the block at the given offset. The reader must be positioned
+    /// at the start of the block.
+    fn read_raw_block(
+        &mut self,
+        offset: u64,
+        size: u64,
+        is_simple_block: bool,
+        duration: Option<u64>,
+    ) -> Result<RawBlock> {
+        let (track, timestamp, flags) = parse_block_header(&mut self.file, self.cluster_timestamp)?;
+        let laced_payload_offset = self.file.stream_position()?;
+
+        Ok(RawBlock {
+            track,
+            timestamp,
+            flags,
+            is_simple_block,
+            laced_payload_offset,
+            size: (offset + size).saturating_sub(laced_payload_offset),
+            duration,
+            timestamp_scale: self.index.info.timestamp_scale,
+        })
+    }
+
+    /// Records the context of the element that was read when the error occurred.
+    fn record_error_context(&mut self, err: DemuxError) -> DemuxError {
+        let err = map_unexpected_eof(err);
+        if let (DemuxError::UnexpectedEof, Some((offset, _))) = (&err, self.current_element) {
+            let file_size = match self.file.stream_position() {
+                Ok(position) => {
+                    let file_size = self.file.seek(SeekFrom::End(0)).unwrap_or(offset);
+                    let _ = self.file.seek(SeekFrom::Start(position));
+                    file_size
+                }
+                Err(_) => offset,
+            };
+            self.resume_position = Some((offset, file_size));
+        }
+        self.error_context = self.current_element.map(|(offset, element_id)| {
+            let mut element_path = Vec::with_capacity(self.parent_elements.len() + 2);
+            // The EBML header of a new segment is the only element read outside a segment.
+            if element_id != ElementId::Ebml {
+                element_path.push(ElementId::Segment);
+            }
+            element_path.extend(self.parent_elements.iter().map(|(id, _)| *id));
+            element_path.push(element_id);
+            ErrorContext {
+                offset,
+                element_path,
+            }
+        });
+        err
+    }
+
+    /// Reads the next element. Returns `None` if the file ends before the next element and
+    /// `DemuxError::UnexpectedEof` if the file ends inside the element.
+    fn next_element_or_eof(&mut self) -> Result<Option<(u32, ElementId, u64, ElementData)>> {
+        try_next_raw_element(&mut self.file, self.index.header_limits()).map_err(map_unexpected_eof)
+    }
+
+    /// Reads the data of the element whose header was just read. Clusters and blocks are
+    /// entered in place, so that we don't need to seek back to their data.
+    fn read_element_data(&mut self, element_id: ElementId, size: u64) -> Result<ElementData> {
+        match element_id {
+            ElementId::Cluster | ElementId::SimpleBlock | ElementId::BlockGroup => {
+                Ok(ElementData::Location {
+                    offset: self.file.stream_position()?,
+                    size,
+                })
+            }
+            _ => parse_element_data(&mut self.file, element_id, size),
+        }
+    }
+
+    /// Reads the header of the next element like `next_element_or_eof()`. The reader is
+    /// positioned at the start of the element data afterwards.
+    fn next_element_header_or_eof(&mut self) -> Result<Option<(u32, ElementId, u64)>> {
+        try_next_element_header(&mut self.file, self.index.header_limits())
+            .map_err(map_unexpected_eof)
+    }
+
+    /// Reads all frames of the file and reports per track timestamp irregularities like gaps
+    /// larger than the `DefaultDuration`, backwards jumps and overlapping subtitle durations.
+    ///
+    /// The position of the demuxer is restored after the analysis.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn analyze_timestamps(&mut self) -> Result<TimestampReport> {
+        let state = self.save_state()?;
+        let result = self.collect_frame_timings();
+        self.restore_state(state)?;
+
+        Ok(self.index.timestamp_report(&result?))
+    }
+
+    /// Returns the first keyframe of every track, or the first frame if the track has no
+    /// keyframe, for example to create a thumbnail or to probe the codecs.
+    ///
+    /// The frames are read from the start of the first segment until a keyframe was found
+    /// for every track. They are returned in the order of the tracks. Tracks without any
+    /// frame are left out. The position of the demuxer is restored afterwards.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn probe_first_frames(&mut self) -> Result<Vec<Frame>> {
+        let state = self.save_state()?;
+        let result = self.collect_first_frames();
+        self.restore_state(state)?;
+        result
+    }
+
+    fn collect_first_frames(&mut self) -> Result<Vec<Frame>> {
+        let cluster_start = self.first_cluster_offset()?;
+        self.file.seek(SeekFrom::Start(cluster_start))?;
+
+        let index = Arc::clone(&self.index);
+        let mut first_frames: HashMap<u64, Frame> = HashMap::new();
+        let mut keyframes = 0;
+        let mut frame = Frame::default();
+        while keyframes < index.tracks().len() {
+            match self.read_next_event(&mut frame)? {
+                Some(DemuxEvent::Frame) if !frame.is_virtual => {}
+                Some(DemuxEvent::EndOfSegment) | None => break,
+                Some(_) => continue,
+            }
+            if index.track_by_number(frame.track).is_none() {
+                continue;
+            }
+
+            let is_keyframe = frame.is_keyframe.unwrap_or(true);
+            match first_frames.get(&frame.track) {
+                Some(first_frame) if first_frame.is_keyframe != Some(false) => continue,
+                Some(_) if !is_keyframe => continue,
+                _ => {}
+            }
+            if is_keyframe {
+                keyframes += 1;
+            }
+            first_frames.insert(frame.track, frame.clone());
+        }
+
+        Ok(index
+            .tracks()
+            .iter()
+            .filter_map(|track| first_frames.remove(&track.track_number().get()))
+            .map(|mut frame| {
+                frame.track = self.remapped_track(frame.track);
+                frame
+            })
+            .collect())
+    }
+
+    /// Saves the state that depends on the position of the reader and resets it, so that
+    /// the file can be read from another position.
+    fn save_state(&mut self) -> Result<SavedState> {
+        Ok(SavedState {
+            position: self.file.stream_position()?,
+            index: Arc::clone(&self.index),
+            cluster_timestamp: std::mem::take(&mut self.cluster_timestamp),
+            queued_frames: std::mem::take(&mut self.queued_frames),
+            current_element: self.current_element.take(),
+            parent_elements: std::mem::take(&mut self.parent_elements),
+            silent_tracks: std::mem::take(&mut self.silent_tracks),
+            last_timestamps: std::mem::take(&mut self.last_timestamps),
+            end_of_segment: std::mem::take(&mut self.end_of_segment),
+            pending_codec_states: std::mem::take(&mut self.pending_codec_states),
+            subtitle_expiries: std::mem::take(&mut self.subtitle_expiries),
+            held_event: self.held_event.take(),
+            pending_event: self.pending_event.take(),
+            pending_frame: self.pending_frame.take(),
+            stitch: std::mem::take(&mut self.stitch),
+            cluster_end: self.cluster_end.take(),
+            block_group_end: self.block_group_end.take(),
+            needs_first_cluster: std::mem::take(&mut self.needs_first_cluster),
+            skipped_elements: self.skipped_elements.len(),
+            resume_position: self.resume_position.take(),
+            unknown_size_cluster: std::mem::take(&mut self.unknown_size_cluster),
+        })
+    }
+
+    /// Restores the state saved by `save_state()`. Elements that were skipped in between
+    /// are not recorded.
+    fn restore_state(&mut self, state: SavedState) -> Result<()> {
+        self.file.seek(SeekFrom::Start(state.position))?;
+        self.index = state.index;
+        self.cluster_timestamp = state.cluster_timestamp;
+        self.queued_frames = state.queued_frames;
+        self.current_element = state.current_element;
+        self.parent_elements = state.parent_elements;
+        self.silent_tracks = state.silent_tracks;
+        self.last_timestamps = state.last_timestamps;
+        self.end_of_segment = state.end_of_segment;
+        self.pending_codec_states = state.pending_codec_states;
+        self.subtitle_expiries = state.subtitle_expiries;
+        self.held_event = state.held_event;
+        self.pending_event = state.pending_event;
+        self.pending_frame = state.pending_frame;
+        self.stitch = state.stitch;
+        self.cluster_end = state.cluster_end;
+        self.block_group_end = state.block_group_end;
+        self.needs_first_cluster = state.needs_first_cluster;
+        self.skipped_elements.truncate(state.skipped_elements);
+        self.resume_position = state.resume_position;
+        self.unknown_size_cluster = state.unknown_size_cluster;
+        Ok(())
+    }
+
+    /// Validates the structure of the file against the Matroska specification and reports
+    /// violations like missing mandatory elements, out of range values, misplaced elements,
+    /// duplicate TrackUIDs and non monotonic cluster timestamps.
+    ///
+    /// The position of the demuxer is restored after the validation.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn validate(&mut self) -> Result<ValidationReport> {
+        let position = self.file.stream_position()?;
+        let result = validate_file(&mut self.file, &self.index);
+        self.file.seek(SeekFrom::Start(position))?;
+        result
+    }
+
+    fn collect_frame_timings(&mut self) -> Result<HashMap<u64, Vec<FrameTiming>>> {
+        let cluster_start = self.first_cluster_offset()?;
+        self.file.seek(SeekFrom::Start(cluster_start))?;
+
+        let mut timings: HashMap<u64, Vec<FrameTiming>> = HashMap::new();
+        let mut frame = Frame::default();
+        while self.read_next_frame(&mut frame)? {
+            timings.entry(frame.track).or_default().push(FrameTiming {
+                timestamp: frame.timestamp,
+                duration: frame.duration,
+                is_keyframe: frame.is_keyframe.unwrap_or(false),
+            });
+        }
+
+        Ok(timings)
+    }
+
+    /// Parses the header of the block and queues its frames. The reader must be positioned
+    /// at the given offset of the block data and is left at the start of the frame data.
+    fn queue_block(&mut self, offset: u64, size: u64, is_simple_block: bool) -> Result<()> {
+        let first_frame = self.queued_frames.len();
+        parse_laced_frames(
+            &mut self.file,
+            &mut self.queued_frames,
+            size,
+            self.cluster_timestamp,
+            offset,
+            is_simple_block,
+        )?;
+
+        let count = self.queued_frames.len() - first_frame;
+        if let Some(max_laced_frames) = self.index.settings.max_laced_frames {
+            if count > max_laced_frames {
+                // Skip the block, so that the caller can continue after the error.
+                self.queued_frames.truncate(first_frame);
+                self.file
+                    .seek(SeekFrom::Start(offset.saturating_add(size)))?;
+                return Err(DemuxError::TooManyLacedFrames(u64::try_from(count)?));
+            }
+        }
+        Ok(())
+    }
+
+    /// Collects the children of a block group and queues the frames of its block.
+    /// Returns `false` if the block group doesn't contain a block.
+    fn queue_block_group(&mut self, position: u64, offset: u64, size: u64) -> Result<bool> {
+        let fields = collect_children(&mut self.file, offset, size, self.index.header_limits())?;
+
+        let (block_offset, block_size) = match fields.iter().find(|(id, _)| *id == ElementId::Block)
+        {
+            Some((_, ElementData::Location { offset, size })) => (*offset, *size),
+            Some(_) => return Err(DemuxError::UnexpectedDataType),
+            None => return Ok(false),
+        };
+        let duration = try_find_unsigned(&fields, ElementId::BlockDuration)?;
+        let reference_priority = find_unsigned_or(&fields, ElementId::ReferencePriority, 0)?;
+        let codec_state = try_find_binary(&mut self.file, &fields, ElementId::CodecState)?;
+        let is_keyframe = !fields
+            .iter()
+            .any(|(id, _)| *id == ElementId::ReferenceBlock);
+
+        let first_frame = self.queued_frames.len();
+        self.file.seek(SeekFrom::Start(block_offset))?;
+        self.queue_block(block_offset, block_size, false)?;
+        self.queued_frames
+            .iter_mut()
+            .skip(first_frame)
+            .for_each(|f| {
+                f.is_keyframe = Some(is_keyframe);
+                f.duration = duration;
+                f.reference_priority = Some(reference_priority);
+                f.block_group = Some((position, offset + size));
+            });
+
+        if let (Some(codec_state), Some(queued_frame)) =
+            (codec_state, self.queued_frames.get(first_frame))
+        {
+            self.pending_codec_states
+                .insert(queued_frame.track, codec_state);
+        }
+
+        Ok(true)
+    }
+
+    /// Read a frame that is left inside the block.
+    fn try_pop_frame(&mut self, frame: &mut Frame) -> Result<bool> {
+        while let Some(queued_frame) = self.queued_frames.pop_front() {
+            let track = self.index.track_by_number(queued_frame.track);
+            if track.is_none() {
+                match self.index.settings.unknown_track_policy {
+                    UnknownTrackPolicy::Pass => {}
+                    UnknownTrackPolicy::Drop => {
+                        trace!(
+                            track = queued_frame.track,
+                            "dropping frame of unknown track"
+                        );
+                        self.file
+                            .seek(SeekFrom::Current(queued_frame.size.try_into()?))?;
+                        continue;
                     }
-                    _ => { /* We ignore all other elements */ }
-                },
-                // If we encounter an IO error, we assume that there
-                // are no more blocks to handle (EOF).
-                Err(err) => {
-                    if let Some(err) = err.source() {
-                        if err.downcast_ref::<std::io::Error>().is_some() {
-                            return Ok(false);
-                        }
+                    UnknownTrackPolicy::Error => {
+                        return Err(DemuxError::TrackNotFound(queued_frame.track));
+                    }
+                }
+            }
+
+            let timestamp = self.lace_timestamp(&queued_frame, track)?;
+            let codec_delay = match track.and_then(|t| t.codec_delay) {
+                Some(codec_delay) => {
+                    i64::try_from(codec_delay / self.index.info.timestamp_scale.get())?
+                }
+                None => 0,
+            };
+
+            let (unsigned, is_clamped) =
+                match unsigned_timestamp(timestamp, self.index.settings.timestamp_policy) {
+                    Ok(timestamp) => timestamp,
+                    Err(err) => {
+                        self.file
+                            .seek(SeekFrom::Current(queued_frame.size.try_into()?))?;
+                        return Err(err);
+                    }
+                };
+            frame.timestamp = unsigned;
+            frame.is_timestamp_clamped = is_clamped;
+            frame.signed_timestamp = timestamp;
+            frame.cluster_timestamp = queued_frame.cluster_timestamp;
+            frame.relative_timestamp = queued_frame.relative_timestamp;
+            frame.presentation_timestamp = timestamp.saturating_sub(codec_delay);
+            frame.track = queued_frame.track;
+            frame.is_discardable = queued_frame.is_discardable;
+            frame.is_invisible = queued_frame.is_invisible;
+            frame.is_keyframe = queued_frame.is_keyframe;
+            frame.lace_index = queued_frame.lace_index;
+            let duration = queued_frame
+                .duration
+                .or(track.and_then(|t| t.default_duration_ticks(self.index.info.timestamp_scale)));
+            frame.duration = match self.index.settings.default_duration_fallback {
+                true => duration,
+                false => queued_frame.duration,
+            };
+            self.stitch.max_frame_end = self
+                .stitch
+                .max_frame_end
+                .max(frame.timestamp.saturating_add(duration.unwrap_or(0)));
+            frame.reference_priority = queued_frame.reference_priority;
+            frame.codec_state = self.pending_codec_states.remove(&queued_frame.track);
+            frame.is_virtual = false;
+            frame.track_type = track
+                .filter(|_| self.index.settings.stamp_track_type)
+                .map(|t| t.track_type);
+            frame.block_kind = match queued_frame.block_group {
+                Some(_) => BlockKind::Block,
+                None => BlockKind::SimpleBlock,
+            };
+            frame.block_group_range = queued_frame.block_group.map(|(start, end)| start..end);
+            frame.timestamp_scale = self.index.info.timestamp_scale;
+            frame.lace_ranges.clear();
+
+            let mut size: usize = queued_frame.size.try_into()?;
+            let lace_count = match queued_frame.lace_index {
+                Some((0, lace_count)) if self.index.settings.combine_laced_frames => lace_count,
+                _ => 0,
+            };
+            if lace_count > 0 {
+                // The data of the laced frames is stored consecutively inside the block.
+                frame.lace_index = None;
+                frame.lace_ranges.push(0..size);
+                for _ in 1..lace_count {
+                    let laced_frame = self
+                        .queued_frames
+                        .pop_front()
+                        .ok_or(DemuxError::UnexpectedDataType)?;
+                    let start = size;
+                    size += usize::try_from(laced_frame.size)?;
+                    frame.lace_ranges.push(start..size);
+                }
+            }
+
+            if let Some(max_capacity) = self.index.settings.max_frame_capacity {
+                if size <= max_capacity && frame.data.capacity() > max_capacity {
+                    frame.data.clear();
+                    frame.data.shrink_to(max_capacity);
+                }
+            }
+            frame.data.resize(size, 0_u8);
+            self.file.read_exact(frame.data.as_mut_slice())?;
+
+            frame.encryption = match track.and_then(TrackEntry::content_encryption) {
+                Some(encryption) => Some(strip_frame(
+                    self.decryptors.get(&frame.track).map(|d| d.0.as_ref()),
+                    encryption.key_id().unwrap_or_default(),
+                    &mut frame.data,
+                    &mut frame.lace_ranges,
+                )?),
+                None => None,
+            };
+            let compression =
+                track.and_then(|t| t.content_compression(ContentEncodingScope::FRAME));
+            frame.compression = match compression {
+                Some(compression) => decompress_frame(
+                    compression,
+                    &mut frame.data,
+                    &mut frame.lace_ranges,
+                    self.index.settings.decompressed_size_limit(),
+                )?,
+                None => None,
+            };
+
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    /// Laced frames share the timestamp of their block. If the track has a `DefaultDuration`,
+    /// we can calculate the timestamp of each frame inside the lace.
+    fn lace_timestamp(&self, queued_frame: &LacedFrame, track: Option<&TrackEntry>) -> Result<i64> {
+        let lace_index = match queued_frame.lace_index {
+            Some((lace_index, _)) if lace_index > 0 => u64::from(lace_index),
+            _ => return Ok(queued_frame.timestamp),
+        };
+
+        let timestamp = match track.and_then(|t| t.default_duration) {
+            Some(default_duration) => {
+                let offset = u128::from(lace_index) * u128::from(default_duration.get())
+                    / u128::from(self.index.info.timestamp_scale.get());
+                queued_frame
+                    .timestamp
+                    .saturating_add(i64::try_from(offset)?)
+            }
+            None => queued_frame.timestamp,
+        };
+
+        Ok(timestamp)
+    }
+
+    /// Seeks to the given timestamp. The next `next_frame()` will write the first frame that comes
+    /// directly AFTER the given timestamp. If the timestamp is outside of the duration of the video,
+    /// the next `next_frame()` will return `None`.
+    ///
+    /// Seek operations will use `Cues` inside the file for faster seek operation. If no `Cues` are
+    /// present, this function will do a linear search through all clusters / blocks until the first
+    /// frame after the given timestamp is found. Deferred `Cues` are parsed by the first seek.
+    ///
+    /// Returns the timestamp of the block that was actually reached, which is usually after the
+    /// requested timestamp.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub fn seek(&mut self, seek_timestamp: u64) -> Result<SeekOutcome> {
+        self.seek_with_cues(seek_timestamp, None)
+    }
+
+    /// Seeks to the given timestamp like `seek()`, but only consults the cue points of the
+    /// given track.
+    ///
+    /// On files with multiple tracks, the cue point found by `seek()` could belong to another
+    /// track, for example a subtitle track, which overshoots the keyframe of a video track.
+    /// Falls back to a linear search if the track has no usable cue point.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub fn seek_track(&mut self, track: u64, seek_timestamp: u64) -> Result<SeekOutcome> {
+        if self.index.track_by_number(track).is_none() {
+            return Err(DemuxError::TrackNotFound(track));
+        }
+        self.seek_with_cues(seek_timestamp, Some(track))
+    }
+
+    fn seek_with_cues(&mut self, seek_timestamp: u64, track: Option<u64>) -> Result<SeekOutcome> {
+        self.reset_state();
+
+        let cluster_start = self.first_cluster_offset()?;
+
+        let (target_offset, cluster_offset, used_cues) =
+            self.seek_broad_phase(seek_timestamp, track, cluster_start)?;
+
+        self.file.seek(SeekFrom::Start(target_offset))?;
+
+        let reached = self.seek_narrow_phase(seek_timestamp, cluster_offset)?;
+        if let Some((_, cluster_offset)) = reached {
+            self.restitch_cluster(cluster_offset);
+        }
+
+        Ok(SeekOutcome {
+            requested: seek_timestamp,
+            reached: reached.map(|(timestamp, _)| timestamp),
+            used_cues,
+            cluster_offset: reached.map(|(_, cluster_offset)| cluster_offset),
+        })
+    }
+
+    /// Seeks to the given absolute byte offset of a `Cluster`, `SimpleBlock` or `BlockGroup`,
+    /// for example a `cluster_position()` of the cue points or an offset of an external index.
+    ///
+    /// The state of the demuxer is reset. For blocks, the timestamp of the cluster that
+    /// contains the block is read first, which requires clusters with a known size.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub fn seek_to_offset(&mut self, offset: u64) -> Result<()> {
+        self.reset_state();
+
+        let (element_id, _) =
+            parse_element_header(&mut self.file, Some(offset), self.index.header_limits())?;
+        match element_id {
+            ElementId::Cluster => {}
+            ElementId::SimpleBlock | ElementId::BlockGroup => {
+                let cluster_start = self.find_cluster_of_offset(offset)?;
+                let (_, timestamp) = self.get_cluster_offset_and_timestamp(cluster_start)?;
+                self.cluster_timestamp = timestamp;
+                self.restitch_cluster(cluster_start);
+            }
+            _ => {
+                return Err(DemuxError::UnexpectedElement((
+                    ElementId::Cluster,
+                    element_id,
+                )))
+            }
+        }
+
+        self.file.seek(SeekFrom::Start(offset))?;
+        Ok(())
+    }
+
+    /// Returns the position of the cluster that contains the given offset by walking the
+    /// headers of the top level elements.
+    fn find_cluster_of_offset(&mut self, offset: u64) -> Result<u64> {
+        let mut position = self.first_cluster_offset()?;
+
+        while position < offset {
+            let (element_id, size) =
+                parse_element_header(&mut self.file, Some(position), self.index.header_limits())?;
+            if size == u64::MAX {
+                break;
+            }
+            let end = self.file.stream_position()? + size;
+            if element_id == ElementId::Cluster && offset < end {
+                return Ok(position);
+            }
+            position = end;
+        }
+
+        Err(DemuxError::CantFindCluster)
+    }
+
+    /// Resets the state that depends on the current position of the reader.
+    fn reset_state(&mut self) {
+        self.cluster_timestamp = 0;
+        self.silent_tracks.clear();
+        self.last_timestamps.clear();
+        self.pending_codec_states.clear();
+        self.subtitle_expiries.clear();
+        self.held_event = None;
+        self.pending_event = None;
+        self.stitch = StitchState::default();
+        self.cluster_end = None;
+        self.block_group_end = None;
+        self.needs_first_cluster = false;
+        self.end_of_segment = false;
+        self.pending_frame = None;
+        self.queued_frames.clear();
+        self.current_element = None;
+        self.parent_elements.clear();
+        self.resume_position = None;
+    }
+
+    /// Seeks to the given time, which is converted into the timestamp scale of the segment.
+    /// Works like `seek()`. Negative times seek to the start of the segment.
+    pub fn seek_time(&mut self, time: impl Into<Timestamp>) -> Result<SeekOutcome> {
+        let ticks = time.into().rescale(self.index.info.timestamp_scale).ticks();
+        self.seek(u64::try_from(ticks).unwrap_or(0))
+    }
+
+    /// Seeks to the given timestamp in nanoseconds. Works like `seek()`, but doesn't depend
+    /// on the timestamp scale of the segment.
+    pub fn seek_ns(&mut self, nanos: u64) -> Result<SeekOutcome> {
+        self.seek_time(Timestamp::from_nanos(i64::try_from(nanos)?))
+    }
+
+    /// Seeks to the given duration since the start of the segment. Works like `seek()`, but
+    /// doesn't depend on the timestamp scale of the segment.
+    pub fn seek_duration(&mut self, duration: Duration) -> Result<SeekOutcome> {
+        self.seek_time(duration)
+    }
+
+    /// Returns the byte ranges of the clusters that contain the frames between the `start`
+    /// and `end` timestamps. Adjacent clusters are merged into a single range.
+    ///
+    /// The first cluster is found with the help of the `Cues`. Only the headers of the
+    /// following clusters are read, so the ranges can be used to fetch partial files via
+    /// HTTP range requests. The end of a cluster with an unknown size is the end of the
+    /// segment, or `u64::MAX` if the segment size is unknown too.
+    ///
+    /// The position of the demuxer is restored after the calculation.
+    pub fn byte_range_for_time_range(
+        &mut self,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<std::ops::Range<u64>>> {
+        let position = self.file.stream_position()?;
+        let result = self.collect_cluster_ranges(start, end);
+        self.file.seek(SeekFrom::Start(position))?;
+        result
+    }
+
+    fn collect_cluster_ranges(
+        &mut self,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<std::ops::Range<u64>>> {
+        let index = Arc::clone(&self.index);
+        let segment_end = index.segment_data_size.map_or(u64::MAX, |size| {
+            index.segment_data_offset.saturating_add(size)
+        });
+
+        let first_cluster = self.first_cluster_offset()?;
+        let mut position = index
+            .load_cue_points(&mut self.file)?
+            .and_then(|cue_points| {
+                cue_points
+                    .iter()
+                    .filter(|p| p.time <= start)
+                    .max_by_key(|p| p.time)
+            })
+            .and_then(|p| p.track_positions.first())
+            .map_or(first_cluster, |t| t.cluster_position);
+
+        let mut ranges: Vec<std::ops::Range<u64>> = Vec::new();
+        while position < segment_end {
+            let (element_id, size) = match parse_element_header(
+                &mut self.file,
+                Some(position),
+                self.index.header_limits(),
+            ) {
+                Ok(header) => header,
+                // We reached the end of the file.
+                Err(DemuxError::IoError(err)) if err.kind() == ErrorKind::UnexpectedEof => break,
+                Err(err) => return Err(err),
+            };
+            let data_offset = self.file.stream_position()?;
+
+            if element_id == ElementId::Cluster {
+                let (_, timestamp) = self.get_cluster_offset_and_timestamp(position)?;
+                if timestamp > end {
+                    break;
+                }
+                // Cues don't need to reference every cluster, so a later cluster
+                // might still start before the requested range.
+                if timestamp <= start {
+                    ranges.clear();
+                }
+
+                let cluster_end = if size == u64::MAX {
+                    segment_end
+                } else {
+                    data_offset + size
+                };
+
+                match ranges.last_mut() {
+                    Some(range) if range.end == position => range.end = cluster_end,
+                    _ => ranges.push(position..cluster_end),
+                }
+
+                if size == u64::MAX {
+                    break;
+                }
+            } else if size == u64::MAX {
+                break;
+            }
+
+            position = data_offset + size;
+        }
+
+        Ok(ranges)
+    }
+
+    /// Returns the byte ranges of the initialization segment and the media segments,
+    /// so that the file can be served by a WebM DASH or Media Source Extensions packager.
+    ///
+    /// Returns an error if the file has no `Cues`. The position of the demuxer is restored
+    /// after the calculation.
+    pub fn streaming_layout(&mut self) -> Result<StreamingLayout> {
+        let position = self.file.stream_position()?;
+        let result = self.collect_streaming_layout();
+        self.file.seek(SeekFrom::Start(position))?;
+        result
+    }
+
+    fn collect_streaming_layout(&mut self) -> Result<StreamingLayout> {
+        let index = Arc::clone(&self.index);
+        let first_cluster = self.first_cluster_offset()?;
+        let cue_points = index
+            .load_cue_points(&mut self.file)?
+            .ok_or(DemuxError::ElementNotFound(ElementId::Cues))?;
+
+        let mut cluster_positions: Vec<u64> = cue_points
+            .iter()
+            .flat_map(|p| p.track_positions.iter().map(|t| t.cluster_position))
+            .collect();
+        cluster_positions.sort_unstable();
+        cluster_positions.dedup();
+
+        let mut media_segments = Vec::with_capacity(cluster_positions.len());
+        for (i, start) in cluster_positions.iter().copied().enumerate() {
+            let (_, timestamp) = self.get_cluster_offset_and_timestamp(start)?;
+            let end = match cluster_positions.get(i + 1) {
+                Some(next) => *next,
+                None => self.end_of_clusters(start)?,
+            };
+            media_segments.push(MediaSegment {
+                timestamp,
+                range: start..end,
+            });
+        }
+
+        Ok(StreamingLayout {
+            init_segment: self.index.header_offset..first_cluster,
+            media_segments,
+        })
+    }
+
+    /// Returns the end of the consecutive clusters starting at the given position.
+    fn end_of_clusters(&mut self, mut position: u64) -> Result<u64> {
+        let segment_end = self.index.segment_data_size.map_or(u64::MAX, |size| {
+            self.index.segment_data_offset.saturating_add(size)
+        });
+
+        while position < segment_end {
+            match parse_element_header(&mut self.file, Some(position), self.index.header_limits()) {
+                Ok((ElementId::Cluster, size)) => {
+                    if size == u64::MAX {
+                        return Ok(segment_end);
                     }
-                    return Err(err);
+                    position = self.file.stream_position()? + size;
                 }
+                // The clusters end with another top level element or the end of the file.
+                _ => break,
             }
         }
+
+        Ok(position.min(segment_end))
+    }
+
+    /// Reads the bytes of the given range, for example the ranges returned by
+    /// `streaming_layout()`.
+    ///
+    /// The position of the demuxer is restored after the read.
+    pub fn read_byte_range(&mut self, range: std::ops::Range<u64>) -> Result<Vec<u8>> {
+        let position = self.file.stream_position()?;
+        let size: usize = range.end.saturating_sub(range.start).try_into()?;
+        let mut data = vec![0_u8; size];
+
+        self.file.seek(SeekFrom::Start(range.start))?;
+        let result = self.file.read_exact(&mut data);
+        self.file.seek(SeekFrom::Start(position))?;
+        result?;
+
+        Ok(data)
+    }
+
+    /// Parses the `Chapters` element at the given byte range, as reported by
+    /// `DemuxEvent::Chapters`.
+    ///
+    /// The position of the demuxer is restored after the parsing.
+    pub fn read_chapters(&mut self, range: std::ops::Range<u64>) -> Result<Vec<EditionEntry>> {
+        self.read_collection::<EditionEntry>(
+            range.start,
+            ElementId::Chapters,
+            ElementId::EditionEntry,
+        )
     }
 
-    /// Read a frame that is left inside the block.
-    fn try_pop_frame(&mut self, frame: &mut Frame) -> Result<bool> {
-        if let Some(queued_frame) = self.queued_frames.pop_front() {
-            frame.timestamp = queued_frame.timestamp;
-            frame.track = queued_frame.track;
-            frame.is_discardable = queued_frame.is_discardable;
-            frame.is_invisible = queued_frame.is_invisible;
-            frame.is_keyframe = queued_frame.is_keyframe;
+    /// Parses the `Tags` element at the given byte range, as reported by `DemuxEvent::Tags`.
+    ///
+    /// The position of the demuxer is restored after the parsing.
+    pub fn read_tags(&mut self, range: std::ops::Range<u64>) -> Result<Vec<Tag>> {
+        self.read_collection::<Tag>(range.start, ElementId::Tags, ElementId::Tag)
+    }
+
+    fn read_collection<T: ParsableElement<R>>(
+        &mut self,
+        offset: u64,
+        master_id: ElementId,
+        child_id: ElementId,
+    ) -> Result<Vec<T::Output>> {
+        let position = self.file.stream_position()?;
+        let result = parse_children_at_offset::<_, T>(
+            &mut self.file,
+            offset,
+            master_id,
+            child_id,
+            self.index.parse_options(),
+        );
+        self.file.seek(SeekFrom::Start(position))?;
+        result
+    }
+
+    /// Seeks to the given presentation timestamp of a track, taking the `SeekPreRoll` and
+    /// `CodecDelay` of the track into account.
+    ///
+    /// Codecs like Opus need to decode some data before the target timestamp to converge
+    /// to the correct output. The demuxer is positioned at the first block that is at most
+    /// `SeekPreRoll` before the target, using the cue points of the track, and the returned
+    /// `PreRollSeek` reports how much of the decoded output must be discarded. Returns `None` if the
+    /// target is after the last block of the file.
+    pub fn seek_with_preroll(&mut self, track: u64, timestamp: u64) -> Result<Option<PreRollSeek>> {
+        let timestamp_scale = self.index.info.timestamp_scale.get();
+        let entry = self
+            .index
+            .track_by_number(track)
+            .ok_or(DemuxError::TrackNotFound(track))?;
+
+        let seek_pre_roll = entry.seek_pre_roll.unwrap_or(0);
+        let pre_roll =
+            seek_pre_roll / timestamp_scale + u64::from(seek_pre_roll % timestamp_scale != 0);
+        let codec_delay = entry.codec_delay.unwrap_or(0) / timestamp_scale;
+
+        // Block timestamps are delayed by the codec delay compared to the presentation.
+        let target = timestamp
+            .saturating_add(codec_delay)
+            .saturating_sub(pre_roll);
+        let seek_timestamp = match self.seek_track(track, target)?.reached {
+            Some(reached) => reached,
+            None => return Ok(None),
+        };
 
-            let size: usize = queued_frame.size.try_into()?;
-            frame.data.resize(size, 0_u8);
-            self.file.read_exact(frame.data.as_mut_slice())?;
+        let discard_duration = timestamp.saturating_sub(seek_timestamp.saturating_sub(codec_delay));
 
-            Ok(true)
-        } else {
-            Ok(false)
-        }
+        Ok(Some(PreRollSeek {
+            seek_timestamp,
+            discard_until: timestamp,
+            discard_duration,
+        }))
     }
 
-    /// Seeks to the given timestamp. The next `next_frame()` will write the first frame that comes
-    /// directly AFTER the given timestamp. If the timestamp is outside of the duration of the video,
-    /// the next `next_frame()` will return `None`.
+    /// Returns the sample index of the given audio track, which maps sample indices to the
+    /// blocks of the track.
     ///
-    /// Seek operations will use `Cues` inside the file for faster seek operation. If no `Cues` are
-    /// present, this function will do a linear search through all clusters / blocks until the first
-    /// frame after the given timestamp is found.
-    pub fn seek(&mut self, seek_timestamp: u64) -> Result<()> {
-        self.cluster_timestamp = 0;
-        self.queued_frames.clear();
+    /// The track is given by the number that is reported in `Frame::track`, so it follows
+    /// the table of `set_track_remap()`. The index is built by reading the block headers of
+    /// the whole file on the first call and cached afterwards. The position of the demuxer
+    /// is restored after the index was built.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub fn sample_index(&mut self, track: u64) -> Result<&SampleIndex> {
+        let index = match self.sample_indices.remove(&track) {
+            Some(index) => index,
+            None => {
+                let state = self.save_state()?;
+                let result = self.build_sample_index(track);
+                self.restore_state(state)?;
+                result?
+            }
+        };
+        Ok(self.sample_indices.entry(track).or_insert(index))
+    }
+
+    fn build_sample_index(&mut self, track: u64) -> Result<SampleIndex> {
+        let timestamp_scale = self.index.info.timestamp_scale.get();
+        let entry = self
+            .index
+            .tracks
+            .iter()
+            .find(|entry| self.remapped_track(entry.track_number.get()) == track)
+            .ok_or(DemuxError::TrackNotFound(track))?;
+        let audio = entry
+            .audio()
+            .ok_or(DemuxError::ElementNotFound(ElementId::Audio))?;
+        let sample_rate = audio
+            .output_sampling_frequency()
+            .unwrap_or_else(|| audio.sampling_frequency());
+        let samples_per_frame = entry
+            .default_duration()
+            .map(|duration| samples_per_frame(duration.get(), sample_rate));
+        let mut sample_index = SampleIndex::new(track, sample_rate, samples_per_frame);
+
+        let cluster_start = self.first_cluster_offset()?;
+        self.file.seek(SeekFrom::Start(cluster_start))?;
+        while let Some(block) = self.read_next_raw_block()? {
+            if self.remapped_track(block.track) != track {
+                continue;
+            }
+            let offset = match self.current_element {
+                Some((offset, _)) => offset,
+                None => continue,
+            };
+            let frames = match block.flags & 0x06 {
+                0 => 1,
+                _ => {
+                    let lacing = block.laced_payload_offset..block.laced_payload_offset + 1;
+                    let count = self.read_byte_range(lacing)?;
+                    count.first().map_or(1, |&count| u64::from(count) + 1)
+                }
+            };
+            let timestamp_ns = block
+                .timestamp
+                .saturating_mul(i64::try_from(timestamp_scale)?);
+            sample_index.push(block.timestamp, timestamp_ns, offset, frames);
+        }
 
-        let cluster_start = *self
-            .seek_head
-            .get(&ElementId::Cluster)
-            .ok_or(DemuxError::CantFindCluster)?;
+        Ok(sample_index)
+    }
 
-        let target_offset = self.seek_broad_phase(seek_timestamp, cluster_start)?;
+    /// Seeks to the block of the given audio track that contains the given sample, for
+    /// random access with sample accuracy.
+    ///
+    /// The sample index is expressed in the output sample rate of the track and is converted
+    /// to a block with the help of the `SampleIndex` of the track, see `sample_index()`.
+    /// The returned `SampleSeek` reports how many decoded samples of the block must be
+    /// discarded. Returns `None` if the sample is outside of the track.
+    pub fn seek_sample(&mut self, track: u64, sample: u64) -> Result<Option<SampleSeek>> {
+        let seek = self
+            .sample_index(track)?
+            .find(sample)
+            .map(|entry| SampleSeek {
+                timestamp: entry.timestamp,
+                offset: entry.offset,
+                first_sample: entry.first_sample,
+                skip_samples: sample - entry.first_sample,
+            });
+        if let Some(seek) = &seek {
+            self.seek_to_offset(seek.offset)?;
+        }
+        Ok(seek)
+    }
 
-        self.file.seek(SeekFrom::Start(target_offset))?;
+    fn note_cluster(&mut self, element_data: &ElementData) {
+        if let ElementData::Location { offset, size } = element_data {
+            if *size == u64::MAX {
+                self.unknown_size_cluster = true;
+            }
+            self.parent_elements
+                .push((ElementId::Cluster, offset.saturating_add(*size)));
+        }
+    }
 
-        self.seek_narrow_phase(seek_timestamp)
+    /// Records the element that is read at the given position and leaves the master
+    /// elements that end before it.
+    fn enter_element(&mut self, position: u64, element_id: ElementId) {
+        self.current_element = Some((position, element_id));
+        while let Some(&(parent_id, end)) = self.parent_elements.last() {
+            // A cluster of unknown size ends at the first element that is not allowed in it.
+            let is_child = element_parents(element_id).map_or(true, |p| p.contains(&parent_id));
+            if position < end && is_child {
+                break;
+            }
+            self.parent_elements.pop();
+        }
     }
 
     fn enter_data_location(&mut self, element_data: &ElementData) -> Result<()> {
@@ -1615,148 +6168,250 @@ impl<R: Read + Seek> MatroskaFile<R> {
         }
     }
 
-    fn seek_broad_phase(&mut self, seek_timestamp: u64, cluster_start: u64) -> Result<u64> {
-        if let Some(cue_points) = self.cue_points.as_ref() {
+    /// Finds the position to start the narrow phase at. Returns the position, the position of
+    /// the cluster that contains it and if the `Cues` were used.
+    ///
+    /// If a track is given, only the cue points of this track are used.
+    fn seek_broad_phase(
+        &mut self,
+        seek_timestamp: u64,
+        track: Option<u64>,
+        cluster_start: u64,
+    ) -> Result<(u64, u64, bool)> {
+        let index = Arc::clone(&self.index);
+        if let Some(cue_points) = index.load_cue_points(&mut self.file)? {
             // Fast path if we have cue points.
-            let seek_pos = match cue_points.binary_search_by(|p| p.time.cmp(&seek_timestamp)) {
-                Ok(seek_pos) => seek_pos,
-                Err(seek_pos) => seek_pos.saturating_sub(1),
+            let point = match track {
+                Some(track) => {
+                    let track_cues = index.track_cues(cue_points, track);
+                    let seek_pos =
+                        match track_cues.binary_search_by(|c| c.time.cmp(&seek_timestamp)) {
+                            Ok(seek_pos) => seek_pos,
+                            Err(seek_pos) => seek_pos.saturating_sub(1),
+                        };
+                    track_cues.get(seek_pos).and_then(|c| {
+                        let point = cue_points.get(c.point)?;
+                        Some((c.time, point.track_positions.get(c.position)?))
+                    })
+                }
+                None => {
+                    let seek_pos =
+                        match cue_points.binary_search_by(|p| p.time.cmp(&seek_timestamp)) {
+                            Ok(seek_pos) => seek_pos,
+                            Err(seek_pos) => seek_pos.saturating_sub(1),
+                        };
+                    cue_points
+                        .get(seek_pos)
+                        .and_then(|p| Some((p.time, p.track_positions.first()?)))
+                }
             };
+            if let Some((time, track_position)) = point {
+                if time <= seek_timestamp {
+                    let mut target_offset = track_position.cluster_position;
 
-            if let Some(point) = cue_points.get(seek_pos) {
-                if point.time <= seek_timestamp {
-                    let mut target_offset = point.track_position.cluster_position;
-
-                    if let Some(relative_position) = point.track_position.relative_position {
+                    if let Some(relative_position) = track_position.relative_position {
                         let (cluster_data_offset, cluster_timestamp) =
-                            self.get_cluster_offset_and_timestamp(cluster_start)?;
+                            self.get_cluster_offset_and_timestamp(track_position.cluster_position)?;
                         self.cluster_timestamp = cluster_timestamp;
                         target_offset = cluster_data_offset + relative_position;
                     }
 
-                    return Ok(target_offset);
+                    if let Some(codec_state) = track_position.codec_state {
+                        let data = self.read_codec_state(codec_state)?;
+                        self.pending_codec_states.insert(track_position.track, data);
+                    }
+
+                    debug!(cue_time = time, target_offset, "seeking to cue point");
+
+                    return Ok((target_offset, track_position.cluster_position, true));
                 }
             }
         };
 
         // Linear search the clusters.
-        let mut last_cluster_offset = 0;
-        let mut current_cluster_offset = 0;
+        debug!("no usable cue point found, scanning the clusters linearly");
+        let mut last_cluster = (0, 0);
+        let mut current_cluster = (0, 0);
         let mut next_cluster_offset = 0;
 
         self.file.seek(SeekFrom::Start(cluster_start))?;
 
         loop {
-            match next_element(&mut self.file) {
-                Ok((element_id, element_data)) => match element_id {
-                    // We enter clusters.
-                    ElementId::Cluster => {
-                        if let ElementData::Location { offset, size } = element_data {
-                            // We can't do a broad phase search when having a live streaming file.
-                            if size == u64::MAX {
-                                return Ok(cluster_start);
-                            }
-                            self.file.seek(SeekFrom::Start(offset))?;
-                            last_cluster_offset = current_cluster_offset;
-                            current_cluster_offset = offset;
-                            next_cluster_offset = offset + size;
-                        } else {
-                            return Err(DemuxError::UnexpectedDataType);
+            let position = self.file.stream_position()?;
+            let (_, element_id, _, element_data) = match self.next_element_or_eof()? {
+                Some(element) => element,
+                // The frames after the timestamp can only be in the last cluster.
+                None => return Ok((current_cluster.0, current_cluster.1, false)),
+            };
+            match element_id {
+                // We enter clusters.
+                ElementId::Cluster => {
+                    if let ElementData::Location { offset, size } = element_data {
+                        // We can't do a broad phase search when having a live streaming file.
+                        if size == u64::MAX {
+                            debug!(offset, "cluster has an unknown size, can't skip clusters");
+                            return Ok((cluster_start, cluster_start, false));
                         }
+                        trace!(offset, size, "scanning cluster");
+                        self.file.seek(SeekFrom::Start(offset))?;
+                        self.report_progress()?;
+                        last_cluster = current_cluster;
+                        current_cluster = (offset, position);
+                        next_cluster_offset = offset + size;
+                    } else {
+                        return Err(DemuxError::UnexpectedDataType);
                     }
-                    // Check the timestamp and seek to the next cluster if we haven't overshoot yet.
-                    ElementId::Timestamp => {
-                        if let ElementData::Unsigned(timestamp) = element_data {
-                            match timestamp {
-                                t if t < seek_timestamp => {
-                                    self.file.seek(SeekFrom::Start(next_cluster_offset))?;
-                                }
-                                t if t > seek_timestamp => {
-                                    return Ok(last_cluster_offset);
-                                }
-                                _ => {
-                                    return Ok(current_cluster_offset);
-                                }
+                }
+                // Check the timestamp and seek to the next cluster if we haven't overshoot yet.
+                ElementId::Timestamp => {
+                    if let ElementData::Unsigned(timestamp) = element_data {
+                        match timestamp {
+                            t if t < seek_timestamp => {
+                                self.file.seek(SeekFrom::Start(next_cluster_offset))?;
+                            }
+                            t if t > seek_timestamp => {
+                                return Ok((last_cluster.0, last_cluster.1, false));
+                            }
+                            _ => {
+                                return Ok((current_cluster.0, current_cluster.1, false));
                             }
-                        } else {
-                            return Err(DemuxError::UnexpectedDataType);
-                        }
-                    }
-                    _ => { /* We ignore all other elements */ }
-                },
-                // If we encounter an IO error, we assume that there
-                // are no more blocks to handle (EOF).
-                Err(err) => {
-                    if let Some(err) = err.source() {
-                        if err.downcast_ref::<std::io::Error>().is_some() {
-                            return Ok(next_cluster_offset);
                         }
+                    } else {
+                        return Err(DemuxError::UnexpectedDataType);
                     }
-                    return Err(err);
                 }
+                _ => { /* We ignore all other elements */ }
             }
         }
     }
 
-    fn seek_narrow_phase(&mut self, seek_timestamp: u64) -> Result<()> {
+    /// Positions the demuxer at the first block at or after the given timestamp. Returns the
+    /// timestamp of the block and the position of its cluster.
+    fn seek_narrow_phase(
+        &mut self,
+        seek_timestamp: u64,
+        mut cluster_offset: u64,
+    ) -> Result<Option<(u64, u64)>> {
         loop {
             let position = self.file.stream_position()?;
-            match next_element(&mut self.file) {
-                Ok((element_id, element_data)) => match element_id {
-                    // We enter cluster and block groups.
-                    ElementId::Cluster | ElementId::BlockGroup => {
-                        self.enter_data_location(&element_data)?;
+            let (_, element_id, _, element_data) = match self.next_element_or_eof()? {
+                Some(element) => element,
+                None => return Ok(None),
+            };
+            match element_id {
+                // We enter clusters.
+                ElementId::Cluster => {
+                    cluster_offset = position;
+                    self.enter_data_location(&element_data)?;
+                    self.silent_tracks.clear();
+                }
+                ElementId::SilentTracks => {
+                    self.read_silent_tracks(&element_data)?;
+                }
+                // Update the current cluster timestamp.
+                ElementId::Timestamp => {
+                    if let ElementData::Unsigned(timestamp) = element_data {
+                        self.cluster_timestamp = timestamp;
+                    } else {
+                        return Err(DemuxError::UnexpectedDataType);
                     }
-                    // Update the current cluster timestamp.
-                    ElementId::Timestamp => {
-                        if let ElementData::Unsigned(timestamp) = element_data {
-                            self.cluster_timestamp = timestamp;
-                        } else {
-                            return Err(DemuxError::UnexpectedDataType);
+                }
+                // Parse the block data.
+                ElementId::SimpleBlock => {
+                    if let ElementData::Location { offset, size } = element_data {
+                        self.file.seek(SeekFrom::Start(offset))?;
+                        let timestamp =
+                            probe_block_timestamp(&mut self.file, self.cluster_timestamp)?;
+                        if timestamp >= seek_timestamp {
+                            // We found the first element after the seeked timestamp.
+                            debug!(timestamp, position, "found the first block to read");
+                            self.file.seek(SeekFrom::Start(position))?;
+                            return Ok(Some((timestamp, cluster_offset)));
                         }
+                        // Jump to the next element.
+                        self.file.seek(SeekFrom::Start(offset + size))?;
+                    } else {
+                        return Err(DemuxError::UnexpectedDataType);
                     }
-                    // Parse the block data.
-                    ElementId::SimpleBlock | ElementId::Block => {
-                        if let ElementData::Location { offset, size } = element_data {
-                            self.file.seek(SeekFrom::Start(offset))?;
-                            let timestamp =
-                                probe_block_timestamp(&mut self.file, self.cluster_timestamp)?;
-
-                            match timestamp {
-                                t if t < seek_timestamp => {
-                                    // Jump to the next element.
-                                    self.file.seek(SeekFrom::Start(offset + size))?;
-                                }
-                                _ => {
-                                    // We found the first element after the seeked timestamp.
+                }
+                // The children of a block group can be stored in any order, so the codec state
+                // may precede its block.
+                ElementId::BlockGroup => {
+                    if let ElementData::Location { offset, size } = element_data {
+                        let fields = collect_children(
+                            &mut self.file,
+                            offset,
+                            size,
+                            self.index.header_limits(),
+                        )?;
+                        match fields.iter().find(|(id, _)| *id == ElementId::Block) {
+                            Some((_, ElementData::Location { offset, .. })) => {
+                                self.file.seek(SeekFrom::Start(*offset))?;
+                                let track = probe_block_track(&mut self.file)?;
+                                self.file.seek(SeekFrom::Start(*offset))?;
+                                let timestamp =
+                                    probe_block_timestamp(&mut self.file, self.cluster_timestamp)?;
+                                if timestamp >= seek_timestamp {
+                                    debug!(timestamp, position, "found the first block to read");
                                     self.file.seek(SeekFrom::Start(position))?;
-                                    return Ok(());
+                                    return Ok(Some((timestamp, cluster_offset)));
+                                }
+                                // Remember the codec states of skipped block groups for the
+                                // decoder.
+                                let codec_state = try_find_binary(
+                                    &mut self.file,
+                                    &fields,
+                                    ElementId::CodecState,
+                                )?;
+                                if let Some(codec_state) = codec_state {
+                                    self.pending_codec_states.insert(track, codec_state);
                                 }
                             }
-                        } else {
-                            return Err(DemuxError::UnexpectedDataType);
-                        }
-                    }
-                    _ => { /* We ignore all other elements */ }
-                },
-                // If we encounter an IO error, we assume that there
-                // are no more blocks to handle (EOF).
-                Err(err) => {
-                    if let Some(err) = err.source() {
-                        if err.downcast_ref::<std::io::Error>().is_some() {
-                            return Ok(());
+                            Some(_) => return Err(DemuxError::UnexpectedDataType),
+                            None => {
+                                debug!(offset, "skipping BlockGroup without Block");
+                            }
                         }
+                        self.file.seek(SeekFrom::Start(offset + size))?;
+                    } else {
+                        return Err(DemuxError::UnexpectedDataType);
                     }
-                    return Err(err);
                 }
+                _ => { /* We ignore all other elements */ }
             }
         }
     }
 
+    /// Reads the codec state at the given position, which is either a `CodecState`
+    /// or a `BlockGroup` that contains one.
+    fn read_codec_state(&mut self, position: u64) -> Result<Vec<u8>> {
+        let (element_id, size) =
+            parse_element_header(&mut self.file, Some(position), self.index.header_limits())?;
+        let offset = self.file.stream_position()?;
+        let fields = match element_id {
+            ElementId::CodecState => vec![(element_id, ElementData::Location { offset, size })],
+            ElementId::BlockGroup => {
+                collect_children(&mut self.file, offset, size, self.index.header_limits())?
+            }
+            _ => {
+                return Err(DemuxError::UnexpectedElement((
+                    ElementId::CodecState,
+                    element_id,
+                )))
+            }
+        };
+        find_binary(&mut self.file, &fields, ElementId::CodecState)
+    }
+
     fn get_cluster_offset_and_timestamp(&mut self, cluster_start: u64) -> Result<(u64, u64)> {
-        let (offset, _) = expect_master(&mut self.file, ElementId::Cluster, Some(cluster_start))?;
+        let (offset, _) = expect_master(
+            &mut self.file,
+            ElementId::Cluster,
+            Some(cluster_start),
+            self.index.header_limits(),
+        )?;
         loop {
-            match next_element(&mut self.file) {
+            match next_element(&mut self.file, self.index.header_limits()) {
                 Ok((element_id, element_data)) => match element_id {
                     // Check the timestamp and seek to the next cluster if we haven't overshoot yet.
                     ElementId::Timestamp => {
@@ -1782,32 +6437,108 @@ impl<R: Read + Seek> MatroskaFile<R> {
     }
 }
 
+impl<'a> MatroskaFile<Cursor<&'a [u8]>> {
+    /// Opens a Matroska file that is completely held in memory.
+    ///
+    /// This is a shorthand for `MatroskaFile::open(Cursor::new(data))`. The data is still
+    /// read through the `Read` and `Seek` implementation of the `Cursor`, so frame data is
+    /// copied into the buffer of the frame like with any other reader.
+    pub fn from_slice(data: &'a [u8]) -> Result<Self> {
+        Self::open(Cursor::new(data))
+    }
+}
+
+impl MatroskaFile<Cursor<Vec<u8>>> {
+    /// Opens a Matroska file from an owned buffer that holds the whole file.
+    ///
+    /// This is a shorthand for `MatroskaFile::open(Cursor::new(data))`.
+    ///
+    /// Useful when the file data is only available as a buffer that can't be borrowed
+    /// for the lifetime of the demuxer, for example when it was received from a network
+    /// request or from JavaScript on WASM targets.
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self> {
+        Self::open(Cursor::new(data))
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl MatroskaFile<Cursor<memmap2::Mmap>> {
+    /// Opens a Matroska file from a memory mapped file.
+    ///
+    /// The map is read through a `Cursor`, so seeking doesn't need any system calls,
+    /// but frame data is copied out of the map like with any other reader.
+    ///
+    /// Creating the memory map is unsafe, since the file could be modified by other
+    /// processes while it is mapped, so the map needs to be created by the caller:
+    ///
+    /// ```no_run
+    /// use std::fs::File;
+    /// use matroska_demuxer::{memmap2::Mmap, MatroskaFile};
+    ///
+    /// let file = File::open("test.mkv").unwrap();
+    /// let mmap = unsafe { Mmap::map(&file).unwrap() };
+    /// let mut mkv = MatroskaFile::from_mmap(mmap).unwrap();
+    /// ```
+    pub fn from_mmap(mmap: memmap2::Mmap) -> Result<Self> {
+        Self::open(Cursor::new(mmap))
+    }
+}
+
 /// Parses and verifies the EBML header.
 fn parse_ebml_header<R: Read + Seek>(r: &mut R) -> Result<EbmlHeader> {
-    let (master_offset, master_size) = expect_master(r, ElementId::Ebml, None)?;
-    let master_children = collect_children(r, master_offset, master_size)?;
-    let header = EbmlHeader::new(r, &master_children)?;
+    let limits = HeaderLimits::default();
+    let (master_offset, master_size) = expect_master(r, ElementId::Ebml, None, limits)?;
+    let master_children = collect_children(r, master_offset, master_size, limits)?;
+    let header = EbmlHeader::new(r, &master_children, ParseOptions::default())?;
     Ok(header)
 }
 
-/// Parses the seek head if present.
+/// The top level elements whose duplicates are merged.
+const MERGED_ELEMENTS: [ElementId; 3] =
+    [ElementId::Chapters, ElementId::Tags, ElementId::Attachments];
+
+/// Parses the seek head if present. Further entries of the `MERGED_ELEMENTS` are added to
+/// the duplicate elements.
 fn parse_seek_head<R: Read + Seek>(
     mut file: &mut R,
     segment_data_offset: u64,
     optional_seek_head: Option<(u64, u64)>,
+    duplicate_elements: &mut HashMap<ElementId, Vec<u64>>,
+    limits: HeaderLimits,
 ) -> Result<HashMap<ElementId, u64>> {
     let mut seek_head = HashMap::new();
 
     if let Some((seek_head_data_offset, seek_head_data_size)) = optional_seek_head {
-        let seek_head_entries =
-            collect_children(&mut file, seek_head_data_offset, seek_head_data_size)?;
+        let seek_head_entries = collect_children(
+            &mut file,
+            seek_head_data_offset,
+            seek_head_data_size,
+            limits,
+        )?;
 
         for (entry_id, entry_data) in &seek_head_entries {
             if let ElementId::Seek = entry_id {
                 if let ElementData::Location { offset, size } = entry_data {
-                    let seek_fields = collect_children(&mut file, *offset, *size)?;
-                    if let Ok(seek_entry) = SeekEntry::new(&mut file, &seek_fields) {
-                        seek_head.insert(seek_entry.id, segment_data_offset + seek_entry.offset);
+                    let seek_fields = collect_children(&mut file, *offset, *size, limits)?;
+                    let options = ParseOptions {
+                        lossy_strings: false,
+                        limits,
+                    };
+                    match SeekEntry::new(&mut file, &seek_fields, options) {
+                        Ok(seek_entry) => {
+                            let offset = segment_data_offset + seek_entry.offset;
+                            if let Some(previous) = seek_head.insert(seek_entry.id, offset) {
+                                if MERGED_ELEMENTS.contains(&seek_entry.id) {
+                                    duplicate_elements
+                                        .entry(seek_entry.id)
+                                        .or_default()
+                                        .push(previous);
+                                }
+                            }
+                        }
+                        Err(_err) => {
+                            warn!(offset, error = %_err, "ignoring invalid Seek entry");
+                        }
                     }
                 }
             }
@@ -1817,15 +6548,95 @@ fn parse_seek_head<R: Read + Seek>(
     Ok(seek_head)
 }
 
+/// Checks that the entries of the SeekHead point at the elements they declare, following
+/// the given policy. Returns the repaired entries.
+fn verify_seek_head<R: Read + Seek>(
+    r: &mut R,
+    segment_data_offset: u64,
+    seek_head: &mut HashMap<ElementId, u64>,
+    duplicate_elements: &mut HashMap<ElementId, Vec<u64>>,
+    policy: SeekHeadPolicy,
+) -> Result<Vec<SeekHeadMismatch>> {
+    if policy == SeekHeadPolicy::Trust {
+        return Ok(Vec::new());
+    }
+
+    // Duplicates are not reported as mismatches, since they have no replacement.
+    for (element_id, offsets) in duplicate_elements.iter_mut() {
+        let mut valid = Vec::with_capacity(offsets.len());
+        for offset in offsets.iter().copied() {
+            let found = parse_element_header(r, Some(offset), HeaderLimits::default())
+                .ok()
+                .map(|(found, _)| found);
+            if found == Some(*element_id) {
+                valid.push(offset);
+            } else if policy == SeekHeadPolicy::Verify {
+                return Err(DemuxError::InvalidSeekEntry(*element_id, offset));
+            } else {
+                warn!(
+                    ?element_id,
+                    offset,
+                    ?found,
+                    "ignoring a wrong SeekHead entry"
+                );
+            }
+        }
+        *offsets = valid;
+    }
+
+    let mut entries: Vec<(ElementId, u64)> = seek_head
+        .iter()
+        .filter(|(element_id, _)| **element_id != ElementId::Unknown)
+        .map(|(element_id, offset)| (*element_id, *offset))
+        .collect();
+    entries.sort_unstable_by_key(|(_, offset)| *offset);
+
+    let mut mismatches = Vec::new();
+    for (element_id, offset) in entries {
+        let found = parse_element_header(r, Some(offset), HeaderLimits::default())
+            .ok()
+            .map(|(found, _)| found);
+        if found == Some(element_id) {
+            continue;
+        }
+        if policy == SeekHeadPolicy::Verify {
+            return Err(DemuxError::InvalidSeekEntry(element_id, offset));
+        }
+        warn!(?element_id, offset, ?found, "found a wrong SeekHead entry");
+        mismatches.push(SeekHeadMismatch {
+            element_id,
+            offset,
+            found,
+            repaired_offset: None,
+        });
+    }
+
+    if !mismatches.is_empty() {
+        debug!("repairing the SeekHead from the top level elements");
+        let mut scanned = HashMap::new();
+        build_seek_head(r, segment_data_offset, &mut scanned, duplicate_elements)?;
+        for mismatch in mismatches.iter_mut() {
+            mismatch.repaired_offset = scanned.get(&mismatch.element_id).copied();
+            match mismatch.repaired_offset {
+                Some(offset) => seek_head.insert(mismatch.element_id, offset),
+                None => seek_head.remove(&mismatch.element_id),
+            };
+        }
+    }
+
+    Ok(mismatches)
+}
+
 /// Seeks the SeekHead element and returns the offset into it when present.
 ///
 /// The specification states that the first non CRC-32 element should be a SeekHead if present.
 fn search_seek_head<R: Read + Seek>(
     r: &mut R,
     segment_data_offset: u64,
+    limits: HeaderLimits,
 ) -> Result<Option<(u64, u64)>> {
     loop {
-        let (element_id, size) = parse_element_header(r, Some(segment_data_offset))?;
+        let (element_id, size) = parse_element_header(r, Some(segment_data_offset), limits)?;
         match element_id {
             ElementId::SeekHead => {
                 let current_pos = r.stream_position()?;
@@ -1837,34 +6648,50 @@ fn search_seek_head<R: Read + Seek>(
     }
 }
 
-/// Build a SeekHead by parsing the top level entries.
+/// Build a SeekHead by parsing the top level entries. Further elements of the
+/// `MERGED_ELEMENTS` are added to the duplicate elements. The elements are only located,
+/// the header limits are enforced once they are parsed.
 fn build_seek_head<R: Read + Seek>(
     r: &mut R,
     segment_data_offset: u64,
     seek_head: &mut HashMap<ElementId, u64>,
+    duplicate_elements: &mut HashMap<ElementId, Vec<u64>>,
 ) -> Result<()> {
     r.seek(SeekFrom::Start(segment_data_offset))?;
     loop {
         let position = r.stream_position()?;
-        match next_element(r) {
+        match next_element(r, HeaderLimits::default()) {
             Ok((element_id, _)) => {
                 if element_id == ElementId::Info
                     || element_id == ElementId::Tracks
                     || element_id == ElementId::Chapters
                     || element_id == ElementId::Cues
+                    || element_id == ElementId::Attachments
                     || element_id == ElementId::Tags
                     || element_id == ElementId::Cluster
                 {
-                    // We only need the first cluster entry.
-                    if element_id != ElementId::Cluster
-                        || !seek_head.contains_key(&ElementId::Cluster)
-                    {
-                        seek_head.insert(element_id, position);
+                    match seek_head.entry(element_id) {
+                        Entry::Vacant(entry) => {
+                            entry.insert(position);
+                        }
+                        Entry::Occupied(_) if MERGED_ELEMENTS.contains(&element_id) => {
+                            duplicate_elements
+                                .entry(element_id)
+                                .or_default()
+                                .push(position);
+                        }
+                        // We only need the first cluster entry, other elements use the last.
+                        Entry::Occupied(mut entry) => {
+                            if element_id != ElementId::Cluster {
+                                entry.insert(position);
+                            }
+                        }
                     }
                 }
             }
-            Err(_) => {
+            Err(_err) => {
                 // EOF or damaged file. We will stop looking for top level entries.
+                debug!(position, error = %_err, "stopped building the SeekHead");
                 break;
             }
         }
@@ -1873,13 +6700,15 @@ fn build_seek_head<R: Read + Seek>(
     Ok(())
 }
 
-/// Tries to find the offset of the first cluster and save it in the SeekHead.
+/// Tries to find the offset of the first cluster and save it in the SeekHead. Headers that
+/// exceed the limits of strict mode are rejected once the cluster is demuxed.
 fn find_first_cluster_offset<R: Read + Seek>(
     r: &mut R,
     seek_head: &mut HashMap<ElementId, u64>,
 ) -> Result<()> {
+    let limits = HeaderLimits::default();
     let (tracks_offset, tracks_size) = if let Some(offset) = seek_head.get(&ElementId::Tracks) {
-        expect_master(r, ElementId::Tracks, Some(*offset))?
+        expect_master(r, ElementId::Tracks, Some(*offset), limits)?
     } else {
         return Err(DemuxError::CantFindCluster);
     };
@@ -1888,7 +6717,7 @@ fn find_first_cluster_offset<R: Read + Seek>(
     loop {
         let position = r.stream_position()?;
 
-        match next_element(r) {
+        match next_element(r, limits) {
             Ok((element_id, element_data)) => {
                 if let ElementId::Cluster = element_id {
                     if let ElementData::Location { .. } = element_data {
@@ -1919,29 +6748,127 @@ fn find_first_cluster_offset<R: Read + Seek>(
 fn parse_segment_info<R: Read + Seek>(
     r: &mut R,
     seek_head: &HashMap<ElementId, u64>,
+    lenient: bool,
+    limits: HeaderLimits,
+    warnings: &mut Vec<ValidationIssue>,
 ) -> Result<Info> {
     if let Some(offset) = seek_head.get(&ElementId::Info) {
-        let (info_data_offset, info_data_size) = expect_master(r, ElementId::Info, Some(*offset))?;
-        let child_fields = collect_children(r, info_data_offset, info_data_size)?;
-        let info = Info::new(r, &child_fields)?;
+        let (info_data_offset, info_data_size) =
+            expect_master(r, ElementId::Info, Some(*offset), limits)?;
+        let mut child_fields = collect_children(r, info_data_offset, info_data_size, limits)?;
+
+        if lenient {
+            // Some broken encoders omit the mandatory application names.
+            for element_id in [ElementId::MuxingApp, ElementId::WritingApp] {
+                if !child_fields.iter().any(|(id, _)| *id == element_id) {
+                    warn!(?element_id, "substituting a missing mandatory string");
+                    child_fields.push((element_id, ElementData::String(String::new())));
+                    warnings.push(ValidationIssue {
+                        severity: Severity::Warning,
+                        kind: ValidationIssueKind::MissingElement,
+                        element: element_id,
+                        offset: *offset,
+                        message: format!("Info has no {:?} element", element_id),
+                    });
+                }
+            }
+        }
+
+        check_strings(&child_fields, lenient)?;
+        let options = ParseOptions {
+            lossy_strings: lenient,
+            limits,
+        };
+        let info = Info::new(r, &child_fields, options)?;
         Ok(info)
     } else {
         Err(DemuxError::ElementNotFound(ElementId::Info))
     }
 }
 
+/// Parses the track entries. In lenient mode, tracks that can't be parsed are skipped and
+/// their errors are returned.
+fn parse_tracks<R: Read + Seek>(
+    r: &mut R,
+    seek_head: &HashMap<ElementId, u64>,
+    lenient: bool,
+    limits: HeaderLimits,
+) -> Result<(Vec<TrackEntry>, Vec<TrackError>)> {
+    let offset = *seek_head
+        .get(&ElementId::Tracks)
+        .ok_or(DemuxError::ElementNotFound(ElementId::Tracks))?;
+    let options = ParseOptions {
+        lossy_strings: lenient,
+        limits,
+    };
+
+    if !lenient {
+        let tracks = parse_children_at_offset::<_, TrackEntry>(
+            r,
+            offset,
+            ElementId::Tracks,
+            ElementId::TrackEntry,
+            options,
+        )?;
+        return Ok((tracks, Vec::new()));
+    }
+
+    let (data_offset, data_size) = expect_master(r, ElementId::Tracks, Some(offset), limits)?;
+    let fields = collect_children(r, data_offset, data_size, limits)?;
+
+    let mut tracks = vec![];
+    let mut track_errors = vec![];
+    for (_, data) in fields.iter().filter(|(id, _)| *id == ElementId::TrackEntry) {
+        let (offset, size) = match data {
+            ElementData::Location { offset, size } => (*offset, *size),
+            _ => return Err(DemuxError::UnexpectedDataType),
+        };
+
+        let track_fields = match collect_children(r, offset, size, limits) {
+            Ok(track_fields) => track_fields,
+            Err(error) => {
+                warn!(offset, %error, "skipping broken track");
+                track_errors.push(TrackError {
+                    offset,
+                    track_number: None,
+                    error,
+                });
+                continue;
+            }
+        };
+
+        match <TrackEntry as ParsableElement<R>>::new(r, &track_fields, options) {
+            Ok(track) => tracks.push(track),
+            Err(error) => {
+                warn!(offset, %error, "skipping broken track");
+                track_errors.push(TrackError {
+                    offset,
+                    track_number: try_find_unsigned(&track_fields, ElementId::TrackNumber)
+                        .ok()
+                        .flatten(),
+                    error,
+                });
+            }
+        }
+    }
+
+    Ok((tracks, track_errors))
+}
+
 fn try_parse_top_element_collection<R, T>(
     r: &mut R,
     seek_head: &HashMap<ElementId, u64>,
     master_id: ElementId,
     child_id: ElementId,
+    options: ParseOptions,
 ) -> Result<Option<Vec<T::Output>>>
 where
     R: Read + Seek,
     T: ParsableElement<R>,
 {
     let cue_points = if let Some(offset) = seek_head.get(&master_id) {
-        let cue_points = parse_children_at_offset::<_, T>(r, *offset, master_id, child_id)?;
+        let cue_points =
+            parse_children_at_offset::<_, T>(r, *offset, master_id, child_id, options)?;
         Some(cue_points)
     } else {
         None
@@ -1953,6 +6880,7 @@ fn find_children_in_fields<R, T>(
     r: &mut R,
     fields: &[(ElementId, ElementData)],
     child_id: ElementId,
+    options: ParseOptions,
 ) -> Result<Vec<T::Output>>
 where
     R: Read + Seek,
@@ -1961,8 +6889,9 @@ where
     let mut children = vec![];
     for (_, data) in fields.iter().filter(|(id, _)| *id == child_id) {
         if let ElementData::Location { offset, size } = data {
-            let child_fields = collect_children(r, *offset, *size)?;
-            let child = T::new(r, &child_fields)?;
+            let child_fields = collect_children(r, *offset, *size, options.limits)?;
+            check_strings(&child_fields, options.lossy_strings)?;
+            let child = T::new(r, &child_fields, options)?;
             children.push(child);
         } else {
             return Err(DemuxError::UnexpectedDataType);
@@ -1983,12 +6912,125 @@ fn seek_to_first_cluster<R: Read + Seek>(
     }
 }
 
+/// Returns an error if the element is not allowed inside the given parent.
+fn check_placement(element_id: ElementId, parent: ElementId, position: u64) -> Result<()> {
+    match element_parents(element_id) {
+        Some(parents) if !parents.contains(&parent) => Err(DemuxError::MisplacedElement {
+            element: element_id,
+            parent,
+            offset: position,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that the element at the given position and all of its children are allowed inside
+/// their parents. Returns the end of the element or `u64::MAX` if its size is unknown.
+fn check_element_placement<R: Read + Seek>(
+    r: &mut R,
+    parent: ElementId,
+    position: u64,
+    limits: HeaderLimits,
+) -> Result<u64> {
+    check_nested_element_placement(r, parent, position, limits, 0)
+}
+
+fn check_nested_element_placement<R: Read + Seek>(
+    r: &mut R,
+    parent: ElementId,
+    position: u64,
+    limits: HeaderLimits,
+    depth: usize,
+) -> Result<u64> {
+    let (element_id, size) = parse_element_header(r, Some(position), limits)?;
+    check_placement(element_id, parent, position)?;
+    if size == u64::MAX {
+        return Ok(u64::MAX);
+    }
+
+    let offset = r.stream_position()?;
+    let end = offset.saturating_add(size);
+    if element_id_to_type(element_id) == ElementType::Master {
+        if depth >= MAX_DEPTH {
+            return Err(DemuxError::NestingTooDeep(element_id));
+        }
+        let mut child = offset;
+        while child < end {
+            child = check_nested_element_placement(r, element_id, child, limits, depth + 1)?;
+        }
+    }
+
+    Ok(end)
+}
+
+/// Converts unsigned ticks into nanoseconds, saturating on overflow.
+fn ticks_to_nanos(ticks: u64, scale: NonZeroU64) -> u64 {
+    ticks.saturating_mul(scale.get())
+}
+
+/// Converts signed ticks into nanoseconds, saturating on overflow.
+fn signed_ticks_to_nanos(ticks: i64, scale: NonZeroU64) -> i64 {
+    ticks.saturating_mul(i64::try_from(scale.get()).unwrap_or(i64::MAX))
+}
+
+/// Expresses the timestamps and the duration of the frame in nanoseconds.
+fn frame_to_nanos(frame: &mut Frame) {
+    let scale = frame.timestamp_scale;
+    frame.timestamp = ticks_to_nanos(frame.timestamp, scale);
+    frame.signed_timestamp = signed_ticks_to_nanos(frame.signed_timestamp, scale);
+    frame.cluster_timestamp = ticks_to_nanos(frame.cluster_timestamp, scale);
+    frame.presentation_timestamp = signed_ticks_to_nanos(frame.presentation_timestamp, scale);
+    frame.duration = frame.duration.map(|d| ticks_to_nanos(d, scale));
+    frame.timestamp_scale = NonZeroU64::MIN;
+}
+
+/// Creates a timestamp from unsigned ticks, saturating ticks that don't fit into an `i64`.
+fn ticks_to_timestamp(ticks: u64, scale: NonZeroU64) -> Timestamp {
+    Timestamp::new(i64::try_from(ticks).unwrap_or(i64::MAX), scale)
+}
+
+/// Reaching the end of the file while reading an element means that the file is truncated.
+fn map_unexpected_eof(err: DemuxError) -> DemuxError {
+    match err {
+        DemuxError::IoError(err) if err.kind() == ErrorKind::UnexpectedEof => {
+            warn!("the file ends inside an element");
+            DemuxError::UnexpectedEof
+        }
+        err => err,
+    }
+}
+
+// The demuxer must stay movable between threads and the metadata shareable.
+const _: () = {
+    const fn assert_send<T: Send>() {}
+    const fn assert_send_sync<T: Send + Sync>() {}
+
+    const fn assert_demuxer<R: Read + Seek + Send>() {
+        assert_send::<MatroskaFile<R>>();
+        assert_send::<MatroskaReader<R>>();
+    }
+
+    assert_demuxer::<std::fs::File>();
+    assert_send_sync::<MatroskaIndex>();
+    assert_send_sync::<ParserSettings>();
+    assert_send_sync::<EbmlHeader>();
+    assert_send_sync::<Info>();
+    assert_send_sync::<TrackEntry>();
+    assert_send_sync::<EditionEntry>();
+    assert_send_sync::<Tag>();
+    assert_send_sync::<AttachedFile>();
+    assert_send_sync::<CuePoint>();
+    assert_send_sync::<Frame>();
+    assert_send_sync::<FramePool>();
+    assert_send_sync::<PooledFrame>();
+    assert_send_sync::<DemuxEvent>();
+    assert_send_sync::<DemuxError>();
+};
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::panic)]
 
-    use std::io::Cursor;
-
     use super::*;
 
     #[test]
@@ -2010,4 +7052,185 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_parse_chapter_process() -> Result<()> {
+        let data: Vec<u8> = vec![
+            0xB6, 0xA1, 0x73, 0xC4, 0x81, 0x01, 0x91, 0x81, 0x00, 0x69, 0x44, 0x97, 0x69, 0x55,
+            0x81, 0x01, 0x45, 0x0D, 0x82, 0x01, 0x02, 0x69, 0x11, 0x8B, 0x69, 0x22, 0x81, 0x01,
+            0x69, 0x33, 0x84, 0x30, 0x02, 0x00, 0x01,
+        ];
+        let mut cursor = Cursor::new(data);
+        let (offset, size) = expect_master(
+            &mut cursor,
+            ElementId::ChapterAtom,
+            None,
+            HeaderLimits::default(),
+        )?;
+        let fields = collect_children(&mut cursor, offset, size, HeaderLimits::default())?;
+        let atom = ChapterAtom::new(&mut cursor, &fields, ParseOptions::default())?;
+
+        let process = &atom.processes()[0];
+        assert_eq!(process.codec_id(), TranslateCodec::DvdMenu);
+        assert_eq!(process.private(), Some([0x01, 0x02].as_ref()));
+        assert_eq!(process.commands()[0].time(), ChapterProcessTime::Before);
+        assert_eq!(process.commands()[0].data(), [0x30, 0x02, 0x00, 0x01]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_nested_chapter_atoms() -> Result<()> {
+        let data: Vec<u8> = vec![
+            0xB6, 0x99, 0x73, 0xC4, 0x81, 0x01, 0x91, 0x81, 0x00, 0xB6, 0x90, 0x73, 0xC4, 0x81,
+            0x02, 0x91, 0x81, 0x05, 0xB6, 0x87, 0x73, 0xC4, 0x81, 0x03, 0x91, 0x81, 0x06,
+        ];
+        let mut cursor = Cursor::new(data);
+        let (offset, size) = expect_master(
+            &mut cursor,
+            ElementId::ChapterAtom,
+            None,
+            HeaderLimits::default(),
+        )?;
+        let fields = collect_children(&mut cursor, offset, size, HeaderLimits::default())?;
+        let atom = ChapterAtom::new(&mut cursor, &fields, ParseOptions::default())?;
+
+        assert_eq!(atom.uid().get(), 1);
+        assert_eq!(atom.children().len(), 1);
+        let child = &atom.children()[0];
+        assert_eq!(child.uid().get(), 2);
+        assert_eq!(child.time_start(), 5);
+        assert_eq!(child.children().len(), 1);
+        assert_eq!(child.children()[0].uid().get(), 3);
+        assert_eq!(child.children()[0].time_start(), 6);
+        assert!(child.children()[0].children().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_chapter_atoms_nesting_depth() -> Result<()> {
+        // Nested ChapterAtom elements with eight byte data sizes.
+        let nested = |depth: usize| {
+            let mut data = Vec::new();
+            for _ in 0..depth {
+                let mut atom = vec![0xB6];
+                let size = u64::try_from(data.len() + 7).unwrap_or_default();
+                atom.extend_from_slice(&(size | 0x0100_0000_0000_0000).to_be_bytes());
+                atom.extend_from_slice(&[0x73, 0xC4, 0x81, 0x01, 0x91, 0x81, 0x00]);
+                atom.append(&mut data);
+                data = atom;
+            }
+            Cursor::new(data)
+        };
+        let parse = |mut cursor: Cursor<Vec<u8>>| -> Result<ChapterAtom> {
+            let (offset, size) = expect_master(
+                &mut cursor,
+                ElementId::ChapterAtom,
+                None,
+                HeaderLimits::default(),
+            )?;
+            let fields = collect_children(&mut cursor, offset, size, HeaderLimits::default())?;
+            ChapterAtom::new(&mut cursor, &fields, ParseOptions::default())
+        };
+
+        parse(nested(MAX_DEPTH))?;
+        assert!(matches!(
+            parse(nested(MAX_DEPTH + 1)),
+            Err(DemuxError::NestingTooDeep(ElementId::ChapterAtom))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_element_placement_nesting_depth() -> Result<()> {
+        // Nested ChapterAtom elements with eight byte data sizes.
+        let nested = |depth: u64| {
+            let mut data = Vec::new();
+            for children in (0..depth).rev() {
+                data.push(0xB6);
+                data.extend_from_slice(&((children * 9) | 0x0100_0000_0000_0000).to_be_bytes());
+            }
+            Cursor::new(data)
+        };
+
+        let max_depth = u64::try_from(MAX_DEPTH)?;
+        check_element_placement(
+            &mut nested(max_depth),
+            ElementId::EditionEntry,
+            0,
+            HeaderLimits::default(),
+        )?;
+        assert!(matches!(
+            check_element_placement(
+                &mut nested(max_depth + 1),
+                ElementId::EditionEntry,
+                0,
+                HeaderLimits::default()
+            ),
+            Err(DemuxError::NestingTooDeep(ElementId::ChapterAtom))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_edition_display() -> Result<()> {
+        let data: Vec<u8> = vec![
+            0x45, 0xB9, 0x97, 0x45, 0x20, 0x94, 0x45, 0x21, 0x87, 0x43, 0x75, 0x74, 0x20, 0x44,
+            0x69, 0x72, 0x45, 0xE4, 0x82, 0x65, 0x6E, 0x45, 0xE4, 0x82, 0x64, 0x65,
+        ];
+        let mut cursor = Cursor::new(data);
+        let (offset, size) = expect_master(
+            &mut cursor,
+            ElementId::EditionEntry,
+            None,
+            HeaderLimits::default(),
+        )?;
+        let fields = collect_children(&mut cursor, offset, size, HeaderLimits::default())?;
+        let edition = EditionEntry::new(&mut cursor, &fields, ParseOptions::default())?;
+
+        assert_eq!(edition.displays().len(), 1);
+        assert_eq!(edition.displays()[0].string(), "Cut Dir");
+        assert_eq!(edition.displays()[0].languages_ietf(), ["en", "de"]);
+        assert!(edition.chapter_atoms().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_info_date_utc() {
+        let mut info = Info {
+            timestamp_scale: NonZeroU64::MIN,
+            duration: None,
+            date_utc: Some(304068183000000000),
+            title: None,
+            invalid_title: None,
+            muxing_app: String::new(),
+            writing_app: String::new(),
+            chapter_translates: vec![],
+        };
+        assert_eq!(
+            info.date_utc_as_system_time(),
+            Some(UNIX_EPOCH + Duration::from_secs(1282375383))
+        );
+        #[cfg(feature = "chrono")]
+        assert_eq!(
+            info.date_utc_as_date_time().map(|d| d.timestamp()),
+            Some(1282375383)
+        );
+
+        info.date_utc = Some(-1);
+        assert_eq!(
+            info.date_utc_as_system_time(),
+            Some(UNIX_EPOCH + Duration::from_nanos(978307199999999999))
+        );
+        #[cfg(feature = "chrono")]
+        assert_eq!(
+            info.date_utc_as_date_time()
+                .map(|d| d.timestamp_nanos_opt()),
+            Some(Some(978307199999999999))
+        );
+    }
 }
@@ -0,0 +1,226 @@
+//! Decompression of frames that are compressed with LZO1X.
+
+use crate::{compression::check_limit, DemuxError, Result};
+
+/// The largest distance of a match that is encoded in two bytes.
+const M2_MAX_OFFSET: usize = 0x0800;
+
+/// Decompresses a LZO1X compressed frame, which may not be larger than `limit` bytes.
+pub(crate) fn decompress(input: &[u8], limit: usize) -> Result<Vec<u8>> {
+    let mut reader = Reader {
+        input,
+        position: 0,
+        limit,
+    };
+    let mut out = Vec::with_capacity(input.len().saturating_mul(2).min(limit));
+
+    // The number of literals that were copied after the last match.
+    let mut state;
+    let first = reader.peek()?;
+    if first > 17 {
+        reader.position += 1;
+        let count = usize::from(first - 17);
+        reader.copy_literals(&mut out, count)?;
+        state = if count < 4 { count } else { 4 };
+    } else {
+        state = 0;
+    }
+
+    loop {
+        let t = usize::from(reader.byte()?);
+        let (distance, length) = if t < 16 {
+            match state {
+                0 => {
+                    // A long run of literals.
+                    let count = match t {
+                        0 => reader.zero_run()? + 15,
+                        t => t,
+                    } + 3;
+                    reader.copy_literals(&mut out, count)?;
+                    state = 4;
+                    continue;
+                }
+                4 => {
+                    let distance =
+                        1 + M2_MAX_OFFSET + (t >> 2) + (usize::from(reader.byte()?) << 2);
+                    reader.copy_match(&mut out, distance, 3)?;
+                    state = t & 3;
+                    reader.copy_literals(&mut out, state)?;
+                    continue;
+                }
+                _ => {
+                    let distance = 1 + (t >> 2) + (usize::from(reader.byte()?) << 2);
+                    reader.copy_match(&mut out, distance, 2)?;
+                    state = t & 3;
+                    reader.copy_literals(&mut out, state)?;
+                    continue;
+                }
+            }
+        } else if t >= 64 {
+            state = t & 3;
+            let distance = 1 + ((t >> 2) & 7) + (usize::from(reader.byte()?) << 3);
+            (distance, (t >> 5) + 1)
+        } else if t >= 32 {
+            let length = match t & 31 {
+                0 => reader.zero_run()? + 31,
+                length => length,
+            } + 2;
+            let next = reader.le16()?;
+            state = next & 3;
+            (1 + (next >> 2), length)
+        } else {
+            let length = match t & 7 {
+                0 => reader.zero_run()? + 7,
+                length => length,
+            } + 2;
+            let next = reader.le16()?;
+            state = next & 3;
+            let distance = ((t & 8) << 11) + (next >> 2);
+            if distance == 0 {
+                // The end of the stream.
+                return match length == 3 && reader.position == input.len() {
+                    true => Ok(out),
+                    false => Err(DemuxError::InvalidCompressedData),
+                };
+            }
+            (distance + 0x4000, length)
+        };
+
+        reader.copy_match(&mut out, distance, length)?;
+        reader.copy_literals(&mut out, state)?;
+    }
+}
+
+struct Reader<'a> {
+    input: &'a [u8],
+    position: usize,
+    /// The largest allowed size of the output.
+    limit: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn peek(&self) -> Result<u8> {
+        self.input
+            .get(self.position)
+            .copied()
+            .ok_or(DemuxError::InvalidCompressedData)
+    }
+
+    fn byte(&mut self) -> Result<u8> {
+        let byte = self.peek()?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    fn le16(&mut self) -> Result<usize> {
+        let low = usize::from(self.byte()?);
+        let high = usize::from(self.byte()?);
+        Ok(low | (high << 8))
+    }
+
+    /// Reads a length that is extended by zero bytes, each adding 255, and a final byte.
+    fn zero_run(&mut self) -> Result<usize> {
+        let mut length: usize = 0;
+        loop {
+            match self.byte()? {
+                0 => {
+                    length = length
+                        .checked_add(255)
+                        .ok_or(DemuxError::InvalidCompressedData)?
+                }
+                byte => return Ok(length + usize::from(byte)),
+            }
+        }
+    }
+
+    /// Copies a match, which may overlap with the bytes it produces.
+    fn copy_match(&self, out: &mut Vec<u8>, distance: usize, length: usize) -> Result<()> {
+        let start = out
+            .len()
+            .checked_sub(distance)
+            .ok_or(DemuxError::InvalidCompressedData)?;
+        check_limit(out.len().saturating_add(length), self.limit)?;
+        out.reserve(length);
+        for i in start..start + length {
+            let byte = out
+                .get(i)
+                .copied()
+                .ok_or(DemuxError::InvalidCompressedData)?;
+            out.push(byte);
+        }
+        Ok(())
+    }
+
+    fn copy_literals(&mut self, out: &mut Vec<u8>, count: usize) -> Result<()> {
+        check_limit(out.len().saturating_add(count), self.limit)?;
+        let end = self.position + count;
+        let literals = self
+            .input
+            .get(self.position..end)
+            .ok_or(DemuxError::InvalidCompressedData)?;
+        out.extend_from_slice(literals);
+        self.position = end;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compression::{check_damaged, read_corpus};
+
+    #[test]
+    fn test_decompress() -> Result<()> {
+        let compressed = [
+            20, b'a', b'b', b'c', // Three literals.
+            232, 0, // A match of 8 bytes with a distance of 3.
+            0x11, 0, 0, // The end of the stream.
+        ];
+        assert_eq!(decompress(&compressed, usize::MAX)?, b"abcabcabcab");
+
+        let compressed = [
+            1, b'h', b'e', b'l', b'l', // A run of four literals.
+            0x11, 0, 0,
+        ];
+        assert_eq!(decompress(&compressed, usize::MAX)?, b"hell");
+
+        assert!(matches!(
+            decompress(&[20, b'a', b'b', b'c', 232, 0, 0x11, 0, 0], 10),
+            Err(DemuxError::DecompressedSizeExceeded(10))
+        ));
+
+        assert!(decompress(&[20, b'a'], usize::MAX).is_err());
+        assert!(decompress(&[20, b'a', b'b', b'c', 232, 9, 0x11, 0, 0], usize::MAX).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_decompress_corpus() -> Result<()> {
+        // The corpus was created from the instructions of the LZO1X format, so it
+        // contains every kind of literal run and match.
+        let corpus = read_corpus("lzo", "lzo")?;
+        assert!(!corpus.is_empty());
+        for (path, compressed, raw) in corpus {
+            assert_eq!(
+                decompress(&compressed, usize::MAX)?,
+                raw,
+                "{}",
+                path.display()
+            );
+            if let Some(limit) = raw.len().checked_sub(1) {
+                assert!(
+                    matches!(
+                        decompress(&compressed, limit),
+                        Err(DemuxError::DecompressedSizeExceeded(_))
+                    ),
+                    "{}",
+                    path.display()
+                );
+            }
+            if compressed.len() <= 1024 {
+                check_damaged(decompress, &compressed, 1 << 20);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,183 @@
+//! Parallel analysis of the clusters of a file.
+//!
+//! The clusters are split into contiguous chunks of roughly the same size, which are demuxed
+//! on a rayon thread pool with their own readers. The results of the chunks are merged in
+//! file order, so the results are identical to the sequential analysis.
+
+use std::{
+    collections::HashMap,
+    io::{ErrorKind, Read, Seek},
+    sync::Arc,
+};
+
+use rayon::prelude::*;
+
+use crate::{
+    analysis::FrameTiming,
+    find_first_cluster_offset, parse_element_header,
+    validation::{collect_cluster_checks, validate_file, validate_file_with_checks},
+    DemuxError, ElementId, Frame, MatroskaFile, MatroskaIndex, Result, TimestampReport,
+    ValidationReport,
+};
+
+impl MatroskaIndex {
+    /// Works like `MatroskaFile::analyze_timestamps()`, but demuxes distinct clusters
+    /// in parallel on the given number of threads.
+    ///
+    /// Every thread opens its own reader by calling `open`, for example by opening the file
+    /// again or by creating a `Cursor` over a shared memory map. If `threads` is 0, the
+    /// default number of threads of rayon is used. Only the clusters of the first segment
+    /// are analyzed. Files with clusters of unknown size are analyzed on a single thread.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn analyze_timestamps_parallel<R, F>(
+        self: &Arc<Self>,
+        open: F,
+        threads: usize,
+    ) -> Result<TimestampReport>
+    where
+        R: Read + Seek,
+        F: Fn() -> std::io::Result<R> + Sync,
+    {
+        let pool = thread_pool(threads)?;
+        let chunks = self.cluster_chunks(&mut open()?, pool.current_num_threads())?;
+        debug!(chunks = chunks.len(), "analyzing the clusters in parallel");
+
+        let results: Vec<HashMap<u64, Vec<FrameTiming>>> = pool.install(|| {
+            chunks
+                .par_iter()
+                .map(|chunk| self.collect_chunk_timings(open()?, chunk.clone()))
+                .collect::<Result<_>>()
+        })?;
+
+        let mut timings: HashMap<u64, Vec<FrameTiming>> = HashMap::new();
+        for result in results {
+            for (track, frames) in result {
+                timings.entry(track).or_default().extend(frames);
+            }
+        }
+
+        Ok(self.timestamp_report(&timings))
+    }
+
+    /// Works like `MatroskaFile::validate()`, but validates distinct clusters in parallel
+    /// on the given number of threads.
+    ///
+    /// Every thread opens its own reader by calling `open`, like for
+    /// `analyze_timestamps_parallel()`. If `threads` is 0, the default number of threads of
+    /// rayon is used. Files with clusters of unknown size are validated on a single thread.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn validate_parallel<R, F>(&self, open: F, threads: usize) -> Result<ValidationReport>
+    where
+        R: Read + Seek,
+        F: Fn() -> std::io::Result<R> + Sync,
+    {
+        let pool = thread_pool(threads)?;
+        let mut r = open()?;
+        let chunks = self.cluster_chunks(&mut r, pool.current_num_threads())?;
+        if chunks.len() < 2 {
+            return validate_file(&mut r, self);
+        }
+        debug!(chunks = chunks.len(), "validating the clusters in parallel");
+
+        let results: Vec<HashMap<u64, Vec<_>>> = pool.install(|| {
+            chunks
+                .par_iter()
+                .map(|chunk| collect_cluster_checks(&mut open()?, self, chunk.clone()))
+                .collect::<Result<_>>()
+        })?;
+        let checks = results.into_iter().flatten().collect();
+
+        validate_file_with_checks(&mut r, self, Some(&checks))
+    }
+
+    /// Splits the clusters of the segment into at most `count` contiguous byte ranges.
+    fn cluster_chunks<R: Read + Seek>(
+        &self,
+        r: &mut R,
+        count: usize,
+    ) -> Result<Vec<std::ops::Range<u64>>> {
+        let mut seek_head = self.seek_head.clone();
+        if self.settings.metadata_only && !seek_head.contains_key(&ElementId::Cluster) {
+            find_first_cluster_offset(r, &mut seek_head)?;
+        }
+        let first_cluster = *seek_head
+            .get(&ElementId::Cluster)
+            .ok_or(DemuxError::CantFindCluster)?;
+        let segment_end = self.segment_data_size.map_or(u64::MAX, |size| {
+            self.segment_data_offset.saturating_add(size)
+        });
+
+        let mut clusters = Vec::new();
+        let mut position = first_cluster;
+        while position < segment_end {
+            let (element_id, size) =
+                match parse_element_header(r, Some(position), self.header_limits()) {
+                    Ok(header) => header,
+                    // We reached the end of the file.
+                    Err(DemuxError::IoError(err)) if err.kind() == ErrorKind::UnexpectedEof => {
+                        break
+                    }
+                    Err(err) => return Err(err),
+                };
+            if size == u64::MAX {
+                // Clusters of unknown size can only be found by reading through them.
+                return Ok(std::iter::once(first_cluster..segment_end).collect());
+            }
+            if element_id == ElementId::Cluster {
+                clusters.push(position);
+            }
+            position = r.stream_position()? + size;
+        }
+        let end = position.min(segment_end);
+
+        let chunk_size = (end - first_cluster) / u64::try_from(count.max(1))? + 1;
+        let mut chunks: Vec<std::ops::Range<u64>> = Vec::with_capacity(count);
+        for cluster in clusters {
+            match chunks.last_mut() {
+                Some(chunk) if cluster - chunk.start < chunk_size => {}
+                Some(chunk) => {
+                    chunk.end = cluster;
+                    chunks.push(cluster..end);
+                }
+                None => chunks.push(cluster..end),
+            }
+        }
+
+        Ok(chunks)
+    }
+
+    /// Demuxes the frames of the blocks that start inside the given range.
+    fn collect_chunk_timings<R: Read + Seek>(
+        self: &Arc<Self>,
+        r: R,
+        range: std::ops::Range<u64>,
+    ) -> Result<HashMap<u64, Vec<FrameTiming>>> {
+        let mut reader = MatroskaFile::new(Arc::clone(self), r)?;
+        reader.seek_to_offset(range.start)?;
+
+        let mut timings: HashMap<u64, Vec<FrameTiming>> = HashMap::new();
+        let mut frame = Frame::default();
+        while reader.read_next_frame(&mut frame)? {
+            // The frame belongs to a block of the next chunk.
+            if matches!(reader.current_element, Some((position, _)) if position >= range.end) {
+                break;
+            }
+            timings.entry(frame.track).or_default().push(FrameTiming {
+                timestamp: frame.timestamp,
+                duration: frame.duration,
+                is_keyframe: frame.is_keyframe.unwrap_or(false),
+            });
+        }
+
+        Ok(timings)
+    }
+}
+
+/// Creates the thread pool that runs the chunks. Uses the default number of threads of rayon
+/// if `threads` is 0.
+fn thread_pool(threads: usize) -> Result<rayon::ThreadPool> {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .map_err(|err| DemuxError::IoError(std::io::Error::new(ErrorKind::Other, err)))
+}
@@ -0,0 +1,272 @@
+//! Export and import of the parsed metadata of a file.
+//!
+//! Instead of serializing every metadata structure, the exported index contains the byte
+//! ranges of the file that were read while parsing the metadata. Importing the index replays
+//! the parsing on these byte ranges, so no other part of the file needs to be read.
+
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+
+use crate::{byte_reader::ByteReader, DemuxError, Result};
+
+/// The magic bytes at the start of an exported index.
+const MAGIC: &[u8; 4] = b"MKVI";
+/// The version of the exported index format.
+const VERSION: u8 = 1;
+
+/// A reader that records all bytes that were read from the inner reader.
+pub(crate) struct RecordingReader<'a, R> {
+    inner: &'a mut R,
+    position: u64,
+    chunks: Vec<(u64, Vec<u8>)>,
+}
+
+impl<'a, R: Read + Seek> RecordingReader<'a, R> {
+    pub(crate) fn new(inner: &'a mut R) -> Result<Self> {
+        let position = inner.stream_position()?;
+        Ok(Self {
+            inner,
+            position,
+            chunks: Vec::new(),
+        })
+    }
+
+    /// Encodes the recorded bytes. Overlapping and adjacent reads are merged.
+    pub(crate) fn encode(mut self) -> Result<Vec<u8>> {
+        let position = self.inner.stream_position()?;
+        let file_len = self.inner.seek(SeekFrom::End(0))?;
+        self.inner.seek(SeekFrom::Start(position))?;
+
+        self.chunks.sort_by_key(|(offset, _)| *offset);
+        let mut merged: Vec<(u64, Vec<u8>)> = Vec::with_capacity(self.chunks.len());
+        for (offset, data) in self.chunks {
+            match merged.last_mut() {
+                Some((last_offset, last_data)) if offset <= *last_offset + len_u64(last_data) => {
+                    let skip = usize::try_from(*last_offset + len_u64(last_data) - offset)?;
+                    if skip < data.len() {
+                        last_data.extend_from_slice(&data[skip..]);
+                    }
+                }
+                _ => merged.push((offset, data)),
+            }
+        }
+
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+        out.extend_from_slice(&file_len.to_le_bytes());
+        out.extend_from_slice(&u32::try_from(merged.len())?.to_le_bytes());
+        for (offset, data) in merged {
+            out.extend_from_slice(&offset.to_le_bytes());
+            out.extend_from_slice(&u32::try_from(data.len())?.to_le_bytes());
+            out.extend_from_slice(&data);
+        }
+        Ok(out)
+    }
+}
+
+impl<'a, R: Read + Seek> Read for RecordingReader<'a, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if read > 0 {
+            self.chunks.push((self.position, buf[..read].to_vec()));
+            self.position += len_u64(&buf[..read]);
+        }
+        Ok(read)
+    }
+}
+
+impl<'a, R: Read + Seek> Seek for RecordingReader<'a, R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.position = self.inner.seek(pos)?;
+        Ok(self.position)
+    }
+}
+
+/// A reader that serves the bytes of an exported index.
+///
+/// Reading bytes that are not part of the index fails with `ErrorKind::InvalidData`.
+pub(crate) struct ReplayReader<'a> {
+    file_len: u64,
+    position: u64,
+    chunks: Vec<(u64, &'a [u8])>,
+}
+
+impl<'a> ReplayReader<'a> {
+    pub(crate) fn decode(data: &'a [u8]) -> Result<Self> {
+        let mut reader = ByteReader::with_error(data, || DemuxError::InvalidIndexData);
+        if reader.bytes(4)? != MAGIC || reader.u8()? != VERSION {
+            return Err(DemuxError::InvalidIndexData);
+        }
+        let file_len = u64::from_le_bytes(reader.array()?);
+        let count = u32::from_le_bytes(reader.array()?);
+
+        // The chunks must be sorted and must not overlap, so they can be searched.
+        let mut chunks: Vec<(u64, &[u8])> = Vec::new();
+        let mut chunk_end = 0;
+        for _ in 0..count {
+            let offset = u64::from_le_bytes(reader.array()?);
+            let len = u32::from_le_bytes(reader.array()?);
+            if offset < chunk_end {
+                return Err(DemuxError::InvalidIndexData);
+            }
+            chunk_end = offset
+                .checked_add(u64::from(len))
+                .filter(|end| *end <= file_len)
+                .ok_or(DemuxError::InvalidIndexData)?;
+            chunks.push((offset, reader.bytes(usize::try_from(len)?)?));
+        }
+        if !reader.is_empty() {
+            return Err(DemuxError::InvalidIndexData);
+        }
+
+        // The parsing started at the first recorded byte, which is the EBML header.
+        let position = chunks.first().map_or(0, |(offset, _)| *offset);
+
+        Ok(Self {
+            file_len,
+            position,
+            chunks,
+        })
+    }
+
+    /// Returns `true` if all recorded bytes match the bytes of the given file.
+    pub(crate) fn matches<R: Read + Seek>(&self, file: &mut R) -> Result<bool> {
+        let file_len = file.seek(SeekFrom::End(0))?;
+        if file_len != self.file_len || self.chunks.is_empty() {
+            return Ok(false);
+        }
+        let mut bytes = Vec::new();
+        for (offset, data) in self.chunks.iter() {
+            bytes.resize(data.len(), 0);
+            file.seek(SeekFrom::Start(*offset))?;
+            file.read_exact(&mut bytes)?;
+            if bytes != *data {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl<'a> Read for ReplayReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if buf.is_empty() || self.position >= self.file_len {
+            return Ok(0);
+        }
+
+        let index = self
+            .chunks
+            .partition_point(|(offset, _)| *offset <= self.position);
+        let chunk = index
+            .checked_sub(1)
+            .and_then(|index| self.chunks.get(index))
+            .and_then(|(offset, data)| {
+                let start = usize::try_from(self.position - offset).ok()?;
+                data.get(start..).filter(|data| !data.is_empty())
+            })
+            .ok_or_else(|| {
+                std::io::Error::new(ErrorKind::InvalidData, "the index doesn't contain the data")
+            })?;
+
+        let read = chunk.len().min(buf.len());
+        buf[..read].copy_from_slice(&chunk[..read]);
+        self.position += len_u64(&buf[..read]);
+        Ok(read)
+    }
+}
+
+impl<'a> Seek for ReplayReader<'a> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+            SeekFrom::End(offset) => self.file_len.checked_add_signed(offset),
+        };
+        self.position = position.ok_or_else(|| {
+            std::io::Error::new(
+                ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+        Ok(self.position)
+    }
+}
+
+fn len_u64(data: &[u8]) -> u64 {
+    u64::try_from(data.len()).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn test_replay_recorded_reads() -> Result<()> {
+        let data: Vec<u8> = (0..32).collect();
+        let mut file = Cursor::new(data);
+
+        let mut recorder = RecordingReader::new(&mut file)?;
+        let mut buf = [0; 4];
+        recorder.seek(SeekFrom::Start(2))?;
+        recorder.read_exact(&mut buf)?;
+        recorder.seek(SeekFrom::Start(4))?;
+        recorder.read_exact(&mut buf)?;
+        recorder.seek(SeekFrom::Start(20))?;
+        recorder.read_exact(&mut buf)?;
+        let index = recorder.encode()?;
+
+        let mut replay = ReplayReader::decode(&index)?;
+        assert_eq!(replay.chunks.len(), 2);
+        assert!(replay.matches(&mut file)?);
+
+        let mut buf = [0; 6];
+        replay.seek(SeekFrom::Start(2))?;
+        replay.read_exact(&mut buf)?;
+        assert_eq!(buf, [2, 3, 4, 5, 6, 7]);
+
+        replay.seek(SeekFrom::Start(10))?;
+        assert!(replay.read(&mut buf).is_err());
+        replay.seek(SeekFrom::End(0))?;
+        assert_eq!(replay.read(&mut buf)?, 0);
+
+        assert!(ReplayReader::decode(&index[..index.len() - 1]).is_err());
+
+        // The bytes of every chunk are compared with the file.
+        file.get_mut()[21] = 0xFF;
+        assert!(!replay.matches(&mut file)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reject_unsorted_chunks() -> Result<()> {
+        let mut index = Vec::new();
+        index.extend_from_slice(MAGIC);
+        index.push(VERSION);
+        index.extend_from_slice(&32_u64.to_le_bytes());
+        index.extend_from_slice(&2_u32.to_le_bytes());
+        for offset in [8_u64, 2] {
+            index.extend_from_slice(&offset.to_le_bytes());
+            index.extend_from_slice(&4_u32.to_le_bytes());
+            index.extend_from_slice(&[0; 4]);
+        }
+        assert!(matches!(
+            ReplayReader::decode(&index),
+            Err(DemuxError::InvalidIndexData)
+        ));
+
+        // Overlapping chunks.
+        index[33..41].copy_from_slice(&10_u64.to_le_bytes());
+        assert!(matches!(
+            ReplayReader::decode(&index),
+            Err(DemuxError::InvalidIndexData)
+        ));
+
+        index[33..41].copy_from_slice(&12_u64.to_le_bytes());
+        assert_eq!(ReplayReader::decode(&index)?.position, 8);
+
+        Ok(())
+    }
+}
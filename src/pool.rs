@@ -0,0 +1,178 @@
+//! Reusable frames to avoid allocations when frames are handed through a pipeline.
+
+use std::{
+    io::{Read, Seek},
+    ops::{Deref, DerefMut},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use crate::{Frame, MatroskaFile, Result};
+
+/// A pool of reusable frames.
+///
+/// Frames taken from the pool are returned to it when their `PooledFrame` is dropped, so
+/// the allocated buffers are reused by the next frames. Cloning a pool is cheap, the clones
+/// share the same frames, so a pool can be shared between threads.
+#[derive(Clone, Debug)]
+pub struct FramePool {
+    inner: Arc<PoolInner>,
+}
+
+#[derive(Debug)]
+struct PoolInner {
+    frames: Mutex<Vec<Frame>>,
+    max_frames: usize,
+    max_capacity: usize,
+}
+
+impl FramePool {
+    /// Creates a pool that keeps at most `max_frames` idle frames. The buffers of the
+    /// returned frames keep their capacity.
+    pub fn new(max_frames: usize) -> Self {
+        Self::with_max_capacity(max_frames, usize::MAX)
+    }
+
+    /// Creates a pool that keeps at most `max_frames` idle frames. The buffers of returned
+    /// frames that are larger than `max_capacity` bytes are shrunk, so that a single large
+    /// frame doesn't keep its memory alive in the pool.
+    pub fn with_max_capacity(max_frames: usize, max_capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(PoolInner {
+                frames: Mutex::new(Vec::with_capacity(max_frames)),
+                max_frames,
+                max_capacity,
+            }),
+        }
+    }
+
+    /// Takes an idle frame from the pool, or creates a new one if the pool is empty.
+    pub fn get(&self) -> PooledFrame {
+        let frame = self.frames().pop().unwrap_or_default();
+        PooledFrame {
+            frame,
+            pool: Arc::clone(&self.inner),
+            detached: false,
+        }
+    }
+
+    /// The number of idle frames in the pool.
+    pub fn len(&self) -> usize {
+        self.frames().len()
+    }
+
+    /// Returns `true` if the pool has no idle frames.
+    pub fn is_empty(&self) -> bool {
+        self.frames().is_empty()
+    }
+
+    /// Drops all idle frames and frees their buffers.
+    pub fn clear(&self) {
+        self.frames().clear();
+    }
+
+    fn frames(&self) -> MutexGuard<'_, Vec<Frame>> {
+        self.inner.frames()
+    }
+}
+
+impl PoolInner {
+    fn frames(&self) -> MutexGuard<'_, Vec<Frame>> {
+        // The frames stay valid even if a thread panicked while holding the lock.
+        match self.frames.lock() {
+            Ok(frames) => frames,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    fn recycle(&self, mut frame: Frame) {
+        let mut frames = self.frames();
+        if frames.len() >= self.max_frames {
+            return;
+        }
+        frame.data.clear();
+        if frame.data.capacity() > self.max_capacity {
+            frame.data.shrink_to(self.max_capacity);
+        }
+        frames.push(frame);
+    }
+}
+
+/// A frame taken from a `FramePool`, which is returned to the pool when dropped.
+#[derive(Debug)]
+pub struct PooledFrame {
+    frame: Frame,
+    pool: Arc<PoolInner>,
+    detached: bool,
+}
+
+impl PooledFrame {
+    /// Detaches the frame from the pool, so that it isn't returned to it.
+    pub fn into_inner(mut self) -> Frame {
+        self.detached = true;
+        std::mem::take(&mut self.frame)
+    }
+}
+
+impl Deref for PooledFrame {
+    type Target = Frame;
+
+    fn deref(&self) -> &Frame {
+        &self.frame
+    }
+}
+
+impl DerefMut for PooledFrame {
+    fn deref_mut(&mut self) -> &mut Frame {
+        &mut self.frame
+    }
+}
+
+impl Drop for PooledFrame {
+    fn drop(&mut self) {
+        if !self.detached {
+            self.pool.recycle(std::mem::take(&mut self.frame));
+        }
+    }
+}
+
+impl<R: Read + Seek> MatroskaFile<R> {
+    /// Reads the next frame into a frame of the given pool. Works like `next_frame()`.
+    ///
+    /// The frame is returned to the pool when the handle is dropped, so that its buffer is
+    /// reused. Returns `None` at the end of the file.
+    pub fn next_frame_into_pool(&mut self, pool: &FramePool) -> Result<Option<PooledFrame>> {
+        let mut frame = pool.get();
+        match self.next_frame(&mut frame)? {
+            true => Ok(Some(frame)),
+            false => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_pool_retention() {
+        let pool = FramePool::with_max_capacity(1, 16);
+
+        let mut small = pool.get();
+        small.data.extend_from_slice(&[0; 8]);
+        let mut large = pool.get();
+        large.data.extend_from_slice(&[0; 64]);
+        drop(large);
+        drop(small);
+
+        // Only one frame is kept and its oversized buffer was shrunk.
+        assert_eq!(pool.len(), 1);
+        let frame = pool.get();
+        assert!(frame.data.is_empty());
+        assert!(frame.data.capacity() <= 16);
+        assert!(pool.is_empty());
+
+        let detached = frame.into_inner();
+        assert!(detached.data.is_empty());
+        assert!(pool.is_empty());
+    }
+}
@@ -0,0 +1,80 @@
+//! Cheap detection of Matroska and WebM streams.
+
+use std::io::{Read, Seek, SeekFrom};
+
+use crate::{
+    ebml::{expect_master, HeaderLimits},
+    parse_ebml_header, DemuxError, ElementId, Result,
+};
+
+/// The magic bytes of the EBML header element.
+const EBML_MAGIC: [u8; 4] = [0x1A, 0x45, 0xDF, 0xA3];
+
+/// The container format of a probed stream.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ContainerKind {
+    /// A Matroska file.
+    Matroska,
+    /// A WebM file.
+    WebM,
+}
+
+/// The result of `probe()`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ProbeResult {
+    /// The container format of the stream.
+    pub kind: ContainerKind,
+    /// The version of DocType interpreter used to create the stream.
+    pub doc_type_version: u64,
+    /// The minimum DocType version an interpreter has to support to read the stream.
+    pub doc_type_read_version: u64,
+    /// Set when the segment has an unknown size, which happens for live streams.
+    pub is_live: bool,
+}
+
+/// Checks if the given reader contains a Matroska or WebM stream.
+///
+/// Only the EBML header and the header of the segment are read, which is much cheaper than
+/// opening the file. Returns `None` if the stream doesn't start with an EBML header or
+/// isn't a supported Matroska or WebM stream.
+pub fn probe<R: Read + Seek>(mut reader: R) -> Result<Option<ProbeResult>> {
+    let start = reader.stream_position()?;
+    let mut magic = [0_u8; 4];
+    match reader.read_exact(&mut magic) {
+        Ok(()) if magic == EBML_MAGIC => {}
+        Ok(()) => return Ok(None),
+        Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err.into()),
+    }
+    reader.seek(SeekFrom::Start(start))?;
+
+    let header = match parse_ebml_header(&mut reader) {
+        Ok(header) => header,
+        Err(DemuxError::InvalidEbmlHeader(_)) => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    let kind = if header.doc_type().trim_end_matches('\0') == "webm" {
+        ContainerKind::WebM
+    } else {
+        ContainerKind::Matroska
+    };
+
+    let (_, segment_size) = expect_master(
+        &mut reader,
+        ElementId::Segment,
+        None,
+        HeaderLimits::default(),
+    )?;
+
+    Ok(Some(ProbeResult {
+        kind,
+        doc_type_version: header.doc_type_version(),
+        doc_type_read_version: header.doc_type_read_version(),
+        is_live: segment_size == u64::MAX,
+    }))
+}
+
+/// Returns `true` if the given reader contains a Matroska or WebM stream. See `probe()`.
+pub fn is_matroska<R: Read + Seek>(reader: R) -> Result<bool> {
+    Ok(probe(reader)?.is_some())
+}
@@ -0,0 +1,431 @@
+//! Copying selected tracks of a file into a new file without re-encoding them.
+
+use std::{
+    convert::TryFrom,
+    io::{Cursor, Read, Seek, SeekFrom, Write},
+};
+
+use crate::{
+    block::parse_block_header,
+    ebml::{try_next_element_header, HeaderLimits},
+    element_id::element_parents,
+    writer::{
+        parse_children, read_element, write_id, write_size, write_void, RawElement, BLOCK_GROUP_ID,
+        BLOCK_ID, CHAPTERS_ID, CLUSTER_ID, CRC32_ID, CUES_ID, CUE_CLUSTER_POSITION_ID,
+        CUE_POINT_ID, CUE_RELATIVE_POSITION_ID, CUE_TIME_ID, CUE_TRACK_ID, CUE_TRACK_POSITIONS_ID,
+        REFERENCE_BLOCK_ID, SEEK_HEAD_ID, SEEK_ID, SEEK_ID_ID, SEEK_POSITION_ID, SEGMENT_ID,
+        SIMPLE_BLOCK_ID, TAGS_ID, TAG_TRACK_UID_ID, TARGETS_ID, TIMESTAMP_ID, TRACKS_ID,
+        TRACK_ENTRY_ID, TRACK_NUMBER_ID,
+    },
+    DemuxError, ElementId, MatroskaFile, Result, TrackType,
+};
+
+impl<R: Read + Seek> MatroskaFile<R> {
+    /// Copies the given tracks into a new Matroska file, without decoding their frames.
+    ///
+    /// The `Info`, the `TrackEntry` elements of the tracks, the `Chapters` and the `Tags`
+    /// are copied. Tags that only target other tracks are dropped. The clusters are copied
+    /// with their timestamp and the blocks of the tracks, clusters without such blocks are
+    /// dropped. New `Cues` reference all key frames of video tracks and the first key frame
+    /// of every other track in each cluster. The track numbers are kept.
+    ///
+    /// The position of the demuxer is restored afterwards.
+    pub fn remux_tracks<W: Write + Seek>(&mut self, mut writer: W, tracks: &[u64]) -> Result<()> {
+        if let Some(&track) = tracks
+            .iter()
+            .find(|&&track| self.index.track_by_number(track).is_none())
+        {
+            return Err(DemuxError::TrackNotFound(track));
+        }
+
+        let position = self.file.stream_position()?;
+        let result = self.write_remux(&mut writer, tracks);
+        self.file.seek(SeekFrom::Start(position))?;
+        result
+    }
+
+    fn write_remux<W: Write + Seek>(&mut self, writer: &mut W, tracks: &[u64]) -> Result<()> {
+        let (ebml_header, _) = read_element(&mut self.file, self.index.header_offset)?;
+        let metadata = self.remux_metadata(tracks)?;
+
+        let start = writer.stream_position()?;
+        let mut out = Vec::new();
+        ebml_header.encode(&mut out)?;
+        let segment_offset = start + u64::try_from(out.len())?;
+        write_id(&mut out, SEGMENT_ID);
+        write_size(&mut out, 0, 8)?;
+        let segment_data_offset = start + u64::try_from(out.len())?;
+
+        // The SeekHead is written once the positions are known. Its space is reserved with
+        // a `Void` element.
+        let reserved_entries: Vec<(u32, u64)> = metadata
+            .iter()
+            .map(|element| (element.id, 0))
+            .chain(std::iter::once((CUES_ID, 0)))
+            .collect();
+        let mut reserved = Vec::new();
+        seek_head(&reserved_entries)?.encode(&mut reserved)?;
+        let reserved = u64::try_from(reserved.len())?;
+        write_void(&mut out, reserved)?;
+
+        let mut seek_entries = Vec::new();
+        for element in &metadata {
+            let position = start + u64::try_from(out.len())? - segment_data_offset;
+            seek_entries.push((element.id, position));
+            element.encode(&mut out)?;
+        }
+        writer.write_all(&out)?;
+
+        let cue_points = self.remux_clusters(writer, tracks, segment_data_offset)?;
+        if !cue_points.is_empty() {
+            seek_entries.push((CUES_ID, writer.stream_position()? - segment_data_offset));
+            let mut out = Vec::new();
+            RawElement::master(CUES_ID, &cue_points)?.encode(&mut out)?;
+            writer.write_all(&out)?;
+        }
+        let end = writer.stream_position()?;
+
+        let mut out = Vec::new();
+        write_size(&mut out, end - segment_data_offset, 8)?;
+        writer.seek(SeekFrom::Start(segment_offset + 4))?;
+        writer.write_all(&out)?;
+
+        let mut out = Vec::new();
+        seek_head(&seek_entries)?.encode(&mut out)?;
+        let used = u64::try_from(out.len())?;
+        if used < reserved {
+            write_void(&mut out, reserved - used)?;
+        }
+        writer.seek(SeekFrom::Start(segment_data_offset))?;
+        writer.write_all(&out)?;
+
+        writer.seek(SeekFrom::Start(end))?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Collects the metadata elements that are copied.
+    fn remux_metadata(&mut self, tracks: &[u64]) -> Result<Vec<RawElement>> {
+        let info_offset = *self
+            .index
+            .seek_head
+            .get(&ElementId::Info)
+            .ok_or(DemuxError::ElementNotFound(ElementId::Info))?;
+        let tracks_offset = *self
+            .index
+            .seek_head
+            .get(&ElementId::Tracks)
+            .ok_or(DemuxError::ElementNotFound(ElementId::Tracks))?;
+
+        let (info, _) = read_element(&mut self.file, info_offset)?;
+        let mut elements = vec![info];
+
+        let (tracks_element, _) = read_element(&mut self.file, tracks_offset)?;
+        let mut entries = Vec::new();
+        for entry in parse_children(&tracks_element.data)? {
+            if entry.id != TRACK_ENTRY_ID {
+                continue;
+            }
+            let track_number = parse_children(&entry.data)?
+                .iter()
+                .find(|child| child.id == TRACK_NUMBER_ID)
+                .and_then(RawElement::as_unsigned);
+            if matches!(track_number, Some(number) if tracks.contains(&number)) {
+                entries.push(entry);
+            }
+        }
+        elements.push(RawElement::master(TRACKS_ID, &entries)?);
+
+        let track_uids: Vec<u64> = tracks
+            .iter()
+            .filter_map(|&track| self.index.track_by_number(track))
+            .map(|track| track.track_uid().get())
+            .collect();
+
+        // Further `Chapters` and `Tags` elements are merged into the first one.
+        for (element_id, id) in [
+            (ElementId::Chapters, CHAPTERS_ID),
+            (ElementId::Tags, TAGS_ID),
+        ] {
+            let offsets: Vec<u64> = self
+                .index
+                .seek_head
+                .get(&element_id)
+                .into_iter()
+                .chain(
+                    self.index
+                        .duplicate_elements
+                        .get(&element_id)
+                        .into_iter()
+                        .flatten(),
+                )
+                .copied()
+                .collect();
+
+            let mut children = Vec::new();
+            for offset in offsets {
+                let (element, _) = read_element(&mut self.file, offset)?;
+                for child in parse_children(&element.data)? {
+                    if child.id == CRC32_ID
+                        || (id == TAGS_ID && !targets_tracks(&child, &track_uids)?)
+                    {
+                        continue;
+                    }
+                    children.push(child);
+                }
+            }
+            if !children.is_empty() {
+                elements.push(RawElement::master(id, &children)?);
+            }
+        }
+
+        Ok(elements)
+    }
+
+    /// Copies the clusters with the blocks of the given tracks. Returns the cue points of
+    /// the written clusters.
+    fn remux_clusters<W: Write + Seek>(
+        &mut self,
+        writer: &mut W,
+        tracks: &[u64],
+        segment_data_offset: u64,
+    ) -> Result<Vec<RawElement>> {
+        let video_tracks: Vec<u64> = tracks
+            .iter()
+            .copied()
+            .filter(|&track| {
+                self.index
+                    .track_by_number(track)
+                    .map(|entry| entry.track_type())
+                    == Some(TrackType::Video)
+            })
+            .collect();
+        let segment_end = self
+            .index
+            .segment_data_size
+            .map(|size| self.index.segment_data_offset + size);
+
+        let mut cue_points = Vec::new();
+        let mut position = self.first_cluster_offset()?;
+        loop {
+            if matches!(segment_end, Some(end) if position >= end) {
+                break;
+            }
+            self.file.seek(SeekFrom::Start(position))?;
+            let (id, element_id, size) =
+                match try_next_element_header(&mut self.file, HeaderLimits::default())? {
+                    Some(header) => header,
+                    None => break,
+                };
+            let data_offset = self.file.stream_position()?;
+
+            let children = match (id, size) {
+                (CLUSTER_ID, _) => {
+                    let (children, end) = self.read_cluster_children(data_offset, size)?;
+                    position = end;
+                    children
+                }
+                (_, u64::MAX) => return Err(DemuxError::UnknownSizeNotAllowed(element_id)),
+                _ => {
+                    position = data_offset
+                        .checked_add(size)
+                        .ok_or(DemuxError::InvalidEbmlDataSize)?;
+                    continue;
+                }
+            };
+
+            let cluster_position = writer.stream_position()? - segment_data_offset;
+            let mut cluster_timestamp = 0;
+            let mut cued_tracks = Vec::new();
+            let mut has_blocks = false;
+            let mut data = Vec::new();
+            // Only the timestamp and the blocks are copied, since elements like `Position`,
+            // `PrevSize` and `CRC-32` are not valid anymore.
+            for child in children {
+                match child.id {
+                    TIMESTAMP_ID => {
+                        cluster_timestamp =
+                            child.as_unsigned().ok_or(DemuxError::UnexpectedDataType)?;
+                    }
+                    SIMPLE_BLOCK_ID | BLOCK_GROUP_ID => {
+                        let (track, timestamp, is_key_frame) =
+                            match block_info(&child, cluster_timestamp)? {
+                                Some(info) => info,
+                                None => continue,
+                            };
+                        if !tracks.contains(&track) {
+                            continue;
+                        }
+                        has_blocks = true;
+
+                        let cue_time = u64::try_from(timestamp).ok();
+                        if let (true, Some(cue_time)) = (is_key_frame, cue_time) {
+                            if video_tracks.contains(&track) || !cued_tracks.contains(&track) {
+                                cued_tracks.push(track);
+                                cue_points.push(cue_point(
+                                    cue_time,
+                                    track,
+                                    cluster_position,
+                                    u64::try_from(data.len())?,
+                                )?);
+                            }
+                        }
+                    }
+                    _ => continue,
+                }
+                child.encode(&mut data)?;
+            }
+
+            if has_blocks {
+                let mut out = Vec::new();
+                RawElement {
+                    id: CLUSTER_ID,
+                    data,
+                }
+                .encode(&mut out)?;
+                writer.write_all(&out)?;
+            }
+        }
+
+        Ok(cue_points)
+    }
+
+    /// Reads the timestamp and the blocks of the cluster whose data starts at the given
+    /// offset. Other children are skipped without reading them. A cluster with an unknown
+    /// size ends with the next top level element. Returns the children and the end of the
+    /// cluster.
+    fn read_cluster_children(&mut self, offset: u64, size: u64) -> Result<(Vec<RawElement>, u64)> {
+        let end = match size {
+            u64::MAX => None,
+            size => Some(
+                offset
+                    .checked_add(size)
+                    .ok_or(DemuxError::InvalidEbmlDataSize)?,
+            ),
+        };
+
+        let mut children = Vec::new();
+        let mut position = offset;
+        loop {
+            if matches!(end, Some(end) if position >= end) {
+                break;
+            }
+            self.file.seek(SeekFrom::Start(position))?;
+            let (id, element_id, size) =
+                match try_next_element_header(&mut self.file, HeaderLimits::default())? {
+                    Some(header) => header,
+                    None => break,
+                };
+            let is_top_level = matches!(
+                element_parents(element_id),
+                Some(parents) if parents.is_empty() || parents == [ElementId::Segment]
+            );
+            if end.is_none() && is_top_level {
+                break;
+            }
+            if size == u64::MAX {
+                return Err(DemuxError::UnknownSizeNotAllowed(element_id));
+            }
+            // A child must not reach beyond the cluster.
+            let child_end = self
+                .file
+                .stream_position()?
+                .checked_add(size)
+                .ok_or(DemuxError::InvalidEbmlDataSize)?;
+            if matches!(end, Some(end) if child_end > end) {
+                return Err(DemuxError::InvalidEbmlDataSize);
+            }
+            if matches!(id, TIMESTAMP_ID | SIMPLE_BLOCK_ID | BLOCK_GROUP_ID) {
+                let (child, _) = read_element(&mut self.file, position)?;
+                children.push(child);
+            }
+            position = child_end;
+        }
+        Ok((children, end.unwrap_or(position)))
+    }
+}
+
+/// Returns `false` if the tag only targets tracks that are not in the given list.
+fn targets_tracks(tag: &RawElement, track_uids: &[u64]) -> Result<bool> {
+    let targets = match parse_children(&tag.data)?
+        .into_iter()
+        .find(|child| child.id == TARGETS_ID)
+    {
+        Some(targets) => parse_children(&targets.data)?,
+        None => return Ok(true),
+    };
+
+    let mut tag_track_uids = targets
+        .iter()
+        .filter(|target| target.id == TAG_TRACK_UID_ID)
+        .filter_map(RawElement::as_unsigned)
+        .filter(|&uid| uid != 0)
+        .peekable();
+    Ok(tag_track_uids.peek().is_none() || tag_track_uids.any(|uid| track_uids.contains(&uid)))
+}
+
+/// Returns the track, the timestamp and whether the block is a key frame. Returns `None`
+/// for block groups without a block.
+fn block_info(element: &RawElement, cluster_timestamp: u64) -> Result<Option<(u64, i64, bool)>> {
+    if element.id == SIMPLE_BLOCK_ID {
+        let (track, timestamp, flags) =
+            parse_block_header(&mut Cursor::new(&element.data), cluster_timestamp)?;
+        return Ok(Some((track, timestamp, flags & 0x80 != 0)));
+    }
+
+    let children = parse_children(&element.data)?;
+    let block = match children.iter().find(|child| child.id == BLOCK_ID) {
+        Some(block) => block,
+        None => return Ok(None),
+    };
+    let (track, timestamp, _) =
+        parse_block_header(&mut Cursor::new(&block.data), cluster_timestamp)?;
+    let is_key_frame = !children.iter().any(|child| child.id == REFERENCE_BLOCK_ID);
+    Ok(Some((track, timestamp, is_key_frame)))
+}
+
+fn cue_point(
+    cue_time: u64,
+    track: u64,
+    cluster_position: u64,
+    relative_position: u64,
+) -> Result<RawElement> {
+    RawElement::master(
+        CUE_POINT_ID,
+        &[
+            RawElement::unsigned(CUE_TIME_ID, cue_time),
+            RawElement::master(
+                CUE_TRACK_POSITIONS_ID,
+                &[
+                    RawElement::unsigned(CUE_TRACK_ID, track),
+                    RawElement::unsigned(CUE_CLUSTER_POSITION_ID, cluster_position),
+                    RawElement::unsigned(CUE_RELATIVE_POSITION_ID, relative_position),
+                ],
+            )?,
+        ],
+    )
+}
+
+/// Creates a SeekHead for the given IDs and positions. The positions are always encoded
+/// with eight bytes, so that the size of the SeekHead doesn't depend on them.
+fn seek_head(entries: &[(u32, u64)]) -> Result<RawElement> {
+    let mut seeks = Vec::with_capacity(entries.len());
+    for &(id, position) in entries {
+        let mut seek_id = Vec::new();
+        write_id(&mut seek_id, id);
+        seeks.push(RawElement::master(
+            SEEK_ID,
+            &[
+                RawElement {
+                    id: SEEK_ID_ID,
+                    data: seek_id,
+                },
+                RawElement {
+                    id: SEEK_POSITION_ID,
+                    data: position.to_be_bytes().to_vec(),
+                },
+            ],
+        )?);
+    }
+    RawElement::master(SEEK_HEAD_ID, &seeks)
+}
@@ -0,0 +1,159 @@
+//! Sample accurate indexing of audio tracks.
+
+/// A block of an audio track inside the `SampleIndex`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SampleIndexEntry {
+    /// The timestamp of the block, expressed in the timestamp scale of the segment.
+    pub timestamp: i64,
+    /// The absolute position of the `SimpleBlock` or `BlockGroup` element.
+    pub offset: u64,
+    /// The number of frames inside the block.
+    pub frame_count: u64,
+    /// The index of the first sample of the block.
+    pub first_sample: u64,
+}
+
+/// An index of all blocks of an audio track, which maps the sample indices of the track to
+/// the blocks that contain them. Created by `MatroskaFile::sample_index()`.
+///
+/// If the track has a `DefaultDuration`, the first sample of the first block is derived
+/// from its timestamp and the samples of all following blocks are counted from the number
+/// of frames inside the blocks. This keeps the index sample accurate, even if the
+/// timestamps of the blocks are rounded to the timestamp scale. Otherwise the first sample
+/// of every block is derived from its timestamp.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SampleIndex {
+    track: u64,
+    sample_rate: f64,
+    samples_per_frame: Option<u64>,
+    entries: Vec<SampleIndexEntry>,
+}
+
+impl SampleIndex {
+    pub(crate) fn new(track: u64, sample_rate: f64, samples_per_frame: Option<u64>) -> Self {
+        Self {
+            track,
+            sample_rate,
+            samples_per_frame,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Adds the next block of the track.
+    pub(crate) fn push(&mut self, timestamp: i64, timestamp_ns: i64, offset: u64, frames: u64) {
+        let first_sample = match (self.samples_per_frame, self.entries.last()) {
+            (Some(samples_per_frame), Some(previous)) => previous
+                .first_sample
+                .saturating_add(previous.frame_count.saturating_mul(samples_per_frame)),
+            _ => self.timestamp_to_sample(timestamp_ns),
+        };
+        self.entries.push(SampleIndexEntry {
+            timestamp,
+            offset,
+            frame_count: frames,
+            first_sample,
+        });
+    }
+
+    #[allow(clippy::as_conversions)]
+    fn timestamp_to_sample(&self, timestamp_ns: i64) -> u64 {
+        ((timestamp_ns.max(0) as f64) * self.sample_rate / 1_000_000_000.0).round() as u64
+    }
+
+    /// The track number of the indexed track.
+    pub fn track(&self) -> u64 {
+        self.track
+    }
+
+    /// The sample rate the sample indices are expressed in. This is the output sampling
+    /// frequency of the track if it's set, otherwise its sampling frequency.
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// The number of samples of every frame, derived from the `DefaultDuration` of the track.
+    pub fn samples_per_frame(&self) -> Option<u64> {
+        self.samples_per_frame
+    }
+
+    /// The blocks of the track in file order.
+    pub fn entries(&self) -> &[SampleIndexEntry] {
+        &self.entries
+    }
+
+    /// The index of the sample after the last sample of the track. Only known if the track
+    /// has a `DefaultDuration`.
+    pub fn total_samples(&self) -> Option<u64> {
+        let samples_per_frame = self.samples_per_frame?;
+        let last = self.entries.last()?;
+        Some(
+            last.first_sample
+                .saturating_add(last.frame_count.saturating_mul(samples_per_frame)),
+        )
+    }
+
+    /// Returns the block that contains the given sample.
+    ///
+    /// Returns `None` if the sample is before the first block or after the end of the
+    /// track.
+    pub fn find(&self, sample: u64) -> Option<&SampleIndexEntry> {
+        if matches!(self.total_samples(), Some(total) if sample >= total) {
+            return None;
+        }
+        let position = self
+            .entries
+            .partition_point(|entry| entry.first_sample <= sample);
+        self.entries.get(position.checked_sub(1)?)
+    }
+}
+
+/// Converts the `DefaultDuration` of a track to the number of samples of every frame.
+#[allow(clippy::as_conversions)]
+pub(crate) fn samples_per_frame(default_duration: u64, sample_rate: f64) -> u64 {
+    ((default_duration as f64) * sample_rate / 1_000_000_000.0).round() as u64
+}
+
+/// The result of `MatroskaFile::seek_sample()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SampleSeek {
+    /// The timestamp of the block the demuxer is positioned at, expressed in the timestamp
+    /// scale of the segment.
+    pub timestamp: i64,
+    /// The absolute position of the block the demuxer is positioned at.
+    pub offset: u64,
+    /// The index of the first sample of the block.
+    pub first_sample: u64,
+    /// The number of decoded samples that must be discarded to reach the requested sample.
+    pub skip_samples: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find() {
+        let mut index = SampleIndex::new(1, 48000.0, Some(960));
+        index.push(0, 0, 100, 1);
+        index.push(20, 20_000_000, 200, 3);
+        index.push(80, 80_000_000, 300, 1);
+
+        assert_eq!(index.total_samples(), Some(4800));
+        assert_eq!(index.find(0).map(|e| e.offset), Some(100));
+        assert_eq!(index.find(959).map(|e| e.offset), Some(100));
+        assert_eq!(index.find(960).map(|e| e.offset), Some(200));
+        assert_eq!(index.find(3839).map(|e| e.offset), Some(200));
+        assert_eq!(index.find(3840).map(|e| e.offset), Some(300));
+        assert_eq!(index.find(4800), None);
+
+        let mut index = SampleIndex::new(1, 44100.0, None);
+        index.push(10, 10_000_000, 100, 1);
+        index.push(30, 30_000_000, 200, 1);
+
+        assert_eq!(index.total_samples(), None);
+        assert_eq!(index.find(440), None);
+        assert_eq!(index.find(441).map(|e| e.offset), Some(100));
+        assert_eq!(index.find(1323).map(|e| e.offset), Some(200));
+        assert_eq!(index.find(1_000_000).map(|e| e.offset), Some(200));
+    }
+}
@@ -0,0 +1,312 @@
+//! Settings that control how a file is parsed.
+
+use std::collections::HashMap;
+
+use crate::{
+    ebml::{HeaderLimits, ParseOptions},
+    element_id::{id_to_element_id, ElementId, ElementType},
+    EbmlHeader,
+};
+
+/// The default limit of the size of a decompressed frame.
+pub(crate) const DEFAULT_MAX_DECOMPRESSED_SIZE: usize = 10 * 1024 * 1024;
+
+/// Defines how frames of tracks that are not present in the `Tracks` element are handled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnknownTrackPolicy {
+    /// The frames are returned like all other frames. `MatroskaFile::track_by_number()`
+    /// returns `None` for their track.
+    #[default]
+    Pass,
+    /// The frames are silently dropped.
+    Drop,
+    /// Reading such a frame returns `DemuxError::TrackNotFound`.
+    Error,
+}
+
+/// Defines how the entries of the `SeekHead` are checked before they are used.
+///
+/// Files edited with faulty tools often contain a stale `SeekHead` whose entries don't point
+/// at the elements they declare anymore.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SeekHeadPolicy {
+    /// The entries are used as they are.
+    #[default]
+    Trust,
+    /// An entry that doesn't point at the element it declares returns
+    /// `DemuxError::InvalidSeekEntry`.
+    Verify,
+    /// Wrong entries are replaced by the offsets found by a scan of the top level elements
+    /// and are reported by `MatroskaIndex::seek_head_mismatches()`. Entries whose element
+    /// can't be found are removed.
+    Repair,
+}
+
+/// Defines how frames are handled whose timestamp lies before the start of the segment.
+///
+/// Block timestamps are stored relative to the cluster timestamp as a signed 16 bit value,
+/// so a block at the start of a cluster with a small timestamp can point before the start
+/// of the segment, for example to carry audio priming samples. `Frame::signed_timestamp`
+/// always contains the unmodified timestamp.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimestampPolicy {
+    /// `Frame::timestamp` is clamped to 0 and `Frame::is_timestamp_clamped` is set.
+    #[default]
+    Clamp,
+    /// Reading such a frame returns `DemuxError::NegativeTimestamp`.
+    Error,
+}
+
+/// An element that is not defined by the Matroska specification, registered with
+/// `ParserSettings::register_element()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CustomElement {
+    /// The raw Element ID.
+    pub id: u32,
+    /// The name of the element.
+    pub name: String,
+    /// The type of the element data.
+    pub element_type: ElementType,
+}
+
+/// Settings that control how a file is parsed.
+#[derive(Clone, Debug, Default)]
+pub struct ParserSettings {
+    pub(crate) combine_laced_frames: bool,
+    pub(crate) lazy_metadata: bool,
+    pub(crate) lenient: bool,
+    pub(crate) record_skipped_elements: bool,
+    pub(crate) strict: bool,
+    pub(crate) max_frame_capacity: Option<usize>,
+    pub(crate) max_laced_frames: Option<usize>,
+    pub(crate) max_decompressed_size: Option<usize>,
+    pub(crate) unknown_track_policy: UnknownTrackPolicy,
+    pub(crate) timestamp_policy: TimestampPolicy,
+    pub(crate) seek_head_policy: SeekHeadPolicy,
+    pub(crate) scan_top_level_elements: bool,
+    pub(crate) stamp_track_type: bool,
+    pub(crate) clear_expired_subtitles: bool,
+    pub(crate) stitch_timestamps: bool,
+    pub(crate) default_duration_fallback: bool,
+    pub(crate) nanosecond_timestamps: bool,
+    pub(crate) custom_elements: HashMap<u32, CustomElement>,
+    /// Set by `MatroskaFile::open_metadata_only()`.
+    pub(crate) metadata_only: bool,
+}
+
+impl ParserSettings {
+    /// Returns all frames of a laced block as a single `Frame`. The byte ranges of the
+    /// individual frames are stored in `Frame::lace_ranges`.
+    ///
+    /// By default every laced frame is returned on its own. Their timestamps are spread
+    /// using the `DefaultDuration` of the track, if present.
+    pub fn combine_laced_frames(mut self, combine_laced_frames: bool) -> Self {
+        self.combine_laced_frames = combine_laced_frames;
+        self
+    }
+
+    /// Defers the parsing of the `Cues`, `Chapters`, `Tags` and `Attachments` elements
+    /// until they are loaded with `MatroskaFile::load_cue_points()`, `load_chapters()`,
+    /// `load_tags()` or `load_attachments()`. The first seek loads the `Cues`. Until then,
+    /// accessors like `MatroskaFile::chapters()` return `None`.
+    ///
+    /// These elements are often placed at the end of the file. When reading from a
+    /// network source, parsing them at open would request the tail of the file before
+    /// the first frame could be read.
+    pub fn lazy_metadata(mut self, lazy_metadata: bool) -> Self {
+        self.lazy_metadata = lazy_metadata;
+        self
+    }
+
+    /// Tolerates broken elements where possible instead of failing to open the file.
+    ///
+    /// Tracks that can't be parsed are skipped and reported via
+    /// `MatroskaFile::track_errors()`. A missing `MuxingApp` or `WritingApp` is substituted
+    /// by an empty string and reported via `MatroskaFile::parse_warnings()`. Strings that
+    /// are not valid UTF-8 are decoded lossily instead of failing the parse. The raw bytes
+    /// of free text like titles, names and tag values are still available, for example via
+    /// `TrackEntry::invalid_name()` or `SimpleTag::invalid_string()`.
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Records every element that is skipped while demuxing frames, so that it can be
+    /// inspected via `MatroskaFile::skipped_elements()`.
+    pub fn record_skipped_elements(mut self, record_skipped_elements: bool) -> Self {
+        self.record_skipped_elements = record_skipped_elements;
+        self
+    }
+
+    /// Enforces the `EBMLMaxIDLength` and `EBMLMaxSizeLength` of the EBML header.
+    ///
+    /// Elements whose ID or data size is encoded with more bytes than allowed are
+    /// rejected with `DemuxError::InvalidEbmlElementId` or `DemuxError::InvalidEbmlDataSize`.
+    ///
+    /// Also checks that every element is placed inside a parent that the Matroska schema
+    /// allows, for example that a `Timestamp` is only found inside a `Cluster`. Misplaced
+    /// elements are rejected with `DemuxError::MisplacedElement`.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Caps the capacity that the data buffer of a `Frame` retains between frames.
+    ///
+    /// The buffer grows to fit the largest frame read. When a later frame fits into the
+    /// given capacity, a larger buffer is shrunk back down to it.
+    pub fn max_frame_capacity(mut self, max_frame_capacity: usize) -> Self {
+        self.max_frame_capacity = Some(max_frame_capacity);
+        self
+    }
+
+    /// Limits the number of frames a single laced block may contain.
+    ///
+    /// A block can declare up to 256 laced frames, which are queued until they are read.
+    /// Blocks with more frames are skipped and `DemuxError::TooManyLacedFrames` is returned.
+    pub fn max_laced_frames(mut self, max_laced_frames: usize) -> Self {
+        self.max_laced_frames = Some(max_laced_frames);
+        self
+    }
+
+    /// Limits the size of a decompressed frame or `CodecPrivate`. Defaults to 10 MiB.
+    ///
+    /// A few bytes of compressed data can expand to gigabytes. Larger frames are rejected
+    /// with `DemuxError::DecompressedSizeExceeded`.
+    pub fn max_decompressed_size(mut self, max_decompressed_size: usize) -> Self {
+        self.max_decompressed_size = Some(max_decompressed_size);
+        self
+    }
+
+    /// Defines how frames of tracks that are not present in the `Tracks` element are
+    /// handled. Defaults to `UnknownTrackPolicy::Pass`.
+    pub fn unknown_track_policy(mut self, unknown_track_policy: UnknownTrackPolicy) -> Self {
+        self.unknown_track_policy = unknown_track_policy;
+        self
+    }
+
+    /// Defines how frames with a timestamp before the start of the segment are handled.
+    /// Defaults to `TimestampPolicy::Clamp`.
+    pub fn timestamp_policy(mut self, timestamp_policy: TimestampPolicy) -> Self {
+        self.timestamp_policy = timestamp_policy;
+        self
+    }
+
+    /// Defines how the entries of the `SeekHead` are checked. Defaults to
+    /// `SeekHeadPolicy::Trust`.
+    pub fn seek_head_policy(mut self, seek_head_policy: SeekHeadPolicy) -> Self {
+        self.seek_head_policy = seek_head_policy;
+        self
+    }
+
+    /// Scans all top level elements of the segment at open, to find `Chapters`, `Tags` and
+    /// `Attachments` elements that are not referenced by the `SeekHead`, for example tags
+    /// that an editor appended to the end of the file.
+    ///
+    /// All elements of the same kind are merged in file order. Elements referenced by
+    /// multiple `SeekHead` entries are always merged.
+    pub fn scan_top_level_elements(mut self, scan_top_level_elements: bool) -> Self {
+        self.scan_top_level_elements = scan_top_level_elements;
+        self
+    }
+
+    /// Sets `Frame::track_type` for every frame, so that the track doesn't have to be
+    /// looked up with `MatroskaFile::track_for_frame()` in demux loops.
+    pub fn stamp_track_type(mut self, stamp_track_type: bool) -> Self {
+        self.stamp_track_type = stamp_track_type;
+        self
+    }
+
+    /// Emits an empty frame with `Frame::is_virtual` set when the subtitles of a subtitle
+    /// track expire, so that renderers driven by the demuxer output know when to clear
+    /// the screen.
+    ///
+    /// A subtitle expires after its `BlockDuration` or the `DefaultDuration` of its track.
+    /// The empty frame is emitted right before the first frame at or after the expiry, or
+    /// at the end of the segment. Subtitles without a duration are never cleared.
+    pub fn clear_expired_subtitles(mut self, clear_expired_subtitles: bool) -> Self {
+        self.clear_expired_subtitles = clear_expired_subtitles;
+        self
+    }
+
+    /// Stitches the parts of files that were created by appending other files, whose
+    /// cluster timestamps jump backwards.
+    ///
+    /// When the timestamp of a cluster is smaller than the timestamp of the previous cluster,
+    /// an offset is added to it and all following timestamps, so that they continue after
+    /// the end of the last frame. The applied offsets are reported by
+    /// `DemuxEvent::TimestampDiscontinuity` and `MatroskaFile::timestamp_offsets()`.
+    ///
+    /// Seeking uses the timestamps as stored in the file. The offsets found so far are
+    /// applied to the frames read after a seek.
+    pub fn stitch_timestamps(mut self, stitch_timestamps: bool) -> Self {
+        self.stitch_timestamps = stitch_timestamps;
+        self
+    }
+
+    /// Sets `Frame::duration` to the `DefaultDuration` of the track for frames that have no
+    /// `BlockDuration`, so that the durations of audio, video and subtitle frames are
+    /// populated the same way. Frames of tracks without a `DefaultDuration` keep `None`.
+    ///
+    /// The duration is expressed in the timestamp scale of the segment and rounded towards
+    /// zero, like the `DefaultDuration` used for gap detection and subtitle expiry.
+    pub fn default_duration_fallback(mut self, default_duration_fallback: bool) -> Self {
+        self.default_duration_fallback = default_duration_fallback;
+        self
+    }
+
+    /// Expresses all timestamp-like values in nanoseconds instead of ticks of the
+    /// `TimestampScale` of the segment, so that they use the same unit as the chapter
+    /// timestamps.
+    ///
+    /// This applies to the timestamps and durations of `Frame`s and `RawBlock`s, whose
+    /// `timestamp_scale` is 1 ns then, to the timestamps of `DemuxEvent`s and to
+    /// `CuePoint::time()`, `CueTrackPositions::duration()` and `CueReference::ref_time()`.
+    /// `Frame::relative_timestamp` stays as stored in the block header. Seek targets are
+    /// always given in ticks, use `MatroskaFile::seek_ns()` to seek to a time in nanoseconds.
+    pub fn nanosecond_timestamps(mut self, nanosecond_timestamps: bool) -> Self {
+        self.nanosecond_timestamps = nanosecond_timestamps;
+        self
+    }
+
+    /// The limit of the size of a decompressed frame.
+    pub(crate) fn decompressed_size_limit(&self) -> usize {
+        self.max_decompressed_size
+            .unwrap_or(DEFAULT_MAX_DECOMPRESSED_SIZE)
+    }
+
+    /// The options to parse the elements that follow the given EBML header with. Only strict
+    /// mode enforces the header limits declared by the EBML header.
+    pub(crate) fn parse_options(&self, ebml_header: &EbmlHeader) -> ParseOptions {
+        ParseOptions {
+            lossy_strings: self.lenient,
+            limits: if self.strict {
+                ebml_header.header_limits()
+            } else {
+                HeaderLimits::default()
+            },
+        }
+    }
+
+    /// Registers an element that is not defined by the Matroska specification, for example
+    /// a proprietary element written by a camera.
+    ///
+    /// Registered elements are still skipped while demuxing, but are reported as
+    /// `ElementId::Custom` by `MatroskaFile::skipped_elements()`. Their name and type can be
+    /// looked up with `MatroskaFile::custom_element()`. `MatroskaFile::dump()` prints them
+    /// with their name and parses their data with the registered type. IDs of elements
+    /// defined by the specification can't be registered and are ignored.
+    pub fn register_element(mut self, id: u32, name: &str, element_type: ElementType) -> Self {
+        if id_to_element_id(id) == ElementId::Unknown {
+            self.custom_elements.insert(
+                id,
+                CustomElement {
+                    id,
+                    name: name.to_owned(),
+                    element_type,
+                },
+            );
+        }
+        self
+    }
+}
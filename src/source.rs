@@ -0,0 +1,137 @@
+//! Adapter for sources that can only be read in byte ranges.
+
+use std::io::{Read, Seek, SeekFrom};
+
+/// The default amount of bytes requested from a `ByteRangeSource` at once.
+const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A pull based source that can read arbitrary byte ranges.
+///
+/// This is the natural interface of HTTP range requests (for example `fetch()` inside
+/// a browser) and doesn't assume any filesystem or cursor state.
+pub trait ByteRangeSource {
+    /// Reads the bytes starting at `offset` into `buf`. Returns the number of bytes read,
+    /// which might be smaller than the size of `buf`. Returns 0 if `offset` is past the end
+    /// of the source.
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize>;
+
+    /// The total length of the source in bytes, if known.
+    fn len(&self) -> Option<u64>;
+
+    /// Returns `true` if the source is known to be empty.
+    fn is_empty(&self) -> bool {
+        self.len() == Some(0)
+    }
+}
+
+/// Provides a `Read` and `Seek` view on top of a `ByteRangeSource`, so that it can be used
+/// with `MatroskaFile::open()`.
+///
+/// The demuxer mostly issues small reads, so the reader requests whole chunks from
+/// the source and serves the reads from the buffered chunk.
+#[derive(Clone, Debug)]
+pub struct ByteRangeReader<S: ByteRangeSource> {
+    source: S,
+    position: u64,
+    chunk_size: usize,
+    buffer: Vec<u8>,
+    buffer_offset: u64,
+}
+
+impl<S: ByteRangeSource> ByteRangeReader<S> {
+    /// Creates a new reader that requests chunks of 64 KiB from the source.
+    pub fn new(source: S) -> Self {
+        Self::with_chunk_size(source, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Creates a new reader that requests chunks of the given size from the source.
+    pub fn with_chunk_size(source: S, chunk_size: usize) -> Self {
+        Self {
+            source,
+            position: 0,
+            chunk_size: chunk_size.max(1),
+            buffer: Vec::new(),
+            buffer_offset: 0,
+        }
+    }
+
+    /// Returns the underlying source.
+    pub fn into_inner(self) -> S {
+        self.source
+    }
+
+    /// Returns the buffered bytes at the current position.
+    fn buffered(&self) -> &[u8] {
+        let end = self.buffer_offset + len_as_u64(self.buffer.len());
+        if self.position >= self.buffer_offset && self.position < end {
+            let start = usize::try_from(self.position - self.buffer_offset).unwrap_or(0);
+            &self.buffer[start..]
+        } else {
+            &[]
+        }
+    }
+}
+
+impl<S: ByteRangeSource> Read for ByteRangeReader<S> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        if self.buffered().is_empty() {
+            // Large reads bypass the buffer.
+            if buf.len() >= self.chunk_size {
+                let read = self.source.read_at(self.position, buf)?;
+                self.position += len_as_u64(read);
+                return Ok(read);
+            }
+
+            self.buffer.resize(self.chunk_size, 0);
+            let read = self.source.read_at(self.position, &mut self.buffer)?;
+            self.buffer.truncate(read);
+            self.buffer_offset = self.position;
+        }
+
+        let buffered = self.buffered();
+        let read = buffered.len().min(buf.len());
+        buf[..read].copy_from_slice(&buffered[..read]);
+        self.position += len_as_u64(read);
+
+        Ok(read)
+    }
+}
+
+impl<S: ByteRangeSource> Seek for ByteRangeReader<S> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+            SeekFrom::End(offset) => {
+                let len = self.source.len().ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::Unsupported,
+                        "the length of the source is unknown",
+                    )
+                })?;
+                len.checked_add_signed(offset)
+            }
+        };
+
+        self.position = position.ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+
+        Ok(self.position)
+    }
+
+    fn stream_position(&mut self) -> std::io::Result<u64> {
+        Ok(self.position)
+    }
+}
+
+fn len_as_u64(len: usize) -> u64 {
+    u64::try_from(len).unwrap_or(u64::MAX)
+}
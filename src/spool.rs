@@ -0,0 +1,232 @@
+//! Adapter for sources that can only be read sequentially.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{ErrorKind, Read, Seek, SeekFrom, Write},
+    path::PathBuf,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// The amount of bytes read from the inner reader at once.
+const SPOOL_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Used to create unique names for the temporary files.
+static SPOOL_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Provides a `Read` and `Seek` view on top of a reader that can only be read sequentially,
+/// for example `stdin` or a pipe, so that it can be used with `MatroskaFile::open()`.
+///
+/// All bytes read from the inner reader are spooled, so that the demuxer can seek back to
+/// them. The bytes are kept in memory, unless a memory limit is set with
+/// `with_memory_limit()`. Once the spooled bytes exceed the limit, they are moved to a
+/// temporary file, which is deleted when the reader is dropped.
+///
+/// Seeking to the end of the stream reads the whole inner reader.
+#[derive(Debug)]
+pub struct SpoolReader<R: Read> {
+    inner: R,
+    storage: Storage,
+    len: u64,
+    position: u64,
+    eof: bool,
+    memory_limit: Option<u64>,
+}
+
+#[derive(Debug)]
+enum Storage {
+    Memory(Vec<u8>),
+    File(SpoolFile),
+}
+
+/// A temporary file that is deleted when dropped.
+#[derive(Debug)]
+struct SpoolFile {
+    file: File,
+    path: PathBuf,
+}
+
+impl SpoolFile {
+    fn create() -> std::io::Result<Self> {
+        let path = std::env::temp_dir().join(format!(
+            "matroska-demuxer-{}-{}.spool",
+            std::process::id(),
+            SPOOL_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        Ok(Self { file, path })
+    }
+}
+
+impl Drop for SpoolFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+impl<R: Read> SpoolReader<R> {
+    /// Creates a new reader that spools the bytes of the inner reader in memory.
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            storage: Storage::Memory(Vec::new()),
+            len: 0,
+            position: 0,
+            eof: false,
+            memory_limit: None,
+        }
+    }
+
+    /// Creates a new reader that moves the spooled bytes into a temporary file once
+    /// they exceed the given amount of bytes.
+    pub fn with_memory_limit(inner: R, memory_limit: u64) -> Self {
+        Self {
+            memory_limit: Some(memory_limit),
+            ..Self::new(inner)
+        }
+    }
+
+    /// The amount of bytes that were read from the inner reader so far.
+    pub fn spooled_len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns `true` if the spooled bytes were moved into a temporary file.
+    pub fn is_spilled(&self) -> bool {
+        matches!(self.storage, Storage::File(_))
+    }
+
+    /// Reads from the inner reader until the given amount of bytes is spooled or the
+    /// inner reader ends.
+    fn fill_to(&mut self, target: u64) -> std::io::Result<()> {
+        let mut chunk = vec![0_u8; SPOOL_CHUNK_SIZE];
+        while self.len < target && !self.eof {
+            let read = match self.inner.read(&mut chunk) {
+                Ok(0) => {
+                    self.eof = true;
+                    break;
+                }
+                Ok(read) => read,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+            self.append(&chunk[..read])?;
+        }
+        Ok(())
+    }
+
+    fn append(&mut self, data: &[u8]) -> std::io::Result<()> {
+        let len = self.len + u64::try_from(data.len()).unwrap_or(u64::MAX);
+        match &mut self.storage {
+            Storage::Memory(buffer) if self.memory_limit.map_or(true, |limit| len <= limit) => {
+                buffer.extend_from_slice(data);
+            }
+            Storage::Memory(buffer) => {
+                let mut spool = SpoolFile::create()?;
+                spool.file.write_all(buffer)?;
+                spool.file.write_all(data)?;
+                self.storage = Storage::File(spool);
+            }
+            Storage::File(spool) => {
+                spool.file.seek(SeekFrom::End(0))?;
+                spool.file.write_all(data)?;
+            }
+        }
+        self.len = len;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for SpoolReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.position >= self.len {
+            self.fill_to(self.position.saturating_add(1))?;
+        }
+        if self.position >= self.len {
+            return Ok(0);
+        }
+
+        let available = usize::try_from(self.len - self.position).unwrap_or(usize::MAX);
+        let len = available.min(buf.len());
+        let read = match &mut self.storage {
+            Storage::Memory(buffer) => {
+                let start = usize::try_from(self.position).unwrap_or(usize::MAX);
+                buf[..len].copy_from_slice(&buffer[start..start + len]);
+                len
+            }
+            Storage::File(spool) => {
+                spool.file.seek(SeekFrom::Start(self.position))?;
+                spool.file.read(&mut buf[..len])?
+            }
+        };
+        self.position += u64::try_from(read).unwrap_or(u64::MAX);
+        Ok(read)
+    }
+}
+
+impl<R: Read> Seek for SpoolReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+            SeekFrom::End(offset) => {
+                self.fill_to(u64::MAX)?;
+                self.len.checked_add_signed(offset)
+            }
+        };
+        self.position = position.ok_or_else(|| {
+            std::io::Error::new(
+                ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+        Ok(self.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Result;
+
+    #[test]
+    fn test_spool_reader() -> Result<()> {
+        let data: Vec<u8> = (0..200_000_u32)
+            .map(|i| u8::try_from(i % 251).unwrap_or_default())
+            .collect();
+
+        for mut reader in [
+            SpoolReader::new(data.as_slice()),
+            SpoolReader::with_memory_limit(data.as_slice(), 1000),
+        ] {
+            let mut buf = [0_u8; 4];
+            reader.seek(SeekFrom::Start(150_000))?;
+            reader.read_exact(&mut buf)?;
+            assert_eq!(buf[..], data[150_000..150_004]);
+            assert!(reader.spooled_len() < 200_000);
+
+            reader.seek(SeekFrom::Start(10))?;
+            reader.read_exact(&mut buf)?;
+            assert_eq!(buf[..], data[10..14]);
+
+            assert_eq!(reader.seek(SeekFrom::End(-4))?, 199_996);
+            let mut rest = Vec::new();
+            reader.read_to_end(&mut rest)?;
+            assert_eq!(rest[..], data[199_996..]);
+            assert_eq!(reader.read(&mut buf)?, 0);
+        }
+
+        let mut reader = SpoolReader::with_memory_limit(data.as_slice(), 1000);
+        reader.seek(SeekFrom::Start(500))?;
+        assert_eq!(reader.read(&mut [0_u8; 4])?, 4);
+        assert!(reader.is_spilled());
+
+        Ok(())
+    }
+}
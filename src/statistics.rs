@@ -0,0 +1,125 @@
+//! The track statistics that muxers like mkvmerge write into the tags.
+
+use std::time::Duration;
+
+use crate::{SimpleTag, Tag, TrackEntry};
+
+/// The statistics of a track, that were written by the muxer into the `_STATISTICS_*`
+/// simple tags of the track. Returned by `TrackEntry::muxer_statistics()`.
+///
+/// Values that are missing or can't be parsed are `None`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MuxerStatistics {
+    /// The average bitrate of the track in bits per second (`BPS`).
+    pub bits_per_second: Option<u64>,
+    /// The duration of the track (`DURATION`).
+    pub duration: Option<Duration>,
+    /// The number of frames of the track (`NUMBER_OF_FRAMES`).
+    pub frame_count: Option<u64>,
+    /// The size of all frames of the track in bytes (`NUMBER_OF_BYTES`).
+    pub byte_count: Option<u64>,
+    /// The application that wrote the statistics (`_STATISTICS_WRITING_APP`).
+    pub writing_app: Option<String>,
+    /// The date the statistics were written, as stored in the file
+    /// (`_STATISTICS_WRITING_DATE_UTC`).
+    pub writing_date_utc: Option<String>,
+}
+
+impl MuxerStatistics {
+    /// Collects the statistics of the given track from the tags. Returns `None` if the
+    /// tags contain no statistics for the track.
+    pub(crate) fn from_tags(track: &TrackEntry, tags: &[Tag]) -> Option<Self> {
+        let mut statistics = Self::default();
+        let mut found = false;
+
+        let simple_tags = tags
+            .iter()
+            .filter(|tag| {
+                tag.targets().and_then(|targets| targets.tag_track_uid())
+                    == Some(track.track_uid().get())
+            })
+            .flat_map(|tag| tag.simple_tags());
+        for simple_tag in simple_tags {
+            found |= statistics.apply(simple_tag);
+        }
+
+        match found {
+            true => Some(statistics),
+            false => None,
+        }
+    }
+
+    /// Applies the value of a simple tag. Returns `true` if the tag is a statistics tag.
+    fn apply(&mut self, simple_tag: &SimpleTag) -> bool {
+        // Older versions of mkvmerge appended the language to the name, like `BPS-eng`.
+        let name = simple_tag.name();
+        let name = name.strip_suffix("-eng").unwrap_or(name);
+        let value = simple_tag.string().map(str::trim);
+
+        match name {
+            "BPS" => self.bits_per_second = value.and_then(|v| v.parse().ok()),
+            "DURATION" => self.duration = value.and_then(parse_duration),
+            "NUMBER_OF_FRAMES" => self.frame_count = value.and_then(|v| v.parse().ok()),
+            "NUMBER_OF_BYTES" => self.byte_count = value.and_then(|v| v.parse().ok()),
+            "_STATISTICS_WRITING_APP" => self.writing_app = value.map(str::to_owned),
+            "_STATISTICS_WRITING_DATE_UTC" => self.writing_date_utc = value.map(str::to_owned),
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Parses a duration in the format `HH:MM:SS.nnnnnnnnn`. The fraction is optional and can
+/// have fewer than nine digits.
+fn parse_duration(value: &str) -> Option<Duration> {
+    let mut parts = value.splitn(3, ':');
+    let hours: u64 = parts.next()?.parse().ok()?;
+    let minutes: u64 = parts.next()?.parse().ok()?;
+    let seconds = parts.next()?;
+    let (seconds, fraction) = seconds.split_once('.').unwrap_or((seconds, ""));
+    let seconds: u64 = seconds.parse().ok()?;
+    if minutes >= 60 || seconds >= 60 {
+        return None;
+    }
+
+    let mut nanos: u32 = 0;
+    for (i, digit) in fraction.chars().enumerate() {
+        let digit = digit.to_digit(10)?;
+        if i < 9 {
+            nanos = nanos * 10 + digit;
+        }
+    }
+    let padding = 9_usize.saturating_sub(fraction.chars().count());
+    let nanos = nanos * 10_u32.pow(u32::try_from(padding).ok()?);
+
+    let seconds = hours
+        .checked_mul(3600)?
+        .checked_add(minutes * 60)?
+        .checked_add(seconds)?;
+    Some(Duration::new(seconds, nanos))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(
+            parse_duration("01:02:03.456000000"),
+            Some(Duration::new(3723, 456_000_000))
+        );
+        assert_eq!(
+            parse_duration("00:00:01.5"),
+            Some(Duration::new(1, 500_000_000))
+        );
+        assert_eq!(
+            parse_duration("00:00:00.1234567891"),
+            Some(Duration::new(0, 123_456_789))
+        );
+        assert_eq!(parse_duration("00:00:05"), Some(Duration::new(5, 0)));
+        assert_eq!(parse_duration("00:61:00"), None);
+        assert_eq!(parse_duration("00:00:0x"), None);
+        assert_eq!(parse_duration("5"), None);
+    }
+}
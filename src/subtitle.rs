@@ -0,0 +1,276 @@
+//! Extraction of the events of subtitle tracks.
+
+use std::io::{Read, Seek};
+
+use crate::{codec_ids, DemuxError, Frame, MatroskaFile, Result};
+
+/// The format of a subtitle track, as defined by its codec ID.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SubtitleFormat {
+    /// Plain UTF-8 text (`S_TEXT/UTF8`), as used by SRT files.
+    Utf8,
+    /// Advanced SubStation Alpha (`S_TEXT/ASS`).
+    Ass,
+    /// SubStation Alpha (`S_TEXT/SSA`).
+    Ssa,
+    /// Presentation Graphic Stream of Blu-ray discs (`S_HDMV/PGS`).
+    Pgs,
+}
+
+impl SubtitleFormat {
+    /// Returns the format of the given codec ID, or `None` if the codec is not supported.
+    pub fn from_codec_id(codec_id: &str) -> Option<Self> {
+        match codec_id {
+            codec_ids::S_TEXT_UTF8 => Some(SubtitleFormat::Utf8),
+            codec_ids::S_TEXT_ASS => Some(SubtitleFormat::Ass),
+            codec_ids::S_TEXT_SSA => Some(SubtitleFormat::Ssa),
+            codec_ids::S_HDMV_PGS => Some(SubtitleFormat::Pgs),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` for the text based formats.
+    pub fn is_text(&self) -> bool {
+        !matches!(self, SubtitleFormat::Pgs)
+    }
+}
+
+/// A single event of a subtitle track.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubtitleEvent {
+    /// The format of the track the event belongs to.
+    pub format: SubtitleFormat,
+    /// The start of the event in nanoseconds.
+    pub start_ns: i64,
+    /// The duration of the event in nanoseconds, taken from the `BlockDuration` or the
+    /// `DefaultDuration` of the track.
+    ///
+    /// PGS events usually have no duration, since they are ended by the next event.
+    pub duration_ns: Option<u64>,
+    /// The payload of the event as stored in the block.
+    ///
+    /// For ASS and SSA it contains the fields `ReadOrder, Layer, Style, Name, MarginL,
+    /// MarginR, MarginV, Effect, Text`, without the start and end time. For PGS it
+    /// contains the segments of a display set.
+    pub payload: Vec<u8>,
+}
+
+impl SubtitleEvent {
+    /// The payload as text. Returns `None` for PGS or if the payload is not valid UTF-8.
+    pub fn text(&self) -> Option<&str> {
+        match self.format.is_text() {
+            true => std::str::from_utf8(&self.payload).ok(),
+            false => None,
+        }
+    }
+
+    /// The order in which the event was read from the original ASS or SSA file.
+    pub fn read_order(&self) -> Option<u64> {
+        match self.format {
+            SubtitleFormat::Ass | SubtitleFormat::Ssa => {
+                self.text()?.split(',').next()?.parse().ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// Formats the event as an entry of a SRT file with the given sequence number.
+    ///
+    /// Only available for `S_TEXT/UTF8` tracks. Events without a duration end at their start.
+    pub fn to_srt(&self, index: usize) -> Option<String> {
+        if self.format != SubtitleFormat::Utf8 {
+            return None;
+        }
+        let text = self.text()?;
+        let (start, end) = self.bounds_ms();
+        Some(format!(
+            "{}\n{} --> {}\n{}\n\n",
+            index,
+            srt_time(start),
+            srt_time(end),
+            text.trim_end()
+        ))
+    }
+
+    /// Formats the event as the `Dialogue` line of the `[Events]` section of an ASS or SSA
+    /// file by re-inserting the start and end time.
+    ///
+    /// Only available for `S_TEXT/ASS` and `S_TEXT/SSA` tracks. Events without a duration
+    /// end at their start.
+    pub fn to_ass_dialogue(&self) -> Option<String> {
+        if !matches!(self.format, SubtitleFormat::Ass | SubtitleFormat::Ssa) {
+            return None;
+        }
+        // The ReadOrder is dropped, the Layer (or Marked for SSA) is kept.
+        let mut fields = self.text()?.splitn(3, ',');
+        let _read_order = fields.next()?;
+        let layer = fields.next()?;
+        let rest = fields.next()?;
+        let (start, end) = self.bounds_ms();
+        Some(format!(
+            "Dialogue: {},{},{},{}",
+            layer,
+            ass_time(start),
+            ass_time(end),
+            rest
+        ))
+    }
+
+    /// The start and end of the event in milliseconds, clamped to 0.
+    fn bounds_ms(&self) -> (u64, u64) {
+        let start = u64::try_from(self.start_ns).unwrap_or(0);
+        let end = start.saturating_add(self.duration_ns.unwrap_or(0));
+        (start / 1_000_000, end / 1_000_000)
+    }
+}
+
+/// Formats milliseconds as `HH:MM:SS,mmm`.
+fn srt_time(ms: u64) -> String {
+    format!(
+        "{:02}:{:02}:{:02},{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        ms % 1000
+    )
+}
+
+/// Formats milliseconds as `H:MM:SS.cc`.
+fn ass_time(ms: u64) -> String {
+    format!(
+        "{}:{:02}:{:02}.{:02}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        ms % 1000 / 10
+    )
+}
+
+/// Iterates over the events of a subtitle track, returned by
+/// `MatroskaFile::subtitle_events()`.
+///
+/// The events are read from the current position of the file. Frames of other tracks
+/// are skipped.
+#[derive(Debug)]
+pub struct SubtitleEvents<'a, R: Read + Seek> {
+    file: &'a mut MatroskaFile<R>,
+    track: u64,
+    format: SubtitleFormat,
+    default_duration: Option<u64>,
+    frame: Frame,
+    done: bool,
+}
+
+impl<'a, R: Read + Seek> SubtitleEvents<'a, R> {
+    /// The format of the subtitle track.
+    pub fn format(&self) -> SubtitleFormat {
+        self.format
+    }
+
+    /// The `CodecPrivate` of the track. For ASS and SSA it contains the script header
+    /// with the `[Script Info]` and `[V4+ Styles]` sections.
+    pub fn header(&self) -> Option<&[u8]> {
+        self.file
+            .track_by_number(self.track)
+            .and_then(|t| t.codec_private())
+    }
+
+    /// The `CodecPrivate` of the track as text. Returns `None` for PGS or if the header
+    /// is not valid UTF-8.
+    pub fn header_text(&self) -> Option<&str> {
+        match self.format.is_text() {
+            true => std::str::from_utf8(self.header()?).ok(),
+            false => None,
+        }
+    }
+
+    fn read_event(&mut self) -> Result<Option<SubtitleEvent>> {
+        loop {
+            if !self.file.next_frame(&mut self.frame)? {
+                return Ok(None);
+            }
+            if self.frame.track != self.file.remapped_track(self.track) || self.frame.is_virtual {
+                continue;
+            }
+
+            let start_ns = i64::try_from(self.frame.time().as_nanos())?;
+            let duration_ns = match self.frame.duration_time() {
+                Some(duration) => Some(u64::try_from(duration.as_nanos())?),
+                None => self.default_duration,
+            };
+            return Ok(Some(SubtitleEvent {
+                format: self.format,
+                start_ns,
+                duration_ns,
+                payload: self.frame.data.clone(),
+            }));
+        }
+    }
+}
+
+impl<'a, R: Read + Seek> Iterator for SubtitleEvents<'a, R> {
+    type Item = Result<SubtitleEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.read_event() {
+            Ok(Some(event)) => Some(Ok(event)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+impl<R: Read + Seek> MatroskaFile<R> {
+    /// Returns an iterator over the events of the given subtitle track, starting at the
+    /// current position of the file.
+    ///
+    /// Supports `S_TEXT/UTF8`, `S_TEXT/ASS`, `S_TEXT/SSA` and `S_HDMV/PGS` tracks. The
+    /// payloads are returned as stored in the container.
+    pub fn subtitle_events(&mut self, track: u64) -> Result<SubtitleEvents<'_, R>> {
+        let entry = self
+            .track_by_number(track)
+            .ok_or(DemuxError::TrackNotFound(track))?;
+        let format = SubtitleFormat::from_codec_id(entry.codec_id())
+            .ok_or_else(|| DemuxError::UnsupportedSubtitleCodec(entry.codec_id().to_owned()))?;
+        let default_duration = entry.default_duration().map(|d| d.get());
+
+        Ok(SubtitleEvents {
+            file: self,
+            track,
+            format,
+            default_duration,
+            frame: Frame::default(),
+            done: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ass_dialogue() {
+        let event = SubtitleEvent {
+            format: SubtitleFormat::Ass,
+            start_ns: 3_723_456_000_000,
+            duration_ns: Some(1_500_000_000),
+            payload: b"7,0,Default,,0,0,0,,Hello, world".to_vec(),
+        };
+        assert_eq!(event.read_order(), Some(7));
+        assert_eq!(
+            event.to_ass_dialogue().as_deref(),
+            Some("Dialogue: 0,1:02:03.45,1:02:04.95,Default,,0,0,0,,Hello, world")
+        );
+        assert_eq!(event.to_srt(1), None);
+    }
+}
@@ -0,0 +1,127 @@
+//! Timestamps that carry their timestamp scale.
+
+use std::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+    num::NonZeroU64,
+    time::Duration,
+};
+
+/// The default timestamp scale of a segment (timestamps are expressed in milliseconds).
+const DEFAULT_TIMESTAMP_SCALE: u64 = 1_000_000;
+
+/// A timestamp expressed in ticks of a timestamp scale.
+///
+/// Matroska stores most timestamps in ticks of the `TimestampScale` of the segment, while
+/// chapters use nanoseconds. Timestamps with different scales can be compared directly.
+#[derive(Clone, Copy, Debug)]
+pub struct Timestamp {
+    ticks: i64,
+    scale: NonZeroU64,
+}
+
+impl Timestamp {
+    /// Creates a timestamp from ticks of the given timestamp scale in nanoseconds.
+    pub fn new(ticks: i64, scale: NonZeroU64) -> Self {
+        Self { ticks, scale }
+    }
+
+    /// Creates a timestamp from nanoseconds.
+    pub fn from_nanos(nanos: i64) -> Self {
+        Self {
+            ticks: nanos,
+            scale: NonZeroU64::MIN,
+        }
+    }
+
+    /// The raw ticks of the timestamp.
+    pub fn ticks(&self) -> i64 {
+        self.ticks
+    }
+
+    /// The timestamp scale in nanoseconds per tick.
+    pub fn scale(&self) -> NonZeroU64 {
+        self.scale
+    }
+
+    /// The timestamp in nanoseconds.
+    pub fn as_nanos(&self) -> i128 {
+        i128::from(self.ticks) * i128::from(self.scale.get())
+    }
+
+    /// The timestamp as a `Duration`. Negative timestamps are clamped to zero.
+    pub fn as_duration(&self) -> Duration {
+        let nanos = u64::try_from(self.as_nanos().max(0)).unwrap_or(u64::MAX);
+        Duration::from_nanos(nanos)
+    }
+
+    /// Converts the timestamp to ticks of another timestamp scale. The ticks are rounded
+    /// towards zero.
+    pub fn rescale(&self, scale: NonZeroU64) -> Self {
+        let ticks = self.as_nanos() / i128::from(scale.get());
+        let ticks = i64::try_from(ticks).unwrap_or(if ticks < 0 { i64::MIN } else { i64::MAX });
+        Self { ticks, scale }
+    }
+}
+
+impl Default for Timestamp {
+    fn default() -> Self {
+        Self {
+            ticks: 0,
+            scale: NonZeroU64::new(DEFAULT_TIMESTAMP_SCALE).unwrap_or(NonZeroU64::MIN),
+        }
+    }
+}
+
+impl From<Duration> for Timestamp {
+    fn from(duration: Duration) -> Self {
+        Self::from_nanos(i64::try_from(duration.as_nanos()).unwrap_or(i64::MAX))
+    }
+}
+
+impl PartialEq for Timestamp {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_nanos() == other.as_nanos()
+    }
+}
+
+impl Eq for Timestamp {}
+
+impl PartialOrd for Timestamp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Timestamp {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_nanos().cmp(&other.as_nanos())
+    }
+}
+
+impl Hash for Timestamp {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_nanos().hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamp_conversions() {
+        let scale = Timestamp::default().scale();
+        assert_eq!(scale.get(), 1_000_000);
+        let timestamp = Timestamp::new(1500, scale);
+        assert_eq!(timestamp.as_nanos(), 1_500_000_000);
+        assert_eq!(timestamp.as_duration(), Duration::from_millis(1500));
+        assert_eq!(Timestamp::from(Duration::from_millis(1500)), timestamp);
+        assert_eq!(
+            Timestamp::from_nanos(1_500_999_999).rescale(scale),
+            timestamp
+        );
+        assert_eq!(Timestamp::new(-10, scale).as_duration(), Duration::ZERO);
+        assert!(Timestamp::new(-1, scale) < Timestamp::from_nanos(0));
+    }
+}
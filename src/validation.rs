@@ -0,0 +1,825 @@
+//! Validation of the file structure against the Matroska specification.
+
+use std::{
+    collections::{HashMap, HashSet},
+    io::{Read, Seek, SeekFrom},
+};
+
+use crate::{
+    block::probe_block_track,
+    ebml::{
+        collect_children, next_element, try_find_string, try_find_unsigned, try_next_raw_element,
+        ElementData, HeaderLimits,
+    },
+    element_id::ElementId,
+    map_unexpected_eof, parse_element_header, MatroskaIndex, Result,
+};
+
+/// The severity of a specification violation.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Severity {
+    /// The file deviates from a recommendation of the specification, but can be demuxed.
+    Warning,
+    /// The file violates a requirement of the specification.
+    Error,
+}
+
+/// The kind of specification violation.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ValidationIssueKind {
+    /// A mandatory element is missing.
+    MissingElement,
+    /// The value of an element is outside of its allowed range.
+    ValueOutOfRange,
+    /// An element was found inside a parent element it is not allowed in.
+    WrongPlacement,
+    /// An element that may only occur once was found multiple times.
+    DuplicateElement,
+    /// Multiple tracks share the same TrackUID.
+    DuplicateTrackUid,
+    /// Multiple tracks share the same TrackNumber.
+    DuplicateTrackNumber,
+    /// A block references a track that is not declared inside the tracks element.
+    UnknownTrack,
+    /// The timestamp of a cluster is smaller than the timestamp of the previous cluster.
+    NonMonotonicClusterTimestamp,
+    /// An entry of the `SeekHead` or the `Position` of a cluster doesn't point at the
+    /// element it refers to.
+    WrongPosition,
+}
+
+/// A specification violation found inside the file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ValidationIssue {
+    /// The severity of the violation.
+    pub severity: Severity,
+    /// The kind of the violation.
+    pub kind: ValidationIssueKind,
+    /// The element that violates the specification.
+    pub element: ElementId,
+    /// The offset of the element header. For missing elements and simple values,
+    /// this is the offset of the parent element header.
+    pub offset: u64,
+    /// A human readable description of the violation.
+    pub message: String,
+}
+
+/// The result of the validation of a file.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ValidationReport {
+    /// The violations in the order they were found inside the file.
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Returns `true` if no errors were found. Warnings are ignored.
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    /// Returns all violations with the severity `Error`.
+    pub fn errors(&self) -> impl Iterator<Item = &ValidationIssue> + '_ {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Error)
+    }
+
+    /// Returns all violations with the severity `Warning`.
+    pub fn warnings(&self) -> impl Iterator<Item = &ValidationIssue> + '_ {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Warning)
+    }
+}
+
+/// Elements that are allowed as direct children of the segment.
+const SEGMENT_CHILDREN: &[ElementId] = &[
+    ElementId::SeekHead,
+    ElementId::Info,
+    ElementId::Tracks,
+    ElementId::Cluster,
+    ElementId::Cues,
+    ElementId::Attachments,
+    ElementId::Chapters,
+    ElementId::Tags,
+];
+
+/// Elements that are allowed as direct children of a cluster.
+const CLUSTER_CHILDREN: &[ElementId] = &[
+    ElementId::Timestamp,
+    ElementId::Position,
+    ElementId::PrevSize,
+    ElementId::SilentTracks,
+    ElementId::SimpleBlock,
+    ElementId::BlockGroup,
+];
+
+/// Flags that only allow the values 0 and 1.
+const TRACK_FLAGS: &[ElementId] = &[
+    ElementId::FlagEnabled,
+    ElementId::FlagDefault,
+    ElementId::FlagForced,
+    ElementId::FlagHearingImpaired,
+    ElementId::FlagVisualImpaired,
+    ElementId::FlagTextDescriptions,
+    ElementId::FlagOriginal,
+    ElementId::FlagCommentary,
+    ElementId::FlagLacing,
+];
+
+/// A check of a cluster that was validated on its own. The checks that depend on the
+/// previous clusters are replayed in file order by the validation of the segment.
+#[derive(Clone, Debug)]
+pub(crate) enum ClusterCheck {
+    Issue(ValidationIssue),
+    Timestamp {
+        position: u64,
+        timestamp: u64,
+    },
+    BlockTrack {
+        position: u64,
+        element: ElementId,
+        track: u64,
+    },
+}
+
+/// Validates the segment described by the index.
+pub(crate) fn validate_file<R: Read + Seek>(
+    r: &mut R,
+    index: &MatroskaIndex,
+) -> Result<ValidationReport> {
+    validate_file_with_checks(r, index, None)
+}
+
+/// Validates the segment described by the index. Clusters whose checks are given are not
+/// read again, their checks are replayed instead.
+pub(crate) fn validate_file_with_checks<R: Read + Seek>(
+    r: &mut R,
+    index: &MatroskaIndex,
+    cluster_checks: Option<&HashMap<u64, Vec<ClusterCheck>>>,
+) -> Result<ValidationReport> {
+    let mut validator = Validator::new(index);
+    validator.cluster_checks = cluster_checks;
+
+    validator.validate_header();
+    validator.validate_seek_head(r);
+    validator.validate_segment(r)?;
+
+    Ok(ValidationReport {
+        issues: validator.issues,
+    })
+}
+
+/// Validates the clusters that start inside the given range on their own. Returns the
+/// checks of every cluster by the offset of its header.
+#[cfg(feature = "parallel")]
+pub(crate) fn collect_cluster_checks<R: Read + Seek>(
+    r: &mut R,
+    index: &MatroskaIndex,
+    range: std::ops::Range<u64>,
+) -> Result<HashMap<u64, Vec<ClusterCheck>>> {
+    let mut validator = Validator::new(index);
+    let mut checks = HashMap::new();
+
+    let mut position = range.start;
+    while position < range.end {
+        let (element_id, size) = parse_element_header(r, Some(position), HeaderLimits::default())?;
+        let offset = r.stream_position()?;
+        if element_id == ElementId::Cluster {
+            validator.recorded = Some(Vec::new());
+            validator.validate_cluster(r, position, offset, size)?;
+            checks.insert(position, validator.recorded.take().unwrap_or_default());
+        }
+        position = offset.saturating_add(size);
+    }
+
+    Ok(checks)
+}
+
+struct Validator<'a> {
+    index: &'a MatroskaIndex,
+    issues: Vec<ValidationIssue>,
+    previous_cluster_timestamp: Option<u64>,
+    /// Unknown tracks are only reported once.
+    reported_tracks: HashSet<u64>,
+    /// The checks of clusters that were already validated.
+    cluster_checks: Option<&'a HashMap<u64, Vec<ClusterCheck>>>,
+    /// Records the checks of a cluster instead of applying them.
+    recorded: Option<Vec<ClusterCheck>>,
+}
+
+impl<'a> Validator<'a> {
+    fn new(index: &'a MatroskaIndex) -> Self {
+        Self {
+            index,
+            issues: Vec::new(),
+            previous_cluster_timestamp: None,
+            reported_tracks: HashSet::new(),
+            cluster_checks: None,
+            recorded: None,
+        }
+    }
+
+    fn push(
+        &mut self,
+        severity: Severity,
+        kind: ValidationIssueKind,
+        element: ElementId,
+        offset: u64,
+        message: String,
+    ) {
+        let issue = ValidationIssue {
+            severity,
+            kind,
+            element,
+            offset,
+            message,
+        };
+        match self.recorded.as_mut() {
+            Some(recorded) => recorded.push(ClusterCheck::Issue(issue)),
+            None => self.issues.push(issue),
+        }
+    }
+
+    fn replay(&mut self, checks: &[ClusterCheck]) {
+        for check in checks {
+            match check {
+                ClusterCheck::Issue(issue) => self.issues.push(issue.clone()),
+                ClusterCheck::Timestamp {
+                    position,
+                    timestamp,
+                } => self.validate_cluster_timestamp(*position, *timestamp),
+                ClusterCheck::BlockTrack {
+                    position,
+                    element,
+                    track,
+                } => self.validate_block_track(*position, *element, *track),
+            }
+        }
+    }
+
+    fn validate_header(&mut self) {
+        let header = &self.index.ebml_header;
+        let mut issues = Vec::new();
+
+        if let Some(version) = header.version.filter(|v| *v != 1) {
+            issues.push((
+                ElementId::EbmlVersion,
+                format!("EBMLVersion must be 1, but is {}", version),
+            ));
+        }
+        if let Some(read_version) = header.read_version.filter(|v| *v != 1) {
+            issues.push((
+                ElementId::EbmlReadVersion,
+                format!("EBMLReadVersion must be 1, but is {}", read_version),
+            ));
+        }
+        if header.max_id_length != 4 {
+            issues.push((
+                ElementId::EbmlMaxIdLength,
+                format!("EBMLMaxIDLength must be 4, but is {}", header.max_id_length),
+            ));
+        }
+        if header.max_size_length == 0 {
+            issues.push((
+                ElementId::EbmlMaxSizeLength,
+                "EBMLMaxSizeLength must not be 0".to_owned(),
+            ));
+        }
+        if header.doc_type_version == 0 {
+            issues.push((
+                ElementId::DocTypeVersion,
+                "DocTypeVersion must not be 0".to_owned(),
+            ));
+        }
+        if header.doc_type_read_version == 0
+            || header.doc_type_read_version > header.doc_type_version
+        {
+            issues.push((
+                ElementId::DocTypeReadVersion,
+                format!(
+                    "DocTypeReadVersion must be between 1 and the DocTypeVersion {}, but is {}",
+                    header.doc_type_version, header.doc_type_read_version
+                ),
+            ));
+        }
+
+        for (element, message) in issues {
+            self.push(
+                Severity::Error,
+                ValidationIssueKind::ValueOutOfRange,
+                element,
+                0,
+                message,
+            );
+        }
+    }
+
+    fn validate_seek_head<R: Read + Seek>(&mut self, r: &mut R) {
+        // Entries repaired while parsing are reported with their original offset.
+        for mismatch in self.index.seek_head_mismatches() {
+            self.push_seek_entry(mismatch.element_id, mismatch.offset, mismatch.found);
+        }
+
+        let mut entries: Vec<(ElementId, u64)> = self
+            .index
+            .seek_head
+            .iter()
+            .filter(|(element_id, _)| **element_id != ElementId::Unknown)
+            .map(|(element_id, offset)| (*element_id, *offset))
+            .collect();
+        entries.sort_unstable_by_key(|(_, offset)| *offset);
+
+        for (element_id, offset) in entries {
+            let found = parse_element_header(r, Some(offset), HeaderLimits::default())
+                .ok()
+                .map(|(found, _)| found);
+            if found != Some(element_id) {
+                self.push_seek_entry(element_id, offset, found);
+            }
+        }
+    }
+
+    fn push_seek_entry(&mut self, element_id: ElementId, offset: u64, found: Option<ElementId>) {
+        let message = match found {
+            Some(found) => format!(
+                "the SeekHead entry of {:?} points at {:?}",
+                element_id, found
+            ),
+            None => format!(
+                "the SeekHead entry of {:?} doesn't point at an element",
+                element_id
+            ),
+        };
+        self.push(
+            Severity::Error,
+            ValidationIssueKind::WrongPosition,
+            element_id,
+            offset,
+            message,
+        );
+    }
+
+    fn validate_segment<R: Read + Seek>(&mut self, r: &mut R) -> Result<()> {
+        let start = self.index.segment_data_offset;
+        let end = self
+            .index
+            .segment_data_size
+            .map_or(u64::MAX, |size| start.saturating_add(size));
+
+        let mut counts: HashMap<ElementId, u64> = HashMap::new();
+
+        r.seek(SeekFrom::Start(start))?;
+        while r.stream_position()? < end {
+            let position = r.stream_position()?;
+            let (element_id, element_data) = match next_element_or_eof(r)? {
+                Some(element) => element,
+                None => break,
+            };
+
+            let count = counts.entry(element_id).or_default();
+            *count += 1;
+            let count = *count;
+
+            let max_count = match element_id {
+                ElementId::Info
+                | ElementId::Tracks
+                | ElementId::Cues
+                | ElementId::Attachments
+                | ElementId::Chapters => 1,
+                ElementId::SeekHead => 2,
+                _ => u64::MAX,
+            };
+            if count > max_count {
+                self.push(
+                    Severity::Error,
+                    ValidationIssueKind::DuplicateElement,
+                    element_id,
+                    position,
+                    format!(
+                        "{:?} must not occur more than {} times",
+                        element_id, max_count
+                    ),
+                );
+            }
+
+            match element_id {
+                ElementId::Info => {
+                    if let ElementData::Location { offset, size } = element_data {
+                        self.validate_info(r, position, offset, size)?;
+                    }
+                }
+                ElementId::Tracks => {
+                    if let ElementData::Location { offset, size } = element_data {
+                        self.validate_tracks(r, offset, size)?;
+                    }
+                }
+                ElementId::Cluster => {
+                    let checks = self
+                        .cluster_checks
+                        .and_then(|cluster_checks| cluster_checks.get(&position));
+                    match (checks, &element_data) {
+                        (Some(checks), _) => self.replay(checks),
+                        (None, ElementData::Location { offset, size }) => {
+                            self.validate_cluster(r, position, *offset, *size)?;
+                            continue;
+                        }
+                        (None, _) => {}
+                    }
+                }
+                ElementId::Void | ElementId::Crc32 | ElementId::Unknown => {}
+                _ if SEGMENT_CHILDREN.contains(&element_id) => {}
+                _ => {
+                    self.push(
+                        Severity::Error,
+                        ValidationIssueKind::WrongPlacement,
+                        element_id,
+                        position,
+                        format!("{:?} is not allowed inside the segment", element_id),
+                    );
+                }
+            }
+
+            if let ElementData::Location { offset, size } = element_data {
+                if size == u64::MAX {
+                    break;
+                }
+                r.seek(SeekFrom::Start(offset + size))?;
+            }
+        }
+
+        if !counts.contains_key(&ElementId::Info) {
+            self.push(
+                Severity::Error,
+                ValidationIssueKind::MissingElement,
+                ElementId::Info,
+                start,
+                "the segment has no Info element".to_owned(),
+            );
+        }
+
+        Ok(())
+    }
+
+    fn validate_info<R: Read + Seek>(
+        &mut self,
+        r: &mut R,
+        position: u64,
+        offset: u64,
+        size: u64,
+    ) -> Result<()> {
+        let fields = collect_children(r, offset, size, HeaderLimits::default())?;
+        for element_id in [ElementId::MuxingApp, ElementId::WritingApp] {
+            if !fields.iter().any(|(id, _)| *id == element_id) {
+                self.missing(element_id, position, "Info");
+            }
+        }
+
+        if let Some(duration) = self.index.info.duration {
+            if duration <= 0.0 {
+                self.push(
+                    Severity::Error,
+                    ValidationIssueKind::ValueOutOfRange,
+                    ElementId::Duration,
+                    position,
+                    format!("Duration must be larger than 0, but is {}", duration),
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_tracks<R: Read + Seek>(&mut self, r: &mut R, offset: u64, size: u64) -> Result<()> {
+        let mut track_numbers: HashSet<u64> = HashSet::new();
+        let mut track_uids: HashSet<u64> = HashSet::new();
+
+        let end = offset + size;
+        r.seek(SeekFrom::Start(offset))?;
+        while r.stream_position()? < end {
+            let position = r.stream_position()?;
+            let (element_id, element_data) = next_element(r, HeaderLimits::default())?;
+
+            let (entry_offset, entry_size) = match element_data {
+                ElementData::Location { offset, size } if size != u64::MAX => (offset, size),
+                _ => continue,
+            };
+
+            match element_id {
+                ElementId::TrackEntry => {
+                    let fields =
+                        collect_children(r, entry_offset, entry_size, HeaderLimits::default())?;
+                    self.validate_track_entry(
+                        position,
+                        &fields,
+                        &mut track_numbers,
+                        &mut track_uids,
+                    )?;
+                }
+                ElementId::Void | ElementId::Crc32 | ElementId::Unknown => {}
+                _ => {
+                    self.push(
+                        Severity::Error,
+                        ValidationIssueKind::WrongPlacement,
+                        element_id,
+                        position,
+                        format!("{:?} is not allowed inside Tracks", element_id),
+                    );
+                }
+            }
+
+            r.seek(SeekFrom::Start(entry_offset + entry_size))?;
+        }
+
+        Ok(())
+    }
+
+    fn validate_track_entry(
+        &mut self,
+        position: u64,
+        fields: &[(ElementId, ElementData)],
+        track_numbers: &mut HashSet<u64>,
+        track_uids: &mut HashSet<u64>,
+    ) -> Result<()> {
+        match try_find_unsigned(fields, ElementId::TrackNumber)? {
+            None => self.missing(ElementId::TrackNumber, position, "TrackEntry"),
+            Some(0) => self.zero(ElementId::TrackNumber, position),
+            Some(track_number) => {
+                if !track_numbers.insert(track_number) {
+                    self.push(
+                        Severity::Error,
+                        ValidationIssueKind::DuplicateTrackNumber,
+                        ElementId::TrackNumber,
+                        position,
+                        format!(
+                            "the TrackNumber {} is used by multiple tracks",
+                            track_number
+                        ),
+                    );
+                }
+            }
+        }
+
+        match try_find_unsigned(fields, ElementId::TrackUid)? {
+            None => self.missing(ElementId::TrackUid, position, "TrackEntry"),
+            Some(0) => self.zero(ElementId::TrackUid, position),
+            Some(track_uid) => {
+                if !track_uids.insert(track_uid) {
+                    self.push(
+                        Severity::Error,
+                        ValidationIssueKind::DuplicateTrackUid,
+                        ElementId::TrackUid,
+                        position,
+                        format!("the TrackUID {} is used by multiple tracks", track_uid),
+                    );
+                }
+            }
+        }
+
+        match try_find_unsigned(fields, ElementId::TrackType)? {
+            None => self.missing(ElementId::TrackType, position, "TrackEntry"),
+            Some(track_type) => {
+                if ![1, 2, 3, 0x10, 0x11, 0x12, 0x20, 0x21].contains(&track_type) {
+                    self.push(
+                        Severity::Error,
+                        ValidationIssueKind::ValueOutOfRange,
+                        ElementId::TrackType,
+                        position,
+                        format!("unknown TrackType {}", track_type),
+                    );
+                }
+            }
+        }
+
+        if try_find_string(fields, ElementId::CodecId)?.is_none() {
+            self.missing(ElementId::CodecId, position, "TrackEntry");
+        }
+
+        for id in [
+            ElementId::DefaultDuration,
+            ElementId::DefaultDecodedFieldDuration,
+        ] {
+            if try_find_unsigned(fields, id)? == Some(0) {
+                self.zero(id, position);
+            }
+        }
+
+        for flag in TRACK_FLAGS {
+            if let Some(value) = try_find_unsigned(fields, *flag)?.filter(|v| *v > 1) {
+                self.push(
+                    Severity::Error,
+                    ValidationIssueKind::ValueOutOfRange,
+                    *flag,
+                    position,
+                    format!("{:?} must be 0 or 1, but is {}", flag, value),
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_cluster<R: Read + Seek>(
+        &mut self,
+        r: &mut R,
+        position: u64,
+        offset: u64,
+        size: u64,
+    ) -> Result<()> {
+        // Clusters of live streams have an unknown size and end with the next top level element.
+        let end = (size != u64::MAX).then(|| offset + size);
+        let mut has_timestamp = false;
+        let mut is_first_child = true;
+
+        r.seek(SeekFrom::Start(offset))?;
+        loop {
+            let child_position = r.stream_position()?;
+            if end.is_some_and(|end| child_position >= end) {
+                break;
+            }
+
+            let (element_id, element_data) = match next_element_or_eof(r)? {
+                Some(element) => element,
+                None => break,
+            };
+
+            if end.is_none() && SEGMENT_CHILDREN.contains(&element_id) {
+                r.seek(SeekFrom::Start(child_position))?;
+                break;
+            }
+
+            match element_id {
+                ElementId::Timestamp => {
+                    has_timestamp = true;
+                    if !is_first_child {
+                        self.push(
+                            Severity::Warning,
+                            ValidationIssueKind::WrongPlacement,
+                            element_id,
+                            child_position,
+                            "Timestamp should be the first element of the cluster".to_owned(),
+                        );
+                    }
+                    if let ElementData::Unsigned(timestamp) = element_data {
+                        self.validate_cluster_timestamp(position, timestamp);
+                    }
+                }
+                ElementId::Position => {
+                    if let ElementData::Unsigned(cluster_position) = element_data {
+                        self.validate_cluster_position(position, cluster_position);
+                    }
+                }
+                ElementId::SimpleBlock => {
+                    if let ElementData::Location { offset, .. } = element_data {
+                        r.seek(SeekFrom::Start(offset))?;
+                        let track = probe_block_track(r)?;
+                        self.validate_block_track(child_position, element_id, track);
+                    }
+                }
+                ElementId::BlockGroup => {
+                    if let ElementData::Location { offset, size } = element_data {
+                        let fields = collect_children(r, offset, size, HeaderLimits::default())?;
+                        match fields.iter().find(|(id, _)| *id == ElementId::Block) {
+                            Some((_, ElementData::Location { offset, .. })) => {
+                                r.seek(SeekFrom::Start(*offset))?;
+                                let track = probe_block_track(r)?;
+                                self.validate_block_track(child_position, ElementId::Block, track);
+                            }
+                            _ => self.missing(ElementId::Block, child_position, "BlockGroup"),
+                        }
+                    }
+                }
+                ElementId::Void | ElementId::Crc32 | ElementId::Unknown => {}
+                _ if CLUSTER_CHILDREN.contains(&element_id) => {}
+                _ => {
+                    self.push(
+                        Severity::Error,
+                        ValidationIssueKind::WrongPlacement,
+                        element_id,
+                        child_position,
+                        format!("{:?} is not allowed inside a Cluster", element_id),
+                    );
+                }
+            }
+
+            if !matches!(
+                element_id,
+                ElementId::Void | ElementId::Crc32 | ElementId::Unknown
+            ) {
+                is_first_child = false;
+            }
+
+            if let ElementData::Location { offset, size } = element_data {
+                if size == u64::MAX {
+                    break;
+                }
+                r.seek(SeekFrom::Start(offset + size))?;
+            }
+        }
+
+        if !has_timestamp {
+            self.missing(ElementId::Timestamp, position, "Cluster");
+        }
+
+        if let Some(end) = end {
+            r.seek(SeekFrom::Start(end))?;
+        }
+
+        Ok(())
+    }
+
+    /// Live streams write a `Position` of 0.
+    fn validate_cluster_position(&mut self, position: u64, cluster_position: u64) {
+        let expected = position.saturating_sub(self.index.segment_data_offset);
+        if cluster_position != 0 && cluster_position != expected {
+            self.push(
+                Severity::Warning,
+                ValidationIssueKind::WrongPosition,
+                ElementId::Position,
+                position,
+                format!(
+                    "the Position of the Cluster is {}, but the Cluster is at {}",
+                    cluster_position, expected
+                ),
+            );
+        }
+    }
+
+    fn validate_cluster_timestamp(&mut self, position: u64, timestamp: u64) {
+        if let Some(recorded) = self.recorded.as_mut() {
+            recorded.push(ClusterCheck::Timestamp {
+                position,
+                timestamp,
+            });
+            return;
+        }
+        if let Some(previous) = self.previous_cluster_timestamp {
+            if timestamp < previous {
+                self.push(
+                    Severity::Error,
+                    ValidationIssueKind::NonMonotonicClusterTimestamp,
+                    ElementId::Timestamp,
+                    position,
+                    format!(
+                        "the cluster timestamp {} is smaller than the previous cluster timestamp {}",
+                        timestamp, previous
+                    ),
+                );
+            }
+        }
+        self.previous_cluster_timestamp = Some(timestamp);
+    }
+
+    fn validate_block_track(&mut self, position: u64, element: ElementId, track: u64) {
+        if let Some(recorded) = self.recorded.as_mut() {
+            recorded.push(ClusterCheck::BlockTrack {
+                position,
+                element,
+                track,
+            });
+            return;
+        }
+        let is_known = self
+            .index
+            .tracks
+            .iter()
+            .any(|t| t.track_number.get() == track);
+
+        if !is_known && self.reported_tracks.insert(track) {
+            self.push(
+                Severity::Error,
+                ValidationIssueKind::UnknownTrack,
+                element,
+                position,
+                format!("the block references the undeclared track {}", track),
+            );
+        }
+    }
+
+    fn missing(&mut self, element: ElementId, position: u64, parent: &str) {
+        self.push(
+            Severity::Error,
+            ValidationIssueKind::MissingElement,
+            element,
+            position,
+            format!("{} has no {:?} element", parent, element),
+        );
+    }
+
+    fn zero(&mut self, element: ElementId, position: u64) {
+        self.push(
+            Severity::Error,
+            ValidationIssueKind::ValueOutOfRange,
+            element,
+            position,
+            format!("{:?} must not be 0", element),
+        );
+    }
+}
+
+/// Reads the next element. Returns `None` if the file ends before the element. A file that
+/// ends inside the element and all other IO errors are returned as errors.
+fn next_element_or_eof<R: Read + Seek>(r: &mut R) -> Result<Option<(ElementId, ElementData)>> {
+    let element = try_next_raw_element(r, HeaderLimits::default()).map_err(map_unexpected_eof)?;
+    Ok(element.map(|(_, element_id, _, element_data)| (element_id, element_data)))
+}
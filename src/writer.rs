@@ -0,0 +1,269 @@
+//! Encoding of EBML elements.
+
+use std::{
+    convert::TryFrom,
+    io::{Cursor, ErrorKind, Read, Seek, SeekFrom},
+};
+
+use crate::{
+    ebml::{element_id_length, try_next_element_header, HeaderLimits},
+    element_id::element_id_to_id,
+    DemuxError, ElementId, Result,
+};
+
+// The raw Element IDs of the elements that are written.
+pub(crate) const VOID_ID: u32 = element_id_to_id(ElementId::Void);
+pub(crate) const CRC32_ID: u32 = element_id_to_id(ElementId::Crc32);
+pub(crate) const SEEK_HEAD_ID: u32 = element_id_to_id(ElementId::SeekHead);
+pub(crate) const SEEK_ID: u32 = element_id_to_id(ElementId::Seek);
+pub(crate) const SEEK_ID_ID: u32 = element_id_to_id(ElementId::SeekId);
+pub(crate) const SEEK_POSITION_ID: u32 = element_id_to_id(ElementId::SeekPosition);
+pub(crate) const INFO_ID: u32 = element_id_to_id(ElementId::Info);
+pub(crate) const TITLE_ID: u32 = element_id_to_id(ElementId::Title);
+pub(crate) const TRACKS_ID: u32 = element_id_to_id(ElementId::Tracks);
+pub(crate) const TRACK_ENTRY_ID: u32 = element_id_to_id(ElementId::TrackEntry);
+pub(crate) const TRACK_NUMBER_ID: u32 = element_id_to_id(ElementId::TrackNumber);
+pub(crate) const NAME_ID: u32 = element_id_to_id(ElementId::Name);
+pub(crate) const LANGUAGE_ID: u32 = element_id_to_id(ElementId::Language);
+pub(crate) const LANGUAGE_BCP47_ID: u32 = element_id_to_id(ElementId::LanguageIetf);
+pub(crate) const FLAG_DEFAULT_ID: u32 = element_id_to_id(ElementId::FlagDefault);
+pub(crate) const FLAG_FORCED_ID: u32 = element_id_to_id(ElementId::FlagForced);
+pub(crate) const TAGS_ID: u32 = element_id_to_id(ElementId::Tags);
+pub(crate) const TAG_ID: u32 = element_id_to_id(ElementId::Tag);
+pub(crate) const TARGETS_ID: u32 = element_id_to_id(ElementId::Targets);
+pub(crate) const TARGET_TYPE_VALUE_ID: u32 = element_id_to_id(ElementId::TargetTypeValue);
+pub(crate) const TAG_TRACK_UID_ID: u32 = element_id_to_id(ElementId::TagTrackUid);
+pub(crate) const TAG_EDITION_UID_ID: u32 = element_id_to_id(ElementId::TagEditionUid);
+pub(crate) const TAG_CHAPTER_UID_ID: u32 = element_id_to_id(ElementId::TagChapterUid);
+pub(crate) const TAG_ATTACHMENT_UID_ID: u32 = element_id_to_id(ElementId::TagAttachmentUid);
+pub(crate) const SIMPLE_TAG_ID: u32 = element_id_to_id(ElementId::SimpleTag);
+pub(crate) const TAG_NAME_ID: u32 = element_id_to_id(ElementId::TagName);
+pub(crate) const TAG_STRING_ID: u32 = element_id_to_id(ElementId::TagString);
+pub(crate) const TAG_BINARY_ID: u32 = element_id_to_id(ElementId::TagBinary);
+pub(crate) const SEGMENT_ID: u32 = element_id_to_id(ElementId::Segment);
+pub(crate) const CHAPTERS_ID: u32 = element_id_to_id(ElementId::Chapters);
+pub(crate) const CLUSTER_ID: u32 = element_id_to_id(ElementId::Cluster);
+pub(crate) const TIMESTAMP_ID: u32 = element_id_to_id(ElementId::Timestamp);
+pub(crate) const SIMPLE_BLOCK_ID: u32 = element_id_to_id(ElementId::SimpleBlock);
+pub(crate) const BLOCK_GROUP_ID: u32 = element_id_to_id(ElementId::BlockGroup);
+pub(crate) const BLOCK_ID: u32 = element_id_to_id(ElementId::Block);
+pub(crate) const REFERENCE_BLOCK_ID: u32 = element_id_to_id(ElementId::ReferenceBlock);
+pub(crate) const CUES_ID: u32 = element_id_to_id(ElementId::Cues);
+pub(crate) const CUE_POINT_ID: u32 = element_id_to_id(ElementId::CuePoint);
+pub(crate) const CUE_TIME_ID: u32 = element_id_to_id(ElementId::CueTime);
+pub(crate) const CUE_TRACK_POSITIONS_ID: u32 = element_id_to_id(ElementId::CueTrackPositions);
+pub(crate) const CUE_TRACK_ID: u32 = element_id_to_id(ElementId::CueTrack);
+pub(crate) const CUE_CLUSTER_POSITION_ID: u32 = element_id_to_id(ElementId::CueClusterPosition);
+pub(crate) const CUE_RELATIVE_POSITION_ID: u32 = element_id_to_id(ElementId::CueRelativePosition);
+
+/// The largest data size that can be encoded in eight bytes. All ones are reserved for
+/// unknown sizes.
+const MAX_DATA_SIZE: u64 = (1 << 56) - 2;
+
+/// Returns the number of bytes needed to encode the data size.
+pub(crate) fn size_length(size: u64) -> usize {
+    let mut length = 1;
+    // All ones in the VINT_DATA are reserved for unknown sizes.
+    while length < 8 && size >= (1 << (7 * length)) - 1 {
+        length += 1;
+    }
+    length
+}
+
+/// Writes the raw Element ID.
+pub(crate) fn write_id(out: &mut Vec<u8>, id: u32) {
+    let length = usize::try_from(element_id_length(id)).unwrap_or(4).min(4);
+    out.extend_from_slice(&id.to_be_bytes()[4 - length..]);
+}
+
+/// Writes the data size with the given number of bytes.
+pub(crate) fn write_size(out: &mut Vec<u8>, size: u64, length: usize) -> Result<()> {
+    if !(1..=8).contains(&length) || size > MAX_DATA_SIZE || size_length(size) > length {
+        return Err(DemuxError::InvalidEbmlDataSize);
+    }
+    let marker = 1_u64 << (7 * length);
+    out.extend_from_slice(&(size | marker).to_be_bytes()[8 - length..]);
+    Ok(())
+}
+
+/// Writes an element with the given data.
+pub(crate) fn write_element(out: &mut Vec<u8>, id: u32, data: &[u8]) -> Result<()> {
+    let size = u64::try_from(data.len())?;
+    write_id(out, id);
+    write_size(out, size, size_length(size))?;
+    out.extend_from_slice(data);
+    Ok(())
+}
+
+/// Writes a `Void` element that occupies exactly the given number of bytes, including its
+/// header. A single byte can't be filled by an element.
+pub(crate) fn write_void(out: &mut Vec<u8>, total_size: u64) -> Result<()> {
+    let length = (1..=8)
+        .find(|&length| {
+            let header = 1 + u64::try_from(length).unwrap_or(8);
+            total_size >= header && size_length(total_size - header) <= length
+        })
+        .ok_or(DemuxError::InvalidEbmlDataSize)?;
+    let size = total_size - 1 - u64::try_from(length)?;
+
+    write_id(out, VOID_ID);
+    write_size(out, size, length)?;
+    out.resize(out.len() + usize::try_from(size)?, 0);
+    Ok(())
+}
+
+/// A child of a master element that was read from the file, which is written back as it is
+/// unless it is modified.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct RawElement {
+    pub(crate) id: u32,
+    pub(crate) data: Vec<u8>,
+}
+
+impl RawElement {
+    /// Creates an unsigned integer element with the shortest encoding of the value.
+    pub(crate) fn unsigned(id: u32, value: u64) -> Self {
+        let bytes = value.to_be_bytes();
+        let skip = bytes.iter().take(7).take_while(|&&byte| byte == 0).count();
+        Self {
+            id,
+            data: bytes[skip..].to_vec(),
+        }
+    }
+
+    /// Creates a string element.
+    pub(crate) fn string(id: u32, value: &str) -> Self {
+        Self {
+            id,
+            data: value.as_bytes().to_vec(),
+        }
+    }
+
+    /// Creates a master element from its children.
+    pub(crate) fn master(id: u32, children: &[RawElement]) -> Result<Self> {
+        Ok(Self {
+            id,
+            data: encode_children(children)?,
+        })
+    }
+
+    /// The value of an unsigned integer element.
+    pub(crate) fn as_unsigned(&self) -> Option<u64> {
+        if self.data.len() > 8 {
+            return None;
+        }
+        Some(
+            self.data
+                .iter()
+                .fold(0_u64, |value, &byte| (value << 8) | u64::from(byte)),
+        )
+    }
+
+    /// Encodes the element including its header.
+    pub(crate) fn encode(&self, out: &mut Vec<u8>) -> Result<()> {
+        write_element(out, self.id, &self.data)
+    }
+
+    /// Encodes the element like `encode()`, but with a data size that uses the given
+    /// number of bytes.
+    pub(crate) fn encode_with_size_length(&self, out: &mut Vec<u8>, length: usize) -> Result<()> {
+        write_id(out, self.id);
+        write_size(out, u64::try_from(self.data.len())?, length)?;
+        out.extend_from_slice(&self.data);
+        Ok(())
+    }
+}
+
+/// Encodes the given elements one after another.
+pub(crate) fn encode_children(children: &[RawElement]) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    for child in children {
+        child.encode(&mut out)?;
+    }
+    Ok(out)
+}
+
+/// Reads the element at the given offset. Returns the element and its end.
+pub(crate) fn read_element<R: Read + Seek>(r: &mut R, offset: u64) -> Result<(RawElement, u64)> {
+    r.seek(SeekFrom::Start(offset))?;
+    let (id, element_id, size) = try_next_element_header(r, HeaderLimits::default())?
+        .ok_or_else(|| DemuxError::IoError(ErrorKind::UnexpectedEof.into()))?;
+    if size == u64::MAX {
+        return Err(DemuxError::UnknownSizeNotAllowed(element_id));
+    }
+
+    // The size is not trusted, so the buffer only grows with the data that was read.
+    let mut data = Vec::new();
+    r.take(size).read_to_end(&mut data)?;
+    if u64::try_from(data.len())? != size {
+        return Err(DemuxError::IoError(ErrorKind::UnexpectedEof.into()));
+    }
+    let end = r.stream_position()?;
+    Ok((RawElement { id, data }, end))
+}
+
+/// Parses the children of a master element.
+pub(crate) fn parse_children(data: &[u8]) -> Result<Vec<RawElement>> {
+    let mut cursor = Cursor::new(data);
+    let mut children = Vec::new();
+    while let Some((id, element_id, size)) =
+        try_next_element_header(&mut cursor, HeaderLimits::default())?
+    {
+        if size == u64::MAX {
+            return Err(DemuxError::UnknownSizeNotAllowed(element_id));
+        }
+        let start = cursor.position();
+        let end = start
+            .checked_add(size)
+            .ok_or(DemuxError::InvalidEbmlDataSize)?;
+        let data = data
+            .get(usize::try_from(start)?..usize::try_from(end)?)
+            .ok_or(DemuxError::InvalidEbmlDataSize)?;
+        children.push(RawElement {
+            id,
+            data: data.to_vec(),
+        });
+        cursor.set_position(end);
+    }
+    Ok(children)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_elements() -> Result<()> {
+        let mut out = Vec::new();
+        RawElement::unsigned(0xD7, 1).encode(&mut out)?;
+        RawElement::unsigned(0x2AD7B1, 1_000_000).encode(&mut out)?;
+        RawElement::string(0x7BA9, "Title").encode(&mut out)?;
+        assert_eq!(
+            out,
+            [
+                0xD7, 0x81, 0x01, 0x2A, 0xD7, 0xB1, 0x83, 0x0F, 0x42, 0x40, 0x7B, 0xA9, 0x85, b'T',
+                b'i', b't', b'l', b'e'
+            ]
+        );
+
+        assert_eq!(size_length(126), 1);
+        assert_eq!(size_length(127), 2);
+
+        for total_size in [2, 3, 129, 130, 16_000] {
+            let mut out = Vec::new();
+            write_void(&mut out, total_size)?;
+            assert_eq!(u64::try_from(out.len())?, total_size);
+        }
+        assert!(write_void(&mut Vec::new(), 1).is_err());
+
+        let element = RawElement::unsigned(0x88, 0);
+        assert_eq!(element.data, [0x00]);
+        assert_eq!(element.as_unsigned(), Some(0));
+
+        assert_eq!(SEEK_HEAD_ID, 0x114D9B74);
+        assert_eq!(TAG_EDITION_UID_ID, 0x63C9);
+        assert_eq!(element_id_to_id(ElementId::Custom(0x1234)), 0x1234);
+
+        Ok(())
+    }
+}
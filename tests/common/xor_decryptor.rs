@@ -0,0 +1,20 @@
+// A test decryptor that is shared by the unit and integration tests. It's included into a
+// module that provides `ContentDecryptor` and `DemuxError`.
+
+use super::{ContentDecryptor, DemuxError};
+
+/// Replaces every byte with its XOR with the first byte of the IV.
+pub(crate) struct XorDecryptor;
+
+impl ContentDecryptor for XorDecryptor {
+    fn decrypt(
+        &self,
+        key_id: &[u8],
+        iv: [u8; 8],
+        data: &mut [u8],
+    ) -> std::result::Result<(), DemuxError> {
+        assert_eq!(key_id, b"key");
+        data.iter_mut().for_each(|byte| *byte ^= iv[0]);
+        Ok(())
+    }
+}
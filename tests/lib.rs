@@ -1,10 +1,32 @@
-use std::{fs::File, num::NonZeroU64};
+use std::{
+    fs::File,
+    io::{Cursor, Read, Seek, SeekFrom},
+    num::NonZeroU64,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
 use matroska_demuxer::{
-    ContentEncodingType, Frame, MatrixCoefficients, MatroskaFile, Primaries, TrackEntry, TrackType,
-    TransferCharacteristics,
+    codec_ids, is_matroska, AdtsFramer, AesSettingsCipherMode, AnnexBConverter, Av1Config,
+    BlockKind, ByteRangeReader, ByteRangeSource, ChannelLayout, ContainerKind, ContentCompAlgo,
+    ContentDecryptor, ContentEncAlgo, ContentEncodingScope, ContentEncodingType, DemuxError,
+    DemuxEvent, ElementId, ElementType, Emphasis, ErrorCode, FlacConfig, Frame,
+    FrameEncryptionInfo, FramePool, IvfWriter, MatrixCoefficients, MatroskaEditor, MatroskaFile,
+    MatroskaIndex, MatroskaReader, MediaSegment, MuxerStatistics, OpusConfig, ParserSettings,
+    PooledFrame, PreRollSeek, Primaries, RawBlock, SampleSeek, SeekHeadMismatch, SeekHeadPolicy,
+    Severity, SkippedElement, Timestamp, TimestampIssueKind, TimestampPolicy, TrackEntry,
+    TrackError, TrackPlaneType, TrackType, TransferCharacteristics, TranslateCodec,
+    UnknownTrackPolicy, ValidationIssueKind, Vp9Config,
 };
 
+mod xor_decryptor {
+    include!("common/xor_decryptor.rs");
+}
+use xor_decryptor::XorDecryptor;
+
 #[test]
 pub fn parse_simple_mkv() {
     let file = File::open("tests/data/simple.mkv").unwrap();
@@ -45,399 +67,3448 @@ pub fn parse_simple_mkv() {
 }
 
 #[test]
-pub fn parse_hdr_mkv() {
-    let file = File::open("tests/data/hdr.mkv").unwrap();
+pub fn language_ietf_simple_mkv() {
+    let file = File::open("tests/data/simple.mkv").unwrap();
+    let mkv = MatroskaFile::open(file).unwrap();
+
+    assert_eq!(mkv.tracks()[0].language(), None);
+    assert_eq!(mkv.tracks()[0].language_ietf(), Some("en"));
+}
+
+#[test]
+pub fn timestamp_simple_mkv() {
+    let file = File::open("tests/data/simple.mkv").unwrap();
     let mut mkv = MatroskaFile::open(file).unwrap();
 
-    let video_tracks: Vec<TrackEntry> = mkv
-        .tracks()
-        .iter()
-        .filter(|t| t.track_type() == TrackType::Video)
-        .cloned()
-        .collect();
+    let chapters = mkv.chapters().unwrap()[0].chapter_atoms();
+    assert_eq!(chapters[0].start_time().as_duration(), Duration::ZERO);
 
-    let video = video_tracks[0].video().unwrap();
+    let mut frame = Frame::default();
+    mkv.seek_time(Duration::from_millis(3)).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 3);
+    assert_eq!(frame.time(), Timestamp::from(Duration::from_millis(3)));
+}
 
-    assert_eq!(video.pixel_width().get(), 3840);
-    assert_eq!(video.pixel_height().get(), 2160);
+#[test]
+pub fn seek_ns_simple_mkv() {
+    let file = File::open("tests/data/simple.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+    let mut frame = Frame::default();
 
-    let colour = video.colour().unwrap();
+    mkv.seek_ns(3_000_000).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 3);
+
+    mkv.seek_duration(Duration::from_millis(3)).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 3);
+}
+
+#[test]
+pub fn seek_outcome_simple_mkv() {
+    let file = File::open("tests/data/simple.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+
+    let outcome = mkv.seek(3).unwrap();
+    assert_eq!(outcome.requested, 3);
+    assert_eq!(outcome.reached, Some(3));
+    assert!(outcome.used_cues);
+    assert_eq!(outcome.cluster_offset, Some(13261));
+
+    let outcome = mkv.seek(1_000_000).unwrap();
+    assert_eq!(outcome.reached, None);
+    assert_eq!(outcome.cluster_offset, None);
+}
+
+#[test]
+pub fn duration_time_simple_mkv() {
+    let file = File::open("tests/data/simple.mkv").unwrap();
+    let mkv = MatroskaFile::open(file).unwrap();
 
     assert_eq!(
-        colour.transfer_characteristics().unwrap(),
-        TransferCharacteristics::Bt2100
-    );
-    assert_eq!(
-        colour.matrix_coefficients().unwrap(),
-        MatrixCoefficients::Bt2020Ncl
+        mkv.info().duration_time().unwrap().as_duration(),
+        Duration::from_millis(1018)
     );
-    assert_eq!(colour.primaries().unwrap(), Primaries::Bt2020);
+}
 
-    let metadata = colour.mastering_metadata().unwrap();
+#[test]
+pub fn report_progress_simple_mkv() {
+    let file = File::open("tests/data/simple.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+    assert_eq!(mkv.segment_size(), Some(47669));
 
-    assert!((1000.0 - metadata.luminance_max().unwrap()).abs() < f64::EPSILON);
-    assert!((0.009999999776482582 - metadata.luminance_min().unwrap()).abs() < f64::EPSILON);
+    let clusters = Arc::new(AtomicUsize::new(0));
+    let counter = clusters.clone();
+    mkv.set_progress_callback(move |progress| {
+        assert!(progress.fraction().unwrap() < 1.0);
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
 
     let mut frame = Frame::default();
-    let mut count = 0;
+    while mkv.next_frame(&mut frame).unwrap() {}
+    assert_eq!(clusters.load(Ordering::SeqCst), 1);
+    assert!(mkv.progress().unwrap().fraction().unwrap() > 0.9);
+}
+
+#[test]
+pub fn parse_block_groups_mkv() {
+    let file = File::open("tests/data/block_groups.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+
+    let mut frames = Vec::new();
+    let mut frame = Frame::default();
     while mkv.next_frame(&mut frame).unwrap() {
-        count += 1;
+        frames.push(frame.clone());
     }
-    assert_eq!(count, 9);
+    assert_eq!(frames.len(), 14);
+
+    assert_eq!(frames[0].track, 1);
+    assert_eq!(frames[0].data, b"V0key");
+    assert_eq!(frames[0].duration, None);
+    assert_eq!(frames[0].block_kind, BlockKind::Block);
+    assert_eq!(frames[0].block_group_range, Some(299..312));
+    assert_eq!(frames[1].block_kind, BlockKind::SimpleBlock);
+    assert_eq!(frames[1].block_group_range, None);
+
+    // Frames of block groups are keyframes unless they contain a ReferenceBlock.
+    let video: Vec<Option<bool>> = frames
+        .iter()
+        .filter(|f| f.track == 1)
+        .map(|f| f.is_keyframe)
+        .collect();
+    assert_eq!(video, [Some(true), Some(false), Some(false), Some(true)]);
 
-    mkv.seek(0).unwrap();
-    assert!(mkv.next_frame(&mut frame).unwrap());
-    assert_eq!(frame.timestamp, 0);
+    let laced: Vec<u64> = frames[1..4].iter().map(|f| f.timestamp).collect();
+    assert_eq!(laced, [0, 20, 40]);
 
-    mkv.seek(45).unwrap();
-    assert!(mkv.next_frame(&mut frame).unwrap());
-    assert_eq!(frame.timestamp, 45);
+    // The audio track has a CodecDelay of 6.5 ms.
+    let presentation: Vec<i64> = frames[1..4]
+        .iter()
+        .map(|f| f.presentation_timestamp)
+        .collect();
+    assert_eq!(presentation, [-6, 14, 34]);
+    assert_eq!(frames[1].signed_timestamp, 0);
+    assert_eq!(frames[0].presentation_timestamp, 0);
 
-    mkv.seek(1_000_000).unwrap();
-    assert!(!mkv.next_frame(&mut frame).unwrap());
+    let subtitles: Vec<(u64, Option<u64>)> = frames
+        .iter()
+        .filter(|f| f.track == 3)
+        .map(|f| (f.timestamp, f.duration))
+        .collect();
+    assert_eq!(
+        subtitles,
+        [(50, Some(500)), (300, Some(400)), (1100, Some(300))]
+    );
 }
 
 #[test]
-pub fn parse_test1_mkv() {
-    let file = File::open("tests/data/test1.mkv").unwrap();
-    let mut mkv = MatroskaFile::open(file).unwrap();
+pub fn default_duration_fallback_block_groups_mkv() {
+    let file = File::open("tests/data/block_groups.mkv").unwrap();
+    let settings = ParserSettings::default().default_duration_fallback(true);
+    let mut mkv = MatroskaFile::open_with_settings(file, &settings).unwrap();
 
-    assert_eq!(mkv.ebml_header().version(), None);
-    assert_eq!(mkv.ebml_header().read_version(), None);
-    assert_eq!(mkv.ebml_header().doc_type(), "matroska");
-    assert_eq!(mkv.ebml_header().doc_type_version(), 2);
-    assert_eq!(mkv.ebml_header().doc_type_read_version(), 2);
-    assert_eq!(mkv.ebml_header().max_id_length(), 4);
-    assert_eq!(mkv.ebml_header().max_size_length(), 8);
+    let mut durations: Vec<(u64, Option<u64>)> = Vec::new();
+    let mut frame = Frame::default();
+    while mkv.next_frame(&mut frame).unwrap() {
+        if !durations.iter().any(|(track, _)| *track == frame.track) {
+            durations.push((frame.track, frame.duration));
+        }
+        if frame.track == 3 {
+            // The BlockDuration takes precedence, the subtitle track has no DefaultDuration.
+            assert!(frame.duration.is_some_and(|d| d >= 300));
+        }
+    }
+    durations.sort_unstable();
+    assert_eq!(durations, [(1, Some(40)), (2, Some(20)), (3, Some(500))]);
+}
 
+#[test]
+pub fn nanosecond_timestamps_block_groups_mkv() {
+    let settings = ParserSettings::default().nanosecond_timestamps(true);
+    let mut ticks = MatroskaFile::open(File::open("tests/data/block_groups.mkv").unwrap()).unwrap();
+    let mut nanos = MatroskaFile::open_with_settings(
+        File::open("tests/data/block_groups.mkv").unwrap(),
+        &settings,
+    )
+    .unwrap();
+    let scale = ticks.info().timestamp_scale().get();
+
+    let mut tick_frame = Frame::default();
+    let mut nano_frame = Frame::default();
+    let mut frames = 0;
+    loop {
+        let tick_event = ticks.next_event(&mut tick_frame).unwrap();
+        let nano_event = nanos.next_event(&mut nano_frame).unwrap();
+        match (tick_event, nano_event) {
+            (Some(DemuxEvent::Frame), Some(DemuxEvent::Frame)) => {
+                frames += 1;
+                assert_eq!(nano_frame.timestamp, tick_frame.timestamp * scale);
+                assert_eq!(
+                    nano_frame.cluster_timestamp,
+                    tick_frame.cluster_timestamp * scale
+                );
+                assert_eq!(nano_frame.duration, tick_frame.duration.map(|d| d * scale));
+                assert_eq!(nano_frame.relative_timestamp, tick_frame.relative_timestamp);
+                assert_eq!(nano_frame.time(), tick_frame.time());
+            }
+            (
+                Some(DemuxEvent::ClusterStart { timestamp: tick }),
+                Some(DemuxEvent::ClusterStart { timestamp: nano }),
+            ) => assert_eq!(nano, tick * scale),
+            (
+                Some(DemuxEvent::Gap {
+                    timestamp: tick, ..
+                }),
+                Some(DemuxEvent::Gap {
+                    timestamp: nano, ..
+                }),
+            ) => assert_eq!(nano, tick * scale),
+            (None, None) => break,
+            (tick_event, nano_event) => {
+                assert_eq!(format!("{tick_event:?}"), format!("{nano_event:?}"))
+            }
+        }
+    }
+    assert!(frames > 0);
+
+    let tick_cues = ticks.cue_points().unwrap();
+    let nano_cues = nanos.cue_points().unwrap();
+    for (tick_cue, nano_cue) in tick_cues.iter().zip(nano_cues) {
+        assert_eq!(nano_cue.time(), tick_cue.time() * scale);
+        assert_eq!(nano_cue.cue_time(), tick_cue.cue_time());
+    }
+
+    ticks.seek(0).unwrap();
+    nanos.seek(0).unwrap();
+    let tick_block = ticks.next_raw_block().unwrap().unwrap();
+    let nano_block = nanos.next_raw_block().unwrap().unwrap();
     assert_eq!(
-        mkv.info().timestamp_scale(),
-        NonZeroU64::new(1000000).unwrap()
-    );
-    assert!((87336.0 - mkv.info().duration().unwrap()).abs() < f64::EPSILON);
-    assert_eq!(
-        mkv.info().muxing_app(),
-        "libebml2 v0.10.0 + libmatroska2 v0.10.1"
+        nano_block.timestamp,
+        tick_block.timestamp * i64::try_from(scale).unwrap()
     );
-    assert_eq!(mkv.info().date_utc().unwrap(), 304068183000000000);
-    assert_eq!(mkv.info().writing_app(), "mkclean 0.5.5 ru from libebml v1.0.0 + libmatroska v1.0.0 + mkvmerge v4.1.1 ('Bouncin' Back') built on Jul  3 2010 22:54:08");
+    assert_eq!(nano_block.time(), tick_block.time());
+}
 
-    assert_eq!(mkv.tracks().len(), 2);
+#[test]
+pub fn combine_laced_frames_block_groups_mkv() {
+    let file = File::open("tests/data/block_groups.mkv").unwrap();
+    let settings = ParserSettings::default().combine_laced_frames(true);
+    let mut mkv = MatroskaFile::open_with_settings(file, &settings).unwrap();
 
+    let mut frames = Vec::new();
     let mut frame = Frame::default();
-    while mkv.next_frame(&mut frame).unwrap() {}
+    while mkv.next_frame(&mut frame).unwrap() {
+        frames.push(frame.clone());
+    }
+    assert_eq!(frames.len(), 12);
+
+    assert!(frames[0].lace_ranges.is_empty());
+    assert_eq!(frames[1].track, 2);
+    assert_eq!(frames[1].timestamp, 0);
+    assert_eq!(frames[1].lace_index, None);
+    assert_eq!(frames[1].data, b"a0a1a2");
+    assert_eq!(frames[1].lace_ranges, [0..2, 2..4, 4..6]);
+    assert!(frames[2].lace_ranges.is_empty());
+    assert_eq!(frames[2].data, b"V1");
+}
 
-    mkv.seek(0).unwrap();
-    assert!(mkv.next_frame(&mut frame).unwrap());
-    assert_eq!(frame.timestamp, 0);
+#[test]
+pub fn header_stripping_mkv() {
+    let file = File::open("tests/data/header_stripping.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
 
-    mkv.seek(180).unwrap();
+    let encoding = &mkv.tracks()[0].content_encodings().unwrap()[0];
+    assert_eq!(
+        encoding.scope(),
+        ContentEncodingScope::FRAME | ContentEncodingScope::PRIVATE
+    );
+    let compression = encoding.compression().unwrap();
+    assert_eq!(compression.algo(), ContentCompAlgo::HeaderStripping);
+    assert_eq!(compression.settings(), Some(&[0_u8, 0][..]));
+
+    // The CodecPrivate was stripped as well.
+    let track = &mkv.tracks()[0];
+    assert_eq!(track.codec_private(), Some(&[1_u8, 0x64][..]));
+    let codec_private = track.decoded_codec_private().unwrap().unwrap();
+    assert_eq!(codec_private.as_ref(), [0, 0, 1, 0x64]);
+    assert!(mkv.tracks()[1].decoded_codec_private().unwrap().is_none());
+
+    let mut frames = Vec::new();
+    let mut frame = Frame::default();
+    while mkv.next_frame(&mut frame).unwrap() {
+        frames.push((frame.track, frame.data.clone()));
+    }
+    assert_eq!(
+        frames,
+        [
+            (1, b"\0\0\x01key".to_vec()),
+            (1, b"\0\0\x01a".to_vec()),
+            (1, b"\0\0\x01bc".to_vec()),
+            (2, b"a0".to_vec()),
+        ]
+    );
+
+    // Every frame of a combined lace gets its header back.
+    let file = File::open("tests/data/header_stripping.mkv").unwrap();
+    let settings = ParserSettings::default().combine_laced_frames(true);
+    let mut mkv = MatroskaFile::open_with_settings(file, &settings).unwrap();
     assert!(mkv.next_frame(&mut frame).unwrap());
-    assert_eq!(frame.timestamp, 192);
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.data, b"\0\0\x01a\0\0\x01bc");
+    assert_eq!(frame.lace_ranges, [0..4, 4..9]);
+}
 
-    mkv.seek(1_000_000).unwrap();
-    assert!(!mkv.next_frame(&mut frame).unwrap());
+#[test]
+#[cfg(not(feature = "bzip2"))]
+pub fn unsupported_compression_mkv() {
+    let file = File::open("tests/data/compressed.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+
+    let mut frame = Frame::default();
+    let err = mkv.next_frame(&mut frame).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::UnsupportedCompression);
 }
 
 #[test]
-pub fn parse_test2_mkv() {
-    let file = File::open("tests/data/test2.mkv").unwrap();
+pub fn zlib_compression_mkv() {
+    let file = File::open("tests/data/zlib.mkv").unwrap();
     let mut mkv = MatroskaFile::open(file).unwrap();
 
-    assert_eq!(mkv.ebml_header().version(), None);
-    assert_eq!(mkv.ebml_header().read_version(), None);
-    assert_eq!(mkv.ebml_header().doc_type(), "matroska");
-    assert_eq!(mkv.ebml_header().doc_type_version(), 2);
-    assert_eq!(mkv.ebml_header().doc_type_read_version(), 2);
-    assert_eq!(mkv.ebml_header().max_id_length(), 4);
-    assert_eq!(mkv.ebml_header().max_size_length(), 8);
+    // Zlib compressed frames are returned as stored and marked.
+    let mut frame = Frame::default();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.compression, Some(ContentCompAlgo::Zlib));
+    assert_eq!(frame.data[..2], [0x78, 0x9C]);
+}
 
-    assert_eq!(
-        mkv.info().timestamp_scale(),
-        NonZeroU64::new(100000).unwrap()
-    );
-    assert!((475090.0 - mkv.info().duration().unwrap()).abs() < f64::EPSILON);
-    assert_eq!(mkv.info().date_utc().unwrap(), 328711520000000000);
+#[test]
+pub fn ivf_writer_block_groups_mkv() {
+    let file = File::open("tests/data/block_groups.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
 
-    assert_eq!(mkv.tracks().len(), 2);
+    let mut writer = IvfWriter::new(std::io::Cursor::new(Vec::new()), &mkv.tracks()[0]).unwrap();
+    assert!(AnnexBConverter::from_track(&mkv.tracks()[0]).is_err());
+    let err = AdtsFramer::from_track(&mkv.tracks()[1]).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::UnsupportedCodec);
 
     let mut frame = Frame::default();
-    while mkv.next_frame(&mut frame).unwrap() {}
+    while mkv.next_frame(&mut frame).unwrap() {
+        if frame.track == 1 {
+            writer.write_frame(&frame).unwrap();
+        }
+    }
+    assert_eq!(writer.frame_count(), 4);
+    let ivf = writer.finish().unwrap().into_inner();
+
+    assert_eq!(&ivf[..12], b"DKIF\0\0\x20\0VP90");
+    assert_eq!(ivf[24..28], 4_u32.to_le_bytes());
+    // The first frame with its size and timestamp in milliseconds.
+    assert_eq!(ivf[32..36], 5_u32.to_le_bytes());
+    assert_eq!(ivf[36..44], 0_u64.to_le_bytes());
+    assert_eq!(&ivf[44..49], b"V0key");
+    assert_eq!(ivf[53..61], 40_u64.to_le_bytes());
+}
 
-    mkv.seek(0).unwrap();
-    assert!(mkv.next_frame(&mut frame).unwrap());
-    assert_eq!(frame.timestamp, 0);
+#[test]
+pub fn vp9_config_block_groups_mkv() {
+    let file = File::open("tests/data/block_groups.mkv").unwrap();
+    let mkv = MatroskaFile::open(file).unwrap();
 
-    // Timescale is "100000"
-    mkv.seek(1800).unwrap();
-    assert!(mkv.next_frame(&mut frame).unwrap());
-    assert_eq!(frame.timestamp, 3410);
+    // The track has no Codec Feature Metadata.
+    let config = Vp9Config::from_track(&mkv.tracks()[0]).unwrap();
+    assert_eq!(config, Vp9Config::default());
+    assert_eq!(config.profile(), None);
 
-    mkv.seek(1_000_000).unwrap();
-    assert!(!mkv.next_frame(&mut frame).unwrap());
+    let err = Av1Config::from_track(&mkv.tracks()[0]).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::UnsupportedCodec);
 }
 
 #[test]
-pub fn parse_test3_mkv() {
-    let file = File::open("tests/data/test3.mkv").unwrap();
-    let mut mkv = MatroskaFile::open(file).unwrap();
+pub fn opus_config_block_groups_mkv() {
+    let file = File::open("tests/data/block_groups.mkv").unwrap();
+    let mkv = MatroskaFile::open(file).unwrap();
 
-    assert_eq!(mkv.ebml_header().version(), None);
-    assert_eq!(mkv.ebml_header().read_version(), None);
-    assert_eq!(mkv.ebml_header().doc_type(), "matroska");
-    assert_eq!(mkv.ebml_header().doc_type_version(), 2);
-    assert_eq!(mkv.ebml_header().doc_type_read_version(), 2);
-    assert_eq!(mkv.ebml_header().max_id_length(), 4);
-    assert_eq!(mkv.ebml_header().max_size_length(), 8);
+    // The track has no OpusHead.
+    let err = OpusConfig::from_track(&mkv.tracks()[1]).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::InvalidCodecData);
+
+    let err = FlacConfig::from_track(&mkv.tracks()[1]).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::UnsupportedCodec);
+}
+
+#[test]
+pub fn channel_layout_block_groups_mkv() {
+    let file = File::open("tests/data/block_groups.mkv").unwrap();
+    let mkv = MatroskaFile::open(file).unwrap();
 
+    assert_eq!(ChannelLayout::from_track(&mkv.tracks()[0]), None);
     assert_eq!(
-        mkv.info().timestamp_scale(),
-        NonZeroU64::new(1000000).unwrap()
+        ChannelLayout::from_track(&mkv.tracks()[1]),
+        Some(ChannelLayout::Stereo)
     );
-    assert!((49064.0 - mkv.info().duration().unwrap()).abs() < f64::EPSILON);
-    assert_eq!(mkv.info().date_utc().unwrap(), 304119805000000000);
+    assert_eq!(mkv.tracks()[1].audio().unwrap().channel_positions(), None);
+}
 
-    assert_eq!(mkv.tracks().len(), 2);
+#[test]
+pub fn codec_ids_block_groups_mkv() {
+    let file = File::open("tests/data/block_groups.mkv").unwrap();
+    let mkv = MatroskaFile::open(file).unwrap();
 
-    assert_eq!(mkv.tracks()[0].content_encodings().unwrap().len(), 1);
-    assert_eq!(mkv.tracks()[1].content_encodings().unwrap().len(), 1);
+    let tracks = mkv.tracks();
+    assert_eq!(tracks[0].codec_id(), codec_ids::V_VP9);
+    assert_eq!(tracks[1].codec_id(), codec_ids::A_OPUS);
+    assert_eq!(tracks[2].codec_id(), codec_ids::S_TEXT_UTF8);
 
+    let classes: Vec<(bool, bool, bool)> = tracks
+        .iter()
+        .map(|t| {
+            (
+                t.is_video_codec(),
+                t.is_audio_codec(),
+                t.is_subtitle_codec(),
+            )
+        })
+        .collect();
     assert_eq!(
-        mkv.tracks()[0].content_encodings().unwrap()[0].encoding_type(),
-        ContentEncodingType::Compression
-    );
-    assert_eq!(
-        mkv.tracks()[1].content_encodings().unwrap()[0].encoding_type(),
-        ContentEncodingType::Compression
+        classes,
+        [
+            (true, false, false),
+            (false, true, false),
+            (false, false, true)
+        ]
     );
+}
 
-    let mut frame = Frame::default();
-    while mkv.next_frame(&mut frame).unwrap() {}
+#[test]
+pub fn track_remap_block_groups_mkv() {
+    let file = File::open("tests/data/block_groups.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+
+    let remap = mkv.index().track_remap_with_offset(10);
+    mkv.set_track_remap(remap);
+    assert_eq!(mkv.remapped_track(1), 11);
 
+    let mut tracks = Vec::new();
+    let mut frame = Frame::default();
+    while mkv.next_frame(&mut frame).unwrap() {
+        tracks.push(frame.track);
+    }
+    assert_eq!(tracks.len(), 14);
+    assert!(tracks.iter().all(|track| (11..=13).contains(track)));
+
+    // Tracks without an entry keep their number.
+    let video_uid = mkv.track_by_number(1).unwrap().track_uid().get();
+    let remap = mkv
+        .index()
+        .track_remap_by_uid([(video_uid, 7), (u64::MAX, 8)]);
+    assert_eq!(remap.len(), 1);
+    mkv.set_track_remap(remap);
     mkv.seek(0).unwrap();
-    assert!(mkv.next_frame(&mut frame).unwrap());
-    assert_eq!(frame.timestamp, 8);
+    let block = mkv.next_raw_block().unwrap().unwrap();
+    assert_eq!(block.track, 7);
+    let block = mkv.next_raw_block().unwrap().unwrap();
+    assert_eq!(block.track, 2);
 
-    mkv.seek(450).unwrap();
+    mkv.clear_track_remap();
+    mkv.seek(0).unwrap();
     assert!(mkv.next_frame(&mut frame).unwrap());
-    assert_eq!(frame.timestamp, 500);
-
-    mkv.seek(1_000_000).unwrap();
-    assert!(!mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.track, 1);
 }
 
 #[test]
-pub fn parse_test4_mkv() {
-    let file = File::open("tests/data/test4.mkv").unwrap();
+pub fn probe_first_frames_block_groups_mkv() {
+    let file = File::open("tests/data/block_groups.mkv").unwrap();
     let mut mkv = MatroskaFile::open(file).unwrap();
 
-    assert_eq!(mkv.ebml_header().version(), None);
-    assert_eq!(mkv.ebml_header().read_version(), None);
-    assert_eq!(mkv.ebml_header().doc_type(), "matroska");
-    assert_eq!(mkv.ebml_header().doc_type_version(), 1);
-    assert_eq!(mkv.ebml_header().doc_type_read_version(), 1);
-    assert_eq!(mkv.ebml_header().max_id_length(), 4);
-    assert_eq!(mkv.ebml_header().max_size_length(), 8);
+    // Stop in the middle of the laced block.
+    let mut frame = Frame::default();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.data, b"a0");
 
+    let first_frames = mkv.probe_first_frames().unwrap();
+    let probed: Vec<(u64, &[u8], Option<bool>)> = first_frames
+        .iter()
+        .map(|f| (f.track, f.data.as_slice(), f.is_keyframe))
+        .collect();
     assert_eq!(
-        mkv.info().timestamp_scale(),
-        NonZeroU64::new(1000000).unwrap()
+        probed,
+        [
+            (1, &b"V0key"[..], Some(true)),
+            (2, &b"a0"[..], Some(true)),
+            (3, &b"Hello"[..], Some(true)),
+        ]
     );
-    assert_eq!(mkv.info().date_utc().unwrap(), 304072935000000000);
 
-    assert_eq!(mkv.tracks().len(), 2);
+    // The demuxer continues where it stopped.
+    assert_eq!(mkv.pending_frames(), 2);
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.data, b"a1");
+    assert_eq!(frame.timestamp, 20);
+}
+
+#[test]
+pub fn pending_frames_block_groups_mkv() {
+    let file = File::open("tests/data/block_groups.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
 
     let mut frame = Frame::default();
-    while mkv.next_frame(&mut frame).unwrap() {}
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.data, b"V0key");
+    assert_eq!(mkv.pending_frames(), 0);
 
-    mkv.seek(0).unwrap();
+    // The second block is a lace of three frames.
     assert!(mkv.next_frame(&mut frame).unwrap());
-    // We are seeking in a file based of a live stream. So the first timestamp is "12345".
-    assert_eq!(frame.timestamp, 12345);
+    assert_eq!(frame.data, b"a0");
+    assert_eq!(mkv.pending_frames(), 2);
+    assert_eq!(mkv.drain_pending().unwrap(), 2);
+    assert_eq!(mkv.pending_frames(), 0);
+    assert_eq!(mkv.drain_pending().unwrap(), 0);
 
-    mkv.seek(50000).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.data, b"V1");
+}
+
+#[test]
+pub fn max_laced_frames_block_groups_mkv() {
+    let file = File::open("tests/data/block_groups.mkv").unwrap();
+    let settings = ParserSettings::default().max_laced_frames(2);
+    let mut mkv = MatroskaFile::open_with_settings(file, &settings).unwrap();
+
+    let mut frame = Frame::default();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.data, b"V0key");
+
+    let err = mkv.next_frame(&mut frame).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::TooManyLacedFrames);
+    assert_eq!(mkv.pending_frames(), 0);
+
+    // The oversized block is skipped.
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.data, b"V1");
+}
+
+#[test]
+pub fn track_lookup_block_groups_mkv() {
+    let file = File::open("tests/data/block_groups.mkv").unwrap();
+    let mkv = MatroskaFile::open(file).unwrap();
+
+    assert_eq!(mkv.track_by_number(2).unwrap().track_uid().get(), 102);
+    assert_eq!(mkv.track_by_uid(103).unwrap().track_number().get(), 3);
+    assert!(mkv.track_by_uid(1).is_none());
+
+    let audio: Vec<u64> = mkv
+        .tracks_of_type(TrackType::Audio)
+        .map(|t| t.track_number().get())
+        .collect();
+    assert_eq!(audio, [2]);
+
+    let default_track = |track_type| {
+        mkv.default_track(track_type)
+            .map(|t| t.track_number().get())
+    };
+    assert_eq!(default_track(TrackType::Video), Some(1));
+    assert_eq!(default_track(TrackType::Audio), Some(2));
+    // The subtitle track is not flagged as default.
+    assert_eq!(default_track(TrackType::Subtitle), None);
+    assert_eq!(default_track(TrackType::Logo), None);
+}
+
+#[test]
+pub fn raw_blocks_block_groups_mkv() {
+    let file = File::open("tests/data/block_groups.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+
+    let mut blocks = Vec::new();
+    while let Some(block) = mkv.next_raw_block().unwrap() {
+        blocks.push(block);
+    }
+    assert_eq!(blocks.len(), 12);
+
+    assert_eq!(blocks[0].track, 1);
+    assert_eq!(blocks[0].timestamp, 0);
+    assert!(!blocks[0].is_simple_block);
+    assert_eq!(
+        mkv.read_byte_range(blocks[0].payload_range()).unwrap(),
+        b"V0key"
+    );
+
+    // The payload of a laced block contains the lacing header.
+    assert_eq!(blocks[1].track, 2);
+    assert_eq!(blocks[1].flags, 0x82);
+    assert!(blocks[1].is_simple_block);
+    assert_eq!(
+        mkv.read_byte_range(blocks[1].payload_range()).unwrap(),
+        b"\x02\x02\x02a0a1a2"
+    );
+
+    assert_eq!(blocks[3].duration, Some(500));
+    assert_eq!(
+        blocks[3].duration_time().unwrap().as_duration(),
+        Duration::from_millis(500)
+    );
+    assert_eq!(blocks[10].timestamp, 980);
+    assert_eq!(
+        blocks[10].time(),
+        Timestamp::from(Duration::from_millis(980))
+    );
+
+    // Remaining frames of a partially read block are skipped.
+    mkv.seek(0).unwrap();
+    let mut frame = Frame::default();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.data, b"a0");
+    let block: RawBlock = mkv.next_raw_block().unwrap().unwrap();
+    assert_eq!(block, blocks[2]);
+}
+
+#[test]
+pub fn seek_with_preroll_block_groups_mkv() {
+    let file = File::open("tests/data/block_groups.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+
+    // The audio track has a SeekPreRoll of 80 ms and a CodecDelay of 6.5 ms. The first
+    // block at or after the pre-roll target of 926 is the block at 1000, so only the codec
+    // delay is discarded.
+    let seek = mkv.seek_with_preroll(2, 1000).unwrap();
+    assert_eq!(
+        seek,
+        Some(PreRollSeek {
+            seek_timestamp: 1000,
+            discard_until: 1000,
+            discard_duration: 6,
+        })
+    );
+
+    let mut frame = Frame::default();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 1000);
+
+    // The pre-roll target of 26 reaches the block at 40.
+    let seek = mkv.seek_with_preroll(2, 100).unwrap().unwrap();
+    assert_eq!(seek.seek_timestamp, 40);
+    assert_eq!(seek.discard_duration, 66);
+
+    // Tracks without a SeekPreRoll behave like a normal seek.
+    let seek = mkv.seek_with_preroll(1, 40).unwrap().unwrap();
+    assert_eq!(seek.seek_timestamp, 40);
+    assert_eq!(seek.discard_duration, 0);
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 40);
+
+    assert_eq!(mkv.seek_with_preroll(2, 100_000).unwrap(), None);
+    assert!(matches!(
+        mkv.seek_with_preroll(9, 0),
+        Err(DemuxError::TrackNotFound(9))
+    ));
+}
+
+#[test]
+pub fn byte_range_for_time_range_block_groups_mkv() {
+    let file = File::open("tests/data/block_groups.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+
+    let ranges = |mkv: &mut MatroskaFile<File>, start, end| -> Vec<(u64, u64)> {
+        let ranges = mkv.byte_range_for_time_range(start, end).unwrap();
+        ranges.into_iter().map(|r| (r.start, r.end)).collect()
+    };
+
+    // The clusters are stored at the bytes 291..405 and 405..458.
+    assert_eq!(ranges(&mut mkv, 0, 500), [(291, 405)]);
+    assert_eq!(ranges(&mut mkv, 1000, 1100), [(405, 458)]);
+    assert_eq!(ranges(&mut mkv, 500, 2000), [(291, 458)]);
+
+    // The position of the demuxer is not changed.
+    let mut frame = Frame::default();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.data, b"V0key");
+
+    // Errors while reading the element headers are not mistaken for the end of the file.
+    let data = std::fs::read("tests/data/block_groups.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(std::io::Cursor::new(data)).unwrap();
+    mkv.get_mut().get_mut()[458] = 0xFF;
+    assert!(matches!(
+        mkv.byte_range_for_time_range(500, 2000),
+        Err(DemuxError::InvalidEbmlElementId)
+    ));
+}
+
+#[test]
+pub fn streaming_layout_block_groups_mkv() {
+    let file = File::open("tests/data/block_groups.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+
+    let layout = mkv.streaming_layout().unwrap();
+    assert_eq!(layout.init_segment, 0..291);
+    assert_eq!(
+        layout.media_segments,
+        [
+            MediaSegment {
+                timestamp: 0,
+                range: 291..405,
+            },
+            MediaSegment {
+                timestamp: 1000,
+                range: 405..458,
+            },
+        ]
+    );
+
+    let init_segment = mkv.read_byte_range(layout.init_segment).unwrap();
+    assert_eq!(init_segment[..4], [0x1A, 0x45, 0xDF, 0xA3]);
+    let media_segment = mkv
+        .read_byte_range(layout.media_segments[1].range.clone())
+        .unwrap();
+    assert_eq!(media_segment.len(), 53);
+    assert_eq!(media_segment[..4], [0x1F, 0x43, 0xB6, 0x75]);
+
+    let mut frame = Frame::default();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.data, b"V0key");
+
+    // The initialization segment starts at the EBML header.
+    let mut shifted = b"JUNK".to_vec();
+    shifted.extend_from_slice(&std::fs::read("tests/data/block_groups.mkv").unwrap());
+    let mut file = Cursor::new(shifted);
+    file.seek(SeekFrom::Start(4)).unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+    let layout = mkv.streaming_layout().unwrap();
+    assert_eq!(layout.init_segment, 4..295);
+    let init_segment = mkv.read_byte_range(layout.init_segment).unwrap();
+    assert_eq!(init_segment[..4], [0x1A, 0x45, 0xDF, 0xA3]);
+}
+
+#[test]
+pub fn analyze_timestamps_block_groups_mkv() {
+    let file = File::open("tests/data/block_groups.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+
+    let report = mkv.analyze_timestamps().unwrap();
+    assert!(!report.is_clean());
+
+    let video = report.track(1).unwrap();
+    assert_eq!(video.frame_count, 4);
+    assert_eq!(video.first_timestamp, Some(0));
+    assert_eq!(video.last_timestamp, Some(1000));
+
+    let audio = report.track(2).unwrap();
+    let audio_issues: Vec<(TimestampIssueKind, u64)> =
+        audio.issues.iter().map(|i| (i.kind, i.timestamp)).collect();
+    assert_eq!(
+        audio_issues,
+        [
+            (TimestampIssueKind::Gap, 200),
+            (TimestampIssueKind::BackwardsJump, 980),
+            (TimestampIssueKind::Gap, 980),
+        ]
+    );
+
+    let subtitles = report.track(3).unwrap();
+    assert_eq!(subtitles.issues.len(), 1);
+    assert_eq!(subtitles.issues[0].kind, TimestampIssueKind::Overlap);
+    assert_eq!(subtitles.issues[0].previous_timestamp, 50);
+
+    // The analysis doesn't change the position of the demuxer.
+    let mut frame = Frame::default();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.data, b"V0key");
+}
+
+#[test]
+pub fn parse_simple_mkv_from_slice() {
+    let data = std::fs::read("tests/data/simple.mkv").unwrap();
+    let mut mkv = MatroskaFile::from_slice(&data).unwrap();
+
+    let mut frame = Frame::default();
+    let mut count = 0;
+    while mkv.next_frame(&mut frame).unwrap() {
+        count += 1;
+    }
+    assert_eq!(count, 74);
+}
+
+#[test]
+pub fn parse_hdr_mkv_from_bytes() {
+    let data = std::fs::read("tests/data/hdr.mkv").unwrap();
+    let mut mkv = MatroskaFile::from_bytes(data).unwrap();
+
+    let mut frame = Frame::default();
+    let mut count = 0;
+    while mkv.next_frame(&mut frame).unwrap() {
+        count += 1;
+    }
+    assert_eq!(count, 9);
+}
+
+#[test]
+pub fn parse_simple_mkv_with_shared_index() {
+    let file = File::open("tests/data/simple.mkv").unwrap();
+    let index = Arc::new(MatroskaIndex::parse(file).unwrap());
+    assert_eq!(index.tracks().len(), 2);
+
+    let handles: Vec<_> = [1, 2]
+        .into_iter()
+        .map(|track| {
+            let index = Arc::clone(&index);
+            std::thread::spawn(move || {
+                let file = File::open("tests/data/simple.mkv").unwrap();
+                let mut reader = MatroskaReader::new(index, file).unwrap();
+                let mut frame = Frame::default();
+                let mut count = 0;
+                while reader.next_frame(&mut frame).unwrap() {
+                    if frame.track == track {
+                        count += 1;
+                    }
+                }
+                count
+            })
+        })
+        .collect();
+
+    let counts: Vec<usize> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+    assert_eq!(counts.iter().sum::<usize>(), 74);
+    assert_eq!(Arc::strong_count(&index), 1);
+}
+
+#[test]
+pub fn move_demuxer_between_threads_simple_mkv() {
+    let file = File::open("tests/data/simple.mkv").unwrap();
+    let mkv = MatroskaFile::open(file).unwrap();
+
+    // The metadata is read on one thread while the demuxer runs on another.
+    let index = Arc::clone(mkv.index());
+    let metadata = std::thread::spawn(move || {
+        let codecs: Vec<String> = index
+            .tracks()
+            .iter()
+            .map(|track| track.codec_id().to_owned())
+            .collect();
+        (codecs, index.chapters().map(|chapters| chapters.to_vec()))
+    });
+
+    let demuxer = std::thread::spawn(move || {
+        let mut mkv = mkv;
+        let mut frame = Frame::default();
+        let mut count = 0;
+        while mkv.next_frame(&mut frame).unwrap() {
+            count += 1;
+        }
+        (count, mkv.into_parts())
+    });
+
+    let (codecs, chapters) = metadata.join().unwrap();
+    assert_eq!(codecs.len(), 2);
+    assert!(chapters.is_some());
+
+    let (count, (index, file)) = demuxer.join().unwrap();
+    assert_eq!(count, 74);
+    let mut mkv = MatroskaFile::new(index, file).unwrap();
+    let mut frame = Frame::default();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 0);
+}
+
+#[test]
+pub fn next_frame_into_pool_simple_mkv() {
+    let file = File::open("tests/data/simple.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+    let pool = FramePool::new(4);
+
+    let (sender, receiver) = std::sync::mpsc::sync_channel::<PooledFrame>(2);
+    let consumer = std::thread::spawn(move || {
+        let mut bytes = 0;
+        let mut count = 0;
+        for frame in receiver {
+            bytes += frame.data.len();
+            count += 1;
+        }
+        (count, bytes)
+    });
+
+    let mut expected_bytes = 0;
+    while let Some(frame) = mkv.next_frame_into_pool(&pool).unwrap() {
+        expected_bytes += frame.data.len();
+        sender.send(frame).unwrap();
+    }
+    drop(sender);
+
+    let (count, bytes) = consumer.join().unwrap();
+    assert_eq!(count, 74);
+    assert_eq!(bytes, expected_bytes);
+
+    // The frames were returned to the pool by the consumer.
+    assert!(!pool.is_empty());
+    assert!(pool.len() <= 4);
+    assert!(pool.get().data.capacity() > 0);
+}
+
+struct RangeSource(Vec<u8>);
+
+impl ByteRangeSource for RangeSource {
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize> {
+        let offset = usize::try_from(offset).unwrap().min(self.0.len());
+        let data = &self.0[offset..];
+        let read = data.len().min(buf.len());
+        buf[..read].copy_from_slice(&data[..read]);
+        Ok(read)
+    }
+
+    fn len(&self) -> Option<u64> {
+        Some(self.0.len() as u64)
+    }
+}
+
+#[test]
+pub fn parse_simple_mkv_from_byte_range_source() {
+    let data = std::fs::read("tests/data/simple.mkv").unwrap();
+    let reader = ByteRangeReader::with_chunk_size(RangeSource(data), 1024);
+    let mut mkv = MatroskaFile::open(reader).unwrap();
+
+    let mut frame = Frame::default();
+    let mut count = 0;
+    while mkv.next_frame(&mut frame).unwrap() {
+        count += 1;
+    }
+    assert_eq!(count, 74);
+
+    mkv.seek(3).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 3);
+}
+
+/// Records the end of the furthest byte range that was requested.
+struct TrackingSource {
+    data: Vec<u8>,
+    max_end: Arc<AtomicUsize>,
+}
+
+impl ByteRangeSource for TrackingSource {
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize> {
+        let offset = usize::try_from(offset).unwrap().min(self.data.len());
+        let data = &self.data[offset..];
+        let read = data.len().min(buf.len());
+        buf[..read].copy_from_slice(&data[..read]);
+        self.max_end.fetch_max(offset + read, Ordering::SeqCst);
+        Ok(read)
+    }
+
+    fn len(&self) -> Option<u64> {
+        Some(self.data.len() as u64)
+    }
+}
+
+/// Fails with a connection reset for every read past the given offset.
+struct FailingSource {
+    data: Vec<u8>,
+    fail_at: usize,
+}
+
+impl ByteRangeSource for FailingSource {
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize> {
+        let offset = usize::try_from(offset).unwrap();
+        if offset >= self.fail_at {
+            return Err(std::io::ErrorKind::ConnectionReset.into());
+        }
+        let data = &self.data[offset..self.fail_at];
+        let read = data.len().min(buf.len());
+        buf[..read].copy_from_slice(&data[..read]);
+        Ok(read)
+    }
+
+    fn len(&self) -> Option<u64> {
+        Some(self.data.len() as u64)
+    }
+}
+
+#[test]
+pub fn parse_simple_mkv_end_of_stream() {
+    let data = std::fs::read("tests/data/simple.mkv").unwrap();
+    let settings = ParserSettings::default().lazy_metadata(true);
+    let mut frame = Frame::default();
+
+    // The cluster starts at byte 13261 and ends at byte 46784.
+    let truncated = std::io::Cursor::new(data[..30000].to_vec());
+    let mut mkv = MatroskaFile::open_with_settings(truncated, &settings).unwrap();
+    let result = loop {
+        match mkv.next_frame(&mut frame) {
+            Ok(true) => {}
+            result => break result,
+        }
+    };
+    assert!(matches!(result, Err(DemuxError::UnexpectedEof)));
+    let err = result.unwrap_err();
+    assert_eq!(err.code(), ErrorCode::UnexpectedEof);
+    assert_eq!(err.code().as_str(), "unexpected_eof");
+    // The file ends inside the SimpleBlock at byte 29816.
+    let context = mkv.error_context().unwrap();
+    assert_eq!(context.offset, 29816);
+    assert_eq!(
+        context.element_path,
+        [
+            ElementId::Segment,
+            ElementId::Cluster,
+            ElementId::SimpleBlock
+        ]
+    );
+
+    let source = FailingSource {
+        data,
+        fail_at: 30000,
+    };
+    let reader = ByteRangeReader::with_chunk_size(source, 1024);
+    let mut mkv = MatroskaFile::open_with_settings(reader, &settings).unwrap();
+    let result = loop {
+        match mkv.next_frame(&mut frame) {
+            Ok(true) => {}
+            result => break result,
+        }
+    };
+    assert!(
+        matches!(result, Err(DemuxError::IoError(err)) if err.kind() == std::io::ErrorKind::ConnectionReset)
+    );
+}
+
+#[test]
+pub fn validate_simple_mkv_read_errors() {
+    let data = std::fs::read("tests/data/simple.mkv").unwrap();
+    let settings = ParserSettings::default().lazy_metadata(true);
+
+    let mut mkv = MatroskaFile::open(std::io::Cursor::new(data.clone())).unwrap();
+    assert!(mkv.validate().is_ok());
+
+    // Read errors are returned instead of ending the validation early.
+    let source = FailingSource {
+        data,
+        fail_at: 30000,
+    };
+    let reader = ByteRangeReader::with_chunk_size(source, 1024);
+    let mut mkv = MatroskaFile::open_with_settings(reader, &settings).unwrap();
+    assert!(
+        matches!(mkv.validate(), Err(DemuxError::IoError(err)) if err.kind() == std::io::ErrorKind::ConnectionReset)
+    );
+}
+
+#[test]
+pub fn parse_broken_track_mkv() {
+    let file = File::open("tests/data/broken_track.mkv").unwrap();
+    assert!(matches!(
+        MatroskaFile::open(file),
+        Err(DemuxError::ElementNotFound(ElementId::CodecId))
+    ));
+
+    let file = File::open("tests/data/broken_track.mkv").unwrap();
+    let settings = ParserSettings::default().lenient(true);
+    let mut mkv = MatroskaFile::open_with_settings(file, &settings).unwrap();
+
+    assert_eq!(mkv.tracks().len(), 1);
+    assert_eq!(mkv.tracks()[0].track_number().get(), 1);
+    assert_eq!(mkv.track_errors().len(), 1);
+    assert!(matches!(
+        mkv.track_errors()[0],
+        TrackError {
+            offset: 180,
+            track_number: Some(2),
+            error: DemuxError::ElementNotFound(ElementId::CodecId),
+        }
+    ));
+
+    let mut frame = Frame::default();
+    let mut count = 0;
+    while mkv.next_frame(&mut frame).unwrap() {
+        count += 1;
+    }
+    assert_eq!(count, 3);
+}
+
+#[test]
+pub fn parse_simple_mkv_element_ranges() {
+    let file = File::open("tests/data/simple.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+
+    assert_eq!(mkv.segment_offset(), 52);
+    assert_eq!(mkv.segment_size(), Some(47669));
+
+    let range = |mkv: &mut MatroskaFile<File>, element_id| {
+        mkv.element_range(element_id)
+            .unwrap()
+            .map(|range| (range.start, range.end))
+    };
+    assert_eq!(range(&mut mkv, ElementId::Info), Some((4151, 4278)));
+    assert_eq!(range(&mut mkv, ElementId::Tracks), Some((4278, 11768)));
+    assert_eq!(range(&mut mkv, ElementId::Cues), Some((46784, 46807)));
+    assert_eq!(range(&mut mkv, ElementId::Tags), Some((46807, 47721)));
+    assert_eq!(range(&mut mkv, ElementId::Attachments), None);
+
+    // The position is restored.
+    let mut frame = Frame::default();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 0);
+}
+
+#[test]
+pub fn unknown_track_policy_broken_track_mkv() {
+    let open = |policy| {
+        let file = File::open("tests/data/broken_track.mkv").unwrap();
+        let settings = ParserSettings::default()
+            .lenient(true)
+            .unknown_track_policy(policy);
+        MatroskaFile::open_with_settings(file, &settings).unwrap()
+    };
+    let mut frame = Frame::default();
+
+    let mut mkv = open(UnknownTrackPolicy::Pass);
+    assert!(mkv.track_by_number(1).is_some());
+    assert!(mkv.track_by_number(2).is_none());
+    let mut tracks = Vec::new();
+    while mkv.next_frame(&mut frame).unwrap() {
+        tracks.push(frame.track);
+    }
+    assert_eq!(tracks, [1, 2, 1]);
+
+    let mut mkv = open(UnknownTrackPolicy::Drop);
+    let mut data = Vec::new();
+    while mkv.next_frame(&mut frame).unwrap() {
+        data.push(frame.data.clone());
+    }
+    assert_eq!(data, [b"V0", b"V1"]);
+
+    let mut mkv = open(UnknownTrackPolicy::Error);
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert!(matches!(
+        mkv.next_frame(&mut frame),
+        Err(DemuxError::TrackNotFound(2))
+    ));
+}
+
+#[test]
+pub fn parse_simple_mkv_skipped_elements() {
+    let file = File::open("tests/data/simple.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+    let mut frame = Frame::default();
+    while mkv.next_frame(&mut frame).unwrap() {}
+    assert!(mkv.skipped_elements().is_empty());
+
+    let file = File::open("tests/data/simple.mkv").unwrap();
+    let settings = ParserSettings::default().record_skipped_elements(true);
+    let mut mkv = MatroskaFile::open_with_settings(file, &settings).unwrap();
+    while mkv.next_frame(&mut frame).unwrap() {}
+
+    let skipped = mkv.skipped_elements();
+    assert!(skipped.contains(&SkippedElement {
+        id: 0x1C53BB6B,
+        element_id: ElementId::Cues,
+        offset: 46784,
+        size: 18,
+    }));
+    assert!(skipped.contains(&SkippedElement {
+        id: 0x1254C367,
+        element_id: ElementId::Tags,
+        offset: 46807,
+        size: 908,
+    }));
+
+    mkv.clear_skipped_elements();
+    assert!(mkv.skipped_elements().is_empty());
+}
+
+#[test]
+pub fn parse_simple_mkv_max_frame_capacity() {
+    let file = File::open("tests/data/simple.mkv").unwrap();
+    let settings = ParserSettings::default().max_frame_capacity(64 * 1024);
+    let mut mkv = MatroskaFile::open_with_settings(file, &settings).unwrap();
+
+    let mut frame = Frame {
+        data: Vec::with_capacity(1024 * 1024),
+        ..Frame::default()
+    };
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert!(frame.data.capacity() <= 64 * 1024);
+
+    frame.data.reserve(1024 * 1024);
+    frame.shrink_to_fit();
+    assert!(frame.data.capacity() < 1024 * 1024);
+}
+
+#[test]
+pub fn parse_simple_mkv_lazy_metadata() {
+    let data = std::fs::read("tests/data/simple.mkv").unwrap();
+    let max_end = Arc::new(AtomicUsize::new(0));
+    let source = TrackingSource {
+        data,
+        max_end: Arc::clone(&max_end),
+    };
+    let reader = ByteRangeReader::with_chunk_size(source, 1024);
+    let settings = ParserSettings::default().lazy_metadata(true);
+    let mut mkv = MatroskaFile::open_with_settings(reader, &settings).unwrap();
+
+    // The cues start at byte 46784 and the tags at byte 46807.
+    assert!(max_end.load(Ordering::SeqCst) < 46784);
+    assert!(!mkv.index().is_metadata_loaded());
+    assert!(mkv.chapters().is_none());
+    assert!(mkv.tags().is_none());
+
+    let mut frame = Frame::default();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 0);
+
+    let chapters = mkv.load_chapters().unwrap().unwrap();
+    assert_eq!(
+        chapters[0].chapter_atoms()[0].displays()[0].string(),
+        "Intro"
+    );
+
+    let tags = mkv.load_tags().unwrap().unwrap();
+    assert_eq!(tags[0].simple_tags()[0].name(), "ENCODER");
+    assert!(max_end.load(Ordering::SeqCst) > 46807);
+
+    // The position is restored after loading the metadata.
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert!(mkv.chapters().is_some());
+    assert!(mkv.tags().is_some());
+
+    assert!(mkv.has_cues().unwrap());
+    assert!(!mkv.index().is_metadata_loaded());
+    assert!(mkv.load_attachments().unwrap().is_none());
+    assert!(mkv.index().is_metadata_loaded());
+
+    mkv.seek(3).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 3);
+}
+
+#[test]
+pub fn open_metadata_only_simple_mkv() {
+    let file = File::open("tests/data/simple.mkv").unwrap();
+    let mut mkv = MatroskaFile::open_metadata_only(file).unwrap();
+
+    assert_eq!(mkv.tracks().len(), 2);
+    assert!(mkv.chapters().is_some());
+    assert!(mkv.tags().is_some());
+    assert!(mkv.cue_points().is_none());
+
+    // The SeekHead doesn't reference a cluster, so it's searched when reading.
+    let mut frame = Frame::default();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 0);
+
+    mkv.seek(3).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 3);
+}
+
+#[test]
+pub fn parse_hdr_mkv() {
+    let file = File::open("tests/data/hdr.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+
+    let video_tracks: Vec<TrackEntry> = mkv
+        .tracks()
+        .iter()
+        .filter(|t| t.track_type() == TrackType::Video)
+        .cloned()
+        .collect();
+
+    let video = video_tracks[0].video().unwrap();
+
+    assert_eq!(video.pixel_width().get(), 3840);
+    assert_eq!(video.pixel_height().get(), 2160);
+
+    let colour = video.colour().unwrap();
+
+    assert_eq!(
+        colour.transfer_characteristics().unwrap(),
+        TransferCharacteristics::Bt2100
+    );
+    assert_eq!(
+        colour.matrix_coefficients().unwrap(),
+        MatrixCoefficients::Bt2020Ncl
+    );
+    assert_eq!(colour.primaries().unwrap(), Primaries::Bt2020);
+
+    let metadata = colour.mastering_metadata().unwrap();
+
+    assert!((1000.0 - metadata.luminance_max().unwrap()).abs() < f64::EPSILON);
+    assert!((0.009999999776482582 - metadata.luminance_min().unwrap()).abs() < f64::EPSILON);
+
+    let mut frame = Frame::default();
+    let mut count = 0;
+    while mkv.next_frame(&mut frame).unwrap() {
+        count += 1;
+    }
+    assert_eq!(count, 9);
+
+    mkv.seek(0).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 0);
+
+    mkv.seek(45).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 45);
+
+    mkv.seek(1_000_000).unwrap();
+    assert!(!mkv.next_frame(&mut frame).unwrap());
+}
+
+#[test]
+pub fn hdr_metadata_hdr_mkv() {
+    let file = File::open("tests/data/hdr.mkv").unwrap();
+    let mkv = MatroskaFile::open(file).unwrap();
+
+    let video = mkv.tracks().iter().find_map(|t| t.video()).unwrap();
+    let colour = video.colour().unwrap();
+    let metadata = colour.mastering_metadata().unwrap();
+
+    assert!(
+        (0.3199999928474426 - metadata.primary_r_chromaticity_y().unwrap()).abs() < f64::EPSILON
+    );
+    assert!(
+        (0.6899999976158142 - metadata.primary_g_chromaticity_y().unwrap()).abs() < f64::EPSILON
+    );
+    assert!(
+        (0.05999999865889549 - metadata.primary_b_chromaticity_y().unwrap()).abs() < f64::EPSILON
+    );
+
+    assert_eq!(colour.max_cll(), Some(1000));
+    assert_eq!(colour.max_fall(), Some(300));
+
+    let hdr_metadata = colour.hdr_metadata().unwrap();
+    assert_eq!(hdr_metadata.max_cll, Some(1000));
+    assert_eq!(hdr_metadata.max_fall, Some(300));
+    let mastering_display = hdr_metadata.mastering_display.unwrap();
+    assert_eq!(
+        mastering_display.primary_g,
+        (0.26499998569488525, 0.6899999976158142)
+    );
+    assert!((1000.0 - mastering_display.luminance_max).abs() < f64::EPSILON);
+}
+
+#[test]
+pub fn parse_test1_mkv() {
+    let file = File::open("tests/data/test1.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+
+    assert_eq!(mkv.ebml_header().version(), None);
+    assert_eq!(mkv.ebml_header().read_version(), None);
+    assert_eq!(mkv.ebml_header().doc_type(), "matroska");
+    assert_eq!(mkv.ebml_header().doc_type_version(), 2);
+    assert_eq!(mkv.ebml_header().doc_type_read_version(), 2);
+    assert_eq!(mkv.ebml_header().max_id_length(), 4);
+    assert_eq!(mkv.ebml_header().max_size_length(), 8);
+
+    assert_eq!(
+        mkv.info().timestamp_scale(),
+        NonZeroU64::new(1000000).unwrap()
+    );
+    assert!((87336.0 - mkv.info().duration().unwrap()).abs() < f64::EPSILON);
+    assert_eq!(
+        mkv.info().muxing_app(),
+        "libebml2 v0.10.0 + libmatroska2 v0.10.1"
+    );
+    assert_eq!(mkv.info().date_utc().unwrap(), 304068183000000000);
+    assert_eq!(mkv.info().writing_app(), "mkclean 0.5.5 ru from libebml v1.0.0 + libmatroska v1.0.0 + mkvmerge v4.1.1 ('Bouncin' Back') built on Jul  3 2010 22:54:08");
+
+    assert_eq!(mkv.tracks().len(), 2);
+
+    let mut frame = Frame::default();
+    while mkv.next_frame(&mut frame).unwrap() {}
+
+    mkv.seek(0).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 0);
+
+    mkv.seek(180).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 192);
+
+    mkv.seek(1_000_000).unwrap();
+    assert!(!mkv.next_frame(&mut frame).unwrap());
+}
+
+#[test]
+pub fn parse_test2_mkv() {
+    let file = File::open("tests/data/test2.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+
+    assert_eq!(mkv.ebml_header().version(), None);
+    assert_eq!(mkv.ebml_header().read_version(), None);
+    assert_eq!(mkv.ebml_header().doc_type(), "matroska");
+    assert_eq!(mkv.ebml_header().doc_type_version(), 2);
+    assert_eq!(mkv.ebml_header().doc_type_read_version(), 2);
+    assert_eq!(mkv.ebml_header().max_id_length(), 4);
+    assert_eq!(mkv.ebml_header().max_size_length(), 8);
+
+    assert_eq!(
+        mkv.info().timestamp_scale(),
+        NonZeroU64::new(100000).unwrap()
+    );
+    assert!((475090.0 - mkv.info().duration().unwrap()).abs() < f64::EPSILON);
+    assert_eq!(mkv.info().date_utc().unwrap(), 328711520000000000);
+
+    assert_eq!(mkv.tracks().len(), 2);
+
+    let mut frame = Frame::default();
+    while mkv.next_frame(&mut frame).unwrap() {}
+
+    mkv.seek(0).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 0);
+
+    // Timescale is "100000"
+    mkv.seek(1800).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 3410);
+
+    mkv.seek(1_000_000).unwrap();
+    assert!(!mkv.next_frame(&mut frame).unwrap());
+}
+
+#[test]
+pub fn parse_test3_mkv() {
+    let file = File::open("tests/data/test3.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+
+    assert_eq!(mkv.ebml_header().version(), None);
+    assert_eq!(mkv.ebml_header().read_version(), None);
+    assert_eq!(mkv.ebml_header().doc_type(), "matroska");
+    assert_eq!(mkv.ebml_header().doc_type_version(), 2);
+    assert_eq!(mkv.ebml_header().doc_type_read_version(), 2);
+    assert_eq!(mkv.ebml_header().max_id_length(), 4);
+    assert_eq!(mkv.ebml_header().max_size_length(), 8);
+
+    assert_eq!(
+        mkv.info().timestamp_scale(),
+        NonZeroU64::new(1000000).unwrap()
+    );
+    assert!((49064.0 - mkv.info().duration().unwrap()).abs() < f64::EPSILON);
+    assert_eq!(mkv.info().date_utc().unwrap(), 304119805000000000);
+
+    assert_eq!(mkv.tracks().len(), 2);
+
+    assert_eq!(mkv.tracks()[0].content_encodings().unwrap().len(), 1);
+    assert_eq!(mkv.tracks()[1].content_encodings().unwrap().len(), 1);
+
+    assert_eq!(
+        mkv.tracks()[0].content_encodings().unwrap()[0].encoding_type(),
+        ContentEncodingType::Compression
+    );
+    assert_eq!(
+        mkv.tracks()[1].content_encodings().unwrap()[0].encoding_type(),
+        ContentEncodingType::Compression
+    );
+
+    let mut frame = Frame::default();
+    while mkv.next_frame(&mut frame).unwrap() {}
+
+    mkv.seek(0).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 8);
+
+    mkv.seek(450).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 500);
+
+    mkv.seek(1_000_000).unwrap();
+    assert!(!mkv.next_frame(&mut frame).unwrap());
+}
+
+#[test]
+pub fn parse_test4_mkv() {
+    let file = File::open("tests/data/test4.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+
+    assert_eq!(mkv.ebml_header().version(), None);
+    assert_eq!(mkv.ebml_header().read_version(), None);
+    assert_eq!(mkv.ebml_header().doc_type(), "matroska");
+    assert_eq!(mkv.ebml_header().doc_type_version(), 1);
+    assert_eq!(mkv.ebml_header().doc_type_read_version(), 1);
+    assert_eq!(mkv.ebml_header().max_id_length(), 4);
+    assert_eq!(mkv.ebml_header().max_size_length(), 8);
+
+    assert_eq!(
+        mkv.info().timestamp_scale(),
+        NonZeroU64::new(1000000).unwrap()
+    );
+    assert_eq!(mkv.info().date_utc().unwrap(), 304072935000000000);
+
+    assert_eq!(mkv.tracks().len(), 2);
+
+    let mut frame = Frame::default();
+    while mkv.next_frame(&mut frame).unwrap() {}
+
+    mkv.seek(0).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    // We are seeking in a file based of a live stream. So the first timestamp is "12345".
+    assert_eq!(frame.timestamp, 12345);
+
+    mkv.seek(50000).unwrap();
     assert!(mkv.next_frame(&mut frame).unwrap());
     assert_eq!(frame.timestamp, 50011);
 
-    mkv.seek(1_000_000).unwrap();
-    assert!(!mkv.next_frame(&mut frame).unwrap());
+    mkv.seek(1_000_000).unwrap();
+    assert!(!mkv.next_frame(&mut frame).unwrap());
+}
+
+#[test]
+pub fn parse_test5_mkv() {
+    let file = File::open("tests/data/test5.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+
+    assert_eq!(mkv.ebml_header().version(), Some(1));
+    assert_eq!(mkv.ebml_header().read_version(), Some(1));
+    assert_eq!(mkv.ebml_header().doc_type(), "matroska");
+    assert_eq!(mkv.ebml_header().doc_type_version(), 2);
+    assert_eq!(mkv.ebml_header().doc_type_read_version(), 2);
+    assert_eq!(mkv.ebml_header().max_id_length(), 4);
+    assert_eq!(mkv.ebml_header().max_size_length(), 8);
+
+    assert_eq!(
+        mkv.info().timestamp_scale(),
+        NonZeroU64::new(1000000).unwrap()
+    );
+    assert!((46665.0 - mkv.info().duration().unwrap()).abs() < f64::EPSILON);
+    assert_eq!(mkv.info().date_utc().unwrap(), 304106803000000000);
+
+    assert_eq!(mkv.tracks().len(), 11);
+    assert_eq!(
+        mkv.tracks()
+            .iter()
+            .filter(|t| t.track_type() == TrackType::Audio)
+            .count(),
+        2
+    );
+    assert_eq!(
+        mkv.tracks()
+            .iter()
+            .filter(|t| t.track_type() == TrackType::Subtitle)
+            .count(),
+        8
+    );
+
+    let audio_tracks: Vec<TrackEntry> = mkv
+        .tracks()
+        .iter()
+        .filter(|t| t.track_type() == TrackType::Audio)
+        .cloned()
+        .collect();
+
+    assert!((48000.0 - audio_tracks[0].audio().unwrap().sampling_frequency()).abs() < f64::EPSILON);
+    assert_eq!(audio_tracks[0].audio().unwrap().channels().get(), 2);
+
+    assert!((22050.0 - audio_tracks[1].audio().unwrap().sampling_frequency()).abs() < f64::EPSILON);
+    assert!(
+        (44100.0
+            - audio_tracks[1]
+                .audio()
+                .unwrap()
+                .output_sampling_frequency()
+                .unwrap())
+        .abs()
+            < f64::EPSILON
+    );
+
+    let mut frame = Frame::default();
+    while mkv.next_frame(&mut frame).unwrap() {}
+
+    mkv.seek(0).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 0);
+
+    mkv.seek(2000).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 2000);
+
+    mkv.seek(1_000_000).unwrap();
+    assert!(!mkv.next_frame(&mut frame).unwrap());
+}
+
+#[test]
+pub fn parse_test6_mkv() {
+    let file = File::open("tests/data/test6.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+
+    assert_eq!(mkv.ebml_header().max_id_length(), 4);
+    assert_eq!(mkv.ebml_header().max_size_length(), 8);
+
+    assert_eq!(
+        mkv.info().timestamp_scale(),
+        NonZeroU64::new(1000000).unwrap()
+    );
+    assert!((87336.0 - mkv.info().duration().unwrap()).abs() < f64::EPSILON);
+    assert_eq!(mkv.info().date_utc().unwrap(), 304101115000000000);
+
+    assert_eq!(mkv.tracks().len(), 2);
+
+    let mut frame = Frame::default();
+    while mkv.next_frame(&mut frame).unwrap() {}
+
+    mkv.seek(0).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 0);
+
+    mkv.seek(1000).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 1000);
+
+    mkv.seek(1_000_000).unwrap();
+    assert!(!mkv.next_frame(&mut frame).unwrap());
+}
+
+#[test]
+pub fn parse_test7_mkv() {
+    let file = File::open("tests/data/test7.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+
+    assert_eq!(mkv.ebml_header().version(), None);
+    assert_eq!(mkv.ebml_header().read_version(), None);
+    assert_eq!(mkv.ebml_header().doc_type(), "matroska");
+    assert_eq!(mkv.ebml_header().doc_type_version(), 2);
+    assert_eq!(mkv.ebml_header().doc_type_read_version(), 2);
+    assert_eq!(mkv.ebml_header().max_id_length(), 4);
+    assert_eq!(mkv.ebml_header().max_size_length(), 8);
+
+    assert_eq!(
+        mkv.info().timestamp_scale(),
+        NonZeroU64::new(1000000).unwrap()
+    );
+    assert!((37043.0 - mkv.info().duration().unwrap()).abs() < f64::EPSILON);
+    assert_eq!(mkv.info().date_utc().unwrap(), 304102823000000000);
+
+    assert_eq!(mkv.tracks().len(), 2);
+
+    let mut frame = Frame::default();
+    while mkv.next_frame(&mut frame).unwrap() {}
+
+    mkv.seek(0).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 8);
+
+    mkv.seek(2000).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 2000);
+
+    mkv.seek(1_000_000).unwrap();
+    assert!(!mkv.next_frame(&mut frame).unwrap());
+}
+
+#[test]
+pub fn parse_test8_mkv() {
+    let file = File::open("tests/data/test8.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+
+    assert_eq!(mkv.ebml_header().version(), None);
+    assert_eq!(mkv.ebml_header().read_version(), None);
+    assert_eq!(mkv.ebml_header().doc_type(), "matroska");
+    assert_eq!(mkv.ebml_header().doc_type_version(), 2);
+    assert_eq!(mkv.ebml_header().doc_type_read_version(), 2);
+    assert_eq!(mkv.ebml_header().max_id_length(), 4);
+    assert_eq!(mkv.ebml_header().max_size_length(), 8);
+
+    assert_eq!(
+        mkv.info().timestamp_scale(),
+        NonZeroU64::new(1000000).unwrap()
+    );
+    assert!((47341.0 - mkv.info().duration().unwrap()).abs() < f64::EPSILON);
+    assert_eq!(mkv.info().date_utc().unwrap(), 304104134000000000);
+
+    assert_eq!(mkv.tracks().len(), 2);
+
+    let mut frame = Frame::default();
+    while mkv.next_frame(&mut frame).unwrap() {}
+
+    mkv.seek(0).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 3);
+
+    mkv.seek(750).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 750);
+
+    mkv.seek(1_000_000).unwrap();
+    assert!(!mkv.next_frame(&mut frame).unwrap());
+}
+
+#[test]
+pub fn validate_simple_mkv() {
+    let file = File::open("tests/data/simple.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+    let report = mkv.validate().unwrap();
+    assert!(report.is_valid(), "{:?}", report);
+
+    let mut frame = Frame::default();
+    let mut count = 0;
+    while mkv.next_frame(&mut frame).unwrap() {
+        count += 1;
+    }
+    assert_eq!(count, 74);
+}
+
+#[test]
+pub fn validate_invalid_mkv() {
+    let file = File::open("tests/data/invalid.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+    let report = mkv.validate().unwrap();
+    assert!(!report.is_valid());
+
+    let issues: Vec<(Severity, ValidationIssueKind, ElementId)> = report
+        .issues
+        .iter()
+        .map(|i| (i.severity, i.kind, i.element))
+        .collect();
+    assert_eq!(
+        issues,
+        [
+            (
+                Severity::Error,
+                ValidationIssueKind::ValueOutOfRange,
+                ElementId::Duration
+            ),
+            (
+                Severity::Error,
+                ValidationIssueKind::DuplicateTrackUid,
+                ElementId::TrackUid
+            ),
+            (
+                Severity::Error,
+                ValidationIssueKind::ValueOutOfRange,
+                ElementId::FlagDefault
+            ),
+            (
+                Severity::Error,
+                ValidationIssueKind::WrongPlacement,
+                ElementId::TrackEntry
+            ),
+            (
+                Severity::Error,
+                ValidationIssueKind::UnknownTrack,
+                ElementId::SimpleBlock
+            ),
+            (
+                Severity::Error,
+                ValidationIssueKind::MissingElement,
+                ElementId::Block
+            ),
+            (
+                Severity::Warning,
+                ValidationIssueKind::WrongPlacement,
+                ElementId::Timestamp
+            ),
+            (
+                Severity::Error,
+                ValidationIssueKind::NonMonotonicClusterTimestamp,
+                ElementId::Timestamp
+            ),
+        ]
+    );
+    assert_eq!(report.warnings().count(), 1);
+    assert_eq!(report.issues[3].offset, 216);
+}
+
+#[test]
+pub fn strict_long_sizes_mkv() {
+    let open = |strict| {
+        let file = File::open("tests/data/long_sizes.mkv").unwrap();
+        let settings = ParserSettings::default().strict(strict);
+        MatroskaFile::open_with_settings(file, &settings).unwrap()
+    };
+    let mut frame = Frame::default();
+
+    let mut mkv = open(false);
+    assert_eq!(mkv.ebml_header().max_size_length(), 4);
+    let mut data = Vec::new();
+    while mkv.next_frame(&mut frame).unwrap() {
+        data.push(frame.data.clone());
+    }
+    assert_eq!(data, [b"V0", b"V1"]);
+
+    let mut mkv = open(true);
+    let err = mkv.next_frame(&mut frame).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::InvalidEbmlDataSize);
+    assert_eq!(mkv.error_context().map(|context| context.offset), Some(174));
+
+    let mut mkv = open(true);
+    let err = mkv.next_raw_block().unwrap_err();
+    assert_eq!(err.code(), ErrorCode::InvalidEbmlDataSize);
+}
+
+#[test]
+pub fn decrypt_encrypted_mkv() {
+    let file = File::open("tests/data/encrypted.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+
+    let encryption = mkv.tracks()[0].content_encodings().unwrap()[0]
+        .encryption()
+        .unwrap();
+    assert_eq!(encryption.algo(), ContentEncAlgo::Aes);
+    assert_eq!(encryption.key_id(), Some(b"key".as_slice()));
+    assert_eq!(
+        encryption
+            .aes_settings()
+            .unwrap()
+            .aes_settings_cipher_mode(),
+        Some(AesSettingsCipherMode::Ctr)
+    );
+
+    // Without a decryptor, the encryption header is stripped and reported.
+    let mut frames = Vec::new();
+    let mut frame = Frame::default();
+    while mkv.next_frame(&mut frame).unwrap() {
+        frames.push(frame.clone());
+    }
+    assert_eq!(frames[0].data, b"V0");
+    assert!(!frames[0].encryption.as_ref().unwrap().is_encrypted());
+    assert_eq!(frames[1].encryption, None);
+    assert_eq!(frames[2].data, b"v\x11");
+    assert_eq!(
+        frames[2].encryption,
+        Some(FrameEncryptionInfo {
+            iv: Some([0x20, 1, 2, 3, 4, 5, 6, 7]),
+            partitions: Vec::new(),
+        })
+    );
+    assert_eq!(frames[3].data, b"V\x12");
+    assert_eq!(frames[3].encryption.as_ref().unwrap().partitions, [1]);
+
+    let file = File::open("tests/data/encrypted.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+    mkv.set_decryptor(1, XorDecryptor);
+    // Tracks without a ContentEncryption are never decrypted.
+    mkv.set_decryptor(2, XorDecryptor);
+
+    let mut data = Vec::new();
+    while mkv.next_frame(&mut frame).unwrap() {
+        data.push(frame.data.clone());
+    }
+    assert_eq!(data, [b"V0", b"A0", b"V1", b"V2"]);
+}
+
+#[test]
+pub fn silent_tracks_mkv() {
+    let file = File::open("tests/data/silent_tracks.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+    assert!(mkv.silent_tracks().is_empty());
+
+    let mut silent_tracks = Vec::new();
+    let mut frame = Frame::default();
+    while mkv.next_frame(&mut frame).unwrap() {
+        silent_tracks.push(mkv.silent_tracks().to_vec());
+    }
+    assert_eq!(silent_tracks, [vec![2], vec![2], vec![], vec![]]);
+
+    let outcome = mkv.seek(1000).unwrap();
+    assert_eq!(outcome.reached, Some(1000));
+    assert_eq!(outcome.cluster_offset, Some(244));
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert!(mkv.silent_tracks().is_empty());
+
+    let outcome = mkv.seek(30).unwrap();
+    assert_eq!(outcome.requested, 30);
+    assert_eq!(outcome.reached, Some(40));
+    assert!(!outcome.used_cues);
+    assert_eq!(outcome.cluster_offset, Some(213));
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.data, b"V1");
+    assert_eq!(mkv.silent_tracks(), [2]);
+}
+
+#[test]
+pub fn cue_points_block_groups_mkv() {
+    let file = File::open("tests/data/block_groups.mkv").unwrap();
+    let settings = ParserSettings::default().lazy_metadata(true);
+    let mut mkv = MatroskaFile::open_with_settings(file, &settings).unwrap();
+    assert!(mkv.cue_points().is_none());
+
+    let cue_points = mkv.load_cue_points().unwrap().unwrap();
+    let times: Vec<u64> = cue_points.iter().map(|p| p.time()).collect();
+    assert_eq!(times, [0, 1000, 1100]);
+
+    let positions = cue_points[1].track_positions();
+    assert_eq!(positions.len(), 2);
+    assert_eq!(positions[1].track(), 2);
+    assert_eq!(
+        positions[0].cluster_position(),
+        positions[1].cluster_position()
+    );
+    assert!(positions[0].references().is_empty());
+
+    let subtitle = &cue_points[2].track_positions()[0];
+    assert_eq!(subtitle.track(), 3);
+    assert_eq!(subtitle.references().len(), 1);
+    assert_eq!(subtitle.references()[0].ref_time(), 1000);
+
+    // Raw values are expressed in segment ticks, the typed accessors carry the scale.
+    assert_eq!(
+        cue_points[2].cue_time(),
+        Timestamp::from(Duration::from_millis(1100))
+    );
+    assert_eq!(
+        subtitle.references()[0].cue_ref_time().as_nanos(),
+        1_000_000_000
+    );
+    assert_eq!(
+        cue_points[2].cue_time().ticks(),
+        i64::try_from(cue_points[2].time()).unwrap()
+    );
+}
+
+#[test]
+pub fn seek_track_block_groups_mkv() {
+    let file = File::open("tests/data/block_groups.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+    let mut frame = Frame::default();
+
+    // The cue point at 1000 has positions for the tracks 1 and 2, but not for track 3.
+    let outcome = mkv.seek(1050).unwrap();
+    assert!(outcome.used_cues);
+    assert_eq!(outcome.cluster_offset, Some(405));
+
+    let outcome = mkv.seek_track(2, 1050).unwrap();
+    assert!(outcome.used_cues);
+    assert_eq!(outcome.cluster_offset, Some(405));
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(Some(frame.timestamp), outcome.reached);
+
+    let outcome = mkv.seek_track(3, 1050).unwrap();
+    assert!(!outcome.used_cues);
+    assert_eq!(outcome.cluster_offset, Some(405));
+
+    let outcome = mkv.seek_track(3, 1100).unwrap();
+    assert!(outcome.used_cues);
+
+    assert!(matches!(
+        mkv.seek_track(99, 0),
+        Err(DemuxError::TrackNotFound(99))
+    ));
+}
+
+#[test]
+pub fn seek_relative_cues_mkv() {
+    let file = File::open("tests/data/relative_cues.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+    let mut frame = Frame::default();
+
+    // The cue points reference blocks inside the second and third cluster.
+    for (seek_timestamp, timestamp) in [(1040, 1040), (1060, 1080), (2080, 2080), (0, 0)] {
+        let outcome = mkv.seek(seek_timestamp).unwrap();
+        assert!(outcome.used_cues);
+        assert_eq!(outcome.reached, Some(timestamp));
+        assert!(mkv.next_frame(&mut frame).unwrap());
+        assert_eq!(frame.timestamp, timestamp);
+        assert_eq!(frame.data, format!("V{}", timestamp).as_bytes());
+    }
+
+    let outcome = mkv.seek(2040).unwrap();
+    assert_eq!(outcome.cluster_offset, Some(367));
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 2040);
+}
+
+#[test]
+pub fn tracks_changed_restart_mkv() {
+    let file = File::open("tests/data/restart.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+    assert_eq!(mkv.tracks()[0].codec_id(), "V_VP9");
+
+    let mut frame = Frame::default();
+    let mut events = Vec::new();
+    while let Some(event) = mkv.next_event(&mut frame).unwrap() {
+        match event {
+            DemuxEvent::Frame => events.push(String::from_utf8(frame.data.clone()).unwrap()),
+            DemuxEvent::EndOfSegment => events.push("end".to_owned()),
+            DemuxEvent::TracksChanged => events.push(mkv.tracks()[0].codec_id().to_owned()),
+            _ => {}
+        }
+    }
+    assert_eq!(events, ["A0", "A1", "end", "V_VP8", "B0", "B1", "end"]);
+    assert_eq!(mkv.tracks()[0].video().unwrap().pixel_width().get(), 320);
+
+    let file = File::open("tests/data/restart.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+    let mut data = Vec::new();
+    while mkv.next_frame(&mut frame).unwrap() {
+        data.push(frame.data.clone());
+    }
+    assert_eq!(data, [b"A0", b"A1", b"B0", b"B1"]);
+}
+
+#[test]
+pub fn next_event_events_mkv() {
+    let file = File::open("tests/data/events.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+
+    let mut frame = Frame::default();
+    let mut events = Vec::new();
+    while let Some(event) = mkv.next_event(&mut frame).unwrap() {
+        let event = match event {
+            DemuxEvent::Frame => format!("frame {}", frame.timestamp),
+            DemuxEvent::ClusterStart { timestamp } => format!("cluster {}", timestamp),
+            DemuxEvent::Chapters(range) => {
+                format!("chapters {}", mkv.read_chapters(range).unwrap().len())
+            }
+            DemuxEvent::Tags(range) => {
+                let tags = mkv.read_tags(range).unwrap();
+                format!("tags {}", tags[0].simple_tags()[0].string().unwrap())
+            }
+            DemuxEvent::Attachments(range) => format!("attachments {:?}", range),
+            DemuxEvent::Gap {
+                track,
+                previous_timestamp,
+                timestamp,
+            } => format!("gap {} {} {}", track, previous_timestamp, timestamp),
+            DemuxEvent::TimestampDiscontinuity { timestamp, .. } => {
+                format!("discontinuity {}", timestamp)
+            }
+            DemuxEvent::EndOfSegment => "end".to_owned(),
+            DemuxEvent::TracksChanged => "tracks".to_owned(),
+        };
+        events.push(event);
+    }
+    assert_eq!(
+        events,
+        [
+            "cluster 0",
+            "frame 0",
+            "frame 40",
+            "gap 1 40 200",
+            "frame 200",
+            "chapters 1",
+            "attachments 382..426",
+            "tags Events",
+            "cluster 1000",
+            "gap 1 200 1000",
+            "frame 1000",
+            "end",
+        ]
+    );
+    assert!(mkv.next_event(&mut frame).unwrap().is_none());
+
+    mkv.seek(0).unwrap();
+    let mut count = 0;
+    while mkv.next_frame(&mut frame).unwrap() {
+        count += 1;
+    }
+    assert_eq!(count, 4);
+}
+
+#[test]
+pub fn corrupt_trailing_tags_mkv() {
+    let file = File::open("tests/data/corrupt_tags.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+
+    // The Tags element isn't parsed while reading frames.
+    let mut frame = Frame::default();
+    let mut data = Vec::new();
+    while mkv.next_frame(&mut frame).unwrap() {
+        data.push(frame.data.clone());
+    }
+    assert_eq!(data, [b"V0", b"V1"]);
+
+    mkv.seek(0).unwrap();
+    let mut range = None;
+    while let Some(event) = mkv.next_event(&mut frame).unwrap() {
+        if let DemuxEvent::Tags(tags_range) = event {
+            range = Some(tags_range);
+        }
+    }
+    let err = mkv.read_tags(range.unwrap()).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::InvalidEbmlElementId);
+}
+
+#[test]
+pub fn seek_to_offset_relative_cues_mkv() {
+    let file = File::open("tests/data/relative_cues.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+    let mut frame = Frame::default();
+
+    let cluster_position = mkv.cue_points().unwrap()[2].track_positions()[0].cluster_position();
+    mkv.seek_to_offset(cluster_position).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 2000);
+
+    // The second block of the second cluster.
+    mkv.next_frame(&mut frame).unwrap();
+    mkv.seek_to_offset(345).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 1040);
+    assert_eq!(frame.data, b"V1040");
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 1080);
+
+    // The cluster timestamp isn't a block.
+    assert!(matches!(
+        mkv.seek_to_offset(330),
+        Err(DemuxError::UnexpectedElement((
+            ElementId::Cluster,
+            ElementId::Timestamp
+        )))
+    ));
+}
+
+#[test]
+pub fn export_index_simple_mkv() {
+    let settings = ParserSettings::default();
+    let file = File::open("tests/data/simple.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+    let index_data = mkv.export_index().unwrap();
+    // The index only contains the metadata, but not the clusters.
+    assert!(index_data.len() < 10_000);
+
+    let file = File::open("tests/data/simple.mkv").unwrap();
+    let mut imported = MatroskaFile::open_with_index(file, &index_data, &settings).unwrap();
+    assert_eq!(imported.tracks().len(), mkv.tracks().len());
+    assert_eq!(imported.tracks()[0].codec_id(), mkv.tracks()[0].codec_id());
+    assert_eq!(imported.info().duration(), mkv.info().duration());
+    assert_eq!(
+        imported.cue_points().unwrap().len(),
+        mkv.cue_points().unwrap().len()
+    );
+    assert_eq!(
+        imported.tags().unwrap()[0].simple_tags()[0].name(),
+        "ENCODER"
+    );
+
+    let mut frame = Frame::default();
+    let mut count = 0;
+    while imported.next_frame(&mut frame).unwrap() {
+        count += 1;
+    }
+    assert_eq!(count, 74);
+
+    let file = File::open("tests/data/block_groups.mkv").unwrap();
+    assert!(matches!(
+        MatroskaFile::open_with_index(file, &index_data, &settings),
+        Err(DemuxError::InvalidIndexData)
+    ));
+
+    let file = File::open("tests/data/simple.mkv").unwrap();
+    assert!(matches!(
+        MatroskaFile::open_with_index(file, &index_data[..index_data.len() / 2], &settings),
+        Err(DemuxError::InvalidIndexData)
+    ));
+}
+
+#[test]
+pub fn export_index_edited_simple_mkv() {
+    let settings = ParserSettings::default();
+    let data = std::fs::read("tests/data/simple.mkv").unwrap();
+
+    // The EBML header doesn't need to start at the first byte.
+    let mut shifted = b"JUNK".to_vec();
+    shifted.extend_from_slice(&data);
+    let mut file = Cursor::new(shifted.clone());
+    file.seek(SeekFrom::Start(4)).unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+    let index_data = mkv.export_index().unwrap();
+    let imported = MatroskaFile::open_with_index(Cursor::new(shifted), &index_data, &settings);
+    assert_eq!(imported.unwrap().tracks().len(), 2);
+
+    // An edit in place keeps the length of the file, but changes the recorded bytes.
+    let mut mkv = MatroskaFile::open(Cursor::new(data.clone())).unwrap();
+    let index_data = mkv.export_index().unwrap();
+    let tags = data.windows(7).rposition(|w| w == b"ENCODER").unwrap();
+    let mut edited = data;
+    edited[tags] = b'X';
+    assert!(matches!(
+        MatroskaFile::open_with_index(Cursor::new(edited), &index_data, &settings),
+        Err(DemuxError::InvalidIndexData)
+    ));
+}
+
+#[test]
+pub fn probe_simple_mkv() {
+    let file = File::open("tests/data/simple.mkv").unwrap();
+    let result = matroska_demuxer::probe(file).unwrap().unwrap();
+    assert_eq!(result.kind, ContainerKind::Matroska);
+    assert_eq!(result.doc_type_version, 4);
+    assert_eq!(result.doc_type_read_version, 2);
+    assert!(!result.is_live);
+
+    let mut data = std::fs::read("tests/data/simple.mkv").unwrap();
+    // Null padded DocType and unknown segment size.
+    data[24..32].copy_from_slice(b"webm\0\0\0\0");
+    data[44..52].copy_from_slice(&[0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]);
+    let result = matroska_demuxer::probe(std::io::Cursor::new(&data))
+        .unwrap()
+        .unwrap();
+    assert_eq!(result.kind, ContainerKind::WebM);
+    assert!(result.is_live);
+
+    data[24..32].copy_from_slice(b"unknown\0");
+    assert!(!is_matroska(std::io::Cursor::new(&data)).unwrap());
+    assert!(!is_matroska(std::io::Cursor::new(b"RIFF\0\0\0\0WAVE")).unwrap());
+    assert!(!is_matroska(std::io::Cursor::new(b"")).unwrap());
+}
+
+#[test]
+pub fn parse_track_cache_mkv() {
+    let file = File::open("tests/data/track_cache.mkv").unwrap();
+    let mkv = MatroskaFile::open(file).unwrap();
+
+    let video = &mkv.tracks()[0];
+    assert_eq!(video.default_duration().unwrap().get(), 40_000_000);
+    assert_eq!(
+        video.default_decoded_field_duration().unwrap().get(),
+        20_000_000
+    );
+    assert_eq!(video.min_cache(), 1);
+    assert_eq!(video.max_cache(), Some(2));
+    assert_eq!(video.max_block_addition_id(), 1);
+
+    let audio = &mkv.tracks()[1];
+    assert!(audio.default_decoded_field_duration().is_none());
+    assert_eq!(audio.min_cache(), 0);
+    assert_eq!(audio.max_cache(), None);
+    assert_eq!(audio.max_block_addition_id(), 0);
+    assert_eq!(audio.audio().unwrap().emphasis(), Emphasis::CdAudio);
+}
+
+#[test]
+pub fn parse_track_operation_mkv() {
+    let file = File::open("tests/data/track_operation.mkv").unwrap();
+    let mkv = MatroskaFile::open(file).unwrap();
+    assert!(mkv.tracks()[0].operation().is_none());
+
+    let planes = mkv.tracks()[2].operation().unwrap().combine_planes();
+    assert_eq!(planes.len(), 2);
+    assert_eq!(planes[0].uid().get(), 101);
+    assert_eq!(planes[0].plane_type(), TrackPlaneType::LeftEye);
+    assert_eq!(planes[1].uid().get(), 102);
+    assert_eq!(planes[1].plane_type(), TrackPlaneType::RightEye);
+    assert!(mkv.tracks()[2]
+        .operation()
+        .unwrap()
+        .join_blocks()
+        .is_empty());
+
+    let operation = mkv.tracks()[3].operation().unwrap();
+    assert!(operation.combine_planes().is_empty());
+    assert_eq!(operation.join_blocks(), [101, 102]);
+}
+
+#[test]
+pub fn codec_state_mkv() {
+    let file = File::open("tests/data/codec_state.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+    let mut frame = Frame::default();
+
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.data, b"V0");
+    assert_eq!(frame.reference_priority, Some(1));
+    assert_eq!(frame.codec_state.as_deref(), Some(&b"S0"[..]));
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.reference_priority, Some(0));
+    assert_eq!(frame.codec_state, None);
+
+    let cue_points = mkv.cue_points().unwrap();
+    assert_eq!(cue_points[0].track_positions()[0].codec_state(), None);
+    assert_eq!(cue_points[1].track_positions()[0].codec_state(), Some(210));
+
+    // The codec state of the cue point is reported with the first frame after the seek.
+    mkv.seek(1000).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.data, b"V2");
+    assert_eq!(frame.codec_state.as_deref(), Some(&b"S0"[..]));
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.codec_state.as_deref(), Some(&b"S1"[..]));
+
+    // Codec states of skipped blocks are reported too.
+    mkv.seek(1080).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.data, b"V4");
+    assert_eq!(frame.codec_state.as_deref(), Some(&b"S1"[..]));
+    assert!(!mkv.next_frame(&mut frame).unwrap());
+}
+
+#[test]
+pub fn codec_state_before_block_mkv() {
+    // Store the CodecState of the skipped BlockGroup before its Block.
+    let mut data = std::fs::read("tests/data/codec_state.mkv").unwrap();
+    assert_eq!(data[256..258], [0xA0, 0x8C]);
+    data[258..270].rotate_left(8);
+    let mut mkv = MatroskaFile::open(Cursor::new(data)).unwrap();
+    let mut frame = Frame::default();
+
+    mkv.seek(1080).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.data, b"V4");
+    assert_eq!(frame.codec_state.as_deref(), Some(&b"S1"[..]));
+}
+
+#[test]
+pub fn attachments_mkv() {
+    let file = File::open("tests/data/attachments.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+
+    let attachments = mkv.attachments().unwrap();
+    assert_eq!(attachments.len(), 2);
+    assert_eq!(attachments[0].uid().get(), 1);
+    assert_eq!(attachments[0].name(), "cover.bin");
+    assert_eq!(attachments[0].description(), Some("Cover"));
+    assert_eq!(attachments[0].media_type(), "application/octet-stream");
+    assert_eq!(attachments[0].size(), 10000);
+    assert_eq!(attachments[1].name(), "readme.txt");
+    assert_eq!(attachments[1].description(), None);
+
+    assert_eq!(mkv.read_attachment(2).unwrap(), b"Hello attachment");
+
+    // Reading the attachments doesn't change the position of the demuxer.
+    let mut frame = Frame::default();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.data, b"V0");
+
+    let mut chunks = Vec::new();
+    let mut data = Vec::new();
+    mkv.read_attachment_chunks(1, 4096, |chunk| {
+        chunks.push(chunk.len());
+        data.extend_from_slice(chunk);
+    })
+    .unwrap();
+    assert_eq!(chunks, [4096, 4096, 1808]);
+    let expected: Vec<u8> = (0..10000).map(|i| (i % 251) as u8).collect();
+    assert_eq!(data, expected);
+
+    let mut reader = mkv.attachment_reader(1).unwrap();
+    let mut head = [0_u8; 300];
+    reader.read_exact(&mut head).unwrap();
+    assert_eq!(head[..], expected[..300]);
+    assert_eq!(reader.remaining(), 9700);
+    let mut rest = Vec::new();
+    reader.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest[..], expected[300..]);
+
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.data, b"V1");
+
+    let err = mkv.read_attachment(3).unwrap_err();
+    assert!(matches!(err, DemuxError::AttachmentNotFound(3)));
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+pub fn analyze_timestamps_parallel() {
+    for path in [
+        "tests/data/simple.mkv",
+        "tests/data/block_groups.mkv",
+        "tests/data/events.mkv",
+    ] {
+        let mut mkv = MatroskaFile::open(File::open(path).unwrap()).unwrap();
+        let expected = mkv.analyze_timestamps().unwrap();
+
+        let index = Arc::clone(mkv.index());
+        for threads in [0, 1, 2, 3, 16] {
+            let report = index
+                .analyze_timestamps_parallel(|| File::open(path), threads)
+                .unwrap();
+            assert_eq!(report, expected, "{} with {} threads", path, threads);
+        }
+    }
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+pub fn analyze_timestamps_parallel_read_errors() {
+    let mut data = std::fs::read("tests/data/block_groups.mkv").unwrap();
+    let mkv = MatroskaFile::open(Cursor::new(data.clone())).unwrap();
+    let index = Arc::clone(mkv.index());
+
+    // A broken cluster header is reported instead of ending the chunks early.
+    data[458] = 0xFF;
+    assert!(matches!(
+        index.analyze_timestamps_parallel(|| Ok(Cursor::new(&data)), 2),
+        Err(DemuxError::InvalidEbmlElementId)
+    ));
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+pub fn validate_parallel() {
+    for path in [
+        "tests/data/simple.mkv",
+        "tests/data/block_groups.mkv",
+        "tests/data/events.mkv",
+        "tests/data/invalid.mkv",
+        "tests/data/misplaced.mkv",
+    ] {
+        let mut mkv = MatroskaFile::open(File::open(path).unwrap()).unwrap();
+        let expected = mkv.validate().unwrap();
+
+        let index = Arc::clone(mkv.index());
+        for threads in [0, 1, 2, 3, 16] {
+            let report = index
+                .validate_parallel(|| File::open(path), threads)
+                .unwrap();
+            assert_eq!(report, expected, "{} with {} threads", path, threads);
+        }
+    }
+}
+
+#[test]
+pub fn into_inner_simple_mkv() {
+    let file = File::open("tests/data/simple.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+    let mut frame = Frame::default();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    let second_frame = frame.clone();
+
+    // Restoring the position after using the reader keeps the demuxer valid.
+    let position = mkv.get_mut().stream_position().unwrap();
+    mkv.get_mut().seek(SeekFrom::Start(0)).unwrap();
+    mkv.get_mut().seek(SeekFrom::Start(position)).unwrap();
+    assert!(mkv.get_ref().metadata().unwrap().len() > position);
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    let third_frame = frame.clone();
+
+    // The reader can be turned back into a demuxer.
+    let index = Arc::clone(mkv.index());
+    let file = mkv.into_inner();
+    let mut mkv = MatroskaReader::new(index, file).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.data, second_frame.data);
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.data, third_frame.data);
+}
+
+#[cfg(feature = "spool")]
+#[test]
+pub fn spool_simple_mkv() {
+    use matroska_demuxer::SpoolReader;
+
+    // Chaining readers removes the ability to seek, like a pipe.
+    let file = File::open("tests/data/simple.mkv").unwrap();
+    let pipe = std::io::empty().chain(file);
+    let mut mkv = MatroskaFile::open(SpoolReader::with_memory_limit(pipe, 16 * 1024)).unwrap();
+
+    let mut frame = Frame::default();
+    let mut count = 0;
+    while mkv.next_frame(&mut frame).unwrap() {
+        count += 1;
+    }
+    assert_eq!(count, 74);
+    assert!(mkv.get_ref().is_spilled());
+
+    mkv.seek(3).unwrap();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.timestamp, 3);
+}
+
+#[test]
+pub fn track_for_frame_simple_mkv() {
+    let file = File::open("tests/data/simple.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+    let mut frame = Frame::default();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.track_type, None);
+    let track = mkv.track_for_frame(&frame).unwrap();
+    assert_eq!(track.track_number().get(), frame.track);
+
+    let file = File::open("tests/data/simple.mkv").unwrap();
+    let settings = ParserSettings::default().stamp_track_type(true);
+    let mut mkv = MatroskaFile::open_with_settings(file, &settings).unwrap();
+    while mkv.next_frame(&mut frame).unwrap() {
+        let track = mkv.track_for_frame(&frame).unwrap();
+        assert_eq!(frame.track_type, Some(track.track_type()));
+    }
+}
+
+#[test]
+pub fn clear_expired_subtitles_block_groups_mkv() {
+    let file = File::open("tests/data/block_groups.mkv").unwrap();
+    let settings = ParserSettings::default().clear_expired_subtitles(true);
+    let mut mkv = MatroskaFile::open_with_settings(file, &settings).unwrap();
+
+    let mut frames = Vec::new();
+    let mut frame = Frame::default();
+    while mkv.next_frame(&mut frame).unwrap() {
+        frames.push(frame.clone());
+    }
+    assert_eq!(frames.len(), 16);
+
+    // The overlapping subtitles at 50 and 300 expire at 550 and 700, so the track is only
+    // cleared once at 700. The subtitle at 1100 expires at 1400.
+    let virtual_frames: Vec<(usize, u64, u64)> = frames
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| f.is_virtual)
+        .map(|(i, f)| (i, f.track, f.timestamp))
+        .collect();
+    assert_eq!(virtual_frames, [(10, 3, 700), (15, 3, 1400)]);
+    assert!(frames[10].data.is_empty());
+    assert_eq!(frames[11].timestamp, 1000);
+    assert_eq!(frames[11].data, b"V3key");
+    assert!(!frames[11].is_virtual);
+}
+
+#[cfg(feature = "subtitle")]
+#[test]
+pub fn subtitle_events_block_groups_mkv() {
+    use matroska_demuxer::SubtitleFormat;
+
+    let file = File::open("tests/data/block_groups.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+    assert!(matches!(
+        mkv.subtitle_events(1),
+        Err(DemuxError::UnsupportedSubtitleCodec(codec_id)) if codec_id == "V_VP9"
+    ));
+
+    let events = mkv.subtitle_events(3).unwrap();
+    assert_eq!(events.format(), SubtitleFormat::Utf8);
+    assert_eq!(events.header(), None);
+    let events: Vec<_> = events.map(|event| event.unwrap()).collect();
+
+    let timings: Vec<(i64, Option<u64>)> =
+        events.iter().map(|e| (e.start_ns, e.duration_ns)).collect();
+    assert_eq!(
+        timings,
+        [
+            (50_000_000, Some(500_000_000)),
+            (300_000_000, Some(400_000_000)),
+            (1_100_000_000, Some(300_000_000))
+        ]
+    );
+    assert_eq!(events[0].text(), Some("Hello"));
+    assert_eq!(
+        events[2].to_srt(3).unwrap(),
+        "3\n00:00:01,100 --> 00:00:01,400\nBye\n\n"
+    );
+    assert_eq!(events[2].to_ass_dialogue(), None);
+}
+
+#[test]
+pub fn timestamp_discontinuity_appended_mkv() {
+    let read_all = |mkv: &mut MatroskaFile<File>| {
+        let mut frame = Frame::default();
+        let mut timestamps = Vec::new();
+        let mut discontinuities = Vec::new();
+        while let Some(event) = mkv.next_event(&mut frame).unwrap() {
+            match event {
+                DemuxEvent::Frame => timestamps.push(frame.timestamp),
+                DemuxEvent::TimestampDiscontinuity {
+                    previous_timestamp,
+                    timestamp,
+                    offset,
+                } => discontinuities.push((previous_timestamp, timestamp, offset)),
+                _ => {}
+            }
+        }
+        (timestamps, discontinuities)
+    };
+
+    let file = File::open("tests/data/appended.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+    let (timestamps, discontinuities) = read_all(&mut mkv);
+    assert_eq!(timestamps, [0, 40, 80, 120, 160, 0, 40, 80]);
+    assert_eq!(discontinuities, [(120, 0, 0)]);
+    assert!(mkv.timestamp_offsets().is_empty());
+
+    // The second part continues after the last frame of the first part, which ends at 200.
+    let file = File::open("tests/data/appended.mkv").unwrap();
+    let settings = ParserSettings::default().stitch_timestamps(true);
+    let mut mkv = MatroskaFile::open_with_settings(file, &settings).unwrap();
+    let (timestamps, discontinuities) = read_all(&mut mkv);
+    assert_eq!(timestamps, [0, 40, 80, 120, 160, 200, 240, 280]);
+    assert_eq!(discontinuities, [(120, 0, 200)]);
+    let offsets = mkv.timestamp_offsets().to_vec();
+    assert_eq!(offsets.len(), 1);
+    assert_eq!((offsets[0].timestamp, offsets[0].offset), (0, 200));
+
+    // The recorded offsets are applied after a seek.
+    mkv.seek_to_offset(offsets[0].position).unwrap();
+    let (timestamps, discontinuities) = read_all(&mut mkv);
+    assert_eq!(timestamps, [200, 240, 280]);
+    assert!(discontinuities.is_empty());
+}
+
+#[test]
+pub fn register_element_custom_element_mkv() {
+    let file = File::open("tests/data/custom_element.mkv").unwrap();
+    let settings = ParserSettings::default()
+        .record_skipped_elements(true)
+        .register_element(0x5FA1, "VendorData", ElementType::Binary)
+        // Elements of the specification can't be overridden.
+        .register_element(0xE7, "NotTimestamp", ElementType::Binary);
+    let mut mkv = MatroskaFile::open_with_settings(file, &settings).unwrap();
+
+    let mut frame = Frame::default();
+    let mut frames = 0;
+    while mkv.next_frame(&mut frame).unwrap() {
+        frames += 1;
+    }
+    assert_eq!(frames, 2);
+
+    let skipped: Vec<(u32, ElementId, u64)> = mkv
+        .skipped_elements()
+        .iter()
+        .map(|e| (e.id, e.element_id, e.offset))
+        .collect();
+    assert_eq!(
+        skipped,
+        [
+            (0x5FA1, ElementId::Custom(0x5FA1), 202),
+            (0x5FA2, ElementId::Unknown, 219)
+        ]
+    );
+
+    let element = mkv.custom_element(0x5FA1).unwrap();
+    assert_eq!(element.name, "VendorData");
+    assert_eq!(element.element_type, ElementType::Binary);
+    assert!(mkv.custom_element(0xE7).is_none());
+    assert!(mkv.custom_element(0x5FA2).is_none());
+}
+
+#[test]
+pub fn strict_placement_valid_files() {
+    for path in [
+        "tests/data/simple.mkv",
+        "tests/data/block_groups.mkv",
+        "tests/data/attachments.mkv",
+        "tests/data/codec_state.mkv",
+        "tests/data/track_operation.mkv",
+        "tests/data/custom_element.mkv",
+        "tests/data/appended.mkv",
+        "tests/data/encrypted.mkv",
+        "tests/data/events.mkv",
+        "tests/data/hdr.mkv",
+        "tests/data/relative_cues.mkv",
+        "tests/data/restart.mkv",
+        "tests/data/silent_tracks.mkv",
+        "tests/data/track_cache.mkv",
+    ] {
+        let file = File::open(path).unwrap();
+        let settings = ParserSettings::default().strict(true);
+        let mut mkv = MatroskaFile::open_with_settings(file, &settings).unwrap();
+        let mut frame = Frame::default();
+        while mkv.next_frame(&mut frame).unwrap() {}
+    }
+}
+
+#[test]
+pub fn strict_placement_misplaced_mkv() {
+    let read_all = |path, strict| -> Result<usize, DemuxError> {
+        let file = File::open(path).unwrap();
+        let settings = ParserSettings::default().strict(strict);
+        let mut mkv = MatroskaFile::open_with_settings(file, &settings)?;
+        let mut frame = Frame::default();
+        let mut frames = 0;
+        while mkv.next_frame(&mut frame)? {
+            frames += 1;
+        }
+        Ok(frames)
+    };
+
+    assert_eq!(read_all("tests/data/misplaced.mkv", false).unwrap(), 2);
+    let err = read_all("tests/data/misplaced.mkv", true).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::MisplacedElement);
+    assert!(matches!(
+        err,
+        DemuxError::MisplacedElement {
+            element: ElementId::Timestamp,
+            parent: ElementId::Segment,
+            offset: 202
+        }
+    ));
+
+    assert_eq!(
+        read_all("tests/data/misplaced_track.mkv", false).unwrap(),
+        2
+    );
+    assert!(matches!(
+        read_all("tests/data/misplaced_track.mkv", true),
+        Err(DemuxError::MisplacedElement {
+            element: ElementId::Channels,
+            parent: ElementId::Video,
+            offset: 186
+        })
+    ));
+}
+
+#[test]
+pub fn editions_mkv() {
+    let file = File::open("tests/data/editions.mkv").unwrap();
+    let mkv = MatroskaFile::open(file).unwrap();
+
+    let chapters = mkv.chapters().unwrap();
+    assert_eq!(chapters.len(), 3);
+    assert!(chapters[0].flag_hidden() && chapters[0].flag_default());
+    assert!(chapters[2].flag_ordered());
+
+    let uids: Vec<u64> = mkv
+        .editions()
+        .map(|edition| edition.uid().unwrap().get())
+        .collect();
+    assert_eq!(uids, [2, 3]);
+    assert_eq!(mkv.default_edition().unwrap().uid().unwrap().get(), 3);
+
+    // Without a flagged edition the first edition is the default.
+    let file = File::open("tests/data/duplicate_metadata.mkv").unwrap();
+    let mkv = MatroskaFile::open(file).unwrap();
+    let edition = mkv.default_edition().unwrap();
+    assert_eq!(edition.uid(), None);
+    assert_eq!(edition.chapter_atoms()[0].uid().get(), 1);
 }
 
 #[test]
-pub fn parse_test5_mkv() {
-    let file = File::open("tests/data/test5.mkv").unwrap();
-    let mut mkv = MatroskaFile::open(file).unwrap();
+pub fn nested_chapters_mkv() {
+    const S: u64 = 1_000_000_000;
+    let file = File::open("tests/data/nested_chapters.mkv").unwrap();
+    let mkv = MatroskaFile::open(file).unwrap();
+
+    let chapter_at = |time| mkv.chapter_at(time, None).map(|atom| atom.uid().get());
+    assert_eq!(chapter_at(0), Some(11));
+    assert_eq!(chapter_at(3 * S), Some(12));
+    assert_eq!(chapter_at(6 * S), Some(2));
+    // Chapter 2 ended at 8 s.
+    assert_eq!(chapter_at(9 * S), None);
+    assert_eq!(chapter_at(12 * S), Some(3));
+
+    assert_eq!(mkv.next_chapter_boundary(0), Some(2 * S));
+    assert_eq!(mkv.next_chapter_boundary(3 * S), Some(5 * S));
+    assert_eq!(mkv.next_chapter_boundary(6 * S), Some(8 * S));
+    assert_eq!(mkv.next_chapter_boundary(8 * S), Some(10 * S));
+    assert_eq!(mkv.next_chapter_boundary(10 * S), None);
+
+    let edition = &mkv.chapters().unwrap()[0];
+    assert_eq!(mkv.chapter_at(5 * S, Some(edition)).unwrap().uid().get(), 2);
+}
 
-    assert_eq!(mkv.ebml_header().version(), Some(1));
-    assert_eq!(mkv.ebml_header().read_version(), Some(1));
-    assert_eq!(mkv.ebml_header().doc_type(), "matroska");
-    assert_eq!(mkv.ebml_header().doc_type_version(), 2);
-    assert_eq!(mkv.ebml_header().doc_type_read_version(), 2);
-    assert_eq!(mkv.ebml_header().max_id_length(), 4);
-    assert_eq!(mkv.ebml_header().max_size_length(), 8);
+#[test]
+pub fn live_mkv() {
+    let data = std::fs::read("tests/data/live.mkv").unwrap();
+    let file = File::open("tests/data/block_groups.mkv").unwrap();
+    assert!(!MatroskaFile::open(file).unwrap().is_live());
+
+    for cut in [306, 310] {
+        let cursor = std::io::Cursor::new(data[..cut].to_vec());
+        let mut mkv = MatroskaFile::open(cursor).unwrap();
+        assert!(mkv.is_live());
+
+        let mut frame = Frame::default();
+        let mut frames = Vec::new();
+        loop {
+            match mkv.next_frame(&mut frame) {
+                Ok(true) => frames.push(frame.data.clone()),
+                Ok(false) => break,
+                Err(err) => {
+                    // The file ends inside the SimpleBlock.
+                    assert_eq!(cut, 310);
+                    assert_eq!(err.code(), ErrorCode::UnexpectedEof);
+                    break;
+                }
+            }
+        }
+        assert_eq!(frames, [b"V0", b"V1"]);
+        assert!(!mkv.refresh().unwrap());
+
+        mkv.get_mut().get_mut().extend_from_slice(&data[cut..]);
+        assert!(mkv.refresh().unwrap());
+        while mkv.next_frame(&mut frame).unwrap() {
+            frames.push(frame.data.clone());
+        }
+        assert_eq!(frames, [b"V0", b"V1", b"V2", b"V3"]);
+        assert_eq!(frame.timestamp, 1040);
+    }
+}
+
+#[test]
+pub fn follow_mode_live_mkv() {
+    let data = std::fs::read("tests/data/live.mkv").unwrap();
+    let cursor = std::io::Cursor::new(data[..310].to_vec());
+    let mut mkv = MatroskaFile::open(cursor).unwrap();
+    mkv.set_follow_mode(true);
 
+    let mut frame = Frame::default();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.data, b"V1");
+
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&attempts);
+    mkv.set_follow_callback(move |attempt| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        attempt < 2
+    });
+    let err = mkv.next_frame(&mut frame).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::WouldBlock);
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+    mkv.clear_follow_callback();
     assert_eq!(
-        mkv.info().timestamp_scale(),
-        NonZeroU64::new(1000000).unwrap()
+        mkv.next_frame(&mut frame).unwrap_err().code(),
+        ErrorCode::WouldBlock
     );
-    assert!((46665.0 - mkv.info().duration().unwrap()).abs() < f64::EPSILON);
-    assert_eq!(mkv.info().date_utc().unwrap(), 304106803000000000);
 
-    assert_eq!(mkv.tracks().len(), 11);
+    // The SimpleBlock is still incomplete.
+    mkv.get_mut().get_mut().extend_from_slice(&data[310..312]);
     assert_eq!(
-        mkv.tracks()
-            .iter()
-            .filter(|t| t.track_type() == TrackType::Audio)
-            .count(),
-        2
+        mkv.next_frame(&mut frame).unwrap_err().code(),
+        ErrorCode::WouldBlock
     );
+
+    mkv.get_mut().get_mut().extend_from_slice(&data[312..]);
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.data, b"V2");
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.data, b"V3");
     assert_eq!(
-        mkv.tracks()
-            .iter()
-            .filter(|t| t.track_type() == TrackType::Subtitle)
-            .count(),
-        8
+        mkv.next_frame(&mut frame).unwrap_err().code(),
+        ErrorCode::WouldBlock
     );
 
-    let audio_tracks: Vec<TrackEntry> = mkv
-        .tracks()
+    mkv.set_follow_mode(false);
+    assert!(!mkv.next_frame(&mut frame).unwrap());
+}
+
+#[test]
+pub fn duplicate_metadata_mkv() {
+    let open = |scan| {
+        let file = File::open("tests/data/duplicate_metadata.mkv").unwrap();
+        let settings = ParserSettings::default().scan_top_level_elements(scan);
+        MatroskaFile::open_with_settings(file, &settings).unwrap()
+    };
+    let titles = |mkv: &MatroskaFile<File>| -> Vec<String> {
+        mkv.tags()
+            .unwrap()
+            .iter()
+            .flat_map(|tag| tag.simple_tags())
+            .filter_map(|simple_tag| simple_tag.string().map(str::to_owned))
+            .collect()
+    };
+
+    // Both Chapters elements are referenced by the SeekHead.
+    let mkv = open(false);
+    let uids: Vec<u64> = mkv
+        .chapters()
+        .unwrap()
         .iter()
-        .filter(|t| t.track_type() == TrackType::Audio)
-        .cloned()
+        .flat_map(|edition| edition.chapter_atoms())
+        .map(|atom| atom.uid().get())
         .collect();
+    assert_eq!(uids, [1, 2]);
+    assert_eq!(titles(&mkv), ["First"]);
 
-    assert!((48000.0 - audio_tracks[0].audio().unwrap().sampling_frequency()).abs() < f64::EPSILON);
-    assert_eq!(audio_tracks[0].audio().unwrap().channels().get(), 2);
+    // The second Tags element is only found by the scan.
+    let mkv = open(true);
+    assert_eq!(mkv.chapters().unwrap().len(), 2);
+    assert_eq!(titles(&mkv), ["First", "Second"]);
+}
 
-    assert!((22050.0 - audio_tracks[1].audio().unwrap().sampling_frequency()).abs() < f64::EPSILON);
-    assert!(
-        (44100.0
-            - audio_tracks[1]
-                .audio()
-                .unwrap()
-                .output_sampling_frequency()
-                .unwrap())
-        .abs()
-            < f64::EPSILON
+#[test]
+pub fn seek_head_policy_stale_seek_head_mkv() {
+    let open = |policy| {
+        let file = File::open("tests/data/stale_seek_head.mkv").unwrap();
+        let settings = ParserSettings::default().seek_head_policy(policy);
+        MatroskaFile::open_with_settings(file, &settings)
+    };
+
+    // The Tracks entry points at the Info element.
+    assert!(open(SeekHeadPolicy::Trust).is_err());
+    assert!(matches!(
+        open(SeekHeadPolicy::Verify),
+        Err(DemuxError::InvalidSeekEntry(ElementId::Tracks, 120))
+    ));
+
+    let mut mkv = open(SeekHeadPolicy::Repair).unwrap();
+    assert_eq!(
+        mkv.seek_head_mismatches(),
+        [
+            SeekHeadMismatch {
+                element_id: ElementId::Tracks,
+                offset: 120,
+                found: Some(ElementId::Info),
+                repaired_offset: Some(167),
+            },
+            SeekHeadMismatch {
+                element_id: ElementId::Cues,
+                offset: 1358,
+                found: None,
+                repaired_offset: Some(358),
+            },
+        ]
     );
+    assert_eq!(mkv.tracks().len(), 3);
+    assert_eq!(mkv.cue_points().unwrap().len(), 2);
 
+    mkv.seek(1000).unwrap();
     let mut frame = Frame::default();
-    while mkv.next_frame(&mut frame).unwrap() {}
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.data, b"V1key");
 
-    mkv.seek(0).unwrap();
+    let report = mkv.validate().unwrap();
+    let issues: Vec<(Severity, ElementId, u64)> = report
+        .issues
+        .iter()
+        .filter(|issue| issue.kind == ValidationIssueKind::WrongPosition)
+        .map(|issue| (issue.severity, issue.element, issue.offset))
+        .collect();
+    assert_eq!(
+        issues,
+        [
+            (Severity::Error, ElementId::Tracks, 120),
+            (Severity::Error, ElementId::Cues, 1358),
+            (Severity::Warning, ElementId::Position, 324),
+        ]
+    );
+}
+
+#[cfg(feature = "dump")]
+#[test]
+pub fn dump_block_groups_mkv() {
+    use matroska_demuxer::dump::DumpOptions;
+
+    let file = File::open("tests/data/block_groups.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+    let mut frame = Frame::default();
     assert!(mkv.next_frame(&mut frame).unwrap());
-    assert_eq!(frame.timestamp, 0);
 
-    mkv.seek(2000).unwrap();
+    let report = mkv.dump(&DumpOptions::default()).unwrap();
+    let lines: Vec<&str> = report.lines().collect();
+    assert!(lines[0].starts_with("+ Ebml [1A45DFA3] offset 0, size "));
+    assert!(lines.contains(&"  + DocType [4282] offset 21, size 8: \"matroska\""));
+    assert!(lines
+        .iter()
+        .any(|line| line.starts_with("    + TimestampScale [2AD7B1] ")
+            && line.ends_with(": 1000000")));
+    assert!(lines
+        .iter()
+        .any(|line| line.starts_with("      + Block [A1] ")
+            && line.ends_with("track 1, timestamp 0, flags 0x00")));
+    assert!(lines.iter().all(|line| !line.contains('!')));
+
+    let without_blocks = mkv.dump(&DumpOptions::default().blocks(false)).unwrap();
+    assert!(!without_blocks.contains("SimpleBlock"));
+    assert!(!without_blocks.contains("BlockGroup"));
+
+    // The position of the demuxer is restored.
     assert!(mkv.next_frame(&mut frame).unwrap());
-    assert_eq!(frame.timestamp, 2000);
+    assert_eq!(frame.data, b"a0");
+}
 
-    mkv.seek(1_000_000).unwrap();
-    assert!(!mkv.next_frame(&mut frame).unwrap());
+#[cfg(feature = "dump")]
+#[test]
+pub fn dump_custom_element_mkv() {
+    use matroska_demuxer::dump::{dump, DumpOptions};
+
+    let settings =
+        ParserSettings::default().register_element(0x5FA1, "VendorData", ElementType::String);
+    let file = File::open("tests/data/custom_element.mkv").unwrap();
+    let mut mkv = MatroskaFile::open_with_settings(file, &settings).unwrap();
+
+    // Registered elements are printed with their name and parsed with their type.
+    let report = mkv.dump(&DumpOptions::default()).unwrap();
+    assert!(report.contains("+ VendorData [5FA1] offset 202, size 6: \"vendor\""));
+    assert!(report.contains("+ Unknown [5FA2] offset 219, size 1: 07"));
+
+    let mut file = File::open("tests/data/custom_element.mkv").unwrap();
+    let report = dump(&mut file, &DumpOptions::default()).unwrap();
+    assert!(report.contains("+ Unknown [5FA1] offset 202, size 6: 76656e646f72"));
 }
 
 #[test]
-pub fn parse_test6_mkv() {
-    let file = File::open("tests/data/test6.mkv").unwrap();
+pub fn lenient_no_muxing_app_mkv() {
+    let file = File::open("tests/data/no_muxing_app.mkv").unwrap();
+    let err = MatroskaFile::open(file).unwrap_err();
+    assert!(matches!(
+        err,
+        DemuxError::ElementNotFound(ElementId::MuxingApp)
+    ));
+
+    let file = File::open("tests/data/no_muxing_app.mkv").unwrap();
+    let settings = ParserSettings::default().lenient(true);
+    let mut mkv = MatroskaFile::open_with_settings(file, &settings).unwrap();
+    assert_eq!(mkv.info().muxing_app(), "");
+    assert_eq!(mkv.info().writing_app(), "");
+
+    let warnings: Vec<(Severity, ValidationIssueKind, ElementId, u64)> = mkv
+        .parse_warnings()
+        .iter()
+        .map(|issue| (issue.severity, issue.kind, issue.element, issue.offset))
+        .collect();
+    assert_eq!(
+        warnings,
+        [
+            (
+                Severity::Warning,
+                ValidationIssueKind::MissingElement,
+                ElementId::MuxingApp,
+                99
+            ),
+            (
+                Severity::Warning,
+                ValidationIssueKind::MissingElement,
+                ElementId::WritingApp,
+                99
+            ),
+        ]
+    );
+
+    let mut frame = Frame::default();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.data, b"V0key");
+
+    let report = mkv.validate().unwrap();
+    let missing: Vec<ElementId> = report
+        .errors()
+        .filter(|issue| issue.kind == ValidationIssueKind::MissingElement)
+        .map(|issue| issue.element)
+        .collect();
+    assert_eq!(missing, [ElementId::MuxingApp, ElementId::WritingApp]);
+}
+
+#[cfg(feature = "bzip2")]
+#[test]
+pub fn bzip2_compression_mkv() {
+    let file = File::open("tests/data/compressed.mkv").unwrap();
     let mut mkv = MatroskaFile::open(file).unwrap();
 
-    assert_eq!(mkv.ebml_header().max_id_length(), 4);
-    assert_eq!(mkv.ebml_header().max_size_length(), 8);
+    let mut frame = Frame::default();
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.track, 1);
+    assert_eq!(frame.data, b"Hello Hello Hello");
+    assert_eq!(frame.compression, None);
+}
+
+#[cfg(feature = "bzip2")]
+#[test]
+pub fn max_decompressed_size_mkv() {
+    let file = File::open("tests/data/compressed.mkv").unwrap();
+    let settings = ParserSettings::default().max_decompressed_size(16);
+    let mut mkv = MatroskaFile::open_with_settings(file, &settings).unwrap();
+
+    let mut frame = Frame::default();
+    let err = mkv.next_frame(&mut frame).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::DecompressedSizeExceeded);
+}
+
+#[cfg(feature = "lzo")]
+#[test]
+pub fn lzo_compression_mkv() {
+    let file = File::open("tests/data/compressed.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+
+    // The first frame needs the bzip2 feature, the demuxer continues after it either way.
+    let mut frame = Frame::default();
+    let _ = mkv.next_frame(&mut frame);
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.track, 2);
+    assert_eq!(frame.data, b"abcabcabcab");
+}
+
+#[test]
+pub fn audio_only_mka() {
+    let file = File::open("tests/data/audio_only.mka").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
 
+    let index = mkv.sample_index(1).unwrap();
+    assert_eq!(index.samples_per_frame(), Some(960));
+    assert_eq!(index.total_samples(), Some(6720));
+    let entries: Vec<(u64, u64, u64)> = index
+        .entries()
+        .iter()
+        .map(|e| (e.offset, e.frame_count, e.first_sample))
+        .collect();
     assert_eq!(
-        mkv.info().timestamp_scale(),
-        NonZeroU64::new(1000000).unwrap()
+        entries,
+        [
+            (244, 3, 0),
+            (267, 1, 2880),
+            (283, 1, 3840),
+            (299, 1, 4800),
+            (307, 1, 5760)
+        ]
     );
-    assert!((87336.0 - mkv.info().duration().unwrap()).abs() < f64::EPSILON);
-    assert_eq!(mkv.info().date_utc().unwrap(), 304101115000000000);
-
-    assert_eq!(mkv.tracks().len(), 2);
 
     let mut frame = Frame::default();
-    while mkv.next_frame(&mut frame).unwrap() {}
+    let seek = mkv.seek_sample(1, 3000).unwrap().unwrap();
+    assert_eq!(
+        seek,
+        SampleSeek {
+            timestamp: 60,
+            offset: 267,
+            first_sample: 2880,
+            skip_samples: 120,
+        }
+    );
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.data, b"a3");
 
-    mkv.seek(0).unwrap();
+    let seek = mkv.seek_sample(1, 5000).unwrap().unwrap();
+    assert_eq!((seek.timestamp, seek.skip_samples), (100, 200));
     assert!(mkv.next_frame(&mut frame).unwrap());
-    assert_eq!(frame.timestamp, 0);
+    assert_eq!((frame.data.as_slice(), frame.timestamp), (&b"a5"[..], 100));
 
-    mkv.seek(1000).unwrap();
+    let seek = mkv.seek_sample(1, 1000).unwrap().unwrap();
+    assert_eq!((seek.offset, seek.skip_samples), (244, 1000));
     assert!(mkv.next_frame(&mut frame).unwrap());
-    assert_eq!(frame.timestamp, 1000);
+    assert_eq!(frame.data, b"a0");
 
-    mkv.seek(1_000_000).unwrap();
-    assert!(!mkv.next_frame(&mut frame).unwrap());
-}
+    assert_eq!(mkv.seek_sample(1, 6720).unwrap(), None);
 
-#[test]
-pub fn parse_test7_mkv() {
-    let file = File::open("tests/data/test7.mkv").unwrap();
+    // Without a DefaultDuration, the samples are derived from the timestamps.
+    let index = mkv.sample_index(2).unwrap();
+    assert_eq!(index.total_samples(), None);
+    let seek = mkv.seek_sample(2, 2300).unwrap().unwrap();
+    assert_eq!(
+        (seek.offset, seek.first_sample, seek.skip_samples),
+        (275, 2205, 95)
+    );
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.data, b"p1");
+
+    // The sample index takes the reported track numbers.
+    let remap = mkv.index().track_remap_with_offset(10);
+    mkv.set_track_remap(remap);
+    let index = mkv.sample_index(11).unwrap();
+    assert_eq!((index.track(), index.total_samples()), (11, Some(6720)));
+    let seek = mkv.seek_sample(11, 3000).unwrap().unwrap();
+    assert_eq!((seek.offset, seek.skip_samples), (267, 120));
+    assert!(mkv.next_frame(&mut frame).unwrap());
+    assert_eq!((frame.track, frame.data.as_slice()), (11, &b"a3"[..]));
+
+    let file = File::open("tests/data/block_groups.mkv").unwrap();
     let mut mkv = MatroskaFile::open(file).unwrap();
+    let err = mkv.seek_sample(1, 0).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ElementNotFound);
+    assert_eq!(
+        mkv.seek_sample(4, 0).unwrap_err().code(),
+        ErrorCode::TrackNotFound
+    );
+}
 
-    assert_eq!(mkv.ebml_header().version(), None);
-    assert_eq!(mkv.ebml_header().read_version(), None);
-    assert_eq!(mkv.ebml_header().doc_type(), "matroska");
-    assert_eq!(mkv.ebml_header().doc_type_version(), 2);
-    assert_eq!(mkv.ebml_header().doc_type_read_version(), 2);
-    assert_eq!(mkv.ebml_header().max_id_length(), 4);
-    assert_eq!(mkv.ebml_header().max_size_length(), 8);
+#[test]
+pub fn statistics_mkv() {
+    let file = File::open("tests/data/statistics.mkv").unwrap();
+    let mkv = MatroskaFile::open(file).unwrap();
+    let tags = mkv.tags().unwrap();
 
+    let statistics = mkv.tracks()[0].muxer_statistics(tags).unwrap();
     assert_eq!(
-        mkv.info().timestamp_scale(),
-        NonZeroU64::new(1000000).unwrap()
+        statistics,
+        MuxerStatistics {
+            bits_per_second: Some(1_234_567),
+            duration: Some(Duration::new(83, 456_000_000)),
+            frame_count: Some(2087),
+            byte_count: Some(12_878_990),
+            writing_app: Some("mkvmerge v80.0 ('Roundabout') 64-bit".to_owned()),
+            writing_date_utc: Some("2024-01-02 03:04:05".to_owned()),
+        }
     );
-    assert!((37043.0 - mkv.info().duration().unwrap()).abs() < f64::EPSILON);
-    assert_eq!(mkv.info().date_utc().unwrap(), 304102823000000000);
 
-    assert_eq!(mkv.tracks().len(), 2);
+    let statistics = mkv.tracks()[1].muxer_statistics(tags).unwrap();
+    assert_eq!(statistics.bits_per_second, Some(96000));
+    assert_eq!(statistics.duration, None);
+
+    assert_eq!(mkv.tracks()[2].muxer_statistics(tags), None);
+}
+
+#[test]
+pub fn invalid_strings_mkv() {
+    let file = File::open("tests/data/invalid_strings.mkv").unwrap();
+    let err = MatroskaFile::open(file).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::FromUtf8);
+
+    for lazy_metadata in [false, true] {
+        let file = File::open("tests/data/invalid_strings.mkv").unwrap();
+        let settings = ParserSettings::default()
+            .lenient(true)
+            .lazy_metadata(lazy_metadata);
+        let mut mkv = MatroskaFile::open_with_settings(file, &settings).unwrap();
+        assert_eq!(mkv.info().title(), Some("Title"));
+        assert_eq!(mkv.tracks()[0].name(), Some("Caf\u{FFFD}"));
+
+        let tags = mkv.load_tags().unwrap().unwrap();
+        let simple_tag = &tags[0].simple_tags()[0];
+        assert_eq!(simple_tag.string(), Some("bad \u{FFFD}\u{FFFD} byte"));
+        assert_eq!(simple_tag.invalid_string(), Some(&b"bad \xff\xfe byte"[..]));
+        assert_eq!(mkv.info().invalid_title(), None);
+        assert_eq!(mkv.tracks()[0].invalid_name(), Some(&b"Caf\xe9"[..]));
+
+        // Tags that are read while demuxing are decoded lossily as well.
+        let data = std::fs::read("tests/data/invalid_strings.mkv").unwrap();
+        let start = data
+            .windows(4)
+            .rposition(|id| id == [0x12, 0x54, 0xC3, 0x67])
+            .unwrap();
+        let range = u64::try_from(start).unwrap()..u64::try_from(data.len()).unwrap();
+        let tags = mkv.read_tags(range).unwrap();
+        let simple_tag = &tags[0].simple_tags()[0];
+        assert_eq!(simple_tag.string(), Some("bad \u{FFFD}\u{FFFD} byte"));
+    }
+}
 
+#[test]
+pub fn negative_timestamps_mkv() {
+    let file = File::open("tests/data/negative_timestamps.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
     let mut frame = Frame::default();
-    while mkv.next_frame(&mut frame).unwrap() {}
+    let mut frames = Vec::new();
+    while mkv.next_frame(&mut frame).unwrap() {
+        frames.push((
+            frame.timestamp,
+            frame.signed_timestamp,
+            frame.is_timestamp_clamped,
+        ));
+    }
+    assert_eq!(frames, [(0, -10, true), (0, 0, false), (10, 10, false)]);
 
-    mkv.seek(0).unwrap();
-    assert!(mkv.next_frame(&mut frame).unwrap());
-    assert_eq!(frame.timestamp, 8);
+    let file = File::open("tests/data/negative_timestamps.mkv").unwrap();
+    let settings = ParserSettings::default().timestamp_policy(TimestampPolicy::Error);
+    let mut mkv = MatroskaFile::open_with_settings(file, &settings).unwrap();
+    let err = mkv.next_frame(&mut frame).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::NegativeTimestamp);
 
-    mkv.seek(2000).unwrap();
+    // The frame is skipped and reading continues with the next frame.
     assert!(mkv.next_frame(&mut frame).unwrap());
-    assert_eq!(frame.timestamp, 2000);
-
-    mkv.seek(1_000_000).unwrap();
-    assert!(!mkv.next_frame(&mut frame).unwrap());
+    assert_eq!(frame.data, b"n1");
 }
 
 #[test]
-pub fn parse_test8_mkv() {
-    let file = File::open("tests/data/test8.mkv").unwrap();
+pub fn cluster_timestamps_block_groups_mkv() {
+    let file = File::open("tests/data/block_groups.mkv").unwrap();
     let mut mkv = MatroskaFile::open(file).unwrap();
+    let mut frame = Frame::default();
+    let mut frames = Vec::new();
+    while mkv.next_frame(&mut frame).unwrap() {
+        if frame.track == 2 {
+            frames.push((
+                frame.timestamp,
+                frame.cluster_timestamp,
+                frame.relative_timestamp,
+            ));
+        }
+    }
+    assert_eq!(
+        frames,
+        [
+            (0, 0, 0),
+            (20, 0, 0),
+            (40, 0, 0),
+            (60, 0, 60),
+            (200, 0, 200),
+            (1000, 1000, 0),
+            (980, 1000, -20),
+        ]
+    );
+}
 
-    assert_eq!(mkv.ebml_header().version(), None);
-    assert_eq!(mkv.ebml_header().read_version(), None);
-    assert_eq!(mkv.ebml_header().doc_type(), "matroska");
-    assert_eq!(mkv.ebml_header().doc_type_version(), 2);
-    assert_eq!(mkv.ebml_header().doc_type_read_version(), 2);
-    assert_eq!(mkv.ebml_header().max_id_length(), 4);
-    assert_eq!(mkv.ebml_header().max_size_length(), 8);
+#[test]
+pub fn editor_mkv() {
+    let original = std::fs::read("tests/data/editor.mkv").unwrap();
+    let cluster = original
+        .windows(4)
+        .position(|id| id == [0x1F, 0x43, 0xB6, 0x75])
+        .unwrap();
+
+    let mut editor = MatroskaEditor::open(Cursor::new(original.clone())).unwrap();
+    editor.set_title(Some("A longer new title"));
+    editor.set_track_name(1, None).unwrap();
+    editor.set_track_name(2, Some("Commentary")).unwrap();
+    editor.set_track_language(2, "eng").unwrap();
+    editor.set_track_flag_default(2, false).unwrap();
+    editor.set_track_flag_forced(2, true).unwrap();
+    editor.set_tag(None, "TITLE", Some("Tagged")).unwrap();
+    editor.set_tag(Some(2), "BPS", Some("96000")).unwrap();
+    assert!(matches!(
+        editor.set_track_name(3, None),
+        Err(DemuxError::TrackNotFound(3))
+    ));
+    editor.write().unwrap();
+    assert_eq!(editor.index().info().title(), Some("A longer new title"));
+
+    // Info and Tracks fit into the Void elements, the new Tags are appended.
+    let data = editor.into_inner().into_inner();
+    assert_eq!(data[cluster..original.len()], original[cluster..]);
+    assert!(data.len() > original.len());
+
+    let mut mkv = MatroskaFile::open(Cursor::new(data.clone())).unwrap();
+    assert_eq!(mkv.info().title(), Some("A longer new title"));
+    assert_eq!(mkv.tracks()[0].name(), None);
+    assert_eq!(mkv.tracks()[1].name(), Some("Commentary"));
+    assert_eq!(mkv.tracks()[1].language(), Some("eng"));
+    assert_eq!(mkv.tracks()[1].language_ietf(), None);
+    assert!(!mkv.tracks()[1].flag_default());
+    assert!(mkv.tracks()[1].flag_forced());
 
+    let tags = mkv.tags().unwrap();
+    assert_eq!(tags.len(), 2);
+    assert_eq!(tags[0].simple_tags()[0].name(), "TITLE");
+    assert_eq!(tags[0].simple_tags()[0].string(), Some("Tagged"));
     assert_eq!(
-        mkv.info().timestamp_scale(),
-        NonZeroU64::new(1000000).unwrap()
+        tags[1]
+            .targets()
+            .and_then(|targets| targets.tag_track_uid()),
+        Some(102)
     );
-    assert!((47341.0 - mkv.info().duration().unwrap()).abs() < f64::EPSILON);
-    assert_eq!(mkv.info().date_utc().unwrap(), 304104134000000000);
+    assert_eq!(tags[1].simple_tags()[0].string(), Some("96000"));
 
-    assert_eq!(mkv.tracks().len(), 2);
+    let mut frame = Frame::default();
+    let mut count = 0;
+    while mkv.next_frame(&mut frame).unwrap() {
+        count += 1;
+    }
+    assert_eq!(count, 3);
+
+    // A title that doesn't fit moves the Info to the end. Removing the only simple tag of
+    // a Tag removes the Tag.
+    let mut editor = MatroskaEditor::open(Cursor::new(data)).unwrap();
+    let title = "T".repeat(200);
+    editor.set_title(Some(&title));
+    editor.set_tag(None, "TITLE", None).unwrap();
+    editor.write().unwrap();
+    let data = editor.into_inner().into_inner();
+    assert_eq!(data[cluster..original.len()], original[cluster..]);
+
+    let mkv = MatroskaFile::open(Cursor::new(data)).unwrap();
+    assert_eq!(mkv.info().title(), Some(title.as_str()));
+    assert_eq!(mkv.info().timestamp_scale().get(), 1_000_000);
+    assert!(mkv.seek_head_mismatches().is_empty());
+    let tags = mkv.tags().unwrap();
+    assert_eq!(tags.len(), 1);
+    assert_eq!(tags[0].simple_tags()[0].name(), "BPS");
+
+    // Without a SeekHead, a moved element couldn't be found again.
+    let mut data = original.clone();
+    let seek_head = data
+        .windows(4)
+        .position(|id| id == [0x11, 0x4D, 0x9B, 0x74])
+        .unwrap();
+    let size = data[seek_head + 4] & 0x7F;
+    data[seek_head..seek_head + 5].copy_from_slice(&[0xEC, 0x10, 0x00, 0x00, size]);
+    let mut editor = MatroskaEditor::open(Cursor::new(data.clone())).unwrap();
+    editor.set_title(Some(&title));
+    assert!(matches!(
+        editor.write(),
+        Err(DemuxError::ElementNotFound(ElementId::SeekHead))
+    ));
+    assert_eq!(editor.into_inner().into_inner(), data);
+}
 
+#[test]
+pub fn remux_tracks_block_groups_mkv() {
+    let file = File::open("tests/data/block_groups.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+    let mut expected = Vec::new();
     let mut frame = Frame::default();
-    while mkv.next_frame(&mut frame).unwrap() {}
+    while mkv.next_frame(&mut frame).unwrap() {
+        if frame.track != 2 {
+            expected.push(frame.clone());
+        }
+    }
 
-    mkv.seek(0).unwrap();
-    assert!(mkv.next_frame(&mut frame).unwrap());
-    assert_eq!(frame.timestamp, 3);
+    let mut output = Cursor::new(Vec::new());
+    mkv.remux_tracks(&mut output, &[1, 3]).unwrap();
+    assert!(matches!(
+        mkv.remux_tracks(Cursor::new(Vec::new()), &[4]),
+        Err(DemuxError::TrackNotFound(4))
+    ));
+    let data = output.into_inner();
 
-    mkv.seek(750).unwrap();
-    assert!(mkv.next_frame(&mut frame).unwrap());
-    assert_eq!(frame.timestamp, 750);
+    let mut remuxed = MatroskaFile::open(Cursor::new(data.clone())).unwrap();
+    let numbers: Vec<u64> = remuxed
+        .tracks()
+        .iter()
+        .map(|track| track.track_number().get())
+        .collect();
+    assert_eq!(numbers, [1, 3]);
+    assert_eq!(
+        remuxed.info().timestamp_scale(),
+        mkv.info().timestamp_scale()
+    );
+    assert!(remuxed.seek_head_mismatches().is_empty());
 
-    mkv.seek(1_000_000).unwrap();
-    assert!(!mkv.next_frame(&mut frame).unwrap());
+    let mut frames = Vec::new();
+    while remuxed.next_frame(&mut frame).unwrap() {
+        frames.push(frame.clone());
+    }
+    assert_eq!(frames.len(), expected.len());
+    for (frame, expected) in frames.iter().zip(&expected) {
+        assert_eq!(frame.track, expected.track);
+        assert_eq!(frame.timestamp, expected.timestamp);
+        assert_eq!(frame.duration, expected.duration);
+        assert_eq!(frame.is_keyframe, expected.is_keyframe);
+        assert_eq!(frame.data, expected.data);
+    }
+
+    // The cues reference the key frames of the video track and the first key frame of the
+    // subtitle track in each cluster.
+    let cues: Vec<(u64, u64)> = remuxed
+        .cue_points()
+        .unwrap()
+        .iter()
+        .map(|cue| (cue.time(), cue.track_positions()[0].track()))
+        .collect();
+    assert_eq!(cues, [(0, 1), (50, 3), (1000, 1), (1100, 3)]);
+
+    for cue in remuxed.cue_points().unwrap() {
+        let positions = &cue.track_positions()[0];
+        let cluster = usize::try_from(positions.cluster_position()).unwrap();
+        assert_eq!(data[cluster..cluster + 4], [0x1F, 0x43, 0xB6, 0x75]);
+        let size_length = usize::try_from(data[cluster + 4].leading_zeros()).unwrap() + 1;
+        let block = cluster
+            + 4
+            + size_length
+            + usize::try_from(positions.relative_position().unwrap()).unwrap();
+        assert!(data[block] == 0xA0 || data[block] == 0xA3);
+    }
+}
+
+#[test]
+pub fn remux_tracks_statistics_mkv() {
+    let file = File::open("tests/data/statistics.mkv").unwrap();
+    let mut mkv = MatroskaFile::open(file).unwrap();
+    let mut output = Cursor::new(Vec::new());
+    mkv.remux_tracks(&mut output, &[1, 3]).unwrap();
+
+    // The tags of the second track are dropped.
+    let remuxed = MatroskaFile::open(Cursor::new(output.into_inner())).unwrap();
+    let tags = remuxed.tags().unwrap();
+    assert_eq!(tags.len(), 2);
+    assert!(remuxed.tracks()[0].muxer_statistics(tags).is_some());
+    assert_eq!(
+        tags[1]
+            .targets()
+            .and_then(|targets| targets.tag_track_uid()),
+        None
+    );
+    assert_eq!(remuxed.cue_points().unwrap().len(), 1);
+}
+
+#[test]
+pub fn parse_block_addition_mapping_mkv() {
+    let file = File::open("tests/data/block_addition_mapping.mkv").unwrap();
+    let mkv = MatroskaFile::open(file).unwrap();
+
+    let mappings = mkv.tracks()[0].block_addition_mappings();
+    assert_eq!(mappings.len(), 2);
+    assert_eq!(mappings[0].id_value(), Some(4));
+    assert_eq!(mappings[0].id_name(), Some("dolby vision"));
+    assert_eq!(mappings[0].id_type(), 0x6476_6343);
+    assert_eq!(mappings[0].id_extra_data(), Some([0x01, 0x00].as_slice()));
+
+    // BlockAddIDType defaults to 0.
+    assert_eq!(mappings[1].id_value(), Some(5));
+    assert_eq!(mappings[1].id_name(), None);
+    assert_eq!(mappings[1].id_type(), 0);
+    assert_eq!(mappings[1].id_extra_data(), None);
+}
+
+#[test]
+pub fn parse_translate_mkv() {
+    let file = File::open("tests/data/translate.mkv").unwrap();
+    let mkv = MatroskaFile::open(file).unwrap();
+
+    let chapter_translates = mkv.info().chapter_translates();
+    assert_eq!(chapter_translates.len(), 1);
+    assert_eq!(chapter_translates[0].id(), [0x00, 0x01]);
+    assert_eq!(chapter_translates[0].codec(), TranslateCodec::DvdMenu);
+    assert_eq!(chapter_translates[0].edition_uids(), [7, 8]);
+
+    let track_translates = mkv.tracks()[0].track_translates();
+    assert_eq!(track_translates.len(), 1);
+    assert_eq!(track_translates[0].track_id(), [0x05]);
+    assert_eq!(track_translates[0].codec(), TranslateCodec::MatroskaScript);
+    assert!(track_translates[0].edition_uids().is_empty());
+
+    assert!(mkv.tracks()[1].track_translates().is_empty());
 }